@@ -0,0 +1,50 @@
+// 负载均衡器选择上游的性能基准测试
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use llmproxy::{
+    balancer::{create_load_balancer, ManagedUpstream},
+    config::{BalanceStrategy, UpstreamRef},
+};
+use std::sync::Arc;
+
+// 创建指定数量的托管上游，权重依次递增
+fn managed_upstreams(count: u32) -> Vec<ManagedUpstream> {
+    (1..=count)
+        .map(|i| ManagedUpstream {
+            upstream_ref: Arc::new(UpstreamRef {
+                name: format!("upstream{}", i),
+                weight: i,
+            }),
+            breaker: None,
+            drained: Default::default(),
+            adaptive: None,
+            endpoint: None,
+        })
+        .collect()
+}
+
+fn bench_select_upstream(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let strategies = [
+        ("roundrobin", BalanceStrategy::RoundRobin),
+        ("weighted_roundrobin", BalanceStrategy::WeightedRoundRobin),
+        ("random", BalanceStrategy::Random),
+        ("response_aware", BalanceStrategy::ResponseAware),
+        ("failover", BalanceStrategy::Failover),
+    ];
+
+    let mut group = c.benchmark_group("select_upstream");
+    for (name, strategy) in &strategies {
+        for count in [3, 32] {
+            let balancer = create_load_balancer(strategy, managed_upstreams(count));
+            group.bench_with_input(BenchmarkId::new(*name, count), &balancer, |b, balancer| {
+                b.to_async(&runtime)
+                    .iter(|| async { balancer.select_upstream().await.unwrap() })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_select_upstream);
+criterion_main!(benches);
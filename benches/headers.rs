@@ -0,0 +1,89 @@
+// 上游请求头处理的性能基准测试
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use llmproxy::{
+    config::{HeaderOp, HeaderOpType, UpstreamConfig},
+    upstream::{process_headers, RequestContext},
+};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+// 创建请求头操作，名称和不含占位符的值与加载配置时一样预解析
+fn header_op(op: HeaderOpType, key: &str, value: Option<&str>) -> HeaderOp {
+    let mut header_op = HeaderOp {
+        op,
+        key: key.to_string(),
+        value: value.map(str::to_string),
+        parsed_name: Some(HeaderName::from_bytes(key.as_bytes()).unwrap()),
+        parsed_value: None,
+    };
+    if !header_op.is_template() {
+        header_op.parsed_value = value.map(|value| HeaderValue::from_str(value).unwrap());
+    }
+    header_op
+}
+
+// 创建带有请求头操作的上游配置
+fn create_upstream(template: bool) -> UpstreamConfig {
+    let mut upstream: UpstreamConfig =
+        serde_yaml::from_str("name: bench\nurl: http://127.0.0.1:8080/v1\n").unwrap();
+    upstream.headers = vec![
+        header_op(HeaderOpType::Remove, "cookie", None),
+        header_op(
+            HeaderOpType::Replace,
+            "authorization",
+            Some("Bearer sk-upstream"),
+        ),
+        header_op(HeaderOpType::Insert, "x-team", Some("platform")),
+    ];
+    if template {
+        upstream.headers.push(header_op(
+            HeaderOpType::Insert,
+            "x-forwarded-request",
+            Some("${request_id}@${forward_name}"),
+        ));
+    }
+    upstream
+}
+
+// 创建典型的客户端请求头
+fn client_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in [
+        ("content-type", "application/json"),
+        ("accept", "text/event-stream"),
+        ("authorization", "Bearer sk-client"),
+        ("cookie", "session=abc"),
+        ("user-agent", "openai-python/1.30.0"),
+        ("x-request-id", "7f8c1f0e-2f5b-4b8f-9d57-3c1d2e8e4a10"),
+        ("x-stainless-lang", "python"),
+        ("x-stainless-runtime", "CPython"),
+    ] {
+        headers.insert(name, HeaderValue::from_static(value));
+    }
+    headers
+}
+
+fn bench_process_headers(c: &mut Criterion) {
+    let context = RequestContext {
+        request_id: "7f8c1f0e-2f5b-4b8f-9d57-3c1d2e8e4a10".to_string(),
+        forward_name: "chat".to_string(),
+        ..Default::default()
+    };
+    let headers = client_headers();
+
+    let mut group = c.benchmark_group("process_headers");
+    for (name, template) in [("static", false), ("template", true)] {
+        let upstream = create_upstream(template);
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || headers.clone(),
+                |headers| process_headers(headers, black_box(&upstream), None, &context).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_process_headers);
+criterion_main!(benches);
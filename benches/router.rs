@@ -0,0 +1,75 @@
+// 路由表匹配的性能基准测试
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use llmproxy::{
+    config::{
+        http_server::{RoutingRule, RoutingRuleType},
+        ForwardConfig,
+    },
+    server::router::Router,
+};
+
+// 创建路由规则
+fn rule(path: &str, r#type: RoutingRuleType, target_group: &str) -> RoutingRule {
+    RoutingRule {
+        path: path.to_string(),
+        r#type,
+        target_group: target_group.to_string(),
+        priority: 0,
+        breaker: None,
+        max_prompt_tokens: None,
+        tokens_per_minute: None,
+    }
+}
+
+// 创建包含静态路径、命名参数、通配符和正则表达式规则的路由器
+fn create_router() -> Router {
+    let mut routing = vec![
+        rule("/v1/chat/completions", RoutingRuleType::Path, "chat"),
+        rule("/v1/embeddings", RoutingRuleType::Path, "embeddings"),
+        rule("/v1/models/:model", RoutingRuleType::Path, "models"),
+        rule("/v1/files/*", RoutingRuleType::Path, "files"),
+        rule(
+            r"^/v1beta/models/[^/]+:(generate|streamGenerate)Content$",
+            RoutingRuleType::PathRegex,
+            "gemini",
+        ),
+    ];
+    routing.extend((0..50).map(|i| {
+        rule(
+            &format!("/tenant{}/v1/chat/completions", i),
+            RoutingRuleType::Path,
+            "tenant",
+        )
+    }));
+
+    let config: ForwardConfig = serde_yaml::from_str(
+        "name: bench\nport: 3000\naddress: 127.0.0.1\ndefault_group: default\n",
+    )
+    .unwrap();
+    let router = Router::new(&config).unwrap();
+    router.replace_routes(&routing).unwrap();
+    router
+}
+
+fn bench_get_target_group(c: &mut Criterion) {
+    let router = create_router();
+    let paths = [
+        ("static", "/v1/chat/completions"),
+        ("param", "/v1/models/gpt-4o"),
+        ("wildcard", "/v1/files/abc/content"),
+        ("regex", "/v1beta/models/gemini-pro:streamGenerateContent"),
+        ("default", "/unknown/path"),
+    ];
+
+    let mut group = c.benchmark_group("get_target_group");
+    for (name, path) in paths {
+        group.bench_function(name, |b| {
+            b.iter(|| router.get_target_group(black_box(path)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_get_target_group);
+criterion_main!(benches);
@@ -0,0 +1,162 @@
+use crate::{config::AdaptiveConfig, metrics::METRICS, r#const::adaptive_limits};
+use futures_util::StreamExt;
+use parking_lot::Mutex;
+use reqwest::Response;
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tracing::debug;
+
+// 并发上限和基线延迟
+struct LimitState {
+    // 当前并发上限，加性增长时按小数累加
+    limit: f64,
+    // 基线延迟（毫秒），尚无样本时为空
+    baseline: Option<f64>,
+}
+
+/// 上游自适应并发限制器
+///
+/// 使用 AIMD 算法调整上游允许的并发请求数：上游返回 429、5xx、请求失败或延迟超过基线延迟的
+/// `latency_tolerance` 倍时按 `backoff` 乘性收缩，上游健康且在途请求接近上限时每个请求增长
+/// `1 / limit`（约每轮增长 1）。同一上游在所有上游组中共享一个限制器。
+pub struct AdaptiveLimiter {
+    // 上游名称
+    name: String,
+    // 自适应并发配置
+    config: AdaptiveConfig,
+    // 并发上限和基线延迟
+    state: Mutex<LimitState>,
+    // 在途请求数
+    in_flight: AtomicU32,
+}
+
+impl AdaptiveLimiter {
+    /// 创建自适应并发限制器
+    pub fn new(name: &str, config: &AdaptiveConfig) -> Arc<Self> {
+        let limiter = Self {
+            name: name.to_string(),
+            config: config.clone(),
+            state: Mutex::new(LimitState {
+                limit: config.initial as f64,
+                baseline: None,
+            }),
+            in_flight: AtomicU32::new(0),
+        };
+        limiter.report(config.initial);
+        Arc::new(limiter)
+    }
+
+    /// 当前并发上限
+    pub fn limit(&self) -> u32 {
+        self.state.lock().limit as u32
+    }
+
+    /// 在途请求数
+    pub fn in_flight(&self) -> u32 {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// 在途请求数是否已达到并发上限，达到时负载均衡器跳过该上游
+    pub fn is_saturated(&self) -> bool {
+        self.in_flight() >= self.limit()
+    }
+
+    /// 开始一个请求，返回的守卫释放时结束该请求
+    pub fn start(self: &Arc<Self>) -> AdaptiveGuard {
+        let in_flight = self.in_flight.fetch_add(1, Ordering::Relaxed) + 1;
+        AdaptiveGuard {
+            limiter: Arc::clone(self),
+            started: Instant::now(),
+            in_flight,
+        }
+    }
+
+    // 按请求结果调整并发上限
+    fn record(&self, latency: Duration, overloaded: bool, in_flight: u32) {
+        let sample = latency.as_secs_f64() * 1000.0;
+        let mut state = self.state.lock();
+        let previous = state.limit as u32;
+
+        // 基线延迟取观测到的最低延迟，并缓慢向较高的样本靠拢
+        let baseline = match state.baseline {
+            Some(baseline) if sample < baseline => sample,
+            Some(baseline) => baseline + (sample - baseline) * adaptive_limits::BASELINE_DRIFT,
+            None => sample,
+        };
+        state.baseline = Some(baseline);
+
+        let (min, max) = (self.config.min as f64, self.config.max as f64);
+        let tolerated =
+            baseline.max(adaptive_limits::MIN_BASELINE_MS) * self.config.latency_tolerance;
+        if overloaded || sample > tolerated {
+            state.limit = (state.limit * self.config.backoff).max(min);
+        } else if in_flight as f64 * 2.0 >= state.limit {
+            // 只在上游得到充分使用时增长，避免空闲时上限无限增长
+            state.limit = (state.limit + 1.0 / state.limit).min(max);
+        }
+
+        let current = state.limit as u32;
+        drop(state);
+        if current != previous {
+            debug!(
+                "Adaptive concurrency limit of upstream '{}' changed from {} to {} (latency: {:.1}ms, overloaded: {})",
+                self.name, previous, current, sample, overloaded
+            );
+            self.report(current);
+        }
+    }
+
+    // 更新并发上限指标
+    fn report(&self, limit: u32) {
+        METRICS
+            .upstream_concurrency_limit()
+            .with_label_values(&[&self.name])
+            .set(limit as i64);
+    }
+}
+
+/// 自适应并发限制器的在途请求守卫，释放时在途请求数减一
+pub struct AdaptiveGuard {
+    // 所属限制器
+    limiter: Arc<AdaptiveLimiter>,
+    // 请求开始时间
+    started: Instant,
+    // 请求开始时的在途请求数（包括该请求）
+    in_flight: u32,
+}
+
+impl AdaptiveGuard {
+    /// 记录上游的响应结果（收到响应头时调用），overloaded 表示上游过载（429、5xx 或请求失败）
+    pub fn finish(&self, overloaded: bool) {
+        self.limiter
+            .record(self.started.elapsed(), overloaded, self.in_flight);
+    }
+
+    /// 将守卫绑定到响应体，响应体传输结束或被丢弃时释放
+    pub fn hold(self, response: Response) -> Response {
+        let status = response.status();
+        let version = response.version();
+        let headers = response.headers().clone();
+        let stream = response.bytes_stream().map(move |chunk| {
+            let _guard = &self;
+            chunk
+        });
+
+        let mut held = hyper::Response::new(reqwest::Body::wrap_stream(stream));
+        *held.status_mut() = status;
+        *held.version_mut() = version;
+        *held.headers_mut() = headers;
+        Response::from(held)
+    }
+}
+
+impl Drop for AdaptiveGuard {
+    fn drop(&mut self) {
+        self.limiter.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -1,17 +1,22 @@
-use crate::api::v1::{api_routes, openapi_routes};
-use crate::config::Config;
+use crate::api::v1::{api_routes, auth::auth_middleware, openapi_routes};
+use crate::audit::AuditLog;
+use crate::config::{Config, MetricsConfig};
 use crate::error::AppError;
 use crate::metrics::METRICS;
-use crate::server::create_tcp_listener;
+use crate::r#const::admin_paths;
+use crate::reload::ConfigReloader;
 use crate::server::ForwardState;
+use crate::server::{create_tcp_listener, tls_acceptor, TlsListener};
 use async_trait::async_trait;
 use axum::{
+    extract::State,
     http::{header, StatusCode},
+    middleware,
     response::{IntoResponse, Response},
     routing::get,
+    serve::Listener,
     Router,
 };
-use prometheus::{Encoder, TextEncoder};
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
@@ -19,8 +24,10 @@ use tokio::sync::RwLock;
 use tokio_graceful_shutdown::{IntoSubsystem, SubsystemHandle};
 use tracing::{error, info};
 
-const HEALTH_PATH: &str = "/health";
-const METRICS_PATH: &str = "/metrics";
+// 内嵌的管理面板页面，数据通过 API v1 获取
+const DASHBOARD_HTML: &str = include_str!("../assets/dashboard.html");
+// 管理面板页面中的指标路径占位符
+const DASHBOARD_METRICS_PLACEHOLDER: &str = "\"{{METRICS_PATH}}\"";
 
 // 管理服务
 pub struct AdminServer {
@@ -32,6 +39,10 @@ pub struct AdminServer {
     config: Arc<RwLock<Config>>,
     // 转发服务状态
     forward_states: Arc<HashMap<String, Arc<ForwardState>>>,
+    // 审计日志
+    audit: Arc<AuditLog>,
+    // 配置重载器
+    reloader: Arc<ConfigReloader>,
 }
 
 impl AdminServer {
@@ -41,54 +52,192 @@ impl AdminServer {
         addr: SocketAddr,
         config: Arc<RwLock<Config>>,
         forward_states: Arc<HashMap<String, Arc<ForwardState>>>,
+        audit: Arc<AuditLog>,
+        reloader: Arc<ConfigReloader>,
     ) -> Self {
         Self {
             addr,
             config,
             forward_states,
+            audit,
+            reloader,
             debug,
         }
     }
-}
 
-#[async_trait]
-impl IntoSubsystem<AppError> for AdminServer {
-    async fn run(self, subsys: SubsystemHandle) -> Result<(), AppError> {
-        // 创建路由
+    // 创建管理服务路由
+    pub async fn router(&self) -> Router {
+        let (dashboard, metrics) = match self.config.read().await.http_server {
+            Some(ref http_server) => (
+                http_server.admin.dashboard,
+                http_server.admin.metrics.clone(),
+            ),
+            None => (true, MetricsConfig::default()),
+        };
+
         let mut app = Router::new()
-            .route(HEALTH_PATH, get(health_handler))
-            .route(METRICS_PATH, get(metrics_handler))
+            .route(admin_paths::HEALTH, get(health_handler))
             // 添加 API v1 路由
-            .merge(api_routes(self.config.clone(), self.forward_states.clone()));
+            .merge(api_routes(
+                self.config.clone(),
+                self.forward_states.clone(),
+                self.audit.clone(),
+                self.reloader.clone(),
+            ));
+
+        // 未配置独立的指标端口时，在管理服务端口提供指标
+        let dashboard_metrics_path = if metrics.port.is_none() {
+            app = app.merge(metrics_routes(&metrics));
+            metrics.path.as_str()
+        } else {
+            ""
+        };
+
+        // 如果开启管理面板，添加管理面板页面
+        if dashboard {
+            let html: Arc<str> = DASHBOARD_HTML
+                .replace(
+                    DASHBOARD_METRICS_PLACEHOLDER,
+                    &serde_json::to_string(dashboard_metrics_path).unwrap_or_default(),
+                )
+                .into();
+            app = app.merge(
+                Router::new()
+                    .route(admin_paths::DASHBOARD, get(dashboard_handler))
+                    .with_state(html),
+            );
+        }
 
         // 如果开启调试模式，添加 OpenAPI UI
         if self.debug {
             app = app.merge(openapi_routes());
         }
 
+        app
+    }
+}
+
+#[async_trait]
+impl IntoSubsystem<AppError> for AdminServer {
+    async fn run(self, subsys: SubsystemHandle) -> Result<(), AppError> {
+        // 创建路由
+        let app = self.router().await;
+
+        let tls = match self.config.read().await.http_server {
+            Some(ref http_server) => http_server.admin.tls.clone(),
+            None => None,
+        };
+
         // 创建 TCP 监听器
         let listener = create_tcp_listener(self.addr, u16::MAX.into())?;
 
-        info!("Admin service listening on {:?}", self.addr);
+        // 配置了 HTTPS 时在 TCP 监听器上完成 TLS 握手
+        match tls {
+            Some(tls) => {
+                let listener = TlsListener::new(listener, tls_acceptor(&tls)?)?;
+                info!(
+                    "Admin service listening on {:?} (HTTPS{})",
+                    self.addr,
+                    if tls.client_ca_file.is_some() {
+                        ", client certificate required"
+                    } else {
+                        ""
+                    }
+                );
+                serve_admin(listener, app, subsys).await
+            }
+            None => {
+                info!("Admin service listening on {:?}", self.addr);
+                serve_admin(listener, app, subsys).await
+            }
+        }
+    }
+}
+
+// 运行管理服务直到服务退出或收到关闭信号
+async fn serve_admin<L>(listener: L, app: Router, subsys: SubsystemHandle) -> Result<(), AppError>
+where
+    L: Listener,
+    L::Addr: std::fmt::Debug,
+{
+    // 使用tokio::select!监听服务器和关闭信号
+    tokio::select! {
+        result = axum::serve(listener, app) => {
+            if let Err(e) = result {
+                error!("Admin service error: {}", e);
+            } else {
+                info!("Admin service completed normally");
+            }
+            Ok(())
+        }
+        _ = subsys.on_shutdown_requested() => {
+            info!("Shutdown requested, stopping admin service");
+            Ok(())
+        }
+    }
+}
+
+// 独立的指标服务，将 Prometheus 指标与管理 API 分开监听
+pub struct MetricsServer {
+    // 监听地址
+    addr: SocketAddr,
+    // 指标端点配置
+    config: MetricsConfig,
+}
+
+impl MetricsServer {
+    // 创建新的指标服务
+    pub fn new(addr: SocketAddr, config: MetricsConfig) -> Self {
+        Self { addr, config }
+    }
+
+    // 创建指标服务路由
+    pub fn router(&self) -> Router {
+        metrics_routes(&self.config)
+    }
+}
+
+#[async_trait]
+impl IntoSubsystem<AppError> for MetricsServer {
+    async fn run(self, subsys: SubsystemHandle) -> Result<(), AppError> {
+        let app = self.router();
+
+        // 创建 TCP 监听器
+        let listener = create_tcp_listener(self.addr, u16::MAX.into())?;
+
+        info!("Metrics service listening on {:?}", self.addr);
 
-        // 使用tokio::select!监听服务器和关闭信号
         tokio::select! {
             result = axum::serve(listener, app) => {
                 if let Err(e) = result {
-                    error!("Admin service error: {}", e);
+                    error!("Metrics service error: {}", e);
                 } else {
-                    info!("Admin service completed normally");
+                    info!("Metrics service completed normally");
                 }
                 Ok(())
             }
             _ = subsys.on_shutdown_requested() => {
-                info!("Shutdown requested, stopping admin service");
+                info!("Shutdown requested, stopping metrics service");
                 Ok(())
             }
         }
     }
 }
 
+// 创建指标路由，配置了令牌时需要 Bearer 认证
+fn metrics_routes(config: &MetricsConfig) -> Router {
+    let mut router = Router::new().route(&config.path, get(metrics_handler));
+
+    if config.token.is_some() {
+        router = router.route_layer(middleware::from_fn_with_state(
+            config.token.clone(),
+            auth_middleware,
+        ));
+    }
+
+    router
+}
+
 // 健康检查处理程序
 async fn health_handler() -> &'static str {
     "OK"
@@ -96,22 +245,14 @@ async fn health_handler() -> &'static str {
 
 // 指标处理函数
 async fn metrics_handler() -> Response {
-    // 创建编码器
-    let encoder = TextEncoder::new();
-
-    // 收集指标
-    let metric_families = METRICS.registry().gather();
-
-    // 预估缓冲区大小，避免多次重新分配
-    // 每个指标家族平均大约需要 200 字节
-    let estimated_size = metric_families.len() * 200;
-    let mut buffer = Vec::with_capacity(estimated_size);
-
     // 编码指标
-    if let Err(e) = encoder.encode(&metric_families, &mut buffer) {
-        error!("Failed to encode metrics: {}", e);
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
-    }
+    let buffer = match METRICS.encode_text() {
+        Ok(buffer) => buffer,
+        Err(e) => {
+            error!("Failed to encode metrics: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
 
     // Prometheus指标数据总是有效的UTF-8，所以可以直接返回字节
     (
@@ -121,3 +262,17 @@ async fn metrics_handler() -> Response {
     )
         .into_response()
 }
+
+// 管理面板处理函数
+// 页面本身不包含运行数据，所有数据都通过需要认证的 API v1 获取
+async fn dashboard_handler(State(html): State<Arc<str>>) -> Response {
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "text/html; charset=utf-8"),
+            (header::CACHE_CONTROL, "no-cache"),
+        ],
+        html.to_string(),
+    )
+        .into_response()
+}
@@ -0,0 +1,362 @@
+use parking_lot::Mutex;
+use prometheus::proto::{MetricFamily, MetricType};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::{Duration, Instant},
+};
+use tokio_graceful_shutdown::{IntoSubsystem, SubsystemHandle};
+use tracing::{debug, info, warn};
+
+use crate::{
+    config::{AlertMetric, AlertRuleConfig, AlertsConfig},
+    error::AppError,
+    events::{unix_millis, SystemEvent, EVENTS},
+    metrics::METRICS,
+    r#const::{alert_limits, status_class_labels},
+};
+
+// 上游请求计数指标
+const UPSTREAM_REQUESTS: &str = "llmproxy_upstream_requests_total";
+// 上游耗时指标
+const UPSTREAM_DURATION: &str = "llmproxy_upstream_duration_seconds";
+
+/// 告警状态变化的通知，以 JSON 格式发送给告警 Webhook
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AlertNotification {
+    /// 告警状态（firing 或 resolved）
+    pub status: &'static str,
+    /// 告警规则名称
+    pub rule: String,
+    /// 评估的指标（error_rate 或 latency）
+    pub metric: &'static str,
+    /// 上游组名称
+    pub group: String,
+    /// 指标值，窗口内请求数不足时为空
+    pub value: Option<f64>,
+    /// 告警阈值
+    pub threshold: f64,
+    /// 统计窗口（秒）
+    pub window: u64,
+    /// 通知时间（Unix 毫秒时间戳）
+    pub timestamp: u64,
+}
+
+// 单个上游组的累计指标
+#[derive(Debug, Clone, Default)]
+struct GroupStats {
+    // 上游请求数
+    requests: u64,
+    // 5xx 响应和未收到响应的请求数
+    errors: u64,
+    // 耗时直方图的分桶上限和累计计数
+    buckets: Vec<(f64, u64)>,
+    // 耗时直方图的样本数
+    samples: u64,
+}
+
+impl GroupStats {
+    // 计算与较早快照之间的增量
+    fn delta(&self, base: Option<&GroupStats>) -> GroupStats {
+        let Some(base) = base else {
+            return self.clone();
+        };
+        let buckets = self
+            .buckets
+            .iter()
+            .map(|&(bound, count)| {
+                let previous = base
+                    .buckets
+                    .iter()
+                    .find(|(b, _)| *b == bound)
+                    .map_or(0, |(_, c)| *c);
+                (bound, count.saturating_sub(previous))
+            })
+            .collect();
+        GroupStats {
+            requests: self.requests.saturating_sub(base.requests),
+            errors: self.errors.saturating_sub(base.errors),
+            buckets,
+            samples: self.samples.saturating_sub(base.samples),
+        }
+    }
+
+    // 按规则计算指标值，请求数不足时返回 None
+    fn value(&self, rule: &AlertRuleConfig) -> Option<f64> {
+        match rule.metric {
+            AlertMetric::ErrorRate => (self.requests >= rule.min_requests)
+                .then(|| self.errors as f64 / self.requests as f64),
+            AlertMetric::Latency => (self.samples >= rule.min_requests)
+                .then(|| quantile(rule.quantile, &self.buckets, self.samples)),
+        }
+    }
+}
+
+// 某一时刻各上游组的累计指标
+type Snapshot = HashMap<String, GroupStats>;
+
+/// 告警评估器
+///
+/// 按评估间隔记录上游请求计数和上游耗时直方图的快照，用当前值与统计窗口起点快照的差值计算
+/// 每条规则的指标值。告警触发或恢复时发布系统事件，并将通知 POST 到配置的 Webhook
+pub struct AlertEvaluator {
+    // 告警配置
+    config: AlertsConfig,
+    // 发送 Webhook 使用的 HTTP 客户端
+    client: reqwest::Client,
+    // 每个 Webhook 附加的请求头
+    headers: Vec<HeaderMap>,
+    // 按时间排序的指标快照
+    snapshots: Mutex<VecDeque<(Instant, Snapshot)>>,
+    // 正在触发的告警（规则名称, 上游组）
+    firing: Mutex<HashSet<(String, String)>>,
+}
+
+impl AlertEvaluator {
+    /// 按告警配置创建评估器
+    pub fn new(config: &AlertsConfig) -> Result<Self, AppError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(alert_limits::WEBHOOK_TIMEOUT))
+            .build()
+            .map_err(|e| {
+                AppError::Config(format!("Failed to create alert webhook client: {}", e))
+            })?;
+        let headers = config
+            .webhooks
+            .iter()
+            .map(|webhook| {
+                let mut headers = HeaderMap::new();
+                for (name, value) in &webhook.headers {
+                    let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| {
+                        AppError::Config(format!("Invalid alert webhook header: {}", name))
+                    })?;
+                    let value = HeaderValue::from_str(value).map_err(|_| {
+                        AppError::Config(format!("Invalid value of alert webhook header: {}", name))
+                    })?;
+                    headers.insert(name, value);
+                }
+                Ok(headers)
+            })
+            .collect::<Result<_, AppError>>()?;
+        Ok(Self {
+            config: config.clone(),
+            client,
+            headers,
+            snapshots: Mutex::new(VecDeque::new()),
+            firing: Mutex::new(HashSet::new()),
+        })
+    }
+
+    /// 以指定的当前时间记录一次指标快照并评估全部规则，返回状态发生变化的告警
+    ///
+    /// 统计窗口起点之前没有快照时（如刚启动），使用最早的快照作为起点
+    pub fn evaluate(&self, now: Instant) -> Vec<AlertNotification> {
+        let current = snapshot(&METRICS.gather());
+        let mut snapshots = self.snapshots.lock();
+
+        // 只保留最长统计窗口起点之后的快照，以及起点之前的最后一个快照
+        let max_window = self.config.rules.iter().map(|r| r.window).max();
+        if let Some(start) = max_window.and_then(|w| now.checked_sub(Duration::from_secs(w))) {
+            while snapshots.len() > 1 && snapshots[1].0 <= start {
+                snapshots.pop_front();
+            }
+        }
+
+        let mut firing = self.firing.lock();
+        let mut notifications = Vec::new();
+        for rule in &self.config.rules {
+            let start = now.checked_sub(Duration::from_secs(rule.window));
+            let base = snapshots
+                .iter()
+                .rev()
+                .find(|(time, _)| start.is_some_and(|start| *time <= start))
+                .or(snapshots.front())
+                .map(|(_, snapshot)| snapshot);
+
+            for (group, stats) in &current {
+                if rule.group.as_ref().is_some_and(|g| g != group) {
+                    continue;
+                }
+                let value = stats.delta(base.and_then(|b| b.get(group))).value(rule);
+                let key = (rule.name.clone(), group.clone());
+                if value.is_some_and(|v| v > rule.threshold) {
+                    if firing.insert(key) {
+                        notifications.push(notification(alert_limits::FIRING, rule, group, value));
+                    }
+                } else if firing.remove(&key) {
+                    notifications.push(notification(alert_limits::RESOLVED, rule, group, value));
+                }
+            }
+        }
+
+        snapshots.push_back((now, current));
+        notifications
+    }
+
+    /// 发布告警状态变化的系统事件，并发送给全部 Webhook
+    pub async fn notify(&self, notifications: &[AlertNotification]) {
+        for notification in notifications {
+            let event = if notification.status == alert_limits::FIRING {
+                warn!(
+                    "Alert {:?} firing for upstream group {:?}: {} {:.4} > {}",
+                    notification.rule,
+                    notification.group,
+                    notification.metric,
+                    notification.value.unwrap_or_default(),
+                    notification.threshold
+                );
+                SystemEvent::AlertFiring {
+                    timestamp: notification.timestamp,
+                    rule: notification.rule.clone(),
+                    group: notification.group.clone(),
+                    value: notification.value.unwrap_or_default(),
+                    threshold: notification.threshold,
+                }
+            } else {
+                info!(
+                    "Alert {:?} resolved for upstream group {:?}",
+                    notification.rule, notification.group
+                );
+                SystemEvent::AlertResolved {
+                    timestamp: notification.timestamp,
+                    rule: notification.rule.clone(),
+                    group: notification.group.clone(),
+                    value: notification.value,
+                    threshold: notification.threshold,
+                }
+            };
+            EVENTS.publish_system(event);
+
+            for (webhook, headers) in self.config.webhooks.iter().zip(&self.headers) {
+                let result = self
+                    .client
+                    .post(&webhook.url)
+                    .headers(headers.clone())
+                    .json(notification)
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status());
+                if let Err(e) = result {
+                    warn!(
+                        "Failed to send alert {:?} to webhook {}: {}",
+                        notification.rule, webhook.url, e
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl IntoSubsystem<AppError> for AlertEvaluator {
+    async fn run(self, subsys: SubsystemHandle) -> Result<(), AppError> {
+        info!(
+            "Alert evaluator started, evaluating {} rules every {}s",
+            self.config.rules.len(),
+            self.config.interval
+        );
+
+        let interval = Duration::from_secs(self.config.interval);
+        loop {
+            let notifications = self.evaluate(Instant::now());
+            debug!(
+                "Alert rules evaluated, {} state changes",
+                notifications.len()
+            );
+            self.notify(&notifications).await;
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = subsys.on_shutdown_requested() => break,
+            }
+        }
+
+        info!("Alert evaluator stopped");
+        Ok(())
+    }
+}
+
+// 创建告警通知
+fn notification(
+    status: &'static str,
+    rule: &AlertRuleConfig,
+    group: &str,
+    value: Option<f64>,
+) -> AlertNotification {
+    AlertNotification {
+        status,
+        rule: rule.name.clone(),
+        metric: rule.metric.as_str(),
+        group: group.to_string(),
+        value,
+        threshold: rule.threshold,
+        window: rule.window,
+        timestamp: unix_millis(),
+    }
+}
+
+// 从指标中汇总各上游组的上游请求计数和耗时直方图
+// 去掉 group 标签后所有上游组汇总为名称为空的一组
+fn snapshot(families: &[MetricFamily]) -> Snapshot {
+    let mut groups = Snapshot::new();
+    for family in families {
+        let name = family.get_name();
+        if name != UPSTREAM_REQUESTS && name != UPSTREAM_DURATION {
+            continue;
+        }
+        for metric in family.get_metric() {
+            let label = |key: &str| {
+                metric
+                    .get_label()
+                    .iter()
+                    .find(|label| label.get_name() == key)
+                    .map_or("", |label| label.get_value())
+            };
+            let stats = groups.entry(label("group").to_string()).or_default();
+            match family.get_field_type() {
+                MetricType::COUNTER => {
+                    let count = metric.get_counter().get_value() as u64;
+                    stats.requests += count;
+                    let class = label("status_class");
+                    if class == status_class_labels::CLASSES[5]
+                        || class == status_class_labels::ERROR
+                    {
+                        stats.errors += count;
+                    }
+                }
+                MetricType::HISTOGRAM => {
+                    let histogram = metric.get_histogram();
+                    stats.samples += histogram.get_sample_count();
+                    for bucket in histogram.get_bucket() {
+                        let bound = bucket.get_upper_bound();
+                        let count = bucket.get_cumulative_count();
+                        match stats.buckets.iter_mut().find(|(b, _)| *b == bound) {
+                            Some((_, total)) => *total += count,
+                            None => stats.buckets.push((bound, count)),
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    groups
+}
+
+// 按累计分桶估算分位数，在样本所在分桶内线性插值
+// 样本落在最大分桶上限之外时返回最大分桶上限
+fn quantile(q: f64, buckets: &[(f64, u64)], samples: u64) -> f64 {
+    let rank = q * samples as f64;
+    let mut lower = (0.0, 0);
+    for &(bound, count) in buckets {
+        if count as f64 >= rank {
+            let in_bucket = count.saturating_sub(lower.1) as f64;
+            if in_bucket == 0.0 {
+                return bound;
+            }
+            return lower.0 + (bound - lower.0) * (rank - lower.1 as f64) / in_bucket;
+        }
+        lower = (bound, count);
+    }
+    lower.0
+}
@@ -0,0 +1,67 @@
+use crate::{
+    api::v1::routes::{AppState, API_V1_PREFIX, CONFIG_VALIDATE_PATH},
+    audit::{actor_from_authorization, config_snapshot, diff_config},
+};
+use axum::{
+    body::Body,
+    extract::{OriginalUri, State},
+    http::{header, Method, Request},
+    middleware::Next,
+    response::Response,
+};
+use tracing::debug;
+
+/// 审计中间件，记录所有成功的配置变更请求
+pub async fn audit_middleware(
+    State(app_state): State<AppState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    // 只读请求不修改配置，无需审计
+    if matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    ) {
+        return next.run(request).await;
+    }
+
+    let method = request.method().to_string();
+    let endpoint = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.path().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+
+    // 配置校验只是试运行，不修改配置，无需审计
+    if endpoint.strip_prefix(API_V1_PREFIX) == Some(CONFIG_VALIDATE_PATH) {
+        return next.run(request).await;
+    }
+    let actor = actor_from_authorization(
+        request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok()),
+    );
+
+    // 持有变更锁直到记录变更后的快照，避免并发的变更混入本次的差异
+    let _mutation = app_state.mutations.lock().await;
+
+    // 记录变更前的配置快照
+    let before = config_snapshot(&*app_state.config.read().await);
+
+    let response = next.run(request).await;
+
+    // 只记录成功的变更
+    if response.status().is_success() {
+        let after = config_snapshot(&*app_state.config.read().await);
+        let changes = diff_config(&before, &after);
+        let entry = app_state.audit.record(actor, method, endpoint, changes);
+        debug!(
+            "API: Recorded audit entry #{} with {} changes",
+            entry.id,
+            entry.changes.len()
+        );
+    }
+
+    response
+}
@@ -0,0 +1,78 @@
+use crate::{
+    api::v1::{handlers::utils::log_response_body, models::ErrorResponse},
+    events::{AccessEvent, AccessFilter, EVENTS},
+    r#const::{api::error_types, event_types},
+};
+use axum::{
+    extract::Query,
+    http::StatusCode,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    Json,
+};
+use futures_util::stream;
+use std::convert::Infallible;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+/// 实时访问日志流
+///
+/// Stream live access events as server-sent events
+#[utoipa::path(
+    get,
+    path = "/api/v1/access-log/stream",
+    tag = "AccessLog",
+    params(
+        ("forward" = Option<String>, Query, description = "按转发服务过滤 | Filter by forwarding service name"),
+        ("group" = Option<String>, Query, description = "按上游组过滤 | Filter by upstream group name"),
+        ("method" = Option<String>, Query, description = "按请求方法过滤 | Filter by request method"),
+        ("path" = Option<String>, Query, description = "按请求路径前缀过滤 | Filter by request path prefix"),
+        ("status" = Option<String>, Query, description = "按状态码过滤，如 404 或 5xx | Filter by status code, e.g. 404 or 5xx"),
+    ),
+    responses(
+        (status = 200, description = "访问事件流（text/event-stream）| Access event stream (text/event-stream)", body = AccessEvent),
+        (status = 400, description = "过滤条件无效 | Invalid filter", body = ErrorResponse),
+    )
+)]
+pub async fn stream_access_log(Query(params): Query<Vec<(String, String)>>) -> Response {
+    let filter = match AccessFilter::parse(params) {
+        Ok(filter) => filter,
+        Err(e) => {
+            let error = ErrorResponse::error(StatusCode::BAD_REQUEST, error_types::BAD_REQUEST, e);
+            log_response_body(&error);
+            return (StatusCode::BAD_REQUEST, Json(error)).into_response();
+        }
+    };
+
+    info!("API: Access log subscriber connected");
+
+    let receiver = EVENTS.subscribe_access();
+    let events = stream::unfold((receiver, filter), |(mut receiver, filter)| async move {
+        loop {
+            let event = match receiver.recv().await {
+                Ok(event) if filter.matches(&event) => Event::default()
+                    .event(event_types::ACCESS)
+                    .json_data(&event)
+                    .unwrap_or_else(|e| Event::default().comment(e.to_string())),
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(
+                        "API: Access log subscriber lagged, {} events dropped",
+                        skipped
+                    );
+                    Event::default()
+                        .event(event_types::LAGGED)
+                        .data(skipped.to_string())
+                }
+                Err(RecvError::Closed) => return None,
+            };
+            return Some((Ok::<_, Infallible>(event), (receiver, filter)));
+        }
+    });
+
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
@@ -0,0 +1,67 @@
+use crate::{
+    api::v1::{
+        handlers::utils::log_response_body,
+        models::{AuditPage, AuditQuery, ErrorResponse, SuccessResponse},
+        routes::AppState,
+    },
+    r#const::{api::error_types, audit_limits},
+};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use tracing::info;
+
+/// 获取配置变更审计记录
+///
+/// Get the audit log of configuration mutations
+#[utoipa::path(
+    get,
+    path = "/api/v1/audit",
+    tag = "Audit",
+    params(AuditQuery),
+    responses(
+        (status = 200, description = "成功获取审计记录 | Successfully retrieved audit entries", body = SuccessResponse<AuditPage>),
+        (status = 400, description = "分页参数无效 | Invalid pagination parameters", body = ErrorResponse),
+    )
+)]
+pub async fn list_audit_entries(
+    State(app_state): State<AppState>,
+    Query(query): Query<AuditQuery>,
+) -> Response {
+    let page = query.page.unwrap_or(1);
+    let page_size = query.page_size.unwrap_or(audit_limits::DEFAULT_PAGE_SIZE);
+
+    if page == 0 || page_size == 0 || page_size > audit_limits::MAX_PAGE_SIZE {
+        let error = ErrorResponse::error(
+            StatusCode::BAD_REQUEST,
+            error_types::BAD_REQUEST,
+            format!(
+                "Invalid pagination: page must be >= 1 and page_size must be between 1 and {}",
+                audit_limits::MAX_PAGE_SIZE
+            ),
+        );
+        log_response_body(&error);
+        return (StatusCode::BAD_REQUEST, Json(error)).into_response();
+    }
+
+    let (total, entries) = app_state.audit.page(page, page_size);
+    info!(
+        "API: Retrieved {} audit entries (page {}, total {})",
+        entries.len(),
+        page,
+        total
+    );
+
+    let response = SuccessResponse::success_with_data(AuditPage {
+        total,
+        page,
+        page_size,
+        entries,
+    });
+    log_response_body(&response);
+
+    Json(response).into_response()
+}
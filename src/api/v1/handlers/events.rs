@@ -0,0 +1,52 @@
+use crate::{
+    events::{SystemEvent, EVENTS},
+    r#const::event_types,
+};
+use axum::response::{
+    sse::{Event, KeepAlive, Sse},
+    IntoResponse, Response,
+};
+use futures_util::stream;
+use std::convert::Infallible;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+/// 实时管理事件流
+///
+/// Stream admin events (circuit breaker transitions, upstream and forward state changes, config mutations and reloads, rate-limit spikes) as server-sent events
+#[utoipa::path(
+    get,
+    path = "/api/v1/events",
+    tag = "Events",
+    responses(
+        (status = 200, description = "系统事件流（text/event-stream）| System event stream (text/event-stream)", body = SystemEvent),
+    )
+)]
+pub async fn stream_events() -> Response {
+    info!("API: Admin event subscriber connected");
+
+    let receiver = EVENTS.subscribe_system();
+    let events = stream::unfold(receiver, |mut receiver| async move {
+        let event = match receiver.recv().await {
+            Ok(event) => Event::default()
+                .event(event_types::SYSTEM)
+                .json_data(&event)
+                .unwrap_or_else(|e| Event::default().comment(e.to_string())),
+            Err(RecvError::Lagged(skipped)) => {
+                warn!(
+                    "API: Admin event subscriber lagged, {} events dropped",
+                    skipped
+                );
+                Event::default()
+                    .event(event_types::LAGGED)
+                    .data(skipped.to_string())
+            }
+            Err(RecvError::Closed) => return None,
+        };
+        Some((Ok::<_, Infallible>(event), receiver))
+    });
+
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
@@ -1,6 +1,8 @@
 use crate::{
-    api::v1::handlers::utils::{log_response_body, not_found_error, success_response_ref},
-    api::v1::models::{ErrorResponse, SuccessResponse},
+    api::v1::handlers::utils::{
+        log_response_body, not_found_error, success_response, success_response_ref,
+    },
+    api::v1::models::{ErrorResponse, ForwardStatus, SuccessResponse},
     api::v1::routes::AppState,
     config::ForwardConfig,
 };
@@ -9,7 +11,7 @@ use axum::{
     response::Response,
     Json,
 };
-use tracing::info;
+use tracing::{info, warn};
 
 /// 获取所有转发服务列表
 ///
@@ -91,3 +93,70 @@ pub async fn get_forward(State(app_state): State<AppState>, Path(name): Path<Str
         }
     }
 }
+
+// 启用或禁用运行中的转发服务
+fn set_forward_enabled(app_state: &AppState, name: &str, enabled: bool) -> Response {
+    match app_state.forward_states.get(name) {
+        Some(state) => {
+            state.set_enabled(enabled);
+            info!(
+                "API: {} forwarding service '{}'",
+                if enabled { "Enabled" } else { "Disabled" },
+                name
+            );
+
+            let status = ForwardStatus::from_state(state);
+            log_response_body(&SuccessResponse::success_with_data(&status));
+
+            success_response(status)
+        }
+        None => {
+            warn!("API: Forwarding service '{}' is not running", name);
+            not_found_error("Forwarding service", name)
+        }
+    }
+}
+
+/// 启用转发服务
+///
+/// Enable a running forwarding service so that it accepts requests again
+#[utoipa::path(
+    post,
+    path = "/api/v1/forwards/{name}/enable",
+    tag = "Forwards",
+    params(
+        ("name" = String, Path, description = "转发服务名称 | Forwarding service name")
+    ),
+    responses(
+        (status = 200, description = "成功启用转发服务 | Successfully enabled forwarding service", body = SuccessResponse<ForwardStatus>),
+        (status = 404, description = "转发服务不存在或未运行 | Forwarding service not found or not running", body = ErrorResponse),
+    )
+)]
+pub async fn enable_forward(
+    State(app_state): State<AppState>,
+    Path(name): Path<String>,
+) -> Response {
+    set_forward_enabled(&app_state, &name, true)
+}
+
+/// 禁用转发服务
+///
+/// Disable a running forwarding service, it responds 503 to all requests until enabled again
+#[utoipa::path(
+    post,
+    path = "/api/v1/forwards/{name}/disable",
+    tag = "Forwards",
+    params(
+        ("name" = String, Path, description = "转发服务名称 | Forwarding service name")
+    ),
+    responses(
+        (status = 200, description = "成功禁用转发服务 | Successfully disabled forwarding service", body = SuccessResponse<ForwardStatus>),
+        (status = 404, description = "转发服务不存在或未运行 | Forwarding service not found or not running", body = ErrorResponse),
+    )
+)]
+pub async fn disable_forward(
+    State(app_state): State<AppState>,
+    Path(name): Path<String>,
+) -> Response {
+    set_forward_enabled(&app_state, &name, false)
+}
@@ -1,6 +1,16 @@
 // API 处理函数模块
+pub mod access_log;
+pub mod audit;
+pub mod events;
 pub mod forward;
+pub mod reload;
+pub mod restart;
 pub mod routing;
+pub mod schema;
+pub mod status;
+pub mod support;
 pub mod upstream;
 pub mod upstream_group;
+pub mod usage;
 pub mod utils;
+pub mod validate;
@@ -0,0 +1,80 @@
+use crate::{
+    api::v1::{
+        handlers::utils::log_response_body,
+        models::{ErrorResponse, SuccessResponse},
+        routes::AppState,
+    },
+    r#const::{api::error_types, reload_trigger_labels},
+    reload::ReloadStatus,
+};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use tracing::{info, warn};
+
+/// 获取配置重载状态
+///
+/// Get the status of the last configuration reload
+#[utoipa::path(
+    get,
+    path = "/api/v1/config/reload",
+    tag = "Config",
+    responses(
+        (status = 200, description = "成功获取配置重载状态 | Successfully retrieved reload status", body = SuccessResponse<ReloadStatus>),
+    )
+)]
+pub async fn get_reload_status(State(app_state): State<AppState>) -> Response {
+    let status = app_state.reloader.status();
+    info!(
+        "API: Retrieved configuration reload status (failed: {})",
+        status.failed
+    );
+
+    let response = SuccessResponse::success_with_data(status);
+    log_response_body(&response);
+
+    Json(response).into_response()
+}
+
+/// 重新加载配置文件
+///
+/// Reload the configuration file, keeping the last-known-good configuration on failure
+#[utoipa::path(
+    post,
+    path = "/api/v1/config/reload",
+    tag = "Config",
+    responses(
+        (status = 200, description = "配置重载成功 | Configuration reloaded successfully", body = SuccessResponse<ReloadStatus>),
+        (status = 500, description = "配置重载失败，继续使用上一次有效的配置 | Reload failed, the last-known-good configuration is still served", body = ErrorResponse),
+    )
+)]
+pub async fn reload_config(State(app_state): State<AppState>) -> Response {
+    match app_state.reloader.reload(reload_trigger_labels::API).await {
+        Ok(status) => {
+            info!("API: Configuration reloaded");
+
+            let response = SuccessResponse::success_with_data(status);
+            log_response_body(&response);
+
+            Json(response).into_response()
+        }
+        Err(e) => {
+            warn!("API: Configuration reload failed: {}", e);
+
+            let error = ErrorResponse::error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                error_types::INTERNAL_SERVER_ERROR,
+                format!(
+                    "Configuration reload failed, keeping last-known-good configuration: {}",
+                    e
+                ),
+            );
+            log_response_body(&error);
+
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(error)).into_response()
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{
+    api::v1::{
+        handlers::utils::log_response_body,
+        models::{ErrorResponse, SuccessResponse},
+    },
+    r#const::api::error_types,
+    restart::{self, RestartError, RestartStatus},
+};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use tracing::{info, warn};
+
+/// 不中断连接地重启进程
+///
+/// Start a new process with the same executable and arguments. Once it is listening, the current process stops accepting connections and exits after in-flight requests finish (Linux only)
+#[utoipa::path(
+    post,
+    path = "/api/v1/restart",
+    tag = "Restart",
+    responses(
+        (status = 200, description = "新进程已启动 | New process started", body = SuccessResponse<RestartStatus>),
+        (status = 409, description = "已有正在进行的重启 | A restart is already in progress", body = ErrorResponse),
+        (status = 500, description = "无法启动新进程 | Failed to start the new process", body = ErrorResponse),
+    )
+)]
+pub async fn restart_process() -> Response {
+    match restart::spawn_replacement() {
+        Ok(status) => {
+            info!("API: Restarting, new process {} started", status.pid);
+
+            let response = SuccessResponse::success_with_data(status);
+            log_response_body(&response);
+
+            Json(response).into_response()
+        }
+        Err(RestartError::InProgress) => {
+            warn!("API: Restart rejected, a restart is already in progress");
+
+            let error = ErrorResponse::error(
+                StatusCode::CONFLICT,
+                error_types::CONFLICT,
+                RestartError::InProgress.to_string(),
+            );
+            log_response_body(&error);
+
+            (StatusCode::CONFLICT, Json(error)).into_response()
+        }
+        Err(e) => {
+            warn!("API: Restart failed: {}", e);
+
+            let error = ErrorResponse::error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                error_types::INTERNAL_SERVER_ERROR,
+                format!("Restart failed: {}", e),
+            );
+            log_response_body(&error);
+
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(error)).into_response()
+        }
+    }
+}
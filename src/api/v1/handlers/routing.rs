@@ -4,10 +4,10 @@ use crate::{
             decode_base64_to_path, log_request_body, log_response_body, not_found_error,
             success_response_ref,
         },
-        models::{ErrorResponse, SuccessResponse, UpdateRoutePayload},
+        models::{ErrorDetail, ErrorResponse, SuccessResponse, UpdateRoutePayload},
         routes::AppState,
     },
-    config::{http_server::RoutingRule, Config},
+    config::{http_server::RoutingRule, validation::check_ambiguous_routing_rules, Config},
     r#const::api::error_types,
 };
 use axum::{
@@ -102,6 +102,27 @@ fn route_not_found(path: &str, forward_name: &str) -> Response {
     not_found_error("Route", path)
 }
 
+// 检查路由规则之间是否存在歧义
+#[inline(always)]
+fn check_routing_ambiguity(
+    routing: &[RoutingRule],
+    forward_name: &str,
+) -> Result<(), ErrorResponse> {
+    if let Err(errors) = check_ambiguous_routing_rules(routing, forward_name) {
+        let error = ErrorResponse::from_details(
+            StatusCode::CONFLICT,
+            error_types::CONFLICT,
+            errors
+                .iter()
+                .map(ErrorDetail::from_validation_error)
+                .collect(),
+        );
+        log_response_body(&error);
+        return Err(error);
+    }
+    Ok(())
+}
+
 // 查找指定的转发服务
 #[inline(always)]
 fn find_forward<'a>(
@@ -132,7 +153,7 @@ async fn update_runtime_router(
     app_state: &AppState,
     forward_name: &str,
     path: &str,
-    target_group: Option<&str>,
+    route: Option<&RoutingRule>,
 ) {
     let forward_state = match app_state.forward_states.get(forward_name) {
         Some(state) => state,
@@ -146,21 +167,16 @@ async fn update_runtime_router(
         }
     };
 
-    let result = match target_group {
+    let result = match route {
         // 添加或更新路由
-        Some(target) => {
-            forward_state
-                .router
-                .insert_or_update_route(path.to_string(), target.to_string())
-                .await
-        }
+        Some(rule) => forward_state.router.insert_or_update_route(rule),
         // 删除路由
-        None => forward_state.router.remove_route(path).await,
+        None => forward_state.router.remove_route(path),
     };
 
     match result {
         Ok(_) => {
-            let action = if target_group.is_some() {
+            let action = if route.is_some() {
                 "updated"
             } else {
                 "removed"
@@ -171,11 +187,7 @@ async fn update_runtime_router(
             );
         }
         Err(e) => {
-            let action = if target_group.is_some() {
-                "update"
-            } else {
-                "remove"
-            };
+            let action = if route.is_some() { "update" } else { "remove" };
             // 只记录错误，不影响API响应
             debug!(
                 "Failed to {} path '{}' in runtime router for forward '{}': {}",
@@ -328,7 +340,7 @@ pub async fn get_route(
         (status = 201, description = "成功创建路由规则 | Successfully created routing rule", body = SuccessResponse<RoutingRule>),
         (status = 400, description = "无效的请求参数 | Invalid request parameters", body = ErrorResponse),
         (status = 404, description = "转发服务或目标上游组不存在 | Forwarding service or target upstream group not found", body = ErrorResponse),
-        (status = 409, description = "路由规则已存在 | Routing rule already exists", body = ErrorResponse),
+        (status = 409, description = "路由规则已存在或与已有规则存在歧义 | Routing rule already exists or is ambiguous with an existing rule", body = ErrorResponse),
         (status = 500, description = "服务器内部错误 | Internal server error", body = ErrorResponse),
     )
 )]
@@ -384,14 +396,17 @@ pub async fn create_route(
             // 添加新的路由规则
             routing.push(payload.clone());
 
+            // 检查新规则是否与已有规则存在歧义
+            if let Err(error) = check_routing_ambiguity(routing, &forward_name) {
+                routing.pop();
+                if routing.is_empty() {
+                    forward.routing = None;
+                }
+                return error.into_response();
+            }
+
             // 同步更新Router中的路由表
-            update_runtime_router(
-                &app_state,
-                &forward_name,
-                &payload.path,
-                Some(&payload.target_group),
-            )
-            .await;
+            update_runtime_router(&app_state, &forward_name, &payload.path, Some(&payload)).await;
 
             info!(
                 "API: Created new route '{}' -> '{}' in forward '{}'",
@@ -423,6 +438,7 @@ pub async fn create_route(
         (status = 200, description = "成功更新路由规则 | Successfully updated routing rule", body = SuccessResponse<RoutingRule>),
         (status = 400, description = "无效的请求参数或Base64编码 | Invalid request parameters or Base64 encoding", body = ErrorResponse),
         (status = 404, description = "转发服务、路由规则或目标上游组不存在 | Forwarding service, routing rule or target upstream group not found", body = ErrorResponse),
+        (status = 409, description = "路由规则与已有规则存在歧义 | Routing rule is ambiguous with an existing rule", body = ErrorResponse),
         (status = 500, description = "服务器内部错误 | Internal server error", body = ErrorResponse),
     )
 )]
@@ -475,17 +491,23 @@ pub async fn update_route(
 
             match route_index {
                 Some(idx) => {
-                    // 更新路由规则
+                    // 更新路由规则，未指定优先级时保持原值
+                    let previous_priority = routing[idx].priority;
+                    if let Some(priority) = payload.priority {
+                        routing[idx].priority = priority;
+                    }
+
+                    // 检查更新后的规则是否与其他规则存在歧义
+                    if let Err(error) = check_routing_ambiguity(routing, &forward_name) {
+                        routing[idx].priority = previous_priority;
+                        return error.into_response();
+                    }
+
                     routing[idx].target_group = payload.target_group.clone();
 
                     // 同步更新Router中的路由表
-                    update_runtime_router(
-                        &app_state,
-                        &forward_name,
-                        &path,
-                        Some(&payload.target_group),
-                    )
-                    .await;
+                    update_runtime_router(&app_state, &forward_name, &path, Some(&routing[idx]))
+                        .await;
 
                     info!(
                         "API: Updated route '{}' to target '{}' in forward '{}'",
@@ -0,0 +1,48 @@
+use crate::{
+    api::v1::{handlers::utils::log_response_body, models::ErrorResponse},
+    config,
+    r#const::{api::error_types, http_headers::content_types},
+};
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use tracing::{error, info};
+
+/// 获取配置文件的 JSON Schema
+///
+/// Get the JSON Schema (Draft 2020-12) of the configuration file, for editor completion and CI validation. The schema is returned as-is, without the response envelope
+#[utoipa::path(
+    get,
+    path = "/api/v1/config/schema",
+    tag = "Config",
+    responses(
+        (status = 200, description = "配置文件的 JSON Schema | JSON Schema of the configuration file", content_type = "application/schema+json", body = Object),
+        (status = 500, description = "服务器内部错误 | Internal server error", body = ErrorResponse),
+    )
+)]
+pub async fn get_config_schema() -> Response {
+    match config::json_schema() {
+        Ok(schema) => {
+            info!("API: Retrieved configuration JSON Schema");
+            (
+                [(header::CONTENT_TYPE, content_types::SCHEMA_JSON)],
+                Json(schema),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!("API: Failed to generate configuration JSON Schema: {}", e);
+
+            let error = ErrorResponse::error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                error_types::INTERNAL_SERVER_ERROR,
+                format!("Failed to generate configuration JSON Schema: {}", e),
+            );
+            log_response_body(&error);
+
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(error)).into_response()
+        }
+    }
+}
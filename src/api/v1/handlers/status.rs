@@ -0,0 +1,65 @@
+use crate::api::v1::{
+    handlers::utils::log_response_body,
+    models::{ForwardStatus, RuntimeStatus, SuccessResponse},
+    routes::AppState,
+};
+use axum::{extract::State, Json};
+use tracing::info;
+
+/// 获取运行状态
+///
+/// Get the runtime status of forwarding services and upstreams
+#[utoipa::path(
+    get,
+    path = "/api/v1/status",
+    tag = "Status",
+    responses(
+        (status = 200, description = "成功获取运行状态 | Successfully retrieved runtime status", body = SuccessResponse<RuntimeStatus>),
+    )
+)]
+pub async fn get_status(State(app_state): State<AppState>) -> Json<SuccessResponse<RuntimeStatus>> {
+    let status = collect_status(&app_state).await;
+    info!(
+        "API: Retrieved runtime status of {} forwards and {} upstream groups",
+        status.forwards.len(),
+        status.upstream_groups.len()
+    );
+
+    let response = SuccessResponse::success_with_data(status);
+    log_response_body(&response);
+
+    Json(response)
+}
+
+// 从运行中的转发服务和上游管理器汇总运行状态
+pub(crate) async fn collect_status(app_state: &AppState) -> RuntimeStatus {
+    let admin_listen = app_state
+        .config
+        .read()
+        .await
+        .http_server
+        .as_ref()
+        .map(|s| format!("{}:{}", s.admin.address, s.admin.port))
+        .unwrap_or_default();
+
+    let mut forwards: Vec<ForwardStatus> = app_state
+        .forward_states
+        .values()
+        .map(|state| ForwardStatus::from_state(state))
+        .collect();
+    forwards.sort_by(|a, b| a.name.cmp(&b.name));
+
+    // 所有转发服务共享同一个上游管理器
+    let upstream_groups = app_state
+        .forward_states
+        .values()
+        .next()
+        .map(|state| state.upstream_manager.group_status())
+        .unwrap_or_default();
+
+    RuntimeStatus {
+        admin_listen,
+        forwards,
+        upstream_groups,
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{
+    api::v1::{
+        handlers::{status::collect_status, utils::log_response_body},
+        models::ErrorResponse,
+        routes::AppState,
+    },
+    error::AppError,
+    metrics::METRICS,
+    r#const::{api::error_types, http_headers::content_types},
+    support::{recent_logs, redacted_config_yaml, EnvironmentInfo, SupportBundle, VersionInfo},
+};
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use tracing::{error, info};
+
+/// 下载支持包
+///
+/// Download a support bundle (tar.gz) with redacted config, version, recent logs, metrics and runtime status
+#[utoipa::path(
+    get,
+    path = "/api/v1/support-bundle",
+    tag = "Support",
+    responses(
+        (status = 200, description = "支持包（application/gzip）| Support bundle (application/gzip)", content_type = "application/gzip", body = Vec<u8>),
+        (status = 500, description = "服务器内部错误 | Internal server error", body = ErrorResponse),
+    )
+)]
+pub async fn download_support_bundle(State(app_state): State<AppState>) -> Response {
+    match build_support_bundle(&app_state).await {
+        Ok((file_name, content)) => {
+            info!(
+                "API: Generated support bundle {:?} ({} bytes)",
+                file_name,
+                content.len()
+            );
+
+            (
+                StatusCode::OK,
+                [
+                    (header::CONTENT_TYPE, content_types::GZIP.to_string()),
+                    (
+                        header::CONTENT_DISPOSITION,
+                        format!("attachment; filename=\"{}\"", file_name),
+                    ),
+                ],
+                content,
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!("API: Failed to generate support bundle: {}", e);
+
+            let error = ErrorResponse::error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                error_types::INTERNAL_SERVER_ERROR,
+                format!("Failed to generate support bundle: {}", e),
+            );
+            log_response_body(&error);
+
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(error)).into_response()
+        }
+    }
+}
+
+// 收集支持包内容并打包
+async fn build_support_bundle(app_state: &AppState) -> Result<(String, Vec<u8>), AppError> {
+    let mut bundle = SupportBundle::new();
+
+    bundle.add_json("version.json", &VersionInfo::current())?;
+    bundle.add_json("environment.json", &EnvironmentInfo::current())?;
+    bundle.add(
+        "config.yaml",
+        redacted_config_yaml(&*app_state.config.read().await)?,
+    );
+    bundle.add_json("status.json", &collect_status(app_state).await)?;
+    bundle.add_json("reload.json", &app_state.reloader.status())?;
+    bundle.add(
+        "metrics.txt",
+        METRICS
+            .encode_text()
+            .map_err(|e| AppError::Internal(format!("Failed to encode metrics: {}", e)))?,
+    );
+    bundle.add("logs.txt", recent_logs());
+
+    let file_name = bundle.file_name();
+    Ok((file_name, bundle.finish()?))
+}
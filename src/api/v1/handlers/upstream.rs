@@ -1,20 +1,22 @@
 use crate::{
     api::v1::handlers::utils::{
-        find_by_name, log_request_body, log_response_body, not_found_error, success_response_ref,
+        find_by_name, log_request_body, log_response_body, not_found_error, success_response,
     },
-    api::v1::models::{ErrorResponse, SuccessResponse},
+    api::v1::models::{ErrorResponse, RevealQuery, SuccessResponse},
     api::v1::routes::AppState,
     config::Config,
     config::UpstreamConfig,
-    r#const::api::error_types,
+    r#const::api::{self, error_types},
 };
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::{IntoResponse, Response},
     Json,
 };
+use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
+use utoipa::ToSchema;
 use validator::Validate;
 
 // 处理上游服务不存在的错误
@@ -81,6 +83,50 @@ fn process_config(config_write: &mut Config, error_message: &str) -> Result<(),
     Ok(())
 }
 
+// 检查是否允许返回令牌和密码原文，只有启用了管理令牌认证（请求已通过认证）时才允许
+fn check_reveal(app_state: &AppState, query: &RevealQuery) -> Result<bool, ErrorResponse> {
+    if query.reveal && !app_state.auth_enabled {
+        warn!("API: Refused to reveal upstream secrets without admin authentication");
+        let error = ErrorResponse::error(
+            StatusCode::FORBIDDEN,
+            error_types::FORBIDDEN,
+            format!(
+                "Revealing secrets requires admin authentication, set {} to enable it",
+                api::ADMIN_AUTH_TOKEN_ENV
+            ),
+        );
+        log_response_body(&error);
+        return Err(error);
+    }
+
+    Ok(query.reveal)
+}
+
+// 根据是否显示原文返回上游服务配置
+#[inline(always)]
+fn present_upstream(upstream: &UpstreamConfig, reveal: bool) -> UpstreamConfig {
+    if reveal {
+        upstream.clone()
+    } else {
+        upstream.redacted()
+    }
+}
+
+// 将上游服务的启用状态应用到所有运行中的转发服务
+fn apply_upstream_enabled(app_state: &AppState, name: &str, enabled: bool) {
+    for forward_state in app_state.forward_states.values() {
+        if !forward_state
+            .upstream_manager
+            .set_upstream_enabled(name, enabled)
+        {
+            debug!(
+                "Upstream '{}' is not loaded by the running services, a restart is required for it to take effect",
+                name
+            );
+        }
+    }
+}
+
 // 查找依赖特定上游服务的组
 #[inline(always)]
 fn find_dependent_groups(config: &Config, upstream_name: &str) -> Vec<String> {
@@ -94,20 +140,35 @@ fn find_dependent_groups(config: &Config, upstream_name: &str) -> Vec<String> {
 
 /// 获取所有上游服务列表
 ///
-/// Get all upstream services list
+/// Get all upstream services list, secrets are redacted unless `reveal=true` is requested with an admin token
 #[utoipa::path(
     get,
     path = "/api/v1/upstreams",
     tag = "Upstreams",
+    params(RevealQuery),
     responses(
         (status = 200, description = "成功获取所有上游服务 | Successfully retrieved all upstream services", body = SuccessResponse<Vec<UpstreamConfig>>),
+        (status = 403, description = "未启用管理令牌认证，不允许显示原文 | Revealing secrets requires admin authentication", body = ErrorResponse),
         (status = 500, description = "服务器内部错误 | Internal server error", body = ErrorResponse),
     )
 )]
 pub async fn list_upstreams(
     State(app_state): State<AppState>,
-) -> Json<SuccessResponse<Vec<UpstreamConfig>>> {
-    let upstreams = app_state.config.read().await.upstreams.clone();
+    Query(query): Query<RevealQuery>,
+) -> Response {
+    let reveal = match check_reveal(&app_state, &query) {
+        Ok(reveal) => reveal,
+        Err(error) => return error.into_response(),
+    };
+
+    let upstreams: Vec<UpstreamConfig> = app_state
+        .config
+        .read()
+        .await
+        .upstreams
+        .iter()
+        .map(|upstream| present_upstream(upstream, reveal))
+        .collect();
     info!("API: Retrieved {} upstream services", upstreams.len());
 
     // 构建响应
@@ -116,27 +177,38 @@ pub async fn list_upstreams(
     // 记录响应体
     log_response_body(&response);
 
-    Json(response)
+    Json(response).into_response()
 }
 
 /// 获取单个上游服务详情
 ///
-/// Get a single upstream service detail
+/// Get a single upstream service detail, secrets are redacted unless `reveal=true` is requested with an admin token
 #[utoipa::path(
     get,
     path = "/api/v1/upstreams/{name}",
     tag = "Upstreams",
     params(
-        ("name" = String, Path, description = "上游服务名称 | Upstream service name")
+        ("name" = String, Path, description = "上游服务名称 | Upstream service name"),
+        RevealQuery
     ),
     responses(
         (status = 200, description = "成功获取上游服务 | Successfully retrieved upstream service", body = SuccessResponse<UpstreamConfig>),
+        (status = 403, description = "未启用管理令牌认证，不允许显示原文 | Revealing secrets requires admin authentication", body = ErrorResponse),
         (status = 404, description = "上游服务不存在 | Upstream service not found", body = ErrorResponse),
         (status = 500, description = "服务器内部错误 | Internal server error", body = ErrorResponse),
     )
 )]
 #[axum::debug_handler]
-pub async fn get_upstream(State(app_state): State<AppState>, Path(name): Path<String>) -> Response {
+pub async fn get_upstream(
+    State(app_state): State<AppState>,
+    Path(name): Path<String>,
+    Query(query): Query<RevealQuery>,
+) -> Response {
+    let reveal = match check_reveal(&app_state, &query) {
+        Ok(reveal) => reveal,
+        Err(error) => return error.into_response(),
+    };
+
     // 查找指定名称的上游服务
     let config_read = app_state.config.read().await;
     let upstream = find_upstream(&config_read, &name);
@@ -144,12 +216,13 @@ pub async fn get_upstream(State(app_state): State<AppState>, Path(name): Path<St
     match upstream {
         Some(upstream) => {
             info!("API: Retrieved upstream service '{}'", name);
+            let upstream = present_upstream(upstream, reveal);
 
             // 记录响应体
-            let response = SuccessResponse::success_with_data(upstream);
+            let response = SuccessResponse::success_with_data(&upstream);
             log_response_body(&response);
 
-            success_response_ref(upstream)
+            success_response(upstream)
         }
         None => upstream_not_found(&name),
     }
@@ -210,7 +283,7 @@ pub async fn create_upstream(
     info!("API: Created upstream service '{}'", upstream_clone.name);
 
     // 构建成功响应并记录
-    let response = SuccessResponse::success_with_data(upstream_clone);
+    let response = SuccessResponse::success_with_data(upstream_clone.redacted());
     log_response_body(&response);
 
     (StatusCode::CREATED, Json(response)).into_response()
@@ -261,6 +334,9 @@ pub async fn update_upstream(
 
     match upstream_index {
         Some(index) => {
+            // 未修改的令牌、密码和请求头的值以脱敏占位值回传时，保留现有的值
+            updated_upstream.restore_secrets(&config_write.upstreams[index]);
+
             // 更新上游服务
             config_write.upstreams[index] = updated_upstream.clone();
 
@@ -271,10 +347,14 @@ pub async fn update_upstream(
                 return response;
             }
 
+            // 释放配置写锁后更新运行中的上游启用状态
+            drop(config_write);
+            apply_upstream_enabled(&app_state, &name, updated_upstream.enabled);
+
             info!("API: Updated upstream service '{}'", name);
 
             // 构建成功响应并记录
-            let response = SuccessResponse::success_with_data(updated_upstream);
+            let response = SuccessResponse::success_with_data(updated_upstream.redacted());
             log_response_body(&response);
 
             Json(response).into_response()
@@ -286,6 +366,74 @@ pub async fn update_upstream(
     }
 }
 
+/// 上游服务PATCH操作的请求体
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct RequestPatchUpstreamPayload {
+    /// 是否启用，禁用时所有负载均衡器跳过该上游（维护/排空），正在处理的请求不受影响
+    pub enabled: bool,
+}
+
+/// 部分更新上游服务
+///
+/// Partially update an upstream service, e.g. drain it for maintenance
+#[utoipa::path(
+    patch,
+    path = "/api/v1/upstreams/{name}",
+    tag = "Upstreams",
+    params(
+        ("name" = String, Path, description = "上游服务名称 | Upstream service name")
+    ),
+    request_body = RequestPatchUpstreamPayload,
+    responses(
+        (status = 200, description = "成功更新上游服务 | Successfully updated upstream service", body = SuccessResponse<UpstreamConfig>),
+        (status = 400, description = "请求体格式错误 | Invalid request body", body = ErrorResponse),
+        (status = 404, description = "上游服务不存在 | Upstream service not found", body = ErrorResponse),
+        (status = 500, description = "服务器内部错误 | Internal server error", body = ErrorResponse),
+    )
+)]
+pub async fn patch_upstream(
+    State(app_state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<RequestPatchUpstreamPayload>,
+) -> Response {
+    // 记录请求体
+    log_request_body(&payload);
+
+    // 获取写锁
+    let mut config_write = app_state.config.write().await;
+
+    let upstream = match config_write.upstreams.iter_mut().find(|u| u.name == name) {
+        Some(upstream) => upstream,
+        None => {
+            warn!("API: Upstream service '{}' not found for patch", name);
+            return upstream_not_found(&name);
+        }
+    };
+
+    upstream.enabled = payload.enabled;
+    let upstream = upstream.redacted();
+
+    // 释放配置写锁后更新运行中的负载均衡器
+    drop(config_write);
+    apply_upstream_enabled(&app_state, &name, payload.enabled);
+
+    info!(
+        "API: {} upstream service '{}'",
+        if payload.enabled {
+            "Enabled"
+        } else {
+            "Drained"
+        },
+        name
+    );
+
+    // 构建成功响应并记录
+    let response = SuccessResponse::success_with_data(upstream);
+    log_response_body(&response);
+
+    Json(response).into_response()
+}
+
 /// 删除上游服务
 ///
 /// Delete an upstream service
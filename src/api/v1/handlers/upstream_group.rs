@@ -3,7 +3,7 @@ use crate::{
         create_upstream_map, find_by_name, log_request_body, log_response_body, not_found_error,
         success_response,
     },
-    api::v1::models::{ErrorResponse, SuccessResponse, UpstreamGroupDetail},
+    api::v1::models::{ErrorDetail, ErrorResponse, SuccessResponse, UpstreamGroupDetail},
     api::v1::routes::AppState,
     config::{validation::check_duplicate_upstreams, UpstreamRef},
     r#const::api::error_types,
@@ -148,34 +148,46 @@ pub async fn patch_upstream_group(
     // 记录请求体
     log_request_body(&payload);
 
-    // 验证请求体
-    if let Err(e) = payload.validate() {
-        warn!("API: Invalid PATCH request for group '{}': {}", name, e);
-        let error = ErrorResponse::from_validation_errors(e);
-        log_response_body(&error);
-        return (StatusCode::BAD_REQUEST, Json(error)).into_response();
-    }
+    // 验证请求体，收集全部问题后一次返回
+    let mut details = match payload.validate() {
+        Ok(()) => Vec::new(),
+        Err(e) => ErrorDetail::from_validation_errors(&e),
+    };
 
     // 检查重复的上游引用
-    if let Err(e) = check_duplicate_upstreams(&payload.upstreams, &name) {
+    if let Err(errors) = check_duplicate_upstreams(&payload.upstreams, &name) {
+        details.extend(errors.iter().map(ErrorDetail::from_validation_error));
+    }
+
+    // 获取写锁
+    let mut config_write = app_state.config.write().await;
+
+    // 验证所有引用的上游服务是否存在
+    let upstream_names: HashSet<&str> = config_write
+        .upstreams
+        .iter()
+        .map(|u| u.name.as_str())
+        .collect();
+    for upstream_ref in &payload.upstreams {
+        if !upstream_names.contains(upstream_ref.name.as_str()) {
+            details.push(ErrorDetail {
+                r#type: error_types::VALIDATION_ERROR.to_string(),
+                message: format!("Upstream '{}' not found", upstream_ref.name),
+            });
+        }
+    }
+
+    if !details.is_empty() {
+        let error =
+            ErrorResponse::from_details(StatusCode::BAD_REQUEST, error_types::BAD_REQUEST, details);
         warn!(
-            "API: Duplicate upstream references found in request for group '{}': {}",
-            name, e
-        );
-        let error = ErrorResponse::error(
-            StatusCode::BAD_REQUEST,
-            error_types::BAD_REQUEST,
-            e.message
-                .unwrap_or_else(|| "Duplicate upstream references found".into())
-                .to_string(),
+            "API: Invalid PATCH request for group '{}': {}",
+            name, error.error.message
         );
         log_response_body(&error);
         return (StatusCode::BAD_REQUEST, Json(error)).into_response();
     }
 
-    // 获取写锁
-    let mut config_write = app_state.config.write().await;
-
     // 查找上游组索引
     let group_index = config_write
         .upstream_groups
@@ -184,29 +196,6 @@ pub async fn patch_upstream_group(
 
     match group_index {
         Some(index) => {
-            // 验证所有引用的上游服务是否存在
-            let upstream_names: HashSet<&str> = config_write
-                .upstreams
-                .iter()
-                .map(|u| u.name.as_str())
-                .collect();
-
-            for upstream_ref in &payload.upstreams {
-                if !upstream_names.contains(upstream_ref.name.as_str()) {
-                    warn!(
-                        "API: Referenced upstream '{}' not found for group '{}'",
-                        upstream_ref.name, name
-                    );
-                    let error = ErrorResponse::error(
-                        StatusCode::BAD_REQUEST,
-                        error_types::BAD_REQUEST,
-                        format!("Upstream '{}' not found", upstream_ref.name),
-                    );
-                    log_response_body(&error);
-                    return (StatusCode::BAD_REQUEST, Json(error)).into_response();
-                }
-            }
-
             // 更新上游组的上游列表，直接赋值payload中的上游列表，避免不必要的clone
             config_write.upstream_groups[index].upstreams = payload.upstreams;
 
@@ -0,0 +1,52 @@
+use crate::{
+    api::v1::{
+        handlers::utils::log_response_body,
+        models::{ErrorResponse, SuccessResponse, UsageQuery},
+    },
+    r#const::{api::error_types, usage_limits},
+    usage::{DailyUsage, USAGE},
+};
+use axum::{
+    extract::Query,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use tracing::info;
+
+/// 获取按天汇总的 token 用量和费用
+///
+/// Get the daily token usage and cost per upstream group, upstream and model
+#[utoipa::path(
+    get,
+    path = "/api/v1/usage",
+    tag = "Usage",
+    params(UsageQuery),
+    responses(
+        (status = 200, description = "成功获取用量汇总 | Successfully retrieved usage summary", body = SuccessResponse<Vec<DailyUsage>>),
+        (status = 400, description = "查询天数无效 | Invalid number of days", body = ErrorResponse),
+    )
+)]
+pub async fn get_usage(Query(query): Query<UsageQuery>) -> Response {
+    let days = query.days.unwrap_or(usage_limits::RETENTION_DAYS);
+    if days == 0 || days > usage_limits::RETENTION_DAYS {
+        let error = ErrorResponse::error(
+            StatusCode::BAD_REQUEST,
+            error_types::BAD_REQUEST,
+            format!(
+                "Invalid days: must be between 1 and {}",
+                usage_limits::RETENTION_DAYS
+            ),
+        );
+        log_response_body(&error);
+        return (StatusCode::BAD_REQUEST, Json(error)).into_response();
+    }
+
+    let usage = USAGE.daily(days);
+    info!("API: Retrieved usage summary of {} days", usage.len());
+
+    let response = SuccessResponse::success_with_data(usage);
+    log_response_body(&response);
+
+    Json(response).into_response()
+}
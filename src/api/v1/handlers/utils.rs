@@ -2,6 +2,7 @@ use crate::{
     api::v1::models::{ErrorResponse, SuccessResponse},
     config::UpstreamConfig,
     r#const::api,
+    redact::to_redacted_json,
 };
 use axum::{
     http::StatusCode,
@@ -10,7 +11,6 @@ use axum::{
 };
 use base64::{engine::general_purpose, Engine as _};
 use serde::Serialize;
-use serde_json;
 use std::collections::HashMap;
 use tracing::{debug, warn};
 
@@ -52,11 +52,11 @@ pub fn find_by_name<'a, T>(
     items.iter().find(|item| get_name(item) == name)
 }
 
-/// 记录请求体日志
+/// 记录请求体日志，令牌和密码始终脱敏
 pub fn log_request_body<T: Serialize>(body: &T) {
-    match serde_json::to_string(body) {
-        Ok(json_str) => {
-            debug!("Request body: {:?}", json_str);
+    match to_redacted_json(body) {
+        Ok(json) => {
+            debug!("Request body: {:?}", json.to_string());
         }
         Err(e) => {
             warn!("Request body is not serializable: {}", e);
@@ -64,11 +64,11 @@ pub fn log_request_body<T: Serialize>(body: &T) {
     }
 }
 
-/// 记录响应体日志
+/// 记录响应体日志，令牌和密码始终脱敏
 pub fn log_response_body<T: Serialize>(body: &T) {
-    match serde_json::to_string(body) {
-        Ok(json_str) => {
-            debug!("Response body: {:?}", json_str);
+    match to_redacted_json(body) {
+        Ok(json) => {
+            debug!("Response body: {:?}", json.to_string());
         }
         Err(e) => {
             warn!("Response body is not serializable: {}", e);
@@ -0,0 +1,135 @@
+use crate::{
+    api::v1::{
+        handlers::utils::log_response_body,
+        models::{ConfigValidationResult, ErrorDetail, SuccessResponse},
+        routes::AppState,
+    },
+    config::Config,
+    r#const::api::error_types,
+    server::Router as ForwardRouter,
+};
+use axum::{extract::State, Json};
+use serde_yaml::{Mapping, Value};
+use tracing::info;
+use validator::Validate;
+
+/// 试运行配置校验
+///
+/// Validate a full or partial configuration document (YAML or JSON) without applying it. Top-level sections missing from the document are taken from the running configuration
+#[utoipa::path(
+    post,
+    path = "/api/v1/config/validate",
+    tag = "Config",
+    request_body(content = String, description = "完整或部分的配置文档（YAML 或 JSON）| Full or partial configuration document (YAML or JSON)", content_type = "application/yaml"),
+    responses(
+        (status = 200, description = "校验完成，结果见 valid 字段 | Validation finished, see the valid field for the result", body = SuccessResponse<ConfigValidationResult>),
+    )
+)]
+pub async fn validate_config(
+    State(app_state): State<AppState>,
+    document: String,
+) -> Json<SuccessResponse<ConfigValidationResult>> {
+    let running = app_state.config.read().await.clone();
+    let result = dry_run(&document, running);
+    info!(
+        "API: Validated configuration document with sections {:?}, {} errors found",
+        result.sections,
+        result.errors.len()
+    );
+
+    let response = SuccessResponse::success_with_data(result);
+    log_response_body(&response);
+
+    Json(response)
+}
+
+// 将配置文档合并到运行中的配置副本，执行预处理和校验，不应用任何变更
+fn dry_run(document: &str, mut config: Config) -> ConfigValidationResult {
+    let mut sections = Vec::new();
+    let mut errors = Vec::new();
+
+    // YAML 是 JSON 的超集，两种格式都可以直接解析
+    let mapping = match serde_yaml::from_str::<Mapping>(document) {
+        Ok(mapping) => mapping,
+        Err(e) => {
+            errors.push(error_detail(
+                error_types::PARSE_ERROR,
+                format!("Configuration document parsing error: {}", e),
+            ));
+            return ConfigValidationResult {
+                valid: false,
+                sections,
+                errors,
+            };
+        }
+    };
+
+    // 逐个替换文档中提供的顶层配置段，与加载配置文件一样忽略未知的配置段
+    for (key, value) in mapping {
+        let Some(section) = key.as_str() else {
+            continue;
+        };
+        let parsed = match section {
+            "http_server" => parse_section(value).map(|v| config.http_server = v),
+            "upstreams" => parse_section(value).map(|v| config.upstreams = v),
+            "upstream_groups" => parse_section(value).map(|v| config.upstream_groups = v),
+            _ => continue,
+        };
+
+        match parsed {
+            Ok(()) => sections.push(section.to_string()),
+            Err(e) => errors.push(error_detail(
+                error_types::PARSE_ERROR,
+                format!("{}: {}", section, e),
+            )),
+        }
+    }
+
+    // 配置段解析失败时不再继续校验
+    if errors.is_empty() {
+        // 预处理配置，例如预解析头部
+        if let Err(e) = config.post_process() {
+            errors.push(error_detail(error_types::CONFIG_ERROR, e.to_string()));
+        }
+
+        // 字段校验及上游、上游组、转发服务之间的引用校验
+        if let Err(e) = config.validate() {
+            errors.extend(ErrorDetail::from_validation_errors(&e));
+        }
+
+        // 检查路由规则能否构建路由表
+        let forwards = config
+            .http_server
+            .as_ref()
+            .map(|http_server| http_server.forwards.as_slice())
+            .unwrap_or_default();
+        for forward in forwards {
+            if let Err(e) = ForwardRouter::new(forward) {
+                errors.push(error_detail(
+                    error_types::CONFIG_ERROR,
+                    format!("http_server.forwards[{}].routing: {}", forward.name, e),
+                ));
+            }
+        }
+    }
+
+    ConfigValidationResult {
+        valid: errors.is_empty(),
+        sections,
+        errors,
+    }
+}
+
+// 解析单个顶层配置段
+#[inline(always)]
+fn parse_section<T: serde::de::DeserializeOwned>(value: Value) -> Result<T, serde_yaml::Error> {
+    serde_yaml::from_value(value)
+}
+
+#[inline(always)]
+fn error_detail(error_type: &str, message: String) -> ErrorDetail {
+    ErrorDetail {
+        r#type: error_type.to_string(),
+        message,
+    }
+}
@@ -1,4 +1,5 @@
 // API v1 模块
+pub mod audit;
 pub mod auth;
 pub mod handlers;
 pub mod models;
@@ -7,5 +8,5 @@ pub mod schemas;
 
 // 公共类型重新导出
 pub use models::{ErrorDetail, ErrorResponse, SuccessResponse};
-pub use routes::api_routes;
+pub use routes::{api_routes, api_routes_with_token};
 pub use schemas::{openapi_routes, ApiDoc};
@@ -1,11 +1,14 @@
 use crate::{
-    config::{UpstreamConfig, UpstreamGroupConfig},
+    audit::AuditEntry,
+    config::{validation, UpstreamConfig, UpstreamGroupConfig},
     r#const::api::{error_types, response_status},
+    server::ForwardState,
+    upstream::UpstreamGroupStatus,
 };
 use axum::{http::StatusCode, response::IntoResponse, Json};
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
-use validator::{Validate, ValidationErrors};
+use utoipa::{IntoParams, ToSchema};
+use validator::{Validate, ValidationError, ValidationErrors};
 
 /// 错误详情结构
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -37,6 +40,9 @@ pub struct ErrorResponse {
     pub status: String,
     /// 错误详情
     pub error: ErrorDetail,
+    /// 全部错误的列表，请求中存在多个问题时一次列出
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<ErrorDetail>,
 }
 
 /// 上游组详情模型 (扩展了标准的 UpstreamGroupConfig)
@@ -74,6 +80,95 @@ pub struct UpdateRoutePayload {
     /// 目标上游组名称
     #[validate(length(min = 1, message = "Target group cannot be empty"))]
     pub target_group: String,
+    /// 优先级，未指定时保持原值
+    #[serde(default)]
+    pub priority: Option<u32>,
+}
+
+/// 审计记录分页结果
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct AuditPage {
+    /// 内存中保留的审计记录总数
+    pub total: usize,
+    /// 当前页码（从 1 开始）
+    pub page: usize,
+    /// 每页记录数
+    pub page_size: usize,
+    /// 当前页的审计记录（最新的在前）
+    pub entries: Vec<AuditEntry>,
+}
+
+/// 配置校验结果
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ConfigValidationResult {
+    /// 配置是否有效
+    pub valid: bool,
+    /// 文档中提供的顶层配置段，未提供的配置段使用运行中的配置
+    pub sections: Vec<String>,
+    /// 校验错误列表，配置有效时为空
+    pub errors: Vec<ErrorDetail>,
+}
+
+/// 敏感信息显示查询参数
+#[derive(Debug, Default, Serialize, Deserialize, IntoParams)]
+pub struct RevealQuery {
+    /// 是否返回令牌和密码原文（仅在启用管理令牌认证时允许）
+    #[serde(default)]
+    pub reveal: bool,
+}
+
+/// 审计记录分页查询参数
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditQuery {
+    /// 页码（从 1 开始），默认为 1
+    pub page: Option<usize>,
+    /// 每页记录数，默认为 50，最大为 500
+    pub page_size: Option<usize>,
+}
+
+/// 用量汇总查询参数
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UsageQuery {
+    /// 返回最近的天数（UTC），默认为保留的全部天数，最大为 31
+    pub days: Option<usize>,
+}
+
+/// 转发服务运行状态
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ForwardStatus {
+    /// 转发服务名称
+    pub name: String,
+    /// 监听地址
+    pub listen: String,
+    /// 默认上游组
+    pub default_group: String,
+    /// 是否接收请求（禁用时所有请求返回 503）
+    pub enabled: bool,
+}
+
+impl ForwardStatus {
+    /// 从运行中的转发服务状态创建
+    pub fn from_state(state: &ForwardState) -> Self {
+        Self {
+            name: state.config.name.clone(),
+            listen: format!("{}:{}", state.config.address, state.config.port),
+            default_group: state.config.default_group.clone(),
+            enabled: state.is_enabled(),
+        }
+    }
+}
+
+/// 运行状态汇总
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct RuntimeStatus {
+    /// 管理服务监听地址
+    pub admin_listen: String,
+    /// 转发服务运行状态列表
+    pub forwards: Vec<ForwardStatus>,
+    /// 上游组运行状态列表
+    pub upstream_groups: Vec<UpstreamGroupStatus>,
 }
 
 impl SuccessResponse<()> {
@@ -120,31 +215,55 @@ impl ErrorResponse {
                 r#type: error_type.into(),
                 message: message.into(),
             },
+            details: Vec::new(),
+        }
+    }
+
+    /// 创建包含多个错误的响应，消息为全部错误消息的合并
+    pub fn from_details(
+        status_code: StatusCode,
+        error_type: impl Into<String>,
+        details: Vec<ErrorDetail>,
+    ) -> Self {
+        let message = details
+            .iter()
+            .map(|detail| detail.message.as_str())
+            .collect::<Vec<_>>()
+            .join("; ");
+        Self {
+            details,
+            ..Self::error(status_code, error_type, message)
         }
     }
 
-    /// 从验证错误创建响应
+    /// 从验证错误创建响应，列出全部验证错误（包括嵌套字段）
     pub fn from_validation_errors(errors: ValidationErrors) -> Self {
-        // 将所有验证错误平铺为一条消息
-        let message = errors
-            .field_errors()
+        Self::from_details(
+            StatusCode::BAD_REQUEST,
+            error_types::BAD_REQUEST,
+            ErrorDetail::from_validation_errors(&errors),
+        )
+    }
+}
+
+impl ErrorDetail {
+    /// 从单个验证错误创建错误详情
+    pub fn from_validation_error(error: &ValidationError) -> Self {
+        ErrorDetail {
+            r#type: error_types::VALIDATION_ERROR.to_string(),
+            message: error.to_string(),
+        }
+    }
+
+    /// 将嵌套的验证错误展开为错误详情列表，消息中包含出错的配置路径
+    pub fn from_validation_errors(errors: &ValidationErrors) -> Vec<Self> {
+        validation::error_messages(errors)
             .into_iter()
-            .map(|(field, errors)| {
-                let messages: Vec<String> = errors
-                    .iter()
-                    .map(|e| {
-                        e.message
-                            .as_ref()
-                            .map(|m| m.to_string())
-                            .unwrap_or_default()
-                    })
-                    .collect();
-                format!("{}: {}", field, messages.join(", "))
+            .map(|message| ErrorDetail {
+                r#type: error_types::VALIDATION_ERROR.to_string(),
+                message,
             })
-            .collect::<Vec<String>>()
-            .join("; ");
-
-        Self::error(StatusCode::BAD_REQUEST, error_types::BAD_REQUEST, message)
+            .collect()
     }
 }
 
@@ -158,7 +277,7 @@ impl IntoResponse for ErrorResponse {
 
 /// 将 UpstreamGroupConfig 转换为 UpstreamGroupDetail
 impl UpstreamGroupDetail {
-    /// 创建一个新的上游组详情，需要提供上游组配置和所有上游配置的映射
+    /// 创建一个新的上游组详情，需要提供上游组配置和所有上游配置的映射，令牌、密码和私钥密码会被脱敏
     pub fn from_config(
         group: &UpstreamGroupConfig,
         upstream_map: &std::collections::HashMap<&str, &UpstreamConfig>,
@@ -169,7 +288,7 @@ impl UpstreamGroupDetail {
             .filter_map(|upstream_ref| {
                 upstream_map
                     .get(&upstream_ref.name.as_str())
-                    .map(|&u| u.clone())
+                    .map(|&u| u.redacted())
             })
             .collect();
 
@@ -177,7 +296,7 @@ impl UpstreamGroupDetail {
             name: group.name.clone(),
             upstreams,
             balance: group.balance.clone(),
-            http_client: group.http_client.clone(),
+            http_client: group.http_client.redacted(),
         }
     }
 }
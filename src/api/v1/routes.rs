@@ -1,10 +1,16 @@
 use crate::{
     api::v1::{
+        audit::audit_middleware,
         auth::auth_middleware,
-        handlers::{forward, routing, upstream, upstream_group},
+        handlers::{
+            access_log, audit, events, forward, reload, restart, routing, schema, status, support,
+            upstream, upstream_group, usage, validate,
+        },
     },
+    audit::AuditLog,
     config::Config,
     r#const::api,
+    reload::ConfigReloader,
     server::ForwardState,
 };
 use axum::{
@@ -13,7 +19,7 @@ use axum::{
     Router,
 };
 use std::{collections::HashMap, sync::Arc};
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 
 /// 应用状态结构体，用于替代之前的元组状态
 #[derive(Clone)]
@@ -22,36 +28,76 @@ pub struct AppState {
     pub config: Arc<RwLock<Config>>,
     /// 转发服务状态
     pub forward_states: Arc<HashMap<String, Arc<ForwardState>>>,
+    /// 审计日志
+    pub audit: Arc<AuditLog>,
+    /// 配置变更锁，审计中间件在变更前快照、处理请求和变更后快照期间持有，
+    /// 使并发的变更依次执行，每条审计记录只包含本次请求的变更
+    pub mutations: Arc<Mutex<()>>,
+    /// 配置重载器
+    pub reloader: Arc<ConfigReloader>,
+    /// 是否启用了管理令牌认证
+    pub auth_enabled: bool,
 }
 
 pub const API_V1_PREFIX: &str = "/api/v1";
 const FORWARD_PATH: &str = "/forwards";
 const FORWARD_NAME_PATH: &str = "/forwards/{name}";
+const FORWARD_ENABLE_PATH: &str = "/forwards/{name}/enable";
+const FORWARD_DISABLE_PATH: &str = "/forwards/{name}/disable";
 const UPSTREAM_GROUP_PATH: &str = "/upstream-groups";
 const UPSTREAM_GROUP_NAME_PATH: &str = "/upstream-groups/{name}";
 const UPSTREAM_PATH: &str = "/upstreams";
 const UPSTREAM_NAME_PATH: &str = "/upstreams/{name}";
 const ROUTES_PATH: &str = "/forwards/{name}/routes";
 const ROUTE_PATH: &str = "/forwards/{name}/routes/{path}";
+pub const ACCESS_LOG_STREAM_PATH: &str = "/access-log/stream";
+const AUDIT_PATH: &str = "/audit";
+const EVENTS_PATH: &str = "/events";
+const CONFIG_RELOAD_PATH: &str = "/config/reload";
+pub const CONFIG_VALIDATE_PATH: &str = "/config/validate";
+const CONFIG_SCHEMA_PATH: &str = "/config/schema";
+const RESTART_PATH: &str = "/restart";
+const STATUS_PATH: &str = "/status";
+const USAGE_PATH: &str = "/usage";
+pub const SUPPORT_BUNDLE_PATH: &str = "/support-bundle";
 
-/// 创建 API v1 路由
+/// 创建 API v1 路由，从环境变量读取管理令牌
 pub fn api_routes(
     config: Arc<RwLock<Config>>,
     forward_states: Arc<HashMap<String, Arc<ForwardState>>>,
+    audit: Arc<AuditLog>,
+    reloader: Arc<ConfigReloader>,
+) -> Router {
+    // 检查是否需要认证
+    let auth_token = std::env::var(api::ADMIN_AUTH_TOKEN_ENV).ok();
+
+    api_routes_with_token(config, forward_states, audit, reloader, auth_token)
+}
+
+/// 使用指定的管理令牌创建 API v1 路由，令牌为空时不需要认证
+pub fn api_routes_with_token(
+    config: Arc<RwLock<Config>>,
+    forward_states: Arc<HashMap<String, Arc<ForwardState>>>,
+    audit: Arc<AuditLog>,
+    reloader: Arc<ConfigReloader>,
+    auth_token: Option<String>,
 ) -> Router {
     // 创建应用状态
     let app_state = AppState {
         config,
         forward_states,
+        audit,
+        mutations: Arc::new(Mutex::new(())),
+        reloader,
+        auth_enabled: auth_token.is_some(),
     };
 
-    // 检查是否需要认证
-    let auth_token = std::env::var(api::ADMIN_AUTH_TOKEN_ENV).ok();
-
     // 创建API路由器
     let mut api_router = Router::new()
         .route(FORWARD_PATH, get(forward::list_forwards))
         .route(FORWARD_NAME_PATH, get(forward::get_forward))
+        .route(FORWARD_ENABLE_PATH, post(forward::enable_forward))
+        .route(FORWARD_DISABLE_PATH, post(forward::disable_forward))
         .route(ROUTES_PATH, get(routing::list_routes))
         .route(ROUTES_PATH, post(routing::create_route))
         .route(ROUTE_PATH, get(routing::get_route))
@@ -73,7 +119,24 @@ pub fn api_routes(
         .route(UPSTREAM_NAME_PATH, get(upstream::get_upstream))
         .route(UPSTREAM_PATH, post(upstream::create_upstream))
         .route(UPSTREAM_NAME_PATH, put(upstream::update_upstream))
+        .route(UPSTREAM_NAME_PATH, patch(upstream::patch_upstream))
         .route(UPSTREAM_NAME_PATH, delete(upstream::delete_upstream))
+        .route(ACCESS_LOG_STREAM_PATH, get(access_log::stream_access_log))
+        .route(AUDIT_PATH, get(audit::list_audit_entries))
+        .route(EVENTS_PATH, get(events::stream_events))
+        .route(CONFIG_RELOAD_PATH, get(reload::get_reload_status))
+        .route(CONFIG_RELOAD_PATH, post(reload::reload_config))
+        .route(CONFIG_VALIDATE_PATH, post(validate::validate_config))
+        .route(CONFIG_SCHEMA_PATH, get(schema::get_config_schema))
+        .route(RESTART_PATH, post(restart::restart_process))
+        .route(STATUS_PATH, get(status::get_status))
+        .route(USAGE_PATH, get(usage::get_usage))
+        .route(SUPPORT_BUNDLE_PATH, get(support::download_support_bundle))
+        // 审计所有成功的配置变更
+        .layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            audit_middleware,
+        ))
         .with_state(app_state);
 
     // 如果设置了认证令牌，添加认证中间件
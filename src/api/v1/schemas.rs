@@ -1,16 +1,37 @@
 use crate::{
-    api::v1::handlers::{forward, routing, upstream, upstream_group},
+    api::v1::handlers::{
+        access_log, audit, events, forward, reload, restart, routing, schema, status, support,
+        upstream, upstream_group, usage, validate,
+    },
     api::v1::models::{
-        ErrorDetail, ErrorResponse, PatchUpstreamGroupPayload, SuccessResponse, UpdateRoutePayload,
+        AuditPage, ConfigValidationResult, ErrorDetail, ErrorResponse, ForwardStatus,
+        PatchUpstreamGroupPayload, RuntimeStatus, SuccessResponse, UpdateRoutePayload,
         UpstreamGroupDetail, UpstreamRef,
     },
     api::v1::routes::API_V1_PREFIX,
+    audit::{AuditChange, AuditEntry},
     config::{
-        http_server::RoutingRule, AuthConfig, AuthType, BalanceConfig, BalanceStrategy,
-        BreakerConfig, ForwardConfig, HeaderOp, HeaderOpType, HttpClientConfig,
-        HttpClientTimeoutConfig, ProxyConfig, RateLimitConfig, RetryConfig, TimeoutConfig,
-        UpstreamConfig, UpstreamGroupConfig, UpstreamRef as ConfigUpstreamRef,
+        http_server::RoutingRule, http_server::RoutingRuleType, AccessLogConfig, AccessLogFormat,
+        AdaptiveConfig, AuditSinkConfig, AuthConfig, AuthType, BalanceConfig, BalanceStrategy,
+        BodyTransformConfig, BreakerConfig, BreakerWindowConfig, BreakerWindowType, BudgetConfig,
+        CacheBackend, CacheConfig, ClientBudgetConfig, ClientTokenLimitConfig,
+        CompressionAlgorithm, CompressionConfig, Dialect, ErrorResponseConfig, ErrorResponseFormat,
+        ExternalAuthConfig, FairQueueConfig, ForwardConfig, HeaderOp, HeaderOpType, Http2Config,
+        HttpClientConfig, HttpClientTimeoutConfig, HttpVersion, ListenerConfig, LoadSheddingConfig,
+        MiddlewareStage, ModelAlias, ModelPriceConfig, OAuth2Config, OAuth2Grant, ParamLimitAction,
+        ParamLimitsConfig, PathRewriteConfig, PiiDetector, PiiPatternConfig, PiiRedactionConfig,
+        PluginConfig, PolicyConfig, PolicyFailMode, PolicyPayload, ProxyConfig, QueryParamOp,
+        QueueConfig, QueueTierConfig, RateLimitConfig, RateLimitKey, RedisConfig,
+        RequestHeadersConfig, RequestPriority, RetryConfig, StickyConfig, StreamNormalizeConfig,
+        SystemPromptConfig, SystemPromptMode, TimeoutConfig, TlsConfig, TlsVersion,
+        TokenLimitConfig, UpstreamConfig, UpstreamGroupConfig, UpstreamRef as ConfigUpstreamRef,
+        WebSocketConfig,
     },
+    events::{AccessEvent, SystemEvent},
+    reload::ReloadStatus,
+    restart::RestartStatus,
+    upstream::{UpstreamGroupStatus, UpstreamStatus},
+    usage::{DailyUsage, UsageEntry},
 };
 use axum::Router;
 use tracing::debug;
@@ -24,6 +45,8 @@ use utoipa_scalar::{Scalar, Servable};
         // 转发服务
         forward::list_forwards,
         forward::get_forward,
+        forward::enable_forward,
+        forward::disable_forward,
         // 路由规则
         routing::list_routes,
         routing::get_route,
@@ -39,7 +62,29 @@ use utoipa_scalar::{Scalar, Servable};
         upstream::get_upstream,
         upstream::create_upstream,
         upstream::update_upstream,
+        upstream::patch_upstream,
         upstream::delete_upstream,
+        // 访问日志
+        access_log::stream_access_log,
+        // 审计日志
+        audit::list_audit_entries,
+        // 管理事件
+        events::stream_events,
+        // 配置重载
+        reload::get_reload_status,
+        reload::reload_config,
+        // 配置校验
+        validate::validate_config,
+        // 配置 Schema
+        schema::get_config_schema,
+        // 进程重启
+        restart::restart_process,
+        // 运行状态
+        status::get_status,
+        // 用量统计
+        usage::get_usage,
+        // 支持包
+        support::download_support_bundle,
     ),
     components(
         schemas(
@@ -56,29 +101,110 @@ use utoipa_scalar::{Scalar, Servable};
             ErrorDetail,
             // 配置模型
             ForwardConfig,
+            ParamLimitsConfig,
+            ParamLimitAction,
+            BudgetConfig,
+            ClientBudgetConfig,
+            TokenLimitConfig,
+            QueueConfig,
+            FairQueueConfig,
+            QueueTierConfig,
+            CacheConfig,
+            AuditSinkConfig,
+            PiiRedactionConfig,
+            PiiDetector,
+            PiiPatternConfig,
+            PolicyConfig,
+            PolicyFailMode,
+            PluginConfig,
+            MiddlewareStage,
+            ListenerConfig,
+            RequestHeadersConfig,
+            WebSocketConfig,
+            CompressionConfig,
+            CompressionAlgorithm,
+            AccessLogConfig,
+            AccessLogFormat,
+            ErrorResponseConfig,
+            ErrorResponseFormat,
+            PolicyPayload,
+            CacheBackend,
+            RedisConfig,
+            LoadSheddingConfig,
+            RequestPriority,
+            ClientTokenLimitConfig,
             UpstreamConfig,
             UpstreamGroupConfig,
             UpstreamGroupDetail,
             RoutingRule,
+            RoutingRuleType,
             // 配置相关类型
             AuthConfig,
             AuthType,
+            OAuth2Config,
+            OAuth2Grant,
+            PathRewriteConfig,
+            QueryParamOp,
+            BodyTransformConfig,
+            StreamNormalizeConfig,
+            ModelPriceConfig,
+            SystemPromptConfig,
+            SystemPromptMode,
+            Dialect,
+            ModelAlias,
+            ExternalAuthConfig,
             BalanceConfig,
             BalanceStrategy,
+            AdaptiveConfig,
             BreakerConfig,
+            BreakerWindowConfig,
+            BreakerWindowType,
             HeaderOp,
             HeaderOpType,
             HttpClientConfig,
             HttpClientTimeoutConfig,
+            HttpVersion,
+            Http2Config,
+            TlsConfig,
+            TlsVersion,
             ProxyConfig,
             RateLimitConfig,
+            RateLimitKey,
             RetryConfig,
+            StickyConfig,
             TimeoutConfig,
             ConfigUpstreamRef,
             // 新增API模型
             PatchUpstreamGroupPayload,
             UpstreamRef,
             UpdateRoutePayload,
+            // 事件模型
+            AccessEvent,
+            // 审计模型
+            SuccessResponse<AuditPage>,
+            AuditPage,
+            AuditEntry,
+            AuditChange,
+            // 配置重载模型
+            SuccessResponse<ReloadStatus>,
+            ReloadStatus,
+            SystemEvent,
+            // 配置校验模型
+            SuccessResponse<ConfigValidationResult>,
+            ConfigValidationResult,
+            // 进程重启模型
+            SuccessResponse<RestartStatus>,
+            RestartStatus,
+            // 运行状态模型
+            SuccessResponse<RuntimeStatus>,
+            RuntimeStatus,
+            ForwardStatus,
+            UpstreamGroupStatus,
+            UpstreamStatus,
+            // 用量统计模型
+            SuccessResponse<Vec<DailyUsage>>,
+            DailyUsage,
+            UsageEntry,
         ),
     ),
     tags(
@@ -86,6 +212,14 @@ use utoipa_scalar::{Scalar, Servable};
         (name = "Routes", description = "路由规则 APIs | Routing Rule APIs"),
         (name = "UpstreamGroups", description = "上游组 APIs | Upstream Group APIs"),
         (name = "Upstreams", description = "上游服务 APIs | Upstream Service APIs"),
+        (name = "AccessLog", description = "访问日志 APIs | Access Log APIs"),
+        (name = "Audit", description = "审计日志 APIs | Audit Log APIs"),
+        (name = "Events", description = "管理事件 APIs | Admin Event APIs"),
+        (name = "Config", description = "配置管理 APIs | Configuration Management APIs"),
+        (name = "Restart", description = "进程重启 APIs | Process Restart APIs"),
+        (name = "Status", description = "运行状态 APIs | Runtime Status APIs"),
+        (name = "Usage", description = "用量统计 APIs | Usage Statistics APIs"),
+        (name = "Support", description = "支持包 APIs | Support Bundle APIs"),
     ),
     info(
         title = "LLMProxy APIs",
@@ -1,6 +1,12 @@
+use crate::config::ConfigFormat;
+use crate::r#const::{
+    config_watch, healthcheck, loadgen, log_file, log_levels, runtime_limits, shutdown_timeout,
+};
+use crate::runtime::CpuList;
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
 use std::path::PathBuf;
-use clap::{ArgAction, Parser};
-use crate::r#const::shutdown_timeout;
+use tracing_subscriber::EnvFilter;
 
 // LLMProxy - 大模型代理服务
 #[derive(Parser, Debug, Clone)]
@@ -25,38 +31,483 @@ pub struct Args {
     #[clap(
         short,
         long,
+        global = true,
         value_name = "FILE",
         default_value = "config.yaml",
-        help = "Path to the configuration file"
+        help = "Path to the configuration file, a directory whose configuration files are merged, or an etcd:// or consul:// key address"
     )]
     pub config: PathBuf,
 
+    // 配置文件格式，未指定时按扩展名识别
+    #[clap(
+        long = "format",
+        global = true,
+        value_name = "FORMAT",
+        value_enum,
+        help = "Configuration file format, detected from the file extension by default (unknown extensions are read as YAML)"
+    )]
+    pub config_format: Option<ConfigFormat>,
+
     // 是否开启调试模式
     #[clap(
         short, 
         long, 
+        global = true,
         action = ArgAction::SetTrue, 
         help = "Enable debug mode"
     )]
     pub debug: bool,
 
+    // 日志输出格式
+    #[clap(
+        long = "log-format",
+        global = true,
+        value_name = "FORMAT",
+        value_enum,
+        default_value_t = LogFormat::Text,
+        help = "Log output format, json writes one JSON object per line for log collectors"
+    )]
+    pub log_format: LogFormat,
+
+    // 日志级别，支持按模块设置
+    #[clap(
+        long = "log-level",
+        global = true,
+        value_name = "DIRECTIVES",
+        help = "Log level, optionally per module (e.g. info,llmproxy::upstream=debug). Overrides --debug"
+    )]
+    pub log_level: Option<String>,
+
+    // 日志文件路径
+    #[clap(
+        long = "log-file",
+        global = true,
+        value_name = "FILE",
+        help = "Write logs to this file instead of stdout, rotated by size and optionally by time"
+    )]
+    pub log_file: Option<PathBuf>,
+
+    // 日志文件的最大大小（MB）
+    #[clap(
+        long = "log-max-size",
+        global = true,
+        value_name = "MB",
+        default_value_t = log_file::DEFAULT_MAX_SIZE,
+        help = "Rotate the log file once it would exceed this size in MB"
+    )]
+    pub log_max_size: u64,
+
+    // 日志文件按时间轮转的周期
+    #[clap(
+        long = "log-rotation",
+        global = true,
+        value_name = "PERIOD",
+        value_enum,
+        default_value_t = LogRotation::Never,
+        help = "Also rotate the log file at the start of every hour or day (UTC)"
+    )]
+    pub log_rotation: LogRotation,
+
+    // 保留的日志轮转文件数
+    #[clap(
+        long = "log-max-files",
+        global = true,
+        value_name = "COUNT",
+        default_value_t = log_file::DEFAULT_MAX_FILES,
+        help = "Number of rotated log files to keep"
+    )]
+    pub log_max_files: u32,
+
+    // 写入日志文件时是否同时输出到标准输出
+    #[clap(
+        long = "log-stdout",
+        global = true,
+        action = ArgAction::SetTrue,
+        help = "Keep writing logs to stdout when --log-file is set"
+    )]
+    pub log_stdout: bool,
+
     // 是否仅测试配置文件
     #[clap(
         short = 't', 
         long = "test", 
         action = ArgAction::SetTrue, 
-        help = "Test configuration file for validity and exit"
+        help = "Test configuration file for validity and exit (same as the validate subcommand)"
     )]
     pub test_config: bool,
 
+    // 配置文件或引用的密钥文件变化时自动重新加载
+    #[clap(
+        long = "watch-config",
+        global = true,
+        action = ArgAction::SetTrue,
+        help = "Reload automatically when the configuration file or directory, or a secret file it references, changes"
+    )]
+    pub watch_config: bool,
+
+    // 自动重新加载前等待文件变化结束的时间（毫秒）
+    #[clap(
+        long = "watch-debounce",
+        global = true,
+        value_name = "MILLISECONDS",
+        default_value_t = config_watch::DEFAULT_DEBOUNCE_MS,
+        help = "Time in milliseconds to wait for file changes to settle before reloading"
+    )]
+    pub watch_debounce: u64,
+
+    // 运行时工作线程数
+    #[clap(
+        long = "worker-threads",
+        global = true,
+        value_name = "COUNT",
+        help = "Number of Tokio runtime worker threads (defaults to the number of CPUs, or of CPUs in --cpu-affinity)"
+    )]
+    pub worker_threads: Option<usize>,
+
+    // 运行时阻塞线程数上限
+    #[clap(
+        long = "max-blocking-threads",
+        global = true,
+        value_name = "COUNT",
+        help = "Maximum number of Tokio runtime threads for blocking operations such as file I/O and DNS resolution (default 512)"
+    )]
+    pub max_blocking_threads: Option<usize>,
+
+    // 进程绑定的 CPU
+    #[clap(
+        long = "cpu-affinity",
+        global = true,
+        value_name = "CPUS",
+        help = "Pin the process to the given CPUs, e.g. 0-3,6 (Linux only)"
+    )]
+    pub cpu_affinity: Option<CpuList>,
+
     // 优雅关闭超时时间（秒）
     #[clap(
         long = "shutdown-timeout", 
+        global = true,
         value_name = "SECONDS", 
         default_value_t = shutdown_timeout::DEFAULT, 
         help = "Maximum time in seconds to wait for complete shutdown"
     )]
     pub shutdown_timeout: u64,
+
+    // 子命令
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+// 日志输出格式
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    // 纯文本
+    Text,
+    // 每行一个 JSON 对象
+    Json,
+}
+
+// 日志文件按时间轮转的周期
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogRotation {
+    // 只按大小轮转
+    Never,
+    // 每小时轮转
+    Hourly,
+    // 每天轮转
+    Daily,
+}
+
+// 子命令，未指定子命令时运行服务
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    // 运行代理服务
+    #[command(about = "Run the proxy service (default when no subcommand is given)")]
+    Run,
+
+    // 验证配置文件
+    #[command(about = "Validate the configuration file and exit")]
+    Validate(ValidateArgs),
+
+    // 配置文件工具
+    #[command(about = "Configuration file tools")]
+    Config(ConfigArgs),
+
+    // 输出配置文件的 JSON Schema
+    #[command(about = "Print the JSON Schema of the configuration file")]
+    Schema,
+
+    // 生成命令行补全脚本
+    #[command(about = "Generate a shell completion script")]
+    Completions(CompletionsArgs),
+
+    // 检查运行中实例的健康状态
+    #[command(
+        about = "Check the health of a running instance via its admin server, exits non-zero when unhealthy (e.g. for Docker HEALTHCHECK)"
+    )]
+    Healthcheck(HealthcheckArgs),
+
+    // 实时查看运行中实例的访问日志
+    #[command(about = "Tail live access logs from a running instance via its admin server")]
+    Tail(TailArgs),
+
+    // 从运行中的实例下载支持包
+    #[command(
+        about = "Download a support bundle (redacted config, recent logs, metrics, runtime status) from a running instance"
+    )]
+    SupportBundle(SupportBundleArgs),
+
+    // 向运行中的实例发送模拟流量
+    #[command(
+        about = "Send synthetic OpenAI-style chat traffic (optionally streaming) to a running instance and report throughput and latency"
+    )]
+    Loadgen(LoadgenArgs),
+}
+
+// loadgen 子命令参数
+#[derive(clap::Args, Debug, Clone)]
+pub struct LoadgenArgs {
+    // 转发服务地址
+    #[clap(
+        long,
+        value_name = "URL",
+        default_value = loadgen::DEFAULT_URL,
+        help = "Base URL of the forward server"
+    )]
+    pub url: String,
+
+    // 请求路径
+    #[clap(
+        long,
+        value_name = "PATH",
+        default_value = loadgen::DEFAULT_PATH,
+        help = "Request path"
+    )]
+    pub path: String,
+
+    // 请求体中的模型名称
+    #[clap(
+        long,
+        value_name = "MODEL",
+        default_value = loadgen::DEFAULT_MODEL,
+        help = "Model name sent in the request body"
+    )]
+    pub model: String,
+
+    // 请求总数
+    #[clap(
+        short = 'n',
+        long,
+        value_name = "COUNT",
+        default_value_t = loadgen::DEFAULT_REQUESTS,
+        help = "Total number of requests to send"
+    )]
+    pub requests: u64,
+
+    // 并发数
+    #[clap(
+        short = 'C',
+        long,
+        value_name = "COUNT",
+        default_value_t = loadgen::DEFAULT_CONCURRENCY,
+        help = "Number of requests in flight at the same time"
+    )]
+    pub concurrency: u32,
+
+    // 流式请求的比例
+    #[clap(
+        long = "stream-ratio",
+        value_name = "RATIO",
+        default_value_t = 0.0,
+        help = "Fraction of requests sent with \"stream\": true (0.0-1.0), streaming responses are read as SSE"
+    )]
+    pub stream_ratio: f64,
+
+    // 提示词单词数
+    #[clap(
+        long = "prompt-words",
+        value_name = "COUNT",
+        default_value_t = loadgen::DEFAULT_PROMPT_WORDS,
+        help = "Number of words in the synthetic user prompt"
+    )]
+    pub prompt_words: usize,
+
+    // 最大生成 token 数
+    #[clap(
+        long = "max-tokens",
+        value_name = "COUNT",
+        default_value_t = loadgen::DEFAULT_MAX_TOKENS,
+        help = "Value of max_tokens in the request body"
+    )]
+    pub max_tokens: u32,
+
+    // 额外的请求头
+    #[clap(
+        short = 'H',
+        long = "header",
+        value_name = "NAME:VALUE",
+        value_parser = parse_header,
+        help = "Extra request header, repeatable (e.g. -H 'Authorization: Bearer sk-...')"
+    )]
+    pub headers: Vec<(String, String)>,
+
+    // 请求超时时间（秒）
+    #[clap(
+        long,
+        value_name = "SECONDS",
+        default_value_t = loadgen::DEFAULT_TIMEOUT,
+        help = "Maximum time in seconds for each request, including reading the response body"
+    )]
+    pub timeout: u64,
+}
+
+// tail 子命令参数
+#[derive(clap::Args, Debug, Clone)]
+pub struct TailArgs {
+    // 管理服务地址
+    #[clap(
+        long,
+        value_name = "URL",
+        default_value = "http://localhost:9000",
+        help = "Base URL of the admin server"
+    )]
+    pub admin: String,
+
+    // 过滤条件
+    #[clap(
+        long = "filter",
+        value_name = "KEY=VALUE",
+        value_parser = parse_filter,
+        help = "Only show matching events, repeatable (keys: forward, group, method, path, status, e.g. status=5xx)"
+    )]
+    pub filters: Vec<(String, String)>,
+
+    // 管理 API 认证令牌
+    #[clap(
+        long,
+        value_name = "TOKEN",
+        help = "Admin API bearer token (defaults to the LLMPROXY_ADMIN_AUTH_TOKEN environment variable)"
+    )]
+    pub token: Option<String>,
+
+    // 是否禁用彩色输出
+    #[clap(
+        long = "no-color",
+        action = ArgAction::SetTrue,
+        help = "Disable colored output"
+    )]
+    pub no_color: bool,
+}
+
+// support-bundle 子命令参数
+#[derive(clap::Args, Debug, Clone)]
+pub struct SupportBundleArgs {
+    // 管理服务地址
+    #[clap(
+        long,
+        value_name = "URL",
+        default_value = "http://localhost:9000",
+        help = "Base URL of the admin server"
+    )]
+    pub admin: String,
+
+    // 管理 API 认证令牌
+    #[clap(
+        long,
+        value_name = "TOKEN",
+        help = "Admin API bearer token (defaults to the LLMPROXY_ADMIN_AUTH_TOKEN environment variable)"
+    )]
+    pub token: Option<String>,
+
+    // 输出文件路径
+    #[clap(
+        short,
+        long,
+        value_name = "FILE",
+        help = "Output file (defaults to the file name suggested by the admin server)"
+    )]
+    pub output: Option<PathBuf>,
+}
+
+// validate 子命令参数
+#[derive(clap::Args, Debug, Clone)]
+pub struct ValidateArgs {
+    // 是否将警告视为错误
+    #[clap(
+        long,
+        action = ArgAction::SetTrue,
+        help = "Treat warnings (e.g. unused upstreams, unreferenced groups) as errors and report them all"
+    )]
+    pub strict: bool,
+}
+
+// config 子命令参数
+#[derive(clap::Args, Debug, Clone)]
+pub struct ConfigArgs {
+    // 配置文件工具子命令
+    #[command(subcommand)]
+    pub command: ConfigCommand,
+}
+
+// 配置文件工具子命令
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigCommand {
+    // 输出生效的配置
+    #[command(
+        about = "Print the effective configuration as YAML, with defaults applied and secrets masked"
+    )]
+    Dump,
+}
+
+// completions 子命令参数
+#[derive(clap::Args, Debug, Clone)]
+pub struct CompletionsArgs {
+    // 目标 Shell
+    #[clap(
+        value_name = "SHELL",
+        value_enum,
+        help = "Shell to generate the completion script for"
+    )]
+    pub shell: Shell,
+}
+
+// healthcheck 子命令参数
+#[derive(clap::Args, Debug, Clone)]
+pub struct HealthcheckArgs {
+    // 管理服务地址
+    #[clap(
+        long = "admin-url",
+        value_name = "URL",
+        default_value = "http://localhost:9000",
+        help = "Base URL of the admin server"
+    )]
+    pub admin_url: String,
+
+    // 请求超时时间（秒）
+    #[clap(
+        long,
+        value_name = "SECONDS",
+        default_value_t = healthcheck::DEFAULT_TIMEOUT,
+        help = "Maximum time in seconds to wait for the health check response"
+    )]
+    pub timeout: u64,
+}
+
+// 解析 NAME:VALUE 形式的请求头
+fn parse_header(value: &str) -> Result<(String, String), String> {
+    match value.split_once(':') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!("Invalid header '{}', expected NAME:VALUE", value)),
+    }
+}
+
+// 解析 KEY=VALUE 形式的过滤条件
+fn parse_filter(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("Invalid filter '{}', expected KEY=VALUE", value)),
+    }
 }
 
 impl Args {
@@ -78,6 +529,101 @@ impl Args {
             ));
         }
 
+        // 验证日志文件轮转参数
+        if self.log_max_size < log_file::MIN_MAX_SIZE || self.log_max_size > log_file::MAX_MAX_SIZE
+        {
+            return Err(format!(
+                "Log max size must be between {} and {} MB",
+                log_file::MIN_MAX_SIZE,
+                log_file::MAX_MAX_SIZE
+            ));
+        }
+        if self.log_max_files < log_file::MIN_MAX_FILES
+            || self.log_max_files > log_file::MAX_MAX_FILES
+        {
+            return Err(format!(
+                "Log max files must be between {} and {}",
+                log_file::MIN_MAX_FILES,
+                log_file::MAX_MAX_FILES
+            ));
+        }
+
+        // 验证运行时参数
+        if let Some(worker_threads) = self.worker_threads {
+            if worker_threads == 0 || worker_threads > runtime_limits::MAX_WORKER_THREADS {
+                return Err(format!(
+                    "Worker threads must be between 1 and {}",
+                    runtime_limits::MAX_WORKER_THREADS
+                ));
+            }
+        }
+        if let Some(max_blocking_threads) = self.max_blocking_threads {
+            if max_blocking_threads == 0
+                || max_blocking_threads > runtime_limits::MAX_BLOCKING_THREADS
+            {
+                return Err(format!(
+                    "Max blocking threads must be between 1 and {}",
+                    runtime_limits::MAX_BLOCKING_THREADS
+                ));
+            }
+        }
+        if self.cpu_affinity.is_some() && !cfg!(target_os = "linux") {
+            return Err("CPU affinity is only supported on Linux".to_string());
+        }
+
+        // 验证健康检查超时时间
+        if let Some(Command::Healthcheck(ref healthcheck_args)) = self.command {
+            if healthcheck_args.timeout < healthcheck::MIN_TIMEOUT
+                || healthcheck_args.timeout > healthcheck::MAX_TIMEOUT
+            {
+                return Err(format!(
+                    "Health check timeout must be between {} and {} seconds",
+                    healthcheck::MIN_TIMEOUT,
+                    healthcheck::MAX_TIMEOUT
+                ));
+            }
+        }
+
+        // 验证压测参数
+        if let Some(Command::Loadgen(ref loadgen_args)) = self.command {
+            if loadgen_args.requests == 0 {
+                return Err("Load generator request count must be at least 1".to_string());
+            }
+            if loadgen_args.concurrency == 0 || loadgen_args.concurrency > loadgen::MAX_CONCURRENCY
+            {
+                return Err(format!(
+                    "Load generator concurrency must be between 1 and {}",
+                    loadgen::MAX_CONCURRENCY
+                ));
+            }
+            if !(0.0..=1.0).contains(&loadgen_args.stream_ratio) {
+                return Err("Load generator stream ratio must be between 0.0 and 1.0".to_string());
+            }
+            if loadgen_args.timeout == 0 {
+                return Err("Load generator timeout must be at least 1 second".to_string());
+            }
+        }
+
+        // 验证日志级别
+        if let Some(level) = &self.log_level {
+            EnvFilter::try_new(level)
+                .map_err(|e| format!("Invalid log level '{}': {}", level, e))?;
+        }
+
         Ok(())
     }
+
+    // 日志过滤条件，未指定日志级别时按调试模式输出 debug 或 info 及以上级别
+    pub fn log_filter(&self) -> EnvFilter {
+        self.log_level
+            .as_deref()
+            .and_then(|level| EnvFilter::try_new(level).ok())
+            .unwrap_or_else(|| {
+                EnvFilter::new(if self.debug {
+                    log_levels::DEBUG
+                } else {
+                    log_levels::DEFAULT
+                })
+            })
+    }
 }
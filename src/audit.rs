@@ -0,0 +1,285 @@
+use crate::{
+    config::{AuditConfig, Config},
+    error::AppError,
+    events::{unix_millis, SystemEvent, EVENTS},
+    r#const::api,
+    redact::redact_secrets,
+};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{
+    collections::{HashSet, VecDeque},
+    fs::{File, OpenOptions},
+    io::Write,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use tracing::{error, info};
+use utoipa::ToSchema;
+use xxhash_rust::xxh3::xxh3_64;
+
+// 单项配置变更
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AuditChange {
+    // 变更的配置路径，如 upstreams[openai].weight
+    pub path: String,
+    // 变更前的值，新增时为空
+    #[schema(value_type = Option<Object>)]
+    pub before: Option<Value>,
+    // 变更后的值，删除时为空
+    #[schema(value_type = Option<Object>)]
+    pub after: Option<Value>,
+}
+
+// 审计记录
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AuditEntry {
+    // 记录序号（进程内单调递增）
+    pub id: u64,
+    // 记录时间（Unix 毫秒时间戳）
+    pub timestamp: u64,
+    // 操作者（管理令牌指纹或 anonymous）
+    pub actor: String,
+    // 请求方法
+    pub method: String,
+    // 请求的 API 端点
+    pub endpoint: String,
+    // 配置变更列表
+    pub changes: Vec<AuditChange>,
+}
+
+// 审计日志，内存中保留最近的记录，并追加写入审计文件或结构化日志
+pub struct AuditLog {
+    // 最近的审计记录（按时间顺序）
+    entries: RwLock<VecDeque<AuditEntry>>,
+    // 内存中保留的最大记录数量
+    max_entries: usize,
+    // 下一个记录序号
+    next_id: AtomicU64,
+    // 审计文件
+    file: Option<Mutex<File>>,
+}
+
+impl AuditLog {
+    // 根据配置创建审计日志
+    pub fn new(config: &AuditConfig) -> Result<Self, AppError> {
+        let file = match config.file {
+            Some(ref path) => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| {
+                        AppError::Config(format!("Failed to open audit log file {:?}: {}", path, e))
+                    })?;
+                info!("Admin audit log is written to {:?}", path);
+                Some(Mutex::new(file))
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            entries: RwLock::new(VecDeque::new()),
+            max_entries: config.max_entries,
+            next_id: AtomicU64::new(1),
+            file,
+        })
+    }
+
+    // 记录一次成功的配置变更
+    pub fn record(
+        &self,
+        actor: String,
+        method: String,
+        endpoint: String,
+        changes: Vec<AuditChange>,
+    ) -> AuditEntry {
+        let entry = AuditEntry {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            timestamp: unix_millis(),
+            actor,
+            method,
+            endpoint,
+            changes,
+        };
+
+        self.write_sink(&entry);
+        EVENTS.publish_system(SystemEvent::ConfigChanged {
+            timestamp: entry.timestamp,
+            audit_id: entry.id,
+            actor: entry.actor.clone(),
+            method: entry.method.clone(),
+            endpoint: entry.endpoint.clone(),
+            changes: entry.changes.len(),
+        });
+
+        let mut entries = self.entries.write();
+        entries.push_back(entry.clone());
+        while entries.len() > self.max_entries {
+            entries.pop_front();
+        }
+
+        entry
+    }
+
+    // 分页获取审计记录（最新的在前），页码从 1 开始，返回总数和当前页记录
+    pub fn page(&self, page: usize, page_size: usize) -> (usize, Vec<AuditEntry>) {
+        let entries = self.entries.read();
+        let skip = page.saturating_sub(1).saturating_mul(page_size);
+        let items = entries
+            .iter()
+            .rev()
+            .skip(skip)
+            .take(page_size)
+            .cloned()
+            .collect();
+
+        (entries.len(), items)
+    }
+
+    // 写入审计文件或结构化日志
+    fn write_sink(&self, entry: &AuditEntry) {
+        let line = match serde_json::to_string(entry) {
+            Ok(line) => line,
+            Err(e) => {
+                error!("Failed to serialize audit entry: {}", e);
+                return;
+            }
+        };
+
+        match self.file {
+            Some(ref file) => {
+                let mut file = file.lock();
+                if let Err(e) = writeln!(file, "{}", line).and_then(|_| file.flush()) {
+                    error!("Failed to write audit log: {}", e);
+                }
+            }
+            None => info!(target: "audit", "{}", line),
+        }
+    }
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self {
+            entries: RwLock::new(VecDeque::new()),
+            max_entries: AuditConfig::default().max_entries,
+            next_id: AtomicU64::new(1),
+            file: None,
+        }
+    }
+}
+
+// 根据 Authorization 头计算操作者标识，只记录令牌指纹而不记录令牌本身
+pub fn actor_from_authorization(authorization: Option<&str>) -> String {
+    match authorization.and_then(|value| value.strip_prefix(api::auth::BEARER_PREFIX)) {
+        Some(token) if !token.trim().is_empty() => {
+            format!("token:{:016x}", xxh3_64(token.trim().as_bytes()))
+        }
+        _ => api::auth::ANONYMOUS_ACTOR.to_string(),
+    }
+}
+
+// 生成脱敏后的配置快照
+pub fn config_snapshot(config: &Config) -> Value {
+    let mut value = serde_json::to_value(config).unwrap_or(Value::Null);
+    redact_secrets(&mut value);
+    value
+}
+
+// 比较两个配置快照，返回变更列表
+pub fn diff_config(before: &Value, after: &Value) -> Vec<AuditChange> {
+    let mut changes = Vec::new();
+    diff_value(String::new(), Some(before), Some(after), &mut changes);
+    changes
+}
+
+// 拼接配置路径
+#[inline(always)]
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+// 获取数组元素的标识（name 或 path 字段）
+#[inline(always)]
+fn element_key(value: &Value) -> Option<&str> {
+    value
+        .get("name")
+        .or_else(|| value.get("path"))
+        .and_then(Value::as_str)
+}
+
+// 将所有元素都有唯一标识的数组转换为以标识为键的映射，便于按名称比较
+fn keyed_elements(items: &[Value]) -> Option<Map<String, Value>> {
+    let mut seen = HashSet::with_capacity(items.len());
+    let mut map = Map::new();
+
+    for item in items {
+        let key = element_key(item)?;
+        if !seen.insert(key) {
+            return None;
+        }
+        map.insert(key.to_string(), item.clone());
+    }
+
+    Some(map)
+}
+
+// 比较两个对象的所有字段
+fn diff_fields(
+    path: &str,
+    before: &Map<String, Value>,
+    after: &Map<String, Value>,
+    format_key: impl Fn(&str, &str) -> String,
+    changes: &mut Vec<AuditChange>,
+) {
+    for (key, value) in before {
+        diff_value(format_key(path, key), Some(value), after.get(key), changes);
+    }
+    for (key, value) in after {
+        if !before.contains_key(key) {
+            diff_value(format_key(path, key), None, Some(value), changes);
+        }
+    }
+}
+
+fn diff_value(
+    path: String,
+    before: Option<&Value>,
+    after: Option<&Value>,
+    changes: &mut Vec<AuditChange>,
+) {
+    match (before, after) {
+        (Some(Value::Object(before)), Some(Value::Object(after))) => {
+            diff_fields(&path, before, after, join_path, changes);
+        }
+        (Some(Value::Array(before_items)), Some(Value::Array(after_items))) => {
+            // 具名元素（上游、上游组、转发服务、路由规则）按名称比较，避免删除元素导致的错位
+            match (keyed_elements(before_items), keyed_elements(after_items)) {
+                (Some(before), Some(after)) => diff_fields(
+                    &path,
+                    &before,
+                    &after,
+                    |path, key| format!("{}[{}]", path, key),
+                    changes,
+                ),
+                _ if before_items != after_items => changes.push(AuditChange {
+                    path,
+                    before: Some(Value::Array(before_items.clone())),
+                    after: Some(Value::Array(after_items.clone())),
+                }),
+                _ => {}
+            }
+        }
+        (before, after) if before != after => changes.push(AuditChange {
+            path,
+            before: before.cloned(),
+            after: after.cloned(),
+        }),
+        _ => {}
+    }
+}
@@ -5,11 +5,15 @@ pub use simple::{
     FailoverBalancer, RandomBalancer, RoundRobinBalancer, WeightedRoundRobinBalancer,
 };
 
+use crate::adaptive::AdaptiveLimiter;
 use crate::breaker::UpstreamCircuitBreaker;
 use crate::config::{BalanceStrategy, UpstreamRef};
+use crate::discovery::Endpoint;
 use crate::error::AppError;
 use async_trait::async_trait;
 use std::any::Any;
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tracing::debug;
 
@@ -20,16 +24,44 @@ pub struct ManagedUpstream {
     pub upstream_ref: Arc<UpstreamRef>,
     /// 熔断器（如果启用）
     pub breaker: Option<Arc<UpstreamCircuitBreaker>>,
+    /// 是否处于排空状态（同一上游在所有组中共享），排空时不再被选中
+    pub drained: Arc<AtomicBool>,
+    /// 自适应并发限制器（同一上游在所有组中共享），在途请求达到上限时不再被选中
+    pub adaptive: Option<Arc<AdaptiveLimiter>>,
+    /// 服务发现得到的上游地址，为 None 时使用上游 URL 中的地址
+    pub endpoint: Option<Arc<Endpoint>>,
 }
 
+impl ManagedUpstream {
+    /// 在负载均衡器中区分托管上游的键，服务发现展开的上游为“名称@地址”
+    pub fn key(&self) -> Cow<'_, str> {
+        match &self.endpoint {
+            Some(endpoint) => Cow::Owned(format!("{}@{}", self.upstream_ref.name, endpoint)),
+            None => Cow::Borrowed(&self.upstream_ref.name),
+        }
+    }
+}
+
+// 上游过滤条件
+pub type UpstreamFilter<'a> = dyn Fn(&ManagedUpstream) -> bool + Send + Sync + 'a;
+
 // 负载均衡器特性
+//
+// 上游列表以不可变快照保存，选择上游时无锁读取快照并返回共享的托管上游，不复制上游
 #[async_trait]
 pub trait LoadBalancer: Send + Sync {
     // 选择一个上游服务器
-    async fn select_upstream(&self) -> Result<ManagedUpstream, AppError>;
+    async fn select_upstream(&self) -> Result<Arc<ManagedUpstream>, AppError>;
 
-    // 更新上游服务器列表
-    async fn update_upstreams(&self, upstreams: Vec<ManagedUpstream>);
+    // 选择第一个满足条件的健康上游（用于会话粘滞），没有满足条件的上游时返回 None
+    async fn select_upstream_by(&self, filter: &UpstreamFilter<'_>)
+        -> Option<Arc<ManagedUpstream>>;
+
+    // 更新上游服务器列表，以新的快照替换当前快照
+    async fn update_upstreams(&self, upstreams: Vec<Arc<ManagedUpstream>>);
+
+    // 获取上游服务器列表（不含权重副本），用于查询运行状态
+    fn upstreams(&self) -> Vec<Arc<ManagedUpstream>>;
 
     // 报告服务器失败
     async fn report_failure(&self, upstream: &ManagedUpstream);
@@ -44,6 +76,15 @@ pub trait LoadBalancer: Send + Sync {
 // 上游健康检查帮助函数
 #[inline(always)]
 pub fn is_upstream_healthy(managed_upstream: &ManagedUpstream) -> bool {
+    // 上游已禁用（维护/排空），不再接收新请求
+    if managed_upstream.drained.load(Ordering::Relaxed) {
+        debug!(
+            "Skipping upstream: {} (drained)",
+            managed_upstream.upstream_ref.name
+        );
+        return false;
+    }
+
     // 如果没有熔断器，则认为上游健康
     if let Some(breaker) = &managed_upstream.breaker {
         if !breaker.is_call_permitted() {
@@ -56,10 +97,35 @@ pub fn is_upstream_healthy(managed_upstream: &ManagedUpstream) -> bool {
         }
     }
 
+    // 在途请求已达到自适应并发上限
+    if let Some(adaptive) = &managed_upstream.adaptive {
+        if adaptive.is_saturated() {
+            debug!(
+                "Skipping upstream: {} (adaptive concurrency limit {} reached)",
+                managed_upstream.upstream_ref.name,
+                adaptive.limit()
+            );
+            return false;
+        }
+    }
+
     // 默认健康
     true
 }
 
+// 查找第一个满足条件的健康上游及其索引
+#[inline(always)]
+pub fn find_healthy_upstream(
+    upstreams: &[Arc<ManagedUpstream>],
+    filter: &UpstreamFilter<'_>,
+) -> Option<(usize, Arc<ManagedUpstream>)> {
+    upstreams
+        .iter()
+        .enumerate()
+        .find(|(_, upstream)| filter(upstream) && is_upstream_healthy(upstream))
+        .map(|(index, upstream)| (index, upstream.clone()))
+}
+
 // 创建负载均衡器
 pub fn create_load_balancer(
     strategy: &BalanceStrategy,
@@ -1,11 +1,14 @@
-use crate::balancer::{is_upstream_healthy, LoadBalancer, ManagedUpstream};
+use crate::balancer::{
+    find_healthy_upstream, is_upstream_healthy, LoadBalancer, ManagedUpstream, UpstreamFilter,
+};
 use crate::error::AppError;
 use crate::r#const::balance_strategy_labels;
+use arc_swap::ArcSwap;
 use async_trait::async_trait;
 use std::any::Any;
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
 use tracing::debug;
 
 // 响应时间感知负载均衡器的固定参数
@@ -15,14 +18,52 @@ const INCLUDE_SUCCESS_RATE: bool = true; // 在得分计算中包含成功率
 
 // 响应时间感知负载均衡器
 pub struct ResponseAwareBalancer {
-    // 服务器列表
-    upstreams: Arc<RwLock<Vec<ManagedUpstream>>>,
+    // 上游列表和指标的快照，更新时整体替换
+    snapshot: ArcSwap<Snapshot>,
     // 当前索引（原子操作）
     current: AtomicUsize,
-    // 节点指标
-    metrics: Arc<RwLock<Vec<UpstreamMetrics>>>,
+}
+
+// 上游列表快照，上游、指标和映射总是一起替换，保持索引一致
+struct Snapshot {
+    // 服务器列表
+    upstreams: Vec<Arc<ManagedUpstream>>,
+    // 节点指标，与服务器列表一一对应
+    metrics: Vec<Arc<UpstreamMetrics>>,
     // 名称到索引的映射
-    name_to_index: Arc<RwLock<HashMap<String, usize>>>,
+    name_to_index: HashMap<String, usize>,
+}
+
+impl Snapshot {
+    // 创建快照，仍在列表中的上游沿用旧快照的指标
+    fn new(upstreams: Vec<Arc<ManagedUpstream>>, previous: Option<&Snapshot>) -> Self {
+        let mut metrics = Vec::with_capacity(upstreams.len());
+        let mut name_to_index = HashMap::with_capacity(upstreams.len());
+
+        for (i, u) in upstreams.iter().enumerate() {
+            let key = u.key().into_owned();
+            // 保留仍在列表中的上游的指标（如服务发现更新地址时），其余从初始值开始
+            let upstream_metrics = previous
+                .and_then(|previous| previous.metrics_of(&key))
+                .cloned()
+                .unwrap_or_default();
+            metrics.push(upstream_metrics);
+            name_to_index.insert(key, i);
+        }
+
+        Self {
+            upstreams,
+            metrics,
+            name_to_index,
+        }
+    }
+
+    // 按上游键查找指标
+    fn metrics_of(&self, key: &str) -> Option<&Arc<UpstreamMetrics>> {
+        self.name_to_index
+            .get(key)
+            .and_then(|index| self.metrics.get(*index))
+    }
 }
 
 struct UpstreamMetrics {
@@ -32,94 +73,137 @@ struct UpstreamMetrics {
     pending_requests: AtomicUsize,
     // 成功率 (0-1000, 表示 0-100.0%)
     success_rate: AtomicUsize,
+    // 是否已记录过响应时间，未记录时平均响应时间为初始估计值
+    observed: AtomicBool,
+}
+
+impl Default for UpstreamMetrics {
+    fn default() -> Self {
+        Self {
+            response_time: AtomicUsize::new(INITIAL_RESPONSE_TIME),
+            pending_requests: AtomicUsize::new(0),
+            success_rate: AtomicUsize::new(1000), // 初始 100% 成功率
+            observed: AtomicBool::new(false),
+        }
+    }
+}
+
+impl UpstreamMetrics {
+    // 按指数平滑更新平均响应时间，返回更新后的值
+    fn update_response_time(&self, response_time_ms: usize) -> usize {
+        self.observed.store(true, Ordering::Relaxed);
+        let old_time = self.response_time.load(Ordering::Relaxed);
+        let new_time = ((1.0 - SMOOTH_FACTOR as f64) * old_time as f64
+            + SMOOTH_FACTOR as f64 * response_time_ms as f64) as usize;
+
+        self.response_time.store(new_time, Ordering::Relaxed);
+        new_time
+    }
+
+    // 更新成功率
+    fn update_success_rate(&self, success: bool) {
+        let old_rate = self.success_rate.load(Ordering::Relaxed);
+        let success_value = if success { 1000 } else { 0 };
+        let new_rate = ((1.0 - SMOOTH_FACTOR as f64) * old_rate as f64
+            + SMOOTH_FACTOR as f64 * success_value as f64) as usize;
+
+        self.success_rate.store(new_rate, Ordering::Relaxed);
+    }
 }
 
 impl ResponseAwareBalancer {
     // 创建新的响应时间感知负载均衡器
     pub fn new(upstreams: Vec<ManagedUpstream>) -> Self {
-        let metrics = (0..upstreams.len())
-            .map(|_| UpstreamMetrics {
-                response_time: AtomicUsize::new(INITIAL_RESPONSE_TIME),
-                pending_requests: AtomicUsize::new(0),
-                success_rate: AtomicUsize::new(1000), // 初始 100% 成功率
-            })
-            .collect();
-
-        let name_to_index = upstreams
-            .iter()
-            .enumerate()
-            .map(|(i, u)| (u.upstream_ref.name.clone(), i))
-            .collect();
+        let upstreams = upstreams.into_iter().map(Arc::new).collect();
 
         Self {
-            upstreams: Arc::new(RwLock::new(upstreams)),
+            snapshot: ArcSwap::from_pointee(Snapshot::new(upstreams, None)),
             current: AtomicUsize::new(0),
-            metrics: Arc::new(RwLock::new(metrics)),
-            name_to_index: Arc::new(RwLock::new(name_to_index)),
         }
     }
 
-    // 查找上游索引
-    fn find_upstream_index(&self, upstream: &ManagedUpstream) -> Option<usize> {
-        let name_to_index = self.name_to_index.read().unwrap();
-        name_to_index.get(&upstream.upstream_ref.name).copied()
+    // 查找上游的指标，上游已被移除时返回 None
+    fn find_metrics(&self, upstream: &ManagedUpstream) -> Option<Arc<UpstreamMetrics>> {
+        self.snapshot
+            .load()
+            .metrics_of(upstream.key().as_ref())
+            .cloned()
     }
 
     // 更新响应时间和减少待处理请求
     pub fn update_metrics(&self, upstream: &ManagedUpstream, response_time_ms: usize) {
-        if let Some(index) = self.find_upstream_index(upstream) {
-            let metrics = self.metrics.read().unwrap();
-            if index < metrics.len() {
-                // 更新响应时间
-                let old_time = metrics[index].response_time.load(Ordering::Relaxed);
-                let new_time = ((1.0 - SMOOTH_FACTOR as f64) * old_time as f64
-                    + SMOOTH_FACTOR as f64 * response_time_ms as f64)
-                    as usize;
-
-                metrics[index]
-                    .response_time
-                    .store(new_time, Ordering::Relaxed);
+        if let Some(metrics) = self.find_metrics(upstream) {
+            // 更新响应时间
+            let new_time = metrics.update_response_time(response_time_ms);
 
-                // 减少待处理请求计数
-                metrics[index]
-                    .pending_requests
-                    .fetch_sub(1, Ordering::SeqCst);
-
-                // 更新成功率 (成功)
-                if INCLUDE_SUCCESS_RATE {
-                    self.update_success_rate(index, true);
-                }
+            // 减少待处理请求计数
+            metrics.pending_requests.fetch_sub(1, Ordering::SeqCst);
 
-                debug!(
-                    "Updated metrics for {:?}: response_time={}ms, pending={}",
-                    upstream.upstream_ref.name,
-                    new_time,
-                    metrics[index].pending_requests.load(Ordering::Relaxed)
-                );
+            // 更新成功率 (成功)
+            if INCLUDE_SUCCESS_RATE {
+                metrics.update_success_rate(true);
             }
+
+            debug!(
+                "Updated metrics for {:?}: response_time={}ms, pending={}",
+                upstream.upstream_ref.name,
+                new_time,
+                metrics.pending_requests.load(Ordering::Relaxed)
+            );
         }
     }
 
-    // 更新成功率
-    fn update_success_rate(&self, index: usize, success: bool) {
-        let metrics = self.metrics.read().unwrap();
-        if index < metrics.len() {
-            let old_rate = metrics[index].success_rate.load(Ordering::Relaxed);
-            let success_value = if success { 1000 } else { 0 };
-            let new_rate = ((1.0 - SMOOTH_FACTOR as f64) * old_rate as f64
-                + SMOOTH_FACTOR as f64 * success_value as f64) as usize;
-
-            metrics[index]
-                .success_rate
-                .store(new_rate, Ordering::Relaxed);
+    // 请求在收到上游响应前被取消（如客户端断开连接），只减少待处理请求计数
+    pub fn cancel_request(&self, upstream: &ManagedUpstream) {
+        if let Some(metrics) = self.find_metrics(upstream) {
+            metrics.pending_requests.fetch_sub(1, Ordering::SeqCst);
         }
     }
+
+    // 记录未经负载均衡器选择的请求（如预热请求）的结果，不改变待处理请求计数
+    // 尚未记录过响应时间时直接使用该请求的耗时，请求失败时 response_time_ms 为 None，只更新成功率
+    pub fn record_unselected(&self, upstream: &ManagedUpstream, response_time_ms: Option<usize>) {
+        let Some(metrics) = self.find_metrics(upstream) else {
+            return;
+        };
+        match response_time_ms {
+            // 第一次记录时直接替换初始估计值
+            Some(response_time_ms) if !metrics.observed.swap(true, Ordering::Relaxed) => {
+                metrics
+                    .response_time
+                    .store(response_time_ms, Ordering::Relaxed);
+            }
+            Some(response_time_ms) => {
+                metrics.update_response_time(response_time_ms);
+            }
+            None => {}
+        }
+        if INCLUDE_SUCCESS_RATE {
+            metrics.update_success_rate(response_time_ms.is_some());
+        }
+    }
+
+    // 上游的平均响应时间（毫秒）
+    pub fn response_time(&self, upstream: &ManagedUpstream) -> usize {
+        self.find_metrics(upstream)
+            .map(|metrics| metrics.response_time.load(Ordering::Relaxed))
+            .unwrap_or_default()
+    }
+
+    // 上游的待处理请求数
+    pub fn pending_requests(&self, upstream: &ManagedUpstream) -> usize {
+        self.find_metrics(upstream)
+            .map(|metrics| metrics.pending_requests.load(Ordering::Relaxed))
+            .unwrap_or_default()
+    }
 }
 
 #[async_trait]
 impl LoadBalancer for ResponseAwareBalancer {
-    async fn select_upstream(&self) -> Result<ManagedUpstream, AppError> {
-        let upstreams = self.upstreams.read().unwrap();
+    async fn select_upstream(&self) -> Result<Arc<ManagedUpstream>, AppError> {
+        let snapshot = self.snapshot.load();
+        let upstreams = &snapshot.upstreams;
+        let metrics = &snapshot.metrics;
         let len = upstreams.len();
         if len == 0 {
             return Err(AppError::NoUpstreamAvailable);
@@ -129,10 +213,7 @@ impl LoadBalancer for ResponseAwareBalancer {
         if len == 1 {
             return if is_upstream_healthy(&upstreams[0]) {
                 // 增加待处理请求计数
-                let metrics = self.metrics.read().unwrap();
-                if !metrics.is_empty() {
-                    metrics[0].pending_requests.fetch_add(1, Ordering::SeqCst);
-                }
+                metrics[0].pending_requests.fetch_add(1, Ordering::SeqCst);
                 Ok(upstreams[0].clone())
             } else {
                 Err(AppError::NoHealthyUpstreamAvailable)
@@ -147,8 +228,6 @@ impl LoadBalancer for ResponseAwareBalancer {
         // 从当前索引开始，确保公平性
         let start_index = self.current.fetch_add(1, Ordering::SeqCst) % len;
 
-        let metrics = self.metrics.read().unwrap();
-
         // 遍历所有上游，找到健康的最佳节点
         for i in 0..len {
             let index = (start_index + i) % len;
@@ -157,26 +236,24 @@ impl LoadBalancer for ResponseAwareBalancer {
             if is_upstream_healthy(managed_upstream) {
                 found_healthy = true;
 
-                if index < metrics.len() {
-                    let resp_time = metrics[index].response_time.load(Ordering::Relaxed) as f64;
-                    let pending = metrics[index].pending_requests.load(Ordering::Relaxed) as f64;
+                let resp_time = metrics[index].response_time.load(Ordering::Relaxed) as f64;
+                let pending = metrics[index].pending_requests.load(Ordering::Relaxed) as f64;
 
-                    // 计算得分
-                    let mut score = resp_time * (pending + 1.0);
+                // 计算得分
+                let mut score = resp_time * (pending + 1.0);
 
-                    // 考虑成功率
-                    if INCLUDE_SUCCESS_RATE {
-                        let success_rate =
-                            metrics[index].success_rate.load(Ordering::Relaxed) as f64 / 1000.0;
-                        if success_rate > 0.0 {
-                            score *= 1.0 / success_rate;
-                        }
+                // 考虑成功率
+                if INCLUDE_SUCCESS_RATE {
+                    let success_rate =
+                        metrics[index].success_rate.load(Ordering::Relaxed) as f64 / 1000.0;
+                    if success_rate > 0.0 {
+                        score *= 1.0 / success_rate;
                     }
+                }
 
-                    if score < best_score {
-                        best_score = score;
-                        best_index = index;
-                    }
+                if score < best_score {
+                    best_score = score;
+                    best_index = index;
                 }
             }
         }
@@ -187,11 +264,9 @@ impl LoadBalancer for ResponseAwareBalancer {
         }
 
         // 增加选中节点的待处理请求计数
-        if best_index < metrics.len() {
-            metrics[best_index]
-                .pending_requests
-                .fetch_add(1, Ordering::SeqCst);
-        }
+        metrics[best_index]
+            .pending_requests
+            .fetch_add(1, Ordering::SeqCst);
 
         debug!(
             "ResponseAwareBalancer selected upstream: {:?}, score: {:.2}",
@@ -201,20 +276,30 @@ impl LoadBalancer for ResponseAwareBalancer {
         Ok(upstreams[best_index].clone())
     }
 
+    async fn select_upstream_by(
+        &self,
+        filter: &UpstreamFilter<'_>,
+    ) -> Option<Arc<ManagedUpstream>> {
+        let snapshot = self.snapshot.load();
+        let (index, upstream) = find_healthy_upstream(&snapshot.upstreams, filter)?;
+
+        // 增加选中节点的待处理请求计数，与 select_upstream 保持一致
+        snapshot.metrics[index]
+            .pending_requests
+            .fetch_add(1, Ordering::SeqCst);
+
+        Some(upstream)
+    }
+
     async fn report_failure(&self, upstream: &ManagedUpstream) {
         // 处理失败情况，可选择更新成功率
         if INCLUDE_SUCCESS_RATE {
-            if let Some(index) = self.find_upstream_index(upstream) {
+            if let Some(metrics) = self.find_metrics(upstream) {
                 // 更新成功率 (失败)
-                self.update_success_rate(index, false);
+                metrics.update_success_rate(false);
 
                 // 减少待处理请求计数
-                let metrics = self.metrics.read().unwrap();
-                if index < metrics.len() {
-                    metrics[index]
-                        .pending_requests
-                        .fetch_sub(1, Ordering::SeqCst);
-                }
+                metrics.pending_requests.fetch_sub(1, Ordering::SeqCst);
             }
         }
     }
@@ -227,38 +312,15 @@ impl LoadBalancer for ResponseAwareBalancer {
         balance_strategy_labels::RESPONSE_AWARE
     }
 
-    async fn update_upstreams(&self, upstreams: Vec<ManagedUpstream>) {
-        // 提前计算capacity以减少内存再分配
-        let upstreams_len = upstreams.len();
-
-        // 创建新的指标和映射，使用with_capacity预分配内存
-        let mut new_metrics = Vec::with_capacity(upstreams_len);
-        for _ in 0..upstreams_len {
-            new_metrics.push(UpstreamMetrics {
-                response_time: AtomicUsize::new(INITIAL_RESPONSE_TIME),
-                pending_requests: AtomicUsize::new(0),
-                success_rate: AtomicUsize::new(1000), // 初始 100% 成功率
-            });
-        }
-
-        // 预分配HashMap容量，避免rehash
-        let mut new_name_to_index = HashMap::with_capacity(upstreams_len);
-
-        // 填充新的名称到索引映射
-        for (i, u) in upstreams.iter().enumerate() {
-            new_name_to_index.insert(u.upstream_ref.name.clone(), i);
-        }
-
-        // 更新所有状态
-        {
-            let mut write_guard_upstreams = self.upstreams.write().unwrap();
-            let mut write_guard_metrics = self.metrics.write().unwrap();
-            let mut write_guard_mapping = self.name_to_index.write().unwrap();
+    fn upstreams(&self) -> Vec<Arc<ManagedUpstream>> {
+        self.snapshot.load().upstreams.clone()
+    }
 
-            *write_guard_upstreams = upstreams;
-            *write_guard_metrics = new_metrics;
-            *write_guard_mapping = new_name_to_index;
-        }
+    async fn update_upstreams(&self, upstreams: Vec<Arc<ManagedUpstream>>) {
+        // 基于当前快照创建新快照并替换，仍在列表中的上游共享同一份指标，
+        // 正在进行的请求完成时更新的指标对新快照同样可见
+        self.snapshot
+            .rcu(|current| Snapshot::new(upstreams.clone(), Some(current)));
 
         debug!("ResponseAwareBalancer upstreams and metrics updated successfully");
     }
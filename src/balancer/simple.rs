@@ -1,17 +1,28 @@
-use crate::balancer::{is_upstream_healthy, LoadBalancer, ManagedUpstream};
+use crate::balancer::{
+    find_healthy_upstream, is_upstream_healthy, LoadBalancer, ManagedUpstream, UpstreamFilter,
+};
 use crate::error::AppError;
 use crate::r#const::balance_strategy_labels;
+use arc_swap::ArcSwap;
 use async_trait::async_trait;
 use rand::{seq::SliceRandom, thread_rng};
 use std::any::Any;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
 use tracing::debug;
 
+// 上游列表快照
+type UpstreamList = ArcSwap<Vec<Arc<ManagedUpstream>>>;
+
+// 创建上游列表快照
+fn upstream_list(upstreams: Vec<ManagedUpstream>) -> UpstreamList {
+    ArcSwap::from_pointee(upstreams.into_iter().map(Arc::new).collect())
+}
+
 // 轮询负载均衡器
 pub struct RoundRobinBalancer {
     // 服务器列表
-    upstreams: Arc<RwLock<Vec<ManagedUpstream>>>,
+    upstreams: UpstreamList,
     // 当前索引（原子操作）
     current: AtomicUsize,
 }
@@ -20,7 +31,7 @@ impl RoundRobinBalancer {
     // 创建新的轮询负载均衡器
     pub fn new(upstreams: Vec<ManagedUpstream>) -> Self {
         Self {
-            upstreams: Arc::new(RwLock::new(upstreams)),
+            upstreams: upstream_list(upstreams),
             current: AtomicUsize::new(0),
         }
     }
@@ -28,8 +39,8 @@ impl RoundRobinBalancer {
 
 #[async_trait]
 impl LoadBalancer for RoundRobinBalancer {
-    async fn select_upstream(&self) -> Result<ManagedUpstream, AppError> {
-        let upstreams = self.upstreams.read().unwrap();
+    async fn select_upstream(&self) -> Result<Arc<ManagedUpstream>, AppError> {
+        let upstreams = self.upstreams.load();
         let len = upstreams.len();
         if len == 0 {
             return Err(AppError::NoUpstreamAvailable);
@@ -65,6 +76,14 @@ impl LoadBalancer for RoundRobinBalancer {
         Err(AppError::NoHealthyUpstreamAvailable)
     }
 
+    async fn select_upstream_by(
+        &self,
+        filter: &UpstreamFilter<'_>,
+    ) -> Option<Arc<ManagedUpstream>> {
+        let upstreams = self.upstreams.load();
+        find_healthy_upstream(&upstreams, filter).map(|(_, upstream)| upstream)
+    }
+
     async fn report_failure(&self, _upstream: &ManagedUpstream) {
         // 轮询策略下不需要特殊处理失败
     }
@@ -77,11 +96,13 @@ impl LoadBalancer for RoundRobinBalancer {
         balance_strategy_labels::ROUND_ROBIN
     }
 
-    async fn update_upstreams(&self, upstreams: Vec<ManagedUpstream>) {
-        // 替换upstreams向量
-        let mut write_guard = self.upstreams.write().unwrap();
-        *write_guard = upstreams;
-        // 写锁在这里被自动释放，确保不会阻塞读取
+    fn upstreams(&self) -> Vec<Arc<ManagedUpstream>> {
+        self.upstreams.load().to_vec()
+    }
+
+    async fn update_upstreams(&self, upstreams: Vec<Arc<ManagedUpstream>>) {
+        // 替换上游列表快照，正在进行的选择继续使用旧快照
+        self.upstreams.store(Arc::new(upstreams));
         debug!("RoundRobinBalancer upstreams updated successfully");
     }
 }
@@ -89,7 +110,7 @@ impl LoadBalancer for RoundRobinBalancer {
 // 加权轮询负载均衡器
 pub struct WeightedRoundRobinBalancer {
     // 服务器列表，按权重复制
-    upstreams: Arc<RwLock<Vec<ManagedUpstream>>>,
+    upstreams: UpstreamList,
     // 当前索引（原子操作）
     current: AtomicUsize,
 }
@@ -97,34 +118,17 @@ pub struct WeightedRoundRobinBalancer {
 impl WeightedRoundRobinBalancer {
     // 创建新的加权轮询负载均衡器
     pub fn new(upstreams: Vec<ManagedUpstream>) -> Self {
-        // 预先计算所需的容量以避免重新分配
-        let total_capacity = upstreams
-            .iter()
-            .map(|u| u.upstream_ref.weight as usize)
-            .sum();
-
-        // 根据权重复制服务器
-        let mut weighted_upstreams = Vec::with_capacity(total_capacity);
-
-        for upstream in upstreams {
-            // 对于每个服务器，按其权重添加多个副本
-            let weight = upstream.upstream_ref.weight;
-            weighted_upstreams.push(upstream.clone());
-
-            // 从第二个开始添加剩余的副本
-            for _ in 1..weight {
-                weighted_upstreams.push(upstream.clone());
-            }
-        }
+        let weighted_upstreams =
+            Self::create_weighted_copies(upstreams.into_iter().map(Arc::new).collect());
 
         Self {
-            upstreams: Arc::new(RwLock::new(weighted_upstreams)),
+            upstreams: ArcSwap::from_pointee(weighted_upstreams),
             current: AtomicUsize::new(0),
         }
     }
 
-    // 根据上游列表创建权重副本
-    fn create_weighted_copies(upstreams: Vec<ManagedUpstream>) -> Vec<ManagedUpstream> {
+    // 根据上游列表创建权重副本，副本共享同一个托管上游
+    fn create_weighted_copies(upstreams: Vec<Arc<ManagedUpstream>>) -> Vec<Arc<ManagedUpstream>> {
         // 预先计算所需的容量，避免重新分配
         // 这里使用fold避免中间Vec分配
         let total_capacity = upstreams
@@ -136,15 +140,10 @@ impl WeightedRoundRobinBalancer {
 
         for upstream in upstreams {
             // 对于每个服务器，按其权重添加多个副本
-            let weight = upstream.upstream_ref.weight;
-
-            // 第一个副本是原始的，不需要克隆
-            weighted_upstreams.push(upstream.clone());
-
-            // 从第二个开始添加剩余的副本
-            for _ in 1..weight {
+            for _ in 1..upstream.upstream_ref.weight {
                 weighted_upstreams.push(upstream.clone());
             }
+            weighted_upstreams.push(upstream);
         }
 
         weighted_upstreams
@@ -153,8 +152,8 @@ impl WeightedRoundRobinBalancer {
 
 #[async_trait]
 impl LoadBalancer for WeightedRoundRobinBalancer {
-    async fn select_upstream(&self) -> Result<ManagedUpstream, AppError> {
-        let upstreams = self.upstreams.read().unwrap();
+    async fn select_upstream(&self) -> Result<Arc<ManagedUpstream>, AppError> {
+        let upstreams = self.upstreams.load();
         let len = upstreams.len();
         if len == 0 {
             return Err(AppError::NoUpstreamAvailable);
@@ -190,6 +189,14 @@ impl LoadBalancer for WeightedRoundRobinBalancer {
         Err(AppError::NoHealthyUpstreamAvailable)
     }
 
+    async fn select_upstream_by(
+        &self,
+        filter: &UpstreamFilter<'_>,
+    ) -> Option<Arc<ManagedUpstream>> {
+        let upstreams = self.upstreams.load();
+        find_healthy_upstream(&upstreams, filter).map(|(_, upstream)| upstream)
+    }
+
     async fn report_failure(&self, _upstream: &ManagedUpstream) {
         // 加权轮询策略下不需要特殊处理失败
     }
@@ -202,14 +209,17 @@ impl LoadBalancer for WeightedRoundRobinBalancer {
         balance_strategy_labels::WEIGHTED_ROUND_ROBIN
     }
 
-    async fn update_upstreams(&self, upstreams: Vec<ManagedUpstream>) {
-        // 创建加权副本
-        let weighted_upstreams = Self::create_weighted_copies(upstreams);
+    fn upstreams(&self) -> Vec<Arc<ManagedUpstream>> {
+        let mut upstreams = self.upstreams.load().to_vec();
+        // 同一上游的权重副本是连续的，去重后只保留一个
+        upstreams.dedup_by(|a, b| Arc::ptr_eq(a, b));
+        upstreams
+    }
 
-        // 替换upstreams向量
-        let mut write_guard = self.upstreams.write().unwrap();
-        *write_guard = weighted_upstreams;
-        // 写锁在这里被自动释放，确保不会阻塞读取
+    async fn update_upstreams(&self, upstreams: Vec<Arc<ManagedUpstream>>) {
+        // 创建加权副本，替换上游列表快照
+        self.upstreams
+            .store(Arc::new(Self::create_weighted_copies(upstreams)));
         debug!("WeightedRoundRobinBalancer upstreams updated successfully");
     }
 }
@@ -217,22 +227,22 @@ impl LoadBalancer for WeightedRoundRobinBalancer {
 // 随机负载均衡器
 pub struct RandomBalancer {
     // 服务器列表
-    upstreams: Arc<RwLock<Vec<ManagedUpstream>>>,
+    upstreams: UpstreamList,
 }
 
 impl RandomBalancer {
     // 创建新的随机负载均衡器
     pub fn new(upstreams: Vec<ManagedUpstream>) -> Self {
         Self {
-            upstreams: Arc::new(RwLock::new(upstreams)),
+            upstreams: upstream_list(upstreams),
         }
     }
 }
 
 #[async_trait]
 impl LoadBalancer for RandomBalancer {
-    async fn select_upstream(&self) -> Result<ManagedUpstream, AppError> {
-        let upstreams = self.upstreams.read().unwrap();
+    async fn select_upstream(&self) -> Result<Arc<ManagedUpstream>, AppError> {
+        let upstreams = self.upstreams.load();
         if upstreams.is_empty() {
             return Err(AppError::NoUpstreamAvailable);
         }
@@ -262,7 +272,7 @@ impl LoadBalancer for RandomBalancer {
         }
 
         // 如果随机选择失败，创建健康上游列表
-        let healthy_upstreams: Vec<&ManagedUpstream> = upstreams
+        let healthy_upstreams: Vec<&Arc<ManagedUpstream>> = upstreams
             .iter()
             .filter(|upstream| is_upstream_healthy(upstream))
             .collect();
@@ -286,6 +296,14 @@ impl LoadBalancer for RandomBalancer {
         Ok((*upstream).clone())
     }
 
+    async fn select_upstream_by(
+        &self,
+        filter: &UpstreamFilter<'_>,
+    ) -> Option<Arc<ManagedUpstream>> {
+        let upstreams = self.upstreams.load();
+        find_healthy_upstream(&upstreams, filter).map(|(_, upstream)| upstream)
+    }
+
     async fn report_failure(&self, _upstream: &ManagedUpstream) {
         // 随机策略下不需要特殊处理失败
     }
@@ -298,11 +316,13 @@ impl LoadBalancer for RandomBalancer {
         crate::r#const::balance_strategy_labels::RANDOM
     }
 
-    async fn update_upstreams(&self, upstreams: Vec<ManagedUpstream>) {
-        // 替换upstreams向量
-        let mut write_guard = self.upstreams.write().unwrap();
-        *write_guard = upstreams;
-        // 写锁在这里被自动释放，确保不会阻塞读取
+    fn upstreams(&self) -> Vec<Arc<ManagedUpstream>> {
+        self.upstreams.load().to_vec()
+    }
+
+    async fn update_upstreams(&self, upstreams: Vec<Arc<ManagedUpstream>>) {
+        // 替换上游列表快照，正在进行的选择继续使用旧快照
+        self.upstreams.store(Arc::new(upstreams));
         debug!("RandomBalancer upstreams updated successfully");
     }
 }
@@ -310,22 +330,22 @@ impl LoadBalancer for RandomBalancer {
 // 故障转移负载均衡器
 pub struct FailoverBalancer {
     // 服务器列表（按优先级顺序排列）
-    upstreams: Arc<RwLock<Vec<ManagedUpstream>>>,
+    upstreams: UpstreamList,
 }
 
 impl FailoverBalancer {
     // 创建新的故障转移负载均衡器
     pub fn new(upstreams: Vec<ManagedUpstream>) -> Self {
         Self {
-            upstreams: Arc::new(RwLock::new(upstreams)),
+            upstreams: upstream_list(upstreams),
         }
     }
 }
 
 #[async_trait]
 impl LoadBalancer for FailoverBalancer {
-    async fn select_upstream(&self) -> Result<ManagedUpstream, AppError> {
-        let upstreams = self.upstreams.read().unwrap();
+    async fn select_upstream(&self) -> Result<Arc<ManagedUpstream>, AppError> {
+        let upstreams = self.upstreams.load();
         if upstreams.is_empty() {
             return Err(AppError::NoUpstreamAvailable);
         }
@@ -346,6 +366,14 @@ impl LoadBalancer for FailoverBalancer {
         Err(AppError::NoHealthyUpstreamAvailable)
     }
 
+    async fn select_upstream_by(
+        &self,
+        filter: &UpstreamFilter<'_>,
+    ) -> Option<Arc<ManagedUpstream>> {
+        let upstreams = self.upstreams.load();
+        find_healthy_upstream(&upstreams, filter).map(|(_, upstream)| upstream)
+    }
+
     async fn report_failure(&self, _upstream: &ManagedUpstream) {
         // 故障转移策略下不需要特殊处理失败
     }
@@ -358,11 +386,13 @@ impl LoadBalancer for FailoverBalancer {
         balance_strategy_labels::FAILOVER
     }
 
-    async fn update_upstreams(&self, upstreams: Vec<ManagedUpstream>) {
-        // 替换upstreams向量
-        let mut write_guard = self.upstreams.write().unwrap();
-        *write_guard = upstreams;
-        // 写锁在这里被自动释放，确保不会阻塞读取
+    fn upstreams(&self) -> Vec<Arc<ManagedUpstream>> {
+        self.upstreams.load().to_vec()
+    }
+
+    async fn update_upstreams(&self, upstreams: Vec<Arc<ManagedUpstream>>) {
+        // 替换上游列表快照，正在进行的选择继续使用旧快照
+        self.upstreams.store(Arc::new(upstreams));
         debug!("FailoverBalancer upstreams updated successfully");
     }
 }
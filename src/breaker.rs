@@ -1,11 +1,24 @@
 use crate::{
-    config::BreakerConfig,
+    config::{BreakerConfig, BreakerWindowType},
     error::AppError,
+    events::{unix_millis, SystemEvent, EVENTS},
     metrics::METRICS,
-    r#const::{breaker_result_labels, breaker_state_labels},
+    r#const::{breaker_limits, breaker_result_labels, breaker_state_labels},
+};
+use circuitbreaker_rs::{
+    BreakerBuilder, BreakerError, CircuitBreaker, DefaultPolicy, HookRegistry, State,
+};
+use parking_lot::Mutex;
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
-use circuitbreaker_rs::{BreakerBuilder, CircuitBreaker, DefaultPolicy, HookRegistry, State};
-use std::{error::Error, fmt, sync::Arc, time::Duration};
 use tracing::{debug, info, warn};
 
 /// 表示上游服务的错误
@@ -34,60 +47,239 @@ struct HookData {
     group: String,
 }
 
+// 按请求数或时间统计的失败率窗口
+struct RequestWindow {
+    // 最近请求的完成时间和结果，true 表示失败
+    outcomes: VecDeque<(Instant, bool)>,
+    // 窗口内的失败次数
+    failures: usize,
+    // 按请求数统计时的窗口大小（请求数）
+    size: usize,
+    // 按时间统计时的窗口时长
+    span: Option<Duration>,
+    // 按失败率熔断所需的最小请求数
+    min_requests: usize,
+}
+
+impl RequestWindow {
+    // 记录一次请求结果，返回窗口内的失败率，请求数不足时返回 None
+    fn record(&mut self, failed: bool) -> Option<f64> {
+        let now = Instant::now();
+        while let Some(&(at, outcome)) = self.outcomes.front() {
+            let expired = match self.span {
+                Some(span) => now.duration_since(at) > span,
+                None => self.outcomes.len() >= self.size,
+            };
+            if !expired {
+                break;
+            }
+            self.outcomes.pop_front();
+            if outcome {
+                self.failures -= 1;
+            }
+        }
+        self.outcomes.push_back((now, failed));
+        if failed {
+            self.failures += 1;
+        }
+        (self.outcomes.len() >= self.min_requests)
+            .then(|| self.failures as f64 / self.outcomes.len() as f64)
+    }
+
+    fn clear(&mut self) {
+        self.outcomes.clear();
+        self.failures = 0;
+    }
+}
+
 /// 上游服务熔断器
 pub struct UpstreamCircuitBreaker {
     breaker: CircuitBreaker<DefaultPolicy, UpstreamError>,
-    #[allow(dead_code)]
     name: String,
-    #[allow(dead_code)]
     group: String,
+    // 连接阶段（DNS、TCP、TLS）连续失败次数
+    connect_failures: AtomicU32,
+    // 连接阶段连续失败阈值，达到后立即熔断
+    connect_failure_threshold: u32,
+    // 失败率阈值
+    threshold: f64,
+    // 配置的失败率窗口，未配置时由熔断器库统计
+    request_window: Option<Mutex<RequestWindow>>,
+    // 冷却时间
+    cooldown: Duration,
+    // 最近一次熔断的时间
+    opened_at: Arc<Mutex<Instant>>,
+}
+
+impl fmt::Debug for UpstreamCircuitBreaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UpstreamCircuitBreaker")
+            .field("name", &self.name)
+            .field("group", &self.group)
+            .field("state", &self.current_state())
+            .finish()
+    }
 }
 
 impl UpstreamCircuitBreaker {
     /// 创建一个新的熔断器
     pub fn new(name: String, group: String, threshold: f64, cooldown: u64) -> Arc<Self> {
+        Self::with_connect_failures(
+            name,
+            group,
+            threshold,
+            cooldown,
+            breaker_limits::DEFAULT_CONNECT_FAILURES,
+        )
+    }
+
+    /// 创建一个新的熔断器，并指定连接阶段连续失败阈值
+    pub fn with_connect_failures(
+        name: String,
+        group: String,
+        threshold: f64,
+        cooldown: u64,
+        connect_failures: u32,
+    ) -> Arc<Self> {
+        Self::from_config(
+            name,
+            group,
+            &BreakerConfig {
+                threshold,
+                cooldown,
+                connect_failures,
+                ..BreakerConfig::default()
+            },
+        )
+    }
+
+    /// 按熔断器配置创建熔断器
+    pub fn from_config(name: String, group: String, config: &BreakerConfig) -> Arc<Self> {
         // 创建事件钩子
-        let hooks = Self::create_hooks(&name, &group);
+        let opened_at = Arc::new(Mutex::new(Instant::now()));
+        let hooks = Self::create_hooks(&name, &group, opened_at.clone());
 
         // 创建熔断器
-        let breaker = BreakerBuilder::<DefaultPolicy, UpstreamError>::default()
-            .failure_threshold(threshold)
-            .cooldown(Duration::from_secs(cooldown))
-            .hooks(hooks)
-            .build();
+        let mut builder = BreakerBuilder::<DefaultPolicy, UpstreamError>::default()
+            .failure_threshold(config.threshold)
+            .cooldown(Duration::from_secs(config.cooldown))
+            .hooks(hooks);
+
+        let request_window = match &config.window {
+            Some(window) => {
+                // 失败率由配置的窗口判断，不再按熔断器库的统计熔断
+                builder = builder.min_throughput(u64::MAX);
+                let (size, span, min_requests) = match window.r#type {
+                    BreakerWindowType::Count => {
+                        let size = window.size as usize;
+                        (size, None, config.min_requests.map_or(size, |n| n as usize))
+                    }
+                    BreakerWindowType::Time => (
+                        usize::MAX,
+                        Some(Duration::from_secs(window.size)),
+                        config
+                            .min_requests
+                            .map_or(breaker_limits::DEFAULT_TIME_WINDOW_MIN_REQUESTS, |n| {
+                                n as usize
+                            }),
+                    ),
+                };
+                Some(Mutex::new(RequestWindow {
+                    outcomes: VecDeque::new(),
+                    failures: 0,
+                    size,
+                    span,
+                    min_requests,
+                }))
+            }
+            None => {
+                if let Some(min_requests) = config.min_requests {
+                    builder = builder.min_throughput(min_requests);
+                }
+                None
+            }
+        };
+        if let Some(probes) = config.half_open_probes {
+            // 全部探测请求成功后关闭，需要的成功次数不能超过允许的探测请求数，否则无法关闭
+            builder = builder
+                .probe_interval(probes)
+                .consecutive_successes(u64::from(probes));
+        }
 
         debug!(
-            "Created circuit breaker for upstream '{}' in group '{}' with threshold={}, cooldown={}s",
-            name, group, threshold, cooldown
+            "Created circuit breaker for upstream '{}' in group '{}' with threshold={}, cooldown={}s, connect_failures={}, window={:?}, min_requests={:?}, half_open_probes={:?}",
+            name,
+            group,
+            config.threshold,
+            config.cooldown,
+            config.connect_failures,
+            config.window,
+            config.min_requests,
+            config.half_open_probes
         );
 
         Arc::new(Self {
-            breaker,
+            breaker: builder.build(),
             name,
             group,
+            cooldown: Duration::from_secs(config.cooldown),
+            opened_at,
+            connect_failures: AtomicU32::new(0),
+            connect_failure_threshold: config.connect_failures,
+            threshold: config.threshold,
+            request_window,
         })
     }
 
     /// 使用熔断器执行异步操作
-    pub async fn call_async<F, Fut, T>(
-        &self,
-        f: F,
-    ) -> Result<T, circuitbreaker_rs::BreakerError<UpstreamError>>
+    pub async fn call_async<F, Fut, T>(&self, f: F) -> Result<T, BreakerError<UpstreamError>>
     where
         F: FnOnce() -> Fut,
         Fut: std::future::Future<Output = Result<T, UpstreamError>>,
     {
-        // 直接使用库提供的 call_async 方法
-        self.breaker.call_async(f).await
+        let result = self.breaker.call_async(f).await;
+        if let Some(window) = &self.request_window {
+            match &result {
+                Ok(_) => self.record_window(window, false),
+                Err(BreakerError::Operation(_)) => self.record_window(window, true),
+                // 熔断器拒绝的调用没有发送到上游
+                Err(_) => {}
+            }
+        }
+        result
+    }
+
+    // 在失败率窗口中记录一次调用结果，失败率达到阈值时熔断
+    fn record_window(&self, window: &Mutex<RequestWindow>, failed: bool) {
+        let mut window = window.lock();
+        // 半开状态的探测请求由熔断器库处理，熔断器关闭后重新开始统计
+        if self.breaker.current_state() != State::Closed {
+            window.clear();
+            return;
+        }
+        let Some(failure_rate) = window.record(failed) else {
+            return;
+        };
+        if failure_rate >= self.threshold {
+            let requests = window.outcomes.len();
+            window.clear();
+            warn!(
+                "Circuit breaker forced open for upstream '{}' in group '{}' after failure rate {:.2} over the last {} requests",
+                self.name, self.group, failure_rate, requests
+            );
+            self.breaker.force_open();
+        }
     }
 
     /// 检查熔断器当前是否允许调用
+    ///
+    /// 熔断后冷却时间已过时允许调用，熔断器库在该调用时转入半开状态
     #[inline(always)]
     pub fn is_call_permitted(&self) -> bool {
-        matches!(
-            self.breaker.current_state(),
-            State::Closed | State::HalfOpen
-        )
+        match self.breaker.current_state() {
+            State::Closed | State::HalfOpen => true,
+            State::Open => self.opened_at.lock().elapsed() >= self.cooldown,
+        }
     }
 
     /// 获取熔断器当前状态
@@ -95,8 +287,60 @@ impl UpstreamCircuitBreaker {
         self.breaker.current_state()
     }
 
+    /// 熔断器名称（上游名称）
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 熔断器所属的上游组
+    pub fn group(&self) -> &str {
+        &self.group
+    }
+
+    /// 记录一次连接阶段（DNS、TCP、TLS）失败
+    ///
+    /// 连接失败通常意味着上游完全不可用，连续失败达到阈值时立即熔断，
+    /// 而不必等待失败率统计达到 `threshold`
+    pub fn record_connect_failure(&self) {
+        METRICS
+            .circuitbreaker_calls_total()
+            .with_label_values(&[
+                &self.group,
+                &self.name,
+                breaker_result_labels::CONNECT_FAILURE,
+            ])
+            .inc();
+
+        let failures = self.connect_failures.fetch_add(1, Ordering::SeqCst) + 1;
+        if failures < self.connect_failure_threshold {
+            return;
+        }
+
+        // 重置计数，熔断器进入半开状态后重新开始统计
+        self.connect_failures.store(0, Ordering::SeqCst);
+
+        if self.breaker.current_state() != State::Open {
+            warn!(
+                "Circuit breaker forced open for upstream '{}' in group '{}' after {} consecutive connect failures",
+                self.name, self.group, failures
+            );
+            self.breaker.force_open();
+        }
+    }
+
+    /// 记录一次成功建立连接，重置连接阶段连续失败次数
+    #[inline(always)]
+    pub fn record_connect_success(&self) {
+        self.connect_failures.store(0, Ordering::Relaxed);
+    }
+
+    /// 获取当前连接阶段连续失败次数
+    pub fn connect_failures(&self) -> u32 {
+        self.connect_failures.load(Ordering::Relaxed)
+    }
+
     /// 创建熔断器事件钩子
-    fn create_hooks(name: &str, group: &str) -> HookRegistry {
+    fn create_hooks(name: &str, group: &str, opened_at: Arc<Mutex<Instant>>) -> HookRegistry {
         // 只克隆一次字符串
         let data = HookData {
             name: name.to_owned(),
@@ -108,6 +352,8 @@ impl UpstreamCircuitBreaker {
         // 状态转换钩子 - 开启
         let data_open = data.clone();
         hooks.set_on_open(move || {
+            *opened_at.lock() = Instant::now();
+
             // 记录状态变化指标：从关闭到开启
             METRICS
                 .circuitbreaker_state_changes_total()
@@ -119,6 +365,12 @@ impl UpstreamCircuitBreaker {
                 ])
                 .inc();
 
+            publish_state_change(
+                &data_open,
+                breaker_state_labels::CLOSED,
+                breaker_state_labels::OPEN,
+            );
+
             warn!(
                 "Circuit breaker opened for upstream '{}' in group '{}'",
                 data_open.name, data_open.group
@@ -140,6 +392,12 @@ impl UpstreamCircuitBreaker {
                 ])
                 .inc();
 
+            publish_state_change(
+                &data_close,
+                breaker_state_labels::HALF_OPEN,
+                breaker_state_labels::CLOSED,
+            );
+
             info!(
                 "Circuit breaker closed for upstream '{}' in group '{}'",
                 data_close.name, data_close.group
@@ -160,6 +418,12 @@ impl UpstreamCircuitBreaker {
                 ])
                 .inc();
 
+            publish_state_change(
+                &data_half,
+                breaker_state_labels::OPEN,
+                breaker_state_labels::HALF_OPEN,
+            );
+
             info!(
                 "Circuit breaker half-opened for upstream '{}' in group '{}'",
                 data_half.name, data_half.group
@@ -196,11 +460,22 @@ impl UpstreamCircuitBreaker {
     }
 }
 
+/// 发布熔断器状态变化事件
+fn publish_state_change(data: &HookData, from: &str, to: &str) {
+    EVENTS.publish_system(SystemEvent::BreakerStateChanged {
+        timestamp: unix_millis(),
+        group: data.group.clone(),
+        upstream: data.name.clone(),
+        from: from.to_string(),
+        to: to.to_string(),
+    });
+}
+
 /// 创建上游服务熔断器
 pub fn create_upstream_circuit_breaker(
     name: String,
     group: String,
     config: &BreakerConfig,
 ) -> Arc<UpstreamCircuitBreaker> {
-    UpstreamCircuitBreaker::new(name, group, config.threshold, config.cooldown)
+    UpstreamCircuitBreaker::from_config(name, group, config)
 }
@@ -0,0 +1,76 @@
+use super::CacheStore;
+use crate::error::AppError;
+use bytes::Bytes;
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// 进程内存缓存
+///
+/// 条目数达到上限时先清理已过期的条目，仍然没有空间时淘汰最早过期的条目
+pub struct MemoryStore {
+    // 最大条目数
+    max_entries: usize,
+    // 缓存键到缓存值和过期时间的映射
+    entries: Mutex<HashMap<String, (Bytes, Instant)>>,
+}
+
+impl MemoryStore {
+    /// 创建内存缓存
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            max_entries,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 当前条目数（包括尚未清理的过期条目）
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    /// 是否没有条目
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().is_empty()
+    }
+}
+
+#[async_trait::async_trait]
+impl CacheStore for MemoryStore {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Bytes>, AppError> {
+        let mut entries = self.entries.lock();
+        match entries.get(key) {
+            Some((value, expires)) if *expires > Instant::now() => Ok(Some(value.clone())),
+            Some(_) => {
+                entries.remove(key);
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn set(&self, key: &str, value: Bytes, ttl: Duration) -> Result<(), AppError> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock();
+        if !entries.contains_key(key) && entries.len() >= self.max_entries {
+            entries.retain(|_, (_, expires)| *expires > now);
+            if entries.len() >= self.max_entries {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, (_, expires))| *expires)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(key.to_string(), (value, now + ttl));
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+//! 响应缓存存储
+//!
+//! 存储后端通过 [`CacheStore`] 抽象：进程内存只在当前实例内有效，Redis 在多个实例之间共享并且在重启后保留。
+
+mod memory;
+mod redis_store;
+
+pub use memory::MemoryStore;
+pub use redis_store::RedisStore;
+
+use crate::{
+    config::{CacheBackend, CacheConfig},
+    error::AppError,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+
+/// 缓存存储后端
+#[async_trait::async_trait]
+pub trait CacheStore: Send + Sync {
+    /// 后端名称
+    fn name(&self) -> &'static str;
+
+    /// 读取缓存值，不存在或已过期时返回 None
+    async fn get(&self, key: &str) -> Result<Option<Bytes>, AppError>;
+
+    /// 写入缓存值，超过有效期后失效
+    async fn set(&self, key: &str, value: Bytes, ttl: Duration) -> Result<(), AppError>;
+}
+
+/// 按缓存配置创建存储后端
+pub fn create_store(config: &CacheConfig) -> Result<Arc<dyn CacheStore>, AppError> {
+    match (config.backend, &config.redis) {
+        (CacheBackend::Memory, _) => Ok(Arc::new(MemoryStore::new(config.max_entries))),
+        (CacheBackend::Redis, Some(redis)) => Ok(Arc::new(RedisStore::new(redis)?)),
+        (CacheBackend::Redis, None) => Err(AppError::Config(
+            "Redis cache backend requires redis configuration".to_string(),
+        )),
+    }
+}
+
+/// 缓存的响应
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedResponse {
+    /// 状态码
+    pub status: u16,
+    /// 响应头
+    pub headers: Vec<(String, String)>,
+    /// 响应体
+    pub body: Bytes,
+}
+
+// 缓存响应的存储格式，响应体使用 base64 编码
+#[derive(Serialize, Deserialize)]
+struct EncodedResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl CachedResponse {
+    /// 编码为缓存值
+    pub fn encode(&self) -> Bytes {
+        let encoded = EncodedResponse {
+            status: self.status,
+            headers: self.headers.clone(),
+            body: STANDARD.encode(&self.body),
+        };
+        serde_json::to_vec(&encoded)
+            .map(Bytes::from)
+            .unwrap_or_default()
+    }
+
+    /// 解码缓存值，格式无效时返回 None
+    pub fn decode(value: &[u8]) -> Option<Self> {
+        let encoded = serde_json::from_slice::<EncodedResponse>(value).ok()?;
+        Some(Self {
+            status: encoded.status,
+            headers: encoded.headers,
+            body: STANDARD.decode(encoded.body).ok()?.into(),
+        })
+    }
+}
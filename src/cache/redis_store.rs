@@ -0,0 +1,55 @@
+use super::CacheStore;
+use crate::{config::RedisConfig, error::AppError, redis_client::RedisClient};
+use bytes::Bytes;
+use redis::AsyncCommands;
+use std::time::Duration;
+
+// 缓存键的前缀，与其他功能的键区分
+const KEY_PREFIX: &str = "cache:";
+
+/// Redis 缓存
+///
+/// 首次使用时建立连接，连接断开后自动重连。连接失败后在重试间隔内直接返回错误，
+/// 避免 Redis 不可用时每个请求都等待连接超时
+pub struct RedisStore {
+    // Redis 客户端
+    client: RedisClient,
+}
+
+impl RedisStore {
+    /// 创建 Redis 缓存，只解析连接地址，不建立连接
+    pub fn new(config: &RedisConfig) -> Result<Self, AppError> {
+        Ok(Self {
+            client: RedisClient::new(config)?,
+        })
+    }
+
+    // 缓存值在 Redis 中的键
+    fn key(&self, key: &str) -> String {
+        self.client.key(&format!("{}{}", KEY_PREFIX, key))
+    }
+}
+
+#[async_trait::async_trait]
+impl CacheStore for RedisStore {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Bytes>, AppError> {
+        let mut connection = self.client.connection().await?;
+        let value: Option<Vec<u8>> = connection
+            .get(self.key(key))
+            .await
+            .map_err(|e| AppError::Cache(format!("Redis GET failed: {}", e)))?;
+        Ok(value.map(Bytes::from))
+    }
+
+    async fn set(&self, key: &str, value: Bytes, ttl: Duration) -> Result<(), AppError> {
+        let mut connection = self.client.connection().await?;
+        connection
+            .set_ex::<_, _, ()>(self.key(key), value.as_ref(), ttl.as_secs().max(1))
+            .await
+            .map_err(|e| AppError::Cache(format!("Redis SET failed: {}", e)))
+    }
+}
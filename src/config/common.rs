@@ -1,13 +1,18 @@
 use crate::{
     config::{
         defaults::{
-            default_burst, default_circuitbreaker_cooldown, default_circuitbreaker_threshold,
-            default_connect_timeout, default_per_second, default_retry_attempts,
-            default_retry_initial,
+            default_adaptive_backoff, default_adaptive_initial, default_adaptive_latency_tolerance,
+            default_adaptive_max, default_adaptive_min, default_burst,
+            default_circuitbreaker_connect_failures, default_circuitbreaker_cooldown,
+            default_circuitbreaker_threshold, default_connect_timeout, default_per_second,
+            default_redis_key_prefix, default_request_timeout, default_retry_attempts,
+            default_retry_initial, default_retry_on_status,
         },
         validation,
     },
-    r#const::{breaker_limits, http_client_limits, rate_limit_limits, retry_limits},
+    r#const::{
+        adaptive_limits, breaker_limits, http_client_limits, rate_limit_limits, retry_limits,
+    },
 };
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
@@ -17,25 +22,79 @@ use validator::Validate;
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
 #[serde(rename_all = "lowercase")]
 pub struct TimeoutConfig {
-    // 连接超时（秒）
+    // 连接超时（秒），客户端建立连接后在该时间内没有发送任何数据时关闭连接
     #[serde(default = "default_connect_timeout")]
     #[validate(range(
         min = "http_client_limits::MIN_CONNECT_TIMEOUT",
         max = "http_client_limits::MAX_CONNECT_TIMEOUT"
     ))]
     pub connect: u64,
+    // 请求超时（秒），从收到请求到开始发送响应的最长时间。非流式响应包含读取完整上游响应体的时间，
+    // 流式响应开始发送后不再受该超时限制
+    #[serde(default = "default_request_timeout")]
+    #[validate(range(
+        min = "http_client_limits::MIN_REQUEST_TIMEOUT",
+        max = "http_client_limits::MAX_REQUEST_TIMEOUT"
+    ))]
+    pub request: u64,
+    // 响应头超时（秒），等待上游返回响应头的最长时间（包括重试），未设置时不单独限制
+    #[serde(default)]
+    #[validate(range(
+        min = "http_client_limits::MIN_REQUEST_TIMEOUT",
+        max = "http_client_limits::MAX_REQUEST_TIMEOUT"
+    ))]
+    pub header: Option<u64>,
 }
 
 impl Default for TimeoutConfig {
     fn default() -> Self {
         Self {
             connect: default_connect_timeout(),
+            request: default_request_timeout(),
+            header: None,
         }
     }
 }
 
+// 限流键类型
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitKey {
+    // 按客户端 IP 限流
+    #[default]
+    Ip,
+    // 按指定请求头的值限流
+    Header,
+    // 按 API 密钥（Authorization Bearer 令牌、x-api-key 或 api-key 请求头）限流
+    ApiKey,
+}
+
+// Redis 连接配置
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_redis_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct RedisConfig {
+    // Redis 地址，如 redis://127.0.0.1:6379/0，使用 rediss:// 开启 TLS
+    pub url: String,
+    // Redis 密码，未配置时使用地址中的密码
+    #[serde(default)]
+    pub password: Option<String>,
+    // 键前缀，多个应用共用一个 Redis 时避免键冲突
+    #[serde(default = "default_redis_key_prefix")]
+    pub key_prefix: String,
+}
+
 // 限流配置
+// 每个限流键拥有独立的令牌桶，未携带请求头或 API 密钥的请求按客户端 IP 限流。
+// 配置了 redis 时令牌桶保存在 Redis 中，多个实例共享限额
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_rate_limit_config",
+    skip_on_field_errors = false
+))]
 #[serde(rename_all = "lowercase")]
 pub struct RateLimitConfig {
     // 每秒请求数
@@ -52,6 +111,16 @@ pub struct RateLimitConfig {
         max = "rate_limit_limits::MAX_BURST"
     ))]
     pub burst: u32,
+    // 限流键类型
+    #[serde(default)]
+    pub key: RateLimitKey,
+    // 限流键为 header 时使用的请求头名称
+    #[serde(default)]
+    pub header: Option<String>,
+    // Redis 连接配置，未配置时只在当前实例内限流
+    #[serde(default)]
+    #[validate(nested)]
+    pub redis: Option<RedisConfig>,
 }
 
 impl Default for RateLimitConfig {
@@ -59,6 +128,9 @@ impl Default for RateLimitConfig {
         Self {
             per_second: default_per_second(),
             burst: default_burst(),
+            key: RateLimitKey::default(),
+            header: None,
+            redis: None,
         }
     }
 }
@@ -66,6 +138,10 @@ impl Default for RateLimitConfig {
 // 重试配置
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
 #[serde(rename_all = "lowercase")]
+#[validate(schema(
+    function = "validation::validate_retry_config",
+    skip_on_field_errors = false
+))]
 pub struct RetryConfig {
     // 最大重试次数
     #[serde(default = "default_retry_attempts")]
@@ -78,6 +154,19 @@ pub struct RetryConfig {
         max = "retry_limits::MAX_INITIAL_MS"
     ))]
     pub initial: u32,
+    // 重试时间预算（毫秒），首次请求与所有重试的总耗时不超过该值，未设置时不限制
+    #[serde(default)]
+    #[validate(range(
+        min = "retry_limits::MIN_MAX_ELAPSED_MS",
+        max = "retry_limits::MAX_MAX_ELAPSED_MS"
+    ))]
+    pub max_elapsed_ms: Option<u64>,
+    // 需要重试的上游响应状态码，连接错误对所有请求重试，超时和状态码只对幂等请求重试
+    #[serde(default = "default_retry_on_status")]
+    pub retry_on_status: Vec<u16>,
+    // 是否在超时和状态码时重试非幂等请求（如 POST），上游可能已经处理了失败的请求，重试会重复计费
+    #[serde(default)]
+    pub retry_non_idempotent: bool,
 }
 
 impl Default for RetryConfig {
@@ -85,22 +174,61 @@ impl Default for RetryConfig {
         Self {
             attempts: default_retry_attempts(),
             initial: default_retry_initial(),
+            max_elapsed_ms: None,
+            retry_on_status: default_retry_on_status(),
+            retry_non_idempotent: false,
         }
     }
 }
 
 // 代理配置
 #[derive(Debug, Clone, Serialize, Deserialize, Default, ToSchema, Validate)]
-#[validate(schema(function = "validation::validate_proxy_config"))]
+#[validate(schema(
+    function = "validation::validate_proxy_config",
+    skip_on_field_errors = false
+))]
 #[serde(rename_all = "lowercase")]
 pub struct ProxyConfig {
     // 代理URL
     #[serde(default)]
     pub url: String,
+    // 不使用代理的目标（域名、IP 或 CIDR）
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+}
+
+// 熔断器统计窗口类型
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BreakerWindowType {
+    // 统计最近 size 秒内的请求
+    #[default]
+    Time,
+    // 统计最近 size 个请求
+    Count,
+}
+
+// 熔断器统计窗口配置
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_breaker_window_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct BreakerWindowConfig {
+    // 窗口类型
+    #[serde(default)]
+    pub r#type: BreakerWindowType,
+    // 窗口大小，按时间统计时为秒数，按请求数统计时为请求数
+    pub size: u64,
 }
 
 // 熔断器配置
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_breaker_config",
+    skip_on_field_errors = false
+))]
 #[serde(rename_all = "lowercase")]
 pub struct BreakerConfig {
     // 触发熔断的失败率阈值 (0.01-1.0, 例如0.5表示50%的调用失败)
@@ -117,6 +245,31 @@ pub struct BreakerConfig {
         max = "breaker_limits::MAX_COOLDOWN"
     ))]
     pub cooldown: u64,
+    // 连接阶段（DNS、TCP、TLS）连续失败达到该次数时立即熔断
+    #[serde(default = "default_circuitbreaker_connect_failures")]
+    #[validate(range(
+        min = "breaker_limits::MIN_CONNECT_FAILURES",
+        max = "breaker_limits::MAX_CONNECT_FAILURES"
+    ))]
+    pub connect_failures: u32,
+    // 统计失败率的滑动窗口，未配置时使用熔断器的默认时间窗口
+    #[serde(default)]
+    #[validate(nested)]
+    pub window: Option<BreakerWindowConfig>,
+    // 窗口内的请求数达到该值后才按失败率熔断，避免低流量上游因少量失败被熔断
+    #[serde(default)]
+    #[validate(range(
+        min = "breaker_limits::MIN_MIN_REQUESTS",
+        max = "breaker_limits::MAX_MIN_REQUESTS"
+    ))]
+    pub min_requests: Option<u64>,
+    // 半开状态下允许通过的探测请求数
+    #[serde(default)]
+    #[validate(range(
+        min = "breaker_limits::MIN_HALF_OPEN_PROBES",
+        max = "breaker_limits::MAX_HALF_OPEN_PROBES"
+    ))]
+    pub half_open_probes: Option<u32>,
 }
 
 impl Default for BreakerConfig {
@@ -124,6 +277,60 @@ impl Default for BreakerConfig {
         Self {
             threshold: default_circuitbreaker_threshold(),
             cooldown: default_circuitbreaker_cooldown(),
+            connect_failures: default_circuitbreaker_connect_failures(),
+            window: None,
+            min_requests: None,
+            half_open_probes: None,
+        }
+    }
+}
+
+// 自适应并发配置
+// 按上游的延迟和过载响应（429、5xx、请求失败）调整允许的并发请求数（AIMD），
+// 在途请求达到上限的上游暂时不参与负载均衡选择
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_adaptive_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct AdaptiveConfig {
+    // 初始并发上限
+    #[serde(default = "default_adaptive_initial")]
+    #[validate(range(min = "adaptive_limits::MIN_LIMIT", max = "adaptive_limits::MAX_LIMIT"))]
+    pub initial: u32,
+    // 并发上限的最小值
+    #[serde(default = "default_adaptive_min")]
+    #[validate(range(min = "adaptive_limits::MIN_LIMIT", max = "adaptive_limits::MAX_LIMIT"))]
+    pub min: u32,
+    // 并发上限的最大值
+    #[serde(default = "default_adaptive_max")]
+    #[validate(range(min = "adaptive_limits::MIN_LIMIT", max = "adaptive_limits::MAX_LIMIT"))]
+    pub max: u32,
+    // 延迟容忍倍数，请求延迟超过基线延迟（观测到的最低延迟）的该倍数时收缩并发上限
+    #[serde(default = "default_adaptive_latency_tolerance")]
+    #[validate(range(
+        min = "adaptive_limits::MIN_LATENCY_TOLERANCE",
+        max = "adaptive_limits::MAX_LATENCY_TOLERANCE"
+    ))]
+    pub latency_tolerance: f64,
+    // 过载时并发上限的收缩系数
+    #[serde(default = "default_adaptive_backoff")]
+    #[validate(range(
+        min = "adaptive_limits::MIN_BACKOFF",
+        max = "adaptive_limits::MAX_BACKOFF"
+    ))]
+    pub backoff: f64,
+}
+
+impl Default for AdaptiveConfig {
+    fn default() -> Self {
+        Self {
+            initial: default_adaptive_initial(),
+            min: default_adaptive_min(),
+            max: default_adaptive_max(),
+            latency_tolerance: default_adaptive_latency_tolerance(),
+            backoff: default_adaptive_backoff(),
         }
     }
 }
@@ -1,5 +1,10 @@
+use crate::config::CompressionAlgorithm;
 use crate::r#const::{
-    breaker_limits, http_client_limits, rate_limit_limits, retry_limits, weight_limits,
+    access_log, adaptive_limits, admin_paths, alert_limits, audit_limits, audit_sink,
+    breaker_limits, budget, cache_limits, compression, concurrency_limits, consul_discovery,
+    discovery_limits, external_auth, http_client_limits, listener_limits, load_shedding,
+    metrics_export, oauth2, plugin, policy, rate_limit_limits, redis_limits, response_buffer,
+    retry_limits, sticky_limits, warmup_limits, websocket, weight_limits,
 };
 
 // 熔断器默认阈值
@@ -12,6 +17,11 @@ pub fn default_circuitbreaker_cooldown() -> u64 {
     breaker_limits::DEFAULT_COOLDOWN
 }
 
+// 熔断器默认连接阶段连续失败阈值
+pub fn default_circuitbreaker_connect_failures() -> u32 {
+    breaker_limits::DEFAULT_CONNECT_FAILURES
+}
+
 // 默认值函数
 pub fn default_listen_address() -> String {
     "0.0.0.0".to_string()
@@ -21,6 +31,16 @@ pub fn default_admin_port() -> u16 {
     9000
 }
 
+// 默认的 Prometheus 指标路径
+pub fn default_metrics_path() -> String {
+    admin_paths::DEFAULT_METRICS.to_string()
+}
+
+// 管理面板默认开启
+pub fn default_admin_dashboard() -> bool {
+    true
+}
+
 pub fn default_listen_port() -> u16 {
     3000
 }
@@ -41,6 +61,14 @@ pub fn default_keepalive() -> u32 {
     http_client_limits::DEFAULT_KEEPALIVE
 }
 
+pub fn default_http2_keepalive_interval() -> u64 {
+    http_client_limits::DEFAULT_HTTP2_KEEPALIVE_INTERVAL
+}
+
+pub fn default_http2_keepalive_timeout() -> u64 {
+    http_client_limits::DEFAULT_HTTP2_KEEPALIVE_TIMEOUT
+}
+
 pub fn default_user_agent() -> String {
     "LLMProxy/1.0".to_string()
 }
@@ -53,6 +81,10 @@ pub fn default_retry_initial() -> u32 {
     retry_limits::DEFAULT_INITIAL_MS
 }
 
+pub fn default_retry_on_status() -> Vec<u16> {
+    retry_limits::DEFAULT_RETRY_ON_STATUS.to_vec()
+}
+
 pub fn default_weight() -> u32 {
     weight_limits::MIN_WEIGHT
 }
@@ -68,3 +100,233 @@ pub fn default_burst() -> u32 {
 pub fn default_stream_mode() -> bool {
     true
 }
+
+// 上游服务默认启用
+pub fn default_upstream_enabled() -> bool {
+    true
+}
+
+// 上游服务默认使用上游组配置的代理
+pub fn default_upstream_proxy() -> bool {
+    true
+}
+
+// 会话粘滞默认有效期（秒）
+pub fn default_sticky_ttl() -> u64 {
+    sticky_limits::DEFAULT_TTL
+}
+
+// 审计日志默认内存保留数量
+pub fn default_audit_max_entries() -> usize {
+    audit_limits::DEFAULT_MAX_ENTRIES
+}
+
+// OAuth2 访问令牌默认提前刷新时间（秒）
+pub fn default_oauth2_refresh_before() -> u64 {
+    oauth2::DEFAULT_REFRESH_BEFORE
+}
+
+// 外部命令认证默认重新执行间隔（秒）
+pub fn default_external_auth_refresh_interval() -> u64 {
+    external_auth::DEFAULT_REFRESH_INTERVAL
+}
+
+// 外部命令认证默认执行超时时间（秒）
+pub fn default_external_auth_timeout() -> u64 {
+    external_auth::DEFAULT_TIMEOUT
+}
+
+// 预热请求默认方法
+pub fn default_warmup_method() -> String {
+    warmup_limits::DEFAULT_METHOD.to_string()
+}
+
+// 默认每个上游地址发送的预热请求数
+pub fn default_warmup_requests() -> u32 {
+    warmup_limits::DEFAULT_REQUESTS
+}
+
+// 预热请求默认超时时间（秒）
+pub fn default_warmup_timeout() -> u64 {
+    warmup_limits::DEFAULT_TIMEOUT
+}
+
+// 客户端预算默认使用 Authorization 请求头标识客户端
+pub fn default_budget_header() -> String {
+    budget::DEFAULT_HEADER.to_string()
+}
+
+// 等待队列默认长度
+pub fn default_queue_max_depth() -> u32 {
+    concurrency_limits::DEFAULT_QUEUE_DEPTH
+}
+
+// 默认排队等待时间（毫秒）
+pub fn default_queue_max_wait_ms() -> u64 {
+    concurrency_limits::DEFAULT_QUEUE_WAIT_MS
+}
+
+// 公平调度默认权重
+pub fn default_queue_weight() -> u32 {
+    concurrency_limits::DEFAULT_WEIGHT
+}
+
+// 自适应并发默认初始上限
+pub fn default_adaptive_initial() -> u32 {
+    adaptive_limits::DEFAULT_INITIAL
+}
+
+// 自适应并发默认最小上限
+pub fn default_adaptive_min() -> u32 {
+    adaptive_limits::DEFAULT_MIN
+}
+
+// 自适应并发默认最大上限
+pub fn default_adaptive_max() -> u32 {
+    adaptive_limits::DEFAULT_MAX
+}
+
+// 自适应并发默认延迟容忍倍数
+pub fn default_adaptive_latency_tolerance() -> f64 {
+    adaptive_limits::DEFAULT_LATENCY_TOLERANCE
+}
+
+// 自适应并发默认收缩系数
+pub fn default_adaptive_backoff() -> f64 {
+    adaptive_limits::DEFAULT_BACKOFF
+}
+
+// 过载保护默认检查间隔（毫秒）
+pub fn default_load_shedding_interval_ms() -> u64 {
+    load_shedding::DEFAULT_INTERVAL_MS
+}
+
+// 过载保护默认的请求优先级头部
+pub fn default_priority_header() -> String {
+    load_shedding::DEFAULT_PRIORITY_HEADER.to_string()
+}
+
+// 响应缓存默认有效期（秒）
+pub fn default_cache_ttl() -> u64 {
+    cache_limits::DEFAULT_TTL
+}
+
+// 内存缓存默认最大条目数
+pub fn default_cache_max_entries() -> usize {
+    cache_limits::DEFAULT_ENTRIES
+}
+
+// 默认可缓存的最大响应体（字节）
+pub fn default_cache_max_body_bytes() -> usize {
+    cache_limits::DEFAULT_MAX_BODY_BYTES
+}
+
+// 默认 Redis 键前缀
+pub fn default_redis_key_prefix() -> String {
+    redis_limits::DEFAULT_KEY_PREFIX.to_string()
+}
+
+// 审计日志默认记录的最大请求体或响应体（字节）
+pub fn default_audit_sink_max_body_bytes() -> usize {
+    audit_sink::DEFAULT_MAX_BODY_BYTES
+}
+
+// 审计日志默认缓冲的记录数
+pub fn default_audit_sink_buffer() -> usize {
+    audit_sink::DEFAULT_BUFFER
+}
+
+// 策略服务默认请求超时（毫秒）
+pub fn default_policy_timeout_ms() -> u64 {
+    policy::DEFAULT_TIMEOUT_MS
+}
+
+// 插件默认每次调用的燃料
+pub fn default_plugin_fuel() -> u64 {
+    plugin::DEFAULT_FUEL
+}
+
+// 插件默认最大线性内存（字节）
+pub fn default_plugin_max_memory_bytes() -> usize {
+    plugin::DEFAULT_MAX_MEMORY_BYTES
+}
+
+// 默认监听队列长度
+pub fn default_listen_backlog() -> u32 {
+    listener_limits::DEFAULT_BACKLOG
+}
+
+// 默认 WebSocket 连接空闲超时（秒）
+pub fn default_websocket_idle_timeout() -> u64 {
+    websocket::DEFAULT_IDLE_TIMEOUT
+}
+
+// 默认启用的响应压缩算法
+pub fn default_compression_algorithms() -> Vec<CompressionAlgorithm> {
+    vec![CompressionAlgorithm::Gzip, CompressionAlgorithm::Br]
+}
+
+// 默认压缩的最小响应体大小（字节）
+pub fn default_compression_min_size() -> u16 {
+    compression::DEFAULT_MIN_SIZE
+}
+
+// 默认访问日志文件的最大大小（MB）
+pub fn default_access_log_max_size() -> u64 {
+    access_log::DEFAULT_MAX_SIZE
+}
+
+// 默认保留的访问日志轮转文件数
+pub fn default_access_log_max_files() -> u32 {
+    access_log::DEFAULT_MAX_FILES
+}
+
+// 默认缓冲的访问日志记录数
+pub fn default_access_log_buffer() -> usize {
+    access_log::DEFAULT_BUFFER
+}
+
+// 默认指标推送间隔（秒）
+pub fn default_metrics_export_interval() -> u64 {
+    metrics_export::DEFAULT_INTERVAL
+}
+
+// 默认告警评估间隔（秒）
+pub fn default_alert_interval() -> u64 {
+    alert_limits::DEFAULT_INTERVAL
+}
+
+// 默认告警统计窗口（秒）
+pub fn default_alert_window() -> u64 {
+    alert_limits::DEFAULT_WINDOW
+}
+
+// 默认告警耗时分位数
+pub fn default_alert_quantile() -> f64 {
+    alert_limits::DEFAULT_QUANTILE
+}
+
+// 默认告警窗口内的最少请求数
+pub fn default_alert_min_requests() -> u64 {
+    alert_limits::DEFAULT_MIN_REQUESTS
+}
+
+// 默认 DNS 服务发现的重新解析间隔（秒）
+pub fn default_dns_discovery_interval() -> u64 {
+    discovery_limits::DEFAULT_INTERVAL
+}
+
+// 默认 Consul 地址
+pub fn default_consul_address() -> String {
+    consul_discovery::DEFAULT_ADDRESS.to_string()
+}
+
+// 默认只使用健康检查全部通过的 Consul 服务实例
+pub fn default_consul_passing() -> bool {
+    true
+}
+
+// 默认的非流式响应体缓冲上限（字节）
+pub fn default_response_buffer_limit() -> u64 {
+    response_buffer::DEFAULT_LIMIT
+}
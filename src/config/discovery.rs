@@ -0,0 +1,120 @@
+use crate::{
+    config::{
+        defaults::{
+            default_consul_address, default_consul_passing, default_dns_discovery_interval,
+        },
+        validation,
+    },
+    r#const::discovery_limits,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+// DNS 记录类型
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DnsRecordType {
+    // 解析 URL 主机名的全部 A/AAAA 记录，使用 URL 中的端口
+    #[default]
+    A,
+    // 解析 URL 主机名的 SRV 记录，使用记录中的目标主机和端口
+    Srv,
+}
+
+// DNS 服务发现配置
+// 定期解析上游 URL 的主机名，每个地址作为一个独立的负载均衡目标，随解析结果自动增减
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "lowercase")]
+pub struct DnsDiscoveryConfig {
+    // 记录类型
+    #[serde(default)]
+    pub record: DnsRecordType,
+    // 重新解析间隔（秒）
+    #[serde(default = "default_dns_discovery_interval")]
+    #[validate(range(
+        min = "discovery_limits::MIN_INTERVAL",
+        max = "discovery_limits::MAX_INTERVAL"
+    ))]
+    pub interval: u64,
+}
+
+impl Default for DnsDiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            record: DnsRecordType::default(),
+            interval: default_dns_discovery_interval(),
+        }
+    }
+}
+
+// 上游组服务发现类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupDiscoveryType {
+    // 监听 Kubernetes 服务的 EndpointSlice
+    Kubernetes,
+    // 监听 Consul 中注册的服务实例
+    Consul,
+}
+
+// 上游组服务发现配置
+// 发现的每个地址都与组内的每个上游组合为一个负载均衡目标，上游 URL 提供协议、路径等其余部分
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_group_discovery",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct GroupDiscoveryConfig {
+    // 服务发现类型
+    pub r#type: GroupDiscoveryType,
+    // Kubernetes 服务发现配置（用于 kubernetes 类型）
+    #[serde(default)]
+    #[validate(nested)]
+    pub kubernetes: Option<KubernetesDiscoveryConfig>,
+    // Consul 服务发现配置（用于 consul 类型）
+    #[serde(default)]
+    #[validate(nested)]
+    pub consul: Option<ConsulDiscoveryConfig>,
+}
+
+// Kubernetes 服务发现配置
+// 通过集群内的服务账号访问 API Server，监听服务的 EndpointSlice，只使用就绪的地址
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "lowercase")]
+pub struct KubernetesDiscoveryConfig {
+    // 服务名称
+    #[validate(length(min = 1, message = "Kubernetes service name cannot be empty"))]
+    pub service: String,
+    // 命名空间，未设置时使用代理所在 Pod 的命名空间
+    #[serde(default)]
+    pub namespace: Option<String>,
+    // 地址使用的端口，未设置时使用 EndpointSlice 中的第一个端口
+    #[serde(default)]
+    #[validate(range(min = 1, message = "Kubernetes discovery port cannot be 0"))]
+    pub port: Option<u16>,
+}
+
+// Consul 服务发现配置
+// 以阻塞查询监听服务的健康实例，实例地址未设置时使用节点地址
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "lowercase")]
+pub struct ConsulDiscoveryConfig {
+    // 服务名称
+    #[validate(length(min = 1, message = "Consul service name cannot be empty"))]
+    pub service: String,
+    // Consul HTTP 地址，ACL 令牌通过 CONSUL_HTTP_TOKEN 环境变量设置
+    #[serde(default = "default_consul_address")]
+    #[validate(url(message = "Consul address must be a valid URL"))]
+    pub address: String,
+    // 数据中心，未设置时使用 Consul 代理所在的数据中心
+    #[serde(default)]
+    pub datacenter: Option<String>,
+    // 实例必须带有的全部标签
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // 是否只使用健康检查全部通过的实例
+    #[serde(default = "default_consul_passing")]
+    pub passing: bool,
+}
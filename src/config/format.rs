@@ -0,0 +1,60 @@
+use clap::ValueEnum;
+use serde::de::DeserializeOwned;
+use std::path::Path;
+
+use crate::error::AppError;
+
+/// 配置文件格式，三种格式使用相同的配置结构
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    /// YAML（默认）
+    Yaml,
+    /// TOML
+    Toml,
+    /// JSON
+    Json,
+}
+
+impl ConfigFormat {
+    /// 按文件扩展名识别格式，无法识别时按 YAML 解析
+    pub fn from_path(path: &Path) -> Self {
+        Self::from_extension(path).unwrap_or(Self::Yaml)
+    }
+
+    /// 按文件扩展名识别格式（`.yaml`/`.yml`、`.toml`、`.json`）
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "yaml" | "yml" => Some(Self::Yaml),
+            "toml" => Some(Self::Toml),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// 将配置文件的内容解析为指定的结构，path 用于错误消息
+    pub fn parse<T: DeserializeOwned>(self, path: &Path, content: &str) -> Result<T, AppError> {
+        match self {
+            Self::Yaml => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+            Self::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+            Self::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+        }
+        .map_err(|e| {
+            AppError::Config(format!(
+                "Configuration file parsing error in {:?} ({}): {}",
+                path,
+                self.as_str(),
+                e
+            ))
+        })
+    }
+
+    /// 格式名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Yaml => "yaml",
+            Self::Toml => "toml",
+            Self::Json => "json",
+        }
+    }
+}
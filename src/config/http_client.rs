@@ -2,12 +2,14 @@ use crate::{
     config::{
         common::{ProxyConfig, RetryConfig},
         defaults::{
-            default_connect_timeout, default_idle_timeout, default_keepalive,
+            default_connect_timeout, default_http2_keepalive_interval,
+            default_http2_keepalive_timeout, default_idle_timeout, default_keepalive,
             default_request_timeout,
         },
         validation,
     },
     r#const::http_client_limits,
+    redact,
 };
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
@@ -15,7 +17,10 @@ use validator::Validate;
 
 /// HTTP客户端配置
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
-#[validate(schema(function = "validation::validate_http_client_config"))]
+#[validate(schema(
+    function = "validation::validate_http_client_config",
+    skip_on_field_errors = false
+))]
 #[serde(rename_all = "lowercase")]
 pub struct HttpClientConfig {
     /// 超时配置
@@ -40,6 +45,42 @@ pub struct HttpClientConfig {
     /// 是否启用流式模式
     #[serde(default)]
     pub stream_mode: bool,
+    /// 与上游通信使用的 HTTP 协议版本
+    #[serde(default)]
+    pub http_version: HttpVersion,
+    /// HTTP/2 连接配置
+    #[serde(default)]
+    #[validate(nested)]
+    pub http2: Option<Http2Config>,
+    /// 上游 TLS 配置（客户端证书、信任的 CA、证书校验和最低版本）
+    #[serde(default)]
+    #[validate(nested)]
+    pub tls: Option<TlsConfig>,
+    /// 发送到上游的 User-Agent，替换客户端请求中的 User-Agent
+    #[serde(default)]
+    pub agent: Option<String>,
+}
+
+impl HttpClientConfig {
+    /// 返回私钥密码已脱敏的副本
+    pub fn redacted(&self) -> Self {
+        Self {
+            tls: self.tls.as_ref().map(|tls| TlsConfig {
+                passphrase: redact::mask(&tls.passphrase),
+                ..tls.clone()
+            }),
+            ..self.clone()
+        }
+    }
+
+    /// 请求中的私钥密码为脱敏占位值时，保留现有的值
+    pub fn restore_secrets(&mut self, existing: &HttpClientConfig) {
+        if let (Some(tls), Some(existing)) = (self.tls.as_mut(), existing.tls.as_ref()) {
+            if redact::is_masked(&tls.passphrase) {
+                tls.passphrase = existing.passphrase.clone();
+            }
+        }
+    }
 }
 
 impl Default for HttpClientConfig {
@@ -50,10 +91,99 @@ impl Default for HttpClientConfig {
             retry: None,
             proxy: None,
             stream_mode: false,
+            http_version: HttpVersion::default(),
+            http2: None,
+            tls: None,
+            agent: None,
         }
     }
 }
 
+/// HTTP 协议版本
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum HttpVersion {
+    /// 自动协商：HTTPS 上游通过 ALPN 协商 HTTP/2，否则使用 HTTP/1.1
+    #[default]
+    Auto,
+    /// 仅使用 HTTP/1.1
+    Http1,
+    /// 不经协商直接使用 HTTP/2（支持明文 h2c）
+    Http2PriorKnowledge,
+}
+
+/// HTTP/2 连接配置
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "lowercase")]
+pub struct Http2Config {
+    /// keepalive ping 间隔（秒）
+    #[serde(default = "default_http2_keepalive_interval")]
+    #[validate(range(
+        min = "http_client_limits::MIN_HTTP2_KEEPALIVE_INTERVAL",
+        max = "http_client_limits::MAX_HTTP2_KEEPALIVE_INTERVAL"
+    ))]
+    pub keepalive_interval: u64,
+    /// 等待 ping 响应的超时时间（秒），超时后关闭连接
+    #[serde(default = "default_http2_keepalive_timeout")]
+    #[validate(range(
+        min = "http_client_limits::MIN_HTTP2_KEEPALIVE_TIMEOUT",
+        max = "http_client_limits::MAX_HTTP2_KEEPALIVE_TIMEOUT"
+    ))]
+    pub keepalive_timeout: u64,
+    /// 连接上没有进行中的请求时是否也发送 ping
+    #[serde(default)]
+    pub keepalive_while_idle: bool,
+}
+
+impl Default for Http2Config {
+    fn default() -> Self {
+        Self {
+            keepalive_interval: default_http2_keepalive_interval(),
+            keepalive_timeout: default_http2_keepalive_timeout(),
+            keepalive_while_idle: false,
+        }
+    }
+}
+
+/// TLS 配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_tls_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct TlsConfig {
+    /// 客户端证书文件路径（PEM，可包含证书链），与 key 同时配置时启用 mTLS
+    #[serde(default)]
+    pub cert: Option<String>,
+    /// 客户端私钥文件路径（PEM）
+    #[serde(default)]
+    pub key: Option<String>,
+    /// 私钥密码（私钥已加密时）
+    #[serde(default)]
+    pub passphrase: Option<String>,
+    /// 额外信任的 CA 证书文件（PEM，可包含多个证书），用于校验使用私有 CA 的上游
+    #[serde(default)]
+    pub ca_file: Option<String>,
+    /// 跳过上游证书校验，仅用于测试环境
+    #[serde(default)]
+    pub insecure_skip_verify: bool,
+    /// 最低 TLS 版本
+    #[serde(default)]
+    pub min_version: Option<TlsVersion>,
+}
+
+/// TLS 版本
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum TlsVersion {
+    #[serde(rename = "1.0")]
+    Tls10,
+    #[serde(rename = "1.1")]
+    Tls11,
+    #[serde(rename = "1.2")]
+    Tls12,
+}
+
 /// HTTP客户端超时配置
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
 #[serde(rename_all = "lowercase")]
@@ -79,6 +209,13 @@ pub struct HttpClientTimeoutConfig {
         max = "http_client_limits::MAX_IDLE_TIMEOUT"
     ))]
     pub idle: u64,
+    /// 响应体空闲超时（秒），上游在该时间内没有发送任何数据时中断响应，未设置时不限制
+    #[serde(default)]
+    #[validate(range(
+        min = "http_client_limits::MIN_STREAM_IDLE_TIMEOUT",
+        max = "http_client_limits::MAX_STREAM_IDLE_TIMEOUT"
+    ))]
+    pub stream_idle: Option<u64>,
 }
 
 impl Default for HttpClientTimeoutConfig {
@@ -87,6 +224,7 @@ impl Default for HttpClientTimeoutConfig {
             connect: default_connect_timeout(),
             request: default_request_timeout(),
             idle: default_idle_timeout(),
+            stream_idle: None,
         }
     }
 }
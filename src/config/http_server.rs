@@ -1,19 +1,76 @@
-use crate::config::common::{RateLimitConfig, TimeoutConfig};
-use crate::config::defaults::{default_admin_port, default_listen_address, default_listen_port};
+use crate::config::common::{
+    BreakerConfig, RateLimitConfig, RateLimitKey, RedisConfig, TimeoutConfig,
+};
+use crate::config::defaults::{
+    default_access_log_buffer, default_access_log_max_files, default_access_log_max_size,
+    default_admin_dashboard, default_admin_port, default_alert_interval,
+    default_alert_min_requests, default_alert_quantile, default_alert_window,
+    default_audit_max_entries, default_audit_sink_buffer, default_audit_sink_max_body_bytes,
+    default_budget_header, default_cache_max_body_bytes, default_cache_max_entries,
+    default_cache_ttl, default_compression_algorithms, default_compression_min_size,
+    default_listen_address, default_listen_backlog, default_listen_port,
+    default_load_shedding_interval_ms, default_metrics_export_interval, default_metrics_path,
+    default_plugin_fuel, default_plugin_max_memory_bytes, default_policy_timeout_ms,
+    default_priority_header, default_queue_max_depth, default_queue_max_wait_ms,
+    default_queue_weight, default_response_buffer_limit, default_websocket_idle_timeout,
+};
+use crate::config::validation;
+use crate::r#const::{
+    access_log, alert_limits, audit_limits, audit_sink, cache_limits, compression,
+    concurrency_limits, listener_limits, load_shedding, metrics_export, plugin, policy,
+    request_header_limits, response_buffer, sse_heartbeat, token_limits, websocket,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use utoipa::ToSchema;
 use validator::Validate;
 
+// 路由规则类型
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutingRuleType {
+    // 路径模式（静态路径、命名参数、通配符）
+    #[default]
+    Path,
+    // 正则表达式，未锚定时匹配请求路径的任意子串
+    PathRegex,
+}
+
 // 路由规则
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
 #[serde(rename_all = "lowercase")]
+#[validate(schema(
+    function = "validation::validate_routing_rule",
+    skip_on_field_errors = false
+))]
 pub struct RoutingRule {
     // 路径模式
     #[validate(length(min = 1, message = "Path pattern cannot be empty"))]
     pub path: String,
+    // 路由规则类型
+    #[serde(default)]
+    pub r#type: RoutingRuleType,
     // 目标上游组
     #[validate(length(min = 1, message = "Target group cannot be empty"))]
     pub target_group: String,
+    // 优先级（数值越大越优先，多个规则同时匹配时使用）
+    #[serde(default)]
+    pub priority: u32,
+    // 路由熔断器配置，覆盖转发服务的 breaker
+    #[serde(default)]
+    #[validate(nested)]
+    pub breaker: Option<BreakerConfig>,
+    // 匹配该路由的请求的最大提示词 token 数（估算值），超出时拒绝请求
+    #[serde(default)]
+    #[validate(range(min = 1, message = "Route max_prompt_tokens must be at least 1"))]
+    pub max_prompt_tokens: Option<u64>,
+    // 匹配该路由的请求每个客户端每分钟的 token 数，覆盖 token_limit.tokens_per_minute，路由拥有独立的令牌桶
+    #[serde(default)]
+    #[validate(range(
+        min = "token_limits::MIN_TOKENS_PER_MINUTE",
+        max = "token_limits::MAX_TOKENS_PER_MINUTE"
+    ))]
+    pub tokens_per_minute: Option<u64>,
 }
 
 // HTTP服务器配置
@@ -28,6 +85,82 @@ pub struct HttpServerConfig {
     #[serde(default)]
     #[validate(nested)]
     pub admin: AdminConfig,
+    // 过载保护配置，未配置时不检查资源使用情况
+    #[serde(default)]
+    #[validate(nested)]
+    pub load_shedding: Option<LoadSheddingConfig>,
+}
+
+// 过载保护配置
+// 定期检查进程内存（RSS）和事件循环延迟，资源紧张时按请求优先级拒绝新请求（返回 503），避免进程内存耗尽。
+// 使用量达到上限的 85% 时拒绝低优先级请求，达到上限时只接收高优先级请求
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_load_shedding_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct LoadSheddingConfig {
+    // 进程内存（RSS）上限（MB），仅 Linux 支持
+    #[serde(default)]
+    #[validate(range(
+        min = "load_shedding::MIN_MEMORY_MB",
+        max = "load_shedding::MAX_MEMORY_MB"
+    ))]
+    pub max_memory_mb: Option<u64>,
+    // 事件循环延迟上限（毫秒）
+    #[serde(default)]
+    #[validate(range(
+        min = "load_shedding::MIN_EVENT_LOOP_LAG_MS",
+        max = "load_shedding::MAX_EVENT_LOOP_LAG_MS"
+    ))]
+    pub max_event_loop_lag_ms: Option<u64>,
+    // 检查间隔（毫秒）
+    #[serde(default = "default_load_shedding_interval_ms")]
+    #[validate(range(
+        min = "load_shedding::MIN_INTERVAL_MS",
+        max = "load_shedding::MAX_INTERVAL_MS"
+    ))]
+    pub interval_ms: u64,
+    // 携带请求优先级（low、normal、high）的请求头，未携带或无法识别时为 normal
+    #[serde(default = "default_priority_header")]
+    pub priority_header: String,
+}
+
+// 请求优先级
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum RequestPriority {
+    // 资源紧张时最先拒绝
+    Low,
+    // 资源使用量达到上限时拒绝
+    #[default]
+    Normal,
+    // 不会被过载保护拒绝
+    High,
+}
+
+impl RequestPriority {
+    // 解析请求头中的优先级
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "low" => Some(Self::Low),
+            "normal" => Some(Self::Normal),
+            "high" => Some(Self::High),
+            _ => None,
+        }
+    }
+
+    // 优先级名称，用于指标标签
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::High => "high",
+        }
+    }
 }
 
 // 转发服务配置
@@ -46,6 +179,17 @@ pub struct ForwardConfig {
     // 指向的上游组名
     #[validate(length(min = 1, message = "Upstream group cannot be empty"))]
     pub default_group: String,
+    // 允许的请求方法，其他方法的请求返回 405，未配置时允许所有方法
+    #[serde(default)]
+    pub allowed_methods: Vec<String>,
+    // 允许的请求路径（glob 模式，* 匹配除 / 外的任意字符，** 匹配任意字符），
+    // 其他路径的请求返回 404，未配置时允许所有路径
+    #[serde(default)]
+    pub allowed_paths: Vec<String>,
+    // 入站请求头配置，未配置时所有请求头原样转发给上游
+    #[serde(default)]
+    #[validate(nested)]
+    pub request_headers: Option<RequestHeadersConfig>,
     // 限流配置
     #[serde(default)]
     #[validate(nested)]
@@ -58,10 +202,843 @@ pub struct ForwardConfig {
     #[serde(default)]
     #[validate(nested)]
     pub routing: Option<Vec<RoutingRule>>,
+    // 路由熔断器配置，每个路由（包括使用默认组的请求）使用独立的熔断器，未配置时不启用
+    #[serde(default)]
+    #[validate(nested)]
+    pub breaker: Option<BreakerConfig>,
+    // 请求参数上限配置
+    #[serde(default)]
+    #[validate(nested)]
+    pub limits: Option<ParamLimitsConfig>,
+    // 是否估算提示词 token 数（记录指标并添加响应头），配置了 limits.max_prompt_tokens 或路由规则的 max_prompt_tokens 时总是估算
+    #[serde(default)]
+    pub count_tokens: bool,
+    // 客户端预算配置
+    #[serde(default)]
+    #[validate(nested)]
+    pub budget: Option<BudgetConfig>,
+    // 令牌速率限制配置
+    #[serde(default)]
+    #[validate(nested)]
+    pub token_limit: Option<TokenLimitConfig>,
+    // 最大并发请求数，超出时进入等待队列（配置了 queue 时）或返回 429
+    #[serde(default)]
+    #[validate(range(
+        min = "concurrency_limits::MIN_CONCURRENT",
+        max = "concurrency_limits::MAX_CONCURRENT"
+    ))]
+    pub max_concurrent: Option<u32>,
+    // 等待队列配置，需要同时配置 max_concurrent
+    #[serde(default)]
+    #[validate(nested)]
+    pub queue: Option<QueueConfig>,
+    // SSE 心跳间隔（秒），上游在该时间内没有发送数据时向客户端发送注释行，避免中间代理关闭空闲连接
+    #[serde(default)]
+    #[validate(range(
+        min = "sse_heartbeat::MIN_INTERVAL",
+        max = "sse_heartbeat::MAX_INTERVAL"
+    ))]
+    pub sse_heartbeat: Option<u64>,
+    // 是否合并相同的在途请求，相同请求并发到达时只转发一个，响应共享给所有等待的请求
+    #[serde(default)]
+    pub coalesce: bool,
+    // 响应缓存配置
+    #[serde(default)]
+    #[validate(nested)]
+    pub cache: Option<CacheConfig>,
+    // 请求审计日志配置
+    #[serde(default)]
+    #[validate(nested)]
+    pub audit_sink: Option<AuditSinkConfig>,
+    // PII 脱敏配置
+    #[serde(default)]
+    #[validate(nested)]
+    pub pii_redaction: Option<PiiRedactionConfig>,
+    // 外部策略服务配置
+    #[serde(default)]
+    #[validate(nested)]
+    pub policy: Option<PolicyConfig>,
+    // WASM 插件配置，按配置顺序执行
+    #[serde(default)]
+    #[validate(nested)]
+    pub plugins: Vec<PluginConfig>,
+    // 请求处理阶段的执行顺序，未配置时按默认顺序执行，已配置的功能必须列出，入口阶段必须排在最前
+    #[serde(default)]
+    pub middlewares: Option<Vec<MiddlewareStage>>,
+    // 入站连接配置
+    #[serde(default)]
+    #[validate(nested)]
+    pub listener: Option<ListenerConfig>,
+    // WebSocket 代理配置，未配置时升级请求按普通 HTTP 请求转发
+    #[serde(default)]
+    #[validate(nested)]
+    pub websocket: Option<WebSocketConfig>,
+    // 响应压缩配置，未配置时不压缩响应
+    #[serde(default)]
+    #[validate(nested)]
+    pub compression: Option<CompressionConfig>,
+    // 访问日志配置，未配置时不记录访问日志
+    #[serde(default)]
+    #[validate(nested)]
+    pub access_log: Option<AccessLogConfig>,
+    // 代理自身错误的响应体配置，未配置时只返回状态码
+    #[serde(default)]
+    pub error_response: Option<ErrorResponseConfig>,
+    // 非流式响应体的缓冲上限（字节），Content-Length 超过该值的响应不读取完整响应体，直接以流式转发
+    #[serde(default = "default_response_buffer_limit")]
+    #[validate(range(min = "response_buffer::MIN_LIMIT", max = "response_buffer::MAX_LIMIT"))]
+    pub response_buffer_limit: u64,
+}
+
+// 入站连接配置
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_listener_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct ListenerConfig {
+    // 监听队列长度，实际长度不超过系统上限（如 Linux 的 net.core.somaxconn）
+    #[serde(default = "default_listen_backlog")]
+    #[validate(range(
+        min = "listener_limits::MIN_BACKLOG",
+        max = "listener_limits::MAX_BACKLOG"
+    ))]
+    pub backlog: u32,
+    // 是否对客户端连接设置 TCP_NODELAY，减少流式响应的发送延迟
+    #[serde(default)]
+    pub tcp_nodelay: bool,
+    // 是否接受 HTTP/2 连接，明文连接要求客户端直接使用 HTTP/2（prior knowledge）
+    #[serde(default)]
+    pub http2: bool,
+    // 每个 HTTP/2 连接的最大并发流数，需要开启 http2
+    #[serde(default)]
+    #[validate(range(
+        min = "listener_limits::MIN_CONCURRENT_STREAMS",
+        max = "listener_limits::MAX_CONCURRENT_STREAMS"
+    ))]
+    pub max_concurrent_streams: Option<u32>,
+    // 请求头的最大字节数，超出时关闭连接
+    #[serde(default)]
+    #[validate(range(
+        min = "listener_limits::MIN_HEADER_BYTES",
+        max = "listener_limits::MAX_HEADER_BYTES"
+    ))]
+    pub max_header_bytes: Option<u32>,
+    // 客户端空闲超时（秒）。HTTP/1 连接在该时间内没有发送完整的请求头时关闭，
+    // HTTP/2 连接按该间隔发送 PING，客户端在该时间内没有响应时关闭
+    #[serde(default)]
+    #[validate(range(
+        min = "listener_limits::MIN_IDLE_TIMEOUT",
+        max = "listener_limits::MAX_IDLE_TIMEOUT"
+    ))]
+    pub idle_timeout: Option<u64>,
+}
+
+impl Default for ListenerConfig {
+    fn default() -> Self {
+        Self {
+            backlog: default_listen_backlog(),
+            tcp_nodelay: false,
+            http2: false,
+            max_concurrent_streams: None,
+            max_header_bytes: None,
+            idle_timeout: None,
+        }
+    }
+}
+
+// 入站请求头配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_request_headers_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct RequestHeadersConfig {
+    // 转发给上游前移除的请求头名称（不区分大小写），如客户端的 Authorization、内部追踪请求头
+    #[serde(default)]
+    pub strip: Vec<String>,
+    // 最大请求头数量，超出时返回 431
+    #[serde(default)]
+    #[validate(range(
+        min = "request_header_limits::MIN_COUNT",
+        max = "request_header_limits::MAX_COUNT"
+    ))]
+    pub max_count: Option<u32>,
+    // 请求头的最大总字节数（所有请求头名称和值的长度之和），超出时返回 431
+    #[serde(default)]
+    #[validate(range(
+        min = "request_header_limits::MIN_BYTES",
+        max = "request_header_limits::MAX_BYTES"
+    ))]
+    pub max_bytes: Option<u32>,
+}
+
+// WebSocket 代理配置
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "lowercase")]
+pub struct WebSocketConfig {
+    // 连接空闲超时（秒），客户端和上游都没有发送数据时关闭连接
+    #[serde(default = "default_websocket_idle_timeout")]
+    #[validate(range(
+        min = "websocket::MIN_IDLE_TIMEOUT",
+        max = "websocket::MAX_IDLE_TIMEOUT"
+    ))]
+    pub idle_timeout: u64,
+}
+
+impl Default for WebSocketConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout: default_websocket_idle_timeout(),
+        }
+    }
+}
+
+// 响应压缩配置
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "lowercase")]
+pub struct CompressionConfig {
+    // 启用的压缩算法，按客户端的 Accept-Encoding 选择
+    #[serde(default = "default_compression_algorithms")]
+    #[validate(length(min = 1))]
+    pub algorithms: Vec<CompressionAlgorithm>,
+    // 压缩的最小响应体大小（字节），小于该值的响应不压缩
+    #[serde(default = "default_compression_min_size")]
+    #[validate(range(min = "compression::MIN_SIZE"))]
+    pub min_size: u16,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            algorithms: default_compression_algorithms(),
+            min_size: default_compression_min_size(),
+        }
+    }
+}
+
+// 响应压缩算法
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionAlgorithm {
+    // gzip
+    Gzip,
+    // brotli
+    Br,
+}
+
+// 访问日志配置
+// 每个请求在响应体发送完成（或客户端断开连接）时记录一行，写入标准输出或文件
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "lowercase")]
+pub struct AccessLogConfig {
+    // 日志格式
+    #[serde(default)]
+    pub format: AccessLogFormat,
+    // 日志文件路径，追加写入，未配置时写入标准输出
+    #[serde(default)]
+    #[validate(length(min = 1, message = "Access log file cannot be empty"))]
+    pub file: Option<String>,
+    // 日志文件的最大大小（MB），超出时轮转
+    #[serde(default = "default_access_log_max_size")]
+    #[validate(range(min = "access_log::MIN_MAX_SIZE", max = "access_log::MAX_MAX_SIZE"))]
+    pub max_size: u64,
+    // 保留的轮转文件数，超出时删除最早的文件
+    #[serde(default = "default_access_log_max_files")]
+    #[validate(range(min = "access_log::MIN_MAX_FILES", max = "access_log::MAX_MAX_FILES"))]
+    pub max_files: u32,
+    // 等待写入的最大记录数，超出时丢弃新记录
+    #[serde(default = "default_access_log_buffer")]
+    #[validate(range(min = "access_log::MIN_BUFFER", max = "access_log::MAX_BUFFER"))]
+    pub buffer: usize,
+}
+
+impl Default for AccessLogConfig {
+    fn default() -> Self {
+        Self {
+            format: AccessLogFormat::default(),
+            file: None,
+            max_size: default_access_log_max_size(),
+            max_files: default_access_log_max_files(),
+            buffer: default_access_log_buffer(),
+        }
+    }
+}
+
+// 访问日志格式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessLogFormat {
+    // 每行一个 JSON 对象
+    #[default]
+    Json,
+    // Apache/Nginx combined 格式，末尾追加请求 ID、路由、上游组、上游和耗时字段
+    Combined,
+}
+
+// 代理自身错误（如上游不可达、熔断器开启）的响应体配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub struct ErrorResponseConfig {
+    // 响应体格式
+    #[serde(default)]
+    pub format: ErrorResponseFormat,
+    // 是否在错误消息中包含错误详情，详情可能包含上游地址等内部信息
+    #[serde(default)]
+    pub details: bool,
+}
+
+// 错误响应体格式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorResponseFormat {
+    // OpenAI 格式：{"error": {"message", "type", "param", "code", "request_id", "retryable"}}
+    #[default]
+    Openai,
+    // 扁平的 JSON 对象：{"code", "message", "request_id", "retryable"}
+    Json,
+}
+
+impl ForwardConfig {
+    /// 请求处理阶段的执行顺序
+    pub fn middleware_order(&self) -> &[MiddlewareStage] {
+        self.middlewares
+            .as_deref()
+            .unwrap_or(&MiddlewareStage::DEFAULT_ORDER)
+    }
+
+    /// 转发服务是否配置了请求处理阶段对应的功能
+    pub fn has_middleware(&self, stage: MiddlewareStage) -> bool {
+        match stage {
+            MiddlewareStage::AccessLog => self.access_log.is_some(),
+            MiddlewareStage::Compression => self.compression.is_some(),
+            MiddlewareStage::RateLimit => self.ratelimit.is_some(),
+            MiddlewareStage::Plugins => !self.plugins.is_empty(),
+            MiddlewareStage::Limits => self.limits.is_some(),
+            MiddlewareStage::PiiRedaction => self.pii_redaction.is_some(),
+            MiddlewareStage::Policy => self.policy.is_some(),
+            MiddlewareStage::TokenLimit => self.token_limit.is_some(),
+            MiddlewareStage::AuditSink => self.audit_sink.is_some(),
+            MiddlewareStage::Cache => self.cache.is_some(),
+        }
+    }
+}
+
+// 请求处理阶段
+// 入口阶段（访问日志、响应压缩、限流）以中间件层包裹请求处理，排在前面的位于外层；
+// 其余阶段在读取请求体之后、转发给上游之前按顺序执行
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MiddlewareStage {
+    // 访问日志
+    AccessLog,
+    // 响应压缩
+    Compression,
+    // 请求限流
+    RateLimit,
+    // 插件的 on_request 钩子
+    Plugins,
+    // 请求参数上限
+    Limits,
+    // PII 脱敏
+    PiiRedaction,
+    // 外部策略服务
+    Policy,
+    // 令牌速率限制
+    TokenLimit,
+    // 记录审计日志的请求体
+    AuditSink,
+    // 响应缓存查询
+    Cache,
+}
+
+impl MiddlewareStage {
+    /// 默认的执行顺序
+    pub const DEFAULT_ORDER: [MiddlewareStage; 10] = [
+        MiddlewareStage::AccessLog,
+        MiddlewareStage::Compression,
+        MiddlewareStage::RateLimit,
+        MiddlewareStage::Plugins,
+        MiddlewareStage::Limits,
+        MiddlewareStage::PiiRedaction,
+        MiddlewareStage::Policy,
+        MiddlewareStage::TokenLimit,
+        MiddlewareStage::AuditSink,
+        MiddlewareStage::Cache,
+    ];
+
+    /// 是否为读取请求体之前以中间件层执行的入口阶段
+    pub fn is_layer(&self) -> bool {
+        matches!(
+            self,
+            MiddlewareStage::AccessLog | MiddlewareStage::Compression | MiddlewareStage::RateLimit
+        )
+    }
+
+    /// 阶段名称，与配置中的名称一致
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MiddlewareStage::AccessLog => "access_log",
+            MiddlewareStage::Compression => "compression",
+            MiddlewareStage::RateLimit => "rate_limit",
+            MiddlewareStage::Plugins => "plugins",
+            MiddlewareStage::Limits => "limits",
+            MiddlewareStage::PiiRedaction => "pii_redaction",
+            MiddlewareStage::Policy => "policy",
+            MiddlewareStage::TokenLimit => "token_limit",
+            MiddlewareStage::AuditSink => "audit_sink",
+            MiddlewareStage::Cache => "cache",
+        }
+    }
+}
+
+// WASM 插件配置
+// 插件导出 memory、alloc 和任意阶段函数（on_request、pre_upstream、on_response），
+// 每次调用使用独立的实例，燃料和内存用尽时调用失败
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "lowercase")]
+pub struct PluginConfig {
+    // 插件名称，用于日志和指标标签
+    #[validate(length(min = 1, message = "Plugin name cannot be empty"))]
+    pub name: String,
+    // WASM 模块文件路径
+    #[validate(length(min = 1, message = "Plugin path cannot be empty"))]
+    pub path: String,
+    // 每次调用的燃料（约等于执行的指令数），避免插件死循环阻塞请求
+    #[serde(default = "default_plugin_fuel")]
+    #[validate(range(min = "plugin::MIN_FUEL", max = "plugin::MAX_FUEL"))]
+    pub fuel: u64,
+    // 最大线性内存（字节）
+    #[serde(default = "default_plugin_max_memory_bytes")]
+    #[validate(range(
+        min = "plugin::MIN_MAX_MEMORY_BYTES",
+        max = "plugin::MAX_MAX_MEMORY_BYTES"
+    ))]
+    pub max_memory_bytes: usize,
+    // 插件执行出错时的处理方式
+    #[serde(default)]
+    pub fail_mode: PolicyFailMode,
+}
+
+// 外部策略服务配置
+// 转发前将请求发送给外部策略服务（如内容审核服务），按返回的裁决放行、拒绝、改写请求体或添加请求头。
+// 策略服务超时或出错时按 fail_mode 放行或拒绝请求
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_policy_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct PolicyConfig {
+    // 策略服务地址，以 JSON 格式 POST 请求
+    pub url: String,
+    // 策略服务的 Bearer 令牌
+    #[serde(default)]
+    pub token: Option<String>,
+    // 请求超时（毫秒）
+    #[serde(default = "default_policy_timeout_ms")]
+    #[validate(range(min = "policy::MIN_TIMEOUT_MS", max = "policy::MAX_TIMEOUT_MS"))]
+    pub timeout_ms: u64,
+    // 策略服务超时或出错时的处理方式
+    #[serde(default)]
+    pub fail_mode: PolicyFailMode,
+    // 发送给策略服务的内容
+    #[serde(default)]
+    pub payload: PolicyPayload,
+}
+
+// 策略服务或插件超时、出错时的处理方式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyFailMode {
+    // 放行请求
+    Open,
+    // 拒绝请求（策略服务返回 503，插件返回 500）
+    #[default]
+    Closed,
+}
+
+// 发送给策略服务的内容
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyPayload {
+    // 完整的请求体
+    #[default]
+    Request,
+    // 从提示词字段中提取的文本
+    Prompt,
+}
+
+// PII 脱敏配置
+// 转发前将请求体提示词文本（messages、prompt、input、system 等字段）中的个人信息替换为占位文本，
+// 内置检测器和自定义规则按配置顺序依次应用
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_pii_redaction_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct PiiRedactionConfig {
+    // 内置检测器
+    #[serde(default)]
+    pub detectors: Vec<PiiDetector>,
+    // 自定义正则规则
+    #[serde(default)]
+    #[validate(nested)]
+    pub patterns: Vec<PiiPatternConfig>,
+}
+
+// 内置 PII 检测器
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiDetector {
+    // 电子邮件地址，替换为 [EMAIL]
+    Email,
+    // 电话号码（北美格式和中国大陆手机号），替换为 [PHONE]
+    Phone,
+    // 通过 Luhn 校验的银行卡号，替换为 [CREDIT_CARD]
+    CreditCard,
+}
+
+impl PiiDetector {
+    /// 检测器名称，用作指标标签
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PiiDetector::Email => "email",
+            PiiDetector::Phone => "phone",
+            PiiDetector::CreditCard => "credit_card",
+        }
+    }
+}
+
+// 自定义 PII 脱敏规则
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "lowercase")]
+pub struct PiiPatternConfig {
+    // 规则名称，用作指标标签
+    #[validate(length(min = 1, message = "PII pattern name cannot be empty"))]
+    pub name: String,
+    // 正则表达式
+    pub regex: String,
+    // 替换文本，默认为 [REDACTED]
+    #[serde(default)]
+    pub replacement: Option<String>,
+}
+
+// 请求审计日志配置
+// 每个转发的请求写入一条 JSONL 记录，包括请求体和响应体，写入文件或按批次 POST 到 HTTP 接收端（二选一）。
+// 记录在后台写入，缓冲已满时丢弃新记录，不阻塞请求
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_audit_sink_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct AuditSinkConfig {
+    // JSONL 文件路径，追加写入
+    #[serde(default)]
+    pub file: Option<String>,
+    // HTTP 接收端地址，每批记录以 JSONL 格式 POST
+    #[serde(default)]
+    pub url: Option<String>,
+    // HTTP 接收端的 Bearer 令牌
+    #[serde(default)]
+    pub token: Option<String>,
+    // 需要脱敏的 JSON 字段名（任意层级），值替换为 ******
+    #[serde(default)]
+    pub redact_fields: Vec<String>,
+    // 记录的最大请求体或响应体（字节），超出时截断
+    #[serde(default = "default_audit_sink_max_body_bytes")]
+    #[validate(range(max = "audit_sink::MAX_MAX_BODY_BYTES"))]
+    pub max_body_bytes: usize,
+    // 等待写入的最大记录数，超出时丢弃新记录
+    #[serde(default = "default_audit_sink_buffer")]
+    #[validate(range(min = "audit_sink::MIN_BUFFER", max = "audit_sink::MAX_BUFFER"))]
+    pub buffer: usize,
+}
+
+// 响应缓存配置
+// 缓存非流式请求的成功响应（2xx），缓存键与请求合并相同（上游组、方法、路径、凭证头部和请求体）
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_cache_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct CacheConfig {
+    // 缓存存储后端
+    #[serde(default)]
+    pub backend: CacheBackend,
+    // 缓存有效期（秒）
+    #[serde(default = "default_cache_ttl")]
+    #[validate(range(min = "cache_limits::MIN_TTL", max = "cache_limits::MAX_TTL"))]
+    pub ttl: u64,
+    // 内存缓存的最大条目数，超出时淘汰最早过期的条目
+    #[serde(default = "default_cache_max_entries")]
+    #[validate(range(min = "cache_limits::MIN_ENTRIES", max = "cache_limits::MAX_ENTRIES"))]
+    pub max_entries: usize,
+    // 可缓存的最大响应体（字节），超出时不缓存
+    #[serde(default = "default_cache_max_body_bytes")]
+    pub max_body_bytes: usize,
+    // Redis 连接配置，backend 为 redis 时必填
+    #[serde(default)]
+    #[validate(nested)]
+    pub redis: Option<RedisConfig>,
+}
+
+// 响应缓存存储后端
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheBackend {
+    // 进程内存，只在当前实例内共享，重启后清空
+    #[default]
+    Memory,
+    // Redis，多个实例共享缓存
+    Redis,
+}
+
+// 等待队列配置
+// 并发请求数达到上限时请求在队列中等待空闲，队列已满时返回 429，等待超时返回 503。
+// 默认按到达顺序调度，配置了 fair 时按客户端加权公平调度
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "lowercase")]
+pub struct QueueConfig {
+    // 最多等待的请求数
+    #[serde(default = "default_queue_max_depth")]
+    #[validate(range(
+        min = "concurrency_limits::MIN_QUEUE_DEPTH",
+        max = "concurrency_limits::MAX_QUEUE_DEPTH"
+    ))]
+    pub max_depth: u32,
+    // 最长等待时间（毫秒）
+    #[serde(default = "default_queue_max_wait_ms")]
+    #[validate(range(
+        min = "concurrency_limits::MIN_QUEUE_WAIT_MS",
+        max = "concurrency_limits::MAX_QUEUE_WAIT_MS"
+    ))]
+    pub max_wait_ms: u64,
+    // 公平调度配置
+    #[serde(default)]
+    #[validate(nested)]
+    pub fair: Option<FairQueueConfig>,
+}
+
+// 公平调度配置
+// 排队的请求按客户端加权公平调度，权重越大的客户端获得的空闲许可越多，同一客户端的请求按到达顺序调度
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_fair_queue_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct FairQueueConfig {
+    // 标识客户端的限流键类型，与请求速率限制相同
+    #[serde(default)]
+    pub key: RateLimitKey,
+    // 限流键为 header 时使用的请求头名称
+    #[serde(default)]
+    pub header: Option<String>,
+    // 未在任何等级中的客户端的权重
+    #[serde(default = "default_queue_weight")]
+    #[validate(range(
+        min = "concurrency_limits::MIN_WEIGHT",
+        max = "concurrency_limits::MAX_WEIGHT"
+    ))]
+    pub default_weight: u32,
+    // 客户端等级
+    #[serde(default)]
+    #[validate(nested)]
+    pub tiers: Vec<QueueTierConfig>,
+}
+
+impl FairQueueConfig {
+    /// 获取客户端的权重，未在任何等级中的客户端使用默认权重
+    pub fn weight(&self, client: Option<&str>) -> u32 {
+        client
+            .and_then(|client| {
+                self.tiers
+                    .iter()
+                    .find(|tier| tier.clients.iter().any(|c| c == client))
+            })
+            .map(|tier| tier.weight)
+            .unwrap_or(self.default_weight)
+    }
+}
+
+// 公平调度的客户端等级
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "lowercase")]
+pub struct QueueTierConfig {
+    // 等级名称
+    #[validate(length(min = 1, message = "Queue tier name cannot be empty"))]
+    pub name: String,
+    // 等级权重
+    #[validate(range(
+        min = "concurrency_limits::MIN_WEIGHT",
+        max = "concurrency_limits::MAX_WEIGHT"
+    ))]
+    pub weight: u32,
+    // 属于该等级的客户端标识，即限流键的值（客户端 IP、请求头的值或 API 密钥）
+    #[serde(default)]
+    pub clients: Vec<String>,
+}
+
+// 请求参数上限配置
+// 限制 JSON 请求体中的生成参数，防止单个客户端提交成本失控的请求
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_param_limits_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct ParamLimitsConfig {
+    // 最大生成 token 数，同时限制 max_tokens 和 max_completion_tokens
+    #[serde(default)]
+    pub max_tokens: Option<u64>,
+    // 最大采样温度
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    // 最大核采样概率
+    #[serde(default)]
+    pub top_p: Option<f64>,
+    // 最大候选结果数
+    #[serde(default)]
+    pub n: Option<u64>,
+    // 最大提示词 token 数（估算值），超出时总是拒绝请求
+    #[serde(default)]
+    pub max_prompt_tokens: Option<u64>,
+    // 参数超出上限时的处理方式
+    #[serde(default)]
+    pub action: ParamLimitAction,
+}
+
+// 参数超出上限时的处理方式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ParamLimitAction {
+    // 改写为上限值后转发
+    #[default]
+    Clamp,
+    // 拒绝请求，返回 400
+    Reject,
+}
+
+// 客户端预算配置
+// 按请求头标识客户端，累计上游价格表计算出的费用，超出预算时返回 429 直到时间窗口（UTC 自然日、自然月）重置
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_budget_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct BudgetConfig {
+    // 标识客户端的请求头，值为 Bearer 令牌时去除 Bearer 前缀，未携带该请求头的请求不受预算限制
+    #[serde(default = "default_budget_header")]
+    pub header: String,
+    // 每个客户端的每日预算（美元）
+    #[serde(default)]
+    pub daily: Option<f64>,
+    // 每个客户端的每月预算（美元）
+    #[serde(default)]
+    pub monthly: Option<f64>,
+    // 指定客户端的预算，覆盖默认预算
+    #[serde(default)]
+    #[validate(nested)]
+    pub clients: Vec<ClientBudgetConfig>,
+}
+
+// 令牌速率限制配置
+// 按每分钟 token 数限流：转发前扣除估算的提示词 token 数，收到上游响应后按实际用量（提示词和生成内容）修正。
+// 生效的限额依次为指定客户端、匹配的路由、转发服务默认值，每个路由和客户端拥有独立的令牌桶。
+// 配置了 redis 时令牌桶保存在 Redis 中，多个实例共享限额
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_token_limit_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct TokenLimitConfig {
+    // 每个客户端每分钟的 token 数
+    #[validate(range(
+        min = "token_limits::MIN_TOKENS_PER_MINUTE",
+        max = "token_limits::MAX_TOKENS_PER_MINUTE"
+    ))]
+    pub tokens_per_minute: u64,
+    // 限流键类型，与请求速率限制相同
+    #[serde(default)]
+    pub key: RateLimitKey,
+    // 限流键为 header 时使用的请求头名称
+    #[serde(default)]
+    pub header: Option<String>,
+    // 指定客户端的限额
+    #[serde(default)]
+    #[validate(nested)]
+    pub clients: Vec<ClientTokenLimitConfig>,
+    // Redis 连接配置，未配置时只在当前实例内限流
+    #[serde(default)]
+    #[validate(nested)]
+    pub redis: Option<RedisConfig>,
+}
+
+impl TokenLimitConfig {
+    /// 获取生效的每分钟 token 数：指定客户端、匹配的路由规则、转发服务默认值
+    pub fn limit(&self, route: Option<u64>, client: Option<&str>) -> u64 {
+        client
+            .and_then(|client| self.clients.iter().find(|c| c.id == client))
+            .map(|c| c.tokens_per_minute)
+            .or(route)
+            .unwrap_or(self.tokens_per_minute)
+    }
+}
+
+// 指定客户端的令牌速率限制配置
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "lowercase")]
+pub struct ClientTokenLimitConfig {
+    // 客户端标识，即限流键的值（客户端 IP、请求头的值或 API 密钥）
+    #[validate(length(min = 1, message = "Client token limit id cannot be empty"))]
+    pub id: String,
+    // 每分钟的 token 数
+    #[validate(range(
+        min = "token_limits::MIN_TOKENS_PER_MINUTE",
+        max = "token_limits::MAX_TOKENS_PER_MINUTE"
+    ))]
+    pub tokens_per_minute: u64,
+}
+
+// 指定客户端的预算配置
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "lowercase")]
+pub struct ClientBudgetConfig {
+    // 客户端标识，即请求头的值
+    #[validate(length(min = 1, message = "Client budget id cannot be empty"))]
+    pub id: String,
+    // 每日预算（美元），未设置时使用默认预算
+    #[serde(default)]
+    pub daily: Option<f64>,
+    // 每月预算（美元），未设置时使用默认预算
+    #[serde(default)]
+    pub monthly: Option<f64>,
+}
+
+impl BudgetConfig {
+    // 获取客户端的每日和每月预算
+    pub fn limits(&self, client: &str) -> (Option<f64>, Option<f64>) {
+        match self.clients.iter().find(|c| c.id == client) {
+            Some(c) => (c.daily.or(self.daily), c.monthly.or(self.monthly)),
+            None => (self.daily, self.monthly),
+        }
+    }
 }
 
 // 管理服务配置
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_admin_config",
+    skip_on_field_errors = false
+))]
 #[serde(rename_all = "lowercase")]
 pub struct AdminConfig {
     // 监听端口
@@ -74,6 +1051,40 @@ pub struct AdminConfig {
     #[serde(default)]
     #[validate(nested)]
     pub timeout: Option<TimeoutConfig>,
+    // 审计日志配置
+    #[serde(default)]
+    #[validate(nested)]
+    pub audit: AuditConfig,
+    // 是否开启内嵌的管理面板（/dashboard）
+    #[serde(default = "default_admin_dashboard")]
+    pub dashboard: bool,
+    // Prometheus 指标端点配置
+    #[serde(default)]
+    #[validate(nested)]
+    pub metrics: MetricsConfig,
+    // HTTPS 配置，未配置时使用 HTTP
+    #[serde(default)]
+    #[validate(nested)]
+    pub tls: Option<AdminTlsConfig>,
+}
+
+// 管理服务的 HTTPS 配置
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "lowercase")]
+pub struct AdminTlsConfig {
+    // 服务端证书文件路径（PEM，可包含证书链）
+    #[validate(length(min = 1, message = "Admin TLS cert cannot be empty"))]
+    pub cert: String,
+    // 服务端私钥文件路径（PEM）
+    #[validate(length(min = 1, message = "Admin TLS key cannot be empty"))]
+    pub key: String,
+    // 私钥密码（私钥已加密时）
+    #[serde(default)]
+    pub passphrase: Option<String>,
+    // 校验客户端证书的 CA 证书文件（PEM，可包含多个证书），配置后要求客户端提供证书（mTLS）
+    #[serde(default)]
+    #[validate(length(min = 1, message = "Admin TLS client_ca_file cannot be empty"))]
+    pub client_ca_file: Option<String>,
 }
 
 impl Default for AdminConfig {
@@ -82,6 +1093,255 @@ impl Default for AdminConfig {
             port: default_admin_port(),
             address: default_listen_address(),
             timeout: None,
+            audit: AuditConfig::default(),
+            dashboard: default_admin_dashboard(),
+            metrics: MetricsConfig::default(),
+            tls: None,
+        }
+    }
+}
+
+// Prometheus 指标端点配置
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_metrics_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct MetricsConfig {
+    // 指标路径
+    #[serde(default = "default_metrics_path")]
+    pub path: String,
+    // 访问指标端点所需的 Bearer 令牌，未配置时不需要认证
+    #[serde(default)]
+    #[validate(length(min = 1, message = "Metrics token cannot be empty"))]
+    pub token: Option<String>,
+    // 独立的指标监听端口，配置后指标只在该端口提供，不再与管理 API 共用端口
+    #[serde(default)]
+    pub port: Option<u16>,
+    // 独立的指标监听地址，未配置时使用管理服务的监听地址
+    #[serde(default)]
+    pub address: Option<String>,
+    // 主动推送指标的配置，用于没有 Prometheus 抓取的环境
+    #[serde(default)]
+    #[validate(nested)]
+    pub export: Option<MetricsExportConfig>,
+    // 指标标签配置
+    #[serde(default)]
+    #[validate(nested)]
+    pub labels: MetricsLabelsConfig,
+    // 请求耗时类直方图（请求耗时、上游耗时、首字节耗时）的分桶上限（秒），未配置时使用默认分桶
+    #[serde(default)]
+    pub duration_buckets: Option<Vec<f64>>,
+    // 告警规则配置，按进程内的指标定期评估
+    #[serde(default)]
+    #[validate(nested)]
+    pub alerts: Option<AlertsConfig>,
+}
+
+// 指标标签配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_metrics_labels_config",
+    skip_on_field_errors = false
+))]
+pub struct MetricsLabelsConfig {
+    // 是否为请求计数添加请求体中的模型名称（model 标签）
+    // 模型名称由客户端决定，开启后指标数量随客户端使用的模型数量增长
+    #[serde(default)]
+    pub model: bool,
+    // upstream 标签的取值
+    #[serde(default)]
+    pub upstream: MetricsUpstreamLabel,
+    // 输出指标时去掉的标签，去掉后标签值相同的序列合并
+    #[serde(default)]
+    pub disabled: Vec<String>,
+}
+
+// upstream 标签的取值
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricsUpstreamLabel {
+    // 上游地址
+    #[default]
+    Url,
+    // 上游名称，地址中包含密钥或经常变化时使用
+    Name,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            path: default_metrics_path(),
+            token: None,
+            port: None,
+            address: None,
+            export: None,
+            labels: MetricsLabelsConfig::default(),
+            duration_buckets: None,
+            alerts: None,
+        }
+    }
+}
+
+// 指标推送配置
+// 按推送间隔将全部指标推送到 OTLP 接收端或 StatsD 服务
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_metrics_export_config",
+    skip_on_field_errors = false
+))]
+pub struct MetricsExportConfig {
+    // 推送协议
+    pub protocol: MetricsExportProtocol,
+    // 推送地址，OTLP 为 HTTP(S) 地址（如 http://collector:4318/v1/metrics），StatsD 为 host:port
+    pub endpoint: String,
+    // 推送间隔（秒）
+    #[serde(default = "default_metrics_export_interval")]
+    #[validate(range(
+        min = "metrics_export::MIN_INTERVAL",
+        max = "metrics_export::MAX_INTERVAL"
+    ))]
+    pub interval: u64,
+    // 指标名称前缀，添加在原指标名称（如 llmproxy_http_requests_total）之前
+    #[serde(default)]
+    pub prefix: String,
+    // 推送 OTLP 时附加的请求头（如认证头），StatsD 不使用
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+}
+
+// 指标推送协议
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricsExportProtocol {
+    // OTLP/HTTP（JSON 编码）
+    Otlp,
+    // StatsD，标签值作为指标名称的后缀
+    Statsd,
+    // DogStatsD（Datadog），标签作为 tag 发送
+    Dogstatsd,
+}
+
+impl MetricsExportProtocol {
+    /// 协议名称，与配置中的名称一致
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetricsExportProtocol::Otlp => "otlp",
+            MetricsExportProtocol::Statsd => "statsd",
+            MetricsExportProtocol::Dogstatsd => "dogstatsd",
+        }
+    }
+}
+
+// 告警配置
+// 按评估间隔从进程内的指标计算告警规则，告警触发或恢复时发布系统事件并通知 Webhook
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_alerts_config",
+    skip_on_field_errors = false
+))]
+pub struct AlertsConfig {
+    // 评估间隔（秒）
+    #[serde(default = "default_alert_interval")]
+    #[validate(range(min = "alert_limits::MIN_INTERVAL", max = "alert_limits::MAX_INTERVAL"))]
+    pub interval: u64,
+    // 告警触发或恢复时通知的 Webhook
+    #[serde(default)]
+    #[validate(nested)]
+    pub webhooks: Vec<AlertWebhookConfig>,
+    // 告警规则
+    #[validate(length(min = 1, message = "At least one alert rule must be configured"))]
+    #[validate(nested)]
+    pub rules: Vec<AlertRuleConfig>,
+}
+
+// 告警 Webhook 配置，告警触发或恢复时以 JSON 格式 POST 到该地址
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_alert_webhook_config",
+    skip_on_field_errors = false
+))]
+pub struct AlertWebhookConfig {
+    // Webhook 地址
+    pub url: String,
+    // 附加的请求头（如认证头）
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+}
+
+// 告警规则配置
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_alert_rule_config",
+    skip_on_field_errors = false
+))]
+pub struct AlertRuleConfig {
+    // 规则名称
+    #[validate(length(min = 1, message = "Alert rule name cannot be empty"))]
+    pub name: String,
+    // 评估的指标
+    pub metric: AlertMetric,
+    // 只评估该上游组，未配置时分别评估每个上游组
+    #[serde(default)]
+    pub group: Option<String>,
+    // 告警阈值，指标值超过阈值时触发告警。错误率为 0-1 的比例，耗时单位为秒
+    pub threshold: f64,
+    // 统计窗口（秒）
+    #[serde(default = "default_alert_window")]
+    #[validate(range(min = "alert_limits::MIN_WINDOW", max = "alert_limits::MAX_WINDOW"))]
+    pub window: u64,
+    // 耗时分位数，仅 latency 指标使用
+    #[serde(default = "default_alert_quantile")]
+    pub quantile: f64,
+    // 窗口内的最少请求数，请求数不足时不评估，避免少量请求造成误报
+    #[serde(default = "default_alert_min_requests")]
+    #[validate(range(min = 1))]
+    pub min_requests: u64,
+}
+
+// 告警规则评估的指标
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertMetric {
+    // 上游错误率：窗口内 5xx 响应和未收到响应的请求占上游请求的比例
+    ErrorRate,
+    // 上游耗时分位数（秒），按上游耗时直方图计算
+    Latency,
+}
+
+impl AlertMetric {
+    /// 指标名称，与配置中的名称一致
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertMetric::ErrorRate => "error_rate",
+            AlertMetric::Latency => "latency",
+        }
+    }
+}
+
+// 审计日志配置，记录所有成功的管理 API 配置变更
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "lowercase")]
+pub struct AuditConfig {
+    // 审计日志文件路径（仅追加写入），未配置时写入结构化日志
+    #[serde(default)]
+    #[validate(length(min = 1, message = "Audit log file path cannot be empty"))]
+    pub file: Option<String>,
+    // 内存中保留的最近审计记录数量，供 GET /api/v1/audit 查询
+    #[serde(default = "default_audit_max_entries")]
+    #[validate(range(
+        min = "audit_limits::MIN_MAX_ENTRIES",
+        max = "audit_limits::MAX_MAX_ENTRIES"
+    ))]
+    pub max_entries: usize,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            file: None,
+            max_entries: default_audit_max_entries(),
         }
     }
 }
@@ -0,0 +1,200 @@
+use serde_yaml::{Mapping, Value};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+use tracing::debug;
+
+use super::ConfigFormat;
+use crate::{error::AppError, r#const::config_dir};
+
+/// 读取配置目录中的全部配置文件（`*.yaml`、`*.yml`、`*.toml`、`*.json`），合并为一个配置文档
+///
+/// 每个文件按扩展名识别格式，文件按名称排序后依次合并，结果与文件的创建顺序无关：
+/// - `upstreams`、`upstream_groups`、`models` 和 `http_server.forwards` 按顺序拼接，名称重复时报错
+/// - 其他配置段（如 `http_server.admin`）只能在一个文件中定义
+///
+/// 隐藏文件和子目录被忽略，例如 Kubernetes ConfigMap 挂载目录中的 `..data`
+pub fn merge_directory(dir: &Path) -> Result<Value, AppError> {
+    let files = config_files(dir)?;
+    if files.is_empty() {
+        return Err(AppError::Config(format!(
+            "No configuration files (*.yaml, *.yml, *.toml, *.json) found in directory {:?}",
+            dir
+        )));
+    }
+
+    let mut merger = Merger::default();
+    for file in &files {
+        debug!("Merging configuration file: {:?}", file);
+        let content = std::fs::read_to_string(file).map_err(|e| {
+            AppError::Config(format!(
+                "Unable to read configuration file {:?}: {}",
+                file, e
+            ))
+        })?;
+        let document: Option<Mapping> = ConfigFormat::from_path(file).parse(file, &content)?;
+        merger.merge(&file_name(file), document.unwrap_or_default())?;
+    }
+
+    Ok(merger.into_document())
+}
+
+// 列出目录中参与合并的配置文件，按文件名排序
+fn config_files(dir: &Path) -> Result<Vec<PathBuf>, AppError> {
+    let entries = std::fs::read_dir(dir).map_err(|e| {
+        AppError::Config(format!(
+            "Unable to read configuration directory {:?}: {}",
+            dir, e
+        ))
+    })?;
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|e| {
+                AppError::Config(format!(
+                    "Unable to read configuration directory {:?}: {}",
+                    dir, e
+                ))
+            })?
+            .path();
+        let hidden = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_none_or(|name| name.starts_with('.'));
+        if !hidden && path.is_file() && ConfigFormat::from_extension(&path).is_some() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[inline(always)]
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+// 合并状态
+#[derive(Default)]
+struct Merger {
+    // 只能定义一次的顶层配置段
+    sections: Mapping,
+    // 只能定义一次的 HTTP 服务器设置
+    http_server: Option<Mapping>,
+    // 按名称拼接的列表，键为配置路径，如 "upstreams" 或 "http_server.forwards"
+    lists: HashMap<String, Vec<Value>>,
+    // 已定义的配置段及其所在文件，如 "upstreams.openai" 或 "http_server.admin"
+    defined: HashMap<String, String>,
+}
+
+impl Merger {
+    // 合并一个配置文件
+    fn merge(&mut self, file: &str, document: Mapping) -> Result<(), AppError> {
+        for (key, value) in document {
+            let Some(section) = key.as_str() else {
+                continue;
+            };
+
+            if config_dir::LIST_SECTIONS.contains(&section) {
+                self.append(file, section, value)?;
+            } else if section == config_dir::HTTP_SERVER {
+                self.merge_http_server(file, value)?;
+            } else {
+                self.define(file, section)?;
+                self.sections.insert(key, value);
+            }
+        }
+        Ok(())
+    }
+
+    // 合并 HTTP 服务器配置段，转发服务按名称拼接，其他设置只能定义一次
+    fn merge_http_server(&mut self, file: &str, value: Value) -> Result<(), AppError> {
+        let http_server = match value {
+            Value::Mapping(http_server) => http_server,
+            Value::Null => Mapping::new(),
+            _ => {
+                return Err(AppError::Config(format!(
+                    "{}: {} must be a mapping",
+                    file,
+                    config_dir::HTTP_SERVER
+                )))
+            }
+        };
+
+        for (key, value) in http_server {
+            let Some(name) = key.as_str() else {
+                continue;
+            };
+            let path = format!("{}.{}", config_dir::HTTP_SERVER, name);
+            if name == config_dir::FORWARDS {
+                self.append(file, &path, value)?;
+            } else {
+                self.define(file, &path)?;
+                self.http_server
+                    .get_or_insert_with(Mapping::new)
+                    .insert(key, value);
+            }
+        }
+        Ok(())
+    }
+
+    // 将列表元素追加到合并结果，名称在任何文件中重复时报错
+    fn append(&mut self, file: &str, path: &str, value: Value) -> Result<(), AppError> {
+        let items = match value {
+            Value::Sequence(items) => items,
+            Value::Null => Vec::new(),
+            _ => {
+                return Err(AppError::Config(format!(
+                    "{}: {} must be a list",
+                    file, path
+                )))
+            }
+        };
+
+        for item in items {
+            // 缺少名称的元素交给配置校验报告
+            if let Some(name) = item.get("name").and_then(Value::as_str) {
+                self.define(file, &format!("{}.{}", path, name))?;
+            }
+            self.lists.entry(path.to_string()).or_default().push(item);
+        }
+        Ok(())
+    }
+
+    // 记录配置段所在的文件，已在其他文件中定义时报错
+    fn define(&mut self, file: &str, path: &str) -> Result<(), AppError> {
+        match self.defined.insert(path.to_string(), file.to_string()) {
+            Some(previous) => Err(AppError::Config(format!(
+                "{} is defined in both {} and {}",
+                path, previous, file
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    // 生成合并后的配置文档
+    fn into_document(self) -> Value {
+        let mut document = self.sections;
+        let mut http_server = self.http_server;
+        for (path, items) in self.lists {
+            if path.starts_with(config_dir::HTTP_SERVER) {
+                http_server
+                    .get_or_insert_with(Mapping::new)
+                    .insert(Value::from(config_dir::FORWARDS), Value::Sequence(items));
+            } else {
+                document.insert(Value::from(path), Value::Sequence(items));
+            }
+        }
+        if let Some(http_server) = http_server {
+            document.insert(
+                Value::from(config_dir::HTTP_SERVER),
+                Value::Mapping(http_server),
+            );
+        }
+        Value::Mapping(document)
+    }
+}
@@ -1,49 +1,131 @@
 pub mod common;
 pub mod defaults;
+pub mod discovery;
+pub mod format;
 pub mod http_client;
 pub mod http_server;
+pub mod merge;
+pub mod model;
+pub mod schema;
 pub mod serializer;
+pub mod source;
 pub mod upstream;
 pub mod upstream_group;
 pub mod validation;
+pub mod watch;
 
 use crate::error::AppError;
-pub use common::{BreakerConfig, ProxyConfig, RateLimitConfig, RetryConfig, TimeoutConfig};
-pub use http_client::{HttpClientConfig, HttpClientTimeoutConfig};
-pub use http_server::{AdminConfig, ForwardConfig, HttpServerConfig};
+use crate::secret;
+pub use common::{
+    AdaptiveConfig, BreakerConfig, BreakerWindowConfig, BreakerWindowType, ProxyConfig,
+    RateLimitConfig, RateLimitKey, RedisConfig, RetryConfig, TimeoutConfig,
+};
+pub use discovery::{
+    ConsulDiscoveryConfig, DnsDiscoveryConfig, DnsRecordType, GroupDiscoveryConfig,
+    GroupDiscoveryType, KubernetesDiscoveryConfig,
+};
+pub use format::ConfigFormat;
+pub use http_client::{
+    Http2Config, HttpClientConfig, HttpClientTimeoutConfig, HttpVersion, TlsConfig, TlsVersion,
+};
+pub use http_server::{
+    AccessLogConfig, AccessLogFormat, AdminConfig, AdminTlsConfig, AlertMetric, AlertRuleConfig,
+    AlertWebhookConfig, AlertsConfig, AuditConfig, AuditSinkConfig, BudgetConfig, CacheBackend,
+    CacheConfig, ClientBudgetConfig, ClientTokenLimitConfig, CompressionAlgorithm,
+    CompressionConfig, ErrorResponseConfig, ErrorResponseFormat, FairQueueConfig, ForwardConfig,
+    HttpServerConfig, ListenerConfig, LoadSheddingConfig, MetricsConfig, MetricsExportConfig,
+    MetricsExportProtocol, MetricsLabelsConfig, MetricsUpstreamLabel, MiddlewareStage,
+    ParamLimitAction, ParamLimitsConfig, PiiDetector, PiiPatternConfig, PiiRedactionConfig,
+    PluginConfig, PolicyConfig, PolicyFailMode, PolicyPayload, QueueConfig, QueueTierConfig,
+    RequestHeadersConfig, RequestPriority, TokenLimitConfig, WebSocketConfig,
+};
+pub use model::ModelAlias;
 use reqwest::header::{HeaderName, HeaderValue};
+pub use schema::json_schema;
 use serde::{Deserialize, Serialize};
+pub use source::ConfigSource;
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
 use tracing::debug;
-pub use upstream::{AuthConfig, AuthType, HeaderOp, HeaderOpType, UpstreamConfig};
-pub use upstream_group::{BalanceConfig, BalanceStrategy, UpstreamGroupConfig, UpstreamRef};
+pub use upstream::{
+    AuthConfig, AuthType, BodyTransformConfig, Dialect, ExternalAuthConfig, HeaderOp, HeaderOpType,
+    ModelPriceConfig, OAuth2Config, OAuth2Grant, PathRewriteConfig, QueryParamOp,
+    StreamNormalizeConfig, SystemPromptConfig, SystemPromptMode, UpstreamConfig, WarmupConfig,
+};
+pub use upstream_group::{
+    BalanceConfig, BalanceStrategy, StickyConfig, UpstreamGroupConfig, UpstreamRef,
+};
 use utoipa::ToSchema;
-use validator::Validate;
+use validator::{Validate, ValidationErrors};
+pub use watch::FileWatcher;
 
 // 配置文件结构
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
-#[validate(schema(function = "validation::validate_config"))]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Config {
     // HTTP服务器配置
     #[serde(default)]
-    #[validate(nested)]
     pub http_server: Option<HttpServerConfig>,
     // 上游定义
     #[serde(default)]
-    #[validate(nested)]
     pub upstreams: Vec<UpstreamConfig>,
     // 上游组定义
     #[serde(default)]
-    #[validate(nested)]
     pub upstream_groups: Vec<UpstreamGroupConfig>,
+    // 模型别名目录
+    #[serde(default)]
+    pub models: Vec<ModelAlias>,
+}
+
+// 手动实现校验，字段有错误时仍然检查引用关系，并列出全部引用错误
+impl Validate for Config {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if let Some(http_server) = &self.http_server {
+            errors.merge_self("http_server", http_server.validate());
+        }
+        errors.merge_self("upstreams", self.upstreams.validate());
+        errors.merge_self("upstream_groups", self.upstream_groups.validate());
+        errors.merge_self("models", self.models.validate());
+
+        if let Err(references) = validation::validate_config(self) {
+            for error in references {
+                errors.add("__all__", error);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
 }
 
 impl Config {
-    // 从文件加载配置
+    // 从文件加载配置，按扩展名识别格式，路径为目录时合并目录中的全部配置文件
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, AppError> {
+        Self::from_file_with_format(path, None)
+    }
+
+    // 从文件加载配置，未指定格式时按扩展名识别
+    // 路径为目录时合并目录中的全部配置文件，每个文件按扩展名识别格式
+    pub fn from_file_with_format<P: AsRef<Path>>(
+        path: P,
+        format: Option<ConfigFormat>,
+    ) -> Result<Self, AppError> {
         let path = path.as_ref();
+        if path.is_dir() {
+            debug!(
+                "Attempting to load configuration from directory: {:?}",
+                path
+            );
+            let document = merge::merge_directory(path)?;
+            let config = serde_yaml::from_value(document).map_err(|e| {
+                AppError::Config(format!("Configuration file parsing error: {}", e))
+            })?;
+            return Self::prepare(config);
+        }
         debug!("Attempting to load configuration from file: {:?}", path);
 
         // 打开并读取文件
@@ -62,24 +144,100 @@ impl Config {
             ))
         })?;
 
-        // 解析YAML
-        let mut config: Config = serde_yaml::from_str(&content)
-            .map_err(|e| AppError::Config(format!("Configuration file parsing error: {}", e)))?;
+        // 解析配置文档
+        let format = format.unwrap_or_else(|| ConfigFormat::from_path(path));
+        Self::from_content(&content, format, path)
+    }
+
+    // 解析并验证配置内容，origin 为配置的来源，用于错误消息
+    pub fn from_content(
+        content: &str,
+        format: ConfigFormat,
+        origin: &Path,
+    ) -> Result<Self, AppError> {
+        Self::prepare(format.parse(origin, content)?)
+    }
 
+    // 预处理并验证解析后的配置
+    fn prepare(mut config: Config) -> Result<Self, AppError> {
         // 预处理配置
         config.post_process()?;
 
-        // 验证配置
-        config
-            .validate()
-            .map_err(|e| AppError::Config(format!("Configuration validation error: {}", e)))?;
+        // 验证配置，一次列出全部错误
+        config.validate().map_err(|e| {
+            let messages = validation::error_messages(&e);
+            AppError::Config(format!(
+                "Configuration validation failed with {} errors:\n  - {}",
+                messages.len(),
+                messages.join("\n  - ")
+            ))
+        })?;
 
         Ok(config)
     }
 
+    // 上游认证引用的全部密钥文件
+    pub fn secret_files(&self) -> Vec<&str> {
+        self.upstreams
+            .iter()
+            .filter_map(|upstream| upstream.auth.as_ref())
+            .flat_map(|auth| {
+                let oauth2 = auth.oauth2.as_ref();
+                [
+                    auth.token_file.as_deref(),
+                    auth.password_file.as_deref(),
+                    oauth2.and_then(|oauth2| oauth2.client_secret_file.as_deref()),
+                    oauth2.and_then(|oauth2| oauth2.service_account_file.as_deref()),
+                ]
+            })
+            .flatten()
+            .collect()
+    }
+
     // 预处理配置，例如预解析头部
     pub fn post_process(&mut self) -> Result<(), AppError> {
+        // 预读取密钥文件，启动或应用配置时尽早发现无法读取的文件
+        for path in self.secret_files() {
+            secret::read_secret_file(path)?;
+        }
+
+        // 校验上游组的 User-Agent
+        for group in &self.upstream_groups {
+            if let Some(agent) = &group.http_client.agent {
+                HeaderValue::from_str(agent).map_err(|e| {
+                    AppError::InvalidHeader(format!(
+                        "Invalid agent '{}' for upstream group '{}': {}",
+                        agent, group.name, e
+                    ))
+                })?;
+            }
+        }
+
         for upstream in &mut self.upstreams {
+            // 校验上游的 User-Agent 和默认请求头
+            if let Some(agent) = &upstream.agent {
+                HeaderValue::from_str(agent).map_err(|e| {
+                    AppError::InvalidHeader(format!(
+                        "Invalid agent '{}' for upstream '{}': {}",
+                        agent, upstream.name, e
+                    ))
+                })?;
+            }
+            for (key, value) in &upstream.default_headers {
+                HeaderName::from_bytes(key.as_bytes()).map_err(|e| {
+                    AppError::InvalidHeader(format!(
+                        "Invalid default header name '{}' for upstream '{}': {}",
+                        key, upstream.name, e
+                    ))
+                })?;
+                HeaderValue::from_str(value).map_err(|e| {
+                    AppError::InvalidHeader(format!(
+                        "Invalid default header value for key '{}' in upstream '{}': {}",
+                        key, upstream.name, e
+                    ))
+                })?;
+            }
+
             for op in &mut upstream.headers {
                 // 预解析头部名称
                 let name = HeaderName::from_bytes(op.key.as_bytes()).map_err(|e| {
@@ -90,8 +248,8 @@ impl Config {
                 })?;
                 op.parsed_name = Some(name);
 
-                // 预解析头部值
-                if let Some(value_str) = &op.value {
+                // 预解析头部值，包含占位符的值在请求时展开
+                if let Some(value_str) = op.value.as_ref().filter(|_| !op.is_template()) {
                     let value = HeaderValue::from_str(value_str).map_err(|e| {
                         AppError::InvalidHeader(format!(
                             "Invalid header value for key '{}' in upstream '{}': {}",
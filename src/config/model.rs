@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+// 模型别名配置
+// 请求体中的 model 字段命中别名时，请求转发到对应的上游组，并改写为服务商的模型名称
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "lowercase")]
+pub struct ModelAlias {
+    // 虚拟模型名称（客户端请求中使用的名称）
+    #[validate(length(min = 1, message = "Model alias name cannot be empty"))]
+    pub name: String,
+    // 目标上游组
+    #[validate(length(min = 1, message = "Model alias group cannot be empty"))]
+    pub group: String,
+    // 服务商的模型名称
+    #[validate(length(min = 1, message = "Model alias provider model cannot be empty"))]
+    pub model: String,
+}
@@ -0,0 +1,55 @@
+use serde_json::{json, Map, Value};
+use utoipa::{PartialSchema, ToSchema};
+
+use super::Config;
+use crate::error::AppError;
+
+// OpenAPI 组件的引用前缀
+const COMPONENTS_PREFIX: &str = "#/components/schemas/";
+// JSON Schema 定义的引用前缀
+const DEFS_PREFIX: &str = "#/$defs/";
+
+/// 生成配置文件的 JSON Schema（Draft 2020-12）
+///
+/// 由配置结构的 OpenAPI 模型转换而来，嵌套的配置类型放在 `$defs` 中
+pub fn json_schema() -> Result<Value, AppError> {
+    let mut components = Vec::new();
+    Config::schemas(&mut components);
+
+    let mut defs = Map::new();
+    for (name, schema) in components {
+        defs.insert(name, serde_json::to_value(schema)?);
+    }
+
+    let mut schema = serde_json::to_value(Config::schema())?;
+    if let Value::Object(ref mut root) = schema {
+        root.insert(
+            "$schema".to_string(),
+            json!("https://json-schema.org/draft/2020-12/schema"),
+        );
+        root.insert("title".to_string(), json!("LLMProxy configuration"));
+        root.insert("$defs".to_string(), Value::Object(defs));
+    }
+    rewrite_refs(&mut schema);
+    Ok(schema)
+}
+
+// 将 OpenAPI 组件引用改写为 JSON Schema 定义引用
+fn rewrite_refs(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                match value {
+                    Value::String(target) if key == "$ref" => {
+                        if let Some(name) = target.strip_prefix(COMPONENTS_PREFIX) {
+                            *target = format!("{}{}", DEFS_PREFIX, name);
+                        }
+                    }
+                    _ => rewrite_refs(value),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(rewrite_refs),
+        _ => {}
+    }
+}
@@ -0,0 +1,488 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures_util::StreamExt;
+use reqwest::{Client, RequestBuilder, StatusCode, Url};
+use serde::Deserialize;
+use serde_json::json;
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tracing::{debug, warn};
+
+use super::{Config, ConfigFormat};
+use crate::{error::AppError, r#const::config_source};
+
+/// 配置来源
+///
+/// 本地文件或目录，或者 etcd（v3 HTTP/JSON 网关）、Consul KV 中的一个键。
+/// 远程来源的地址为 `etcd://host[:port]/key` 或 `consul://host[:port]/key`，
+/// 使用 HTTPS 时协议为 `etcd+https`、`consul+https`
+#[derive(Debug, Clone)]
+pub enum ConfigSource {
+    /// 本地文件或目录
+    File(PathBuf),
+    /// 远程键值存储中的键
+    Remote(Arc<RemoteSource>),
+}
+
+impl ConfigSource {
+    /// 按地址创建配置来源，不是 etcd 或 Consul 地址时视为本地路径
+    pub fn from_location(location: &Path) -> Result<Self, AppError> {
+        let remote = location
+            .to_str()
+            .and_then(|location| Some((location, RemoteBackend::from_location(location)?)));
+        match remote {
+            Some((location, (backend, https))) => Ok(Self::Remote(Arc::new(RemoteSource::new(
+                backend, https, location,
+            )?))),
+            None => Ok(Self::File(location.to_path_buf())),
+        }
+    }
+
+    /// 是否为远程配置来源
+    pub fn is_remote(&self) -> bool {
+        matches!(self, Self::Remote(_))
+    }
+
+    /// 加载并验证配置，未指定格式时按文件或键的扩展名识别
+    pub async fn load(&self, format: Option<ConfigFormat>) -> Result<Config, AppError> {
+        match self {
+            Self::File(path) => Config::from_file_with_format(path, format),
+            Self::Remote(remote) => {
+                let content = remote.fetch().await?;
+                let format =
+                    format.unwrap_or_else(|| ConfigFormat::from_path(Path::new(&remote.key)));
+                Config::from_content(&content, format, Path::new(&remote.location))
+            }
+        }
+    }
+
+    /// 等待配置发生变化，本地文件或目录永不返回
+    ///
+    /// 监听失败时按间隔重试，直到检测到变化
+    pub async fn changed(&self) {
+        let Self::Remote(remote) = self else {
+            return std::future::pending().await;
+        };
+        loop {
+            match remote.watch().await {
+                Ok(true) => return,
+                Ok(false) => {}
+                Err(e) => {
+                    warn!(
+                        "Failed to watch configuration in {}: {}, retrying in {}s",
+                        remote.location,
+                        e,
+                        config_source::RETRY_INTERVAL
+                    );
+                    tokio::time::sleep(Duration::from_secs(config_source::RETRY_INTERVAL)).await;
+                }
+            }
+        }
+    }
+}
+
+// 路径总是视为本地文件或目录
+impl<P: AsRef<Path>> From<P> for ConfigSource {
+    fn from(path: P) -> Self {
+        Self::File(path.as_ref().to_path_buf())
+    }
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::Remote(remote) => f.write_str(&remote.location),
+        }
+    }
+}
+
+// 远程键值存储类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RemoteBackend {
+    Etcd,
+    Consul,
+}
+
+impl RemoteBackend {
+    // 按地址协议识别存储类型，返回类型和是否使用 HTTPS
+    fn from_location(location: &str) -> Option<(Self, bool)> {
+        let (scheme, _) = location.split_once("://")?;
+        match scheme.to_ascii_lowercase().as_str() {
+            config_source::ETCD => Some((Self::Etcd, false)),
+            config_source::ETCD_HTTPS => Some((Self::Etcd, true)),
+            config_source::CONSUL => Some((Self::Consul, false)),
+            config_source::CONSUL_HTTPS => Some((Self::Consul, true)),
+            _ => None,
+        }
+    }
+
+    // 默认端口
+    fn default_port(self) -> u16 {
+        match self {
+            Self::Etcd => config_source::ETCD_DEFAULT_PORT,
+            Self::Consul => config_source::CONSUL_DEFAULT_PORT,
+        }
+    }
+}
+
+/// 远程键值存储中的配置
+///
+/// 记录最近一次读取或监听到的版本（etcd 的 revision、Consul 的索引），
+/// 监听从该版本之后开始，读取配置和监听之间的修改不会丢失
+pub struct RemoteSource {
+    // 存储类型
+    backend: RemoteBackend,
+    // 配置来源地址，用于日志和错误消息
+    location: String,
+    // 存储的 HTTP 地址
+    endpoint: Url,
+    // 配置所在的键
+    key: String,
+    // HTTP 客户端
+    client: Client,
+    // 最近一次读取或监听到的版本
+    version: AtomicU64,
+}
+
+impl fmt::Debug for RemoteSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteSource")
+            .field("location", &self.location)
+            .field("version", &self.version.load(Ordering::Acquire))
+            .finish()
+    }
+}
+
+impl RemoteSource {
+    // 解析远程配置来源地址
+    fn new(backend: RemoteBackend, https: bool, location: &str) -> Result<Self, AppError> {
+        let invalid = |reason: &str| {
+            AppError::Config(format!(
+                "Invalid configuration source {:?}: {}",
+                location, reason
+            ))
+        };
+
+        let url = Url::parse(location).map_err(|e| invalid(&e.to_string()))?;
+        let host = url.host_str().ok_or_else(|| invalid("missing host"))?;
+        if !url.username().is_empty() || url.password().is_some() {
+            return Err(invalid(
+                "credentials must be set through environment variables, not in the address",
+            ));
+        }
+        let key = url.path().trim_start_matches('/');
+        if key.is_empty() {
+            return Err(invalid("missing key"));
+        }
+        // etcd 的键通常以 / 开头，Consul 的键不以 / 开头
+        let key = match backend {
+            RemoteBackend::Etcd => url.path().to_string(),
+            RemoteBackend::Consul => key.to_string(),
+        };
+
+        let scheme = if https { "https" } else { "http" };
+        let port = url.port().unwrap_or(backend.default_port());
+        let endpoint = Url::parse(&format!("{}://{}:{}", scheme, host, port))
+            .map_err(|e| invalid(&e.to_string()))?;
+
+        let client = Client::builder()
+            .connect_timeout(Duration::from_secs(config_source::REQUEST_TIMEOUT))
+            .build()
+            .map_err(|e| {
+                AppError::Config(format!(
+                    "Failed to create client for configuration source {:?}: {}",
+                    location, e
+                ))
+            })?;
+
+        Ok(Self {
+            backend,
+            location: location.to_string(),
+            endpoint,
+            key,
+            client,
+            version: AtomicU64::new(0),
+        })
+    }
+
+    // 读取配置内容，并记录读取到的版本
+    async fn fetch(&self) -> Result<String, AppError> {
+        debug!("Attempting to load configuration from {}", self.location);
+        match self.backend {
+            RemoteBackend::Etcd => self.etcd_fetch().await,
+            RemoteBackend::Consul => self.consul_fetch().await,
+        }
+    }
+
+    // 等待一次配置变化，检测到变化时返回 true，监听超时返回 false
+    async fn watch(&self) -> Result<bool, AppError> {
+        let result = match self.backend {
+            RemoteBackend::Etcd => self.etcd_watch().await,
+            RemoteBackend::Consul => self.consul_watch().await,
+        };
+        match result {
+            Err(AppError::HttpError(e)) if e.is_timeout() => Ok(false),
+            result => result,
+        }
+    }
+
+    // 存储中的请求地址
+    fn url(&self, path: &str) -> Url {
+        let mut url = self.endpoint.clone();
+        url.set_path(path);
+        url
+    }
+
+    // 发送 etcd 请求，设置了 ETCDCTL_USER 时先认证并附加令牌
+    async fn etcd_request(&self, path: &str) -> Result<RequestBuilder, AppError> {
+        let request = self.client.post(self.url(path));
+        let Ok(user) = std::env::var(config_source::ETCD_USER_ENV) else {
+            return Ok(request);
+        };
+        let (name, password) = match user.split_once(':') {
+            Some((name, password)) => (name.to_string(), password.to_string()),
+            None => (
+                user.clone(),
+                std::env::var(config_source::ETCD_PASSWORD_ENV).unwrap_or_default(),
+            ),
+        };
+
+        let response: EtcdAuthResponse = self
+            .client
+            .post(self.url("/v3/auth/authenticate"))
+            .timeout(Duration::from_secs(config_source::REQUEST_TIMEOUT))
+            .json(&json!({ "name": name, "password": password }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(request.header(reqwest::header::AUTHORIZATION, response.token))
+    }
+
+    // 读取 etcd 中的键
+    async fn etcd_fetch(&self) -> Result<String, AppError> {
+        let response: EtcdRangeResponse = self
+            .etcd_request("/v3/kv/range")
+            .await?
+            .timeout(Duration::from_secs(config_source::REQUEST_TIMEOUT))
+            .json(&json!({ "key": STANDARD.encode(&self.key) }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let value = response
+            .kvs
+            .into_iter()
+            .next()
+            .map(|kv| kv.value)
+            .ok_or_else(|| {
+                AppError::Config(format!("Key {:?} not found in {}", self.key, self.location))
+            })?;
+        let content = STANDARD
+            .decode(value)
+            .ok()
+            .and_then(|value| String::from_utf8(value).ok())
+            .ok_or_else(|| {
+                AppError::Config(format!(
+                    "Value of key {:?} in {} is not valid UTF-8",
+                    self.key, self.location
+                ))
+            })?;
+
+        self.version
+            .store(revision(&response.header.revision), Ordering::Release);
+        Ok(content)
+    }
+
+    // 从已读取的版本之后监听 etcd 中的键，收到修改或删除事件时返回 true
+    async fn etcd_watch(&self) -> Result<bool, AppError> {
+        let start = self.version.load(Ordering::Acquire) + 1;
+        let mut stream = self
+            .etcd_request("/v3/watch")
+            .await?
+            .timeout(Duration::from_secs(config_source::WATCH_TIMEOUT))
+            .json(&json!({
+                "create_request": {
+                    "key": STANDARD.encode(&self.key),
+                    "start_revision": start.to_string(),
+                }
+            }))
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes_stream();
+
+        // 网关以换行分隔流式返回的每个监听响应
+        let mut buffer = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            buffer.extend_from_slice(&chunk?);
+            while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+                let message: EtcdWatchMessage = serde_json::from_slice(&line)?;
+                if let Some(error) = message.error {
+                    return Err(AppError::Config(format!(
+                        "Failed to watch key {:?} in {}: {}",
+                        self.key, self.location, error.message
+                    )));
+                }
+                let Some(result) = message.result else {
+                    continue;
+                };
+                // 起始版本已被压缩时无法得知期间的修改，按已变化处理并重新读取
+                if !result.events.is_empty() || revision(&result.compact_revision) > 0 {
+                    self.version
+                        .store(revision(&result.header.revision), Ordering::Release);
+                    return Ok(true);
+                }
+                if result.canceled {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    // 读取 Consul 中的键
+    async fn consul_fetch(&self) -> Result<String, AppError> {
+        let response = self
+            .consul_request("raw")
+            .timeout(Duration::from_secs(config_source::REQUEST_TIMEOUT))
+            .send()
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(AppError::Config(format!(
+                "Key {:?} not found in {}",
+                self.key, self.location
+            )));
+        }
+        let response = response.error_for_status()?;
+        let index = consul_index(&response);
+        let content = response.text().await?;
+
+        self.version.store(index, Ordering::Release);
+        Ok(content)
+    }
+
+    // 以阻塞查询等待 Consul 中的键发生变化，索引变化时返回 true
+    async fn consul_watch(&self) -> Result<bool, AppError> {
+        let version = self.version.load(Ordering::Acquire);
+        let query = format!("index={}&wait={}s", version, config_source::WATCH_TIMEOUT);
+        let response = self
+            .consul_request(&query)
+            // Consul 在等待时间上附加少量随机抖动
+            .timeout(Duration::from_secs(
+                config_source::WATCH_TIMEOUT + config_source::REQUEST_TIMEOUT,
+            ))
+            .send()
+            .await?;
+        // 键被删除时返回 404，同样视为变化，重新读取时报告错误
+        if response.status() != StatusCode::NOT_FOUND {
+            response.error_for_status_ref()?;
+        }
+
+        let index = consul_index(&response);
+        if index == version {
+            return Ok(false);
+        }
+        self.version.store(index, Ordering::Release);
+        Ok(true)
+    }
+
+    // 创建 Consul KV 请求，设置了 CONSUL_HTTP_TOKEN 时附加令牌
+    fn consul_request(&self, query: &str) -> RequestBuilder {
+        let mut url = self.url(&format!("/v1/kv/{}", self.key));
+        url.set_query(Some(query));
+        let request = self.client.get(url);
+        match std::env::var(config_source::CONSUL_TOKEN_ENV) {
+            Ok(token) => request.header(config_source::CONSUL_TOKEN_HEADER, token),
+            Err(_) => request,
+        }
+    }
+}
+
+// 解析 etcd 网关以字符串表示的 int64 版本号
+fn revision(value: &str) -> u64 {
+    value.parse().unwrap_or_default()
+}
+
+// 读取 Consul 响应的索引
+pub(crate) fn consul_index(response: &reqwest::Response) -> u64 {
+    response
+        .headers()
+        .get(config_source::CONSUL_INDEX_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_default()
+}
+
+// etcd 响应头
+#[derive(Debug, Default, Deserialize)]
+struct EtcdHeader {
+    #[serde(default)]
+    revision: String,
+}
+
+// etcd 认证响应
+#[derive(Debug, Deserialize)]
+struct EtcdAuthResponse {
+    token: String,
+}
+
+// etcd 读取响应
+#[derive(Debug, Deserialize)]
+struct EtcdRangeResponse {
+    #[serde(default)]
+    header: EtcdHeader,
+    #[serde(default)]
+    kvs: Vec<EtcdKeyValue>,
+}
+
+// etcd 键值
+#[derive(Debug, Deserialize)]
+struct EtcdKeyValue {
+    // 值为空时网关省略该字段
+    #[serde(default)]
+    value: String,
+}
+
+// etcd 监听流中的一条消息
+#[derive(Debug, Deserialize)]
+struct EtcdWatchMessage {
+    #[serde(default)]
+    result: Option<EtcdWatchResult>,
+    #[serde(default)]
+    error: Option<EtcdError>,
+}
+
+// etcd 监听响应
+#[derive(Debug, Deserialize)]
+struct EtcdWatchResult {
+    #[serde(default)]
+    header: EtcdHeader,
+    #[serde(default)]
+    events: Vec<serde_json::Value>,
+    #[serde(default)]
+    canceled: bool,
+    #[serde(default)]
+    compact_revision: String,
+}
+
+// etcd 网关错误
+#[derive(Debug, Deserialize)]
+struct EtcdError {
+    #[serde(default)]
+    message: String,
+}
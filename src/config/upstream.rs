@@ -1,16 +1,36 @@
-use crate::config::common::BreakerConfig;
-use crate::config::defaults::default_weight;
+use crate::config::common::{AdaptiveConfig, BreakerConfig};
+use crate::config::defaults::{
+    default_external_auth_refresh_interval, default_external_auth_timeout,
+    default_oauth2_refresh_before, default_upstream_enabled, default_upstream_proxy,
+    default_warmup_method, default_warmup_requests, default_warmup_timeout, default_weight,
+};
 use crate::config::serializer::SerializableArcString;
 use crate::config::validation;
+use crate::error::AppError;
+use crate::r#const::{
+    external_auth, header_placeholders, oauth2, unix_socket, usage_limits, warmup_limits,
+};
+use crate::redact;
+use crate::secret;
+use crate::upstream::split_unix_socket_url;
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
 use reqwest::header::{HeaderName, HeaderValue};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
 use utoipa::ToSchema;
 use validator::{Validate, ValidationError};
 
+use super::discovery::DnsDiscoveryConfig;
 use super::http_client::HttpClientConfig;
 
 /// 上游服务配置
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_upstream_discovery",
+    skip_on_field_errors = false
+))]
 #[serde(rename_all = "lowercase")]
 pub struct UpstreamConfig {
     // 上游服务名称
@@ -36,25 +56,162 @@ pub struct UpstreamConfig {
     #[serde(default)]
     #[validate(nested)]
     pub headers: Vec<HeaderOp>,
+    // 发送到该上游的 User-Agent，覆盖上游组的 http_client.agent
+    #[serde(default)]
+    pub agent: Option<String>,
+    // 默认请求头，只在客户端请求没有该头部时添加
+    #[serde(default)]
+    pub default_headers: BTreeMap<String, String>,
+    // 查询参数操作
+    #[serde(default)]
+    #[validate(nested)]
+    pub query_params: Vec<QueryParamOp>,
     // 熔断器配置
     #[serde(default)]
     #[validate(nested)]
     pub breaker: Option<BreakerConfig>,
+    // 自适应并发配置，未配置时不限制并发
+    #[serde(default)]
+    #[validate(nested)]
+    pub adaptive: Option<AdaptiveConfig>,
+    // 路由提示（如区域或分片），与上游组 sticky.hint_header 返回的值匹配
+    #[serde(default)]
+    pub hint: Option<String>,
+    // 是否启用，禁用（维护/排空）时所有负载均衡器跳过该上游，正在处理的请求不受影响
+    #[serde(default = "default_upstream_enabled")]
+    pub enabled: bool,
+    // 是否使用上游组配置的代理，false 时直连（同时忽略系统代理环境变量）
+    #[serde(default = "default_upstream_proxy")]
+    pub proxy: bool,
+    // 请求路径重写规则，未配置时使用 url 中的路径
+    #[serde(default)]
+    #[validate(nested)]
+    pub path: Option<PathRewriteConfig>,
+    // JSON 请求体转换规则，如改写模型名称、补充默认参数
+    #[serde(default)]
+    #[validate(nested)]
+    pub body_transform: Option<BodyTransformConfig>,
+    // 上游 API 格式，非 openai 时在 OpenAI chat-completions 格式与上游格式之间转换请求和响应
+    #[serde(default)]
+    pub dialect: Option<Dialect>,
+    // 事件流响应规范化规则，未配置时事件流原样转发（API 格式转换除外）
+    #[serde(default)]
+    #[validate(nested)]
+    pub stream_normalize: Option<StreamNormalizeConfig>,
+    // 模型价格表，按响应中的 token 用量统计费用
+    #[serde(default)]
+    #[validate(nested)]
+    pub pricing: Vec<ModelPriceConfig>,
+    // DNS 服务发现配置，启用后 URL 主机名解析出的每个地址都作为一个负载均衡目标
+    #[serde(default)]
+    #[validate(nested)]
+    pub dns: Option<DnsDiscoveryConfig>,
+    // 预热请求配置，启动时和熔断器恢复后向上游发送预热请求
+    #[serde(default)]
+    #[validate(nested)]
+    pub warmup: Option<WarmupConfig>,
+}
+
+impl UpstreamConfig {
+    // 返回令牌、密码、私钥密码以及请求头和查询参数的值已脱敏的副本，用于 API 响应
+    pub fn redacted(&self) -> Self {
+        Self {
+            auth: self.auth.as_ref().map(AuthConfig::redacted),
+            http_client: self.http_client.redacted(),
+            headers: self
+                .headers
+                .iter()
+                .map(|op| HeaderOp {
+                    value: redact::mask(&op.value),
+                    parsed_value: None,
+                    ..op.clone()
+                })
+                .collect(),
+            default_headers: redact::mask_values(&self.default_headers),
+            query_params: self
+                .query_params
+                .iter()
+                .map(|op| QueryParamOp {
+                    value: redact::mask(&op.value),
+                    ..op.clone()
+                })
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    // 请求中的令牌、密码和请求头、查询参数的值为脱敏占位值时（如修改 GET 返回的配置后回传），保留现有的值
+    pub fn restore_secrets(&mut self, existing: &UpstreamConfig) {
+        self.http_client.restore_secrets(&existing.http_client);
+        redact::restore_values(&mut self.default_headers, &existing.default_headers);
+        // 请求头名称不区分大小写，按名称匹配现有的操作
+        for op in self
+            .headers
+            .iter_mut()
+            .filter(|op| redact::is_masked(&op.value))
+        {
+            if let Some(current) = existing
+                .headers
+                .iter()
+                .find(|current| current.key.eq_ignore_ascii_case(&op.key))
+            {
+                op.value = current.value.clone();
+            }
+        }
+        for op in self
+            .query_params
+            .iter_mut()
+            .filter(|op| redact::is_masked(&op.value))
+        {
+            if let Some(current) = existing
+                .query_params
+                .iter()
+                .find(|current| current.key == op.key)
+            {
+                op.value = current.value.clone();
+            }
+        }
+        if let (Some(auth), Some(existing)) = (self.auth.as_mut(), existing.auth.as_ref()) {
+            if redact::is_masked(&auth.token) {
+                auth.token = existing.token.clone();
+            }
+            if redact::is_masked(&auth.password) {
+                auth.password = existing.password.clone();
+            }
+            if let (Some(oauth2), Some(existing)) = (auth.oauth2.as_mut(), existing.oauth2.as_ref())
+            {
+                if redact::is_masked(&oauth2.client_secret) {
+                    oauth2.client_secret = existing.client_secret.clone();
+                }
+            }
+        }
+    }
 }
 
 // URL 自定义验证函数
 fn validate_url(url: &SerializableArcString) -> Result<(), ValidationError> {
-    if url::Url::parse(url.as_ref()).is_err() {
+    let Ok(parsed) = url::Url::parse(url.as_ref()) else {
         let mut err = ValidationError::new("invalid_url");
         err.message = Some("Upstream URL is invalid".into());
         return Err(err);
+    };
+    if parsed.scheme() == unix_socket::SCHEME && split_unix_socket_url(&parsed).is_none() {
+        let mut err = ValidationError::new("invalid_unix_socket_url");
+        err.message = Some(
+            "Unix socket upstream URL must be unix:///path/to/socket or unix:///path/to/socket:/http/path"
+                .into(),
+        );
+        return Err(err);
     }
     Ok(())
 }
 
 // 认证配置
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
-#[validate(schema(function = "validation::validate_auth_config"))]
+#[validate(schema(
+    function = "validation::validate_auth_config",
+    skip_on_field_errors = false
+))]
 #[serde(rename_all = "lowercase")]
 pub struct AuthConfig {
     // 认证类型
@@ -63,12 +220,76 @@ pub struct AuthConfig {
     // 认证令牌（用于Bearer认证）
     #[serde(default)]
     pub token: Option<String>,
+    // 认证令牌文件路径（用于Bearer认证），与 token 二选一，文件内容会定期重新读取
+    #[serde(default)]
+    pub token_file: Option<String>,
     // 用户名（用于Basic认证）
     #[serde(default)]
     pub username: Option<String>,
     // 密码（用于Basic认证）
     #[serde(default)]
     pub password: Option<String>,
+    // 密码文件路径（用于Basic认证），与 password 二选一，文件内容会定期重新读取
+    #[serde(default)]
+    pub password_file: Option<String>,
+    // OAuth2 配置（用于OAuth2认证）
+    #[serde(default)]
+    #[validate(nested)]
+    pub oauth2: Option<OAuth2Config>,
+    // 外部命令配置（用于External认证）
+    #[serde(default)]
+    #[validate(nested)]
+    pub external: Option<ExternalAuthConfig>,
+}
+
+impl AuthConfig {
+    // 返回令牌和密码已脱敏的副本
+    pub fn redacted(&self) -> Self {
+        Self {
+            token: redact::mask(&self.token),
+            password: redact::mask(&self.password),
+            oauth2: self.oauth2.as_ref().map(|oauth2| OAuth2Config {
+                client_secret: redact::mask(&oauth2.client_secret),
+                ..oauth2.clone()
+            }),
+            ..self.clone()
+        }
+    }
+
+    // 获取认证令牌，配置了令牌文件时从文件读取
+    pub fn resolve_token(&self) -> Result<Option<String>, AppError> {
+        resolve_secret(&self.token, &self.token_file)
+    }
+
+    // 获取密码，配置了密码文件时从文件读取
+    pub fn resolve_password(&self) -> Result<Option<String>, AppError> {
+        resolve_secret(&self.password, &self.password_file)
+    }
+}
+
+// 优先使用密钥文件中的内容，否则使用内联的值
+#[inline(always)]
+fn resolve_secret(
+    inline: &Option<String>,
+    file: &Option<String>,
+) -> Result<Option<String>, AppError> {
+    match file {
+        Some(path) => secret::read_secret_file(path).map(Some),
+        None => Ok(inline.clone()),
+    }
+}
+
+// 上游 API 格式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Dialect {
+    // OpenAI chat-completions，不做转换
+    #[serde(rename = "openai")]
+    OpenAI,
+    // Anthropic Messages API
+    Anthropic,
+    // Google Gemini generateContent API
+    Gemini,
 }
 
 // 认证类型
@@ -79,6 +300,11 @@ pub enum AuthType {
     Bearer,
     // 基本认证
     Basic,
+    // OAuth2 认证，自动获取并刷新访问令牌
+    #[serde(rename = "oauth2")]
+    OAuth2,
+    // 外部命令认证，定期执行命令获取令牌
+    External,
     // 无认证
     None,
 }
@@ -89,6 +315,84 @@ impl Default for AuthType {
     }
 }
 
+// OAuth2 认证配置
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_oauth2_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct OAuth2Config {
+    // 获取访问令牌的方式
+    #[serde(default)]
+    pub grant: OAuth2Grant,
+    // 令牌端点，service_account 方式默认使用密钥文件中的 token_uri
+    #[serde(default)]
+    pub token_url: Option<String>,
+    // 客户端 ID（用于 client_credentials 方式）
+    #[serde(default)]
+    pub client_id: Option<String>,
+    // 客户端密钥（用于 client_credentials 方式）
+    #[serde(default)]
+    pub client_secret: Option<String>,
+    // 客户端密钥文件路径，与 client_secret 二选一，文件内容会定期重新读取
+    #[serde(default)]
+    pub client_secret_file: Option<String>,
+    // GCP 服务账号密钥文件（JSON）路径（用于 service_account 方式）
+    #[serde(default)]
+    pub service_account_file: Option<String>,
+    // 申请的权限范围，service_account 方式默认使用 cloud-platform
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    // 在访问令牌过期前多少秒刷新
+    #[serde(default = "default_oauth2_refresh_before")]
+    #[validate(range(
+        max = "oauth2::MAX_REFRESH_BEFORE",
+        message = "OAuth2 refresh_before must be at most 3600 seconds"
+    ))]
+    pub refresh_before: u64,
+}
+
+// OAuth2 获取访问令牌的方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OAuth2Grant {
+    // 客户端凭证模式
+    #[default]
+    ClientCredentials,
+    // GCP 服务账号 JWT 模式
+    ServiceAccount,
+}
+
+// 外部命令认证配置
+// 命令的标准输出（去除首尾空白）作为 Bearer 令牌，上游返回 401 时下一次请求会重新执行命令
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "lowercase")]
+pub struct ExternalAuthConfig {
+    // 要执行的命令（不经过 shell）
+    #[validate(length(min = 1, message = "External auth command cannot be empty"))]
+    pub command: String,
+    // 命令参数
+    #[serde(default)]
+    pub args: Vec<String>,
+    // 重新执行命令的间隔（秒）
+    #[serde(default = "default_external_auth_refresh_interval")]
+    #[validate(range(
+        min = "external_auth::MIN_REFRESH_INTERVAL",
+        max = "external_auth::MAX_REFRESH_INTERVAL",
+        message = "External auth refresh_interval must be between 1 and 86400 seconds"
+    ))]
+    pub refresh_interval: u64,
+    // 命令执行超时时间（秒）
+    #[serde(default = "default_external_auth_timeout")]
+    #[validate(range(
+        min = "external_auth::MIN_TIMEOUT",
+        max = "external_auth::MAX_TIMEOUT",
+        message = "External auth timeout must be between 1 and 300 seconds"
+    ))]
+    pub timeout: u64,
+}
+
 /// HTTP 请求头操作类型
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy, ToSchema)]
 #[serde(rename_all = "lowercase")]
@@ -103,7 +407,10 @@ pub enum HeaderOpType {
 
 // 请求头操作
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
-#[validate(schema(function = "validation::validate_header_op"))]
+#[validate(schema(
+    function = "validation::validate_header_op",
+    skip_on_field_errors = false
+))]
 #[serde(rename_all = "lowercase")]
 pub struct HeaderOp {
     pub op: HeaderOpType,
@@ -115,3 +422,341 @@ pub struct HeaderOp {
     #[serde(skip)]
     pub parsed_value: Option<HeaderValue>,
 }
+
+// 请求头值中的占位符，如 ${client_ip}、${env:VAR}
+static HEADER_PLACEHOLDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"\$\{([^}]*)\}").unwrap());
+
+impl HeaderOp {
+    // 头部值是否包含需要在请求时展开的占位符
+    pub fn is_template(&self) -> bool {
+        self.value
+            .as_deref()
+            .is_some_and(|value| HEADER_PLACEHOLDER.is_match(value))
+    }
+
+    // 展开头部值中的占位符，resolve 返回 None 时替换为空字符串
+    pub fn render_value(&self, resolve: impl Fn(&str) -> Option<String>) -> Option<String> {
+        let value = self.value.as_deref()?;
+        Some(
+            HEADER_PLACEHOLDER
+                .replace_all(value, |caps: &Captures| {
+                    resolve(&caps[1]).unwrap_or_default()
+                })
+                .into_owned(),
+        )
+    }
+
+    // 头部值中不支持的占位符
+    pub fn unknown_placeholders(&self) -> Vec<&str> {
+        let Some(value) = &self.value else {
+            return vec![];
+        };
+        HEADER_PLACEHOLDER
+            .captures_iter(value)
+            .filter_map(|caps| caps.get(1))
+            .map(|name| name.as_str())
+            .filter(|name| {
+                !matches!(
+                    *name,
+                    header_placeholders::CLIENT_IP
+                        | header_placeholders::REQUEST_ID
+                        | header_placeholders::FORWARD_NAME
+                ) && name
+                    .strip_prefix(header_placeholders::ENV_PREFIX)
+                    .is_none_or(str::is_empty)
+            })
+            .collect()
+    }
+}
+
+// 查询参数操作
+// insert 仅在参数不存在时添加，replace 删除同名参数后添加，remove 删除所有同名参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_query_param_op",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct QueryParamOp {
+    pub op: HeaderOpType,
+    #[validate(length(min = 1, message = "Query parameter key cannot be empty"))]
+    pub key: String,
+    pub value: Option<String>,
+}
+
+// 路径模板中的占位符
+static PLACEHOLDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{([A-Za-z0-9_]+)\}").unwrap());
+
+// 请求路径重写配置
+// 依次执行去除前缀、添加前缀，再代入完整路径模板，结果替换上游 url 的路径（保留 url 中的查询参数）
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_path_rewrite_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct PathRewriteConfig {
+    // 去除的请求路径前缀
+    #[serde(default)]
+    pub strip_prefix: Option<String>,
+    // 添加的请求路径前缀
+    #[serde(default)]
+    pub add_prefix: Option<String>,
+    // 完整路径模板，如 /openai/deployments/{deployment}{path}，{path} 为处理前缀后的请求路径
+    #[serde(default)]
+    pub template: Option<String>,
+    // 模板中其他占位符的值
+    #[serde(default)]
+    pub params: BTreeMap<String, String>,
+}
+
+// JSON 请求体转换配置
+// 仅处理 JSON 对象请求体，其他请求体原样转发
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_body_transform_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct BodyTransformConfig {
+    // 模型名称映射，请求体 model 字段命中时替换为对应的值
+    #[serde(default)]
+    pub model_map: BTreeMap<String, String>,
+    // 默认参数，请求体中不存在对应字段时添加
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub defaults: Map<String, Value>,
+    // 注入聊天请求的系统提示词
+    #[serde(default)]
+    #[validate(nested)]
+    pub system_prompt: Option<SystemPromptConfig>,
+}
+
+// 事件流规范化配置
+// 解析 OpenAI 格式（包括 API 格式转换后）的事件流并重新编码：每个事件只保留 data 行，
+// 丢弃注释和无法解析的事件，结束标记 data: [DONE] 在流结束时发送且只发送一次
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_stream_normalize_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct StreamNormalizeConfig {
+    // 从每个事件中移除的顶层字段，如服务商特有的 x_groq、system_fingerprint
+    #[serde(default)]
+    pub strip_fields: Vec<String>,
+    // 是否汇总用量：移除各事件中的 usage，在结束标记之前发送一个只包含最终用量的事件
+    #[serde(default)]
+    pub aggregate_usage: bool,
+}
+
+// 上游预热请求配置
+// 预热请求建立连接池中的连接（包括 TLS 握手），并按响应耗时初始化响应时间感知负载均衡器的指标
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_warmup_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct WarmupConfig {
+    // 请求方法
+    #[serde(default = "default_warmup_method")]
+    pub method: String,
+    // 请求路径，替换上游 URL 中的路径，如 /v1/models；未配置时使用上游 URL
+    #[serde(default)]
+    pub path: Option<String>,
+    // JSON 请求体，如一个 max_tokens 为 1 的聊天请求
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub body: Option<Value>,
+    // 每个上游地址同时发送的预热请求数，即预先建立的连接数
+    #[serde(default = "default_warmup_requests")]
+    #[validate(range(
+        min = "warmup_limits::MIN_REQUESTS",
+        max = "warmup_limits::MAX_REQUESTS",
+        message = "Warmup requests must be between 1 and 64"
+    ))]
+    pub requests: u32,
+    // 单个预热请求的超时时间（秒）
+    #[serde(default = "default_warmup_timeout")]
+    #[validate(range(
+        min = "warmup_limits::MIN_TIMEOUT",
+        max = "warmup_limits::MAX_TIMEOUT",
+        message = "Warmup timeout must be between 1 and 300 seconds"
+    ))]
+    pub timeout: u64,
+}
+
+// 系统提示词注入配置
+// 仅处理包含 messages 数组的聊天请求，用于统一下发安全护栏等提示词
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "lowercase")]
+pub struct SystemPromptConfig {
+    // 系统提示词内容
+    #[validate(length(min = 1, message = "System prompt content cannot be empty"))]
+    pub content: String,
+    // 注入方式
+    #[serde(default)]
+    pub mode: SystemPromptMode,
+}
+
+// 模型价格配置，价格单位为美元 / 百万 token
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_model_price_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct ModelPriceConfig {
+    // 模型名称，以 * 结尾时按前缀匹配（如 gpt-4o* 匹配 gpt-4o-2024-08-06）
+    #[validate(length(min = 1, message = "Model price name cannot be empty"))]
+    pub model: String,
+    // 提示词价格
+    #[serde(default)]
+    pub prompt: f64,
+    // 生成内容价格
+    #[serde(default)]
+    pub completion: f64,
+}
+
+impl ModelPriceConfig {
+    // 查找模型价格，优先精确匹配，其次按顺序匹配第一个前缀规则
+    pub fn find<'a>(prices: &'a [ModelPriceConfig], model: &str) -> Option<&'a ModelPriceConfig> {
+        prices
+            .iter()
+            .find(|price| price.model == model)
+            .or_else(|| {
+                prices.iter().find(|price| {
+                    price
+                        .model
+                        .strip_suffix('*')
+                        .is_some_and(|prefix| model.starts_with(prefix))
+                })
+            })
+    }
+
+    // 计算 token 用量的费用（美元）
+    pub fn cost(&self, prompt_tokens: u64, completion_tokens: u64) -> f64 {
+        (prompt_tokens as f64 * self.prompt + completion_tokens as f64 * self.completion)
+            / usage_limits::TOKENS_PER_PRICE_UNIT
+    }
+}
+
+// 系统提示词注入方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SystemPromptMode {
+    // 插入到所有消息之前
+    #[default]
+    Prepend,
+    // 插入到客户端的系统消息之后
+    Append,
+    // 移除客户端的系统消息，只保留配置的提示词
+    Override,
+}
+
+impl BodyTransformConfig {
+    // 转换请求体，请求体不是 JSON 对象或未发生变化时返回 None
+    pub fn apply(&self, body: &[u8]) -> Option<Vec<u8>> {
+        let Ok(Value::Object(mut object)) = serde_json::from_slice::<Value>(body) else {
+            return None;
+        };
+
+        let mut changed = false;
+        if let Some(Value::String(model)) = object.get_mut("model") {
+            if let Some(mapped) = self.model_map.get(model.as_str()) {
+                *model = mapped.clone();
+                changed = true;
+            }
+        }
+        for (key, value) in &self.defaults {
+            if !object.contains_key(key) {
+                object.insert(key.clone(), value.clone());
+                changed = true;
+            }
+        }
+        if let (Some(prompt), Some(Value::Array(messages))) =
+            (&self.system_prompt, object.get_mut("messages"))
+        {
+            prompt.inject(messages);
+            changed = true;
+        }
+
+        if !changed {
+            return None;
+        }
+        serde_json::to_vec(&Value::Object(object)).ok()
+    }
+}
+
+impl SystemPromptConfig {
+    // 将系统提示词注入消息列表
+    fn inject(&self, messages: &mut Vec<Value>) {
+        let is_system = |message: &Value| {
+            matches!(
+                message.get("role").and_then(Value::as_str),
+                Some("system" | "developer")
+            )
+        };
+        let index = match self.mode {
+            SystemPromptMode::Prepend => 0,
+            SystemPromptMode::Append => messages.iter().take_while(|m| is_system(m)).count(),
+            SystemPromptMode::Override => {
+                messages.retain(|message| !is_system(message));
+                0
+            }
+        };
+        messages.insert(
+            index,
+            serde_json::json!({"role": "system", "content": self.content}),
+        );
+    }
+}
+
+impl PathRewriteConfig {
+    // 重写请求路径
+    pub fn rewrite(&self, path: &str) -> String {
+        let mut path = match &self.strip_prefix {
+            Some(prefix) => match path.strip_prefix(prefix.trim_end_matches('/')) {
+                Some(rest) if rest.is_empty() || rest.starts_with('/') => rest,
+                _ => path,
+            },
+            None => path,
+        }
+        .to_string();
+        if !path.starts_with('/') {
+            path.insert(0, '/');
+        }
+
+        if let Some(prefix) = &self.add_prefix {
+            path = format!("{}{}", prefix.trim_end_matches('/'), path);
+        }
+
+        match &self.template {
+            Some(template) => PLACEHOLDER
+                .replace_all(template, |caps: &Captures| match &caps[1] {
+                    "path" => path.clone(),
+                    name => self
+                        .params
+                        .get(name)
+                        .cloned()
+                        .unwrap_or_else(|| caps[0].to_string()),
+                })
+                .into_owned(),
+            None => path,
+        }
+    }
+
+    // 模板中没有对应参数的占位符
+    pub fn undefined_placeholders(&self) -> Vec<&str> {
+        let Some(template) = &self.template else {
+            return vec![];
+        };
+        PLACEHOLDER
+            .captures_iter(template)
+            .filter_map(|caps| caps.get(1))
+            .map(|name| name.as_str())
+            .filter(|name| *name != "path" && !self.params.contains_key(*name))
+            .collect()
+    }
+}
@@ -1,6 +1,11 @@
 use crate::{
-    config::{defaults::default_weight, http_client::HttpClientConfig, validation},
-    r#const::balance_strategy_labels,
+    config::{
+        defaults::{default_sticky_ttl, default_weight},
+        discovery::GroupDiscoveryConfig,
+        http_client::HttpClientConfig,
+        validation,
+    },
+    r#const::{balance_strategy_labels, sticky_limits},
 };
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
@@ -8,7 +13,10 @@ use validator::Validate;
 
 // 上游组配置
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
-#[validate(schema(function = "validation::validate_weighted_round_robin"))]
+#[validate(schema(
+    function = "validation::validate_weighted_round_robin",
+    skip_on_field_errors = false
+))]
 #[serde(rename_all = "lowercase")]
 pub struct UpstreamGroupConfig {
     // 上游组名称
@@ -24,6 +32,35 @@ pub struct UpstreamGroupConfig {
     #[serde(default)]
     #[validate(nested)]
     pub http_client: HttpClientConfig,
+    // 会话粘滞配置
+    #[serde(default)]
+    #[validate(nested)]
+    pub sticky: Option<StickyConfig>,
+    // 服务发现配置，启用后组内的每个上游按发现的地址展开为多个负载均衡目标
+    #[serde(default)]
+    #[validate(nested)]
+    pub discovery: Option<GroupDiscoveryConfig>,
+}
+
+// 会话粘滞配置
+// 根据上游返回的路由提示（如区域或分片），将同一会话的后续请求固定到匹配的上游
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(
+    function = "validation::validate_sticky_config",
+    skip_on_field_errors = false
+))]
+#[serde(rename_all = "lowercase")]
+pub struct StickyConfig {
+    // 携带会话 ID 的请求头
+    #[validate(length(min = 1, message = "Sticky session header cannot be empty"))]
+    pub session_header: String,
+    // 上游返回路由提示的响应头
+    #[validate(length(min = 1, message = "Sticky hint header cannot be empty"))]
+    pub hint_header: String,
+    // 会话粘滞有效期（秒）
+    #[serde(default = "default_sticky_ttl")]
+    #[validate(range(min = "sticky_limits::MIN_TTL", max = "sticky_limits::MAX_TTL"))]
+    pub ttl: u64,
 }
 
 // 上游引用
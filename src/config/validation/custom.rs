@@ -1,14 +1,57 @@
 // This file will contain custom validation functions.
 use validator::ValidationError;
 
+use crate::api::v1::routes::API_V1_PREFIX;
 use crate::config::{
-    http_client::HttpClientConfig, http_server::RoutingRule, upstream::AuthConfig,
-    upstream::AuthType, upstream::HeaderOp, upstream::HeaderOpType,
-    upstream_group::BalanceStrategy, upstream_group::UpstreamGroupConfig, Config, ProxyConfig,
-    UpstreamRef,
+    common::{
+        AdaptiveConfig, BreakerConfig, BreakerWindowConfig, BreakerWindowType, RateLimitConfig,
+        RateLimitKey, RedisConfig, RetryConfig,
+    },
+    discovery::{GroupDiscoveryConfig, GroupDiscoveryType},
+    http_client::HttpClientConfig,
+    http_client::{HttpVersion, TlsConfig},
+    http_server::AdminConfig,
+    http_server::AuditSinkConfig,
+    http_server::BudgetConfig,
+    http_server::FairQueueConfig,
+    http_server::ListenerConfig,
+    http_server::LoadSheddingConfig,
+    http_server::MetricsConfig,
+    http_server::MetricsLabelsConfig,
+    http_server::MiddlewareStage,
+    http_server::ParamLimitsConfig,
+    http_server::PiiRedactionConfig,
+    http_server::PolicyConfig,
+    http_server::RequestHeadersConfig,
+    http_server::RoutingRule,
+    http_server::RoutingRuleType,
+    http_server::TokenLimitConfig,
+    http_server::{AlertMetric, AlertRuleConfig, AlertWebhookConfig, AlertsConfig},
+    http_server::{CacheBackend, CacheConfig},
+    http_server::{MetricsExportConfig, MetricsExportProtocol},
+    upstream::AuthConfig,
+    upstream::AuthType,
+    upstream::HeaderOp,
+    upstream::HeaderOpType,
+    upstream::ModelPriceConfig,
+    upstream::OAuth2Config,
+    upstream::OAuth2Grant,
+    upstream::PathRewriteConfig,
+    upstream::QueryParamOp,
+    upstream::{BodyTransformConfig, StreamNormalizeConfig, UpstreamConfig, WarmupConfig},
+    upstream_group::BalanceStrategy,
+    upstream_group::StickyConfig,
+    upstream_group::UpstreamGroupConfig,
+    Config, ProxyConfig, UpstreamRef,
 };
-use crate::r#const::http_client_limits;
-use std::collections::HashSet;
+use crate::r#const::{
+    admin_paths, breaker_limits, http_client_limits, metrics_buckets, unix_socket,
+};
+use reqwest::{
+    header::{HeaderName, HeaderValue},
+    Method,
+};
+use std::collections::{HashMap, HashSet};
 
 pub fn validate_proxy_config(proxy: &ProxyConfig) -> Result<(), ValidationError> {
     if proxy.url.is_empty() {
@@ -21,30 +64,66 @@ pub fn validate_proxy_config(proxy: &ProxyConfig) -> Result<(), ValidationError>
         err.message = Some("Proxy URL is not a valid URL".into());
         return Err(err);
     }
+    if proxy
+        .no_proxy
+        .iter()
+        .any(|entry| entry.is_empty() || entry.contains(|c: char| c == ',' || c.is_whitespace()))
+    {
+        let mut err = ValidationError::new("no_proxy_invalid");
+        err.message =
+            Some("Proxy no_proxy entries cannot be empty or contain commas or whitespace".into());
+        return Err(err);
+    }
     Ok(())
 }
 
-// 检查上游引用列表中是否有重复项
+// 检查上游引用列表中是否有重复项，返回全部重复项
 pub fn check_duplicate_upstreams(
     upstreams: &[UpstreamRef],
     group_name: &str,
-) -> Result<(), ValidationError> {
+) -> Result<(), Vec<ValidationError>> {
     let mut upstream_names = HashSet::new();
+    let mut errors = Vec::new();
 
     for upstream_ref in upstreams {
         if !upstream_names.insert(&upstream_ref.name) {
-            let mut err = ValidationError::new("duplicate_upstream_in_group");
-            err.message = Some(
-                format!(
-                    "Duplicate upstream '{}' found in group '{}'",
-                    upstream_ref.name, group_name
-                )
-                .into(),
+            errors.push(
+                ValidationError::new("duplicate_upstream_in_group").with_message(
+                    format!(
+                        "Duplicate upstream '{}' found in group '{}'",
+                        upstream_ref.name, group_name
+                    )
+                    .into(),
+                ),
             );
-            return Err(err);
         }
     }
 
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+pub fn validate_tls_config(tls: &TlsConfig) -> Result<(), ValidationError> {
+    let cert = tls.cert.as_ref().is_some_and(|s| !s.is_empty());
+    let key = tls.key.as_ref().is_some_and(|s| !s.is_empty());
+    if cert != key {
+        let mut err = ValidationError::new("tls_identity_incomplete");
+        err.message = Some("TLS client cert and key must be configured together".into());
+        return Err(err);
+    }
+    if tls.passphrase.is_some() && !key {
+        let mut err = ValidationError::new("tls_passphrase_without_key");
+        err.message = Some("TLS passphrase requires a client key".into());
+        return Err(err);
+    }
+    if tls.ca_file.as_ref().is_some_and(|s| s.is_empty()) {
+        let mut err = ValidationError::new("tls_ca_file_empty");
+        err.message = Some("TLS ca_file cannot be empty".into());
+        return Err(err);
+    }
     Ok(())
 }
 
@@ -62,32 +141,122 @@ pub fn validate_http_client_config(config: &HttpClientConfig) -> Result<(), Vali
         return Err(err);
     }
 
+    // HTTP/2 配置对仅使用 HTTP/1.1 的客户端无效
+    if config.http2.is_some() && config.http_version == HttpVersion::Http1 {
+        let mut err = ValidationError::new("http2_with_http1");
+        err.message = Some("HTTP/2 settings cannot be used with http_version \"http1\"".into());
+        return Err(err);
+    }
+
     Ok(())
 }
 
 pub fn validate_auth_config(auth: &AuthConfig) -> Result<(), ValidationError> {
     match auth.r#type {
         AuthType::Bearer => {
-            if auth.token.as_ref().is_none_or(|s| s.is_empty()) {
+            if auth.token.is_some() && auth.token_file.is_some() {
+                let mut err = ValidationError::new("bearer_token_conflict");
+                err.message = Some("Bearer token and token_file cannot both be set".into());
+                return Err(err);
+            }
+            if auth.token.as_ref().is_none_or(|s| s.is_empty())
+                && auth.token_file.as_ref().is_none_or(|s| s.is_empty())
+            {
                 let mut err = ValidationError::new("bearer_token_empty");
                 err.message = Some("Bearer token cannot be empty".into());
                 return Err(err);
             }
         }
         AuthType::Basic => {
+            if auth.password.is_some() && auth.password_file.is_some() {
+                let mut err = ValidationError::new("basic_password_conflict");
+                err.message =
+                    Some("Basic auth password and password_file cannot both be set".into());
+                return Err(err);
+            }
             if auth.username.as_ref().is_none_or(|s| s.is_empty())
-                || auth.password.as_ref().is_none_or(|s| s.is_empty())
+                || (auth.password.as_ref().is_none_or(|s| s.is_empty())
+                    && auth.password_file.as_ref().is_none_or(|s| s.is_empty()))
             {
                 let mut err = ValidationError::new("basic_credentials_empty");
                 err.message = Some("Basic auth requires a non-empty username and password".into());
                 return Err(err);
             }
         }
+        AuthType::OAuth2 => {
+            if auth.oauth2.is_none() {
+                let mut err = ValidationError::new("oauth2_config_missing");
+                err.message = Some("OAuth2 auth requires an oauth2 configuration".into());
+                return Err(err);
+            }
+        }
+        AuthType::External => {
+            if auth.external.is_none() {
+                let mut err = ValidationError::new("external_config_missing");
+                err.message = Some("External auth requires an external configuration".into());
+                return Err(err);
+            }
+        }
         AuthType::None => {}
     }
     Ok(())
 }
 
+pub fn validate_oauth2_config(oauth2: &OAuth2Config) -> Result<(), ValidationError> {
+    if let Some(token_url) = &oauth2.token_url {
+        if url::Url::parse(token_url).is_err() {
+            let mut err = ValidationError::new("oauth2_token_url_invalid");
+            err.message = Some("OAuth2 token_url is not a valid URL".into());
+            return Err(err);
+        }
+    }
+
+    match oauth2.grant {
+        OAuth2Grant::ClientCredentials => {
+            if oauth2.token_url.is_none() {
+                let mut err = ValidationError::new("oauth2_token_url_missing");
+                err.message = Some("OAuth2 client_credentials grant requires a token_url".into());
+                return Err(err);
+            }
+            if oauth2.client_id.as_ref().is_none_or(|s| s.is_empty()) {
+                let mut err = ValidationError::new("oauth2_client_id_empty");
+                err.message = Some("OAuth2 client_credentials grant requires a client_id".into());
+                return Err(err);
+            }
+            if oauth2.client_secret.is_some() && oauth2.client_secret_file.is_some() {
+                let mut err = ValidationError::new("oauth2_client_secret_conflict");
+                err.message =
+                    Some("OAuth2 client_secret and client_secret_file cannot both be set".into());
+                return Err(err);
+            }
+            if oauth2.client_secret.as_ref().is_none_or(|s| s.is_empty())
+                && oauth2
+                    .client_secret_file
+                    .as_ref()
+                    .is_none_or(|s| s.is_empty())
+            {
+                let mut err = ValidationError::new("oauth2_client_secret_empty");
+                err.message =
+                    Some("OAuth2 client_credentials grant requires a client_secret".into());
+                return Err(err);
+            }
+        }
+        OAuth2Grant::ServiceAccount => {
+            if oauth2
+                .service_account_file
+                .as_ref()
+                .is_none_or(|s| s.is_empty())
+            {
+                let mut err = ValidationError::new("oauth2_service_account_file_empty");
+                err.message =
+                    Some("OAuth2 service_account grant requires a service_account_file".into());
+                return Err(err);
+            }
+        }
+    }
+    Ok(())
+}
+
 pub fn validate_header_op(op: &HeaderOp) -> Result<(), ValidationError> {
     match op.op {
         HeaderOpType::Insert | HeaderOpType::Replace => {
@@ -97,133 +266,1113 @@ pub fn validate_header_op(op: &HeaderOp) -> Result<(), ValidationError> {
                     Some("Header value cannot be empty for insert/replace operations".into());
                 return Err(err);
             }
+
+            let unknown = op.unknown_placeholders();
+            if !unknown.is_empty() {
+                let mut err = ValidationError::new("header_value_placeholder_unknown");
+                err.message = Some(
+                    format!(
+                        "Unknown placeholders in value of header '{}': {}",
+                        op.key,
+                        unknown.join(", ")
+                    )
+                    .into(),
+                );
+                return Err(err);
+            }
         }
         HeaderOpType::Remove => {}
     }
     Ok(())
 }
 
-pub fn validate_weighted_round_robin(group: &UpstreamGroupConfig) -> Result<(), ValidationError> {
-    if group.balance.strategy == BalanceStrategy::WeightedRoundRobin
-        && group.upstreams.iter().any(|u| u.weight == 0)
+pub fn validate_query_param_op(op: &QueryParamOp) -> Result<(), ValidationError> {
+    match op.op {
+        HeaderOpType::Insert | HeaderOpType::Replace => {
+            if op.value.is_none() {
+                let mut err = ValidationError::new("query_param_value_missing");
+                err.message =
+                    Some("Query parameter value is required for insert/replace operations".into());
+                return Err(err);
+            }
+        }
+        HeaderOpType::Remove => {}
+    }
+    Ok(())
+}
+
+pub fn validate_path_rewrite_config(rewrite: &PathRewriteConfig) -> Result<(), ValidationError> {
+    if rewrite.strip_prefix.is_none() && rewrite.add_prefix.is_none() && rewrite.template.is_none()
     {
-        let mut err = ValidationError::new("zero_weight_in_weighted_group");
+        let mut err = ValidationError::new("path_rewrite_empty");
         err.message = Some(
-            "All upstreams in a weighted round-robin group must have a weight greater than 0"
-                .into(),
+            "Path rewrite requires at least one of strip_prefix, add_prefix or template".into(),
+        );
+        return Err(err);
+    }
+    for value in [
+        &rewrite.strip_prefix,
+        &rewrite.add_prefix,
+        &rewrite.template,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if !value.starts_with('/') {
+            let mut err = ValidationError::new("path_rewrite_not_absolute");
+            err.message = Some(format!("Path rewrite value must start with '/': {}", value).into());
+            return Err(err);
+        }
+    }
+    let undefined = rewrite.undefined_placeholders();
+    if !undefined.is_empty() {
+        let mut err = ValidationError::new("path_rewrite_undefined_placeholder");
+        err.message = Some(
+            format!(
+                "Path template placeholders without params: {}",
+                undefined.join(", ")
+            )
+            .into(),
         );
         return Err(err);
     }
     Ok(())
 }
 
-// 检查路由规则列表中是否有重复的路径
-pub fn check_duplicate_routing_paths(
-    routing: &[RoutingRule],
-    forward_name: &str,
+pub fn validate_stream_normalize_config(
+    normalize: &StreamNormalizeConfig,
 ) -> Result<(), ValidationError> {
-    let mut path_patterns = HashSet::new();
+    if normalize.strip_fields.iter().any(|field| field.is_empty()) {
+        let mut err = ValidationError::new("stream_normalize_field_empty");
+        err.message = Some("Stream normalize field names cannot be empty".into());
+        return Err(err);
+    }
+    Ok(())
+}
 
-    for rule in routing {
-        if !path_patterns.insert(&rule.path) {
-            let mut err = ValidationError::new("duplicate_routing_path");
+pub fn validate_body_transform_config(
+    transform: &BodyTransformConfig,
+) -> Result<(), ValidationError> {
+    if transform.model_map.is_empty()
+        && transform.defaults.is_empty()
+        && transform.system_prompt.is_none()
+    {
+        let mut err = ValidationError::new("body_transform_empty");
+        err.message = Some(
+            "Body transform requires at least one of model_map, defaults or system_prompt".into(),
+        );
+        return Err(err);
+    }
+    if transform
+        .model_map
+        .iter()
+        .any(|(from, to)| from.is_empty() || to.is_empty())
+    {
+        let mut err = ValidationError::new("body_transform_model_empty");
+        err.message = Some("Body transform model names cannot be empty".into());
+        return Err(err);
+    }
+    if transform.defaults.keys().any(|key| key.is_empty()) {
+        let mut err = ValidationError::new("body_transform_default_key_empty");
+        err.message = Some("Body transform default parameter names cannot be empty".into());
+        return Err(err);
+    }
+    Ok(())
+}
+
+pub fn validate_warmup_config(warmup: &WarmupConfig) -> Result<(), ValidationError> {
+    if Method::from_bytes(warmup.method.as_bytes()).is_err() {
+        let mut err = ValidationError::new("invalid_warmup_method");
+        err.message = Some(format!("Invalid warmup request method: {}", warmup.method).into());
+        return Err(err);
+    }
+    if let Some(path) = warmup.path.as_deref().filter(|path| !path.starts_with('/')) {
+        let mut err = ValidationError::new("warmup_path_not_absolute");
+        err.message = Some(format!("Warmup path must start with '/': {}", path).into());
+        return Err(err);
+    }
+    Ok(())
+}
+
+pub fn validate_retry_config(retry: &RetryConfig) -> Result<(), ValidationError> {
+    // 只有错误响应才需要重试
+    if let Some(status) = retry
+        .retry_on_status
+        .iter()
+        .find(|status| !(400..=599).contains(*status))
+    {
+        let mut err = ValidationError::new("invalid_retry_status");
+        err.message = Some(
+            format!(
+                "Retry status codes must be between 400 and 599, got {}",
+                status
+            )
+            .into(),
+        );
+        return Err(err);
+    }
+    Ok(())
+}
+
+pub fn validate_breaker_window_config(window: &BreakerWindowConfig) -> Result<(), ValidationError> {
+    let max = match window.r#type {
+        BreakerWindowType::Time => breaker_limits::MAX_WINDOW_SECS,
+        BreakerWindowType::Count => breaker_limits::MAX_WINDOW_REQUESTS,
+    };
+    if !(breaker_limits::MIN_WINDOW_SIZE..=max).contains(&window.size) {
+        let mut err = ValidationError::new("invalid_breaker_window_size");
+        err.message = Some(
+            format!(
+                "Circuit breaker window size must be between {} and {}, got {}",
+                breaker_limits::MIN_WINDOW_SIZE,
+                max,
+                window.size
+            )
+            .into(),
+        );
+        return Err(err);
+    }
+    Ok(())
+}
+
+pub fn validate_breaker_config(breaker: &BreakerConfig) -> Result<(), ValidationError> {
+    // 按请求数统计时，窗口装不下最小请求数就永远不会熔断
+    if let (Some(window), Some(min_requests)) = (&breaker.window, breaker.min_requests) {
+        if window.r#type == BreakerWindowType::Count && min_requests > window.size {
+            let mut err = ValidationError::new("breaker_min_requests_exceeds_window");
             err.message = Some(
                 format!(
-                    "Duplicate path pattern '{}' found in forward '{}'",
-                    rule.path, forward_name
+                    "Circuit breaker min_requests ({}) cannot exceed the request-count window size ({})",
+                    min_requests, window.size
                 )
                 .into(),
             );
             return Err(err);
         }
     }
+    Ok(())
+}
 
+pub fn validate_adaptive_config(adaptive: &AdaptiveConfig) -> Result<(), ValidationError> {
+    if adaptive.min > adaptive.initial || adaptive.initial > adaptive.max {
+        let mut err = ValidationError::new("invalid_adaptive_limits");
+        err.message = Some(
+            format!(
+                "Adaptive concurrency limits must satisfy min <= initial <= max (min: {}, initial: {}, max: {})",
+                adaptive.min, adaptive.initial, adaptive.max
+            )
+            .into(),
+        );
+        return Err(err);
+    }
     Ok(())
 }
 
-pub fn validate_config(config: &Config) -> Result<(), ValidationError> {
-    let mut upstream_names = HashSet::new();
-    for upstream in &config.upstreams {
-        if !upstream_names.insert(&upstream.name) {
-            let mut err = ValidationError::new("duplicate_upstream_name");
-            err.message = Some(format!("Duplicate upstream name found: {}", upstream.name).into());
-            return Err(err);
-        }
+pub fn validate_load_shedding_config(shedding: &LoadSheddingConfig) -> Result<(), ValidationError> {
+    if shedding.max_memory_mb.is_none() && shedding.max_event_loop_lag_ms.is_none() {
+        let mut err = ValidationError::new("missing_load_shedding_threshold");
+        err.message = Some("Load shedding requires max_memory_mb or max_event_loop_lag_ms".into());
+        return Err(err);
+    }
+    if HeaderName::from_bytes(shedding.priority_header.as_bytes()).is_err() {
+        let mut err = ValidationError::new("invalid_priority_header");
+        err.message = Some(
+            format!(
+                "Invalid load shedding priority header name: {}",
+                shedding.priority_header
+            )
+            .into(),
+        );
+        return Err(err);
     }
+    Ok(())
+}
 
-    let mut group_names = HashSet::new();
-    for group in &config.upstream_groups {
-        if !group_names.insert(&group.name) {
-            let mut err = ValidationError::new("duplicate_upstream_group_name");
-            err.message =
-                Some(format!("Duplicate upstream group name found: {}", group.name).into());
+pub fn validate_model_price_config(price: &ModelPriceConfig) -> Result<(), ValidationError> {
+    for (name, value) in [("prompt", price.prompt), ("completion", price.completion)] {
+        if !value.is_finite() || value < 0.0 {
+            let mut err = ValidationError::new("invalid_model_price");
+            err.message = Some(
+                format!(
+                    "Model price {} of {} must be a non-negative number",
+                    name, price.model
+                )
+                .into(),
+            );
             return Err(err);
         }
+    }
+    Ok(())
+}
 
-        // 检查组中的上游引用是否有重复项
-        if let Err(e) = check_duplicate_upstreams(&group.upstreams, &group.name) {
-            return Err(e);
-        }
-
-        for upstream_ref in &group.upstreams {
-            if !upstream_names.contains(&upstream_ref.name) {
-                let mut err = ValidationError::new("unknown_upstream_reference");
-                err.message = Some(
-                    format!(
-                        "Upstream group '{}' references an unknown upstream: {}",
-                        group.name, upstream_ref.name
-                    )
-                    .into(),
-                );
-                return Err(err);
-            }
+pub fn validate_sticky_config(sticky: &StickyConfig) -> Result<(), ValidationError> {
+    for header in [&sticky.session_header, &sticky.hint_header] {
+        if HeaderName::from_bytes(header.as_bytes()).is_err() {
+            let mut err = ValidationError::new("invalid_sticky_header");
+            err.message = Some(format!("Invalid sticky header name: {}", header).into());
+            return Err(err);
         }
     }
+    Ok(())
+}
 
-    let mut forward_names = HashSet::new();
-    if let Some(http_server) = config.http_server.as_ref() {
-        for forward in &http_server.forwards {
-            if !forward_names.insert(&forward.name) {
-                let mut err = ValidationError::new("duplicate_forward_name");
-                err.message =
-                    Some(format!("Duplicate forward name found: {}", forward.name).into());
-                return Err(err);
-            }
-            if !group_names.contains(&forward.default_group) {
-                let mut err = ValidationError::new("unknown_upstream_group_reference");
-                err.message = Some(
-                    format!(
-                        "Forward '{}' references an unknown upstream group: {}",
-                        forward.name, forward.default_group
-                    )
-                    .into(),
-                );
-                return Err(err);
-            }
+pub fn validate_weighted_round_robin(group: &UpstreamGroupConfig) -> Result<(), ValidationError> {
+    if group.balance.strategy == BalanceStrategy::WeightedRoundRobin
+        && group.upstreams.iter().any(|u| u.weight == 0)
+    {
+        let mut err = ValidationError::new("zero_weight_in_weighted_group");
+        err.message = Some(
+            "All upstreams in a weighted round-robin group must have a weight greater than 0"
+                .into(),
+        );
+        return Err(err);
+    }
+    Ok(())
+}
 
-            // 验证路由规则中的上游组引用
-            if let Some(routing) = &forward.routing {
-                // 检查路由规则中是否有重复的路径
-                if let Err(e) = check_duplicate_routing_paths(routing, &forward.name) {
-                    return Err(e);
-                }
+pub fn validate_listener_config(listener: &ListenerConfig) -> Result<(), ValidationError> {
+    // 并发流数只对 HTTP/2 连接生效
+    if listener.max_concurrent_streams.is_some() && !listener.http2 {
+        let mut err = ValidationError::new("max_concurrent_streams_without_http2");
+        err.message = Some("Listener max_concurrent_streams requires http2".into());
+        return Err(err);
+    }
+    Ok(())
+}
 
-                for rule in routing {
-                    if !group_names.contains(&rule.target_group) {
-                        let mut err = ValidationError::new("unknown_upstream_group_reference");
-                        err.message = Some(
-                            format!(
-                                "Routing rule in forward '{}' references an unknown upstream group: {}",
-                                forward.name, rule.target_group
-                            )
-                            .into(),
-                        );
-                        return Err(err);
-                    }
-                }
-            }
+pub fn validate_request_headers_config(
+    config: &RequestHeadersConfig,
+) -> Result<(), ValidationError> {
+    for name in &config.strip {
+        if HeaderName::from_bytes(name.as_bytes()).is_err() {
+            let mut err = ValidationError::new("invalid_strip_header");
+            err.message = Some(format!("Invalid request header name to strip: {}", name).into());
+            return Err(err);
         }
     }
+    Ok(())
+}
 
+pub fn validate_metrics_config(metrics: &MetricsConfig) -> Result<(), ValidationError> {
+    // 指标路径是固定路径，不允许包含路径参数或通配符
+    if !metrics.path.starts_with('/')
+        || metrics
+            .path
+            .contains(|c: char| c.is_whitespace() || matches!(c, '{' | '}' | '*' | ':'))
+    {
+        let mut err = ValidationError::new("invalid_metrics_path");
+        err.message = Some(
+            format!(
+                "Invalid metrics path '{}', it must start with '/' and must not contain parameters",
+                metrics.path
+            )
+            .into(),
+        );
+        return Err(err);
+    }
+
+    // 与管理 API 共用端口时，不允许与其他管理路径冲突
+    if metrics.port.is_none()
+        && (metrics.path == admin_paths::HEALTH
+            || metrics.path == admin_paths::DASHBOARD
+            || metrics.path == API_V1_PREFIX
+            || metrics.path.starts_with(&format!("{}/", API_V1_PREFIX)))
+    {
+        let mut err = ValidationError::new("conflicting_metrics_path");
+        err.message = Some(
+            format!(
+                "Metrics path '{}' conflicts with another admin endpoint",
+                metrics.path
+            )
+            .into(),
+        );
+        return Err(err);
+    }
+
+    if metrics.address.is_some() && metrics.port.is_none() {
+        let mut err = ValidationError::new("metrics_address_without_port");
+        err.message =
+            Some("Metrics address requires a separate metrics port to be configured".into());
+        return Err(err);
+    }
+
+    // 分桶上限必须为正数且严格递增
+    if let Some(ref buckets) = metrics.duration_buckets {
+        let valid = !buckets.is_empty()
+            && buckets.len() <= metrics_buckets::MAX_BUCKETS
+            && buckets
+                .iter()
+                .all(|bound| bound.is_finite() && *bound > 0.0)
+            && buckets.windows(2).all(|pair| pair[0] < pair[1]);
+        if !valid {
+            let mut err = ValidationError::new("invalid_metrics_duration_buckets");
+            err.message = Some(
+                format!(
+                    "Invalid metrics duration buckets {:?}, expected 1-{} positive bounds in increasing order",
+                    buckets,
+                    metrics_buckets::MAX_BUCKETS
+                )
+                .into(),
+            );
+            return Err(err);
+        }
+    }
+
+    Ok(())
+}
+
+// 验证指标标签配置，去掉的标签必须是合法的 Prometheus 标签名称
+pub fn validate_metrics_labels_config(labels: &MetricsLabelsConfig) -> Result<(), ValidationError> {
+    for name in &labels.disabled {
+        let valid = name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            let mut err = ValidationError::new("invalid_metrics_label");
+            err.message = Some(format!("Invalid metrics label name '{}'", name).into());
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+// 验证告警配置，规则名称不能重复
+pub fn validate_alerts_config(alerts: &AlertsConfig) -> Result<(), ValidationError> {
+    let mut names = HashSet::new();
+    for rule in &alerts.rules {
+        if !names.insert(rule.name.as_str()) {
+            let mut err = ValidationError::new("duplicate_alert_rule");
+            err.message = Some(format!("Duplicate alert rule name '{}'", rule.name).into());
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+// 验证告警 Webhook 配置，地址必须是 HTTP(S) 地址
+pub fn validate_alert_webhook_config(webhook: &AlertWebhookConfig) -> Result<(), ValidationError> {
+    if !url::Url::parse(&webhook.url)
+        .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.has_host())
+    {
+        let mut err = ValidationError::new("invalid_alert_webhook_url");
+        err.message = Some(
+            format!(
+                "Invalid alert webhook URL '{}', expected an http(s) URL",
+                webhook.url
+            )
+            .into(),
+        );
+        return Err(err);
+    }
+    Ok(())
+}
+
+// 验证告警规则配置，错误率阈值必须在 0-1 之间，耗时阈值必须为正数，分位数必须在 0-1 之间
+pub fn validate_alert_rule_config(rule: &AlertRuleConfig) -> Result<(), ValidationError> {
+    let valid_threshold = match rule.metric {
+        AlertMetric::ErrorRate => (0.0..1.0).contains(&rule.threshold),
+        AlertMetric::Latency => rule.threshold.is_finite() && rule.threshold > 0.0,
+    };
+    if !valid_threshold {
+        let mut err = ValidationError::new("invalid_alert_threshold");
+        err.message = Some(
+            format!(
+                "Invalid threshold {} of alert rule '{}', expected a ratio in [0, 1) for error_rate or a positive number of seconds for latency",
+                rule.threshold, rule.name
+            )
+            .into(),
+        );
+        return Err(err);
+    }
+    if !(rule.quantile > 0.0 && rule.quantile < 1.0) {
+        let mut err = ValidationError::new("invalid_alert_quantile");
+        err.message = Some(
+            format!(
+                "Invalid quantile {} of alert rule '{}', expected a value between 0 and 1",
+                rule.quantile, rule.name
+            )
+            .into(),
+        );
+        return Err(err);
+    }
+    Ok(())
+}
+
+// 验证指标推送配置，OTLP 推送地址必须是 HTTP(S) 地址，StatsD 推送地址必须是 host:port
+pub fn validate_metrics_export_config(export: &MetricsExportConfig) -> Result<(), ValidationError> {
+    let valid = match export.protocol {
+        MetricsExportProtocol::Otlp => url::Url::parse(&export.endpoint)
+            .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.has_host()),
+        MetricsExportProtocol::Statsd | MetricsExportProtocol::Dogstatsd => export
+            .endpoint
+            .rsplit_once(':')
+            .is_some_and(|(host, port)| {
+                !host.is_empty() && !host.contains('/') && port.parse::<u16>().is_ok()
+            }),
+    };
+    if !valid {
+        let mut err = ValidationError::new("invalid_metrics_export_endpoint");
+        err.message = Some(
+            format!(
+                "Invalid metrics export endpoint '{}', expected an http(s) URL for otlp or host:port for statsd",
+                export.endpoint
+            )
+            .into(),
+        );
+        return Err(err);
+    }
+
+    // 前缀会拼接到指标名称前，只允许字母、数字、下划线和点
+    if !export
+        .prefix
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.'))
+    {
+        let mut err = ValidationError::new("invalid_metrics_export_prefix");
+        err.message = Some(
+            format!(
+                "Invalid metrics export prefix '{}', only letters, digits, '_' and '.' are allowed",
+                export.prefix
+            )
+            .into(),
+        );
+        return Err(err);
+    }
+
+    for (name, value) in &export.headers {
+        if HeaderName::from_bytes(name.as_bytes()).is_err() || HeaderValue::from_str(value).is_err()
+        {
+            let mut err = ValidationError::new("invalid_metrics_export_header");
+            err.message = Some(format!("Invalid metrics export header '{}'", name).into());
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_admin_config(admin: &AdminConfig) -> Result<(), ValidationError> {
+    let metrics_address = admin.metrics.address.as_ref().unwrap_or(&admin.address);
+    if admin.metrics.port == Some(admin.port) && *metrics_address == admin.address {
+        let mut err = ValidationError::new("conflicting_metrics_listener");
+        err.message = Some(
+            format!(
+                "Metrics port {} conflicts with the admin service listener",
+                admin.port
+            )
+            .into(),
+        );
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+pub fn validate_routing_rule(rule: &RoutingRule) -> Result<(), ValidationError> {
+    if rule.r#type == RoutingRuleType::PathRegex {
+        if let Err(e) = regex::Regex::new(&rule.path) {
+            let mut err = ValidationError::new("invalid_path_regex");
+            err.message = Some(format!("Invalid path regex '{}': {}", rule.path, e).into());
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_param_limits_config(limits: &ParamLimitsConfig) -> Result<(), ValidationError> {
+    if limits.max_tokens.is_none()
+        && limits.temperature.is_none()
+        && limits.top_p.is_none()
+        && limits.n.is_none()
+        && limits.max_prompt_tokens.is_none()
+    {
+        let mut err = ValidationError::new("param_limits_empty");
+        err.message = Some("Parameter limits require at least one limit".into());
+        return Err(err);
+    }
+    for (name, limit) in [("temperature", limits.temperature), ("top_p", limits.top_p)] {
+        if limit.is_some_and(|limit| !limit.is_finite() || limit < 0.0) {
+            let mut err = ValidationError::new("invalid_param_limit");
+            err.message =
+                Some(format!("Parameter limit {} must be a non-negative number", name).into());
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_budget_config(budget: &BudgetConfig) -> Result<(), ValidationError> {
+    if HeaderName::from_bytes(budget.header.as_bytes()).is_err() {
+        let mut err = ValidationError::new("invalid_budget_header");
+        err.message = Some(format!("Invalid budget header name: {}", budget.header).into());
+        return Err(err);
+    }
+    if budget.daily.is_none()
+        && budget.monthly.is_none()
+        && budget
+            .clients
+            .iter()
+            .all(|c| c.daily.is_none() && c.monthly.is_none())
+    {
+        let mut err = ValidationError::new("budget_empty");
+        err.message = Some("Budget requires at least one daily or monthly limit".into());
+        return Err(err);
+    }
+    let limits = [budget.daily, budget.monthly]
+        .into_iter()
+        .chain(budget.clients.iter().flat_map(|c| [c.daily, c.monthly]));
+    for limit in limits.flatten() {
+        if !limit.is_finite() || limit < 0.0 {
+            let mut err = ValidationError::new("invalid_budget_limit");
+            err.message = Some("Budget limits must be non-negative numbers".into());
+            return Err(err);
+        }
+    }
+    let mut ids = HashSet::new();
+    if let Some(client) = budget.clients.iter().find(|c| !ids.insert(&c.id)) {
+        let mut err = ValidationError::new("duplicate_client_budget");
+        err.message = Some(format!("Duplicate client budget: {}", client.id).into());
+        return Err(err);
+    }
+    Ok(())
+}
+
+// 验证限流配置，按请求头限流时必须指定有效的请求头名称
+pub fn validate_rate_limit_config(ratelimit: &RateLimitConfig) -> Result<(), ValidationError> {
+    validate_limit_key(ratelimit.key, ratelimit.header.as_deref())
+}
+
+// 验证令牌速率限制配置，客户端不能重复
+pub fn validate_token_limit_config(limit: &TokenLimitConfig) -> Result<(), ValidationError> {
+    validate_limit_key(limit.key, limit.header.as_deref())?;
+    let mut ids = HashSet::new();
+    if let Some(client) = limit.clients.iter().find(|c| !ids.insert(&c.id)) {
+        let mut err = ValidationError::new("duplicate_client_token_limit");
+        err.message = Some(format!("Duplicate client token limit: {}", client.id).into());
+        return Err(err);
+    }
+    Ok(())
+}
+
+// 验证公平调度配置，等级名称不能重复，客户端只能属于一个等级
+pub fn validate_fair_queue_config(fair: &FairQueueConfig) -> Result<(), ValidationError> {
+    validate_limit_key(fair.key, fair.header.as_deref())?;
+    let mut names = HashSet::new();
+    if let Some(tier) = fair.tiers.iter().find(|t| !names.insert(&t.name)) {
+        let mut err = ValidationError::new("duplicate_queue_tier");
+        err.message = Some(format!("Duplicate queue tier: {}", tier.name).into());
+        return Err(err);
+    }
+    let mut clients = HashSet::new();
+    if let Some(client) = fair
+        .tiers
+        .iter()
+        .flat_map(|t| &t.clients)
+        .find(|c| !clients.insert(*c))
+    {
+        let mut err = ValidationError::new("duplicate_queue_tier_client");
+        err.message = Some(format!("Client belongs to multiple queue tiers: {}", client).into());
+        return Err(err);
+    }
+    Ok(())
+}
+
+pub fn validate_cache_config(cache: &CacheConfig) -> Result<(), ValidationError> {
+    if cache.backend == CacheBackend::Redis && cache.redis.is_none() {
+        let mut err = ValidationError::new("cache_redis_missing");
+        err.message = Some("Redis cache backend requires redis configuration".into());
+        return Err(err);
+    }
+    Ok(())
+}
+
+// 验证请求审计日志配置，文件和 HTTP 接收端必须且只能配置一个
+pub fn validate_audit_sink_config(sink: &AuditSinkConfig) -> Result<(), ValidationError> {
+    match (&sink.file, &sink.url) {
+        (Some(file), None) if file.trim().is_empty() => {
+            let mut err = ValidationError::new("audit_sink_file_empty");
+            err.message = Some("Audit sink file cannot be empty".into());
+            Err(err)
+        }
+        (Some(_), None) => Ok(()),
+        (None, Some(url)) => {
+            let valid = url::Url::parse(url)
+                .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.has_host());
+            if !valid {
+                let mut err = ValidationError::new("invalid_audit_sink_url");
+                err.message = Some(format!("Invalid audit sink URL: {}", url).into());
+                return Err(err);
+            }
+            Ok(())
+        }
+        _ => {
+            let mut err = ValidationError::new("audit_sink_destination");
+            err.message = Some("Audit sink requires exactly one of file or url".into());
+            Err(err)
+        }
+    }
+}
+
+// 验证 PII 脱敏配置，至少配置一个检测器或规则，规则名称不能重复，正则表达式必须有效
+pub fn validate_pii_redaction_config(pii: &PiiRedactionConfig) -> Result<(), ValidationError> {
+    if pii.detectors.is_empty() && pii.patterns.is_empty() {
+        let mut err = ValidationError::new("pii_redaction_empty");
+        err.message = Some("PII redaction requires at least one detector or pattern".into());
+        return Err(err);
+    }
+    let mut names: HashSet<&str> = pii.detectors.iter().map(|d| d.as_str()).collect();
+    if names.len() != pii.detectors.len() {
+        let mut err = ValidationError::new("duplicate_pii_detector");
+        err.message = Some("Duplicate PII detector".into());
+        return Err(err);
+    }
+    for pattern in &pii.patterns {
+        if !names.insert(&pattern.name) {
+            let mut err = ValidationError::new("duplicate_pii_pattern");
+            err.message = Some(format!("Duplicate PII pattern name: {}", pattern.name).into());
+            return Err(err);
+        }
+        if let Err(e) = regex::Regex::new(&pattern.regex) {
+            let mut err = ValidationError::new("invalid_pii_pattern");
+            err.message =
+                Some(format!("Invalid PII pattern regex '{}': {}", pattern.name, e).into());
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+// 验证外部策略服务配置，地址必须是 HTTP(S) 地址
+pub fn validate_policy_config(policy: &PolicyConfig) -> Result<(), ValidationError> {
+    let valid = url::Url::parse(&policy.url)
+        .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.has_host());
+    if !valid {
+        let mut err = ValidationError::new("invalid_policy_url");
+        err.message = Some(format!("Invalid policy service URL: {}", policy.url).into());
+        return Err(err);
+    }
+    Ok(())
+}
+
+pub fn validate_redis_config(redis: &RedisConfig) -> Result<(), ValidationError> {
+    let valid = url::Url::parse(&redis.url)
+        .is_ok_and(|url| matches!(url.scheme(), "redis" | "rediss") && url.has_host());
+    if !valid {
+        let mut err = ValidationError::new("invalid_redis_url");
+        // 地址中可能包含密码，不在错误信息中输出
+        err.message =
+            Some("Invalid Redis URL, expected redis://host:port or rediss://host:port".into());
+        return Err(err);
+    }
+    Ok(())
+}
+
+// 限流键为 header 时必须指定有效的请求头名称
+fn validate_limit_key(key: RateLimitKey, header: Option<&str>) -> Result<(), ValidationError> {
+    if key != RateLimitKey::Header {
+        return Ok(());
+    }
+    match header {
+        Some(header) if HeaderName::from_bytes(header.as_bytes()).is_ok() => Ok(()),
+        Some(header) => {
+            let mut err = ValidationError::new("invalid_ratelimit_header");
+            err.message = Some(format!("Invalid rate limit header name: {}", header).into());
+            Err(err)
+        }
+        None => {
+            let mut err = ValidationError::new("ratelimit_header_required");
+            err.message = Some("Rate limit key 'header' requires a header name".into());
+            Err(err)
+        }
+    }
+}
+
+// 检查路由规则列表中是否有重复的路径，返回全部重复项
+pub fn check_duplicate_routing_paths(
+    routing: &[RoutingRule],
+    forward_name: &str,
+) -> Result<(), Vec<ValidationError>> {
+    let mut path_patterns = HashSet::new();
+    let mut errors = Vec::new();
+
+    for rule in routing {
+        if !path_patterns.insert(&rule.path) {
+            errors.push(
+                ValidationError::new("duplicate_routing_path").with_message(
+                    format!(
+                        "Duplicate path pattern '{}' found in forward '{}'",
+                        rule.path, forward_name
+                    )
+                    .into(),
+                ),
+            );
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+// 计算路径模式的匹配签名，忽略参数名称
+// 例如 "/users/:id" 与 "/users/:name" 的签名相同，会匹配完全相同的请求路径
+fn routing_pattern_signature(path: &str) -> String {
+    path.split('/')
+        .map(|segment| {
+            if segment.starts_with(':') {
+                ":".to_string()
+            } else if segment.starts_with('{') && segment.ends_with('}') {
+                match segment[1..segment.len() - 1].split_once(':') {
+                    Some((_, regex)) => format!("{{{}}}", regex),
+                    None => ":".to_string(),
+                }
+            } else {
+                segment.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// 检查路由规则列表中是否有相同优先级下含义相同的路径模式，返回全部有歧义的规则
+pub fn check_ambiguous_routing_rules(
+    routing: &[RoutingRule],
+    forward_name: &str,
+) -> Result<(), Vec<ValidationError>> {
+    let mut signatures: HashMap<(String, u32), &str> = HashMap::with_capacity(routing.len());
+    let mut errors = Vec::new();
+
+    for rule in routing {
+        // 正则规则按原始表达式比较，不与路径模式混淆
+        let signature = match rule.r#type {
+            RoutingRuleType::Path => routing_pattern_signature(&rule.path),
+            RoutingRuleType::PathRegex => format!("regex:{}", rule.path),
+        };
+        // 完全相同的路径由重复路径检查报告
+        let existing = signatures.insert((signature, rule.priority), &rule.path);
+        if let Some(existing) = existing.filter(|existing| *existing != rule.path) {
+            errors.push(
+                ValidationError::new("ambiguous_routing_rule").with_message(
+                    format!(
+                        "Path patterns '{}' and '{}' in forward '{}' match the same requests with the same priority {}, set different priorities to disambiguate",
+                        existing, rule.path, forward_name, rule.priority
+                    )
+                    .into(),
+                ),
+            );
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// 校验上游、上游组、转发服务、告警规则和模型别名之间的名称唯一性和引用关系
+///
+/// 不在第一个问题处返回，一次收集全部错误
+pub fn validate_config(config: &Config) -> Result<(), Vec<ValidationError>> {
+    let mut errors = Vec::new();
+
+    let mut upstream_names = HashSet::new();
+    for upstream in &config.upstreams {
+        if !upstream_names.insert(&upstream.name) {
+            errors.push(
+                ValidationError::new("duplicate_upstream_name").with_message(
+                    format!("Duplicate upstream name found: {}", upstream.name).into(),
+                ),
+            );
+        }
+    }
+
+    let mut group_names = HashSet::new();
+    for group in &config.upstream_groups {
+        if !group_names.insert(&group.name) {
+            errors.push(
+                ValidationError::new("duplicate_upstream_group_name").with_message(
+                    format!("Duplicate upstream group name found: {}", group.name).into(),
+                ),
+            );
+        }
+
+        // 检查组中的上游引用是否有重复项
+        if let Err(e) = check_duplicate_upstreams(&group.upstreams, &group.name) {
+            errors.extend(e);
+        }
+
+        for upstream_ref in &group.upstreams {
+            if !upstream_names.contains(&upstream_ref.name) {
+                errors.push(
+                    ValidationError::new("unknown_upstream_reference").with_message(
+                        format!(
+                            "Upstream group '{}' references an unknown upstream: {}",
+                            group.name, upstream_ref.name
+                        )
+                        .into(),
+                    ),
+                );
+            }
+        }
+    }
+
+    let mut forward_names = HashSet::new();
+    if let Some(http_server) = config.http_server.as_ref() {
+        for forward in &http_server.forwards {
+            if !forward_names.insert(&forward.name) {
+                errors.push(ValidationError::new("duplicate_forward_name").with_message(
+                    format!("Duplicate forward name found: {}", forward.name).into(),
+                ));
+            }
+            if !group_names.contains(&forward.default_group) {
+                errors.push(
+                    ValidationError::new("unknown_upstream_group_reference").with_message(
+                        format!(
+                            "Forward '{}' references an unknown upstream group: {}",
+                            forward.name, forward.default_group
+                        )
+                        .into(),
+                    ),
+                );
+            }
+
+            let mut plugin_names = HashSet::new();
+            for plugin in &forward.plugins {
+                if !plugin_names.insert(&plugin.name) {
+                    errors.push(
+                        ValidationError::new("duplicate_plugin_name").with_message(
+                            format!(
+                                "Duplicate plugin name found in forward '{}': {}",
+                                forward.name, plugin.name
+                            )
+                            .into(),
+                        ),
+                    );
+                }
+            }
+
+            // 配置了请求处理阶段顺序时，每个阶段只能出现一次，已配置的功能必须列出，
+            // 入口阶段以中间件层执行，必须排在读取请求体之后的阶段之前
+            if let Some(middlewares) = &forward.middlewares {
+                let mut stages = HashSet::new();
+                let mut body_stage: Option<&MiddlewareStage> = None;
+                for stage in middlewares {
+                    match body_stage {
+                        Some(body_stage) if stage.is_layer() => errors.push(
+                            ValidationError::new("misordered_middleware_stage").with_message(
+                                format!(
+                                    "Forward '{}' lists {} after {}, access_log, compression and rate_limit must come before the other middleware stages",
+                                    forward.name,
+                                    stage.as_str(),
+                                    body_stage.as_str()
+                                )
+                                .into(),
+                            ),
+                        ),
+                        None if !stage.is_layer() => body_stage = Some(stage),
+                        _ => {}
+                    }
+                    if !stages.insert(stage) {
+                        errors.push(
+                            ValidationError::new("duplicate_middleware_stage").with_message(
+                                format!(
+                                    "Duplicate middleware stage found in forward '{}': {}",
+                                    forward.name,
+                                    stage.as_str()
+                                )
+                                .into(),
+                            ),
+                        );
+                    }
+                }
+                for stage in MiddlewareStage::DEFAULT_ORDER {
+                    if forward.has_middleware(stage) && !stages.contains(&stage) {
+                        errors.push(
+                            ValidationError::new("missing_middleware_stage").with_message(
+                                format!(
+                                    "Forward '{}' configures {} but does not list it in middlewares",
+                                    forward.name,
+                                    stage.as_str()
+                                )
+                                .into(),
+                            ),
+                        );
+                    }
+                }
+            }
+
+            // 允许的方法必须是有效的 HTTP 方法，允许的路径必须以 / 开头
+            for method in &forward.allowed_methods {
+                if Method::from_bytes(method.as_bytes()).is_err() {
+                    errors.push(
+                        ValidationError::new("invalid_allowed_method").with_message(
+                            format!(
+                                "Invalid allowed method '{}' in forward '{}'",
+                                method, forward.name
+                            )
+                            .into(),
+                        ),
+                    );
+                }
+            }
+            for path in &forward.allowed_paths {
+                if !path.starts_with('/') {
+                    errors.push(
+                        ValidationError::new("invalid_allowed_path").with_message(
+                            format!(
+                                "Invalid allowed path '{}' in forward '{}', it must start with '/'",
+                                path, forward.name
+                            )
+                            .into(),
+                        ),
+                    );
+                }
+            }
+
+            if forward.queue.is_some() && forward.max_concurrent.is_none() {
+                errors.push(
+                    ValidationError::new("queue_without_max_concurrent").with_message(
+                        format!(
+                            "Forward '{}' configures a queue without max_concurrent",
+                            forward.name
+                        )
+                        .into(),
+                    ),
+                );
+            }
+
+            // 验证路由规则中的上游组引用
+            if let Some(routing) = &forward.routing {
+                // 检查路由规则中是否有重复的路径
+                if let Err(e) = check_duplicate_routing_paths(routing, &forward.name) {
+                    errors.extend(e);
+                }
+
+                // 检查相同优先级下是否存在有歧义的路径模式
+                if let Err(e) = check_ambiguous_routing_rules(routing, &forward.name) {
+                    errors.extend(e);
+                }
+
+                for rule in routing {
+                    if rule.tokens_per_minute.is_some() && forward.token_limit.is_none() {
+                        errors.push(
+                            ValidationError::new("route_tokens_per_minute_without_token_limit")
+                                .with_message(
+                                    format!(
+                                        "Routing rule '{}' in forward '{}' configures tokens_per_minute without token_limit",
+                                        rule.path, forward.name
+                                    )
+                                    .into(),
+                                ),
+                        );
+                    }
+                    if !group_names.contains(&rule.target_group) {
+                        errors.push(
+                            ValidationError::new("unknown_upstream_group_reference").with_message(
+                                format!(
+                                    "Routing rule in forward '{}' references an unknown upstream group: {}",
+                                    forward.name, rule.target_group
+                                )
+                                .into(),
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+
+        let alerts = http_server.admin.metrics.alerts.as_ref();
+        for rule in alerts
+            .map(|alerts| alerts.rules.as_slice())
+            .unwrap_or_default()
+        {
+            if let Some(group) = &rule.group {
+                if !group_names.contains(group) {
+                    errors.push(
+                        ValidationError::new("unknown_upstream_group_reference").with_message(
+                            format!(
+                                "Alert rule '{}' references an unknown upstream group: {}",
+                                rule.name, group
+                            )
+                            .into(),
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    let mut model_names = HashSet::new();
+    for alias in &config.models {
+        if !model_names.insert(&alias.name) {
+            errors.push(
+                ValidationError::new("duplicate_model_alias")
+                    .with_message(format!("Duplicate model alias found: {}", alias.name).into()),
+            );
+        }
+        if !group_names.contains(&alias.group) {
+            errors.push(
+                ValidationError::new("unknown_upstream_group_reference").with_message(
+                    format!(
+                        "Model alias '{}' references an unknown upstream group: {}",
+                        alias.name, alias.group
+                    )
+                    .into(),
+                ),
+            );
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+pub fn validate_upstream_discovery(upstream: &UpstreamConfig) -> Result<(), ValidationError> {
+    if upstream.dns.is_none() {
+        return Ok(());
+    }
+    // URL 无效时已由 URL 校验报告
+    let Ok(url) = url::Url::parse(&upstream.url) else {
+        return Ok(());
+    };
+    if url.scheme() == unix_socket::SCHEME || url.host_str().is_none_or(str::is_empty) {
+        let mut err = ValidationError::new("invalid_dns_discovery_url");
+        err.message = Some(
+            format!(
+                "DNS discovery for upstream '{}' requires an HTTP(S) URL with a host name",
+                upstream.name
+            )
+            .into(),
+        );
+        return Err(err);
+    }
+    Ok(())
+}
+
+pub fn validate_group_discovery(discovery: &GroupDiscoveryConfig) -> Result<(), ValidationError> {
+    match discovery.r#type {
+        GroupDiscoveryType::Kubernetes => {
+            if discovery.kubernetes.is_none() {
+                let mut err = ValidationError::new("kubernetes_discovery_config_missing");
+                err.message =
+                    Some("Kubernetes discovery requires a kubernetes configuration".into());
+                return Err(err);
+            }
+        }
+        GroupDiscoveryType::Consul => {
+            if discovery.consul.is_none() {
+                let mut err = ValidationError::new("consul_discovery_config_missing");
+                err.message = Some("Consul discovery requires a consul configuration".into());
+                return Err(err);
+            }
+        }
+    }
     Ok(())
 }
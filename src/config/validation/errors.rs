@@ -0,0 +1,42 @@
+use validator::{ValidationErrors, ValidationErrorsKind};
+
+/// 将嵌套的验证错误展开为按字母排序的错误消息列表，消息以出错的配置路径开头
+///
+/// 结构体级别的校验错误使用结构体本身的路径，配置根节点的错误不带路径
+pub fn error_messages(errors: &ValidationErrors) -> Vec<String> {
+    let mut messages = Vec::new();
+    collect_messages("", errors, &mut messages);
+    messages.sort();
+    messages
+}
+
+// 递归收集验证错误，path 为当前结构体的配置路径
+fn collect_messages(path: &str, errors: &ValidationErrors, messages: &mut Vec<String>) {
+    for (field, kind) in errors.errors() {
+        // 结构体级别的校验错误（__all__）属于结构体本身
+        let field_path = match (path.is_empty(), *field == "__all__") {
+            (true, true) => String::new(),
+            (false, true) => path.to_string(),
+            (true, false) => field.to_string(),
+            (false, false) => format!("{}.{}", path, field),
+        };
+
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                for error in errors {
+                    if field_path.is_empty() {
+                        messages.push(error.to_string());
+                    } else {
+                        messages.push(format!("{}: {}", field_path, error));
+                    }
+                }
+            }
+            ValidationErrorsKind::Struct(errors) => collect_messages(&field_path, errors, messages),
+            ValidationErrorsKind::List(items) => {
+                for (index, errors) in items {
+                    collect_messages(&format!("{}[{}]", field_path, index), errors, messages);
+                }
+            }
+        }
+    }
+}
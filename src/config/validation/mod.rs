@@ -1,3 +1,7 @@
 pub mod custom;
+pub mod errors;
+pub mod warnings;
 
 pub use custom::*;
+pub use errors::error_messages;
+pub use warnings::config_warnings;
@@ -0,0 +1,78 @@
+// 配置警告：不影响启动、但很可能是配置错误的问题
+use std::collections::HashSet;
+
+use crate::config::{BalanceStrategy, Config};
+use crate::r#const::config_warnings::SHORT_STREAM_IDLE_TIMEOUT;
+
+/// 检查配置中的潜在问题，返回全部警告
+///
+/// 默认只记录到日志，严格模式（`validate --strict`）下视为错误
+pub fn config_warnings(config: &Config) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    for group in &config.upstream_groups {
+        // 只有一个上游的故障转移组没有可以切换的上游
+        if group.balance.strategy == BalanceStrategy::Failover && group.upstreams.len() < 2 {
+            warnings.push(format!(
+                "Upstream group '{}' uses the failover strategy with only one upstream, there is nothing to fail over to",
+                group.name
+            ));
+        }
+
+        // 流式模式下模型思考或排队的停顿超过空闲超时会中断响应
+        let http_client = &group.http_client;
+        if let Some(stream_idle) = http_client.timeout.stream_idle {
+            if http_client.stream_mode && stream_idle < SHORT_STREAM_IDLE_TIMEOUT {
+                warnings.push(format!(
+                    "Upstream group '{}' enables stream_mode with a stream_idle timeout of {}s, streams are aborted when the model pauses longer (at least {}s is recommended)",
+                    group.name, stream_idle, SHORT_STREAM_IDLE_TIMEOUT
+                ));
+            }
+        }
+    }
+
+    // 未被任何上游组引用的上游不会收到请求
+    let referenced_upstreams: HashSet<&str> = config
+        .upstream_groups
+        .iter()
+        .flat_map(|group| {
+            group
+                .upstreams
+                .iter()
+                .map(|upstream| upstream.name.as_str())
+        })
+        .collect();
+    for upstream in &config.upstreams {
+        if !referenced_upstreams.contains(upstream.name.as_str()) {
+            warnings.push(format!(
+                "Upstream '{}' is not referenced by any upstream group",
+                upstream.name
+            ));
+        }
+    }
+
+    // 未被转发服务、路由规则或模型别名引用的上游组不会收到请求
+    let mut referenced_groups: HashSet<&str> = config
+        .models
+        .iter()
+        .map(|alias| alias.group.as_str())
+        .collect();
+    if let Some(http_server) = &config.http_server {
+        for forward in &http_server.forwards {
+            referenced_groups.insert(&forward.default_group);
+            for rule in forward.routing.iter().flatten() {
+                referenced_groups.insert(&rule.target_group);
+            }
+        }
+    }
+    for group in &config.upstream_groups {
+        if !referenced_groups.contains(group.name.as_str()) {
+            warnings.push(format!(
+                "Upstream group '{}' is not referenced by any forward, routing rule or model alias",
+                group.name
+            ));
+        }
+    }
+
+    warnings
+}
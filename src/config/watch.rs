@@ -0,0 +1,146 @@
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsString,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tokio::{sync::mpsc, time::Instant};
+use tracing::{debug, warn};
+
+use crate::{error::AppError, r#const::config_watch};
+
+/// 配置文件监听器
+///
+/// 监听文件所在的目录而不是文件本身，文件被替换（如编辑器保存、Kubernetes 更新 ConfigMap
+/// 时切换 ..data 符号链接）后仍能收到通知。一批变化结束并经过防抖时间后才报告变化
+pub struct FileWatcher {
+    // 文件系统通知
+    watcher: RecommendedWatcher,
+    // 通知事件
+    events: mpsc::UnboundedReceiver<notify::Result<Event>>,
+    // 监听的目录和其中关注的文件名，为 None 时关注目录中的全部条目
+    targets: HashMap<PathBuf, Option<HashSet<OsString>>>,
+    // 防抖时间
+    debounce: Duration,
+}
+
+impl FileWatcher {
+    /// 创建监听器，尚未监听任何路径
+    pub fn new(debounce: Duration) -> Result<Self, AppError> {
+        let (sender, events) = mpsc::unbounded_channel();
+        let watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+        })
+        .map_err(|e| AppError::Config(format!("Failed to create file watcher: {}", e)))?;
+
+        Ok(Self {
+            watcher,
+            events,
+            targets: HashMap::new(),
+            debounce,
+        })
+    }
+
+    /// 更新监听的路径，路径为目录时关注其中的全部条目
+    ///
+    /// 无法监听的目录记录警告后跳过
+    pub fn watch(&mut self, paths: &[PathBuf]) {
+        let mut targets: HashMap<PathBuf, Option<HashSet<OsString>>> = HashMap::new();
+        for path in paths {
+            if path.is_dir() {
+                targets.insert(canonical(path), None);
+                continue;
+            }
+            let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+                continue;
+            };
+            let dir = if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir
+            };
+            if let Some(names) = targets
+                .entry(canonical(dir))
+                .or_insert_with(|| Some(HashSet::new()))
+            {
+                names.insert(name.to_os_string());
+            }
+        }
+
+        for dir in self.targets.keys() {
+            if !targets.contains_key(dir) {
+                let _ = self.watcher.unwatch(dir);
+            }
+        }
+        targets.retain(|dir, _| {
+            if self.targets.contains_key(dir) {
+                return true;
+            }
+            match self.watcher.watch(dir, RecursiveMode::NonRecursive) {
+                Ok(()) => {
+                    debug!("Watching {:?} for configuration changes", dir);
+                    true
+                }
+                Err(e) => {
+                    warn!("Failed to watch {:?} for changes: {}", dir, e);
+                    false
+                }
+            }
+        });
+        self.targets = targets;
+    }
+
+    /// 等待监听的文件发生变化，并在变化停止一个防抖时间后返回
+    pub async fn changed(&mut self) {
+        loop {
+            match self.events.recv().await {
+                Some(Ok(event)) if self.is_relevant(&event) => break,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => warn!("File watcher error: {}", e),
+                None => return std::future::pending().await,
+            }
+        }
+
+        let mut deadline = Instant::now() + self.debounce;
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep_until(deadline) => return,
+                event = self.events.recv() => match event {
+                    Some(Ok(event)) if self.is_relevant(&event) => {
+                        deadline = Instant::now() + self.debounce;
+                    }
+                    Some(_) => {}
+                    None => return,
+                },
+            }
+        }
+    }
+
+    // 事件是否涉及关注的文件，Kubernetes 切换 ..data 符号链接时同样视为变化
+    fn is_relevant(&self, event: &Event) -> bool {
+        if matches!(event.kind, EventKind::Access(_)) {
+            return false;
+        }
+        event.paths.iter().any(|path| {
+            let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+                return false;
+            };
+            match self.targets.get(dir) {
+                Some(None) => true,
+                Some(Some(names)) => {
+                    names.contains(name)
+                        || name
+                            .to_string_lossy()
+                            .starts_with(config_watch::KUBERNETES_PREFIX)
+                }
+                None => false,
+            }
+        })
+    }
+}
+
+// 目录的规范路径，与通知事件中的路径保持一致，无法解析时使用原路径
+fn canonical(dir: &Path) -> PathBuf {
+    dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf())
+}
@@ -14,6 +14,174 @@ pub mod shutdown_timeout {
     pub const MAX: u64 = 120;
 }
 
+// 配置目录
+pub mod config_dir {
+    // 按名称拼接的顶层配置段
+    pub const LIST_SECTIONS: [&str; 3] = ["upstreams", "upstream_groups", "models"];
+    // HTTP 服务器配置段
+    pub const HTTP_SERVER: &str = "http_server";
+    // HTTP 服务器配置段中按名称拼接的转发服务列表
+    pub const FORWARDS: &str = "forwards";
+}
+
+// 远程配置来源
+pub mod config_source {
+    // etcd（v3 HTTP/JSON 网关）的地址协议
+    pub const ETCD: &str = "etcd";
+    pub const ETCD_HTTPS: &str = "etcd+https";
+    // Consul KV 的地址协议
+    pub const CONSUL: &str = "consul";
+    pub const CONSUL_HTTPS: &str = "consul+https";
+    // 未指定端口时使用的默认端口
+    pub const ETCD_DEFAULT_PORT: u16 = 2379;
+    pub const CONSUL_DEFAULT_PORT: u16 = 8500;
+    // etcd 认证用户（user 或 user:password），与 etcdctl 相同
+    pub const ETCD_USER_ENV: &str = "ETCDCTL_USER";
+    // etcd 认证密码，ETCDCTL_USER 中未包含密码时使用
+    pub const ETCD_PASSWORD_ENV: &str = "ETCDCTL_PASSWORD";
+    // Consul ACL 令牌，与 consul 命令行工具相同
+    pub const CONSUL_TOKEN_ENV: &str = "CONSUL_HTTP_TOKEN";
+    // Consul ACL 令牌请求头
+    pub const CONSUL_TOKEN_HEADER: &str = "X-Consul-Token";
+    // Consul 阻塞查询的索引响应头
+    pub const CONSUL_INDEX_HEADER: &str = "X-Consul-Index";
+    // 读取配置的请求超时时间（秒）
+    pub const REQUEST_TIMEOUT: u64 = 10;
+    // 一次监听请求的最长等待时间（秒），超时后重新发起监听
+    pub const WATCH_TIMEOUT: u64 = 300;
+    // 监听失败后的重试间隔（秒）
+    pub const RETRY_INTERVAL: u64 = 5;
+}
+
+// 上游服务发现限制
+pub mod discovery_limits {
+    // 默认重新解析间隔（秒）
+    pub const DEFAULT_INTERVAL: u64 = 30;
+    // 最小重新解析间隔（秒）
+    pub const MIN_INTERVAL: u64 = 1;
+    // 最大重新解析间隔（秒）
+    pub const MAX_INTERVAL: u64 = 3600;
+}
+
+// Kubernetes 服务发现
+pub mod kubernetes_discovery {
+    // 集群内 API Server 地址的环境变量
+    pub const SERVICE_HOST_ENV: &str = "KUBERNETES_SERVICE_HOST";
+    pub const SERVICE_PORT_ENV: &str = "KUBERNETES_SERVICE_PORT";
+    // 服务账号的令牌、CA 证书和命名空间文件
+    pub const TOKEN_PATH: &str = "/var/run/secrets/kubernetes.io/serviceaccount/token";
+    pub const CA_CERT_PATH: &str = "/var/run/secrets/kubernetes.io/serviceaccount/ca.crt";
+    pub const NAMESPACE_PATH: &str = "/var/run/secrets/kubernetes.io/serviceaccount/namespace";
+    // EndpointSlice 关联服务的标签
+    pub const SERVICE_NAME_LABEL: &str = "kubernetes.io/service-name";
+    // 列出 EndpointSlice 的请求超时时间（秒）
+    pub const REQUEST_TIMEOUT: u64 = 10;
+    // 一次监听请求的最长等待时间（秒），超时后重新列出并监听
+    pub const WATCH_TIMEOUT: u64 = 300;
+    // 监听失败后的重试间隔（秒）
+    pub const RETRY_INTERVAL: u64 = 5;
+}
+
+// Consul 服务发现
+pub mod consul_discovery {
+    // 默认 Consul HTTP 地址
+    pub const DEFAULT_ADDRESS: &str = "http://127.0.0.1:8500";
+    // 查询服务实例的请求超时时间（秒）
+    pub const REQUEST_TIMEOUT: u64 = 10;
+    // 一次阻塞查询的最长等待时间（秒），超时后重新发起查询
+    pub const WATCH_TIMEOUT: u64 = 300;
+    // 查询失败后的重试间隔（秒）
+    pub const RETRY_INTERVAL: u64 = 5;
+}
+
+// 配置文件监听
+pub mod config_watch {
+    // 自动重新加载前等待文件变化结束的默认时间（毫秒）
+    pub const DEFAULT_DEBOUNCE_MS: u64 = 2000;
+    // Kubernetes 挂载 ConfigMap 和 Secret 时使用的隐藏条目前缀（如 ..data 符号链接）
+    pub const KUBERNETES_PREFIX: &str = "..";
+}
+
+// 配置警告
+pub mod config_warnings {
+    // 流式模式下过短的响应体空闲超时（秒）
+    pub const SHORT_STREAM_IDLE_TIMEOUT: u64 = 30;
+}
+
+// 健康检查子命令
+pub mod healthcheck {
+    // 默认请求超时时间（秒）
+    pub const DEFAULT_TIMEOUT: u64 = 5;
+    // 最小请求超时时间（秒）
+    pub const MIN_TIMEOUT: u64 = 1;
+    // 最大请求超时时间（秒）
+    pub const MAX_TIMEOUT: u64 = 60;
+}
+
+// Tokio 运行时参数限制
+pub mod runtime_limits {
+    // 最大工作线程数
+    pub const MAX_WORKER_THREADS: usize = 1024;
+    // 最大阻塞线程数
+    pub const MAX_BLOCKING_THREADS: usize = 10_000;
+    // 最大 CPU 编号（Linux CPU 集合的容量为 1024）
+    pub const MAX_CPU: usize = 1023;
+}
+
+// 压测子命令
+pub mod loadgen {
+    // 默认的转发服务地址
+    pub const DEFAULT_URL: &str = "http://localhost:3000";
+    // 默认的请求路径
+    pub const DEFAULT_PATH: &str = "/v1/chat/completions";
+    // 默认的模型名称
+    pub const DEFAULT_MODEL: &str = "gpt-4o-mini";
+    // 默认的请求总数
+    pub const DEFAULT_REQUESTS: u64 = 100;
+    // 默认的并发数
+    pub const DEFAULT_CONCURRENCY: u32 = 10;
+    // 最大并发数
+    pub const MAX_CONCURRENCY: u32 = 10_000;
+    // 默认的提示词单词数
+    pub const DEFAULT_PROMPT_WORDS: usize = 32;
+    // 默认的最大生成 token 数
+    pub const DEFAULT_MAX_TOKENS: u32 = 16;
+    // 默认的请求超时时间（秒）
+    pub const DEFAULT_TIMEOUT: u64 = 60;
+    // 报告中输出的延迟百分位
+    pub const PERCENTILES: [f64; 4] = [50.0, 90.0, 99.0, 100.0];
+    // 没有收到响应的请求在状态统计中的名称
+    pub const ERROR_STATUS: &str = "error";
+}
+
+// 日志级别
+pub mod log_levels {
+    // 默认级别
+    pub const DEFAULT: &str = "info";
+    // 调试模式的级别
+    pub const DEBUG: &str = "debug";
+}
+
+// 日志文件
+pub mod log_file {
+    // 默认日志文件的最大大小（MB）
+    pub const DEFAULT_MAX_SIZE: u64 = 100;
+    // 最小日志文件大小（MB）
+    pub const MIN_MAX_SIZE: u64 = 1;
+    // 最大日志文件大小（MB）
+    pub const MAX_MAX_SIZE: u64 = 10_240;
+    // 默认保留的轮转文件数
+    pub const DEFAULT_MAX_FILES: u32 = 5;
+    // 最少保留的轮转文件数
+    pub const MIN_MAX_FILES: u32 = 1;
+    // 最多保留的轮转文件数
+    pub const MAX_MAX_FILES: u32 = 100;
+    // 等待写入的最大日志行数，超出后丢弃
+    pub const BUFFER: usize = 10_000;
+    // 退出时等待日志写完的时间（毫秒）
+    pub const FLUSH_TIMEOUT_MS: u64 = 2000;
+}
+
 // HTTP客户端配置限制
 pub mod http_client_limits {
     // 默认连接超时（秒）
@@ -34,12 +202,46 @@ pub mod http_client_limits {
     pub const MIN_IDLE_TIMEOUT: u64 = 5;
     // 最大空闲超时（秒）
     pub const MAX_IDLE_TIMEOUT: u64 = 1800;
+    // 最小流式响应空闲超时（秒）
+    pub const MIN_STREAM_IDLE_TIMEOUT: u64 = 1;
+    // 最大流式响应空闲超时（秒）
+    pub const MAX_STREAM_IDLE_TIMEOUT: u64 = 3600;
     // 默认keepalive时间（秒）
     pub const DEFAULT_KEEPALIVE: u32 = 30;
     // 最小keepalive时间（秒）
     pub const MIN_KEEPALIVE: u32 = 5;
     // 最大keepalive时间（秒）
     pub const MAX_KEEPALIVE: u32 = 600;
+    // 默认HTTP/2 keepalive ping间隔（秒）
+    pub const DEFAULT_HTTP2_KEEPALIVE_INTERVAL: u64 = 30;
+    // 最小HTTP/2 keepalive ping间隔（秒）
+    pub const MIN_HTTP2_KEEPALIVE_INTERVAL: u64 = 1;
+    // 最大HTTP/2 keepalive ping间隔（秒）
+    pub const MAX_HTTP2_KEEPALIVE_INTERVAL: u64 = 600;
+    // 默认HTTP/2 keepalive ping超时（秒）
+    pub const DEFAULT_HTTP2_KEEPALIVE_TIMEOUT: u64 = 20;
+    // 最小HTTP/2 keepalive ping超时（秒）
+    pub const MIN_HTTP2_KEEPALIVE_TIMEOUT: u64 = 1;
+    // 最大HTTP/2 keepalive ping超时（秒）
+    pub const MAX_HTTP2_KEEPALIVE_TIMEOUT: u64 = 300;
+}
+
+// Unix 域套接字上游
+pub mod unix_socket {
+    // URL 协议
+    pub const SCHEME: &str = "unix";
+    // 套接字路径与 HTTP 路径的分隔符
+    pub const PATH_SEPARATOR: char = ':';
+    // 请求未携带 Host 头时使用的值
+    pub const HOST: &str = "localhost";
+}
+
+// 路由熔断器
+pub mod route_breaker {
+    // 使用默认组的请求的路由名称
+    pub const DEFAULT_ROUTE: &str = "default";
+    // 计为路由失败的上游响应状态码（5xx 之外），通常意味着组内共用的凭据失效
+    pub const FAILURE_STATUSES: [u16; 2] = [401, 403];
 }
 
 // 重试配置限制
@@ -60,6 +262,14 @@ pub mod retry_limits {
     pub const MIN_INITIAL_MS: u32 = 100;
     // 最大初始重试延迟（毫秒）
     pub const MAX_INITIAL_MS: u32 = 10000;
+    // 最小重试时间预算（毫秒）
+    pub const MIN_MAX_ELAPSED_MS: u64 = 100;
+    // 最大重试时间预算（毫秒）
+    pub const MAX_MAX_ELAPSED_MS: u64 = 3_600_000;
+    // 默认重试的上游响应状态码
+    pub const DEFAULT_RETRY_ON_STATUS: [u16; 4] = [429, 500, 502, 503];
+    // 请求携带该头部时不重试，转发前移除
+    pub const NO_RETRY_HEADER: &str = "x-llmproxy-no-retry";
 }
 
 // 权重配置限制
@@ -84,6 +294,210 @@ pub mod rate_limit_limits {
     pub const DEFAULT_PER_SECOND: u32 = 100;
     // 默认突发请求数
     pub const DEFAULT_BURST: u32 = 200;
+    // Redis 不可用时本地令牌桶数量上限，超出时清理已完全恢复的令牌桶
+    pub const MAX_LOCAL_BUCKETS: usize = 10_000;
+}
+
+// 限流键常量
+pub mod rate_limit_keys {
+    // 未携带 Authorization Bearer 令牌时依次读取的 API 密钥请求头（Anthropic、Azure OpenAI）
+    pub const API_KEY_HEADERS: [&str; 2] = ["x-api-key", "api-key"];
+}
+
+// 限流响应头
+pub mod rate_limit_headers {
+    // 令牌桶容量（突发请求上限）
+    pub const LIMIT: &str = "x-ratelimit-limit";
+    // 令牌桶中剩余的请求数
+    pub const REMAINING: &str = "x-ratelimit-remaining";
+    // 被限流后可以重试的秒数，与 Retry-After 相同
+    pub const AFTER: &str = "x-ratelimit-after";
+}
+
+// SSE 心跳常量
+pub mod sse_heartbeat {
+    // 最短心跳间隔（秒）
+    pub const MIN_INTERVAL: u64 = 1;
+    // 最长心跳间隔（秒）
+    pub const MAX_INTERVAL: u64 = 300;
+    // 心跳注释行
+    pub const COMMENT: &[u8] = b": ping\n\n";
+}
+
+// 入站连接配置限制
+pub mod listener_limits {
+    // 默认监听队列长度
+    pub const DEFAULT_BACKLOG: u32 = u16::MAX as u32;
+    // 最小监听队列长度
+    pub const MIN_BACKLOG: u32 = 1;
+    // 最大监听队列长度
+    pub const MAX_BACKLOG: u32 = 65535;
+    // 每个 HTTP/2 连接的最小并发流数
+    pub const MIN_CONCURRENT_STREAMS: u32 = 1;
+    // 每个 HTTP/2 连接的最大并发流数
+    pub const MAX_CONCURRENT_STREAMS: u32 = 10000;
+    // 最小请求头大小（字节），HTTP/1 读缓冲区不能小于 8KiB
+    pub const MIN_HEADER_BYTES: u32 = 8192;
+    // 最大请求头大小（字节）
+    pub const MAX_HEADER_BYTES: u32 = 1024 * 1024;
+    // 最短客户端空闲超时（秒）
+    pub const MIN_IDLE_TIMEOUT: u64 = 1;
+    // 最长客户端空闲超时（秒）
+    pub const MAX_IDLE_TIMEOUT: u64 = 3600;
+    // 接受连接出错后的重试间隔（毫秒）
+    pub const ACCEPT_RETRY_DELAY_MS: u64 = 100;
+}
+
+// 入站请求头限制
+pub mod request_header_limits {
+    // 最小请求头数量上限
+    pub const MIN_COUNT: u32 = 1;
+    // 最大请求头数量上限
+    pub const MAX_COUNT: u32 = 10000;
+    // 最小请求头总字节数上限
+    pub const MIN_BYTES: u32 = 256;
+    // 最大请求头总字节数上限
+    pub const MAX_BYTES: u32 = 1024 * 1024;
+}
+
+// 响应缓存常量
+pub mod cache_limits {
+    // 最短缓存有效期（秒）
+    pub const MIN_TTL: u64 = 1;
+    // 最长缓存有效期（秒）
+    pub const MAX_TTL: u64 = 604_800;
+    // 默认缓存有效期（秒）
+    pub const DEFAULT_TTL: u64 = 300;
+    // 内存缓存的最小条目数
+    pub const MIN_ENTRIES: usize = 1;
+    // 内存缓存的最大条目数
+    pub const MAX_ENTRIES: usize = 1_000_000;
+    // 内存缓存的默认条目数
+    pub const DEFAULT_ENTRIES: usize = 1000;
+    // 默认可缓存的最大响应体（字节）
+    pub const DEFAULT_MAX_BODY_BYTES: usize = 1024 * 1024;
+    // 命中缓存的响应头部值
+    pub const HIT: &str = "hit";
+    // 未命中缓存
+    pub const MISS: &str = "miss";
+}
+
+// 响应缓冲常量
+pub mod response_buffer {
+    // 默认的非流式响应体缓冲上限（字节）
+    pub const DEFAULT_LIMIT: u64 = 4 * 1024 * 1024;
+    // 最小缓冲上限（字节）
+    pub const MIN_LIMIT: u64 = 1024;
+    // 最大缓冲上限（字节）
+    pub const MAX_LIMIT: u64 = 1024 * 1024 * 1024;
+}
+
+// Redis 常量
+pub mod redis_limits {
+    // 默认键前缀
+    pub const DEFAULT_KEY_PREFIX: &str = "llmproxy:";
+    // 连接和命令超时（毫秒）
+    pub const TIMEOUT_MS: u64 = 1000;
+    // 连接失败后的重试间隔（秒）
+    pub const RETRY_INTERVAL: u64 = 5;
+}
+
+// 并发限制常量
+pub mod concurrency_limits {
+    // 最小并发请求数
+    pub const MIN_CONCURRENT: u32 = 1;
+    // 最大并发请求数
+    pub const MAX_CONCURRENT: u32 = 100_000;
+    // 最小等待队列长度
+    pub const MIN_QUEUE_DEPTH: u32 = 1;
+    // 最大等待队列长度
+    pub const MAX_QUEUE_DEPTH: u32 = 100_000;
+    // 默认等待队列长度
+    pub const DEFAULT_QUEUE_DEPTH: u32 = 100;
+    // 最短排队等待时间（毫秒）
+    pub const MIN_QUEUE_WAIT_MS: u64 = 1;
+    // 最长排队等待时间（毫秒）
+    pub const MAX_QUEUE_WAIT_MS: u64 = 600_000;
+    // 默认排队等待时间（毫秒）
+    pub const DEFAULT_QUEUE_WAIT_MS: u64 = 30_000;
+    // 拒绝请求时的错误类型
+    pub const ERROR_TYPE: &str = "requests";
+    // 等待队列已满时的错误代码
+    pub const QUEUE_FULL_CODE: &str = "concurrency_limit_exceeded";
+    // 排队超时时的错误代码
+    pub const QUEUE_TIMEOUT_CODE: &str = "queue_timeout";
+    // 最小公平调度权重
+    pub const MIN_WEIGHT: u32 = 1;
+    // 最大公平调度权重
+    pub const MAX_WEIGHT: u32 = 1000;
+    // 默认公平调度权重
+    pub const DEFAULT_WEIGHT: u32 = 1;
+    // 公平调度虚拟时间的单位，每个请求推进的虚拟时间为该值除以权重
+    pub const VIRTUAL_TIME_UNIT: u64 = 1_000_000;
+    // 公平调度保存的客户端数量上限，超出时清理已空闲的客户端
+    pub const MAX_FAIR_CLIENTS: usize = 10_000;
+}
+
+// 代理错误响应常量
+pub mod proxy_errors {
+    // OpenAI 格式错误的 type 字段，区分代理错误与上游返回的错误
+    pub const ERROR_TYPE: &str = "proxy_error";
+    // 熔断器开启，请求没有发送到上游
+    pub const CIRCUIT_OPEN: &str = "circuit_open";
+    // 没有可用的上游，请求没有发送到上游
+    pub const NO_UPSTREAM: &str = "no_upstream_available";
+    // 请求上游失败（连接失败、超时等）
+    pub const UPSTREAM_ERROR: &str = "upstream_error";
+    // 配置错误（如上游组不存在）
+    pub const CONFIG_ERROR: &str = "configuration_error";
+    // 其他内部错误
+    pub const INTERNAL_ERROR: &str = "internal_error";
+}
+
+// 令牌速率限制常量
+pub mod token_limits {
+    // 最小每分钟 token 数
+    pub const MIN_TOKENS_PER_MINUTE: u64 = 1;
+    // 最大每分钟 token 数
+    pub const MAX_TOKENS_PER_MINUTE: u64 = 1_000_000_000;
+    // 令牌桶完全恢复的秒数
+    pub const WINDOW_SECONDS: f64 = 60.0;
+    // 令牌桶数量上限，超出时清理已完全恢复的令牌桶
+    pub const MAX_BUCKETS: usize = 10_000;
+    // 拒绝请求时的错误类型（与 OpenAI TPM 限流的错误类型一致）
+    pub const ERROR_TYPE: &str = "tokens";
+    // 拒绝请求时的错误代码
+    pub const ERROR_CODE: &str = "rate_limit_exceeded";
+}
+
+// 过载保护常量
+pub mod load_shedding {
+    // 最小内存上限（MB）
+    pub const MIN_MEMORY_MB: u64 = 16;
+    // 最大内存上限（MB）
+    pub const MAX_MEMORY_MB: u64 = 1_048_576;
+    // 最小事件循环延迟上限（毫秒）
+    pub const MIN_EVENT_LOOP_LAG_MS: u64 = 1;
+    // 最大事件循环延迟上限（毫秒）
+    pub const MAX_EVENT_LOOP_LAG_MS: u64 = 60_000;
+    // 最短检查间隔（毫秒）
+    pub const MIN_INTERVAL_MS: u64 = 10;
+    // 最长检查间隔（毫秒）
+    pub const MAX_INTERVAL_MS: u64 = 60_000;
+    // 默认检查间隔（毫秒）
+    pub const DEFAULT_INTERVAL_MS: u64 = 500;
+    // 默认的请求优先级头部
+    pub const DEFAULT_PRIORITY_HEADER: &str = "x-priority";
+    // 资源使用量达到上限的该比例时开始拒绝低优先级请求
+    pub const ELEVATED_RATIO: f64 = 0.85;
+    // 每 MB 的字节数
+    pub const BYTES_PER_MB: u64 = 1024 * 1024;
+    // 拒绝请求时建议的重试间隔（秒）
+    pub const RETRY_AFTER_SECONDS: u64 = 5;
+    // 拒绝请求时的错误类型
+    pub const ERROR_TYPE: &str = "server_error";
+    // 拒绝请求时的错误代码
+    pub const ERROR_CODE: &str = "overloaded";
 }
 
 // HTTP 头部常量
@@ -92,11 +506,25 @@ pub mod http_headers {
     pub const CONTENT_TYPE: &str = "content-type";
     // 传输编码头部
     pub const TRANSFER_ENCODING: &str = "transfer-encoding";
+    // 请求 ID 头部
+    pub const REQUEST_ID: &str = "x-request-id";
+    // 提示词 token 估算值响应头部
+    pub const PROMPT_TOKENS: &str = "x-llmproxy-prompt-tokens";
+    // 共享相同在途请求响应的响应头部
+    pub const COALESCED: &str = "x-llmproxy-coalesced";
+    // 响应缓存结果头部
+    pub const CACHE: &str = "x-llmproxy-cache";
 
     // 内容类型值
     pub mod content_types {
         // 事件流内容类型
         pub const EVENT_STREAM: &str = "text/event-stream";
+        // JSON 内容类型
+        pub const JSON: &str = "application/json";
+        // gzip 压缩归档内容类型
+        pub const GZIP: &str = "application/gzip";
+        // JSON Schema 内容类型
+        pub const SCHEMA_JSON: &str = "application/schema+json";
     }
 
     // 传输编码值
@@ -106,6 +534,100 @@ pub mod http_headers {
     }
 }
 
+// 上游请求头值模板中的占位符
+pub mod header_placeholders {
+    // 客户端 IP 地址
+    pub const CLIENT_IP: &str = "client_ip";
+    // 请求 ID，取自客户端的 x-request-id 头部，缺失时自动生成
+    pub const REQUEST_ID: &str = "request_id";
+    // 转发服务名称
+    pub const FORWARD_NAME: &str = "forward_name";
+    // 环境变量占位符前缀，如 ${env:VAR}
+    pub const ENV_PREFIX: &str = "env:";
+}
+
+// 上游 API 格式转换
+pub mod dialect {
+    // 请求未指定 max_tokens 时使用的值（Anthropic 要求必须设置）
+    pub const DEFAULT_MAX_TOKENS: u64 = 4096;
+    // Anthropic API 版本头部
+    pub const ANTHROPIC_VERSION_HEADER: &str = "anthropic-version";
+    // 请求未携带 anthropic-version 时使用的版本
+    pub const ANTHROPIC_VERSION: &str = "2023-06-01";
+    // Gemini 非流式生成方法
+    pub const GEMINI_GENERATE: &str = "generateContent";
+    // Gemini 流式生成方法
+    pub const GEMINI_STREAM_GENERATE: &str = "streamGenerateContent";
+    // 流式响应结束标记
+    pub const STREAM_DONE: &str = "[DONE]";
+}
+
+// 请求参数上限相关常量
+pub mod param_limits {
+    // 拒绝请求时的错误类型（OpenAI 错误格式）
+    pub const ERROR_TYPE: &str = "invalid_request_error";
+    // 拒绝请求时的错误代码
+    pub const ERROR_CODE: &str = "parameter_limit_exceeded";
+    // 提示词超出 token 上限时的错误代码（与 OpenAI 上下文超长的错误代码一致）
+    pub const PROMPT_ERROR_CODE: &str = "context_length_exceeded";
+}
+
+// PII 脱敏常量
+pub mod pii {
+    // 包含提示词的请求体字段（OpenAI、Anthropic、Gemini）
+    pub const PROMPT_FIELDS: [&str; 6] = [
+        "messages",
+        "prompt",
+        "input",
+        "system",
+        "instructions",
+        "contents",
+    ];
+    // 提示词字段中继续查找文本的嵌套字段
+    pub const TEXT_FIELDS: [&str; 3] = ["content", "text", "parts"];
+    // 自定义规则的默认替换文本
+    pub const DEFAULT_REPLACEMENT: &str = "[REDACTED]";
+    // 电子邮件地址
+    pub const EMAIL_PATTERN: &str = r"(?i)\b[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}\b";
+    // 电话号码：北美格式（可带国家代码）和中国大陆手机号
+    pub const PHONE_PATTERN: &str =
+        r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{3}\)\s?|\b\d{3}[\s.-]?)\d{3}[\s.-]?\d{4}\b|\b1[3-9]\d{9}\b";
+    // 银行卡号：13-19 位数字，可用空格或连字符分隔，匹配后再做 Luhn 校验
+    pub const CREDIT_CARD_PATTERN: &str = r"\b(?:\d[ -]?){12,18}\d\b";
+    // 电子邮件地址的替换文本
+    pub const EMAIL_REPLACEMENT: &str = "[EMAIL]";
+    // 电话号码的替换文本
+    pub const PHONE_REPLACEMENT: &str = "[PHONE]";
+    // 银行卡号的替换文本
+    pub const CREDIT_CARD_REPLACEMENT: &str = "[CREDIT_CARD]";
+}
+
+// 客户端预算相关常量
+pub mod budget {
+    // 默认标识客户端的请求头
+    pub const DEFAULT_HEADER: &str = "authorization";
+    // 拒绝请求时的错误类型（与 OpenAI 额度不足的错误类型一致）
+    pub const ERROR_TYPE: &str = "insufficient_quota";
+    // 拒绝请求时的错误代码
+    pub const ERROR_CODE: &str = "budget_exceeded";
+    // 每日预算
+    pub const DAILY: &str = "daily";
+    // 每月预算
+    pub const MONTHLY: &str = "monthly";
+}
+
+// 提示词 token 估算常量（与 OpenAI 聊天格式的计算方式一致）
+pub mod prompt_tokens {
+    // 每条消息的格式开销
+    pub const PER_MESSAGE: usize = 3;
+    // 消息携带 name 字段时的额外开销
+    pub const PER_NAME: usize = 1;
+    // 回复的起始标记开销
+    pub const REPLY_PRIMING: usize = 3;
+    // 请求体超过该字节数时在阻塞线程池中估算，避免分词阻塞异步工作线程
+    pub const BLOCKING_BODY_BYTES: usize = 64 * 1024;
+}
+
 //
 // 指标标签常量
 //
@@ -124,11 +646,55 @@ pub mod error_labels {
     pub const CONFIG_ERROR: &str = "config_error";
     // 验证错误
     pub const VALIDATION_ERROR: &str = "validation_error";
+    // 服务已禁用
+    pub const SERVICE_DISABLED: &str = "service_disabled";
+    // 请求方法或路径不在白名单中
+    pub const NOT_ALLOWED: &str = "not_allowed";
+    // 请求头数量或大小超出限制
+    pub const HEADERS_TOO_LARGE: &str = "headers_too_large";
+    // 客户端预算已用尽
+    pub const BUDGET_EXCEEDED: &str = "budget_exceeded";
+    // 每分钟 token 数超出限制
+    pub const TOKEN_LIMIT_EXCEEDED: &str = "token_limit_exceeded";
+    // 并发请求数超出限制且等待队列已满
+    pub const CONCURRENCY_LIMITED: &str = "concurrency_limited";
+    // 排队等待超时
+    pub const QUEUE_TIMEOUT: &str = "queue_timeout";
+    // 资源紧张时拒绝的请求
+    pub const LOAD_SHED: &str = "load_shed";
+    // 策略服务拒绝的请求
+    pub const POLICY_BLOCKED: &str = "policy_blocked";
+    // 策略服务不可用时拒绝的请求
+    pub const POLICY_UNAVAILABLE: &str = "policy_unavailable";
+    // 插件拒绝的请求
+    pub const PLUGIN_REJECTED: &str = "plugin_rejected";
+    // 插件出错时拒绝的请求
+    pub const PLUGIN_ERROR: &str = "plugin_error";
+    // 上游响应体长时间没有数据
+    pub const STREAM_IDLE_TIMEOUT: &str = "stream_idle_timeout";
+    // 客户端在流式响应完成前断开连接
+    pub const CLIENT_DISCONNECTED: &str = "client_disconnected";
     // 未知状态
     pub const UNKNOWN_ERROR: &str = "unknown_error";
     //
 }
 
+// 用量统计限制
+pub mod usage_limits {
+    // 价格对应的 token 数（价格单位为美元 / 百万 token）
+    pub const TOKENS_PER_PRICE_UNIT: f64 = 1_000_000.0;
+    // 按天汇总的用量保留天数
+    pub const RETENTION_DAYS: usize = 31;
+}
+
+// token 用量类型标签
+pub mod token_type_labels {
+    // 提示词
+    pub const PROMPT: &str = "prompt";
+    // 生成内容
+    pub const COMPLETION: &str = "completion";
+}
+
 // 上游标签
 pub mod upstream_labels {
     // 未知上游
@@ -137,6 +703,50 @@ pub mod upstream_labels {
     pub const RETRY: &str = "retry";
 }
 
+// 上游连接标签
+pub mod connection_labels {
+    // 新建的连接
+    pub const OPENED: &str = "opened";
+    // 建立失败的连接
+    pub const FAILED: &str = "failed";
+    // 请求使用了新建的连接
+    pub const NEW: &str = "new";
+    // 请求复用了连接池中的连接
+    pub const REUSED: &str = "reused";
+}
+
+// 上游连接建立阶段标签
+pub mod connection_phase_labels {
+    // DNS 解析
+    pub const DNS: &str = "dns";
+    // TCP 连接和 TLS 握手
+    pub const HANDSHAKE: &str = "handshake";
+}
+
+// 上游预热请求结果标签
+pub mod warmup_labels {
+    // 预热请求成功（2xx）
+    pub const SUCCESS: &str = "success";
+    // 预热请求失败或返回非 2xx 状态码
+    pub const FAILURE: &str = "failure";
+}
+
+// 状态码类别标签
+pub mod status_class_labels {
+    // 按状态码百位数字索引的类别
+    pub const CLASSES: [&str; 6] = ["unknown", "1xx", "2xx", "3xx", "4xx", "5xx"];
+    // 没有收到上游响应（连接失败、超时、熔断等）
+    pub const ERROR: &str = "error";
+}
+
+// 模型标签
+pub mod model_labels {
+    // 模型名称的最大长度，超出时使用 OTHER，避免客户端构造超长的标签值
+    pub const MAX_LEN: usize = 128;
+    // 模型名称过长
+    pub const OTHER: &str = "other";
+}
+
 // 负载均衡策略标签
 pub mod balance_strategy_labels {
     // 轮询
@@ -152,6 +762,35 @@ pub mod balance_strategy_labels {
 }
 
 // 熔断器限制
+pub mod adaptive_limits {
+    // 默认初始并发上限
+    pub const DEFAULT_INITIAL: u32 = 16;
+    // 默认最小并发上限
+    pub const DEFAULT_MIN: u32 = 1;
+    // 默认最大并发上限
+    pub const DEFAULT_MAX: u32 = 256;
+    // 并发上限的取值下限
+    pub const MIN_LIMIT: u32 = 1;
+    // 并发上限的取值上限
+    pub const MAX_LIMIT: u32 = 100_000;
+    // 默认延迟容忍倍数，请求延迟超过基线延迟的该倍数时视为过载
+    pub const DEFAULT_LATENCY_TOLERANCE: f64 = 2.0;
+    // 最小延迟容忍倍数
+    pub const MIN_LATENCY_TOLERANCE: f64 = 1.0;
+    // 最大延迟容忍倍数
+    pub const MAX_LATENCY_TOLERANCE: f64 = 100.0;
+    // 默认过载时的收缩系数
+    pub const DEFAULT_BACKOFF: f64 = 0.9;
+    // 最小收缩系数
+    pub const MIN_BACKOFF: f64 = 0.1;
+    // 最大收缩系数
+    pub const MAX_BACKOFF: f64 = 0.99;
+    // 基线延迟向较高延迟样本靠拢的比例，避免上游整体变慢后基线长期偏低
+    pub const BASELINE_DRIFT: f64 = 0.01;
+    // 参与延迟比较的最小基线延迟（毫秒），忽略亚毫秒级的延迟抖动
+    pub const MIN_BASELINE_MS: f64 = 1.0;
+}
+
 pub mod breaker_limits {
     // 熔断器默认失败阈值
     pub const DEFAULT_THRESHOLD: f64 = 0.5;
@@ -166,6 +805,451 @@ pub mod breaker_limits {
     pub const MIN_COOLDOWN: u64 = 1;
     // 最大冷却时间（秒）
     pub const MAX_COOLDOWN: u64 = 3600;
+
+    // 默认连接阶段连续失败阈值（DNS、TCP、TLS）
+    pub const DEFAULT_CONNECT_FAILURES: u32 = 3;
+    // 最小连接阶段连续失败阈值
+    pub const MIN_CONNECT_FAILURES: u32 = 1;
+    // 最大连接阶段连续失败阈值
+    pub const MAX_CONNECT_FAILURES: u32 = 100;
+
+    // 最小统计窗口大小（请求数或秒）
+    pub const MIN_WINDOW_SIZE: u64 = 1;
+    // 按请求数统计时的最大窗口大小
+    pub const MAX_WINDOW_REQUESTS: u64 = 10000;
+    // 按时间统计时的最大窗口大小（秒）
+    pub const MAX_WINDOW_SECS: u64 = 3600;
+    // 按失败率熔断所需的最小请求数范围
+    pub const MIN_MIN_REQUESTS: u64 = 1;
+    pub const MAX_MIN_REQUESTS: u64 = 10000;
+    // 按时间统计且未配置最小请求数时使用的最小请求数，与熔断器库的默认值一致
+    pub const DEFAULT_TIME_WINDOW_MIN_REQUESTS: usize = 10;
+    // 半开状态探测请求数范围
+    pub const MIN_HALF_OPEN_PROBES: u32 = 1;
+    pub const MAX_HALF_OPEN_PROBES: u32 = 100;
+}
+
+// 会话粘滞配置限制
+pub mod sticky_limits {
+    // 默认会话粘滞有效期（秒）
+    pub const DEFAULT_TTL: u64 = 3600;
+    // 最小会话粘滞有效期（秒）
+    pub const MIN_TTL: u64 = 1;
+    // 最大会话粘滞有效期（秒）
+    pub const MAX_TTL: u64 = 86400;
+    // 每个进程最多保留的会话数量，超过后清理过期会话
+    pub const MAX_SESSIONS: usize = 100_000;
+}
+
+// 审计日志限制
+pub mod audit_limits {
+    // 默认内存中保留的审计记录数量
+    pub const DEFAULT_MAX_ENTRIES: usize = 1000;
+    // 最小保留数量
+    pub const MIN_MAX_ENTRIES: usize = 1;
+    // 最大保留数量
+    pub const MAX_MAX_ENTRIES: usize = 100_000;
+    // 默认分页大小
+    pub const DEFAULT_PAGE_SIZE: usize = 50;
+    // 最大分页大小
+    pub const MAX_PAGE_SIZE: usize = 500;
+}
+
+// 请求审计日志常量
+pub mod audit_sink {
+    // 默认记录的最大请求体或响应体（字节）
+    pub const DEFAULT_MAX_BODY_BYTES: usize = 64 * 1024;
+    // 最大记录的请求体或响应体（字节）
+    pub const MAX_MAX_BODY_BYTES: usize = 16 * 1024 * 1024;
+    // 默认缓冲的记录数
+    pub const DEFAULT_BUFFER: usize = 10_000;
+    // 最小缓冲的记录数
+    pub const MIN_BUFFER: usize = 1;
+    // 最大缓冲的记录数
+    pub const MAX_BUFFER: usize = 1_000_000;
+    // 每批写入的最大记录数
+    pub const BATCH_SIZE: usize = 100;
+    // HTTP 接收端的请求超时（秒）
+    pub const HTTP_TIMEOUT: u64 = 10;
+    // HTTP 接收端的内容类型
+    pub const CONTENT_TYPE: &str = "application/x-ndjson";
+    // 已写入的记录
+    pub const WRITTEN: &str = "written";
+    // 缓冲已满或写入失败而丢弃的记录
+    pub const DROPPED: &str = "dropped";
+}
+
+// 外部策略服务相关常量
+pub mod policy {
+    // 默认请求超时（毫秒）
+    pub const DEFAULT_TIMEOUT_MS: u64 = 2_000;
+    // 最小请求超时（毫秒）
+    pub const MIN_TIMEOUT_MS: u64 = 10;
+    // 最大请求超时（毫秒）
+    pub const MAX_TIMEOUT_MS: u64 = 60_000;
+    // 拒绝请求时默认的响应状态码
+    pub const DEFAULT_BLOCK_STATUS: u16 = 403;
+    // 拒绝请求时的错误类型（OpenAI 错误格式）
+    pub const ERROR_TYPE: &str = "policy_violation";
+    // 策略服务未给出原因时的错误信息
+    pub const DEFAULT_BLOCK_MESSAGE: &str = "Request blocked by policy";
+    // 策略服务不可用且拒绝请求时的错误信息
+    pub const UNAVAILABLE_MESSAGE: &str = "Policy service unavailable";
+    // 放行请求
+    pub const ALLOW: &str = "allow";
+    // 改写请求体后放行
+    pub const MODIFY: &str = "modify";
+    // 拒绝请求
+    pub const BLOCK: &str = "block";
+    // 调用策略服务失败
+    pub const ERROR: &str = "error";
+}
+
+// WASM 插件相关常量
+pub mod plugin {
+    // 默认每次调用的燃料（约等于执行的指令数）
+    pub const DEFAULT_FUEL: u64 = 100_000_000;
+    // 最小每次调用的燃料
+    pub const MIN_FUEL: u64 = 1_000;
+    // 最大每次调用的燃料
+    pub const MAX_FUEL: u64 = 10_000_000_000;
+    // 默认最大线性内存（字节）
+    pub const DEFAULT_MAX_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+    // 最小线性内存（字节，一个 WASM 内存页）
+    pub const MIN_MAX_MEMORY_BYTES: usize = 64 * 1024;
+    // 最大线性内存（字节）
+    pub const MAX_MAX_MEMORY_BYTES: usize = 1024 * 1024 * 1024;
+    // 宿主函数所在的模块名称
+    pub const HOST_MODULE: &str = "llmproxy";
+    // 插件导出的线性内存
+    pub const MEMORY_EXPORT: &str = "memory";
+    // 插件导出的内存分配函数
+    pub const ALLOC_EXPORT: &str = "alloc";
+    // 插件拒绝请求时默认的响应状态码
+    pub const DEFAULT_REJECT_STATUS: u16 = 403;
+    // 插件出错时的错误类型（OpenAI 错误格式）
+    pub const ERROR_TYPE: &str = "plugin_error";
+    // 插件出错时的错误信息
+    pub const ERROR_MESSAGE: &str = "Plugin failed to process the request";
+    // 插件未做修改
+    pub const CONTINUE: &str = "continue";
+    // 插件修改了请求或响应
+    pub const MODIFY: &str = "modify";
+    // 插件拒绝了请求
+    pub const REJECT: &str = "reject";
+    // 插件执行出错
+    pub const ERROR: &str = "error";
+}
+
+// 访问日志相关常量
+pub mod access_log {
+    // 默认日志文件的最大大小（MB）
+    pub const DEFAULT_MAX_SIZE: u64 = 100;
+    // 最小日志文件大小（MB）
+    pub const MIN_MAX_SIZE: u64 = 1;
+    // 最大日志文件大小（MB）
+    pub const MAX_MAX_SIZE: u64 = 10_240;
+    // 默认保留的轮转文件数
+    pub const DEFAULT_MAX_FILES: u32 = 5;
+    // 最少保留的轮转文件数
+    pub const MIN_MAX_FILES: u32 = 1;
+    // 最多保留的轮转文件数
+    pub const MAX_MAX_FILES: u32 = 100;
+    // 默认缓冲的记录数
+    pub const DEFAULT_BUFFER: usize = 10_000;
+    // 最小缓冲的记录数
+    pub const MIN_BUFFER: usize = 1;
+    // 最大缓冲的记录数
+    pub const MAX_BUFFER: usize = 1_000_000;
+    // 每批写入的最大记录数
+    pub const BATCH_SIZE: usize = 100;
+    // combined 格式中缺失字段的占位符
+    pub const EMPTY_FIELD: &str = "-";
+    // 已写入的记录
+    pub const WRITTEN: &str = "written";
+    // 缓冲已满或写入失败而丢弃的记录
+    pub const DROPPED: &str = "dropped";
+}
+
+// 指标推送相关常量
+pub mod metrics_export {
+    // 默认推送间隔（秒）
+    pub const DEFAULT_INTERVAL: u64 = 15;
+    // 最短推送间隔（秒）
+    pub const MIN_INTERVAL: u64 = 1;
+    // 最长推送间隔（秒）
+    pub const MAX_INTERVAL: u64 = 3600;
+    // OTLP 推送请求超时（秒）
+    pub const HTTP_TIMEOUT: u64 = 10;
+    // OTLP 资源中的服务名称
+    pub const SERVICE_NAME: &str = "llmproxy";
+    // OTLP 指标的累计方式：累计值
+    pub const CUMULATIVE: u8 = 2;
+    // 单个 StatsD UDP 数据包的最大字节数，避免超过常见网络的 MTU
+    pub const MAX_PACKET_BYTES: usize = 1432;
+    // 推送成功
+    pub const SUCCESS: &str = "success";
+    // 推送失败
+    pub const FAILURE: &str = "failure";
+}
+
+// 告警相关常量
+pub mod alert_limits {
+    // 默认评估间隔（秒）
+    pub const DEFAULT_INTERVAL: u64 = 15;
+    // 最短评估间隔（秒）
+    pub const MIN_INTERVAL: u64 = 1;
+    // 最长评估间隔（秒）
+    pub const MAX_INTERVAL: u64 = 3600;
+    // 默认统计窗口（秒）
+    pub const DEFAULT_WINDOW: u64 = 300;
+    // 最短统计窗口（秒）
+    pub const MIN_WINDOW: u64 = 10;
+    // 最长统计窗口（秒）
+    pub const MAX_WINDOW: u64 = 86400;
+    // 默认耗时分位数
+    pub const DEFAULT_QUANTILE: f64 = 0.95;
+    // 默认窗口内的最少请求数
+    pub const DEFAULT_MIN_REQUESTS: u64 = 1;
+    // Webhook 请求超时（秒）
+    pub const WEBHOOK_TIMEOUT: u64 = 10;
+    // 告警触发
+    pub const FIRING: &str = "firing";
+    // 告警恢复
+    pub const RESOLVED: &str = "resolved";
+}
+
+// 指标直方图相关常量
+pub mod metrics_buckets {
+    // 请求耗时类直方图的默认分桶上限（秒）
+    pub const DURATION: [f64; 13] = [
+        0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 60.0,
+    ];
+    // 首字节耗时直方图的默认分桶上限（秒）
+    pub const TTFB: [f64; 12] = [
+        0.05, 0.1, 0.25, 0.5, 1.0, 2.0, 3.0, 5.0, 10.0, 20.0, 30.0, 60.0,
+    ];
+    // 上游连接建立耗时直方图的分桶上限（秒）
+    pub const CONNECT: [f64; 13] = [
+        0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+    ];
+    // 自定义分桶的最大数量
+    pub const MAX_BUCKETS: usize = 64;
+}
+
+// 响应压缩相关常量
+pub mod compression {
+    // 默认压缩的最小响应体大小（字节）
+    pub const DEFAULT_MIN_SIZE: u16 = 1024;
+    // 压缩的最小响应体大小下限（字节），避免压缩空响应
+    pub const MIN_SIZE: u16 = 1;
+}
+
+// WebSocket 代理相关常量
+pub mod websocket {
+    // 默认连接空闲超时（秒）
+    pub const DEFAULT_IDLE_TIMEOUT: u64 = 300;
+    // 最短连接空闲超时（秒）
+    pub const MIN_IDLE_TIMEOUT: u64 = 1;
+    // 最长连接空闲超时（秒）
+    pub const MAX_IDLE_TIMEOUT: u64 = 86_400;
+    // 每个方向的读缓冲区大小（字节）
+    pub const BUFFER_SIZE: usize = 16 * 1024;
+    // 上游接受升级，连接已建立
+    pub const UPGRADED: &str = "upgraded";
+    // 上游拒绝升级
+    pub const REJECTED: &str = "rejected";
+    // 请求上游或升级连接失败
+    pub const ERROR: &str = "error";
+    // 客户端发往上游的数据
+    pub const CLIENT_TO_UPSTREAM: &str = "client_to_upstream";
+    // 上游发往客户端的数据
+    pub const UPSTREAM_TO_CLIENT: &str = "upstream_to_client";
+}
+
+// 敏感信息脱敏
+pub mod redact {
+    // 需要脱敏的配置字段
+    pub const SECRET_FIELDS: [&str; 4] = ["token", "password", "client_secret", "passphrase"];
+    // 值需要全部脱敏的请求头和查询参数字段（映射中的值或操作列表中的 value），其中常包含 API 密钥
+    pub const SECRET_VALUE_FIELDS: [&str; 3] = ["headers", "default_headers", "query_params"];
+    // 脱敏后的占位值
+    pub const MASKED_VALUE: &str = "******";
+}
+
+// OAuth2 认证
+pub mod oauth2 {
+    // 默认在访问令牌过期前刷新的提前量（秒）
+    pub const DEFAULT_REFRESH_BEFORE: u64 = 60;
+    // 最大提前刷新时间（秒）
+    pub const MAX_REFRESH_BEFORE: u64 = 3600;
+    // 令牌端点未返回 expires_in 时假定的有效期（秒）
+    pub const DEFAULT_EXPIRES_IN: u64 = 3600;
+    // 服务账号 JWT 断言的有效期（秒），Google 令牌端点允许的最大值
+    pub const JWT_LIFETIME: u64 = 3600;
+    // 请求令牌端点的超时时间（秒）
+    pub const REQUEST_TIMEOUT: u64 = 10;
+    // 默认的 Google 令牌端点
+    pub const GOOGLE_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+    // 服务账号默认的权限范围
+    pub const DEFAULT_SERVICE_ACCOUNT_SCOPE: &str =
+        "https://www.googleapis.com/auth/cloud-platform";
+    // 服务账号 JWT 断言的授权类型
+    pub const JWT_BEARER_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:jwt-bearer";
+    // 客户端凭证模式的授权类型
+    pub const CLIENT_CREDENTIALS_GRANT_TYPE: &str = "client_credentials";
+}
+
+// 进程重启
+pub mod restart {
+    // 记录旧进程 ID 的环境变量，新进程绑定监听地址后据此通知旧进程退出
+    pub const PARENT_ENV: &str = "LLMPROXY_RESTART_PARENT";
+    // 可执行文件被替换后 /proc/self/exe 路径的后缀
+    pub const DELETED_SUFFIX: &str = " (deleted)";
+}
+
+// systemd 集成
+pub mod systemd {
+    // 状态通知套接字的环境变量
+    pub const NOTIFY_SOCKET: &str = "NOTIFY_SOCKET";
+    // 套接字激活的目标进程 ID
+    pub const LISTEN_PID: &str = "LISTEN_PID";
+    // 套接字激活传入的描述符数量
+    pub const LISTEN_FDS: &str = "LISTEN_FDS";
+    // 套接字激活传入的第一个描述符
+    pub const LISTEN_FDS_START: i32 = 3;
+    // 看门狗超时时间（微秒）
+    pub const WATCHDOG_USEC: &str = "WATCHDOG_USEC";
+    // 看门狗监控的进程 ID
+    pub const WATCHDOG_PID: &str = "WATCHDOG_PID";
+    // 服务已就绪
+    pub const READY: &str = "READY=1";
+    // 服务正在停止
+    pub const STOPPING: &str = "STOPPING=1";
+    // 看门狗心跳
+    pub const WATCHDOG: &str = "WATCHDOG=1";
+}
+
+// 外部命令认证
+pub mod external_auth {
+    // 默认重新执行命令的间隔（秒）
+    pub const DEFAULT_REFRESH_INTERVAL: u64 = 300;
+    // 最小重新执行间隔（秒）
+    pub const MIN_REFRESH_INTERVAL: u64 = 1;
+    // 最大重新执行间隔（秒）
+    pub const MAX_REFRESH_INTERVAL: u64 = 86400;
+    // 默认命令执行超时时间（秒）
+    pub const DEFAULT_TIMEOUT: u64 = 10;
+    // 最小命令执行超时时间（秒）
+    pub const MIN_TIMEOUT: u64 = 1;
+    // 最大命令执行超时时间（秒）
+    pub const MAX_TIMEOUT: u64 = 300;
+}
+
+// 上游预热请求
+pub mod warmup_limits {
+    // 默认请求方法
+    pub const DEFAULT_METHOD: &str = "GET";
+    // 默认每个上游地址发送的预热请求数
+    pub const DEFAULT_REQUESTS: u32 = 1;
+    // 最小预热请求数
+    pub const MIN_REQUESTS: u32 = 1;
+    // 最大预热请求数
+    pub const MAX_REQUESTS: u32 = 64;
+    // 默认预热请求超时时间（秒）
+    pub const DEFAULT_TIMEOUT: u64 = 10;
+    // 最小预热请求超时时间（秒）
+    pub const MIN_TIMEOUT: u64 = 1;
+    // 最大预热请求超时时间（秒）
+    pub const MAX_TIMEOUT: u64 = 300;
+}
+
+// 密钥文件
+pub mod secret_file {
+    // 重新读取密钥文件的间隔（秒），便于轮换挂载的密钥
+    pub const REFRESH_INTERVAL: u64 = 30;
+}
+
+// 转发服务的探针路径，不经过路由、限流、访问日志和指标
+pub mod forward_paths {
+    // 存活探针
+    pub const HEALTHZ: &str = "/__llmproxy/healthz";
+    // 就绪探针
+    pub const READYZ: &str = "/__llmproxy/readyz";
+}
+
+// 管理服务路径
+pub mod admin_paths {
+    // 健康检查
+    pub const HEALTH: &str = "/health";
+    // 默认的 Prometheus 指标路径
+    pub const DEFAULT_METRICS: &str = "/metrics";
+    // 管理面板
+    pub const DASHBOARD: &str = "/dashboard";
+}
+
+// 管理服务 HTTPS 限制
+pub mod admin_tls {
+    // TLS 握手超时（秒），超时的连接直接关闭
+    pub const HANDSHAKE_TIMEOUT: u64 = 10;
+    // 等待处理的已握手连接数
+    pub const ACCEPT_BACKLOG: usize = 128;
+    // 接受连接出错后的重试间隔（毫秒）
+    pub const ACCEPT_RETRY_DELAY_MS: u64 = 100;
+}
+
+// 事件流限制
+pub mod event_limits {
+    // 访问事件广播通道容量，订阅者落后超过该数量时丢弃旧事件
+    pub const ACCESS_CHANNEL_CAPACITY: usize = 1024;
+    // 系统事件广播通道容量
+    pub const SYSTEM_CHANNEL_CAPACITY: usize = 256;
+    // 同一转发服务两次限流事件之间的最小间隔（毫秒），期间的限流请求合并到下一次事件中
+    pub const RATE_LIMIT_EVENT_INTERVAL_MS: u64 = 1000;
+}
+
+// 事件流中的事件类型
+pub mod event_types {
+    // 访问事件
+    pub const ACCESS: &str = "access";
+    // 系统事件
+    pub const SYSTEM: &str = "system";
+    // 订阅者落后，部分事件被丢弃
+    pub const LAGGED: &str = "lagged";
+}
+
+// 配置重载结果标签
+pub mod reload_result_labels {
+    // 成功
+    pub const SUCCESS: &str = "success";
+    // 失败（继续使用上一次有效的配置）
+    pub const FAILURE: &str = "failure";
+}
+
+// 配置重载触发方式标签
+pub mod reload_trigger_labels {
+    // SIGHUP 信号
+    pub const SIGNAL: &str = "signal";
+    // 管理 API
+    pub const API: &str = "api";
+    // 监听到配置变化
+    pub const WATCH: &str = "watch";
+}
+
+// 支持包相关常量
+pub mod support_bundle {
+    // 内存中保留的最近日志行数
+    pub const RECENT_LOG_LINES: usize = 2000;
+    // 收集的环境变量前缀
+    pub const ENV_PREFIX: &str = "LLMPROXY_";
+    // 额外收集的环境变量
+    pub const EXTRA_ENV_VARS: [&str; 2] = ["RUST_LOG", "RUST_BACKTRACE"];
+}
+
+// 上游运行统计限制
+pub mod upstream_stats_limits {
+    // 近期错误率的衰减系数，每次请求的结果占 1/ERROR_RATE_DECAY 的权重
+    pub const ERROR_RATE_DECAY: u32 = 10;
 }
 
 // 熔断器状态标签
@@ -186,6 +1270,8 @@ pub mod breaker_result_labels {
     pub const FAILURE: &str = "failure";
     // 被拒绝（熔断器开启）
     pub const REJECTED: &str = "rejected";
+    // 连接阶段失败（DNS、TCP、TLS）
+    pub const CONNECT_FAILURE: &str = "connect_failure";
 }
 
 //
@@ -204,6 +1290,8 @@ pub mod api {
         pub const BEARER_PREFIX: &str = "Bearer ";
         // Bearer 认证安全方案标识符 (用于 OpenAPI)
         pub const BEARER_SECURITY_SCHEME: &str = "bearer_auth";
+        // 未认证请求的审计操作者
+        pub const ANONYMOUS_ACTOR: &str = "anonymous";
     }
 
     // API 响应状态常量
@@ -218,6 +1306,8 @@ pub mod api {
     pub mod error_types {
         // 未授权
         pub const UNAUTHORIZED: &str = "Unauthorized";
+        // 禁止访问
+        pub const FORBIDDEN: &str = "Forbidden";
         // 未找到
         pub const NOT_FOUND: &str = "NotFound";
         // 冲突
@@ -226,5 +1316,11 @@ pub mod api {
         pub const INTERNAL_SERVER_ERROR: &str = "InternalServerError";
         // 请求错误
         pub const BAD_REQUEST: &str = "BadRequest";
+        // 配置文档解析错误
+        pub const PARSE_ERROR: &str = "ParseError";
+        // 配置校验错误
+        pub const VALIDATION_ERROR: &str = "ValidationError";
+        // 配置预处理错误（如无效的请求头）
+        pub const CONFIG_ERROR: &str = "ConfigError";
     }
 }
@@ -0,0 +1,50 @@
+pub mod consul;
+pub mod dns;
+pub mod kubernetes;
+pub use consul::ConsulDiscovery;
+pub use dns::DnsDiscovery;
+pub use kubernetes::{KubernetesApi, KubernetesDiscovery};
+
+use reqwest::Url;
+use std::fmt;
+
+/// 服务发现得到的上游地址，转发时替换上游 URL 中的主机和端口
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Endpoint {
+    /// 主机名或 IP 地址
+    pub host: String,
+    /// 端口
+    pub port: u16,
+}
+
+impl Endpoint {
+    /// 创建上游地址
+    pub fn new(host: impl Into<String>, port: u16) -> Self {
+        Self {
+            host: host.into(),
+            port,
+        }
+    }
+
+    /// 将 URL 的主机和端口替换为该地址
+    pub fn apply(&self, url: &mut Url) {
+        let host = if self.host.contains(':') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        };
+        let _ = url.set_host(Some(&host));
+        let _ = url.set_port(Some(self.port));
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // IPv6 地址加方括号，与 URL 中的写法一致
+        if self.host.contains(':') {
+            write!(f, "[{}]:{}", self.host, self.port)
+        } else {
+            write!(f, "{}:{}", self.host, self.port)
+        }
+    }
+}
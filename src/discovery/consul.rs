@@ -0,0 +1,267 @@
+use futures_util::future::join_all;
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use std::{sync::Arc, time::Duration};
+use tokio_graceful_shutdown::{IntoSubsystem, SubsystemHandle};
+use tracing::{debug, info, warn};
+
+use super::Endpoint;
+use crate::{
+    config::{
+        source::consul_index, ConsulDiscoveryConfig, GroupDiscoveryType, UpstreamGroupConfig,
+    },
+    error::AppError,
+    r#const::{config_source, consul_discovery},
+    upstream::UpstreamManager,
+};
+
+// 启用了 Consul 服务发现的上游组
+struct ConsulTarget {
+    // 上游组名称
+    group: String,
+    // 服务健康实例的查询地址
+    url: Url,
+    // 服务发现配置
+    config: ConsulDiscoveryConfig,
+}
+
+/// Consul 服务发现
+///
+/// 以阻塞查询监听启用了 Consul 服务发现的上游组所对应的服务，并将符合健康状态和标签条件的实例地址
+/// 同步到上游管理器。没有符合条件的实例或查询失败时保留上一次的地址
+pub struct ConsulDiscovery {
+    // 上游管理器
+    manager: Arc<UpstreamManager>,
+    // HTTP 客户端
+    client: Client,
+    // 启用了 Consul 服务发现的上游组
+    targets: Vec<ConsulTarget>,
+}
+
+impl ConsulDiscovery {
+    /// 为启用了 Consul 服务发现的上游组创建服务发现，没有这样的上游组时返回 None
+    pub fn new(
+        manager: Arc<UpstreamManager>,
+        groups: &[UpstreamGroupConfig],
+    ) -> Result<Option<Self>, AppError> {
+        let mut targets = Vec::new();
+        for group in groups {
+            let Some(config) = group
+                .discovery
+                .as_ref()
+                .filter(|discovery| discovery.r#type == GroupDiscoveryType::Consul)
+                .and_then(|discovery| discovery.consul.clone())
+            else {
+                continue;
+            };
+            let mut url = Url::parse(&config.address).map_err(|e| {
+                AppError::Config(format!(
+                    "Invalid Consul address {:?} for upstream group '{}': {}",
+                    config.address, group.name, e
+                ))
+            })?;
+            url.set_path(&format!("/v1/health/service/{}", config.service));
+            {
+                let mut query = url.query_pairs_mut();
+                if config.passing {
+                    query.append_pair("passing", "true");
+                }
+                if let Some(datacenter) = &config.datacenter {
+                    query.append_pair("dc", datacenter);
+                }
+                for tag in &config.tags {
+                    query.append_pair("tag", tag);
+                }
+            }
+            targets.push(ConsulTarget {
+                group: group.name.clone(),
+                url,
+                config,
+            });
+        }
+        if targets.is_empty() {
+            return Ok(None);
+        }
+
+        let client = Client::builder()
+            .connect_timeout(Duration::from_secs(consul_discovery::REQUEST_TIMEOUT))
+            .build()?;
+
+        Ok(Some(Self {
+            manager,
+            client,
+            targets,
+        }))
+    }
+
+    /// 查询全部服务一次并同步地址，返回地址发生变化的上游组数量
+    pub async fn refresh(&self) -> usize {
+        join_all(self.targets.iter().map(|target| async move {
+            match self.query(target, 0).await {
+                Ok((endpoints, _)) => self.apply(target, endpoints).await,
+                Err(e) => {
+                    warn!(
+                        "Consul discovery for upstream group '{}' failed, keeping the previous endpoints: {}",
+                        target.group, e
+                    );
+                    false
+                }
+            }
+        }))
+        .await
+        .into_iter()
+        .filter(|changed| *changed)
+        .count()
+    }
+
+    // 查询服务的实例地址，索引不为 0 时以阻塞查询等待索引之后的变化，返回实例地址和新的索引
+    async fn query(
+        &self,
+        target: &ConsulTarget,
+        index: u64,
+    ) -> Result<(Vec<Endpoint>, u64), AppError> {
+        let mut url = target.url.clone();
+        let timeout = if index > 0 {
+            url.query_pairs_mut()
+                .append_pair("index", &index.to_string())
+                .append_pair("wait", &format!("{}s", consul_discovery::WATCH_TIMEOUT));
+            // Consul 在等待时间上附加少量随机抖动
+            consul_discovery::WATCH_TIMEOUT + consul_discovery::REQUEST_TIMEOUT
+        } else {
+            consul_discovery::REQUEST_TIMEOUT
+        };
+
+        let mut request = self.client.get(url).timeout(Duration::from_secs(timeout));
+        if let Ok(token) = std::env::var(config_source::CONSUL_TOKEN_ENV) {
+            request = request.header(config_source::CONSUL_TOKEN_HEADER, token);
+        }
+        let response = request.send().await?.error_for_status()?;
+        let index = consul_index(&response);
+        let entries: Vec<ServiceEntry> = response.json().await?;
+
+        let endpoints = entries
+            .into_iter()
+            // 旧版本的 Consul 只按第一个标签过滤
+            .filter(|entry| {
+                target
+                    .config
+                    .tags
+                    .iter()
+                    .all(|tag| entry.service.tags.iter().flatten().any(|t| t == tag))
+            })
+            .map(|entry| {
+                // 实例未注册地址时使用节点地址
+                let host = if entry.service.address.is_empty() {
+                    entry.node.address
+                } else {
+                    entry.service.address
+                };
+                Endpoint::new(host, entry.service.port)
+            })
+            .collect();
+        Ok((endpoints, index))
+    }
+
+    // 将实例地址同步到上游管理器，地址发生变化时返回 true
+    async fn apply(&self, target: &ConsulTarget, endpoints: Vec<Endpoint>) -> bool {
+        if endpoints.is_empty() {
+            warn!(
+                "Consul discovery found no instances of service {:?} (upstream group '{}'), keeping the previous endpoints",
+                target.config.service, target.group
+            );
+            return false;
+        }
+        debug!(
+            "Consul discovery found {} instances of service {:?}",
+            endpoints.len(),
+            target.config.service
+        );
+
+        match self
+            .manager
+            .set_group_endpoints(&target.group, endpoints)
+            .await
+        {
+            Ok(changed) => changed,
+            Err(e) => {
+                warn!(
+                    "Failed to update endpoints of upstream group '{}': {}",
+                    target.group, e
+                );
+                false
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl IntoSubsystem<AppError> for ConsulDiscovery {
+    async fn run(self, subsys: SubsystemHandle) -> Result<(), AppError> {
+        info!(
+            "Consul discovery started for {} upstream groups",
+            self.targets.len()
+        );
+
+        // 每个上游组各自以阻塞查询监听，失败后按间隔重试
+        join_all(self.targets.iter().map(|target| async {
+            let mut index = 0;
+            loop {
+                tokio::select! {
+                    result = self.query(target, index) => {
+                        match result {
+                            Ok((endpoints, next)) => {
+                                // 索引回退时（例如 Consul 重建）重新开始
+                                index = if next < index { 0 } else { next };
+                                self.apply(target, endpoints).await;
+                                continue;
+                            }
+                            Err(e) => {
+                                warn!(
+                                    "Consul discovery for upstream group '{}' failed: {}, retrying in {}s",
+                                    target.group,
+                                    e,
+                                    consul_discovery::RETRY_INTERVAL
+                                );
+                            }
+                        }
+                        tokio::select! {
+                            _ = tokio::time::sleep(Duration::from_secs(consul_discovery::RETRY_INTERVAL)) => {}
+                            _ = subsys.on_shutdown_requested() => break,
+                        }
+                    }
+                    _ = subsys.on_shutdown_requested() => break,
+                }
+            }
+        }))
+        .await;
+
+        info!("Consul discovery stopped");
+        Ok(())
+    }
+}
+
+// 健康检查接口返回的服务实例
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ServiceEntry {
+    node: ServiceNode,
+    service: ServiceInstance,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ServiceNode {
+    #[serde(default)]
+    address: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ServiceInstance {
+    #[serde(default)]
+    address: String,
+    port: u16,
+    // 没有标签时为 null
+    #[serde(default)]
+    tags: Option<Vec<String>>,
+}
@@ -0,0 +1,191 @@
+use futures_util::future::join_all;
+use hickory_resolver::{system_conf::read_system_conf, TokioAsyncResolver};
+use reqwest::Url;
+use std::{sync::Arc, time::Duration};
+use tokio_graceful_shutdown::{IntoSubsystem, SubsystemHandle};
+use tracing::{debug, info, warn};
+
+use super::Endpoint;
+use crate::{
+    config::{DnsDiscoveryConfig, DnsRecordType, UpstreamConfig},
+    error::AppError,
+    upstream::UpstreamManager,
+};
+
+// 启用了 DNS 服务发现的上游
+struct DnsTarget {
+    // 上游服务名称
+    upstream: String,
+    // 解析的主机名
+    host: String,
+    // A/AAAA 记录使用的端口
+    port: u16,
+    // 服务发现配置
+    config: DnsDiscoveryConfig,
+}
+
+/// DNS 服务发现
+///
+/// 按配置的间隔解析启用了 DNS 服务发现的上游的主机名，并将解析得到的地址同步到上游管理器。
+/// 解析失败或没有记录时保留上一次的地址，尚未解析成功时直接使用上游 URL
+pub struct DnsDiscovery {
+    // 上游管理器
+    manager: Arc<UpstreamManager>,
+    // DNS 解析器
+    resolver: TokioAsyncResolver,
+    // 启用了 DNS 服务发现的上游
+    targets: Vec<DnsTarget>,
+}
+
+impl DnsDiscovery {
+    /// 为启用了 DNS 服务发现的上游创建服务发现，没有这样的上游时返回 None
+    pub fn new(
+        manager: Arc<UpstreamManager>,
+        upstreams: &[UpstreamConfig],
+    ) -> Result<Option<Self>, AppError> {
+        let targets: Vec<DnsTarget> = upstreams
+            .iter()
+            .filter_map(|upstream| {
+                let config = upstream.dns.clone()?;
+                let url = Url::parse(&upstream.url).ok()?;
+                Some(DnsTarget {
+                    upstream: upstream.name.clone(),
+                    host: url.host_str()?.trim_matches(['[', ']']).to_string(),
+                    port: url.port_or_known_default()?,
+                    config,
+                })
+            })
+            .collect();
+        if targets.is_empty() {
+            return Ok(None);
+        }
+
+        let (config, mut options) = read_system_conf().map_err(|e| {
+            AppError::Config(format!("Failed to read system DNS configuration: {}", e))
+        })?;
+        // 每次都重新查询，刷新频率由解析间隔控制
+        options.cache_size = 0;
+
+        Ok(Some(Self {
+            manager,
+            resolver: TokioAsyncResolver::tokio(config, options),
+            targets,
+        }))
+    }
+
+    /// 解析全部上游一次并同步地址，返回地址发生变化的上游数量
+    pub async fn refresh(&self) -> usize {
+        join_all(
+            self.targets
+                .iter()
+                .map(|target| self.refresh_target(target)),
+        )
+        .await
+        .into_iter()
+        .filter(|changed| *changed)
+        .count()
+    }
+
+    // 解析一个上游并同步地址，地址发生变化时返回 true
+    async fn refresh_target(&self, target: &DnsTarget) -> bool {
+        let endpoints = match self.resolve(target).await {
+            Ok(endpoints) if !endpoints.is_empty() => endpoints,
+            Ok(_) => {
+                warn!(
+                    "DNS discovery found no records for {:?} (upstream '{}'), keeping the previous endpoints",
+                    target.host, target.upstream
+                );
+                return false;
+            }
+            Err(e) => {
+                warn!(
+                    "DNS discovery for upstream '{}' failed, keeping the previous endpoints: {}",
+                    target.upstream, e
+                );
+                return false;
+            }
+        };
+        debug!(
+            "DNS discovery resolved {:?} to {} endpoints",
+            target.host,
+            endpoints.len()
+        );
+
+        match self
+            .manager
+            .set_upstream_endpoints(&target.upstream, endpoints)
+            .await
+        {
+            Ok(changed) => changed,
+            Err(e) => {
+                warn!(
+                    "Failed to update endpoints of upstream '{}': {}",
+                    target.upstream, e
+                );
+                false
+            }
+        }
+    }
+
+    // 解析上游的主机名
+    async fn resolve(&self, target: &DnsTarget) -> Result<Vec<Endpoint>, AppError> {
+        let failed = |e: hickory_resolver::error::ResolveError| {
+            AppError::Upstream(format!("Failed to resolve {:?}: {}", target.host, e))
+        };
+
+        match target.config.record {
+            DnsRecordType::A => {
+                let lookup = self
+                    .resolver
+                    .lookup_ip(target.host.as_str())
+                    .await
+                    .map_err(failed)?;
+                Ok(lookup
+                    .iter()
+                    .map(|ip| Endpoint::new(ip.to_string(), target.port))
+                    .collect())
+            }
+            DnsRecordType::Srv => {
+                let lookup = self
+                    .resolver
+                    .srv_lookup(target.host.as_str())
+                    .await
+                    .map_err(failed)?;
+                // 只使用优先级最高（数值最小）的记录
+                let priority = lookup.iter().map(|srv| srv.priority()).min();
+                Ok(lookup
+                    .iter()
+                    .filter(|srv| Some(srv.priority()) == priority)
+                    .map(|srv| {
+                        let host = srv.target().to_utf8();
+                        Endpoint::new(host.trim_end_matches('.'), srv.port())
+                    })
+                    .collect())
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl IntoSubsystem<AppError> for DnsDiscovery {
+    async fn run(self, subsys: SubsystemHandle) -> Result<(), AppError> {
+        info!("DNS discovery started for {} upstreams", self.targets.len());
+
+        // 每个上游按各自的间隔重新解析
+        join_all(self.targets.iter().map(|target| async {
+            let interval = Duration::from_secs(target.config.interval);
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(interval) => {
+                        self.refresh_target(target).await;
+                    }
+                    _ = subsys.on_shutdown_requested() => break,
+                }
+            }
+        }))
+        .await;
+
+        info!("DNS discovery stopped");
+        Ok(())
+    }
+}
@@ -0,0 +1,475 @@
+use futures_util::{future::join_all, StreamExt};
+use reqwest::{Certificate, Client, RequestBuilder, Url};
+use serde::Deserialize;
+use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
+use tokio_graceful_shutdown::{IntoSubsystem, SubsystemHandle};
+use tracing::{debug, info, warn};
+
+use super::Endpoint;
+use crate::{
+    config::{GroupDiscoveryType, KubernetesDiscoveryConfig, UpstreamGroupConfig},
+    error::AppError,
+    r#const::kubernetes_discovery,
+    upstream::UpstreamManager,
+};
+
+// API Server 认证令牌
+enum Token {
+    // 不认证
+    None,
+    // 固定令牌
+    Static(String),
+    // 服务账号令牌文件，令牌会定期轮换，每次请求前重新读取
+    File(PathBuf),
+}
+
+/// Kubernetes API Server 连接
+pub struct KubernetesApi {
+    // API Server 地址
+    base: Url,
+    // HTTP 客户端
+    client: Client,
+    // 认证令牌
+    token: Token,
+    // 未指定命名空间时使用的命名空间
+    namespace: String,
+}
+
+impl KubernetesApi {
+    /// 使用 Pod 的服务账号连接集群内的 API Server
+    pub fn in_cluster() -> Result<Self, AppError> {
+        let env = |name: &str| {
+            std::env::var(name).map_err(|_| {
+                AppError::Config(format!(
+                    "Kubernetes discovery must run inside a cluster: {} is not set",
+                    name
+                ))
+            })
+        };
+        let host = env(kubernetes_discovery::SERVICE_HOST_ENV)?;
+        let port = env(kubernetes_discovery::SERVICE_PORT_ENV)?;
+        // IPv6 集群中主机为不带方括号的地址
+        let host = if host.contains(':') {
+            format!("[{}]", host)
+        } else {
+            host
+        };
+        let base = Url::parse(&format!("https://{}:{}", host, port)).map_err(|e| {
+            AppError::Config(format!("Invalid Kubernetes API server address: {}", e))
+        })?;
+
+        let read = |path: &str| {
+            std::fs::read(path).map_err(|e| {
+                AppError::Config(format!(
+                    "Failed to read Kubernetes service account file {:?}: {}",
+                    path, e
+                ))
+            })
+        };
+        let ca = Certificate::from_pem(&read(kubernetes_discovery::CA_CERT_PATH)?)?;
+        let namespace = String::from_utf8_lossy(&read(kubernetes_discovery::NAMESPACE_PATH)?)
+            .trim()
+            .to_string();
+        let client = Client::builder()
+            .connect_timeout(Duration::from_secs(kubernetes_discovery::REQUEST_TIMEOUT))
+            .add_root_certificate(ca)
+            .build()?;
+
+        Ok(Self {
+            base,
+            client,
+            token: Token::File(PathBuf::from(kubernetes_discovery::TOKEN_PATH)),
+            namespace,
+        })
+    }
+
+    /// 连接指定的 API Server，令牌为 None 时不认证
+    pub fn new(base: Url, token: Option<String>, namespace: impl Into<String>) -> Self {
+        Self {
+            base,
+            client: Client::new(),
+            token: token.map_or(Token::None, Token::Static),
+            namespace: namespace.into(),
+        }
+    }
+
+    // 创建列出或监听服务 EndpointSlice 的请求
+    async fn endpoint_slices(
+        &self,
+        target: &KubernetesTarget,
+        query: &[(&str, &str)],
+    ) -> Result<RequestBuilder, AppError> {
+        let mut url = self.base.clone();
+        url.set_path(&format!(
+            "/apis/discovery.k8s.io/v1/namespaces/{}/endpointslices",
+            target.namespace
+        ));
+        url.query_pairs_mut()
+            .append_pair(
+                "labelSelector",
+                &format!(
+                    "{}={}",
+                    kubernetes_discovery::SERVICE_NAME_LABEL,
+                    target.config.service
+                ),
+            )
+            .extend_pairs(query);
+
+        let request = self.client.get(url);
+        let token = match &self.token {
+            Token::None => return Ok(request),
+            Token::Static(token) => token.clone(),
+            Token::File(path) => tokio::fs::read_to_string(path).await?.trim().to_string(),
+        };
+        Ok(request.bearer_auth(token))
+    }
+}
+
+// 启用了 Kubernetes 服务发现的上游组
+struct KubernetesTarget {
+    // 上游组名称
+    group: String,
+    // 服务所在的命名空间
+    namespace: String,
+    // 服务发现配置
+    config: KubernetesDiscoveryConfig,
+}
+
+/// Kubernetes 服务发现
+///
+/// 列出并监听启用了 Kubernetes 服务发现的上游组所对应服务的 EndpointSlice，
+/// 并将就绪的地址同步到上游管理器。没有就绪地址或请求失败时保留上一次的地址
+pub struct KubernetesDiscovery {
+    // 上游管理器
+    manager: Arc<UpstreamManager>,
+    // API Server 连接
+    api: KubernetesApi,
+    // 启用了 Kubernetes 服务发现的上游组
+    targets: Vec<KubernetesTarget>,
+}
+
+impl KubernetesDiscovery {
+    /// 为启用了 Kubernetes 服务发现的上游组创建服务发现，使用 Pod 的服务账号连接 API Server。
+    /// 没有这样的上游组时返回 None
+    pub fn new(
+        manager: Arc<UpstreamManager>,
+        groups: &[UpstreamGroupConfig],
+    ) -> Result<Option<Self>, AppError> {
+        if !groups
+            .iter()
+            .any(|group| kubernetes_config(group).is_some())
+        {
+            return Ok(None);
+        }
+        Ok(Self::with_api(
+            manager,
+            groups,
+            KubernetesApi::in_cluster()?,
+        ))
+    }
+
+    /// 使用指定的 API Server 连接创建服务发现，没有启用 Kubernetes 服务发现的上游组时返回 None
+    pub fn with_api(
+        manager: Arc<UpstreamManager>,
+        groups: &[UpstreamGroupConfig],
+        api: KubernetesApi,
+    ) -> Option<Self> {
+        let targets: Vec<KubernetesTarget> = groups
+            .iter()
+            .filter_map(|group| {
+                let config = kubernetes_config(group)?.clone();
+                Some(KubernetesTarget {
+                    group: group.name.clone(),
+                    namespace: config
+                        .namespace
+                        .clone()
+                        .unwrap_or_else(|| api.namespace.clone()),
+                    config,
+                })
+            })
+            .collect();
+        if targets.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            manager,
+            api,
+            targets,
+        })
+    }
+
+    /// 列出全部服务的 EndpointSlice 一次并同步地址，返回地址发生变化的上游组数量
+    pub async fn refresh(&self) -> usize {
+        join_all(self.targets.iter().map(|target| async move {
+            match self.list(target).await {
+                Ok((slices, _)) => self.apply(target, &slices).await,
+                Err(e) => {
+                    warn!(
+                        "Kubernetes discovery for upstream group '{}' failed, keeping the previous endpoints: {}",
+                        target.group, e
+                    );
+                    false
+                }
+            }
+        }))
+        .await
+        .into_iter()
+        .filter(|changed| *changed)
+        .count()
+    }
+
+    // 列出服务的 EndpointSlice，返回按名称索引的 EndpointSlice 和列表的资源版本
+    async fn list(
+        &self,
+        target: &KubernetesTarget,
+    ) -> Result<(HashMap<String, EndpointSlice>, String), AppError> {
+        let list: EndpointSliceList = self
+            .api
+            .endpoint_slices(target, &[])
+            .await?
+            .timeout(Duration::from_secs(kubernetes_discovery::REQUEST_TIMEOUT))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let slices = list
+            .items
+            .into_iter()
+            .map(|slice| (slice.metadata.name.clone(), slice))
+            .collect();
+        Ok((slices, list.metadata.resource_version))
+    }
+
+    // 列出服务的 EndpointSlice 后从列表的资源版本开始监听，每次变化后同步地址。
+    // 监听超时或资源版本过期时返回，由调用方重新列出
+    async fn watch(&self, target: &KubernetesTarget) -> Result<(), AppError> {
+        let (mut slices, resource_version) = self.list(target).await?;
+        self.apply(target, &slices).await;
+
+        let timeout = kubernetes_discovery::WATCH_TIMEOUT.to_string();
+        let mut stream = self
+            .api
+            .endpoint_slices(
+                target,
+                &[
+                    ("watch", "true"),
+                    ("resourceVersion", &resource_version),
+                    ("timeoutSeconds", &timeout),
+                ],
+            )
+            .await?
+            .timeout(Duration::from_secs(
+                kubernetes_discovery::WATCH_TIMEOUT + kubernetes_discovery::REQUEST_TIMEOUT,
+            ))
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes_stream();
+
+        // API Server 以换行分隔流式返回的每个监听事件
+        let mut buffer = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            buffer.extend_from_slice(&chunk?);
+            while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+                let event: WatchEvent = serde_json::from_slice(&line)?;
+                match event.r#type.as_str() {
+                    "ADDED" | "MODIFIED" => {
+                        let slice: EndpointSlice = serde_json::from_value(event.object)?;
+                        slices.insert(slice.metadata.name.clone(), slice);
+                    }
+                    "DELETED" => {
+                        let slice: EndpointSlice = serde_json::from_value(event.object)?;
+                        slices.remove(&slice.metadata.name);
+                    }
+                    "ERROR" => {
+                        let status: WatchStatus = serde_json::from_value(event.object)?;
+                        // 资源版本过期（410 Gone），重新列出
+                        if status.code == 410 {
+                            debug!(
+                                "Kubernetes watch for upstream group '{}' expired, listing again",
+                                target.group
+                            );
+                            return Ok(());
+                        }
+                        return Err(AppError::Upstream(format!(
+                            "Kubernetes watch for service {:?} failed: {}",
+                            target.config.service, status.message
+                        )));
+                    }
+                    _ => continue,
+                }
+                self.apply(target, &slices).await;
+            }
+        }
+        Ok(())
+    }
+
+    // 将 EndpointSlice 中就绪的地址同步到上游管理器，地址发生变化时返回 true
+    async fn apply(
+        &self,
+        target: &KubernetesTarget,
+        slices: &HashMap<String, EndpointSlice>,
+    ) -> bool {
+        let endpoints: Vec<Endpoint> = slices
+            .values()
+            .flat_map(|slice| slice.ready_endpoints(target.config.port))
+            .collect();
+        if endpoints.is_empty() {
+            warn!(
+                "Kubernetes discovery found no ready endpoints for service {:?} (upstream group '{}'), keeping the previous endpoints",
+                target.config.service, target.group
+            );
+            return false;
+        }
+
+        match self
+            .manager
+            .set_group_endpoints(&target.group, endpoints)
+            .await
+        {
+            Ok(changed) => changed,
+            Err(e) => {
+                warn!(
+                    "Failed to update endpoints of upstream group '{}': {}",
+                    target.group, e
+                );
+                false
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl IntoSubsystem<AppError> for KubernetesDiscovery {
+    async fn run(self, subsys: SubsystemHandle) -> Result<(), AppError> {
+        info!(
+            "Kubernetes discovery started for {} upstream groups",
+            self.targets.len()
+        );
+
+        // 每个上游组各自监听，失败后按间隔重试
+        join_all(self.targets.iter().map(|target| async {
+            loop {
+                tokio::select! {
+                    result = self.watch(target) => {
+                        let Err(e) = result else {
+                            continue;
+                        };
+                        warn!(
+                            "Kubernetes discovery for upstream group '{}' failed: {}, retrying in {}s",
+                            target.group,
+                            e,
+                            kubernetes_discovery::RETRY_INTERVAL
+                        );
+                        tokio::select! {
+                            _ = tokio::time::sleep(Duration::from_secs(kubernetes_discovery::RETRY_INTERVAL)) => {}
+                            _ = subsys.on_shutdown_requested() => break,
+                        }
+                    }
+                    _ = subsys.on_shutdown_requested() => break,
+                }
+            }
+        }))
+        .await;
+
+        info!("Kubernetes discovery stopped");
+        Ok(())
+    }
+}
+
+// 上游组的 Kubernetes 服务发现配置
+fn kubernetes_config(group: &UpstreamGroupConfig) -> Option<&KubernetesDiscoveryConfig> {
+    group
+        .discovery
+        .as_ref()
+        .filter(|discovery| discovery.r#type == GroupDiscoveryType::Kubernetes)?
+        .kubernetes
+        .as_ref()
+}
+
+// EndpointSlice 列表
+#[derive(Deserialize)]
+struct EndpointSliceList {
+    metadata: ListMeta,
+    items: Vec<EndpointSlice>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListMeta {
+    #[serde(default)]
+    resource_version: String,
+}
+
+#[derive(Deserialize)]
+struct ObjectMeta {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct EndpointSlice {
+    metadata: ObjectMeta,
+    // 没有地址时为 null
+    #[serde(default)]
+    endpoints: Option<Vec<SliceEndpoint>>,
+    #[serde(default)]
+    ports: Option<Vec<SlicePort>>,
+}
+
+impl EndpointSlice {
+    // 就绪的地址，未指定端口时使用第一个端口，没有可用端口时为空
+    fn ready_endpoints(&self, port: Option<u16>) -> Vec<Endpoint> {
+        let port = port.or_else(|| self.ports.iter().flatten().find_map(|port| port.port));
+        let Some(port) = port else {
+            return Vec::new();
+        };
+        self.endpoints
+            .iter()
+            .flatten()
+            // 未设置就绪状态时视为就绪
+            .filter(|endpoint| endpoint.conditions.ready.unwrap_or(true))
+            .flat_map(|endpoint| &endpoint.addresses)
+            .map(|address| Endpoint::new(address.as_str(), port))
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct SliceEndpoint {
+    addresses: Vec<String>,
+    #[serde(default)]
+    conditions: SliceConditions,
+}
+
+#[derive(Deserialize, Default)]
+struct SliceConditions {
+    ready: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct SlicePort {
+    port: Option<u16>,
+}
+
+// 监听事件
+#[derive(Deserialize)]
+struct WatchEvent {
+    r#type: String,
+    object: serde_json::Value,
+}
+
+// 监听失败时 ERROR 事件中的状态
+#[derive(Deserialize)]
+struct WatchStatus {
+    #[serde(default)]
+    code: u16,
+    #[serde(default)]
+    message: String,
+}
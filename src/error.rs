@@ -75,4 +75,12 @@ pub enum AppError {
     // 认证错误
     #[error("Authentication error: {0}")]
     AuthError(String),
+
+    // 缓存存储错误
+    #[error("Cache error: {0}")]
+    Cache(String),
+
+    // Redis 连接或命令错误
+    #[error("Redis error: {0}")]
+    Redis(String),
 }
@@ -0,0 +1,342 @@
+use crate::r#const::event_limits;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::broadcast;
+use utoipa::ToSchema;
+
+// 访问事件，每个转发请求完成后产生一条
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AccessEvent {
+    // 请求完成时间（Unix 毫秒时间戳）
+    pub timestamp: u64,
+    // 转发服务名称
+    pub forward: String,
+    // 请求方法
+    pub method: String,
+    // 请求路径
+    pub path: String,
+    // 目标上游组
+    pub group: String,
+    // 响应状态码
+    pub status: u16,
+    // 请求耗时（毫秒）
+    pub duration_ms: u64,
+    // 请求失败原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AccessEvent {
+    // 创建访问事件，时间戳取当前时间
+    pub fn new(
+        forward: &str,
+        method: &str,
+        path: &str,
+        group: &str,
+        status: u16,
+        duration: Duration,
+    ) -> Self {
+        Self {
+            timestamp: unix_millis(),
+            forward: forward.to_string(),
+            method: method.to_string(),
+            path: path.to_string(),
+            group: group.to_string(),
+            status,
+            duration_ms: duration.as_millis() as u64,
+            error: None,
+        }
+    }
+
+    // 设置请求失败原因
+    pub fn with_error(mut self, error: impl ToString) -> Self {
+        self.error = Some(error.to_string());
+        self
+    }
+}
+
+// 访问事件过滤条件
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum AccessFilterRule {
+    // 转发服务名称
+    Forward(String),
+    // 上游组名称
+    Group(String),
+    // 请求方法（不区分大小写）
+    Method(String),
+    // 请求路径前缀
+    Path(String),
+    // 精确状态码
+    Status(u16),
+    // 状态码类别，如 5xx
+    StatusClass(u16),
+}
+
+// 访问事件过滤器，所有条件都满足时才匹配
+#[derive(Debug, Clone, Default)]
+pub struct AccessFilter {
+    rules: Vec<AccessFilterRule>,
+}
+
+impl AccessFilter {
+    // 从键值对解析过滤器，支持 forward、group、method、path、status
+    pub fn parse<I, K, V>(pairs: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let mut rules = Vec::new();
+
+        for (key, value) in pairs {
+            let (key, value) = (key.as_ref(), value.as_ref());
+            let rule = match key {
+                "forward" => AccessFilterRule::Forward(value.to_string()),
+                "group" => AccessFilterRule::Group(value.to_string()),
+                "method" => AccessFilterRule::Method(value.to_ascii_uppercase()),
+                "path" => AccessFilterRule::Path(value.to_string()),
+                "status" => parse_status_filter(value)?,
+                _ => {
+                    return Err(format!(
+                        "Unsupported filter '{}', expected one of: forward, group, method, path, status",
+                        key
+                    ))
+                }
+            };
+            rules.push(rule);
+        }
+
+        Ok(Self { rules })
+    }
+
+    // 检查事件是否满足所有过滤条件
+    pub fn matches(&self, event: &AccessEvent) -> bool {
+        self.rules.iter().all(|rule| match rule {
+            AccessFilterRule::Forward(forward) => event.forward == *forward,
+            AccessFilterRule::Group(group) => event.group == *group,
+            AccessFilterRule::Method(method) => event.method.eq_ignore_ascii_case(method),
+            AccessFilterRule::Path(prefix) => event.path.starts_with(prefix.as_str()),
+            AccessFilterRule::Status(status) => event.status == *status,
+            AccessFilterRule::StatusClass(class) => event.status / 100 == *class,
+        })
+    }
+}
+
+// 解析状态码过滤条件，支持精确值（如 404）和类别（如 5xx）
+fn parse_status_filter(value: &str) -> Result<AccessFilterRule, String> {
+    let invalid = || {
+        format!(
+            "Invalid status filter '{}', expected e.g. 404 or 5xx",
+            value
+        )
+    };
+
+    if let Some(class) = value
+        .strip_suffix("xx")
+        .or_else(|| value.strip_suffix("XX"))
+    {
+        return match class.parse::<u16>() {
+            Ok(class @ 1..=5) => Ok(AccessFilterRule::StatusClass(class)),
+            _ => Err(invalid()),
+        };
+    }
+
+    value
+        .parse::<u16>()
+        .map(AccessFilterRule::Status)
+        .map_err(|_| invalid())
+}
+
+// 系统事件，用于通知运行状态变化
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SystemEvent {
+    // 配置重载成功
+    ConfigReloaded {
+        // 事件时间（Unix 毫秒时间戳）
+        timestamp: u64,
+        // 配置文件路径
+        path: String,
+    },
+    // 配置重载失败，继续使用上一次有效的配置
+    ConfigReloadFailed {
+        // 事件时间（Unix 毫秒时间戳）
+        timestamp: u64,
+        // 配置文件路径
+        path: String,
+        // 失败原因
+        error: String,
+    },
+    // 通过管理 API 修改了配置
+    ConfigChanged {
+        // 事件时间（Unix 毫秒时间戳）
+        timestamp: u64,
+        // 审计记录序号
+        audit_id: u64,
+        // 操作者（管理令牌指纹或 anonymous）
+        actor: String,
+        // 请求方法
+        method: String,
+        // 请求的 API 端点
+        endpoint: String,
+        // 变更的配置项数量
+        changes: usize,
+    },
+    // 熔断器状态变化
+    BreakerStateChanged {
+        // 事件时间（Unix 毫秒时间戳）
+        timestamp: u64,
+        // 上游组名称
+        group: String,
+        // 上游服务名称
+        upstream: String,
+        // 变化前的状态（closed、open、half_open）
+        from: String,
+        // 变化后的状态（closed、open、half_open）
+        to: String,
+    },
+    // 上游服务被启用或禁用（维护/排空）
+    UpstreamStateChanged {
+        // 事件时间（Unix 毫秒时间戳）
+        timestamp: u64,
+        // 上游服务名称
+        upstream: String,
+        // 是否启用
+        enabled: bool,
+    },
+    // 转发服务被启用或禁用
+    ForwardStateChanged {
+        // 事件时间（Unix 毫秒时间戳）
+        timestamp: u64,
+        // 转发服务名称
+        forward: String,
+        // 是否启用
+        enabled: bool,
+    },
+    // 转发服务触发限流
+    RateLimited {
+        // 事件时间（Unix 毫秒时间戳）
+        timestamp: u64,
+        // 转发服务名称
+        forward: String,
+        // 自上一次限流事件以来被拒绝的请求数量
+        rejected: u64,
+    },
+    // 告警规则触发
+    AlertFiring {
+        // 事件时间（Unix 毫秒时间戳）
+        timestamp: u64,
+        // 告警规则名称
+        rule: String,
+        // 上游组名称
+        group: String,
+        // 指标值
+        value: f64,
+        // 告警阈值
+        threshold: f64,
+    },
+    // 告警恢复
+    AlertResolved {
+        // 事件时间（Unix 毫秒时间戳）
+        timestamp: u64,
+        // 告警规则名称
+        rule: String,
+        // 上游组名称
+        group: String,
+        // 指标值，窗口内请求数不足时为空
+        value: Option<f64>,
+        // 告警阈值
+        threshold: f64,
+    },
+}
+
+// 限流事件上报器，合并短时间内的大量限流请求，避免淹没事件流
+#[derive(Debug, Default)]
+pub struct RateLimitReporter {
+    // 尚未上报的被拒绝请求数量
+    rejected: AtomicU64,
+    // 上一次上报的时间（Unix 毫秒时间戳）
+    last_reported: AtomicU64,
+}
+
+impl RateLimitReporter {
+    // 记录一次被拒绝的请求，距上一次上报超过最小间隔时发布限流事件
+    pub fn record(&self, forward: &str) {
+        self.rejected.fetch_add(1, Ordering::Relaxed);
+
+        let now = unix_millis();
+        let last = self.last_reported.load(Ordering::Relaxed);
+        if now.saturating_sub(last) < event_limits::RATE_LIMIT_EVENT_INTERVAL_MS
+            || self
+                .last_reported
+                .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+                .is_err()
+        {
+            return;
+        }
+
+        EVENTS.publish_system(SystemEvent::RateLimited {
+            timestamp: now,
+            forward: forward.to_string(),
+            rejected: self.rejected.swap(0, Ordering::Relaxed),
+        });
+    }
+}
+
+// 当前 Unix 毫秒时间戳
+#[inline(always)]
+pub fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+// 进程内事件总线
+pub struct EventBus {
+    access: broadcast::Sender<AccessEvent>,
+    system: broadcast::Sender<SystemEvent>,
+}
+
+impl EventBus {
+    fn new() -> Self {
+        let (access, _) = broadcast::channel(event_limits::ACCESS_CHANNEL_CAPACITY);
+        let (system, _) = broadcast::channel(event_limits::SYSTEM_CHANNEL_CAPACITY);
+        Self { access, system }
+    }
+
+    // 是否有访问事件订阅者，没有订阅者时无需构造事件
+    #[inline(always)]
+    pub fn has_access_subscribers(&self) -> bool {
+        self.access.receiver_count() > 0
+    }
+
+    // 发布访问事件
+    pub fn publish_access(&self, event: AccessEvent) {
+        // 没有订阅者时发送会失败，直接忽略
+        let _ = self.access.send(event);
+    }
+
+    // 订阅访问事件
+    pub fn subscribe_access(&self) -> broadcast::Receiver<AccessEvent> {
+        self.access.subscribe()
+    }
+
+    // 发布系统事件
+    pub fn publish_system(&self, event: SystemEvent) {
+        // 没有订阅者时发送会失败，直接忽略
+        let _ = self.system.send(event);
+    }
+
+    // 订阅系统事件
+    pub fn subscribe_system(&self) -> broadcast::Receiver<SystemEvent> {
+        self.system.subscribe()
+    }
+}
+
+// 全局事件总线
+pub static EVENTS: Lazy<EventBus> = Lazy::new(EventBus::new);
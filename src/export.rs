@@ -0,0 +1,436 @@
+use parking_lot::Mutex;
+use prometheus::proto::{Histogram, LabelPair, Metric, MetricFamily, MetricType};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::net::UdpSocket;
+use tokio_graceful_shutdown::{IntoSubsystem, SubsystemHandle};
+use tracing::{debug, info, warn};
+
+use crate::{
+    config::{MetricsExportConfig, MetricsExportProtocol},
+    error::AppError,
+    metrics::METRICS,
+    r#const::metrics_export,
+};
+
+/// 指标推送器
+///
+/// 按推送间隔读取 Prometheus 指标注册表中的全部指标，推送到 OTLP 接收端或 StatsD 服务，
+/// 用于没有 Prometheus 抓取的环境。OTLP 按累计值推送，StatsD 计数器按两次推送之间的增量推送
+pub struct MetricsExporter {
+    // 推送配置
+    config: MetricsExportConfig,
+    // OTLP 推送使用的 HTTP 客户端
+    client: reqwest::Client,
+    // OTLP 推送附加的请求头
+    headers: HeaderMap,
+    // 推送器创建时间（纳秒），作为 OTLP 累计值的起始时间
+    start_time: u128,
+    // 上次推送的 StatsD 计数器值，用于计算增量
+    counters: Mutex<HashMap<String, f64>>,
+}
+
+impl MetricsExporter {
+    /// 按推送配置创建推送器
+    pub fn new(config: &MetricsExportConfig) -> Result<Self, AppError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(metrics_export::HTTP_TIMEOUT))
+            .build()
+            .map_err(|e| {
+                AppError::Config(format!("Failed to create metrics export client: {}", e))
+            })?;
+        let mut headers = HeaderMap::new();
+        for (name, value) in &config.headers {
+            let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| {
+                AppError::Config(format!("Invalid metrics export header: {}", name))
+            })?;
+            let value = HeaderValue::from_str(value).map_err(|_| {
+                AppError::Config(format!("Invalid value of metrics export header: {}", name))
+            })?;
+            headers.insert(name, value);
+        }
+        Ok(Self {
+            config: config.clone(),
+            client,
+            headers,
+            start_time: unix_nanos(),
+            counters: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// 推送一次全部指标
+    pub async fn export(&self) -> Result<(), AppError> {
+        let families = METRICS.gather();
+        let result = match self.config.protocol {
+            MetricsExportProtocol::Otlp => self.export_otlp(&families).await,
+            MetricsExportProtocol::Statsd | MetricsExportProtocol::Dogstatsd => {
+                self.export_statsd(&families).await
+            }
+        };
+        let label = if result.is_ok() {
+            metrics_export::SUCCESS
+        } else {
+            metrics_export::FAILURE
+        };
+        METRICS
+            .metrics_exports_total()
+            .with_label_values(&[self.config.protocol.as_str(), label])
+            .inc();
+        result
+    }
+
+    // 以 OTLP/HTTP JSON 格式推送
+    async fn export_otlp(&self, families: &[MetricFamily]) -> Result<(), AppError> {
+        let body = self.otlp_body(families);
+        let response = self
+            .client
+            .post(&self.config.endpoint)
+            .headers(self.headers.clone())
+            .json(&body)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(AppError::Internal(format!(
+                "OTLP endpoint returned HTTP status {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+
+    // 生成 OTLP 请求体，计数器转换为单调累计和，直方图转换为显式边界的累计直方图
+    fn otlp_body(&self, families: &[MetricFamily]) -> Value {
+        let start = self.start_time.to_string();
+        let now = unix_nanos().to_string();
+        let metrics: Vec<Value> = families
+            .iter()
+            .filter_map(|family| {
+                let name = format!("{}{}", self.config.prefix, family.get_name());
+                let points = |value: fn(&Metric) -> f64, start: Option<&str>| {
+                    family
+                        .get_metric()
+                        .iter()
+                        .map(|metric| number_point(metric.get_label(), value(metric), start, &now))
+                        .collect::<Vec<_>>()
+                };
+                let (kind, data) = match family.get_field_type() {
+                    MetricType::COUNTER => (
+                        "sum",
+                        json!({
+                            "dataPoints": points(|m| m.get_counter().get_value(), Some(&start)),
+                            "aggregationTemporality": metrics_export::CUMULATIVE,
+                            "isMonotonic": true,
+                        }),
+                    ),
+                    MetricType::GAUGE => (
+                        "gauge",
+                        json!({"dataPoints": points(|m| m.get_gauge().get_value(), None)}),
+                    ),
+                    MetricType::UNTYPED => (
+                        "gauge",
+                        json!({"dataPoints": points(|m| m.get_untyped().get_value(), None)}),
+                    ),
+                    MetricType::HISTOGRAM => {
+                        let points: Vec<_> = family
+                            .get_metric()
+                            .iter()
+                            .map(|m| {
+                                histogram_point(m.get_label(), m.get_histogram(), &start, &now)
+                            })
+                            .collect();
+                        (
+                            "histogram",
+                            json!({
+                                "dataPoints": points,
+                                "aggregationTemporality": metrics_export::CUMULATIVE,
+                            }),
+                        )
+                    }
+                    MetricType::SUMMARY => return None,
+                };
+                Some(json!({
+                    "name": name,
+                    "description": family.get_help(),
+                    kind: data,
+                }))
+            })
+            .collect();
+
+        json!({"resourceMetrics": [{
+            "resource": {"attributes": [{
+                "key": "service.name",
+                "value": {"stringValue": metrics_export::SERVICE_NAME},
+            }]},
+            "scopeMetrics": [{
+                "scope": {
+                    "name": metrics_export::SERVICE_NAME,
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+                "metrics": metrics,
+            }],
+        }]})
+    }
+
+    // 以 StatsD 行协议推送，按数据包大小分批发送
+    async fn export_statsd(&self, families: &[MetricFamily]) -> Result<(), AppError> {
+        let lines = self.statsd_lines(families);
+        if lines.is_empty() {
+            return Ok(());
+        }
+
+        // 每次推送重新解析地址，StatsD 服务地址变化后无需重启
+        let address: SocketAddr = tokio::net::lookup_host(&self.config.endpoint)
+            .await?
+            .next()
+            .ok_or_else(|| {
+                AppError::Config(format!(
+                    "Metrics export endpoint {} did not resolve to any address",
+                    self.config.endpoint
+                ))
+            })?;
+        let local = if address.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = UdpSocket::bind(local).await?;
+        socket.connect(address).await?;
+
+        let mut packet = String::new();
+        for line in lines {
+            if !packet.is_empty()
+                && packet.len() + line.len() + 1 > metrics_export::MAX_PACKET_BYTES
+            {
+                socket.send(packet.as_bytes()).await?;
+                packet.clear();
+            }
+            if !packet.is_empty() {
+                packet.push('\n');
+            }
+            packet.push_str(&line);
+        }
+        socket.send(packet.as_bytes()).await?;
+        Ok(())
+    }
+
+    // 生成 StatsD 指标行，计数器和直方图的数量与总和按增量推送，增量为 0 时不推送
+    fn statsd_lines(&self, families: &[MetricFamily]) -> Vec<String> {
+        let mut counters = self.counters.lock();
+        let mut lines = Vec::new();
+        let mut counter =
+            |name: String, labels: &[LabelPair], value: f64, lines: &mut Vec<String>| {
+                let (name, tags) = self.statsd_name(&name, labels);
+                let previous = counters
+                    .insert(format!("{}{}", name, tags), value)
+                    .unwrap_or_default();
+                // 当前值小于上次推送的值时，计数器已被重置，推送当前值
+                let delta = if value >= previous {
+                    value - previous
+                } else {
+                    value
+                };
+                if delta > 0.0 && delta.is_finite() {
+                    lines.push(format!("{}:{}|c{}", name, delta, tags));
+                }
+            };
+
+        for family in families {
+            let name = format!("{}{}", self.config.prefix, family.get_name());
+            for metric in family.get_metric() {
+                let labels = metric.get_label();
+                match family.get_field_type() {
+                    MetricType::COUNTER => counter(
+                        name.clone(),
+                        labels,
+                        metric.get_counter().get_value(),
+                        &mut lines,
+                    ),
+                    MetricType::HISTOGRAM => {
+                        let histogram = metric.get_histogram();
+                        counter(
+                            format!("{}_count", name),
+                            labels,
+                            histogram.get_sample_count() as f64,
+                            &mut lines,
+                        );
+                        counter(
+                            format!("{}_sum", name),
+                            labels,
+                            histogram.get_sample_sum(),
+                            &mut lines,
+                        );
+                    }
+                    MetricType::GAUGE | MetricType::UNTYPED => {
+                        let value = if family.get_field_type() == MetricType::GAUGE {
+                            metric.get_gauge().get_value()
+                        } else {
+                            metric.get_untyped().get_value()
+                        };
+                        if !value.is_finite() {
+                            continue;
+                        }
+                        let (name, tags) = self.statsd_name(&name, labels);
+                        // 带符号的仪表值在 StatsD 中表示增减，设置负值前先归零
+                        if value < 0.0 {
+                            lines.push(format!("{}:0|g{}", name, tags));
+                        }
+                        lines.push(format!("{}:{}|g{}", name, value, tags));
+                    }
+                    MetricType::SUMMARY => {}
+                }
+            }
+        }
+        lines
+    }
+
+    // 生成指标的 StatsD 名称和标签后缀
+    // StatsD 将标签值按顺序追加到名称后，DogStatsD 以 tag 发送标签
+    fn statsd_name(&self, name: &str, labels: &[LabelPair]) -> (String, String) {
+        match self.config.protocol {
+            MetricsExportProtocol::Dogstatsd if !labels.is_empty() => {
+                let tags: Vec<String> = labels
+                    .iter()
+                    .map(|label| {
+                        format!("{}:{}", label.get_name(), sanitize_tag(label.get_value()))
+                    })
+                    .collect();
+                (name.to_string(), format!("|#{}", tags.join(",")))
+            }
+            MetricsExportProtocol::Dogstatsd => (name.to_string(), String::new()),
+            _ => {
+                let name = labels.iter().fold(name.to_string(), |mut name, label| {
+                    name.push('.');
+                    name.push_str(&sanitize_segment(label.get_value()));
+                    name
+                });
+                (name, String::new())
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl IntoSubsystem<AppError> for MetricsExporter {
+    async fn run(self, subsys: SubsystemHandle) -> Result<(), AppError> {
+        info!(
+            "Metrics exporter started, pushing {} metrics to {} every {}s",
+            self.config.protocol.as_str(),
+            self.config.endpoint,
+            self.config.interval
+        );
+
+        let interval = Duration::from_secs(self.config.interval);
+        loop {
+            let shutdown = tokio::select! {
+                _ = tokio::time::sleep(interval) => false,
+                _ = subsys.on_shutdown_requested() => true,
+            };
+            // 关闭前再推送一次，避免丢失最后一个间隔内的数据
+            match self.export().await {
+                Ok(()) => debug!("Metrics pushed to {}", self.config.endpoint),
+                Err(e) => warn!("Failed to push metrics to {}: {}", self.config.endpoint, e),
+            }
+            if shutdown {
+                break;
+            }
+        }
+
+        info!("Metrics exporter stopped");
+        Ok(())
+    }
+}
+
+// OTLP 计数器或仪表的数据点，仪表没有起始时间
+fn number_point(labels: &[LabelPair], value: f64, start: Option<&str>, now: &str) -> Value {
+    let mut point = json!({
+        "attributes": otlp_attributes(labels),
+        "timeUnixNano": now,
+        "asDouble": value,
+    });
+    if let Some(start) = start {
+        point["startTimeUnixNano"] = json!(start);
+    }
+    point
+}
+
+// OTLP 直方图的数据点
+// Prometheus 的桶计数是累计值且不含 +Inf 桶，OTLP 的桶计数是每个桶内的数量且最后一个桶为 +Inf
+fn histogram_point(labels: &[LabelPair], histogram: &Histogram, start: &str, now: &str) -> Value {
+    let buckets: Vec<_> = histogram
+        .get_bucket()
+        .iter()
+        .filter(|bucket| bucket.get_upper_bound().is_finite())
+        .collect();
+    let mut previous = 0;
+    let mut counts: Vec<String> = buckets
+        .iter()
+        .map(|bucket| {
+            let count = bucket.get_cumulative_count().saturating_sub(previous);
+            previous = bucket.get_cumulative_count();
+            count.to_string()
+        })
+        .collect();
+    counts.push(
+        histogram
+            .get_sample_count()
+            .saturating_sub(previous)
+            .to_string(),
+    );
+    json!({
+        "attributes": otlp_attributes(labels),
+        "startTimeUnixNano": start,
+        "timeUnixNano": now,
+        "count": histogram.get_sample_count().to_string(),
+        "sum": histogram.get_sample_sum(),
+        "bucketCounts": counts,
+        "explicitBounds": buckets.iter().map(|bucket| bucket.get_upper_bound()).collect::<Vec<_>>(),
+    })
+}
+
+// OTLP 数据点的属性
+fn otlp_attributes(labels: &[LabelPair]) -> Vec<Value> {
+    labels
+        .iter()
+        .map(|label| {
+            json!({
+                "key": label.get_name(),
+                "value": {"stringValue": label.get_value()},
+            })
+        })
+        .collect()
+}
+
+// 将标签值转换为 StatsD 名称片段，只保留字母、数字、下划线和连字符
+fn sanitize_segment(value: &str) -> String {
+    if value.is_empty() {
+        return "_".to_string();
+    }
+    value
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '_' | '-') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+// 替换 DogStatsD tag 值中的分隔符
+fn sanitize_tag(value: &str) -> String {
+    value.replace([',', '|', '\n'], "_")
+}
+
+// 当前 Unix 时间（纳秒）
+fn unix_nanos() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+}
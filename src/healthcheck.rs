@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+use crate::{args::HealthcheckArgs, error::AppError, r#const::admin_paths};
+
+/// 请求运行中实例管理服务的健康检查端点，返回非成功状态码或请求失败时返回错误
+pub async fn run(args: &HealthcheckArgs) -> Result<(), AppError> {
+    let url = format!(
+        "{}{}",
+        args.admin_url.trim_end_matches('/'),
+        admin_paths::HEALTH
+    );
+    let response = reqwest::Client::builder()
+        .timeout(Duration::from_secs(args.timeout))
+        .build()?
+        .get(&url)
+        .send()
+        .await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(AppError::Internal(format!(
+            "Admin server returned {} for {}",
+            status, url
+        )));
+    }
+    Ok(())
+}
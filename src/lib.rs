@@ -1,13 +1,34 @@
+pub mod adaptive;
 pub mod admin;
+pub mod alert;
 pub mod api;
 pub mod args;
+pub mod audit;
 pub mod balancer;
 pub mod breaker;
+pub mod cache;
 pub mod config;
 pub mod r#const;
+pub mod discovery;
 pub mod error;
+pub mod events;
+pub mod export;
+pub mod healthcheck;
+pub mod loadgen;
+pub mod logfile;
 pub mod metrics;
+pub mod redact;
+pub mod redis_client;
+pub mod reload;
+pub mod restart;
+pub mod runtime;
+pub mod secret;
 pub mod server;
+pub mod support;
+pub mod systemd;
+pub mod tail;
+pub mod translate;
 pub mod upstream;
+pub mod usage;
 
 pub use crate::metrics::METRICS;
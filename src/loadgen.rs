@@ -0,0 +1,300 @@
+use crate::{
+    args::LoadgenArgs,
+    error::AppError,
+    r#const::{dialect, loadgen},
+    tail::SseParser,
+};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Client,
+};
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+// 合成提示词使用的单词
+const WORDS: [&str; 16] = [
+    "proxy", "latency", "token", "stream", "balance", "upstream", "model", "request", "cluster",
+    "cache", "route", "retry", "budget", "window", "signal", "buffer",
+];
+
+// 单个请求的结果
+#[derive(Debug, Clone)]
+struct Sample {
+    // 是否为流式请求
+    stream: bool,
+    // 状态码，没有收到响应时为 None
+    status: Option<u16>,
+    // 是否成功（2xx 且完整读取了响应体）
+    success: bool,
+    // 从发送请求到读完响应体的耗时
+    latency: Duration,
+    // 流式响应的首字节耗时
+    ttfb: Option<Duration>,
+    // 流式响应的事件数，不含结束标记
+    events: u64,
+}
+
+/// 压测结果
+#[derive(Debug, Clone, Default)]
+pub struct LoadReport {
+    /// 发送的请求数
+    pub total: u64,
+    /// 成功的请求数
+    pub succeeded: u64,
+    /// 流式请求数
+    pub streamed: u64,
+    /// 按状态码统计的请求数，没有收到响应的请求记为 `error`
+    pub statuses: BTreeMap<String, u64>,
+    /// 压测总耗时
+    pub elapsed: Duration,
+    /// 请求耗时，升序排列
+    pub latencies: Vec<Duration>,
+    /// 流式响应的首字节耗时，升序排列
+    pub ttfbs: Vec<Duration>,
+    /// 流式响应的事件总数
+    pub events: u64,
+}
+
+impl LoadReport {
+    /// 每秒完成的请求数
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.total as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// 汇总结果为多行文本
+    pub fn summary(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(
+            text,
+            "Requests:   {} total, {} succeeded, {} failed, {} streamed",
+            self.total,
+            self.succeeded,
+            self.total - self.succeeded,
+            self.streamed
+        );
+        let _ = writeln!(
+            text,
+            "Duration:   {:.2}s ({:.1} req/s)",
+            self.elapsed.as_secs_f64(),
+            self.throughput()
+        );
+        let statuses: Vec<String> = self
+            .statuses
+            .iter()
+            .map(|(status, count)| format!("{}={}", status, count))
+            .collect();
+        let _ = writeln!(text, "Status:     {}", statuses.join(" "));
+        let _ = writeln!(text, "Latency:    {}", format_percentiles(&self.latencies));
+        if !self.ttfbs.is_empty() {
+            let _ = writeln!(text, "TTFB:       {}", format_percentiles(&self.ttfbs));
+            let _ = writeln!(text, "SSE events: {}", self.events);
+        }
+        text
+    }
+}
+
+/// 计算升序排列的耗时的百分位（最近秩法），列表为空时返回 None
+pub fn percentile(sorted: &[Duration], percentile: f64) -> Option<Duration> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (percentile / 100.0 * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+// 格式化耗时的百分位
+fn format_percentiles(sorted: &[Duration]) -> String {
+    loadgen::PERCENTILES
+        .iter()
+        .filter_map(|p| {
+            percentile(sorted, *p).map(|value| {
+                let label = if *p >= 100.0 {
+                    "max".to_string()
+                } else {
+                    format!("p{}", p)
+                };
+                format!("{}={:.1}ms", label, value.as_secs_f64() * 1000.0)
+            })
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 第 index 个请求是否为流式请求，按比例均匀分布在请求序列中
+pub fn is_stream_request(index: u64, ratio: f64) -> bool {
+    ((index + 1) as f64 * ratio).floor() > (index as f64 * ratio).floor()
+}
+
+/// 构建 OpenAI 格式的聊天请求体，流式请求要求上游在最后的事件中返回用量
+pub fn build_request_body(args: &LoadgenArgs, index: u64, stream: bool) -> Value {
+    let prompt = (0..args.prompt_words)
+        .map(|i| WORDS[(index as usize + i) % WORDS.len()])
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut body = json!({
+        "model": args.model,
+        "messages": [{"role": "user", "content": prompt}],
+        "max_tokens": args.max_tokens,
+    });
+    if stream {
+        body["stream"] = json!(true);
+        body["stream_options"] = json!({"include_usage": true});
+    }
+    body
+}
+
+// 构建额外的请求头
+fn build_headers(headers: &[(String, String)]) -> Result<HeaderMap, AppError> {
+    let mut map = HeaderMap::with_capacity(headers.len());
+    for (name, value) in headers {
+        let header_name = HeaderName::from_bytes(name.as_bytes()).map_err(|e| {
+            AppError::InvalidHeader(format!("Invalid header name '{}': {}", name, e))
+        })?;
+        let header_value = HeaderValue::from_str(value).map_err(|e| {
+            AppError::InvalidHeader(format!("Invalid header value for '{}': {}", name, e))
+        })?;
+        map.append(header_name, header_value);
+    }
+    Ok(map)
+}
+
+// 发送一个请求并读完响应体
+async fn send_request(client: &Client, url: &str, body: &Value, stream: bool) -> Sample {
+    let started = Instant::now();
+    let mut sample = Sample {
+        stream,
+        status: None,
+        success: false,
+        latency: Duration::ZERO,
+        ttfb: None,
+        events: 0,
+    };
+
+    let Ok(mut response) = client.post(url).json(body).send().await else {
+        sample.latency = started.elapsed();
+        return sample;
+    };
+    let status = response.status();
+    sample.status = Some(status.as_u16());
+
+    let mut parser = SseParser::default();
+    let completed = loop {
+        match response.chunk().await {
+            Ok(Some(chunk)) => {
+                if stream {
+                    sample.ttfb.get_or_insert_with(|| started.elapsed());
+                    sample.events += parser
+                        .feed(&chunk)
+                        .iter()
+                        .filter(|frame| frame.data != dialect::STREAM_DONE)
+                        .count() as u64;
+                }
+            }
+            Ok(None) => break true,
+            Err(_) => break false,
+        }
+    };
+
+    sample.latency = started.elapsed();
+    sample.success = completed && status.is_success();
+    sample
+}
+
+/// 按参数发送请求并汇总结果
+///
+/// 启动与并发数相同的工作任务，依次领取请求序号，直到发送完全部请求。
+pub async fn execute(args: &LoadgenArgs) -> Result<LoadReport, AppError> {
+    let client = Client::builder()
+        .timeout(Duration::from_secs(args.timeout))
+        .default_headers(build_headers(&args.headers)?)
+        .build()?;
+    let url = format!(
+        "{}/{}",
+        args.url.trim_end_matches('/'),
+        args.path.trim_start_matches('/')
+    );
+
+    let args = Arc::new(args.clone());
+    let next = Arc::new(AtomicU64::new(0));
+    let started = Instant::now();
+
+    let workers: Vec<_> = (0..(args.concurrency as u64).min(args.requests))
+        .map(|_| {
+            let (client, url, args, next) =
+                (client.clone(), url.clone(), args.clone(), next.clone());
+            tokio::spawn(async move {
+                let mut samples = Vec::new();
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    if index >= args.requests {
+                        break;
+                    }
+                    let stream = is_stream_request(index, args.stream_ratio);
+                    let body = build_request_body(&args, index, stream);
+                    samples.push(send_request(&client, &url, &body, stream).await);
+                }
+                samples
+            })
+        })
+        .collect();
+
+    let mut report = LoadReport::default();
+    for worker in workers {
+        let samples = worker
+            .await
+            .map_err(|e| AppError::Internal(format!("Load generator worker failed: {}", e)))?;
+        for sample in samples {
+            report.total += 1;
+            report.succeeded += sample.success as u64;
+            let status = sample
+                .status
+                .map_or_else(|| loadgen::ERROR_STATUS.to_string(), |s| s.to_string());
+            *report.statuses.entry(status).or_default() += 1;
+            report.streamed += sample.stream as u64;
+            report.latencies.push(sample.latency);
+            report.ttfbs.extend(sample.ttfb);
+            report.events += sample.events;
+        }
+    }
+    report.elapsed = started.elapsed();
+    report.latencies.sort_unstable();
+    report.ttfbs.sort_unstable();
+
+    Ok(report)
+}
+
+/// 运行压测并输出结果，所有请求都失败时返回错误
+pub async fn run(args: &LoadgenArgs) -> Result<(), AppError> {
+    eprintln!(
+        "Sending {} requests to {}{} with concurrency {} ({:.0}% streaming)",
+        args.requests,
+        args.url.trim_end_matches('/'),
+        args.path,
+        args.concurrency,
+        args.stream_ratio * 100.0
+    );
+
+    let report = execute(args).await?;
+    print!("{}", report.summary());
+
+    if report.succeeded == 0 {
+        return Err(AppError::Internal(
+            "No request succeeded, check the URL, path and headers".to_string(),
+        ));
+    }
+    Ok(())
+}
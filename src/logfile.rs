@@ -0,0 +1,191 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, SyncSender},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{args::LogRotation, r#const::log_file};
+
+/// 按大小和时间轮转的日志文件
+///
+/// 写入后超出最大大小，或写入时已进入新的小时/天（UTC）时，将当前文件依次重命名为
+/// `<path>.1`、`<path>.2` 等，只保留配置数量的轮转文件
+pub struct RotatingFile {
+    // 日志文件路径
+    path: PathBuf,
+    // 当前文件
+    file: File,
+    // 当前文件大小（字节）
+    size: u64,
+    // 文件的最大大小（字节）
+    max_size: u64,
+    // 保留的轮转文件数
+    max_files: u32,
+    // 按时间轮转的周期
+    rotation: LogRotation,
+    // 当前文件所属的周期
+    period: u64,
+}
+
+impl RotatingFile {
+    /// 以追加方式打开日志文件，已有文件的周期按最后修改时间计算
+    pub fn open(
+        path: impl AsRef<Path>,
+        max_size: u64,
+        max_files: u32,
+        rotation: LogRotation,
+    ) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let metadata = file.metadata()?;
+        let modified = metadata.modified().unwrap_or_else(|_| SystemTime::now());
+        Ok(Self {
+            path,
+            file,
+            size: metadata.len(),
+            max_size,
+            max_files,
+            rotation,
+            period: period(rotation, modified),
+        })
+    }
+
+    /// 写入数据
+    pub fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        self.write_at(data, SystemTime::now())
+    }
+
+    /// 以指定的当前时间写入数据，写入后超出最大大小或已进入新的周期时先轮转
+    pub fn write_at(&mut self, data: &[u8], now: SystemTime) -> io::Result<()> {
+        let current = period(self.rotation, now);
+        if self.size > 0
+            && (self.size + data.len() as u64 > self.max_size || current != self.period)
+        {
+            self.rotate()?;
+        }
+        self.period = current;
+        self.file.write_all(data)?;
+        self.file.flush()?;
+        self.size += data.len() as u64;
+        Ok(())
+    }
+
+    // 轮转日志文件并重新打开
+    fn rotate(&mut self) -> io::Result<()> {
+        let rotated = |n: u32| {
+            let mut path = self.path.clone().into_os_string();
+            path.push(format!(".{}", n));
+            PathBuf::from(path)
+        };
+        for n in (1..self.max_files).rev() {
+            let from = rotated(n);
+            if from.exists() {
+                fs::rename(from, rotated(n + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated(1))?;
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+// 写入队列中的消息
+enum Message {
+    // 一行日志
+    Line(Vec<u8>),
+    // 写完之前的日志后通知
+    Flush(SyncSender<()>),
+}
+
+/// 非阻塞的日志文件写入器
+///
+/// 日志行放入有界队列后立即返回，由后台线程写入轮转文件。队列已满或写入失败时丢弃日志行，
+/// 不阻塞输出日志的线程
+#[derive(Clone)]
+pub struct LogFileWriter {
+    // 等待写入的日志行
+    sender: SyncSender<Message>,
+}
+
+/// 日志文件写入器的守卫，丢弃时等待后台线程写完队列中的日志
+pub struct LogFileGuard {
+    // 写入器
+    writer: LogFileWriter,
+}
+
+impl LogFileWriter {
+    /// 启动后台写入线程
+    pub fn spawn(file: RotatingFile) -> io::Result<(Self, LogFileGuard)> {
+        let (sender, receiver) = mpsc::sync_channel(log_file::BUFFER);
+        std::thread::Builder::new()
+            .name("log-file".to_string())
+            .spawn(move || run_writer(receiver, file))?;
+        let writer = Self { sender };
+        let guard = LogFileGuard {
+            writer: writer.clone(),
+        };
+        Ok((writer, guard))
+    }
+
+    /// 将日志行放入写入队列，队列已满时丢弃并返回 false
+    pub fn write(&self, line: Vec<u8>) -> bool {
+        self.sender.try_send(Message::Line(line)).is_ok()
+    }
+
+    /// 等待队列中已有的日志写入文件，超时后返回 false
+    pub fn flush(&self, timeout: Duration) -> bool {
+        let (ack, done) = mpsc::sync_channel(1);
+        self.sender.send(Message::Flush(ack)).is_ok() && done.recv_timeout(timeout).is_ok()
+    }
+}
+
+impl Drop for LogFileGuard {
+    fn drop(&mut self) {
+        self.writer
+            .flush(Duration::from_millis(log_file::FLUSH_TIMEOUT_MS));
+    }
+}
+
+// 后台写入线程，每次取出队列中的全部日志行后批量写入
+fn run_writer(receiver: Receiver<Message>, mut file: RotatingFile) {
+    while let Ok(message) = receiver.recv() {
+        let mut batch = Vec::new();
+        let mut acks = Vec::new();
+        let mut next = Some(message);
+        while let Some(message) = next {
+            match message {
+                Message::Line(line) => batch.extend_from_slice(&line),
+                Message::Flush(ack) => acks.push(ack),
+            }
+            next = receiver.try_recv().ok();
+        }
+        if !batch.is_empty() {
+            if let Err(e) = file.write(&batch) {
+                // 日志系统本身无法写入，只能输出到标准错误
+                let _ = writeln!(io::stderr(), "Failed to write log file: {}", e);
+            }
+        }
+        for ack in acks {
+            let _ = ack.send(());
+        }
+    }
+}
+
+// 计算时间所属的轮转周期，不按时间轮转时始终为 0
+fn period(rotation: LogRotation, time: SystemTime) -> u64 {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    match rotation {
+        LogRotation::Never => 0,
+        LogRotation::Hourly => secs / 3600,
+        LogRotation::Daily => secs / 86400,
+    }
+}
@@ -1,83 +1,356 @@
+use clap::CommandFactory;
 use llmproxy::{
-    admin::AdminServer, args::Args, config::Config, error::AppError, server::ForwardServer,
-    upstream::UpstreamManager,
+    admin::{AdminServer, MetricsServer},
+    alert::AlertEvaluator,
+    args::{Args, Command, ConfigCommand, LogFormat, ValidateArgs},
+    audit::AuditLog,
+    config::{self, validation, Config, ConfigFormat, ConfigSource, MetricsUpstreamLabel},
+    discovery::{ConsulDiscovery, DnsDiscovery, KubernetesDiscovery},
+    error::AppError,
+    export::MetricsExporter,
+    healthcheck, loadgen,
+    logfile::{LogFileGuard, LogFileWriter, RotatingFile},
+    metrics::{self, METRICS},
+    reload::ConfigReloader,
+    restart, runtime,
+    server::{ForwardServer, LoadWatchdog},
+    support::{self, LogWriter},
+    systemd, tail,
+    upstream::{UpstreamManager, UpstreamWarmup},
 };
 use mimalloc::MiMalloc;
 use std::{collections::HashMap, process, sync::Arc};
 use tokio::sync::RwLock;
 use tokio_graceful_shutdown::{IntoSubsystem, SubsystemBuilder, Toplevel};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 // 使用 mimalloc 分配器提高内存效率
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
 
-fn init_logging(args: &Args) {
+fn init_logging(args: &Args) -> Result<Option<LogFileGuard>, AppError> {
+    // 配置了日志文件时由后台线程写入按大小和时间轮转的文件
+    let guard = match &args.log_file {
+        Some(path) => {
+            let file = RotatingFile::open(
+                path,
+                args.log_max_size * 1024 * 1024,
+                args.log_max_files,
+                args.log_rotation,
+            )
+            .map_err(|e| AppError::Config(format!("Failed to open log file {:?}: {}", path, e)))?;
+            let (writer, guard) = LogFileWriter::spawn(file)?;
+            support::set_log_file(writer, args.log_stdout);
+            Some(guard)
+        }
+        None => None,
+    };
+
+    // 日志同时保留在内存中，用于生成支持包
     let builder = tracing_subscriber::fmt()
         .with_ansi(false)
-        .with_line_number(false);
+        .with_line_number(false)
+        .with_writer(LogWriter)
+        .with_env_filter(args.log_filter());
 
-    // 如果启用调试模式，输出调试信息，否则只输出 info 及以上级别
-    if args.debug {
-        builder.with_max_level(tracing::Level::DEBUG)
-    } else {
-        builder.with_max_level(tracing::Level::INFO)
+    // JSON 格式将请求范围的字段（请求 ID、转发服务、上游组）展开到每条日志中
+    match args.log_format {
+        LogFormat::Text => builder.init(),
+        LogFormat::Json => builder
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(false)
+            .init(),
     }
-    .init();
+    Ok(guard)
+}
+
+// 写完日志文件后退出进程
+fn exit(code: i32) -> ! {
+    support::flush_log_file();
+    process::exit(code)
 }
 
 // 程序入口
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 解析命令行参数
     let args = Args::parse_args();
 
-    // 初始化日志
-    init_logging(&args);
-
-    // 验证参数
+    // 验证参数，日志参数无效时还不能输出日志
     if let Err(e) = args.validation() {
-        error!("Invalid command line arguments: {}", e);
+        eprintln!("Invalid command line arguments: {}", e);
         process::exit(1);
     }
 
+    // 按参数创建 Tokio 运行时（工作线程数、阻塞线程数和 CPU 绑定）
+    let runtime = match runtime::build(&args) {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to start the Tokio runtime: {}", e);
+            process::exit(1);
+        }
+    };
+    runtime.block_on(run(args))
+}
+
+// 在运行时中执行子命令或运行服务
+async fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    // 初始化日志，退出时等待日志文件写完
+    let _log_guard = match init_logging(&args) {
+        Ok(guard) => guard,
+        Err(e) => {
+            eprintln!("Failed to initialize logging: {}", e);
+            process::exit(1);
+        }
+    };
+
+    // 执行子命令
+    match args.command {
+        Some(Command::Tail(ref tail_args)) => {
+            if let Err(e) = tail::run(tail_args).await {
+                error!("Failed to tail access log: {}", e);
+                exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::SupportBundle(ref bundle_args)) => {
+            match support::run(bundle_args).await {
+                Ok(output) => info!("Support bundle saved to {:?}", output),
+                Err(e) => {
+                    error!("Failed to download support bundle: {}", e);
+                    exit(1);
+                }
+            }
+            return Ok(());
+        }
+        Some(Command::Config(ref config_args)) => match config_args.command {
+            ConfigCommand::Dump => {
+                // 输出的是加载并验证后的配置，未填写的字段显示为默认值
+                let config = match ConfigSource::from_location(&args.config) {
+                    Ok(source) => source.load(args.config_format).await,
+                    Err(e) => Err(e),
+                };
+                match config.and_then(|config| support::redacted_config_yaml(&config)) {
+                    Ok(yaml) => print!("{}", yaml),
+                    Err(e) => {
+                        error!("Failed to dump configuration: {}", e);
+                        exit(1);
+                    }
+                }
+                return Ok(());
+            }
+        },
+        Some(Command::Schema) => {
+            match config::json_schema()
+                .and_then(|schema| Ok(serde_json::to_string_pretty(&schema)?))
+            {
+                Ok(schema) => println!("{}", schema),
+                Err(e) => {
+                    error!("Failed to generate configuration schema: {}", e);
+                    exit(1);
+                }
+            }
+            return Ok(());
+        }
+        Some(Command::Completions(ref completions_args)) => {
+            clap_complete::generate(
+                completions_args.shell,
+                &mut Args::command(),
+                env!("CARGO_BIN_NAME"),
+                &mut std::io::stdout(),
+            );
+            return Ok(());
+        }
+        Some(Command::Healthcheck(ref healthcheck_args)) => {
+            if let Err(e) = healthcheck::run(healthcheck_args).await {
+                error!("Health check failed: {}", e);
+                exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Loadgen(ref loadgen_args)) => {
+            if let Err(e) = loadgen::run(loadgen_args).await {
+                error!("Load generation failed: {}", e);
+                exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Run) | Some(Command::Validate(_)) | None => {}
+    }
+
     info!("Starting LLMProxy - Large Model Proxy Service");
+    let runtime_metrics = tokio::runtime::Handle::current().metrics();
+    info!(
+        "Tokio runtime: {} worker threads, CPU affinity: {}",
+        runtime_metrics.num_workers(),
+        args.cpu_affinity
+            .as_ref()
+            .map_or_else(|| "none".to_string(), |cpus| cpus.to_string())
+    );
 
-    // 加载配置
-    let config = match Config::from_file(&args.config) {
+    // 加载配置，地址为 etcd 或 Consul 时从远程键值存储读取
+    let source = match ConfigSource::from_location(&args.config) {
+        Ok(source) => source,
+        Err(e) => {
+            error!("Failed to load configuration file: {}", e);
+            exit(1);
+        }
+    };
+    let config = match source.load(args.config_format).await {
         Ok(config) => {
-            info!("Successfully loaded configuration: {:?}", args.config);
+            info!("Successfully loaded configuration: {}", source);
             config
         }
         Err(e) => {
             error!("Failed to load configuration file: {}", e);
-            process::exit(1);
+            exit(1);
         }
     };
 
+    // 检查配置中的潜在问题，严格模式下视为错误并一次列出全部问题
+    let warnings = validation::config_warnings(&config);
+    if matches!(
+        args.command,
+        Some(Command::Validate(ValidateArgs { strict: true }))
+    ) && !warnings.is_empty()
+    {
+        for warning in &warnings {
+            error!("{}", warning);
+        }
+        error!(
+            "Configuration validation failed in strict mode with {} warnings",
+            warnings.len()
+        );
+        exit(1);
+    }
+    for warning in &warnings {
+        warn!("Configuration warning: {}", warning);
+    }
+
     // 如果是测试模式，成功验证配置后退出
-    if args.test_config {
+    if args.test_config || matches!(args.command, Some(Command::Validate(_))) {
         info!("Configuration file validated successfully");
         return Ok(());
     }
 
     // 创建应用组件
-    let components = match create_components(args.debug, config).await {
-        Ok(components) => components,
-        Err(e) => {
-            error!("Failed to create application components: {}", e);
-            process::exit(1);
+    let mut components =
+        match create_components(args.debug, source, args.config_format, config).await {
+            Ok(components) => components,
+            Err(e) => {
+                error!("Failed to create application components: {}", e);
+                exit(1);
+            }
+        };
+
+    // 启动服务前绑定转发服务的监听地址
+    for forward_server in &mut components.forward_servers {
+        if let Err(e) = forward_server.listen() {
+            error!("Failed to listen on {:?}: {}", forward_server.get_addr(), e);
+            exit(1);
         }
-    };
+    }
+
+    // 由重启启动时，监听地址已就绪，通知旧进程停止接受连接并退出
+    restart::notify_parent();
+
+    // 远程配置来源总是监听变化，本地文件或目录在指定 --watch-config 时监听
+    let watch_config = components.reloader.source().is_remote() || args.watch_config;
+    let watch_debounce = tokio::time::Duration::from_millis(args.watch_debounce);
 
     // 创建优雅关闭顶层管理器
-    let toplevel = Toplevel::new(|s| async move {
+    let toplevel = Toplevel::new(move |s| async move {
         // 启动管理服务子系统
         let admin_server = components.admin_server;
         s.start(SubsystemBuilder::new("admin_server", move |s| async move {
             admin_server.run(s).await
         }));
 
+        // 启动独立的指标服务子系统
+        if let Some(metrics_server) = components.metrics_server {
+            s.start(SubsystemBuilder::new(
+                "metrics_server",
+                move |s| async move { metrics_server.run(s).await },
+            ));
+        }
+
+        // 启动指标推送子系统
+        if let Some(metrics_exporter) = components.metrics_exporter {
+            s.start(SubsystemBuilder::new(
+                "metrics_exporter",
+                move |s| async move { metrics_exporter.run(s).await },
+            ));
+        }
+
+        // 启动告警评估子系统
+        if let Some(alert_evaluator) = components.alert_evaluator {
+            s.start(SubsystemBuilder::new(
+                "alert_evaluator",
+                move |s| async move { alert_evaluator.run(s).await },
+            ));
+        }
+
+        // 启动配置重载子系统
+        let reloader = components.reloader;
+        if watch_config {
+            let watcher = reloader.clone();
+            s.start(SubsystemBuilder::new(
+                "config_watcher",
+                move |s| async move { watcher.watch(watch_debounce, s).await },
+            ));
+        }
+        s.start(SubsystemBuilder::new(
+            "config_reloader",
+            move |s| async move { reloader.run(s).await },
+        ));
+
+        // 启动重启信号监听子系统
+        s.start(SubsystemBuilder::new("restart_listener", restart::run));
+
+        // 启动 systemd 通知子系统
+        s.start(SubsystemBuilder::new("systemd_notify", systemd::run));
+
+        // 启动资源监控子系统
+        if let Some(watchdog) = components.watchdog {
+            s.start(SubsystemBuilder::new(
+                "load_watchdog",
+                move |s| async move { watchdog.run(s).await },
+            ));
+        }
+
+        // 启动 DNS 服务发现子系统
+        if let Some(dns_discovery) = components.dns_discovery {
+            s.start(SubsystemBuilder::new(
+                "dns_discovery",
+                move |s| async move { dns_discovery.run(s).await },
+            ));
+        }
+
+        // 启动 Kubernetes 服务发现子系统
+        if let Some(kubernetes_discovery) = components.kubernetes_discovery {
+            s.start(SubsystemBuilder::new(
+                "kubernetes_discovery",
+                move |s| async move { kubernetes_discovery.run(s).await },
+            ));
+        }
+
+        // 启动 Consul 服务发现子系统
+        if let Some(consul_discovery) = components.consul_discovery {
+            s.start(SubsystemBuilder::new(
+                "consul_discovery",
+                move |s| async move { consul_discovery.run(s).await },
+            ));
+        }
+
+        // 启动上游预热子系统，熔断器恢复时重新预热
+        if let Some(upstream_warmup) = components.upstream_warmup {
+            s.start(SubsystemBuilder::new(
+                "upstream_warmup",
+                move |s| async move { upstream_warmup.run(s).await },
+            ));
+        }
+
         // 启动所有转发服务子系统
         for (i, forward_server) in components.forward_servers.into_iter().enumerate() {
             let subsystem_name = format!("forward_server_{}", i);
@@ -100,7 +373,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
         Err(e) => {
             error!("Application shutdown error: {}", e);
-            process::exit(1);
+            exit(1);
         }
     }
 }
@@ -109,17 +382,40 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 struct AppComponents {
     // 管理服务
     admin_server: AdminServer,
+    // 独立的指标服务
+    metrics_server: Option<MetricsServer>,
+    // 指标推送器
+    metrics_exporter: Option<MetricsExporter>,
+    // 告警评估器
+    alert_evaluator: Option<AlertEvaluator>,
     // 转发服务列表
     forward_servers: Vec<ForwardServer>,
+    // 配置重载器
+    reloader: Arc<ConfigReloader>,
+    // 资源监控（过载保护）
+    watchdog: Option<LoadWatchdog>,
+    // DNS 服务发现
+    dns_discovery: Option<DnsDiscovery>,
+    // Kubernetes 服务发现
+    kubernetes_discovery: Option<KubernetesDiscovery>,
+    // Consul 服务发现
+    consul_discovery: Option<ConsulDiscovery>,
+    // 上游预热
+    upstream_warmup: Option<UpstreamWarmup>,
 }
 
 // 创建应用组件
-async fn create_components(debug: bool, config: Config) -> Result<AppComponents, AppError> {
+async fn create_components(
+    debug: bool,
+    config_source: ConfigSource,
+    config_format: Option<ConfigFormat>,
+    config: Config,
+) -> Result<AppComponents, AppError> {
     // 创建配置的共享引用，使用RwLock包装以支持动态更新
     let config_arc = Arc::new(RwLock::new(config));
 
     // 在一个读锁范围内获取所有配置，避免多次获取锁
-    let (upstreams, upstream_groups, http_server_config) = {
+    let (upstreams, upstream_groups, models, http_server_config) = {
         let config_guard = config_arc.read().await;
         let http_server = config_guard
             .http_server
@@ -129,13 +425,21 @@ async fn create_components(debug: bool, config: Config) -> Result<AppComponents,
         (
             config_guard.upstreams.clone(),
             config_guard.upstream_groups.clone(),
+            config_guard.models.clone(),
             http_server,
         )
     };
 
+    // 按配置设置请求耗时类直方图的分桶，必须在创建任何指标之前设置
+    if let Some(ref buckets) = http_server_config.admin.metrics.duration_buckets {
+        if !metrics::set_duration_buckets(buckets.clone()) {
+            warn!("Metrics were created before applying duration buckets, using default buckets");
+        }
+    }
+
     // 创建上游管理器
     let upstream_manager: Arc<UpstreamManager> =
-        match UpstreamManager::new(upstreams, upstream_groups).await {
+        match UpstreamManager::new(upstreams.clone(), upstream_groups.clone()).await {
             Ok(manager) => Arc::new(manager),
             Err(e) => {
                 error!("Failed to initialize upstream manager: {}", e);
@@ -143,6 +447,31 @@ async fn create_components(debug: bool, config: Config) -> Result<AppComponents,
             }
         };
 
+    // 创建 DNS 服务发现，开始服务前先解析一次
+    let dns_discovery = DnsDiscovery::new(upstream_manager.clone(), &upstreams)?;
+    if let Some(dns_discovery) = &dns_discovery {
+        dns_discovery.refresh().await;
+    }
+
+    // 创建 Kubernetes 服务发现，开始服务前先列出一次
+    let kubernetes_discovery =
+        KubernetesDiscovery::new(upstream_manager.clone(), &upstream_groups)?;
+    if let Some(kubernetes_discovery) = &kubernetes_discovery {
+        kubernetes_discovery.refresh().await;
+    }
+
+    // 创建 Consul 服务发现，开始服务前先查询一次
+    let consul_discovery = ConsulDiscovery::new(upstream_manager.clone(), &upstream_groups)?;
+    if let Some(consul_discovery) = &consul_discovery {
+        consul_discovery.refresh().await;
+    }
+
+    // 创建上游预热，开始服务前先预热一次
+    let upstream_warmup = UpstreamWarmup::new(upstream_manager.clone());
+    if let Some(upstream_warmup) = &upstream_warmup {
+        upstream_warmup.warm_up_all().await;
+    }
+
     // 创建转发服务
     let mut forward_servers = Vec::with_capacity(http_server_config.forwards.len());
 
@@ -151,7 +480,7 @@ async fn create_components(debug: bool, config: Config) -> Result<AppComponents,
 
     for forward_config in &http_server_config.forwards {
         // 使用克隆避免所有权转移
-        match ForwardServer::new(forward_config.clone(), upstream_manager.clone()) {
+        match ForwardServer::new(forward_config.clone(), upstream_manager.clone(), &models) {
             Ok(server) => {
                 info!(
                     "Forwarding service {:?} initialized successfully",
@@ -182,12 +511,78 @@ async fn create_components(debug: bool, config: Config) -> Result<AppComponents,
     )
     .parse()
     .map_err(|e| AppError::Config(format!("Invalid admin server address: {}", e)))?;
-    let admin_server = AdminServer::new(debug, admin_addr, config_arc.clone(), forward_states);
+    // 创建独立的指标服务
+    let metrics_config = &http_server_config.admin.metrics;
+    let metrics_server = match metrics_config.port {
+        Some(port) => {
+            let address = metrics_config
+                .address
+                .as_ref()
+                .unwrap_or(&http_server_config.admin.address);
+            let metrics_addr = format!("{}:{}", address, port)
+                .parse()
+                .map_err(|e| AppError::Config(format!("Invalid metrics server address: {}", e)))?;
+            info!(
+                "Metrics server initialized successfully: {:?}",
+                metrics_addr
+            );
+            Some(MetricsServer::new(metrics_addr, metrics_config.clone()))
+        }
+        None => None,
+    };
+    // 按配置设置指标标签
+    METRICS.set_model_label(metrics_config.labels.model);
+    METRICS.set_upstream_name_label(metrics_config.labels.upstream == MetricsUpstreamLabel::Name);
+    METRICS.set_disabled_labels(metrics_config.labels.disabled.clone());
+
+    // 创建指标推送器
+    let metrics_exporter = metrics_config
+        .export
+        .as_ref()
+        .map(MetricsExporter::new)
+        .transpose()?;
+
+    // 创建告警评估器
+    let alert_evaluator = metrics_config
+        .alerts
+        .as_ref()
+        .map(AlertEvaluator::new)
+        .transpose()?;
+
+    // 创建审计日志
+    let audit = Arc::new(AuditLog::new(&http_server_config.admin.audit)?);
+
+    // 创建配置重载器
+    let reloader = Arc::new(
+        ConfigReloader::new(config_source, config_arc.clone(), forward_states.clone())
+            .with_format(config_format),
+    );
+
+    let admin_server = AdminServer::new(
+        debug,
+        admin_addr,
+        config_arc.clone(),
+        forward_states,
+        audit,
+        reloader.clone(),
+    );
     info!("Admin server initialized successfully: {:?}", admin_addr);
 
+    // 创建资源监控
+    let watchdog = http_server_config.load_shedding.map(LoadWatchdog::new);
+
     // 返回应用组件
     Ok(AppComponents {
         admin_server,
+        metrics_server,
+        metrics_exporter,
+        alert_evaluator,
         forward_servers,
+        reloader,
+        watchdog,
+        dns_discovery,
+        kubernetes_discovery,
+        consul_discovery,
+        upstream_warmup,
     })
 }
@@ -1,35 +1,106 @@
-use once_cell::sync::Lazy;
-use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry};
+use crate::r#const::{metrics_buckets, model_labels, status_class_labels, token_type_labels};
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::RwLock;
+use prometheus::{
+    proto::{LabelPair, Metric, MetricFamily},
+    CounterVec, Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+// 自定义的请求耗时类直方图分桶，必须在首次使用指标前设置
+static DURATION_BUCKETS: OnceCell<Vec<f64>> = OnceCell::new();
 
 // 应用指标
 pub struct Metrics {
     registry: Registry,
+    // 是否按请求体中的模型名称记录请求计数
+    model_label: AtomicBool,
+    // upstream 标签是否使用上游名称（否则使用上游地址）
+    upstream_name_label: AtomicBool,
+    // 输出指标时去掉的标签
+    disabled_labels: RwLock<Vec<String>>,
     // 上游请求计数
     upstream_requests_total: IntCounterVec,
     // 上游请求耗时
     upstream_duration_seconds: HistogramVec,
     // 上游错误计数
     upstream_errors_total: IntCounterVec,
+    // 上游首字节耗时
+    upstream_ttfb_seconds: HistogramVec,
+    // 上游连接数
+    upstream_connections_total: IntCounterVec,
+    // 上游请求使用新连接和复用连接的计数
+    upstream_connection_requests_total: IntCounterVec,
+    // 上游连接建立耗时
+    upstream_connect_seconds: HistogramVec,
+    // 上游预热请求计数
+    upstream_warmup_requests_total: IntCounterVec,
+    // 事件流的输出速度（估算的每秒 token 数）
+    stream_tokens_per_second: HistogramVec,
+    // 事件流的事件数
+    stream_chunks_total: IntCounterVec,
     // HTTP请求计数
     http_requests_total: IntCounterVec,
     // HTTP请求耗时
     http_request_duration_seconds: HistogramVec,
     // HTTP请求错误计数
     http_request_errors_total: IntCounterVec,
+    // 正在处理的请求数
+    inflight_requests: IntGaugeVec,
     // 限流计数
     ratelimit_total: IntCounterVec,
+    // 合并到相同在途请求的请求计数
+    coalesced_requests_total: IntCounterVec,
+    audit_records_total: IntCounterVec,
+    access_log_records_total: IntCounterVec,
+    // 指标推送计数
+    metrics_exports_total: IntCounterVec,
+    pii_redactions_total: IntCounterVec,
+    policy_decisions_total: IntCounterVec,
+    plugin_calls_total: IntCounterVec,
+    // WebSocket 连接计数
+    websocket_connections_total: IntCounterVec,
+    // 当前 WebSocket 连接数
+    websocket_active_connections: IntGaugeVec,
+    // WebSocket 连接转发的字节数
+    websocket_bytes_total: IntCounterVec,
+    // WebSocket 连接持续时间
+    websocket_duration_seconds: HistogramVec,
+    // 响应缓存查找计数
+    cache_requests_total: IntCounterVec,
     // 熔断器状态变化计数
     circuitbreaker_state_changes_total: IntCounterVec,
     // 熔断器调用结果计数
     circuitbreaker_calls_total: IntCounterVec,
     // 路由匹配计数
     route_matches_total: IntCounterVec,
+    // 提示词 token 数（估算值）
+    prompt_tokens: HistogramVec,
+    // 上游响应中的 token 用量
+    tokens_total: IntCounterVec,
+    // 按价格表计算的费用（美元）
+    cost_usd_total: CounterVec,
+    // 配置重载计数
+    config_reloads_total: IntCounterVec,
+    // 最近一次配置重载是否失败
+    config_reload_failed: IntGauge,
+    // 上游的自适应并发上限
+    upstream_concurrency_limit: IntGaugeVec,
+    // 过载保护的资源压力等级
+    load_shedding_pressure: IntGauge,
 }
 
 impl Metrics {
     // 创建新的指标收集器
     fn new() -> Self {
         let registry = Registry::new();
+        let duration_buckets = DURATION_BUCKETS.get();
 
         // 上游请求计数
         let upstream_requests_total = IntCounterVec::new(
@@ -37,7 +108,7 @@ impl Metrics {
                 "llmproxy_upstream_requests_total",
                 "Total number of requests forwarded to upstream services.",
             ),
-            &["group", "upstream"],
+            &["group", "upstream", "status_class", "model"],
         )
         .unwrap();
 
@@ -47,9 +118,11 @@ impl Metrics {
                 "llmproxy_upstream_duration_seconds",
                 "The latency of requests forwarded to upstream services, in seconds.",
             )
-            .buckets(vec![
-                0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 60.0,
-            ]),
+            .buckets(
+                duration_buckets
+                    .cloned()
+                    .unwrap_or_else(|| metrics_buckets::DURATION.to_vec()),
+            ),
             &["group", "upstream"],
         )
         .unwrap();
@@ -64,13 +137,92 @@ impl Metrics {
         )
         .unwrap();
 
+        // 上游首字节耗时
+        let upstream_ttfb_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "llmproxy_upstream_ttfb_seconds",
+                "Time from forwarding a request until the first byte of the upstream response body arrives, in seconds.",
+            )
+            .buckets(
+                duration_buckets
+                    .cloned()
+                    .unwrap_or_else(|| metrics_buckets::TTFB.to_vec()),
+            ),
+            &["group", "upstream"],
+        )
+        .unwrap();
+
+        // 上游连接数
+        let upstream_connections_total = IntCounterVec::new(
+            Opts::new(
+                "llmproxy_upstream_connections_total",
+                "Total number of connections opened to upstream services, including failed attempts.",
+            ),
+            &["group", "result"],
+        )
+        .unwrap();
+
+        // 上游预热请求计数
+        let upstream_warmup_requests_total = IntCounterVec::new(
+            Opts::new(
+                "llmproxy_upstream_warmup_requests_total",
+                "Total number of warm-up requests sent to upstream services at startup and after circuit breaker recovery.",
+            ),
+            &["group", "upstream", "result"],
+        )
+        .unwrap();
+
+        // 上游请求使用新连接和复用连接的计数
+        let upstream_connection_requests_total = IntCounterVec::new(
+            Opts::new(
+                "llmproxy_upstream_connection_requests_total",
+                "Total number of upstream requests sent on a new connection or a reused pooled connection.",
+            ),
+            &["group", "connection"],
+        )
+        .unwrap();
+
+        // 上游连接建立耗时
+        let upstream_connect_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "llmproxy_upstream_connect_seconds",
+                "Time spent establishing upstream connections by phase (DNS resolution, TCP connect and TLS handshake), in seconds.",
+            )
+            .buckets(metrics_buckets::CONNECT.to_vec()),
+            &["group", "phase"],
+        )
+        .unwrap();
+
+        // 事件流的输出速度
+        let stream_tokens_per_second = HistogramVec::new(
+            HistogramOpts::new(
+                "llmproxy_stream_tokens_per_second",
+                "Estimated output tokens per second of streaming (SSE) responses after the first byte, counting one token per event.",
+            )
+            .buckets(vec![
+                1.0, 5.0, 10.0, 20.0, 30.0, 50.0, 75.0, 100.0, 150.0, 200.0, 500.0,
+            ]),
+            &["group", "upstream"],
+        )
+        .unwrap();
+
+        // 事件流的事件数
+        let stream_chunks_total = IntCounterVec::new(
+            Opts::new(
+                "llmproxy_stream_chunks_total",
+                "Total number of events received in streaming (SSE) responses from upstream services.",
+            ),
+            &["group", "upstream"],
+        )
+        .unwrap();
+
         // HTTP请求计数
         let http_requests_total = IntCounterVec::new(
             Opts::new(
                 "llmproxy_http_requests_total",
                 "Total number of incoming HTTP requests received by the proxy.",
             ),
-            &["forward", "method"],
+            &["forward", "method", "status_class", "model"],
         )
         .unwrap();
 
@@ -80,13 +232,25 @@ impl Metrics {
                 "llmproxy_http_request_duration_seconds",
                 "The latency of incoming HTTP requests, from the moment they are received until a response is sent, in seconds.",
             )
-            .buckets(vec![
-                0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 60.0,
-            ]),
+            .buckets(
+                duration_buckets
+                    .cloned()
+                    .unwrap_or_else(|| metrics_buckets::DURATION.to_vec()),
+            ),
             &["forward", "method"],
         )
         .unwrap();
 
+        // 正在处理的请求数
+        let inflight_requests = IntGaugeVec::new(
+            Opts::new(
+                "llmproxy_inflight_requests",
+                "Current number of requests being processed by forward services, including responses still being streamed.",
+            ),
+            &["forward"],
+        )
+        .unwrap();
+
         // HTTP请求错误计数
         let http_request_errors_total = IntCounterVec::new(
             Opts::new(
@@ -107,6 +271,129 @@ impl Metrics {
         )
         .unwrap();
 
+        // 合并到相同在途请求的请求计数
+        let coalesced_requests_total = IntCounterVec::new(
+            Opts::new(
+                "llmproxy_coalesced_requests_total",
+                "Total number of requests served by sharing the response of an identical in-flight request.",
+            ),
+            &["forward"],
+        )
+        .unwrap();
+
+        // 请求审计记录计数
+        let audit_records_total = IntCounterVec::new(
+            Opts::new(
+                "llmproxy_audit_records_total",
+                "Total number of request audit records written or dropped.",
+            ),
+            &["forward", "result"],
+        )
+        .unwrap();
+
+        // 访问日志记录计数
+        let access_log_records_total = IntCounterVec::new(
+            Opts::new(
+                "llmproxy_access_log_records_total",
+                "Total number of access log records written or dropped.",
+            ),
+            &["forward", "result"],
+        )
+        .unwrap();
+
+        // 指标推送计数
+        let metrics_exports_total = IntCounterVec::new(
+            Opts::new(
+                "llmproxy_metrics_exports_total",
+                "Total number of metrics pushes to the configured exporter.",
+            ),
+            &["protocol", "result"],
+        )
+        .unwrap();
+
+        // PII 脱敏替换计数
+        let pii_redactions_total = IntCounterVec::new(
+            Opts::new(
+                "llmproxy_pii_redactions_total",
+                "Total number of PII occurrences redacted from request prompts.",
+            ),
+            &["forward", "detector"],
+        )
+        .unwrap();
+
+        // 外部策略服务裁决计数
+        let policy_decisions_total = IntCounterVec::new(
+            Opts::new(
+                "llmproxy_policy_decisions_total",
+                "Total number of external policy service decisions.",
+            ),
+            &["forward", "decision"],
+        )
+        .unwrap();
+
+        // WASM 插件调用计数
+        let plugin_calls_total = IntCounterVec::new(
+            Opts::new(
+                "llmproxy_plugin_calls_total",
+                "Total number of WASM plugin calls.",
+            ),
+            &["forward", "plugin", "stage", "result"],
+        )
+        .unwrap();
+
+        // WebSocket 连接计数
+        let websocket_connections_total = IntCounterVec::new(
+            Opts::new(
+                "llmproxy_websocket_connections_total",
+                "Total number of WebSocket upgrade requests, labelled by result (upgraded, rejected or error).",
+            ),
+            &["forward", "group", "result"],
+        )
+        .unwrap();
+
+        // 当前 WebSocket 连接数
+        let websocket_active_connections = IntGaugeVec::new(
+            Opts::new(
+                "llmproxy_websocket_active_connections",
+                "Current number of open WebSocket connections.",
+            ),
+            &["forward", "group"],
+        )
+        .unwrap();
+
+        // WebSocket 连接转发的字节数
+        let websocket_bytes_total = IntCounterVec::new(
+            Opts::new(
+                "llmproxy_websocket_bytes_total",
+                "Total number of bytes relayed over WebSocket connections.",
+            ),
+            &["forward", "group", "direction"],
+        )
+        .unwrap();
+
+        // WebSocket 连接持续时间
+        let websocket_duration_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "llmproxy_websocket_duration_seconds",
+                "The duration of WebSocket connections, from the upgrade until either side closes, in seconds.",
+            )
+            .buckets(vec![
+                1.0, 5.0, 15.0, 30.0, 60.0, 300.0, 600.0, 1800.0, 3600.0, 7200.0,
+            ]),
+            &["forward", "group"],
+        )
+        .unwrap();
+
+        // 响应缓存查找计数
+        let cache_requests_total = IntCounterVec::new(
+            Opts::new(
+                "llmproxy_cache_requests_total",
+                "Total number of response cache lookups, labelled by result (hit or miss).",
+            ),
+            &["forward", "result"],
+        )
+        .unwrap();
+
         // 熔断器状态变化计数
         let circuitbreaker_state_changes_total = IntCounterVec::new(
             Opts::new(
@@ -137,6 +424,73 @@ impl Metrics {
         )
         .unwrap();
 
+        // 提示词 token 数
+        let prompt_tokens = HistogramVec::new(
+            HistogramOpts::new(
+                "llmproxy_prompt_tokens",
+                "Estimated number of prompt tokens in chat and completion requests.",
+            )
+            .buckets(vec![
+                64.0, 256.0, 1024.0, 4096.0, 8192.0, 16384.0, 32768.0, 65536.0, 131072.0, 262144.0,
+            ]),
+            &["forward"],
+        )
+        .unwrap();
+
+        // 上游响应中的 token 用量
+        let tokens_total = IntCounterVec::new(
+            Opts::new(
+                "llmproxy_tokens_total",
+                "Total number of tokens reported in the usage of upstream responses.",
+            ),
+            &["group", "upstream", "model", "type"],
+        )
+        .unwrap();
+
+        // 按价格表计算的费用
+        let cost_usd_total = CounterVec::new(
+            Opts::new(
+                "llmproxy_cost_usd_total",
+                "Total cost in USD of upstream token usage, calculated from the configured price table.",
+            ),
+            &["group", "upstream", "model"],
+        )
+        .unwrap();
+
+        // 配置重载计数
+        let config_reloads_total = IntCounterVec::new(
+            Opts::new(
+                "llmproxy_config_reloads_total",
+                "Total number of configuration reload attempts.",
+            ),
+            &["result", "trigger"],
+        )
+        .unwrap();
+
+        // 最近一次配置重载是否失败
+        let config_reload_failed = IntGauge::new(
+            "llmproxy_config_reload_failed",
+            "Whether the last configuration reload failed (1) and the last-known-good configuration is still being served.",
+        )
+        .unwrap();
+
+        // 上游的自适应并发上限
+        let upstream_concurrency_limit = IntGaugeVec::new(
+            Opts::new(
+                "llmproxy_upstream_concurrency_limit",
+                "Current adaptive concurrency limit of upstream services.",
+            ),
+            &["upstream"],
+        )
+        .unwrap();
+
+        // 过载保护的资源压力等级
+        let load_shedding_pressure = IntGauge::new(
+            "llmproxy_load_shedding_pressure",
+            "Current load shedding pressure level: 0 accepts all requests, 1 rejects low priority requests, 2 only accepts high priority requests.",
+        )
+        .unwrap();
+
         // 注册指标
         registry
             .register(Box::new(upstream_requests_total.clone()))
@@ -147,6 +501,27 @@ impl Metrics {
         registry
             .register(Box::new(upstream_errors_total.clone()))
             .unwrap();
+        registry
+            .register(Box::new(upstream_ttfb_seconds.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(upstream_connections_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(upstream_connection_requests_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(upstream_connect_seconds.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(upstream_warmup_requests_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(stream_tokens_per_second.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(stream_chunks_total.clone()))
+            .unwrap();
         registry
             .register(Box::new(http_requests_total.clone()))
             .unwrap();
@@ -156,9 +531,48 @@ impl Metrics {
         registry
             .register(Box::new(http_request_errors_total.clone()))
             .unwrap();
+        registry
+            .register(Box::new(inflight_requests.clone()))
+            .unwrap();
         registry
             .register(Box::new(ratelimit_total.clone()))
             .unwrap();
+        registry
+            .register(Box::new(coalesced_requests_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(audit_records_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(access_log_records_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(metrics_exports_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(pii_redactions_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(policy_decisions_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(plugin_calls_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(websocket_connections_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(websocket_active_connections.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(websocket_bytes_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(websocket_duration_seconds.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(cache_requests_total.clone()))
+            .unwrap();
         registry
             .register(Box::new(circuitbreaker_state_changes_total.clone()))
             .unwrap();
@@ -168,19 +582,64 @@ impl Metrics {
         registry
             .register(Box::new(route_matches_total.clone()))
             .unwrap();
+        registry.register(Box::new(prompt_tokens.clone())).unwrap();
+        registry.register(Box::new(tokens_total.clone())).unwrap();
+        registry.register(Box::new(cost_usd_total.clone())).unwrap();
+        registry
+            .register(Box::new(config_reloads_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(config_reload_failed.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(upstream_concurrency_limit.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(load_shedding_pressure.clone()))
+            .unwrap();
 
         Self {
             registry,
+            model_label: AtomicBool::new(false),
+            upstream_name_label: AtomicBool::new(false),
+            disabled_labels: RwLock::new(Vec::new()),
             upstream_requests_total,
             upstream_duration_seconds,
             upstream_errors_total,
+            upstream_ttfb_seconds,
+            upstream_connections_total,
+            upstream_connection_requests_total,
+            upstream_connect_seconds,
+            upstream_warmup_requests_total,
+            stream_tokens_per_second,
+            stream_chunks_total,
             http_requests_total,
             http_request_duration_seconds,
             http_request_errors_total,
+            inflight_requests,
             ratelimit_total,
+            coalesced_requests_total,
+            audit_records_total,
+            access_log_records_total,
+            metrics_exports_total,
+            pii_redactions_total,
+            policy_decisions_total,
+            plugin_calls_total,
+            websocket_connections_total,
+            websocket_active_connections,
+            websocket_bytes_total,
+            websocket_duration_seconds,
+            cache_requests_total,
             circuitbreaker_state_changes_total,
             circuitbreaker_calls_total,
             route_matches_total,
+            prompt_tokens,
+            tokens_total,
+            cost_usd_total,
+            config_reloads_total,
+            config_reload_failed,
+            upstream_concurrency_limit,
+            load_shedding_pressure,
         }
     }
 
@@ -189,6 +648,27 @@ impl Metrics {
         &self.registry
     }
 
+    // 收集所有指标，去掉配置中关闭的标签
+    pub fn gather(&self) -> Vec<MetricFamily> {
+        drop_labels(self.registry.gather(), &self.disabled_labels.read())
+    }
+
+    // 以 Prometheus 文本格式编码所有指标
+    pub fn encode_text(&self) -> prometheus::Result<Vec<u8>> {
+        let encoder = TextEncoder::new();
+
+        // 收集指标
+        let metric_families = self.gather();
+
+        // 预估缓冲区大小，避免多次重新分配
+        // 每个指标家族平均大约需要 200 字节
+        let estimated_size = metric_families.len() * 200;
+        let mut buffer = Vec::with_capacity(estimated_size);
+
+        encoder.encode(&metric_families, &mut buffer)?;
+        Ok(buffer)
+    }
+
     // 上游请求计数
     pub fn upstream_requests_total(&self) -> &IntCounterVec {
         &self.upstream_requests_total
@@ -204,6 +684,41 @@ impl Metrics {
         &self.upstream_errors_total
     }
 
+    // 上游首字节耗时
+    pub fn upstream_ttfb_seconds(&self) -> &HistogramVec {
+        &self.upstream_ttfb_seconds
+    }
+
+    // 上游连接数
+    pub fn upstream_connections_total(&self) -> &IntCounterVec {
+        &self.upstream_connections_total
+    }
+
+    // 上游请求使用新连接和复用连接的计数
+    pub fn upstream_connection_requests_total(&self) -> &IntCounterVec {
+        &self.upstream_connection_requests_total
+    }
+
+    // 上游连接建立耗时
+    pub fn upstream_connect_seconds(&self) -> &HistogramVec {
+        &self.upstream_connect_seconds
+    }
+
+    // 上游预热请求计数
+    pub fn upstream_warmup_requests_total(&self) -> &IntCounterVec {
+        &self.upstream_warmup_requests_total
+    }
+
+    // 事件流的输出速度
+    pub fn stream_tokens_per_second(&self) -> &HistogramVec {
+        &self.stream_tokens_per_second
+    }
+
+    // 事件流的事件数
+    pub fn stream_chunks_total(&self) -> &IntCounterVec {
+        &self.stream_chunks_total
+    }
+
     // HTTP请求计数
     pub fn http_requests_total(&self) -> &IntCounterVec {
         &self.http_requests_total
@@ -219,11 +734,76 @@ impl Metrics {
         &self.http_request_errors_total
     }
 
+    // 正在处理的请求数
+    pub fn inflight_requests(&self) -> &IntGaugeVec {
+        &self.inflight_requests
+    }
+
     // 限流计数
     pub fn ratelimit_total(&self) -> &IntCounterVec {
         &self.ratelimit_total
     }
 
+    // 合并请求计数
+    pub fn coalesced_requests_total(&self) -> &IntCounterVec {
+        &self.coalesced_requests_total
+    }
+
+    // 请求审计记录计数
+    pub fn audit_records_total(&self) -> &IntCounterVec {
+        &self.audit_records_total
+    }
+
+    // 访问日志记录计数
+    pub fn access_log_records_total(&self) -> &IntCounterVec {
+        &self.access_log_records_total
+    }
+
+    // 指标推送计数
+    pub fn metrics_exports_total(&self) -> &IntCounterVec {
+        &self.metrics_exports_total
+    }
+
+    // PII 脱敏替换计数
+    pub fn pii_redactions_total(&self) -> &IntCounterVec {
+        &self.pii_redactions_total
+    }
+
+    // 外部策略服务裁决计数
+    pub fn policy_decisions_total(&self) -> &IntCounterVec {
+        &self.policy_decisions_total
+    }
+
+    // WASM 插件调用计数
+    pub fn plugin_calls_total(&self) -> &IntCounterVec {
+        &self.plugin_calls_total
+    }
+
+    // WebSocket 连接计数
+    pub fn websocket_connections_total(&self) -> &IntCounterVec {
+        &self.websocket_connections_total
+    }
+
+    // 当前 WebSocket 连接数
+    pub fn websocket_active_connections(&self) -> &IntGaugeVec {
+        &self.websocket_active_connections
+    }
+
+    // WebSocket 连接转发的字节数
+    pub fn websocket_bytes_total(&self) -> &IntCounterVec {
+        &self.websocket_bytes_total
+    }
+
+    // WebSocket 连接持续时间
+    pub fn websocket_duration_seconds(&self) -> &HistogramVec {
+        &self.websocket_duration_seconds
+    }
+
+    // 响应缓存查找计数
+    pub fn cache_requests_total(&self) -> &IntCounterVec {
+        &self.cache_requests_total
+    }
+
     // 熔断器状态变化计数
     pub fn circuitbreaker_state_changes_total(&self) -> &IntCounterVec {
         &self.circuitbreaker_state_changes_total
@@ -234,6 +814,41 @@ impl Metrics {
         &self.circuitbreaker_calls_total
     }
 
+    // 提示词 token 数
+    pub fn prompt_tokens(&self) -> &HistogramVec {
+        &self.prompt_tokens
+    }
+
+    // 上游响应中的 token 用量
+    pub fn tokens_total(&self) -> &IntCounterVec {
+        &self.tokens_total
+    }
+
+    // 按价格表计算的费用
+    pub fn cost_usd_total(&self) -> &CounterVec {
+        &self.cost_usd_total
+    }
+
+    // 配置重载计数
+    pub fn config_reloads_total(&self) -> &IntCounterVec {
+        &self.config_reloads_total
+    }
+
+    // 最近一次配置重载是否失败
+    pub fn config_reload_failed(&self) -> &IntGauge {
+        &self.config_reload_failed
+    }
+
+    // 上游的自适应并发上限
+    pub fn upstream_concurrency_limit(&self) -> &IntGaugeVec {
+        &self.upstream_concurrency_limit
+    }
+
+    // 过载保护的资源压力等级
+    pub fn load_shedding_pressure(&self) -> &IntGauge {
+        &self.load_shedding_pressure
+    }
+
     // 记录上游请求错误
     pub fn record_upstream_request_error(&self, group: &str, upstream: &str, error_type: &str) {
         self.upstream_errors_total
@@ -241,6 +856,105 @@ impl Metrics {
             .inc();
     }
 
+    // 记录上游响应中的 token 用量，数量为 0 的类型不记录
+    pub fn record_tokens(
+        &self,
+        group: &str,
+        upstream: &str,
+        model: &str,
+        prompt_tokens: u64,
+        completion_tokens: u64,
+    ) {
+        for (token_type, tokens) in [
+            (token_type_labels::PROMPT, prompt_tokens),
+            (token_type_labels::COMPLETION, completion_tokens),
+        ] {
+            if tokens > 0 {
+                self.tokens_total
+                    .with_label_values(&[group, upstream, model, token_type])
+                    .inc_by(tokens);
+            }
+        }
+    }
+
+    // 记录按价格表计算的费用
+    pub fn record_cost(&self, group: &str, upstream: &str, model: &str, cost: f64) {
+        self.cost_usd_total
+            .with_label_values(&[group, upstream, model])
+            .inc_by(cost);
+    }
+
+    // 设置是否按请求体中的模型名称记录请求计数
+    pub fn set_model_label(&self, enabled: bool) {
+        self.model_label.store(enabled, Ordering::Relaxed);
+    }
+
+    // 设置 upstream 标签是否使用上游名称
+    pub fn set_upstream_name_label(&self, enabled: bool) {
+        self.upstream_name_label.store(enabled, Ordering::Relaxed);
+    }
+
+    // upstream 标签的取值，按配置使用上游名称或上游地址
+    pub fn upstream_label<'a>(&self, name: &'a str, url: &'a str) -> &'a str {
+        if self.upstream_name_label.load(Ordering::Relaxed) {
+            name
+        } else {
+            url
+        }
+    }
+
+    // 设置输出指标时去掉的标签
+    pub fn set_disabled_labels(&self, labels: Vec<String>) {
+        *self.disabled_labels.write() = labels;
+    }
+
+    // 请求体中的模型名称，用作 model 标签
+    // 未开启模型标签、请求体不是 JSON 或没有模型名称时为空，模型名称过长时为 other
+    pub fn model_label(&self, body: Option<&[u8]>) -> String {
+        #[derive(Deserialize)]
+        struct ModelField {
+            model: Option<String>,
+        }
+
+        if !self.model_label.load(Ordering::Relaxed) {
+            return String::new();
+        }
+        let model = body
+            .and_then(|body| serde_json::from_slice::<ModelField>(body).ok())
+            .and_then(|field| field.model)
+            .unwrap_or_default();
+        if model.len() > model_labels::MAX_LEN {
+            return model_labels::OTHER.to_string();
+        }
+        model
+    }
+
+    // 记录入站请求，按响应状态码类别和模型名称分类
+    pub fn record_http_request(
+        &self,
+        forward: &str,
+        method: &str,
+        status: StatusCode,
+        model: &str,
+    ) {
+        self.http_requests_total
+            .with_label_values(&[forward, method, status_class(status), model])
+            .inc();
+    }
+
+    // 记录转发给上游的请求，status_class 为上游响应的状态码类别，没有收到响应时为 error
+    pub fn record_upstream_request(
+        &self,
+        group: &str,
+        upstream: &str,
+        status_class: &str,
+        model: &str,
+    ) {
+        self.upstream_requests_total
+            .with_label_values(&[group, upstream, status_class, model])
+            .inc();
+    }
+
     // 记录路由匹配
     pub fn record_route_match(&self, forward: &str, group: &str) {
         self.route_matches_total
@@ -249,5 +963,82 @@ impl Metrics {
     }
 }
 
+/// 状态码类别（如 2xx、4xx）
+pub fn status_class(status: StatusCode) -> &'static str {
+    status_class_labels::CLASSES
+        .get(status.as_u16() as usize / 100)
+        .copied()
+        .unwrap_or(status_class_labels::CLASSES[0])
+}
+
+/// 设置请求耗时类直方图（请求耗时、上游耗时、首字节耗时）的分桶
+///
+/// 分桶在创建指标时确定，指标已经创建或已设置过分桶时返回 false
+pub fn set_duration_buckets(buckets: Vec<f64>) -> bool {
+    Lazy::get(&METRICS).is_none() && DURATION_BUCKETS.set(buckets).is_ok()
+}
+
+/// 从指标中去掉指定的标签，去掉标签后其余标签值相同的序列合并为一个序列
+pub fn drop_labels(families: Vec<MetricFamily>, labels: &[String]) -> Vec<MetricFamily> {
+    if labels.is_empty() {
+        return families;
+    }
+    families
+        .into_iter()
+        .map(|mut family| {
+            let mut merged: Vec<Metric> = Vec::new();
+            let mut index = HashMap::new();
+            for mut metric in family.take_metric().into_vec() {
+                let kept: Vec<LabelPair> = metric
+                    .take_label()
+                    .into_vec()
+                    .into_iter()
+                    .filter(|pair| !labels.iter().any(|label| label == pair.get_name()))
+                    .collect();
+                let key: Vec<String> = kept
+                    .iter()
+                    .map(|pair| pair.get_value().to_string())
+                    .collect();
+                metric.set_label(kept.into());
+                match index.get(&key) {
+                    Some(&i) => merge_metric(&mut merged[i], &metric),
+                    None => {
+                        index.insert(key, merged.len());
+                        merged.push(metric);
+                    }
+                }
+            }
+            family.set_metric(merged.into());
+            family
+        })
+        .collect()
+}
+
+// 将同一指标的两个序列相加，直方图的分桶相同
+fn merge_metric(into: &mut Metric, from: &Metric) {
+    if from.has_counter() {
+        let value = into.get_counter().get_value() + from.get_counter().get_value();
+        into.mut_counter().set_value(value);
+    }
+    if from.has_gauge() {
+        let value = into.get_gauge().get_value() + from.get_gauge().get_value();
+        into.mut_gauge().set_value(value);
+    }
+    if from.has_untyped() {
+        let value = into.get_untyped().get_value() + from.get_untyped().get_value();
+        into.mut_untyped().set_value(value);
+    }
+    if from.has_histogram() {
+        let from = from.get_histogram();
+        let histogram = into.mut_histogram();
+        histogram.set_sample_count(histogram.get_sample_count() + from.get_sample_count());
+        histogram.set_sample_sum(histogram.get_sample_sum() + from.get_sample_sum());
+        for (bucket, other) in histogram.mut_bucket().iter_mut().zip(from.get_bucket()) {
+            bucket
+                .set_cumulative_count(bucket.get_cumulative_count() + other.get_cumulative_count());
+        }
+    }
+}
+
 // 全局指标实例
 pub static METRICS: Lazy<Metrics> = Lazy::new(Metrics::new);
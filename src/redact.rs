@@ -0,0 +1,120 @@
+use crate::r#const::redact::{MASKED_VALUE, SECRET_FIELDS, SECRET_VALUE_FIELDS};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+// 脱敏 JSON 中的令牌、密码、请求头和查询参数的值，并去除 URL 中的用户信息（任意层级）
+pub fn redact_secrets(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if field.is_null() {
+                    continue;
+                }
+                if SECRET_FIELDS.contains(&key.as_str()) {
+                    *field = Value::String(MASKED_VALUE.to_string());
+                } else if SECRET_VALUE_FIELDS.contains(&key.as_str()) {
+                    redact_values(field);
+                } else if let (true, Value::String(url)) = (is_url_field(key), &mut *field) {
+                    if let Some(stripped) = strip_userinfo(url) {
+                        *url = stripped;
+                    }
+                } else {
+                    redact_secrets(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_secrets),
+        _ => {}
+    }
+}
+
+// 脱敏请求头映射的所有值，或请求头、查询参数操作列表中每个操作的 value
+fn redact_values(value: &mut Value) {
+    match value {
+        Value::Object(map) => map
+            .values_mut()
+            .filter(|value| !value.is_null())
+            .for_each(|value| *value = Value::String(MASKED_VALUE.to_string())),
+        Value::Array(ops) => ops
+            .iter_mut()
+            .filter_map(|op| op.get_mut("value"))
+            .filter(|value| !value.is_null())
+            .for_each(|value| *value = Value::String(MASKED_VALUE.to_string())),
+        _ => {}
+    }
+}
+
+// 字段是否为 URL（url、endpoint 或以 _url 结尾）
+#[inline(always)]
+fn is_url_field(key: &str) -> bool {
+    key == "url" || key == "endpoint" || key.ends_with("_url")
+}
+
+// 去除 URL 中的用户名和密码（如 redis://:pass@host），URL 无法解析或不包含用户信息时返回 None
+fn strip_userinfo(url: &str) -> Option<String> {
+    let mut parsed = url::Url::parse(url).ok()?;
+    if parsed.username().is_empty() && parsed.password().is_none() {
+        return None;
+    }
+    parsed.set_username("").ok()?;
+    parsed.set_password(None).ok()?;
+    Some(parsed.to_string())
+}
+
+// 脱敏 JSON 中的指定字段（任意层级）
+pub fn redact_fields<S: AsRef<str>>(value: &mut Value, fields: &[S]) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if fields.iter().any(|name| name.as_ref() == key) && !field.is_null() {
+                    *field = Value::String(MASKED_VALUE.to_string());
+                } else {
+                    redact_fields(field, fields);
+                }
+            }
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| redact_fields(item, fields)),
+        _ => {}
+    }
+}
+
+// 序列化为 JSON 并脱敏
+pub fn to_redacted_json<T: Serialize>(value: &T) -> serde_json::Result<Value> {
+    let mut value = serde_json::to_value(value)?;
+    redact_secrets(&mut value);
+    Ok(value)
+}
+
+// 脱敏单个敏感值
+#[inline(always)]
+pub fn mask(secret: &Option<String>) -> Option<String> {
+    secret.as_ref().map(|_| MASKED_VALUE.to_string())
+}
+
+// 判断值是否为脱敏后的占位值
+#[inline(always)]
+pub fn is_masked(secret: &Option<String>) -> bool {
+    secret.as_deref() == Some(MASKED_VALUE)
+}
+
+// 脱敏请求头映射的所有值
+pub fn mask_values(values: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+    values
+        .keys()
+        .map(|key| (key.clone(), MASKED_VALUE.to_string()))
+        .collect()
+}
+
+// 映射中的值为脱敏占位值时（如修改 GET 返回的配置后回传），保留现有的值
+pub fn restore_values(values: &mut BTreeMap<String, String>, existing: &BTreeMap<String, String>) {
+    for (key, value) in values.iter_mut() {
+        if value == MASKED_VALUE {
+            if let Some(existing) = existing.get(key) {
+                *value = existing.clone();
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Redis 连接
+//!
+//! 响应缓存和分布式限流共用的 Redis 客户端：首次使用时建立连接，连接断开后自动重连。
+
+use crate::{config::RedisConfig, error::AppError, r#const::redis_limits};
+use parking_lot::Mutex;
+use redis::{
+    aio::{ConnectionManager, ConnectionManagerConfig},
+    Client, IntoConnectionInfo,
+};
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+use tokio::sync::OnceCell;
+use tracing::{info, warn};
+
+/// Redis 客户端
+///
+/// 连接失败后在重试间隔内直接返回错误，避免 Redis 不可用时每个请求都等待连接超时
+pub struct RedisClient {
+    // Redis 客户端
+    client: Client,
+    // 键前缀
+    key_prefix: String,
+    // 共享的连接
+    connection: OnceCell<ConnectionManager>,
+    // 最近一次连接失败的时间
+    last_failure: Mutex<Option<Instant>>,
+}
+
+impl fmt::Debug for RedisClient {
+    // 连接信息中可能包含密码，只输出键前缀
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedisClient")
+            .field("key_prefix", &self.key_prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RedisClient {
+    /// 创建 Redis 客户端，只解析连接地址，不建立连接
+    pub fn new(config: &RedisConfig) -> Result<Self, AppError> {
+        let mut info = config
+            .url
+            .as_str()
+            .into_connection_info()
+            .map_err(|e| AppError::Config(format!("Invalid Redis URL: {}", e)))?;
+        if let Some(password) = &config.password {
+            info.redis.password = Some(password.clone());
+        }
+        let client = Client::open(info)
+            .map_err(|e| AppError::Config(format!("Failed to create Redis client: {}", e)))?;
+        Ok(Self {
+            client,
+            key_prefix: config.key_prefix.clone(),
+            connection: OnceCell::new(),
+            last_failure: Mutex::new(None),
+        })
+    }
+
+    /// 添加键前缀
+    pub fn key(&self, key: &str) -> String {
+        format!("{}{}", self.key_prefix, key)
+    }
+
+    /// 获取连接，尚未连接时建立连接
+    pub async fn connection(&self) -> Result<ConnectionManager, AppError> {
+        if let Some(connection) = self.connection.get() {
+            return Ok(connection.clone());
+        }
+        let retry_interval = Duration::from_secs(redis_limits::RETRY_INTERVAL);
+        if self
+            .last_failure
+            .lock()
+            .is_some_and(|failed| failed.elapsed() < retry_interval)
+        {
+            return Err(AppError::Redis("Redis is unavailable".to_string()));
+        }
+
+        let timeout = Duration::from_millis(redis_limits::TIMEOUT_MS);
+        let config = ConnectionManagerConfig::new()
+            .set_number_of_retries(1)
+            .set_connection_timeout(timeout)
+            .set_response_timeout(timeout);
+        let result = self
+            .connection
+            .get_or_try_init(|| ConnectionManager::new_with_config(self.client.clone(), config))
+            .await;
+        match result {
+            Ok(connection) => {
+                info!("Connected to Redis");
+                Ok(connection.clone())
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to connect to Redis, retry in {} seconds: {}",
+                    redis_limits::RETRY_INTERVAL,
+                    e
+                );
+                *self.last_failure.lock() = Some(Instant::now());
+                Err(AppError::Redis(format!(
+                    "Failed to connect to Redis: {}",
+                    e
+                )))
+            }
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use crate::{
+    config::{Config, ConfigFormat, ConfigSource, FileWatcher},
+    error::AppError,
+    events::{unix_millis, SystemEvent, EVENTS},
+    metrics::METRICS,
+    r#const::{reload_result_labels, reload_trigger_labels},
+    secret,
+    server::ForwardState,
+    upstream::UpstreamManager,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
+use tokio::sync::{Mutex, RwLock};
+use tokio_graceful_shutdown::SubsystemHandle;
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+
+// 配置重载状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct ReloadStatus {
+    // 配置文件路径或远程配置来源地址
+    pub path: String,
+    // 最近一次重载是否失败（失败时继续使用上一次有效的配置）
+    pub failed: bool,
+    // 最近一次重载失败的原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    // 最近一次尝试重载的时间（Unix 毫秒时间戳）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_attempt: Option<u64>,
+    // 最近一次成功重载的时间（Unix 毫秒时间戳）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_success: Option<u64>,
+    // 成功重载次数
+    pub successes: u64,
+    // 失败重载次数
+    pub failures: u64,
+}
+
+// 配置重载器
+// 重新读取配置并应用到运行中的服务，读取、校验或应用失败时保留上一次有效的配置
+// 热更新的内容：已有转发服务的路由规则、模型别名、上游服务的启用状态和已有上游组的上游列表；
+// 新增的转发服务和上游组、上游服务的地址、认证、请求头和超时等其他配置需要重启才能生效
+pub struct ConfigReloader {
+    // 配置来源
+    source: ConfigSource,
+    // 配置文件格式，未指定时按扩展名识别
+    format: Option<ConfigFormat>,
+    // 运行中的配置
+    config: Arc<RwLock<Config>>,
+    // 转发服务状态
+    forward_states: Arc<HashMap<String, Arc<ForwardState>>>,
+    // 重载状态
+    status: parking_lot::RwLock<ReloadStatus>,
+    // 保证同一时间只有一次重载
+    lock: Mutex<()>,
+}
+
+impl ConfigReloader {
+    // 创建配置重载器
+    pub fn new(
+        source: impl Into<ConfigSource>,
+        config: Arc<RwLock<Config>>,
+        forward_states: Arc<HashMap<String, Arc<ForwardState>>>,
+    ) -> Self {
+        let source = source.into();
+        let status = ReloadStatus {
+            path: source.to_string(),
+            ..Default::default()
+        };
+
+        Self {
+            source,
+            format: None,
+            config,
+            forward_states,
+            status: parking_lot::RwLock::new(status),
+            lock: Mutex::new(()),
+        }
+    }
+
+    // 指定配置文件格式，未指定时按扩展名识别
+    pub fn with_format(mut self, format: Option<ConfigFormat>) -> Self {
+        self.format = format;
+        self
+    }
+
+    // 获取配置来源
+    pub fn source(&self) -> &ConfigSource {
+        &self.source
+    }
+
+    // 获取当前重载状态
+    pub fn status(&self) -> ReloadStatus {
+        self.status.read().clone()
+    }
+
+    // 重新加载配置，失败时继续使用上一次有效的配置，trigger 为触发方式
+    pub async fn reload(&self, trigger: &str) -> Result<ReloadStatus, AppError> {
+        let _guard = self.lock.lock().await;
+        info!("Reloading configuration from {}", self.source);
+
+        let result = match self.source.load(self.format).await {
+            Ok(config) => self.apply(config).await,
+            Err(e) => Err(e),
+        };
+
+        let path = self.source.to_string();
+        let now = unix_millis();
+        let mut status = self.status.write();
+        status.last_attempt = Some(now);
+
+        match result {
+            Ok(()) => {
+                status.failed = false;
+                status.error = None;
+                status.last_success = Some(now);
+                status.successes += 1;
+
+                METRICS.config_reload_failed().set(0);
+                METRICS
+                    .config_reloads_total()
+                    .with_label_values(&[reload_result_labels::SUCCESS, trigger])
+                    .inc();
+                EVENTS.publish_system(SystemEvent::ConfigReloaded {
+                    timestamp: now,
+                    path,
+                });
+                info!("Configuration reloaded successfully from {}", self.source);
+
+                Ok(status.clone())
+            }
+            Err(e) => {
+                status.failed = true;
+                status.error = Some(e.to_string());
+                status.failures += 1;
+
+                METRICS.config_reload_failed().set(1);
+                METRICS
+                    .config_reloads_total()
+                    .with_label_values(&[reload_result_labels::FAILURE, trigger])
+                    .inc();
+                EVENTS.publish_system(SystemEvent::ConfigReloadFailed {
+                    timestamp: now,
+                    path,
+                    error: e.to_string(),
+                });
+                error!(
+                    "Failed to reload configuration, keeping last-known-good configuration: {}",
+                    e
+                );
+
+                Err(e)
+            }
+        }
+    }
+
+    // 将新配置应用到运行中的服务
+    // 先为所有转发服务构建路由表、为所有上游组创建上游列表，任一失败时返回错误，
+    // 运行中的服务和配置保持不变；全部成功后再替换
+    async fn apply(&self, config: Config) -> Result<(), AppError> {
+        let forwards = config
+            .http_server
+            .as_ref()
+            .map(|http_server| http_server.forwards.as_slice())
+            .unwrap_or_default();
+
+        // 构建路由表
+        let mut routes = Vec::with_capacity(forwards.len());
+        for forward in forwards {
+            match self.forward_states.get(&forward.name) {
+                Some(state) => routes.push((
+                    state,
+                    state
+                        .router
+                        .prepare_routes(forward.routing.as_deref().unwrap_or_default())?,
+                )),
+                None => warn!(
+                    "Forwarding service {:?} was added to the configuration, a restart is required to start it",
+                    forward.name
+                ),
+            }
+        }
+
+        // 创建上游组的上游列表，转发服务共享同一个上游管理器时只创建一次
+        let mut managers: Vec<&Arc<UpstreamManager>> = Vec::new();
+        for state in self.forward_states.values() {
+            if !managers
+                .iter()
+                .any(|manager| Arc::ptr_eq(manager, &state.upstream_manager))
+            {
+                managers.push(&state.upstream_manager);
+            }
+        }
+        let mut balancers = Vec::new();
+        for group in &config.upstream_groups {
+            for manager in &managers {
+                if !manager.has_group(&group.name) {
+                    warn!(
+                        "Upstream group {:?} was added to the configuration, a restart is required to use it",
+                        group.name
+                    );
+                    continue;
+                }
+                balancers.push((
+                    *manager,
+                    &group.name,
+                    manager.prepare_group_upstreams(&group.name, &group.upstreams)?,
+                ));
+            }
+        }
+
+        // 替换路由表
+        for (state, prepared) in routes {
+            state.router.commit_routes(prepared);
+        }
+
+        // 更新模型别名
+        for state in self.forward_states.values() {
+            state.models.replace(&config.models);
+        }
+
+        // 更新上游服务的启用状态
+        for upstream in &config.upstreams {
+            for manager in &managers {
+                manager.set_upstream_enabled(&upstream.name, upstream.enabled);
+            }
+        }
+
+        // 替换上游组的上游列表
+        for (manager, group_name, upstreams) in balancers {
+            manager
+                .commit_group_upstreams(group_name, upstreams)
+                .await?;
+        }
+
+        *self.config.write().await = config;
+        Ok(())
+    }
+
+    // 收到 SIGHUP 信号时重新加载配置，直到服务关闭
+    pub async fn run(self: Arc<Self>, subsys: SubsystemHandle) -> Result<(), AppError> {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+
+            let mut hangup = signal(SignalKind::hangup())
+                .map_err(|e| AppError::Internal(format!("Failed to listen for SIGHUP: {}", e)))?;
+            info!("Send SIGHUP to reload configuration from {}", self.source);
+
+            loop {
+                tokio::select! {
+                    _ = hangup.recv() => {
+                        // 失败已在 reload 中记录，服务继续使用上一次有效的配置
+                        let _ = self.reload(reload_trigger_labels::SIGNAL).await;
+                    }
+                    _ = subsys.on_shutdown_requested() => break,
+                }
+            }
+        }
+
+        #[cfg(not(unix))]
+        subsys.on_shutdown_requested().await;
+
+        Ok(())
+    }
+
+    // 配置发生变化时重新加载配置，直到服务关闭
+    // 远程配置来源监听其中的键；本地文件或目录监听其本身和配置引用的密钥文件，变化停止 debounce 后才重新加载
+    pub async fn watch(
+        self: Arc<Self>,
+        debounce: Duration,
+        subsys: SubsystemHandle,
+    ) -> Result<(), AppError> {
+        let mut files = match self.source {
+            ConfigSource::File(_) => Some(FileWatcher::new(debounce)?),
+            ConfigSource::Remote(_) => None,
+        };
+        info!("Watching configuration in {} for changes", self.source);
+
+        loop {
+            // 每次重载后更新监听的文件，新配置可能引用了不同的密钥文件
+            if let (Some(files), ConfigSource::File(path)) = (&mut files, &self.source) {
+                let mut paths = vec![path.clone()];
+                paths.extend(
+                    self.config
+                        .read()
+                        .await
+                        .secret_files()
+                        .into_iter()
+                        .map(PathBuf::from),
+                );
+                files.watch(&paths);
+            }
+
+            let changed = async {
+                match &mut files {
+                    Some(files) => files.changed().await,
+                    None => self.source.changed().await,
+                }
+            };
+            tokio::select! {
+                _ = changed => {
+                    info!("Configuration changed in {}", self.source);
+                    // 跳过缓存重新读取密钥文件，变化的可能是配置引用的密钥文件
+                    for path in self.config.read().await.secret_files() {
+                        let _ = secret::read_secret_file_with_interval(path, Duration::ZERO);
+                    }
+                    // 失败已在 reload 中记录，服务继续使用上一次有效的配置
+                    let _ = self.reload(reload_trigger_labels::WATCH).await;
+                }
+                _ = subsys.on_shutdown_requested() => break,
+            }
+        }
+
+        Ok(())
+    }
+}
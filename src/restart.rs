@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use thiserror::Error;
+use tokio_graceful_shutdown::SubsystemHandle;
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+
+use crate::{
+    error::AppError,
+    r#const::{restart, systemd as systemd_env},
+    systemd,
+};
+
+// 是否已启动新进程且新进程尚未接管
+static RESTARTING: AtomicBool = AtomicBool::new(false);
+
+/// 重启状态
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RestartStatus {
+    /// 新进程的进程 ID
+    pub pid: u32,
+}
+
+/// 重启错误
+#[derive(Debug, Error)]
+pub enum RestartError {
+    /// 已启动的新进程尚未接管
+    #[error("A restart is already in progress")]
+    InProgress,
+    /// 无法启动新进程
+    #[error(transparent)]
+    Failed(#[from] AppError),
+}
+
+/// 是否有正在进行的重启
+pub fn is_restarting() -> bool {
+    RESTARTING.load(Ordering::Acquire)
+}
+
+/// 使用相同的可执行文件和命令行参数启动新进程
+///
+/// 新进程借助 SO_REUSEPORT 绑定相同的监听地址，开始接受连接后向当前进程发送 SIGTERM，
+/// 当前进程随后停止接受新连接并等待在途请求完成。新进程在接管前退出时记录错误，当前进程继续服务。
+/// 仅支持 Linux
+pub fn spawn_replacement() -> Result<RestartStatus, RestartError> {
+    if !cfg!(target_os = "linux") {
+        return Err(AppError::Internal(
+            "Restart with socket handover is only supported on Linux".to_string(),
+        )
+        .into());
+    }
+    if RESTARTING.swap(true, Ordering::AcqRel) {
+        return Err(RestartError::InProgress);
+    }
+
+    spawn().map_err(|e| {
+        RESTARTING.store(false, Ordering::Release);
+        e.into()
+    })
+}
+
+// 启动新进程，并在后台等待其退出
+fn spawn() -> Result<RestartStatus, AppError> {
+    // 可执行文件被升级替换后，/proc/self/exe 指向的路径带有 " (deleted)" 后缀
+    let exe = std::env::current_exe()?;
+    let program = exe
+        .to_str()
+        .and_then(|path| path.strip_suffix(restart::DELETED_SUFFIX))
+        .map(Into::into)
+        .unwrap_or(exe);
+
+    let mut child = tokio::process::Command::new(&program)
+        .args(std::env::args_os().skip(1))
+        .env(restart::PARENT_ENV, std::process::id().to_string())
+        // 新进程成为主进程后自行发送看门狗心跳
+        .env_remove(systemd_env::WATCHDOG_PID)
+        .spawn()
+        .map_err(|e| {
+            AppError::Internal(format!("Failed to start new process {:?}: {}", program, e))
+        })?;
+    let pid = child.id().unwrap_or_default();
+    info!("Restarting, started new process {} from {:?}", pid, program);
+
+    // 新进程接管后当前进程会退出，等待只在新进程提前退出时返回
+    tokio::spawn(async move {
+        match child.wait().await {
+            Ok(status) => error!(
+                "New process {} exited before taking over ({}), keep serving",
+                pid, status
+            ),
+            Err(e) => error!("Failed to wait for new process {}: {}", pid, e),
+        }
+        RESTARTING.store(false, Ordering::Release);
+    });
+
+    Ok(RestartStatus { pid })
+}
+
+/// 由重启启动的新进程在绑定监听地址后调用，通知旧进程停止接受连接并退出
+///
+/// 不是由重启启动时不做任何事
+pub fn notify_parent() {
+    let Some(pid) = std::env::var(restart::PARENT_ENV)
+        .ok()
+        .and_then(|pid| pid.parse::<u32>().ok())
+    else {
+        return;
+    };
+
+    #[cfg(target_os = "linux")]
+    {
+        // 只通知启动本进程的旧进程，避免继承的环境变量误关闭其他进程
+        if std::os::unix::process::parent_id() != pid {
+            warn!(
+                "Process {} from {} is not the parent process, skip notifying",
+                pid,
+                restart::PARENT_ENV
+            );
+            return;
+        }
+
+        // 在 systemd 下先报告新的主进程，旧进程退出时服务不会被视为停止
+        systemd::notify(&format!("MAINPID={}", std::process::id()));
+        info!(
+            "Listeners ready, asking previous process {} to shut down",
+            pid
+        );
+        // SAFETY: kill 只向指定的进程发送信号，不访问内存
+        if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } != 0 {
+            warn!(
+                "Failed to notify previous process {}: {}",
+                pid,
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    warn!(
+        "Restart with socket handover is only supported on Linux, previous process {} is still running",
+        pid
+    );
+}
+
+/// 收到 SIGUSR2 信号时重启进程，直到服务关闭
+pub async fn run(subsys: SubsystemHandle) -> Result<(), AppError> {
+    #[cfg(target_os = "linux")]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut user2 = signal(SignalKind::user_defined2())
+            .map_err(|e| AppError::Internal(format!("Failed to listen for SIGUSR2: {}", e)))?;
+        info!("Send SIGUSR2 to restart without dropping connections");
+
+        loop {
+            tokio::select! {
+                _ = user2.recv() => {
+                    if let Err(e) = spawn_replacement() {
+                        warn!("Failed to restart: {}", e);
+                    }
+                }
+                _ = subsys.on_shutdown_requested() => break,
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    subsys.on_shutdown_requested().await;
+
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use crate::{args::Args, r#const::runtime_limits};
+use std::{fmt, io, str::FromStr};
+use tokio::runtime::{Builder, Runtime};
+
+/// CPU 编号列表，格式为逗号分隔的编号或范围，如 `0-3,6`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpuList(Vec<usize>);
+
+impl CpuList {
+    /// 升序排列且不重复的 CPU 编号
+    pub fn cpus(&self) -> &[usize] {
+        &self.0
+    }
+}
+
+impl FromStr for CpuList {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let parse = |cpu: &str| {
+            cpu.trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|cpu| *cpu <= runtime_limits::MAX_CPU)
+                .ok_or_else(|| {
+                    format!(
+                        "Invalid CPU '{}' in '{}', expected a number between 0 and {}",
+                        cpu,
+                        value,
+                        runtime_limits::MAX_CPU
+                    )
+                })
+        };
+
+        let mut cpus = Vec::new();
+        for part in value.split(',') {
+            match part.split_once('-') {
+                Some((start, end)) => {
+                    let (start, end) = (parse(start)?, parse(end)?);
+                    if start > end {
+                        return Err(format!("Invalid CPU range '{}' in '{}'", part, value));
+                    }
+                    cpus.extend(start..=end);
+                }
+                None => cpus.push(parse(part)?),
+            }
+        }
+        cpus.sort_unstable();
+        cpus.dedup();
+        Ok(Self(cpus))
+    }
+}
+
+impl fmt::Display for CpuList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cpus: Vec<String> = self.0.iter().map(|cpu| cpu.to_string()).collect();
+        f.write_str(&cpus.join(","))
+    }
+}
+
+/// 按命令行参数创建多线程 Tokio 运行时
+///
+/// 指定了 CPU 绑定时先绑定当前线程，运行时创建的线程继承该绑定，未指定工作线程数时
+/// 工作线程数与绑定的 CPU 数相同。
+pub fn build(args: &Args) -> io::Result<Runtime> {
+    let mut builder = Builder::new_multi_thread();
+    builder.enable_all();
+
+    if let Some(cpu_affinity) = &args.cpu_affinity {
+        pin_current_thread(cpu_affinity.cpus())?;
+        builder.worker_threads(cpu_affinity.cpus().len());
+    }
+    if let Some(worker_threads) = args.worker_threads {
+        builder.worker_threads(worker_threads);
+    }
+    if let Some(max_blocking_threads) = args.max_blocking_threads {
+        builder.max_blocking_threads(max_blocking_threads);
+    }
+
+    builder.build()
+}
+
+// 将当前线程绑定到指定的 CPU，之后创建的线程继承该绑定
+#[cfg(target_os = "linux")]
+fn pin_current_thread(cpus: &[usize]) -> io::Result<()> {
+    // SAFETY: cpu_set_t 是普通的位图结构，全零即空集合；CPU 编号不超过集合容量
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        for cpu in cpus {
+            libc::CPU_SET(*cpu, &mut set);
+        }
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            let error = io::Error::last_os_error();
+            return Err(io::Error::new(
+                error.kind(),
+                format!("Failed to set CPU affinity: {}", error),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// 其他平台不支持 CPU 绑定，参数验证时已拒绝
+#[cfg(not(target_os = "linux"))]
+fn pin_current_thread(_cpus: &[usize]) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "CPU affinity is only supported on Linux",
+    ))
+}
@@ -0,0 +1,71 @@
+use crate::{error::AppError, r#const::secret_file::REFRESH_INTERVAL};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use std::{
+    fs,
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+// 已读取的密钥文件内容
+struct CachedSecret {
+    // 密钥内容
+    value: String,
+    // 最近一次读取的时间
+    loaded_at: Instant,
+}
+
+// 密钥文件缓存，按文件路径索引
+static SECRET_FILES: Lazy<DashMap<String, CachedSecret>> = Lazy::new(DashMap::new);
+
+// 读取密钥文件，缓存内容超过刷新间隔后重新读取
+pub fn read_secret_file(path: &str) -> Result<String, AppError> {
+    read_secret_file_with_interval(path, Duration::from_secs(REFRESH_INTERVAL))
+}
+
+// 读取密钥文件，缓存内容超过指定间隔后重新读取
+// 重新读取失败时继续使用上一次读取的内容，避免密钥轮换过程中的短暂缺失导致请求失败
+pub fn read_secret_file_with_interval(path: &str, interval: Duration) -> Result<String, AppError> {
+    if let Some(cached) = SECRET_FILES.get(path) {
+        if cached.loaded_at.elapsed() < interval {
+            return Ok(cached.value.clone());
+        }
+    }
+
+    match load(path) {
+        Ok(value) => {
+            let previous = SECRET_FILES.insert(
+                path.to_string(),
+                CachedSecret {
+                    value: value.clone(),
+                    loaded_at: Instant::now(),
+                },
+            );
+            if previous.is_some_and(|previous| previous.value != value) {
+                info!("Secret file {:?} changed, using the new secret", path);
+            }
+            Ok(value)
+        }
+        Err(e) => match SECRET_FILES.get_mut(path) {
+            Some(mut cached) => {
+                warn!("{}, keeping the previously loaded secret", e);
+                cached.loaded_at = Instant::now();
+                Ok(cached.value.clone())
+            }
+            None => Err(e),
+        },
+    }
+}
+
+// 读取文件内容，去除首尾空白（如挂载文件末尾的换行符）
+fn load(path: &str) -> Result<String, AppError> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| AppError::Config(format!("Unable to read secret file {:?}: {}", path, e)))?;
+
+    let value = content.trim();
+    if value.is_empty() {
+        return Err(AppError::Config(format!("Secret file {:?} is empty", path)));
+    }
+
+    Ok(value.to_string())
+}
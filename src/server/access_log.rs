@@ -0,0 +1,431 @@
+use axum::{
+    body::{Body, Bytes},
+    extract::{ConnectInfo, Request, State},
+    http::header::{REFERER, USER_AGENT},
+    middleware::Next,
+    response::Response,
+};
+use hyper::body::{Body as HttpBody, Frame, SizeHint};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{self, Write},
+    net::SocketAddr,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::{
+    args::LogRotation,
+    config::{AccessLogConfig, AccessLogFormat},
+    error::AppError,
+    events::unix_millis,
+    logfile::RotatingFile,
+    metrics::METRICS,
+    r#const::{access_log, http_headers},
+    upstream::ServedBy,
+    usage::civil_date,
+};
+
+// combined 格式中的月份缩写
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// 访问日志记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessRecord {
+    /// 请求完成时间（Unix 毫秒时间戳）
+    pub timestamp: u64,
+    /// 转发服务名称
+    pub forward: String,
+    /// 请求 ID，请求在分配请求 ID 之前被拒绝时取自客户端的 x-request-id 头部
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// 客户端 IP
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_ip: Option<String>,
+    /// 客户端标识（转发服务配置了客户端预算时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// 请求方法
+    pub method: String,
+    /// 请求路径
+    pub path: String,
+    /// HTTP 版本
+    pub protocol: String,
+    /// 匹配的路由规则路径，使用默认组时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route: Option<String>,
+    /// 目标上游组，请求在路由之前被拒绝时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// 处理请求的上游，请求未转发给上游时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upstream: Option<String>,
+    /// 响应状态码
+    pub status: u16,
+    /// 发送给客户端的响应体字节数（压缩后）
+    pub bytes: u64,
+    /// 请求耗时（毫秒），包括发送响应体的时间
+    pub duration_ms: u64,
+    /// 首字节耗时（毫秒），从收到请求到发送响应体的第一个数据块
+    pub ttfb_ms: u64,
+    /// Referer 请求头
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub referer: Option<String>,
+    /// User-Agent 请求头
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+}
+
+impl AccessRecord {
+    /// 格式化为 combined 格式，末尾追加请求 ID、路由、上游组、上游和耗时字段
+    pub fn to_combined(&self) -> String {
+        let field = |value: &Option<String>| match value {
+            Some(value) => value.replace('"', "\\\""),
+            None => access_log::EMPTY_FIELD.to_string(),
+        };
+        let bytes = match self.bytes {
+            0 => access_log::EMPTY_FIELD.to_string(),
+            bytes => bytes.to_string(),
+        };
+        format!(
+            "{} - {} [{}] \"{} {} {}\" {} {} \"{}\" \"{}\" request_id=\"{}\" route=\"{}\" group=\"{}\" upstream=\"{}\" duration_ms={} ttfb_ms={}",
+            self.client_ip.as_deref().unwrap_or(access_log::EMPTY_FIELD),
+            self.client_id.as_deref().unwrap_or(access_log::EMPTY_FIELD),
+            format_time(self.timestamp),
+            self.method,
+            self.path,
+            self.protocol,
+            self.status,
+            bytes,
+            field(&self.referer),
+            field(&self.user_agent),
+            field(&self.request_id),
+            field(&self.route),
+            field(&self.group),
+            field(&self.upstream),
+            self.duration_ms,
+            self.ttfb_ms,
+        )
+    }
+}
+
+// 将 Unix 毫秒时间戳格式化为 combined 格式的时间，如 10/Oct/2000:13:55:36 +0000（UTC）
+fn format_time(timestamp: u64) -> String {
+    let seconds = timestamp / 1000;
+    let (year, month, day) = civil_date(seconds / 86_400);
+    let time = seconds % 86_400;
+    format!(
+        "{:02}/{}/{}:{:02}:{:02}:{:02} +0000",
+        day,
+        MONTHS[month as usize - 1],
+        year,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+// 转发处理函数填写的字段
+#[derive(Debug, Default)]
+struct AccessFields {
+    // 请求 ID
+    request_id: Option<String>,
+    // 客户端标识
+    client_id: Option<String>,
+    // 匹配的路由规则路径
+    route: Option<String>,
+    // 目标上游组
+    group: Option<String>,
+}
+
+/// 由转发处理函数填写的访问日志字段
+///
+/// 访问日志中间件将其放入请求扩展，转发处理函数确定请求 ID、路由和目标上游组后写入
+#[derive(Debug, Clone, Default)]
+pub struct AccessInfo(Arc<Mutex<AccessFields>>);
+
+impl AccessInfo {
+    /// 记录请求 ID 和客户端标识
+    pub fn set_request(&self, request_id: &str, client_id: Option<&str>) {
+        let mut fields = self.0.lock();
+        fields.request_id = Some(request_id.to_string());
+        fields.client_id = client_id.map(str::to_string);
+    }
+
+    /// 记录匹配的路由规则路径和目标上游组
+    pub fn set_route(&self, route: Option<&str>, group: &str) {
+        let mut fields = self.0.lock();
+        fields.route = route.map(str::to_string);
+        fields.group = Some(group.to_string());
+    }
+
+    /// 更新目标上游组（如模型别名指定了其他上游组）
+    pub fn set_group(&self, group: &str) {
+        self.0.lock().group = Some(group.to_string());
+    }
+}
+
+// 记录写入的目的地
+enum Destination {
+    // 标准输出
+    Stdout,
+    // 按大小轮转的文件
+    File(RotatingFile),
+}
+
+impl Destination {
+    // 写入一批记录
+    fn write(&mut self, lines: &[u8]) -> io::Result<()> {
+        match self {
+            Destination::Stdout => {
+                let mut stdout = io::stdout().lock();
+                stdout.write_all(lines)?;
+                stdout.flush()
+            }
+            Destination::File(file) => file.write(lines),
+        }
+    }
+}
+
+/// 转发服务的访问日志
+///
+/// 每个请求在响应体发送完成（或客户端断开连接）时生成一行记录，由后台线程按批次写入标准输出或文件。
+/// 缓冲已满或写入失败时丢弃记录并计入指标，不影响请求
+pub struct AccessLog {
+    // 转发服务名称
+    forward: String,
+    // 日志格式
+    format: AccessLogFormat,
+    // 等待写入的记录
+    sender: mpsc::Sender<String>,
+}
+
+impl AccessLog {
+    /// 按访问日志配置创建访问日志并启动后台写入线程
+    pub fn new(forward: &str, config: &AccessLogConfig) -> Result<Self, AppError> {
+        let destination = match &config.file {
+            Some(path) => {
+                let file = RotatingFile::open(
+                    path,
+                    config.max_size * 1024 * 1024,
+                    config.max_files,
+                    LogRotation::Never,
+                )
+                .map_err(|e| {
+                    AppError::Config(format!("Failed to open access log file {:?}: {}", path, e))
+                })?;
+                info!(
+                    "Access log of forwarding service {:?} is written to {:?}",
+                    forward, path
+                );
+                Destination::File(file)
+            }
+            None => Destination::Stdout,
+        };
+
+        let (sender, receiver) = mpsc::channel(config.buffer);
+        let name = forward.to_string();
+        std::thread::Builder::new()
+            .name("access-log".to_string())
+            .spawn(move || run_writer(name, receiver, destination))
+            .map_err(|e| AppError::Config(format!("Failed to start access log writer: {}", e)))?;
+        Ok(Self {
+            forward: forward.to_string(),
+            format: config.format,
+            sender,
+        })
+    }
+
+    // 按日志格式生成一行记录
+    fn format(&self, record: &AccessRecord) -> String {
+        let mut line = match self.format {
+            AccessLogFormat::Json => serde_json::to_string(record).unwrap_or_default(),
+            AccessLogFormat::Combined => record.to_combined(),
+        };
+        line.push('\n');
+        line
+    }
+}
+
+/// 访问日志中间件，响应体发送完成或被丢弃时记录请求
+pub async fn record_access(
+    State(log): State<Arc<AccessLog>>,
+    mut request: Request<Body>,
+    next: Next,
+) -> Response {
+    let start_time = Instant::now();
+    let info = AccessInfo::default();
+    request.extensions_mut().insert(info.clone());
+
+    let headers = request.headers();
+    let header = |name| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let record = AccessRecord {
+        timestamp: 0,
+        forward: log.forward.clone(),
+        request_id: header(http_headers::REQUEST_ID).filter(|id| !id.is_empty()),
+        client_ip: request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip().to_string()),
+        client_id: None,
+        method: request.method().to_string(),
+        path: request.uri().path().to_string(),
+        protocol: format!("{:?}", request.version()),
+        route: None,
+        group: None,
+        upstream: None,
+        status: 0,
+        bytes: 0,
+        duration_ms: 0,
+        ttfb_ms: 0,
+        referer: header(REFERER.as_str()),
+        user_agent: header(USER_AGENT.as_str()),
+    };
+
+    let response = next.run(request).await;
+    let mut pending = PendingRecord {
+        record: Some(record),
+        info,
+        log,
+        start_time,
+        headers_sent: start_time.elapsed(),
+        first_byte: None,
+        bytes: 0,
+    };
+    if let Some(record) = &mut pending.record {
+        record.status = response.status().as_u16();
+        record.upstream = response
+            .extensions()
+            .get::<ServedBy>()
+            .map(|ServedBy(upstream)| upstream.clone());
+    }
+    response.map(|body| {
+        Body::new(AccessBody {
+            inner: body,
+            pending,
+        })
+    })
+}
+
+// 待完成的访问日志记录，释放时写入
+struct PendingRecord {
+    // 待完成的记录
+    record: Option<AccessRecord>,
+    // 转发处理函数填写的字段
+    info: AccessInfo,
+    // 访问日志
+    log: Arc<AccessLog>,
+    // 收到请求的时间
+    start_time: Instant,
+    // 返回响应头的耗时
+    headers_sent: Duration,
+    // 发送第一个数据块的耗时
+    first_byte: Option<Duration>,
+    // 已发送的响应体字节数
+    bytes: u64,
+}
+
+impl Drop for PendingRecord {
+    fn drop(&mut self) {
+        let Some(mut record) = self.record.take() else {
+            return;
+        };
+        let fields = std::mem::take(&mut *self.info.0.lock());
+        if fields.request_id.is_some() {
+            record.request_id = fields.request_id;
+        }
+        record.client_id = fields.client_id;
+        record.route = fields.route;
+        record.group = fields.group;
+        record.timestamp = unix_millis();
+        record.bytes = self.bytes;
+        record.duration_ms = self.start_time.elapsed().as_millis() as u64;
+        record.ttfb_ms = self.first_byte.unwrap_or(self.headers_sent).as_millis() as u64;
+        if self.log.sender.try_send(self.log.format(&record)).is_err() {
+            METRICS
+                .access_log_records_total()
+                .with_label_values(&[&record.forward, access_log::DROPPED])
+                .inc();
+        }
+    }
+}
+
+// 统计发送给客户端的响应体，保留原响应体的大小提示
+struct AccessBody {
+    // 原响应体
+    inner: Body,
+    // 待完成的记录
+    pending: PendingRecord,
+}
+
+impl HttpBody for AccessBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = &mut *self;
+        let poll = Pin::new(&mut this.inner).poll_frame(cx);
+        if let Poll::Ready(Some(Ok(frame))) = &poll {
+            if let Some(data) = frame.data_ref() {
+                let pending = &mut this.pending;
+                pending
+                    .first_byte
+                    .get_or_insert_with(|| pending.start_time.elapsed());
+                pending.bytes += data.len() as u64;
+            }
+        }
+        poll
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+// 后台写入线程，每次写入已缓冲的一批记录，访问日志释放后退出
+fn run_writer(forward: String, mut receiver: mpsc::Receiver<String>, mut destination: Destination) {
+    while let Some(line) = receiver.blocking_recv() {
+        let mut lines = line;
+        let mut count = 1;
+        while count < access_log::BATCH_SIZE {
+            let Ok(line) = receiver.try_recv() else {
+                break;
+            };
+            lines.push_str(&line);
+            count += 1;
+        }
+        let result = match destination.write(lines.as_bytes()) {
+            Ok(()) => access_log::WRITTEN,
+            Err(e) => {
+                warn!(
+                    "Failed to write {} access log records of forwarding service {:?}: {}",
+                    count, forward, e
+                );
+                access_log::DROPPED
+            }
+        };
+        METRICS
+            .access_log_records_total()
+            .with_label_values(&[&forward, result])
+            .inc_by(count as u64);
+    }
+}
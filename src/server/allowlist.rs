@@ -0,0 +1,137 @@
+use axum::{
+    http::{header, HeaderValue, Method, StatusCode},
+    response::{IntoResponse, Response},
+};
+use regex::RegexSet;
+
+use crate::{config::ForwardConfig, error::AppError};
+
+/// 请求不在转发服务的白名单中
+#[derive(Debug, Clone)]
+pub enum RequestNotAllowed {
+    /// 请求路径不在允许的路径中，返回 404
+    Path,
+    /// 请求方法不在允许的方法中，返回 405 和 Allow 响应头
+    Method(HeaderValue),
+}
+
+impl RequestNotAllowed {
+    /// 拒绝请求的状态码
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::Path => StatusCode::NOT_FOUND,
+            Self::Method(_) => StatusCode::METHOD_NOT_ALLOWED,
+        }
+    }
+}
+
+impl IntoResponse for RequestNotAllowed {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Path => StatusCode::NOT_FOUND.into_response(),
+            Self::Method(allow) => {
+                (StatusCode::METHOD_NOT_ALLOWED, [(header::ALLOW, allow)]).into_response()
+            }
+        }
+    }
+}
+
+/// 请求方法和路径白名单
+///
+/// 在读取请求体和选择上游之前检查请求，先检查路径再检查方法
+#[derive(Debug)]
+pub struct RequestAllowlist {
+    // 允许的请求方法，为空时允许所有方法
+    methods: Vec<Method>,
+    // 允许的方法列表，用作 405 响应的 Allow 响应头
+    allow: HeaderValue,
+    // 允许的路径模式，未配置时允许所有路径
+    paths: Option<RegexSet>,
+}
+
+impl RequestAllowlist {
+    /// 根据转发服务配置创建白名单，没有配置允许的方法和路径时返回 None
+    pub fn new(config: &ForwardConfig) -> Result<Option<Self>, AppError> {
+        if config.allowed_methods.is_empty() && config.allowed_paths.is_empty() {
+            return Ok(None);
+        }
+
+        let methods = config
+            .allowed_methods
+            .iter()
+            .map(|method| {
+                Method::from_bytes(method.to_ascii_uppercase().as_bytes()).map_err(|_| {
+                    AppError::Config(format!(
+                        "Invalid allowed method '{}' in forward '{}'",
+                        method, config.name
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let allow = methods
+            .iter()
+            .map(Method::as_str)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let allow = HeaderValue::from_str(&allow).map_err(|e| {
+            AppError::Config(format!(
+                "Invalid allowed methods in forward '{}': {}",
+                config.name, e
+            ))
+        })?;
+
+        let paths = if config.allowed_paths.is_empty() {
+            None
+        } else {
+            let patterns = config.allowed_paths.iter().map(|path| glob_to_regex(path));
+            Some(RegexSet::new(patterns).map_err(|e| {
+                AppError::Config(format!(
+                    "Invalid allowed paths in forward '{}': {}",
+                    config.name, e
+                ))
+            })?)
+        };
+
+        Ok(Some(Self {
+            methods,
+            allow,
+            paths,
+        }))
+    }
+
+    /// 检查请求方法和路径是否在白名单中
+    pub fn check(&self, method: &Method, path: &str) -> Result<(), RequestNotAllowed> {
+        if self
+            .paths
+            .as_ref()
+            .is_some_and(|paths| !paths.is_match(path))
+        {
+            return Err(RequestNotAllowed::Path);
+        }
+        if !self.methods.is_empty() && !self.methods.contains(method) {
+            return Err(RequestNotAllowed::Method(self.allow.clone()));
+        }
+        Ok(())
+    }
+}
+
+// 将路径 glob 模式转换为完整匹配的正则表达式
+// ** 匹配任意字符，* 匹配除 / 外的任意字符，? 匹配除 / 外的单个字符
+fn glob_to_regex(pattern: &str) -> String {
+    let mut regex = String::with_capacity(pattern.len() * 2 + 2);
+    regex.push('^');
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                regex.push_str(".*");
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0u8; 4]))),
+        }
+    }
+    regex.push('$');
+    regex
+}
@@ -0,0 +1,376 @@
+use axum::{
+    body::Body,
+    http::{header::CONTENT_TYPE, HeaderMap, Method},
+    response::Response,
+};
+use bytes::Bytes;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    fs::OpenOptions,
+    net::IpAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{fs::File, io::AsyncWriteExt, sync::mpsc};
+use tracing::{info, warn};
+
+use crate::{
+    config::AuditSinkConfig,
+    error::AppError,
+    events::unix_millis,
+    metrics::METRICS,
+    r#const::{audit_sink, http_headers::content_types},
+    redact::redact_fields,
+};
+
+/// 请求审计记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// 请求完成时间（Unix 毫秒时间戳）
+    pub timestamp: u64,
+    /// 转发服务名称
+    pub forward: String,
+    /// 请求 ID
+    pub request_id: String,
+    /// 客户端 IP
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_ip: Option<String>,
+    /// 请求方法
+    pub method: String,
+    /// 请求路径
+    pub path: String,
+    /// 目标上游组
+    pub group: String,
+    /// 响应状态码
+    pub status: u16,
+    /// 请求耗时（毫秒），流式响应包括发送响应体的时间
+    pub duration_ms: u64,
+    /// 请求体
+    pub request: AuditBody,
+    /// 响应体
+    pub response: AuditBody,
+}
+
+/// 记录的请求体或响应体
+///
+/// JSON 按原样记录并脱敏，事件流记录为各事件数据组成的数组，其他内容记录为文本。
+/// 超过记录上限时截断为文本，配置了脱敏字段时无法脱敏，不记录内容
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuditBody {
+    /// 内容，没有请求体或响应体时为 null
+    pub body: Value,
+    /// 是否超过记录上限被截断
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+/// 待完成的审计记录，由转发处理函数填写请求信息
+#[derive(Debug)]
+pub struct AuditRequest {
+    /// 请求开始时间
+    pub start_time: Instant,
+    /// 请求 ID
+    pub request_id: String,
+    /// 客户端 IP
+    pub client_ip: Option<IpAddr>,
+    /// 请求方法
+    pub method: Method,
+    /// 请求路径
+    pub path: String,
+    /// 目标上游组
+    pub group: String,
+    /// 请求体
+    pub body: Option<Bytes>,
+}
+
+// 记录写入的目的地
+enum Destination {
+    // JSONL 文件
+    File(File),
+    // HTTP 接收端
+    Http {
+        client: reqwest::Client,
+        url: String,
+        token: Option<String>,
+    },
+}
+
+impl Destination {
+    // 写入一批 JSONL 记录
+    async fn write(&mut self, lines: Vec<u8>) -> Result<(), String> {
+        match self {
+            Destination::File(file) => {
+                file.write_all(&lines).await.map_err(|e| e.to_string())?;
+                file.flush().await.map_err(|e| e.to_string())
+            }
+            Destination::Http { client, url, token } => {
+                let mut request = client
+                    .post(url.as_str())
+                    .header(CONTENT_TYPE, audit_sink::CONTENT_TYPE)
+                    .body(lines);
+                if let Some(token) = token {
+                    request = request.bearer_auth(token);
+                }
+                let response = request.send().await.map_err(|e| e.to_string())?;
+                if !response.status().is_success() {
+                    return Err(format!("HTTP status {}", response.status()));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// 记录内容的格式和脱敏设置，各请求共享
+#[derive(Debug)]
+struct BodyPolicy {
+    // 需要脱敏的字段名
+    redact_fields: Vec<String>,
+    // 记录的最大请求体或响应体（字节）
+    max_body_bytes: usize,
+}
+
+impl BodyPolicy {
+    // 按记录上限截取内容
+    fn capture(&self, data: &[u8]) -> (Vec<u8>, bool) {
+        let len = data.len().min(self.max_body_bytes);
+        (data[..len].to_vec(), data.len() > len)
+    }
+
+    // 转换为记录的内容，event_stream 表示内容为事件流
+    fn body(&self, data: &[u8], truncated: bool, event_stream: bool) -> AuditBody {
+        let body = if truncated {
+            if self.redact_fields.is_empty() {
+                Value::String(String::from_utf8_lossy(data).into_owned())
+            } else {
+                Value::Null
+            }
+        } else if data.is_empty() {
+            Value::Null
+        } else if event_stream {
+            let text = String::from_utf8_lossy(data);
+            let events = text
+                .lines()
+                .filter_map(|line| line.strip_prefix("data:"))
+                .filter_map(|data| serde_json::from_str::<Value>(data.trim()).ok())
+                .map(|mut event| {
+                    redact_fields(&mut event, &self.redact_fields);
+                    event
+                })
+                .collect();
+            Value::Array(events)
+        } else {
+            match serde_json::from_slice::<Value>(data) {
+                Ok(mut value) => {
+                    redact_fields(&mut value, &self.redact_fields);
+                    value
+                }
+                Err(_) => Value::String(String::from_utf8_lossy(data).into_owned()),
+            }
+        };
+        AuditBody { body, truncated }
+    }
+}
+
+/// 转发服务的请求审计日志
+///
+/// 记录在响应体发送完成（或客户端断开连接）时生成，由后台任务按批次写入文件或 HTTP 接收端。
+/// 缓冲已满或写入失败时丢弃记录并计入指标，不影响请求
+pub struct AuditSink {
+    // 转发服务名称
+    forward: String,
+    // 等待写入的记录
+    sender: mpsc::Sender<AuditRecord>,
+    // 记录内容的格式和脱敏设置
+    policy: Arc<BodyPolicy>,
+}
+
+impl AuditSink {
+    /// 按审计日志配置创建审计日志并启动后台写入任务，必须在 tokio 运行时中调用
+    pub fn new(forward: &str, config: &AuditSinkConfig) -> Result<Self, AppError> {
+        let destination = match (&config.file, &config.url) {
+            (Some(path), _) => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| {
+                        AppError::Config(format!(
+                            "Failed to open audit sink file {:?}: {}",
+                            path, e
+                        ))
+                    })?;
+                info!(
+                    "Audit log of forwarding service {:?} is written to {:?}",
+                    forward, path
+                );
+                Destination::File(File::from_std(file))
+            }
+            (None, Some(url)) => {
+                let client = reqwest::Client::builder()
+                    .timeout(Duration::from_secs(audit_sink::HTTP_TIMEOUT))
+                    .build()
+                    .map_err(|e| {
+                        AppError::Config(format!("Failed to create audit sink client: {}", e))
+                    })?;
+                info!(
+                    "Audit log of forwarding service {:?} is sent to {}",
+                    forward, url
+                );
+                Destination::Http {
+                    client,
+                    url: url.clone(),
+                    token: config.token.clone(),
+                }
+            }
+            (None, None) => {
+                return Err(AppError::Config(
+                    "Audit sink requires exactly one of file or url".to_string(),
+                ))
+            }
+        };
+
+        let (sender, receiver) = mpsc::channel(config.buffer);
+        tokio::spawn(run_writer(forward.to_string(), receiver, destination));
+        Ok(Self {
+            forward: forward.to_string(),
+            sender,
+            policy: Arc::new(BodyPolicy {
+                redact_fields: config.redact_fields.clone(),
+                max_body_bytes: config.max_body_bytes,
+            }),
+        })
+    }
+
+    /// 记录请求和响应，响应体发送完成或被丢弃时写入记录
+    pub fn record(&self, request: AuditRequest, response: Response) -> Response {
+        let (request_body, request_truncated) = match &request.body {
+            Some(body) => self.policy.capture(body),
+            None => (Vec::new(), false),
+        };
+        let mut capture = ResponseCapture {
+            record: Some(AuditRecord {
+                timestamp: 0,
+                forward: self.forward.clone(),
+                request_id: request.request_id,
+                client_ip: request.client_ip.map(|ip| ip.to_string()),
+                method: request.method.to_string(),
+                path: request.path,
+                group: request.group,
+                status: response.status().as_u16(),
+                duration_ms: 0,
+                request: self.policy.body(&request_body, request_truncated, false),
+                response: AuditBody::default(),
+            }),
+            start_time: request.start_time,
+            event_stream: is_event_stream(response.headers()),
+            body: Vec::new(),
+            truncated: false,
+            policy: self.policy.clone(),
+            sender: self.sender.clone(),
+        };
+        response.map(|body| {
+            Body::from_stream(body.into_data_stream().map(move |chunk| {
+                if let Ok(data) = &chunk {
+                    capture.push(data);
+                }
+                chunk
+            }))
+        })
+    }
+}
+
+// 响应体是否为事件流
+fn is_event_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with(content_types::EVENT_STREAM))
+}
+
+// 记录发送给客户端的响应体，释放时生成审计记录
+struct ResponseCapture {
+    // 待完成的审计记录
+    record: Option<AuditRecord>,
+    // 请求开始时间
+    start_time: Instant,
+    // 响应体是否为事件流
+    event_stream: bool,
+    // 已记录的响应体
+    body: Vec<u8>,
+    // 响应体是否超过记录上限
+    truncated: bool,
+    // 记录内容的格式和脱敏设置
+    policy: Arc<BodyPolicy>,
+    // 等待写入的记录
+    sender: mpsc::Sender<AuditRecord>,
+}
+
+impl ResponseCapture {
+    // 追加响应体数据块，超过记录上限的部分丢弃
+    fn push(&mut self, data: &Bytes) {
+        let remaining = self.policy.max_body_bytes - self.body.len();
+        if data.len() > remaining {
+            self.truncated = true;
+        }
+        self.body
+            .extend_from_slice(&data[..data.len().min(remaining)]);
+    }
+}
+
+impl Drop for ResponseCapture {
+    fn drop(&mut self) {
+        let Some(mut record) = self.record.take() else {
+            return;
+        };
+        record.timestamp = unix_millis();
+        record.duration_ms = self.start_time.elapsed().as_millis() as u64;
+        record.response = self
+            .policy
+            .body(&self.body, self.truncated, self.event_stream);
+        if let Err(e) = self.sender.try_send(record) {
+            let record = match e {
+                mpsc::error::TrySendError::Full(record) => record,
+                mpsc::error::TrySendError::Closed(record) => record,
+            };
+            METRICS
+                .audit_records_total()
+                .with_label_values(&[&record.forward, audit_sink::DROPPED])
+                .inc();
+        }
+    }
+}
+
+// 后台写入任务，每次写入已缓冲的一批记录，所有审计日志释放后退出
+async fn run_writer(
+    forward: String,
+    mut receiver: mpsc::Receiver<AuditRecord>,
+    mut destination: Destination,
+) {
+    let mut batch = Vec::with_capacity(audit_sink::BATCH_SIZE);
+    while receiver.recv_many(&mut batch, audit_sink::BATCH_SIZE).await > 0 {
+        let mut lines = Vec::new();
+        for record in batch.drain(..) {
+            if serde_json::to_writer(&mut lines, &record).is_ok() {
+                lines.push(b'\n');
+            }
+        }
+        let count = lines.iter().filter(|byte| **byte == b'\n').count() as u64;
+        let result = match destination.write(lines).await {
+            Ok(()) => audit_sink::WRITTEN,
+            Err(e) => {
+                warn!(
+                    "Failed to write {} audit records of forwarding service {:?}: {}",
+                    count, forward, e
+                );
+                audit_sink::DROPPED
+            }
+        };
+        METRICS
+            .audit_records_total()
+            .with_label_values(&[&forward, result])
+            .inc_by(count);
+    }
+}
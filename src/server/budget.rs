@@ -0,0 +1,85 @@
+use crate::{
+    config::BudgetConfig,
+    r#const::{api::auth::BEARER_PREFIX, budget},
+    usage::{seconds_until_reset, USAGE},
+};
+use axum::{
+    http::{header::RETRY_AFTER, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use std::net::IpAddr;
+
+// 客户端预算已用尽
+#[derive(Debug, Clone)]
+pub struct BudgetExceeded {
+    // 预算周期：daily 或 monthly
+    pub period: &'static str,
+    // 预算（美元）
+    pub limit: f64,
+    // 当前周期内的费用（美元）
+    pub spent: f64,
+    // 距离预算重置的秒数
+    pub retry_after: u64,
+}
+
+impl IntoResponse for BudgetExceeded {
+    // 返回 OpenAI 格式的 429 错误，Retry-After 为预算重置前的秒数
+    fn into_response(self) -> Response {
+        let body = json!({"error": {
+            "message": format!(
+                "The {} budget of ${:.2} has been exceeded (spent ${:.2}), retry after the budget resets in {} seconds",
+                self.period, self.limit, self.spent, self.retry_after
+            ),
+            "type": budget::ERROR_TYPE,
+            "param": null,
+            "code": budget::ERROR_CODE,
+        }});
+        let mut response = (StatusCode::TOO_MANY_REQUESTS, Json(body)).into_response();
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, HeaderValue::from(self.retry_after));
+        response
+    }
+}
+
+// 从请求头中读取客户端标识，Bearer 令牌去除前缀
+// 未携带请求头时与限流一样回退为客户端 IP，避免省略请求头绕过预算，两者都缺失时返回 None
+pub fn client_id(config: &BudgetConfig, headers: &HeaderMap, ip: Option<IpAddr>) -> Option<String> {
+    headers
+        .get(config.header.as_str())
+        .and_then(|value| value.to_str().ok())
+        .map(|value| {
+            let value = value.trim();
+            value.strip_prefix(BEARER_PREFIX).unwrap_or(value).trim()
+        })
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .or_else(|| ip.map(|ip| ip.to_string()))
+}
+
+// 检查客户端在当日和当月的费用是否已达到预算
+pub fn check_budget(
+    config: &BudgetConfig,
+    forward: &str,
+    client: &str,
+) -> Result<(), BudgetExceeded> {
+    let (daily, monthly) = config.limits(client);
+    let spend = USAGE.client_spend(forward, client);
+    let (day_reset, month_reset) = seconds_until_reset();
+    for (period, limit, spent, retry_after) in [
+        (budget::MONTHLY, monthly, spend.monthly, month_reset),
+        (budget::DAILY, daily, spend.daily, day_reset),
+    ] {
+        if let Some(limit) = limit.filter(|limit| spent >= *limit) {
+            return Err(BudgetExceeded {
+                period,
+                limit,
+                spent,
+                retry_after,
+            });
+        }
+    }
+    Ok(())
+}
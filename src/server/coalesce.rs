@@ -0,0 +1,186 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
+    response::{IntoResponse, Response},
+};
+use bytes::Bytes;
+use parking_lot::Mutex;
+use serde_json::Value;
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::watch;
+use tracing::warn;
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::r#const::http_headers;
+
+// 携带客户端凭证的请求头，凭证不同的请求不会合并
+const CREDENTIAL_HEADERS: [&str; 3] = ["authorization", "x-api-key", "api-key"];
+
+/// 合并请求和响应缓存的键，上游组、请求方法、路径、客户端凭证和请求体都相同的请求才会合并或命中缓存
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CoalesceKey {
+    group: String,
+    method: Method,
+    path: String,
+    credentials: Vec<Option<HeaderValue>>,
+    body: Bytes,
+}
+
+impl CoalesceKey {
+    /// 创建合并请求的键，流式请求（请求体中 stream 为 true）不合并
+    pub fn new(
+        group: &str,
+        method: &Method,
+        path: &str,
+        headers: &HeaderMap,
+        body: Option<&Bytes>,
+    ) -> Option<Self> {
+        let body = body.cloned().unwrap_or_default();
+        if !body.is_empty()
+            && serde_json::from_slice::<Value>(&body)
+                .is_ok_and(|data| data["stream"].as_bool() == Some(true))
+        {
+            return None;
+        }
+        Some(Self {
+            group: group.to_string(),
+            method: method.clone(),
+            path: path.to_string(),
+            credentials: CREDENTIAL_HEADERS
+                .iter()
+                .map(|name| headers.get(HeaderName::from_static(name)).cloned())
+                .collect(),
+            body,
+        })
+    }
+
+    /// 键的摘要，用作响应缓存的键，不同实例计算出的摘要相同
+    pub fn digest(&self) -> String {
+        let mut hasher = Xxh3::new();
+        // 每个字段前写入长度，避免字段边界不同的键得到相同的摘要
+        let mut write = |data: &[u8]| {
+            hasher.update(&(data.len() as u64).to_le_bytes());
+            hasher.update(data);
+        };
+        write(self.group.as_bytes());
+        write(self.method.as_str().as_bytes());
+        write(self.path.as_bytes());
+        for credential in &self.credentials {
+            write(credential.as_ref().map_or(&[][..], HeaderValue::as_bytes));
+        }
+        write(&self.body);
+        format!("{:032x}", hasher.digest128())
+    }
+}
+
+// 共享给所有等待请求的响应
+#[derive(Debug)]
+struct SharedResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl SharedResponse {
+    // 构建等待请求的响应，添加合并请求响应头
+    fn to_response(&self) -> Response {
+        let mut response = Response::new(Body::from(self.body.clone()));
+        *response.status_mut() = self.status;
+        *response.headers_mut() = self.headers.clone();
+        response
+            .headers_mut()
+            .insert(http_headers::COALESCED, HeaderValue::from_static("true"));
+        response
+    }
+}
+
+// 在途请求的响应，转发完成前为 None
+type Pending = watch::Receiver<Option<Arc<SharedResponse>>>;
+
+/// 在途请求合并器
+///
+/// 相同的请求并发到达时只有第一个请求转发给上游，其他请求等待该请求完成后共享其响应。
+/// 转发请求被取消或返回流式响应时，等待的请求各自转发
+#[derive(Default)]
+pub struct RequestCoalescer {
+    // 合并请求的键到在途请求响应的映射
+    inflight: Mutex<HashMap<CoalesceKey, Pending>>,
+}
+
+/// 加入在途请求的结果
+pub enum Coalesced<'a> {
+    /// 没有相同的在途请求，由该请求转发并共享响应
+    Leader(CoalesceLeader<'a>),
+    /// 已有相同的在途请求，等待其响应
+    Follower(CoalesceFollower),
+}
+
+impl RequestCoalescer {
+    /// 加入相同的在途请求，没有时登记为在途请求
+    pub fn join(&self, key: CoalesceKey) -> Coalesced<'_> {
+        let mut inflight = self.inflight.lock();
+        if let Some(pending) = inflight.get(&key) {
+            return Coalesced::Follower(CoalesceFollower {
+                pending: pending.clone(),
+            });
+        }
+        let (sender, pending) = watch::channel(None);
+        inflight.insert(key.clone(), pending);
+        Coalesced::Leader(CoalesceLeader {
+            coalescer: self,
+            key,
+            sender,
+        })
+    }
+}
+
+/// 负责转发的请求，释放时从在途请求中移除
+pub struct CoalesceLeader<'a> {
+    coalescer: &'a RequestCoalescer,
+    key: CoalesceKey,
+    sender: watch::Sender<Option<Arc<SharedResponse>>>,
+}
+
+impl CoalesceLeader<'_> {
+    /// 将响应共享给等待的请求，以流式转发的响应不共享
+    pub async fn share(self, response: Response) -> Response {
+        if super::utils::is_unbuffered_response(&response) {
+            return response;
+        }
+
+        // 非流式响应已读取完整响应体
+        let (parts, body) = response.into_parts();
+        let body = match to_bytes(body, usize::MAX).await {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("Failed to read response body for coalesced requests: {}", e);
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+        self.sender.send_replace(Some(Arc::new(SharedResponse {
+            status: parts.status,
+            headers: parts.headers.clone(),
+            body: body.clone(),
+        })));
+        Response::from_parts(parts, Body::from(body))
+    }
+}
+
+impl Drop for CoalesceLeader<'_> {
+    fn drop(&mut self) {
+        self.coalescer.inflight.lock().remove(&self.key);
+    }
+}
+
+/// 等待相同在途请求的请求
+pub struct CoalesceFollower {
+    pending: Pending,
+}
+
+impl CoalesceFollower {
+    /// 等待在途请求的响应，在途请求没有共享响应时返回 None
+    pub async fn wait(mut self) -> Option<Response> {
+        let shared = self.pending.wait_for(Option::is_some).await.ok()?;
+        shared.as_deref().map(SharedResponse::to_response)
+    }
+}
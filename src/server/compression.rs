@@ -0,0 +1,25 @@
+use axum::http::{Extensions, HeaderMap, StatusCode, Version};
+use tower_http::compression::{
+    predicate::{Predicate, SizeAbove},
+    CompressionLayer,
+};
+
+use crate::config::{CompressionAlgorithm, CompressionConfig};
+
+use super::utils::is_streaming_response;
+
+/// 按响应压缩配置创建压缩中间件
+///
+/// 只压缩不小于 min_size 的非流式响应，事件流和分块传输的响应原样转发。
+/// 上游已压缩（带有 Content-Encoding）的响应不会被再次压缩
+pub fn compression_layer(config: &CompressionConfig) -> CompressionLayer<impl Predicate> {
+    let enabled = |algorithm| config.algorithms.contains(&algorithm);
+    CompressionLayer::new()
+        .gzip(enabled(CompressionAlgorithm::Gzip))
+        .br(enabled(CompressionAlgorithm::Br))
+        .compress_when(SizeAbove::new(config.min_size).and(
+            |status: StatusCode, _: Version, headers: &HeaderMap, _: &Extensions| {
+                status != StatusCode::SWITCHING_PROTOCOLS && !is_streaming_response(headers)
+            },
+        ))
+}
@@ -0,0 +1,298 @@
+use super::ratelimit::{ClientKey, ClientKeyExtractor};
+use crate::{config::QueueConfig, r#const::concurrency_limits};
+use axum::{
+    http::{header::RETRY_AFTER, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use parking_lot::Mutex;
+use serde_json::json;
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+    net::IpAddr,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::oneshot;
+
+/// 并发请求数超出限制
+#[derive(Debug, Clone, Copy)]
+pub enum ConcurrencyRejected {
+    /// 等待队列已满（或未配置等待队列），返回 429
+    QueueFull {
+        /// 建议的重试间隔（秒）
+        retry_after: u64,
+    },
+    /// 排队等待超时，返回 503
+    Timeout {
+        /// 建议的重试间隔（秒）
+        retry_after: u64,
+    },
+}
+
+impl IntoResponse for ConcurrencyRejected {
+    // 返回 OpenAI 格式的错误和 Retry-After 头部
+    fn into_response(self) -> Response {
+        let (status, code, message, retry_after) = match self {
+            Self::QueueFull { retry_after } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                concurrency_limits::QUEUE_FULL_CODE,
+                "Too many concurrent requests, please retry later",
+                retry_after,
+            ),
+            Self::Timeout { retry_after } => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                concurrency_limits::QUEUE_TIMEOUT_CODE,
+                "Timed out waiting for a free request slot, please retry later",
+                retry_after,
+            ),
+        };
+        let body = json!({"error": {
+            "message": message,
+            "type": concurrency_limits::ERROR_TYPE,
+            "param": null,
+            "code": code,
+        }});
+        let mut response = (status, Json(body)).into_response();
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, HeaderValue::from(retry_after));
+        response
+    }
+}
+
+/// 转发服务的并发请求限制和等待队列
+///
+/// 排队的请求按起始虚拟时间调度（Start-time Fair Queuing）：每个请求的起始虚拟时间为当前虚拟时间
+/// 与该客户端上一个请求的结束虚拟时间中的较大者，结束虚拟时间再推进单位时间除以权重。
+/// 未配置公平调度时所有请求属于同一客户端，即按到达顺序调度
+pub struct ConcurrencyLimiter {
+    inner: Arc<Inner>,
+}
+
+// 并发限制器的共享状态，许可释放时需要访问
+struct Inner {
+    // 等待队列配置，未配置时超出并发上限的请求直接拒绝
+    queue: Option<QueueConfig>,
+    // 公平调度的客户端键提取器，未配置公平调度时为 None
+    extractor: Option<ClientKeyExtractor>,
+    // 调度状态
+    state: Mutex<Scheduler>,
+}
+
+// 调度状态
+#[derive(Default)]
+struct Scheduler {
+    // 空闲的许可数
+    available: usize,
+    // 正在排队的请求数（不包括已离开但尚未出队的请求）
+    waiting: usize,
+    // 排队的请求，起始虚拟时间最小的在堆顶
+    queue: BinaryHeap<Waiter>,
+    // 当前虚拟时间，即最近一个出队请求的起始虚拟时间
+    virtual_time: u64,
+    // 每个客户端最后一个请求的结束虚拟时间
+    finish: HashMap<Option<ClientKey>, u64>,
+    // 到达序号，起始虚拟时间相同时按到达顺序调度
+    sequence: u64,
+}
+
+// 排队的请求
+struct Waiter {
+    // 起始虚拟时间
+    start: u64,
+    // 到达序号
+    sequence: u64,
+    // 获得许可时通知排队的请求
+    sender: oneshot::Sender<()>,
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Waiter {
+    // BinaryHeap 为最大堆，反转比较使起始虚拟时间最小、到达最早的请求在堆顶
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.start, other.sequence).cmp(&(self.start, self.sequence))
+    }
+}
+
+impl Scheduler {
+    // 释放一个许可：交给下一个仍在等待的请求，没有等待的请求时归还
+    fn release(&mut self) {
+        while let Some(waiter) = self.queue.pop() {
+            self.virtual_time = waiter.start;
+            // 发送失败说明请求已超时或断开连接，跳过
+            if waiter.sender.send(()).is_ok() {
+                return;
+            }
+        }
+        self.available += 1;
+    }
+
+    // 计算请求的起始虚拟时间，并推进客户端的结束虚拟时间
+    fn start_time(&mut self, client: Option<ClientKey>, weight: u32) -> u64 {
+        // 客户端过多时清理已空闲的客户端，它们的结束虚拟时间不会再影响调度
+        if self.finish.len() >= concurrency_limits::MAX_FAIR_CLIENTS {
+            let virtual_time = self.virtual_time;
+            self.finish.retain(|_, finish| *finish > virtual_time);
+        }
+        let finish = self.finish.entry(client).or_default();
+        let start = (*finish).max(self.virtual_time);
+        *finish = start + concurrency_limits::VIRTUAL_TIME_UNIT / u64::from(weight.max(1));
+        start
+    }
+}
+
+/// 并发请求许可，释放时交给下一个排队的请求
+pub struct ConcurrencyPermit {
+    inner: Arc<Inner>,
+}
+
+impl Drop for ConcurrencyPermit {
+    fn drop(&mut self) {
+        self.inner.state.lock().release();
+    }
+}
+
+// 排队中的请求，请求离开队列（包括超时和客户端断开连接）时减少排队计数，
+// 已获得但未使用的许可交给下一个请求
+struct Queued<'a> {
+    inner: &'a Inner,
+    receiver: Option<oneshot::Receiver<()>>,
+}
+
+impl Queued<'_> {
+    // 等待许可，超时返回 false
+    async fn wait(&mut self, max_wait: Duration) -> bool {
+        let Some(receiver) = self.receiver.as_mut() else {
+            return false;
+        };
+        if let Ok(Ok(())) = tokio::time::timeout(max_wait, receiver).await {
+            self.receiver = None;
+            self.inner.state.lock().waiting -= 1;
+            return true;
+        }
+        // 超时的同时可能已获得许可
+        self.settle()
+    }
+
+    // 离开队列，返回是否已获得许可
+    fn settle(&mut self) -> bool {
+        let Some(mut receiver) = self.receiver.take() else {
+            return false;
+        };
+        // 持有调度状态锁时检查并丢弃接收端，之后释放的许可不会再交给该请求
+        let mut state = self.inner.state.lock();
+        state.waiting -= 1;
+        let granted = receiver.try_recv().is_ok();
+        drop(receiver);
+        granted
+    }
+}
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        if self.settle() {
+            self.inner.state.lock().release();
+        }
+    }
+}
+
+impl ConcurrencyLimiter {
+    /// 根据最大并发请求数和等待队列配置创建并发限制器
+    pub fn new(max_concurrent: u32, queue: Option<&QueueConfig>) -> Self {
+        let extractor = queue
+            .and_then(|queue| queue.fair.as_ref())
+            .map(|fair| ClientKeyExtractor::new(fair.key, fair.header.as_deref()));
+        Self {
+            inner: Arc::new(Inner {
+                queue: queue.cloned(),
+                extractor,
+                state: Mutex::new(Scheduler {
+                    available: max_concurrent as usize,
+                    ..Default::default()
+                }),
+            }),
+        }
+    }
+
+    /// 获取请求许可，许可在请求（包括流式响应）结束时释放
+    ///
+    /// 没有空闲许可时进入等待队列，队列已满返回 QueueFull，等待超时返回 Timeout
+    pub async fn acquire(
+        &self,
+        headers: &HeaderMap,
+        client_ip: Option<IpAddr>,
+    ) -> Result<ConcurrencyPermit, ConcurrencyRejected> {
+        let (queue, receiver) = {
+            let mut state = self.inner.state.lock();
+            if state.available > 0 {
+                state.available -= 1;
+                return Ok(ConcurrencyPermit {
+                    inner: self.inner.clone(),
+                });
+            }
+
+            let Some(queue) = &self.inner.queue else {
+                return Err(ConcurrencyRejected::QueueFull { retry_after: 1 });
+            };
+            if state.waiting >= queue.max_depth as usize {
+                return Err(ConcurrencyRejected::QueueFull {
+                    retry_after: queue.max_wait_ms.div_ceil(1000),
+                });
+            }
+
+            let (client, weight) = match (&self.inner.extractor, &queue.fair) {
+                (Some(extractor), Some(fair)) => {
+                    let id = match extractor.value(headers) {
+                        Some(value) => Some(value.to_string()),
+                        None => client_ip.map(|ip| ip.to_string()),
+                    };
+                    (
+                        extractor.key(headers, client_ip),
+                        fair.weight(id.as_deref()),
+                    )
+                }
+                _ => (None, concurrency_limits::DEFAULT_WEIGHT),
+            };
+            let start = state.start_time(client, weight);
+            let (sender, receiver) = oneshot::channel();
+            state.sequence += 1;
+            let sequence = state.sequence;
+            state.queue.push(Waiter {
+                start,
+                sequence,
+                sender,
+            });
+            state.waiting += 1;
+            (queue, receiver)
+        };
+
+        let mut queued = Queued {
+            inner: &self.inner,
+            receiver: Some(receiver),
+        };
+        if queued.wait(Duration::from_millis(queue.max_wait_ms)).await {
+            Ok(ConcurrencyPermit {
+                inner: self.inner.clone(),
+            })
+        } else {
+            Err(ConcurrencyRejected::Timeout {
+                retry_after: queue.max_wait_ms.div_ceil(1000),
+            })
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{
+    config::{ErrorResponseConfig, ErrorResponseFormat},
+    error::AppError,
+    r#const::proxy_errors,
+};
+use axum::{
+    http::{Method, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use circuitbreaker_rs::BreakerError;
+use serde_json::json;
+
+/// 代理自身的错误，请求没有得到上游的响应
+#[derive(Debug, Clone)]
+pub struct ProxyError {
+    /// 错误码
+    pub code: &'static str,
+    /// 错误消息
+    pub message: String,
+    /// 请求 ID
+    pub request_id: String,
+    /// 客户端重试是否安全
+    pub retryable: bool,
+}
+
+impl ProxyError {
+    /// 根据转发请求的错误创建代理错误
+    ///
+    /// 请求没有发送到上游时重试总是安全的；上游请求失败时只有幂等方法的请求可以安全重试，
+    /// 非幂等请求可能已被上游处理。details 为 false 时使用通用的错误消息，不暴露上游地址等内部信息
+    pub fn new(error: &AppError, method: &Method, request_id: &str, details: bool) -> Self {
+        let (code, message) = match error {
+            AppError::CircuitBreakerOpen(_) | AppError::CircuitBreakerError(BreakerError::Open) => {
+                (
+                    proxy_errors::CIRCUIT_OPEN,
+                    "Upstream circuit breaker is open",
+                )
+            }
+            AppError::NoUpstreamAvailable | AppError::NoHealthyUpstreamAvailable => (
+                proxy_errors::NO_UPSTREAM,
+                "No upstream is available to handle the request",
+            ),
+            AppError::Upstream(_)
+            | AppError::HttpError(_)
+            | AppError::HttpMiddlewareError(_)
+            | AppError::CircuitBreakerError(_) => (
+                proxy_errors::UPSTREAM_ERROR,
+                "Failed to get a response from the upstream service",
+            ),
+            AppError::UpstreamGroupNotFound(_) | AppError::Config(_) | AppError::Routing(_) => (
+                proxy_errors::CONFIG_ERROR,
+                "The proxy is not configured to handle this request",
+            ),
+            _ => (proxy_errors::INTERNAL_ERROR, "Internal proxy error"),
+        };
+        let retryable = match code {
+            proxy_errors::CIRCUIT_OPEN | proxy_errors::NO_UPSTREAM => true,
+            proxy_errors::UPSTREAM_ERROR => method.is_idempotent(),
+            _ => false,
+        };
+        Self {
+            code,
+            message: if details {
+                error.to_string()
+            } else {
+                message.to_string()
+            },
+            request_id: request_id.to_string(),
+            retryable,
+        }
+    }
+
+    /// 按配置的格式生成 500 响应，未配置时只返回状态码
+    pub fn into_response(self, config: Option<&ErrorResponseConfig>) -> Response {
+        let Some(config) = config else {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        };
+        let body = match config.format {
+            ErrorResponseFormat::Openai => json!({"error": {
+                "message": self.message,
+                "type": proxy_errors::ERROR_TYPE,
+                "param": null,
+                "code": self.code,
+                "request_id": self.request_id,
+                "retryable": self.retryable,
+            }}),
+            ErrorResponseFormat::Json => json!({
+                "code": self.code,
+                "message": self.message,
+                "request_id": self.request_id,
+                "retryable": self.retryable,
+            }),
+        };
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(body)).into_response()
+    }
+}
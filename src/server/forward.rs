@@ -1,10 +1,39 @@
-use crate::{config::ForwardConfig, error::AppError, upstream::UpstreamManager};
-use std::{net::SocketAddr, sync::Arc};
+use crate::{
+    config::{ForwardConfig, ModelAlias},
+    error::AppError,
+    events::{unix_millis, SystemEvent, EVENTS},
+    metrics::METRICS,
+    upstream::UpstreamManager,
+};
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::net::TcpListener;
 use tokio_graceful_shutdown::{IntoSubsystem, SubsystemHandle};
-use tracing::{error, info};
+use tracing::info;
 
 use super::{
+    access_log::AccessLog,
+    allowlist::RequestAllowlist,
+    audit_sink::AuditSink,
+    coalesce::RequestCoalescer,
+    concurrency::ConcurrencyLimiter,
+    listener,
+    models::ModelCatalog,
+    pii::PiiRedactor,
+    plugin::PluginChain,
+    policy::PolicyClient,
+    probe::probe_router,
+    ratelimit::DistributedRateLimiter,
+    request_headers::RequestHeaderFilter,
+    response_cache::ResponseCache,
     router::Router,
+    token_limit::TokenLimiter,
     utils::{apply_middlewares, build_router, create_tcp_listener},
 };
 
@@ -16,6 +45,60 @@ pub struct ForwardState {
     pub config: ForwardConfig,
     // 路由器
     pub router: Router,
+    // 模型别名目录
+    pub models: ModelCatalog,
+    // 分布式请求速率限制器，未配置限流或限流未配置 Redis 时为 None
+    pub ratelimiter: Option<Arc<DistributedRateLimiter>>,
+    // 令牌速率限制器，未配置令牌速率限制时为 None
+    pub token_limiter: Option<TokenLimiter>,
+    // 并发限制器，未配置最大并发请求数时为 None
+    pub concurrency: Option<ConcurrencyLimiter>,
+    // 在途请求合并器，未开启请求合并时为 None
+    pub coalescer: Option<RequestCoalescer>,
+    // 响应缓存，未配置响应缓存时为 None
+    pub cache: Option<ResponseCache>,
+    // 请求审计日志，未配置审计日志时为 None
+    pub audit_sink: Option<AuditSink>,
+    // PII 脱敏器，未配置 PII 脱敏时为 None
+    pub pii: Option<PiiRedactor>,
+    // 外部策略服务客户端，未配置策略服务时为 None
+    pub policy: Option<PolicyClient>,
+    // WASM 插件链，未配置插件时为 None
+    pub plugins: Option<PluginChain>,
+    // 访问日志，未配置访问日志时为 None
+    pub access_log: Option<Arc<AccessLog>>,
+    // 请求方法和路径白名单，未配置允许的方法和路径时为 None
+    pub allowlist: Option<RequestAllowlist>,
+    // 入站请求头过滤器，未配置入站请求头时为 None
+    pub header_filter: Option<RequestHeaderFilter>,
+    // 是否已禁用，禁用时所有请求返回 503
+    disabled: AtomicBool,
+}
+
+impl ForwardState {
+    // 转发服务是否接收请求
+    #[inline(always)]
+    pub fn is_enabled(&self) -> bool {
+        !self.disabled.load(Ordering::Relaxed)
+    }
+
+    // 启用或禁用转发服务，返回状态是否发生变化
+    pub fn set_enabled(&self, enabled: bool) -> bool {
+        let changed = self.disabled.swap(!enabled, Ordering::Relaxed) == enabled;
+        if changed {
+            EVENTS.publish_system(SystemEvent::ForwardStateChanged {
+                timestamp: unix_millis(),
+                forward: self.config.name.clone(),
+                enabled,
+            });
+            info!(
+                "Forwarding service {:?} is now {}",
+                self.config.name,
+                if enabled { "enabled" } else { "disabled" }
+            );
+        }
+        changed
+    }
 }
 
 // 转发服务
@@ -24,6 +107,8 @@ pub struct ForwardServer {
     addr: SocketAddr,
     // 服务状态
     state: Arc<ForwardState>,
+    // 启动前绑定的监听器，为空时在启动时绑定
+    listener: Option<TcpListener>,
 }
 
 impl ForwardServer {
@@ -31,6 +116,7 @@ impl ForwardServer {
     pub fn new(
         config: ForwardConfig,
         upstream_manager: Arc<UpstreamManager>,
+        models: &[ModelAlias],
     ) -> Result<Self, AppError> {
         // 解析监听地址
         let addr = format!("{}:{}", config.address, config.port)
@@ -40,13 +126,94 @@ impl ForwardServer {
         // 创建路由器(转发路由，不是 axum 的路由)
         let router = Router::new(&config)?;
 
+        // 限流配置了 Redis 时创建分布式请求速率限制器
+        let ratelimiter = config
+            .ratelimit
+            .as_ref()
+            .and_then(|ratelimit| Some((ratelimit, ratelimit.redis.as_ref()?)))
+            .map(|(ratelimit, redis)| DistributedRateLimiter::new(&config.name, ratelimit, redis))
+            .transpose()?
+            .map(Arc::new);
+        // 创建令牌速率限制器
+        let token_limiter = config
+            .token_limit
+            .as_ref()
+            .map(|token_limit| TokenLimiter::new(&config.name, token_limit))
+            .transpose()?;
+        // 创建并发限制器
+        let concurrency = config
+            .max_concurrent
+            .map(|max_concurrent| ConcurrencyLimiter::new(max_concurrent, config.queue.as_ref()));
+        // 创建在途请求合并器
+        let coalescer = config.coalesce.then(RequestCoalescer::default);
+        // 创建响应缓存
+        let cache = config.cache.as_ref().map(ResponseCache::new).transpose()?;
+        // 创建请求审计日志
+        let audit_sink = config
+            .audit_sink
+            .as_ref()
+            .map(|sink| AuditSink::new(&config.name, sink))
+            .transpose()?;
+        // 创建 PII 脱敏器
+        let pii = config
+            .pii_redaction
+            .as_ref()
+            .map(PiiRedactor::new)
+            .transpose()?;
+        // 创建外部策略服务客户端
+        let policy = config
+            .policy
+            .as_ref()
+            .map(|policy| PolicyClient::new(&config.name, policy))
+            .transpose()?;
+        // 加载 WASM 插件
+        let plugins = if config.plugins.is_empty() {
+            None
+        } else {
+            Some(PluginChain::new(&config.name, &config.plugins)?)
+        };
+        // 创建访问日志
+        let access_log = config
+            .access_log
+            .as_ref()
+            .map(|log| AccessLog::new(&config.name, log))
+            .transpose()?
+            .map(Arc::new);
+
+        // 创建请求方法和路径白名单
+        let allowlist = RequestAllowlist::new(&config)?;
+        // 创建入站请求头过滤器
+        let header_filter = config
+            .request_headers
+            .as_ref()
+            .map(RequestHeaderFilter::new)
+            .transpose()?;
+
         let state = Arc::new(ForwardState {
             upstream_manager,
             config,
             router,
+            models: ModelCatalog::new(models),
+            ratelimiter,
+            token_limiter,
+            concurrency,
+            coalescer,
+            cache,
+            audit_sink,
+            pii,
+            policy,
+            plugins,
+            access_log,
+            allowlist,
+            header_filter,
+            disabled: AtomicBool::new(false),
         });
 
-        Ok(Self { addr, state })
+        Ok(Self {
+            addr,
+            state,
+            listener: None,
+        })
     }
 
     // 获取服务器监听地址
@@ -60,6 +227,16 @@ impl ForwardServer {
     pub fn get_state(&self) -> &Arc<ForwardState> {
         &self.state
     }
+
+    // 在启动前绑定监听地址，重启时新进程绑定全部监听地址后才通知旧进程退出
+    pub fn listen(&mut self) -> Result<(), AppError> {
+        let listener_config = self.state.config.listener.clone().unwrap_or_default();
+        self.listener = Some(create_tcp_listener(
+            self.addr,
+            listener_config.backlog as i32,
+        )?);
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
@@ -68,31 +245,48 @@ impl IntoSubsystem<AppError> for ForwardServer {
         // 创建路由
         let app = build_router(self.state.clone());
 
-        // 应用中间件
-        let app = apply_middlewares(app, &self.state);
+        // 应用中间件，探针路由在中间件之后添加，不受限流、超时和访问日志影响
+        let app = apply_middlewares(app, &self.state).merge(probe_router(self.state.clone()));
 
-        // 创建 TCP 监听器
-        let listener = create_tcp_listener(self.addr, u16::MAX.into())?;
+        // 创建 TCP 监听器，已在启动前绑定时直接使用
+        let listener_config = self.state.config.listener.clone().unwrap_or_default();
+        let listener = match self.listener {
+            Some(listener) => listener,
+            None => create_tcp_listener(self.addr, listener_config.backlog as i32)?,
+        };
 
         info!(
-            "Forwarding service {:?} listening on {:?}",
-            self.state.config.name, self.addr
+            "Forwarding service {:?} listening on {:?}{}",
+            self.state.config.name,
+            self.addr,
+            if listener_config.http2 {
+                " (HTTP/1.1 and HTTP/2)"
+            } else {
+                ""
+            }
         );
 
-        // 使用tokio::select!监听服务器和关闭信号
-        tokio::select! {
-            result = axum::serve(listener, app) => {
-                if let Err(e) = result {
-                    error!("Forwarding service error: {}", e);
-                } else {
-                    info!("Forwarding service completed normally");
-                }
-                Ok(())
-            }
-            _ = subsys.on_shutdown_requested() => {
-                info!("Shutdown requested, stopping forwarding service");
-                Ok(())
-            }
-        }
+        // 请求中记录客户端地址，用于展开上游请求头中的 ${client_ip}
+        // 收到关闭信号后停止接受新连接，等待在途请求（包括流式响应）完成，超过关闭超时时间后强制结束
+        let name = &self.state.config.name;
+        let shutdown = async {
+            subsys.on_shutdown_requested().await;
+            info!(
+                "Shutdown requested, draining forwarding service {:?} ({} in-flight requests)",
+                name,
+                METRICS.inflight_requests().with_label_values(&[name]).get()
+            );
+        };
+        let timeout = self.state.config.timeout.clone().unwrap_or_default();
+        listener::serve(
+            listener,
+            app,
+            &listener_config,
+            Duration::from_secs(timeout.connect),
+            shutdown,
+        )
+        .await;
+        info!("Forwarding service {:?} stopped", name);
+        Ok(())
     }
 }
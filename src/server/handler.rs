@@ -1,30 +1,61 @@
 use axum::{
     body::Body,
-    extract::{Path, Request, State},
-    http::{HeaderMap, Method, StatusCode},
+    extract::{ConnectInfo, Path, Request, State},
+    http::{
+        header::{CONTENT_LENGTH, TRANSFER_ENCODING},
+        HeaderMap, HeaderValue, Method, StatusCode,
+    },
     response::{IntoResponse, Response},
 };
+use bytes::Bytes;
+use circuitbreaker_rs::BreakerError;
+use futures_util::StreamExt;
+use std::future::Future;
+use std::net::SocketAddr;
 use std::sync::Arc;
-use std::time::Instant;
-use tracing::{debug, info};
+use std::time::{Duration, Instant};
+use tracing::{debug, field, info, info_span, Instrument, Span};
+use uuid::Uuid;
 
-use crate::{error::AppError, metrics::METRICS, r#const::error_labels};
+use crate::{
+    breaker::{UpstreamCircuitBreaker, UpstreamError},
+    config::MiddlewareStage,
+    error::AppError,
+    events::{AccessEvent, EVENTS},
+    metrics::METRICS,
+    r#const::{breaker_result_labels, cache_limits, error_labels, http_headers, route_breaker},
+    upstream::{RequestContext, ServedBy},
+};
 
 use super::{
+    access_log::AccessInfo,
+    audit_sink::AuditRequest,
+    budget::{check_budget, client_id},
+    coalesce::{CoalesceKey, Coalesced},
+    concurrency::{ConcurrencyPermit, ConcurrencyRejected},
+    error_response::ProxyError,
     forward::ForwardState,
-    utils::{extract_request_body, normalize_path},
+    heartbeat::with_heartbeat,
+    limits::{check_prompt_tokens, enforce_limits, LimitExceeded},
+    plugin::{PluginContext, PluginRejected, PluginStage},
+    shedding::SHEDDER,
+    tokens::count_prompt_tokens_async,
+    utils::{extract_request_body, normalize_path, UnbufferedBody},
+    websocket::{is_upgrade_request, proxy_websocket},
 };
 
 /// 处理上游响应并转换为适合客户端的响应
 ///
-/// 根据响应类型（流式/非流式）处理不同的响应策略
-async fn handle_response(
+/// 根据响应类型（流式/非流式）处理不同的响应策略，Content-Length 超过 buffer_limit 的非流式响应
+/// 同样以流式转发，避免在内存中缓冲大的响应体
+pub(super) async fn handle_response(
     response: reqwest::Response,
     start_time: Instant,
     config_name: &str,
     method: &Method,
     path: &str,
     default_group: &str,
+    buffer_limit: u64,
 ) -> Response {
     // 获取响应状态码和头
     let status = response.status();
@@ -53,9 +84,17 @@ async fn handle_response(
 
     // 检查是否为流式响应
     let is_stream = super::utils::is_streaming_response(&headers);
+    // 响应体超过缓冲上限的非流式响应直接以流式转发
+    let unbuffered = !is_stream
+        && response
+            .content_length()
+            .is_some_and(|length| length > buffer_limit);
 
-    // 创建响应构建器
+    // 创建响应构建器，保留处理请求的上游，用于访问日志
     let mut axum_response = Response::builder().status(status);
+    if let Some(served_by) = response.extensions().get::<ServedBy>() {
+        axum_response = axum_response.extension(served_by.clone());
+    }
 
     // 复制响应头
     if let Some(headers_mut) = axum_response.headers_mut() {
@@ -63,12 +102,20 @@ async fn handle_response(
     }
 
     // 根据响应类型处理
-    let result = if is_stream {
+    let result = if is_stream || unbuffered {
         // 对于流式响应，直接转发流
-        tracing::debug!("Handling streaming response");
+        if unbuffered {
+            tracing::debug!(
+                "Response body exceeds the buffer limit of {} bytes, streaming it",
+                buffer_limit
+            );
+            axum_response = axum_response.extension(UnbufferedBody);
+        } else {
+            tracing::debug!("Handling streaming response");
+        }
 
-        // 将 reqwest 响应流转换为 axum 流
-        let stream = response.bytes_stream();
+        // 将 reqwest 响应流转换为 axum 流，客户端断开连接时随响应流一起中止上游请求
+        let stream = watch_disconnect(response, config_name, status);
         // 使用 Body::from_stream 直接传递流，避免额外的内存复制
         let body = Body::from_stream(stream);
         match axum_response.body(body) {
@@ -104,19 +151,101 @@ async fn handle_response(
         method, path, default_group, status, duration_ms
     );
 
+    // 发布访问事件
+    if EVENTS.has_access_subscribers() {
+        EVENTS.publish_access(AccessEvent::new(
+            config_name,
+            method.as_str(),
+            path,
+            default_group,
+            status.as_u16(),
+            duration,
+        ));
+    }
+
     result
 }
 
-/// 处理请求错误并生成适当的错误响应
-fn handle_request_error(
+// 客户端断开连接检测
+struct DisconnectWatch {
+    // 转发服务名称
+    forward: String,
+    // 响应状态码
+    status: StatusCode,
+    // 响应流是否已结束（正常结束或上游出错）
+    finished: bool,
+}
+
+impl DisconnectWatch {
+    // 响应流已结束，不是客户端断开连接
+    fn finish(&mut self) {
+        self.finished = true;
+    }
+}
+
+impl Drop for DisconnectWatch {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        info!(
+            "Client disconnected before the streaming response of forward '{}' completed, upstream request aborted",
+            self.forward
+        );
+        METRICS
+            .http_request_errors_total()
+            .with_label_values(&[
+                &self.forward,
+                error_labels::CLIENT_DISCONNECTED,
+                self.status.as_str(),
+            ])
+            .inc();
+    }
+}
+
+// 转发上游响应流，客户端断开连接时服务器丢弃响应流，上游响应随之丢弃，上游连接被关闭，
+// 不再继续生成内容（避免为已放弃的对话付费），同时记录日志和错误指标
+fn watch_disconnect(
+    response: reqwest::Response,
+    config_name: &str,
+    status: StatusCode,
+) -> impl futures_util::Stream<Item = reqwest::Result<bytes::Bytes>> + Send + 'static {
+    let watch = DisconnectWatch {
+        forward: config_name.to_string(),
+        status,
+        finished: false,
+    };
+    futures_util::stream::unfold(
+        (response.bytes_stream().boxed(), watch),
+        |(mut upstream, mut watch)| async move {
+            match upstream.next().await {
+                Some(Ok(chunk)) => Some((Ok(chunk), (upstream, watch))),
+                Some(Err(e)) => {
+                    watch.finish();
+                    Some((Err(e), (upstream, watch)))
+                }
+                None => {
+                    watch.finish();
+                    None
+                }
+            }
+        },
+    )
+}
+
+/// 处理请求错误并按转发服务的配置生成错误响应
+pub(super) fn handle_request_error(
     error: &AppError,
     start_time: Instant,
-    config_name: &str,
+    state: &ForwardState,
     method: &Method,
     path: &str,
     default_group: &str,
+    request_id: &str,
 ) -> Response {
     tracing::error!("Failed to forward request: {}", error);
+    let config = &state.config;
+    let config_name = config.name.as_str();
 
     // 记录错误指标
     METRICS
@@ -140,35 +269,217 @@ fn handle_request_error(
         duration.as_millis()
     );
 
-    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+    // 发布访问事件
+    if EVENTS.has_access_subscribers() {
+        EVENTS.publish_access(
+            AccessEvent::new(
+                config_name,
+                method.as_str(),
+                path,
+                default_group,
+                StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                duration,
+            )
+            .with_error(error),
+        );
+    }
+
+    let details = config
+        .error_response
+        .as_ref()
+        .is_some_and(|errors| errors.details);
+    ProxyError::new(error, method, request_id, details)
+        .into_response(config.error_response.as_ref())
 }
 
 // 转发处理函数
+// 请求在 request 日志范围内处理，请求 ID 和上游组确定后记录到范围中，之后的日志都带有这些字段
 pub async fn forward_handler(
     State(state): State<Arc<ForwardState>>,
     path: Option<Path<String>>,
     method: Method,
     headers: HeaderMap,
     req: Request<Body>,
+) -> Response {
+    let span = info_span!(
+        "request",
+        request_id = field::Empty,
+        forward = %state.config.name,
+        group = field::Empty,
+    );
+    let forward = state.config.name.clone();
+    let mut model = String::new();
+    let response = forward_request(state, path, method.clone(), headers, req, &mut model)
+        .instrument(span)
+        .await;
+
+    // 记录请求指标
+    METRICS.record_http_request(&forward, method.as_str(), response.status(), &model);
+    response
+}
+
+// 处理转发请求，读取请求体后将请求的模型名称写入 model，用作请求指标的标签
+async fn forward_request(
+    state: Arc<ForwardState>,
+    path: Option<Path<String>>,
+    method: Method,
+    mut headers: HeaderMap,
+    mut req: Request<Body>,
+    model: &mut String,
 ) -> Response {
     // 记录开始时间
     let start_time = Instant::now();
+    // 开启访问日志时由访问日志中间件放入请求扩展
+    let access = req.extensions().get::<AccessInfo>().cloned();
 
     // 标准化请求路径
     let path = normalize_path(path);
     debug!("Forwarding request url path: {:?}", path);
 
-    // 记录请求指标
-    METRICS
-        .http_requests_total()
-        .with_label_values(&[&state.config.name, method.as_str()])
-        .inc();
+    // 转发服务已被禁用（如事故处理期间从外部负载均衡器摘除），直接返回 503
+    if !state.is_enabled() {
+        debug!(
+            "Forwarding service {:?} is disabled, rejecting request",
+            state.config.name
+        );
+        METRICS
+            .http_request_errors_total()
+            .with_label_values(&[
+                &state.config.name,
+                error_labels::SERVICE_DISABLED,
+                StatusCode::SERVICE_UNAVAILABLE.as_str(),
+            ])
+            .inc();
+        return StatusCode::SERVICE_UNAVAILABLE.into_response();
+    }
 
-    // 提取请求体
-    let (_, body) = req.into_parts();
-    let body_bytes = match extract_request_body(body, &state.config.name).await {
-        Ok(bytes) => bytes,
-        Err(response) => return response,
+    // 请求方法或路径不在白名单中时直接拒绝，不读取请求体也不请求上游
+    if let Some(allowlist) = &state.allowlist {
+        if let Err(rejected) = allowlist.check(&method, &path) {
+            debug!(
+                "Request {:?} {:?} is not allowed by forwarding service {:?}",
+                method, path, state.config.name
+            );
+            METRICS
+                .http_request_errors_total()
+                .with_label_values(&[
+                    &state.config.name,
+                    error_labels::NOT_ALLOWED,
+                    rejected.status().as_str(),
+                ])
+                .inc();
+            return rejected.into_response();
+        }
+    }
+
+    // 请求头数量或大小超出限制时返回 431
+    if let Some(filter) = &state.header_filter {
+        if let Err(exceeded) = filter.check(&headers) {
+            debug!(
+                "Request has {} headers ({} bytes), exceeding the limits of forwarding service {:?}",
+                exceeded.count, exceeded.bytes, state.config.name
+            );
+            METRICS
+                .http_request_errors_total()
+                .with_label_values(&[
+                    &state.config.name,
+                    error_labels::HEADERS_TOO_LARGE,
+                    StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE.as_str(),
+                ])
+                .inc();
+            return exceeded.into_response();
+        }
+    }
+
+    // 资源紧张时在读取请求体之前按优先级拒绝请求，避免进程内存耗尽
+    if let Err(shed) = SHEDDER.check(&headers) {
+        debug!(
+            "Load shedding rejected {} priority request to forwarding service {:?}",
+            shed.priority.as_str(),
+            state.config.name
+        );
+        METRICS
+            .http_request_errors_total()
+            .with_label_values(&[
+                &state.config.name,
+                error_labels::LOAD_SHED,
+                StatusCode::SERVICE_UNAVAILABLE.as_str(),
+            ])
+            .inc();
+        return shed.into_response();
+    }
+
+    // 构建请求上下文，客户端未携带请求 ID 时自动生成
+    let client_ip = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let mut context = RequestContext {
+        client_ip,
+        request_id: headers
+            .get(http_headers::REQUEST_ID)
+            .and_then(|value| value.to_str().ok())
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| Uuid::new_v4().to_string()),
+        forward_name: state.config.name.clone(),
+        client_id: state
+            .config
+            .budget
+            .as_ref()
+            .and_then(|budget| client_id(budget, &headers, client_ip)),
+        token_charge: None,
+    };
+    Span::current().record("request_id", context.request_id.as_str());
+    if let Some(access) = &access {
+        access.set_request(&context.request_id, context.client_id.as_deref());
+    }
+
+    // 客户端预算已用尽时返回 429，直到预算重置
+    if let (Some(budget), Some(client)) = (&state.config.budget, &context.client_id) {
+        if let Err(exceeded) = check_budget(budget, &state.config.name, client) {
+            debug!(
+                "Client {} budget of forwarding service {:?} exceeded",
+                exceeded.period, state.config.name
+            );
+            METRICS
+                .http_request_errors_total()
+                .with_label_values(&[
+                    &state.config.name,
+                    error_labels::BUDGET_EXCEEDED,
+                    StatusCode::TOO_MANY_REQUESTS.as_str(),
+                ])
+                .inc();
+            return exceeded.into_response();
+        }
+    }
+
+    // 并发请求数达到上限时排队等待空闲，等待队列已满或等待超时时拒绝请求
+    let permit = match &state.concurrency {
+        Some(limiter) => match limiter.acquire(&headers, context.client_ip).await {
+            Ok(permit) => Some(permit),
+            Err(rejected) => {
+                let (label, status) = match rejected {
+                    ConcurrencyRejected::QueueFull { .. } => (
+                        error_labels::CONCURRENCY_LIMITED,
+                        StatusCode::TOO_MANY_REQUESTS,
+                    ),
+                    ConcurrencyRejected::Timeout { .. } => {
+                        (error_labels::QUEUE_TIMEOUT, StatusCode::SERVICE_UNAVAILABLE)
+                    }
+                };
+                debug!(
+                    "Concurrency limit of forwarding service {:?} reached, rejecting request with {}",
+                    state.config.name, status
+                );
+                METRICS
+                    .http_request_errors_total()
+                    .with_label_values(&[&state.config.name, label, status.as_str()])
+                    .inc();
+                return rejected.into_response();
+            }
+        },
+        None => None,
     };
 
     // 此处应该还有一个路由模块
@@ -182,36 +493,647 @@ pub async fn forward_handler(
     // 3. 如果找不到对应的 routing 规则，则使用默认的 "default_group" 配置。
     //
     // 使用路由器获取目标上游组
-    let routing_result = state.router.get_target_group(&path).await;
-    let target_group = &routing_result.target_group;
+    let routing_result = state.router.get_target_group(&path);
+    Span::current().record("group", routing_result.target_group.as_str());
+    if let Some(access) = &access {
+        access.set_route(
+            routing_result.route.as_deref(),
+            &routing_result.target_group,
+        );
+    }
+    let mut target_group = routing_result.target_group;
+    let route_breaker = routing_result.breaker;
+    let route_max_prompt_tokens = routing_result.max_prompt_tokens;
+    let route_token_limit = routing_result
+        .route
+        .as_deref()
+        .zip(routing_result.tokens_per_minute);
+
+    // 开启 WebSocket 代理时升级连接并转发给上游，不读取请求体，也不执行请求处理阶段
+    if let Some(websocket) = &state.config.websocket {
+        if is_upgrade_request(&method, &headers) {
+            if let Some(filter) = &state.header_filter {
+                filter.strip(req.headers_mut());
+            }
+            return proxy_websocket(
+                &state,
+                websocket,
+                req,
+                &target_group,
+                &path,
+                &context,
+                permit,
+            )
+            .await;
+        }
+    }
+
+    // 转发服务和目标上游组都不需要读取请求体时，客户端请求体不经缓冲直接转发给上游，
+    // 否则读取完整的请求体
+    let (_, body) = req.into_parts();
+    let streams_body = has_request_body(&headers)
+        && !needs_request_body(&state)
+        && route_max_prompt_tokens.is_none()
+        && state.upstream_manager.accepts_streaming_body(&target_group);
+    let (mut body_bytes, body_stream) = if streams_body {
+        debug!(
+            "Streaming request body to upstream group {:?}",
+            target_group
+        );
+        (None, Some(body))
+    } else {
+        match extract_request_body(body, &state.config.name).await {
+            Ok(bytes) => (bytes, None),
+            Err(response) => return response,
+        }
+    };
+    *model = METRICS.model_label(body_bytes.as_deref());
+
+    // 按转发服务配置的顺序执行请求处理阶段
+    let mut prompt_tokens = None;
+    let mut audit = None;
+    let mut key = None;
+    for stage in state.config.middleware_order() {
+        match stage {
+            // 入口阶段已由中间件层执行
+            MiddlewareStage::AccessLog
+            | MiddlewareStage::Compression
+            | MiddlewareStage::RateLimit => {}
+            // 读取请求后调用插件
+            MiddlewareStage::Plugins => {
+                let Some(plugins) = &state.plugins else {
+                    continue;
+                };
+                let plugin_context = PluginContext {
+                    request_id: &context.request_id,
+                    method: &method,
+                    path: &path,
+                    group: None,
+                };
+                if let Err(rejected) = plugins
+                    .process_request(
+                        PluginStage::OnRequest,
+                        &plugin_context,
+                        &mut headers,
+                        &mut body_bytes,
+                    )
+                    .await
+                {
+                    let response = plugin_rejected(&state.config.name, rejected);
+                    return with_prompt_tokens(response, prompt_tokens.flatten());
+                }
+            }
+            // 按转发服务的参数上限改写或拒绝请求
+            MiddlewareStage::Limits => {
+                let (Some(limits), Some(body)) = (&state.config.limits, &body_bytes) else {
+                    continue;
+                };
+                let tokens = match prompt_tokens {
+                    Some(tokens) => tokens,
+                    None => *prompt_tokens.insert(
+                        estimate_prompt_tokens(&state, Some(body), route_max_prompt_tokens).await,
+                    ),
+                };
+                match enforce_limits(limits, body, tokens) {
+                    Ok(Some(body)) => {
+                        body_bytes = Some(body);
+                        headers.remove(CONTENT_LENGTH);
+                    }
+                    Ok(None) => {}
+                    Err(exceeded) => {
+                        let response = limit_exceeded(&state.config.name, exceeded);
+                        return with_prompt_tokens(response, tokens);
+                    }
+                }
+            }
+            // 转发前脱敏提示词中的个人信息
+            MiddlewareStage::PiiRedaction => {
+                let (Some(redactor), Some(body)) = (&state.pii, &body_bytes) else {
+                    continue;
+                };
+                if let Some(redacted) = redactor.redact(body) {
+                    for (detector, count) in &redacted.counts {
+                        METRICS
+                            .pii_redactions_total()
+                            .with_label_values(&[&state.config.name, detector])
+                            .inc_by(*count);
+                    }
+                    debug!(
+                        "Redacted PII from request {:?} {:?}: {:?}",
+                        method, path, redacted.counts
+                    );
+                    body_bytes = Some(redacted.body);
+                    headers.remove(CONTENT_LENGTH);
+                }
+            }
+            // 按外部策略服务的裁决放行、拒绝或改写请求
+            MiddlewareStage::Policy => {
+                let Some(policy) = &state.policy else {
+                    continue;
+                };
+                let request = policy.request(
+                    &context.request_id,
+                    context.client_ip,
+                    &method,
+                    &path,
+                    body_bytes.as_ref(),
+                );
+                match policy.check(&request).await {
+                    Ok(mut allowed) => {
+                        allowed.apply_headers(&mut headers);
+                        if let Some(body) = allowed.body {
+                            body_bytes = Some(body);
+                            headers.remove(CONTENT_LENGTH);
+                        }
+                    }
+                    Err(blocked) => {
+                        debug!(
+                            "Policy service of forwarding service {:?} blocked request {:?} {:?}",
+                            state.config.name, method, path
+                        );
+                        let label = if blocked.unavailable {
+                            error_labels::POLICY_UNAVAILABLE
+                        } else {
+                            error_labels::POLICY_BLOCKED
+                        };
+                        METRICS
+                            .http_request_errors_total()
+                            .with_label_values(&[
+                                &state.config.name,
+                                label,
+                                blocked.status.as_str(),
+                            ])
+                            .inc();
+                        return with_prompt_tokens(
+                            blocked.into_response(),
+                            prompt_tokens.flatten(),
+                        );
+                    }
+                }
+            }
+            // 扣除估算的提示词 token 数，超出每分钟 token 数限制时返回 429
+            MiddlewareStage::TokenLimit => {
+                let Some(limiter) = &state.token_limiter else {
+                    continue;
+                };
+                let tokens = match prompt_tokens {
+                    Some(tokens) => tokens,
+                    None => *prompt_tokens.insert(
+                        estimate_prompt_tokens(
+                            &state,
+                            body_bytes.as_ref(),
+                            route_max_prompt_tokens,
+                        )
+                        .await,
+                    ),
+                };
+                let estimated = tokens.unwrap_or_default() as u64;
+                match limiter
+                    .acquire(route_token_limit, &headers, context.client_ip, estimated)
+                    .await
+                {
+                    Ok(charge) => context.token_charge = Some(charge),
+                    Err(exceeded) => {
+                        debug!(
+                            "Token limit of forwarding service {:?} exceeded, retry after {} seconds",
+                            state.config.name, exceeded.retry_after
+                        );
+                        METRICS
+                            .http_request_errors_total()
+                            .with_label_values(&[
+                                &state.config.name,
+                                error_labels::TOKEN_LIMIT_EXCEEDED,
+                                StatusCode::TOO_MANY_REQUESTS.as_str(),
+                            ])
+                            .inc();
+                        return with_prompt_tokens(exceeded.into_response(), tokens);
+                    }
+                }
+            }
+            // 开启审计日志时保留此时的请求信息，目标上游组在转发前确定
+            MiddlewareStage::AuditSink => {
+                audit = state.audit_sink.as_ref().map(|sink| {
+                    let request = AuditRequest {
+                        start_time,
+                        request_id: context.request_id.clone(),
+                        client_ip: context.client_ip,
+                        method: method.clone(),
+                        path: path.to_string(),
+                        group: target_group.clone(),
+                        body: body_bytes.clone(),
+                    };
+                    (sink, request)
+                });
+            }
+            // 开启响应缓存时计算请求的键，命中时直接返回缓存的响应，流式请求不缓存
+            MiddlewareStage::Cache => {
+                let (Some(cache), None) = (&state.cache, &body_stream) else {
+                    continue;
+                };
+                key =
+                    CoalesceKey::new(&target_group, &method, &path, &headers, body_bytes.as_ref());
+                let Some(key) = &key else {
+                    continue;
+                };
+                let cached = cache.lookup(key).await;
+                let result = if cached.is_some() {
+                    cache_limits::HIT
+                } else {
+                    cache_limits::MISS
+                };
+                METRICS
+                    .cache_requests_total()
+                    .with_label_values(&[&state.config.name, result])
+                    .inc();
+                if let Some(response) = cached {
+                    debug!("Request {:?} {:?} served from response cache", method, path);
+                    METRICS
+                        .http_request_duration_seconds()
+                        .with_label_values(&[&state.config.name, method.as_str()])
+                        .observe(start_time.elapsed().as_secs_f64());
+                    let response = match &state.plugins {
+                        Some(plugins) => {
+                            let plugin_context = PluginContext {
+                                request_id: &context.request_id,
+                                method: &method,
+                                path: &path,
+                                group: Some(&target_group),
+                            };
+                            plugins.process_response(&plugin_context, response).await
+                        }
+                        None => response,
+                    };
+                    let response = match audit {
+                        Some((sink, request)) => sink.record(request, response),
+                        None => response,
+                    };
+                    return hold_permit(
+                        with_prompt_tokens(response, prompt_tokens.flatten()),
+                        permit,
+                    );
+                }
+            }
+        }
+    }
+    let prompt_tokens = match prompt_tokens {
+        Some(tokens) => tokens,
+        None => estimate_prompt_tokens(&state, body_bytes.as_ref(), route_max_prompt_tokens).await,
+    };
+
+    // 按匹配路由的提示词 token 数上限拒绝请求
+    if let Some(max) = route_max_prompt_tokens {
+        if let Err(exceeded) = check_prompt_tokens(max, prompt_tokens) {
+            let response = limit_exceeded(&state.config.name, exceeded);
+            return hold_permit(with_prompt_tokens(response, prompt_tokens), permit);
+        }
+    }
+
+    // 请求体中的模型命中别名时，转发到别名对应的上游组并改写模型名称
+    let resolved = match &body_bytes {
+        Some(body) => state.models.resolve(body),
+        None => None,
+    };
+    if let Some(resolved) = resolved {
+        // 路由时已记录上游组，只在别名改变上游组时重新记录，避免日志中重复输出
+        if resolved.target_group != target_group {
+            Span::current().record("group", resolved.target_group.as_str());
+        }
+        target_group = resolved.target_group;
+        body_bytes = Some(resolved.body);
+        headers.remove(CONTENT_LENGTH);
+    }
+    let target_group = &target_group;
+    if let Some((_, request)) = &mut audit {
+        request.group = target_group.clone();
+    }
+    if let Some(access) = &access {
+        access.set_group(target_group);
+    }
 
     // 记录路由匹配
     METRICS.record_route_match(&state.config.name, target_group);
 
-    // 转发请求
-    match state
-        .upstream_manager
-        .forward_request(target_group, &method, headers, body_bytes)
-        .await
-    {
-        Ok(response) => {
-            handle_response(
-                response,
+    // 转发给上游前调用插件
+    let plugin_context = PluginContext {
+        request_id: &context.request_id,
+        method: &method,
+        path: &path,
+        group: Some(target_group),
+    };
+    if let Some(plugins) = &state.plugins {
+        if let Err(rejected) = plugins
+            .process_request(
+                PluginStage::PreUpstream,
+                &plugin_context,
+                &mut headers,
+                &mut body_bytes,
+            )
+            .await
+        {
+            let response = plugin_rejected(&state.config.name, rejected);
+            return hold_permit(with_prompt_tokens(response, prompt_tokens), permit);
+        }
+    }
+
+    // 开启请求合并且响应缓存阶段没有计算请求的键时计算请求的键，流式请求不合并
+    if key.is_none() && state.coalescer.is_some() && body_stream.is_none() {
+        key = CoalesceKey::new(target_group, &method, &path, &headers, body_bytes.as_ref());
+    }
+
+    // 移除不转发给上游的请求头，请求合并和响应缓存的键包含移除前的请求头（如客户端凭证）
+    if let Some(filter) = &state.header_filter {
+        filter.strip(&mut headers);
+    }
+
+    // 开启请求合并时，相同的非流式请求共享同一个在途请求的响应
+    let coalesce = match (&state.coalescer, &key) {
+        (Some(coalescer), Some(key)) => Some(coalescer.join(key.clone())),
+        _ => None,
+    };
+
+    // 转发请求，配置了路由熔断器时由路由熔断器保护整个上游组
+    let header_timeout = state
+        .config
+        .timeout
+        .as_ref()
+        .and_then(|timeout| timeout.header);
+    let forward = async {
+        // 上游请求的 future 较大，放在堆上，避免经过路由熔断器时占用过多栈空间
+        // 配置了响应头超时时，超时未收到上游响应头的请求按上游错误处理，路由熔断器计为失败
+        let upstream = Box::pin(async {
+            let forwarding = async {
+                match body_stream {
+                    Some(body) => {
+                        let body = reqwest::Body::wrap_stream(body.into_data_stream());
+                        state
+                            .upstream_manager
+                            .forward_request_stream(
+                                target_group,
+                                &path,
+                                &context,
+                                &method,
+                                headers,
+                                body,
+                            )
+                            .await
+                    }
+                    None => {
+                        state
+                            .upstream_manager
+                            .forward_request(
+                                target_group,
+                                &path,
+                                &context,
+                                &method,
+                                headers,
+                                body_bytes,
+                            )
+                            .await
+                    }
+                }
+            };
+            match header_timeout {
+                Some(timeout) => tokio::time::timeout(Duration::from_secs(timeout), forwarding)
+                    .await
+                    .unwrap_or_else(|_| {
+                        Err(AppError::Upstream(format!(
+                            "No response headers from upstream group {:?} within {}s",
+                            target_group, timeout
+                        )))
+                    }),
+                None => forwarding.await,
+            }
+        });
+        let result = match &route_breaker {
+            Some(breaker) => call_route_breaker(breaker, upstream).await,
+            None => upstream.await,
+        };
+        match result {
+            Ok(response) => {
+                handle_response(
+                    response,
+                    start_time,
+                    &state.config.name,
+                    &method,
+                    &path,
+                    target_group,
+                    state.config.response_buffer_limit,
+                )
+                .await
+            }
+            Err(e) => handle_request_error(
+                &e,
                 start_time,
-                &state.config.name,
+                &state,
                 &method,
                 &path,
                 target_group,
-            )
+                &context.request_id,
+            ),
+        }
+    };
+    // 转发的响应写入响应缓存
+    let forward = async {
+        let response = forward.await;
+        match (&state.cache, &key) {
+            (Some(cache), Some(key)) => cache.store(key, response).await,
+            _ => response,
+        }
+    };
+    let response = match coalesce {
+        Some(Coalesced::Leader(leader)) => leader.share(forward.await).await,
+        Some(Coalesced::Follower(follower)) => match follower.wait().await {
+            Some(response) => {
+                debug!(
+                    "Request {:?} {:?} shared the response of an identical in-flight request",
+                    method, path
+                );
+                METRICS
+                    .coalesced_requests_total()
+                    .with_label_values(&[&state.config.name])
+                    .inc();
+                METRICS
+                    .http_request_duration_seconds()
+                    .with_label_values(&[&state.config.name, method.as_str()])
+                    .observe(start_time.elapsed().as_secs_f64());
+                response
+            }
+            // 在途请求被取消或返回了流式响应，单独转发
+            None => forward.await,
+        },
+        None => forward.await,
+    };
+    // 返回给客户端前调用插件，只处理非流式响应
+    let response = match &state.plugins {
+        Some(plugins) => plugins.process_response(&plugin_context, response).await,
+        None => response,
+    };
+    // 事件流响应按转发服务配置注入心跳
+    let response = match state.config.sse_heartbeat {
+        Some(interval) => with_heartbeat(response, Duration::from_secs(interval)),
+        None => response,
+    };
+    // 响应体发送完成后写入审计记录
+    let response = match audit {
+        Some((sink, request)) => sink.record(request, response),
+        None => response,
+    };
+    hold_permit(with_prompt_tokens(response, prompt_tokens), permit)
+}
+
+// 通过路由熔断器转发请求
+// 请求失败、上游返回 5xx 或认证失败（401、403）时计为失败，失败的响应原样返回给客户端。
+// 路由熔断器开启时不再请求上游组，直接返回错误
+async fn call_route_breaker(
+    breaker: &UpstreamCircuitBreaker,
+    upstream: impl Future<Output = Result<reqwest::Response, AppError>>,
+) -> Result<reqwest::Response, AppError> {
+    let mut outcome = None;
+    let result = {
+        let outcome = &mut outcome;
+        breaker
+            .call_async(move || async move {
+                let result = upstream.await;
+                let failure = match &result {
+                    Ok(response) => {
+                        let status = response.status();
+                        (status.is_server_error()
+                            || route_breaker::FAILURE_STATUSES.contains(&status.as_u16()))
+                        .then(|| format!("Upstream responded with {}", status))
+                    }
+                    Err(e) => Some(e.to_string()),
+                };
+                *outcome = Some(result);
+                match failure {
+                    Some(message) => Err(UpstreamError(message)),
+                    None => Ok(()),
+                }
+            })
             .await
+    };
+    match (result, outcome) {
+        (_, Some(result)) => result,
+        (Err(BreakerError::Open), None) => {
+            debug!("Route circuit breaker {:?} is open", breaker.name());
+            METRICS
+                .circuitbreaker_calls_total()
+                .with_label_values(&[
+                    breaker.group(),
+                    breaker.name(),
+                    breaker_result_labels::REJECTED,
+                ])
+                .inc();
+            Err(AppError::CircuitBreakerOpen(Arc::new(
+                breaker.name().to_string(),
+            )))
         }
-        Err(e) => handle_request_error(
-            &e,
-            start_time,
-            &state.config.name,
-            &method,
-            &path,
-            target_group,
-        ),
+        (Err(e), None) => Err(e.into()),
+        (Ok(()), None) => Err(AppError::Internal(
+            "Route circuit breaker completed without a response".to_string(),
+        )),
+    }
+}
+
+// 估算提示词 token 数并记录指标，转发服务和匹配的路由都不需要 token 数时不估算
+async fn estimate_prompt_tokens(
+    state: &ForwardState,
+    body: Option<&Bytes>,
+    route_max_prompt_tokens: Option<u64>,
+) -> Option<usize> {
+    let needed = state.config.count_tokens
+        || route_max_prompt_tokens.is_some()
+        || state.token_limiter.is_some()
+        || state
+            .config
+            .limits
+            .as_ref()
+            .is_some_and(|limits| limits.max_prompt_tokens.is_some());
+    let tokens = count_prompt_tokens_async(body.filter(|_| needed)?).await?;
+    METRICS
+        .prompt_tokens()
+        .with_label_values(&[&state.config.name])
+        .observe(tokens as f64);
+    Some(tokens)
+}
+
+// 记录超出参数上限的请求并返回 400 错误
+fn limit_exceeded(forward: &str, exceeded: LimitExceeded) -> Response {
+    debug!(
+        "Request parameter {} exceeds the limit of forwarding service {:?}",
+        exceeded.param, forward
+    );
+    METRICS
+        .http_request_errors_total()
+        .with_label_values(&[
+            forward,
+            error_labels::VALIDATION_ERROR,
+            StatusCode::BAD_REQUEST.as_str(),
+        ])
+        .inc();
+    exceeded.into_response()
+}
+
+// 记录插件拒绝的请求并返回插件的响应
+fn plugin_rejected(forward: &str, rejected: PluginRejected) -> Response {
+    debug!(
+        "Plugin {:?} of forwarding service {:?} rejected request with {}",
+        rejected.plugin, forward, rejected.status
+    );
+    let label = if rejected.error {
+        error_labels::PLUGIN_ERROR
+    } else {
+        error_labels::PLUGIN_REJECTED
+    };
+    METRICS
+        .http_request_errors_total()
+        .with_label_values(&[forward, label, rejected.status.as_str()])
+        .inc();
+    rejected.into_response()
+}
+
+// 请求是否携带请求体
+fn has_request_body(headers: &HeaderMap) -> bool {
+    headers.contains_key(TRANSFER_ENCODING)
+        || headers
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok())
+            .is_some_and(|length| length > 0)
+}
+
+// 转发服务是否需要读取完整的请求体（估算 token 数、参数上限、请求合并、响应缓存、审计日志、PII 脱敏、策略服务、插件、模型别名）
+fn needs_request_body(state: &ForwardState) -> bool {
+    state.config.count_tokens
+        || state.config.coalesce
+        || state.config.cache.is_some()
+        || state.config.audit_sink.is_some()
+        || state.config.pii_redaction.is_some()
+        || state.config.policy.is_some()
+        || !state.config.plugins.is_empty()
+        || state.token_limiter.is_some()
+        || state.config.limits.is_some()
+        || !state.models.is_empty()
+}
+
+// 以流式转发的响应在响应体发送完成前持有并发许可，其他响应已读取完整响应体，直接释放许可
+fn hold_permit(response: Response, permit: Option<ConcurrencyPermit>) -> Response {
+    match permit {
+        Some(permit) if super::utils::is_unbuffered_response(&response) => response.map(|body| {
+            Body::from_stream(body.into_data_stream().map(move |chunk| {
+                let _permit = &permit;
+                chunk
+            }))
+        }),
+        _ => response,
+    }
+}
+
+// 添加提示词 token 估算值响应头
+fn with_prompt_tokens(mut response: Response, prompt_tokens: Option<usize>) -> Response {
+    if let Some(tokens) = prompt_tokens {
+        response
+            .headers_mut()
+            .insert(http_headers::PROMPT_TOKENS, HeaderValue::from(tokens));
     }
+    response
 }
@@ -0,0 +1,84 @@
+use crate::r#const::{http_headers, sse_heartbeat};
+use axum::{body::Body, response::Response};
+use bytes::Bytes;
+use futures_util::{stream::BoxStream, StreamExt};
+use std::time::Duration;
+
+// 心跳状态
+struct HeartbeatState {
+    // 上游响应流
+    upstream: BoxStream<'static, Result<Bytes, axum::Error>>,
+    // 心跳间隔
+    interval: Duration,
+    // 已发送数据的末尾字节，用于判断是否位于事件边界
+    tail: Vec<u8>,
+    // 上游流是否已结束
+    done: bool,
+}
+
+impl HeartbeatState {
+    // 记录已发送的数据
+    fn observe(&mut self, chunk: &[u8]) {
+        self.tail.extend_from_slice(chunk);
+        let excess = self.tail.len().saturating_sub(4);
+        self.tail.drain(..excess);
+    }
+
+    // 是否位于事件边界（尚未发送数据或上一个事件已结束），只在事件边界插入心跳，避免拆分事件
+    fn at_event_boundary(&self) -> bool {
+        self.tail.is_empty() || self.tail.ends_with(b"\n\n") || self.tail.ends_with(b"\r\n\r\n")
+    }
+}
+
+// 为事件流响应注入心跳
+// 上游在心跳间隔内没有发送数据时，向客户端发送 SSE 注释行（客户端会忽略），
+// 避免中间的负载均衡器和浏览器因连接空闲而中断长时间的生成。其他响应原样返回
+pub(super) fn with_heartbeat(response: Response, interval: Duration) -> Response {
+    let is_event_stream = response
+        .headers()
+        .get(http_headers::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains(http_headers::content_types::EVENT_STREAM));
+    if !is_event_stream {
+        return response;
+    }
+
+    response.map(|body| {
+        let state = HeartbeatState {
+            upstream: body.into_data_stream().boxed(),
+            interval,
+            tail: Vec::new(),
+            done: false,
+        };
+        Body::from_stream(futures_util::stream::unfold(
+            state,
+            |mut state| async move {
+                if state.done {
+                    return None;
+                }
+                loop {
+                    tokio::select! {
+                        chunk = state.upstream.next() => {
+                            return match chunk {
+                                Some(Ok(chunk)) => {
+                                    state.observe(&chunk);
+                                    Some((Ok(chunk), state))
+                                }
+                                Some(Err(e)) => {
+                                    state.done = true;
+                                    Some((Err(e), state))
+                                }
+                                None => None,
+                            };
+                        }
+                        _ = tokio::time::sleep(state.interval) => {
+                            if state.at_event_boundary() {
+                                return Some((Ok(Bytes::from_static(sse_heartbeat::COMMENT)), state));
+                            }
+                        }
+                    }
+                }
+            },
+        ))
+    })
+}
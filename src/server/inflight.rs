@@ -0,0 +1,74 @@
+use axum::{
+    body::{Body, Bytes},
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use hyper::body::{Body as HttpBody, Frame, SizeHint};
+use prometheus::IntGauge;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// 统计正在处理的请求数
+///
+/// 收到请求时加一，响应体发送完成或客户端断开连接时减一，流式响应在整个流结束前都计为在途请求
+pub async fn track_inflight(
+    State(gauge): State<IntGauge>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let guard = InflightGuard::new(gauge);
+    let response = next.run(request).await;
+    response.map(|body| {
+        Body::new(InflightBody {
+            inner: body,
+            _guard: guard,
+        })
+    })
+}
+
+// 在途请求计数，释放时减一
+struct InflightGuard(IntGauge);
+
+impl InflightGuard {
+    fn new(gauge: IntGauge) -> Self {
+        gauge.inc();
+        Self(gauge)
+    }
+}
+
+impl Drop for InflightGuard {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+// 持有在途请求计数的响应体
+struct InflightBody {
+    // 原响应体
+    inner: Body,
+    // 在途请求计数
+    _guard: InflightGuard,
+}
+
+impl HttpBody for InflightBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        Pin::new(&mut self.inner).poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{
+    config::{ParamLimitAction, ParamLimitsConfig},
+    r#const::param_limits,
+};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use bytes::Bytes;
+use serde_json::{json, Value};
+use tracing::debug;
+
+// 请求参数超出上限
+#[derive(Debug, Clone)]
+pub struct LimitExceeded {
+    // 参数名称
+    pub param: &'static str,
+    // 错误代码
+    pub code: &'static str,
+    // 错误信息
+    pub message: String,
+}
+
+impl IntoResponse for LimitExceeded {
+    // 返回 OpenAI 格式的 400 错误
+    fn into_response(self) -> Response {
+        let body = json!({"error": {
+            "message": self.message,
+            "type": param_limits::ERROR_TYPE,
+            "param": self.param,
+            "code": self.code,
+        }});
+        (StatusCode::BAD_REQUEST, Json(body)).into_response()
+    }
+}
+
+// 按参数上限检查请求体
+// 请求体不是 JSON 对象或未超出上限时返回 None，改写为上限值时返回新的请求体，拒绝时返回超限的参数。
+// prompt_tokens 为估算的提示词 token 数，超出上限时总是拒绝
+pub fn enforce_limits(
+    limits: &ParamLimitsConfig,
+    body: &[u8],
+    prompt_tokens: Option<usize>,
+) -> Result<Option<Bytes>, LimitExceeded> {
+    if let Some(max) = limits.max_prompt_tokens {
+        check_prompt_tokens(max, prompt_tokens)?;
+    }
+
+    let Ok(Value::Object(mut object)) = serde_json::from_slice::<Value>(body) else {
+        return Ok(None);
+    };
+
+    let rules = [
+        ("max_tokens", limits.max_tokens.map(Value::from)),
+        ("max_completion_tokens", limits.max_tokens.map(Value::from)),
+        ("temperature", limits.temperature.map(Value::from)),
+        ("top_p", limits.top_p.map(Value::from)),
+        ("n", limits.n.map(Value::from)),
+    ];
+    let mut changed = false;
+    for (param, limit) in rules {
+        let (Some(limit), Some(value)) = (limit, object.get_mut(param)) else {
+            continue;
+        };
+        // 非数值参数交由上游校验
+        let (Some(current), Some(max)) = (value.as_f64(), limit.as_f64()) else {
+            continue;
+        };
+        if current <= max {
+            continue;
+        }
+
+        match limits.action {
+            ParamLimitAction::Reject => {
+                return Err(LimitExceeded {
+                    param,
+                    code: param_limits::ERROR_CODE,
+                    message: format!(
+                        "{} {} exceeds the maximum allowed value {}",
+                        param, value, limit
+                    ),
+                })
+            }
+            ParamLimitAction::Clamp => {
+                debug!(
+                    "Clamping request parameter {} from {} to {}",
+                    param, value, limit
+                );
+                *value = limit;
+                changed = true;
+            }
+        }
+    }
+
+    if !changed {
+        return Ok(None);
+    }
+    Ok(serde_json::to_vec(&Value::Object(object))
+        .ok()
+        .map(Bytes::from))
+}
+
+// 检查估算的提示词 token 数是否超出上限，无法估算时放行
+pub fn check_prompt_tokens(max: u64, prompt_tokens: Option<usize>) -> Result<(), LimitExceeded> {
+    match prompt_tokens {
+        Some(tokens) if tokens as u64 > max => Err(LimitExceeded {
+            param: "messages",
+            code: param_limits::PROMPT_ERROR_CODE,
+            message: format!(
+                "Prompt has about {} tokens, which exceeds the maximum allowed {} tokens",
+                tokens, max
+            ),
+        }),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,208 @@
+use axum::{extract::ConnectInfo, http::Request, Router};
+use hyper::{body::Incoming, server::conn::http1};
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo, TokioTimer},
+    server::conn::auto,
+    service::TowerToHyperService,
+};
+use std::{
+    error::Error as StdError, future::Future, io, net::SocketAddr, sync::Arc, time::Duration,
+};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::watch,
+    task::JoinSet,
+};
+use tower::ServiceExt;
+use tracing::{debug, info, warn};
+
+use crate::{config::ListenerConfig, r#const::listener_limits};
+
+/// HTTP 连接处理器，支持 WebSocket 等协议升级
+///
+/// 未开启 http2 时只接受 HTTP/1 连接，开启后同时接受 HTTP/1 和 HTTP/2 连接
+pub enum ConnectionBuilder {
+    /// 只接受 HTTP/1 连接
+    Http1(http1::Builder),
+    /// 按连接前言识别 HTTP/1 或 HTTP/2
+    Auto(auto::Builder<TokioExecutor>),
+}
+
+impl ConnectionBuilder {
+    /// 按入站连接配置创建连接处理器
+    pub fn new(config: &ListenerConfig) -> Self {
+        let idle_timeout = config.idle_timeout.map(Duration::from_secs);
+        if !config.http2 {
+            let mut builder = http1::Builder::new();
+            if let Some(max) = config.max_header_bytes {
+                builder.max_buf_size(max as usize);
+            }
+            if let Some(timeout) = idle_timeout {
+                builder
+                    .timer(TokioTimer::new())
+                    .header_read_timeout(timeout);
+            }
+            return Self::Http1(builder);
+        }
+
+        let mut builder = auto::Builder::new(TokioExecutor::new());
+        if let Some(max) = config.max_header_bytes {
+            builder.http1().max_buf_size(max as usize);
+            builder.http2().max_header_list_size(max);
+        }
+        if let Some(timeout) = idle_timeout {
+            builder
+                .http1()
+                .timer(TokioTimer::new())
+                .header_read_timeout(timeout);
+            builder
+                .http2()
+                .timer(TokioTimer::new())
+                .keep_alive_interval(timeout)
+                .keep_alive_timeout(timeout);
+        }
+        builder
+            .http2()
+            .max_concurrent_streams(config.max_concurrent_streams);
+        Self::Auto(builder)
+    }
+
+    /// 处理连接上的所有请求，连接关闭时返回
+    ///
+    /// drain 变为 true 后优雅关闭连接：HTTP/1 连接处理完当前请求后关闭，HTTP/2 连接发送 GOAWAY，
+    /// 已接收的请求（包括流式响应）处理完成后关闭
+    pub async fn serve_connection<S, B>(
+        &self,
+        stream: TcpStream,
+        service: S,
+        mut drain: watch::Receiver<bool>,
+    ) -> Result<(), Box<dyn StdError + Send + Sync>>
+    where
+        S: hyper::service::Service<Request<Incoming>, Response = hyper::Response<B>>
+            + Send
+            + 'static,
+        S::Future: Send + 'static,
+        S::Error: Into<Box<dyn StdError + Send + Sync>>,
+        B: hyper::body::Body + Send + 'static,
+        B::Data: Send,
+        B::Error: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        let io = TokioIo::new(stream);
+        match self {
+            Self::Http1(builder) => {
+                let connection = builder.serve_connection(io, service).with_upgrades();
+                tokio::pin!(connection);
+                tokio::select! {
+                    result = connection.as_mut() => return result.map_err(Into::into),
+                    _ = drain.wait_for(|drain| *drain) => connection.as_mut().graceful_shutdown(),
+                }
+                connection.await.map_err(Into::into)
+            }
+            Self::Auto(builder) => {
+                let connection = builder.serve_connection_with_upgrades(io, service);
+                tokio::pin!(connection);
+                tokio::select! {
+                    result = connection.as_mut() => return result,
+                    _ = drain.wait_for(|drain| *drain) => connection.as_mut().graceful_shutdown(),
+                }
+                connection.await
+            }
+        }
+    }
+}
+
+/// 接受入站连接并交给路由处理，请求扩展中记录客户端地址（ConnectInfo）
+///
+/// 客户端建立连接后在 connect_timeout 内没有发送任何数据时关闭连接。
+/// shutdown 完成后停止接受新连接并优雅关闭已有连接，等待所有连接上的请求处理完成后返回
+pub(super) async fn serve(
+    listener: TcpListener,
+    app: Router,
+    config: &ListenerConfig,
+    connect_timeout: Duration,
+    shutdown: impl Future<Output = ()>,
+) {
+    let builder = Arc::new(ConnectionBuilder::new(config));
+    let (drain, draining) = watch::channel(false);
+    let mut connections = JoinSet::new();
+    tokio::pin!(shutdown);
+    loop {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            // 回收已关闭的连接
+            Some(_) = connections.join_next(), if !connections.is_empty() => continue,
+            _ = &mut shutdown => break,
+        };
+        let (stream, addr) = match accepted {
+            Ok(connection) => connection,
+            // 客户端在接受前断开，直接接受下一个连接
+            Err(e) if is_connection_error(&e) => continue,
+            Err(e) => {
+                warn!("Failed to accept connection: {}", e);
+                tokio::time::sleep(Duration::from_millis(
+                    listener_limits::ACCEPT_RETRY_DELAY_MS,
+                ))
+                .await;
+                continue;
+            }
+        };
+        if config.tcp_nodelay {
+            if let Err(e) = stream.set_nodelay(true) {
+                debug!("Failed to set TCP_NODELAY for {}: {}", addr, e);
+            }
+        }
+
+        let service = app
+            .clone()
+            .map_request(move |mut request: Request<Incoming>| {
+                request
+                    .extensions_mut()
+                    .insert(ConnectInfo::<SocketAddr>(addr));
+                request
+            });
+        let builder = builder.clone();
+        let draining = draining.clone();
+        connections.spawn(async move {
+            if !wait_for_data(&stream, connect_timeout).await {
+                debug!(
+                    "Connection from {} sent no data within {:?}, closing",
+                    addr, connect_timeout
+                );
+                return;
+            }
+            if let Err(e) = builder
+                .serve_connection(stream, TowerToHyperService::new(service), draining)
+                .await
+            {
+                debug!("Connection from {} closed with error: {}", addr, e);
+            }
+        });
+    }
+
+    // 关闭监听器，不再接受新连接
+    drop(listener);
+    if !connections.is_empty() {
+        info!("Draining {} connections before shutdown", connections.len());
+    }
+    let _ = drain.send(true);
+    while connections.join_next().await.is_some() {}
+}
+
+// 等待客户端发送数据，超时或连接关闭时返回 false，数据留在套接字中由连接处理器读取
+async fn wait_for_data(stream: &TcpStream, timeout: Duration) -> bool {
+    let mut buf = [0u8; 1];
+    matches!(
+        tokio::time::timeout(timeout, stream.peek(&mut buf)).await,
+        Ok(Ok(n)) if n > 0
+    )
+}
+
+// 只影响单个连接的错误
+fn is_connection_error(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::ConnectionReset
+    )
+}
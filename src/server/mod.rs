@@ -1,11 +1,65 @@
 // 子模块定义
+mod access_log;
+mod allowlist;
+mod audit_sink;
+mod budget;
+mod coalesce;
+mod compression;
+mod concurrency;
+mod error_response;
 mod forward;
 mod handler;
+mod heartbeat;
+mod inflight;
+mod limits;
+mod listener;
+mod models;
+pub mod path_map;
+mod pii;
+mod plugin;
+mod policy;
+mod probe;
+mod ratelimit;
+mod redis_bucket;
+mod request_headers;
+mod response_cache;
 pub mod router;
+mod shedding;
+mod tls;
+mod token_limit;
+mod tokens;
 mod utils;
+mod websocket;
 
 // 公共 API 重新导出
+pub use access_log::{record_access, AccessInfo, AccessLog, AccessRecord};
+pub use allowlist::{RequestAllowlist, RequestNotAllowed};
+pub use audit_sink::{AuditBody, AuditRecord, AuditRequest, AuditSink};
+pub use budget::{check_budget, client_id, BudgetExceeded};
+pub use coalesce::{CoalesceFollower, CoalesceKey, CoalesceLeader, Coalesced, RequestCoalescer};
+pub use compression::compression_layer;
+pub use concurrency::{ConcurrencyLimiter, ConcurrencyPermit, ConcurrencyRejected};
+pub use error_response::ProxyError;
 pub use forward::{ForwardServer, ForwardState};
 pub use handler::forward_handler;
-pub use router::{Router, RoutingResult};
+pub use inflight::track_inflight;
+pub use limits::{check_prompt_tokens, enforce_limits, LimitExceeded};
+pub use listener::ConnectionBuilder;
+pub use models::{ModelCatalog, ResolvedModel};
+pub use pii::{PiiRedacted, PiiRedactor};
+pub use plugin::{
+    PluginAction, PluginChain, PluginContext, PluginOutput, PluginRejected, PluginStage,
+};
+pub use policy::{
+    PolicyAction, PolicyAllowed, PolicyBlocked, PolicyClient, PolicyRequest, PolicyVerdict,
+};
+pub use ratelimit::{ClientKey, ClientKeyExtractor, DistributedRateLimiter};
+pub use request_headers::{HeadersTooLarge, RequestHeaderFilter};
+pub use response_cache::ResponseCache;
+pub use router::{PreparedRoutes, Router, RoutingResult};
+pub use shedding::{LoadShed, LoadShedder, LoadWatchdog, Pressure, SHEDDER};
+pub use tls::{tls_acceptor, TlsListener};
+pub use token_limit::{TokenCharge, TokenLimitExceeded, TokenLimiter};
+pub use tokens::{count_prompt_tokens, count_prompt_tokens_async};
 pub use utils::create_tcp_listener;
+pub use websocket::{is_upgrade_request, proxy_websocket};
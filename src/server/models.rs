@@ -0,0 +1,74 @@
+use crate::config::ModelAlias;
+use arc_swap::ArcSwap;
+use bytes::Bytes;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::debug;
+
+// 模型别名解析结果
+#[derive(Debug, Clone)]
+pub struct ResolvedModel {
+    // 目标上游组
+    pub target_group: String,
+    // 模型名称已改写的请求体
+    pub body: Bytes,
+}
+
+// 模型别名目录
+pub struct ModelCatalog {
+    // 别名映射表快照，更新时整体替换
+    aliases: ArcSwap<HashMap<String, ModelAlias>>,
+}
+
+impl ModelCatalog {
+    pub fn new(models: &[ModelAlias]) -> Self {
+        Self {
+            aliases: ArcSwap::from_pointee(build_alias_map(models)),
+        }
+    }
+
+    // 整体替换模型别名
+    pub fn replace(&self, models: &[ModelAlias]) {
+        self.aliases.store(Arc::new(build_alias_map(models)));
+    }
+
+    // 是否未配置模型别名
+    pub fn is_empty(&self) -> bool {
+        self.aliases.load().is_empty()
+    }
+
+    // 根据请求体中的 model 字段解析别名，未配置别名或未命中时返回 None
+    pub fn resolve(&self, body: &[u8]) -> Option<ResolvedModel> {
+        let aliases = self.aliases.load();
+        if aliases.is_empty() {
+            return None;
+        }
+
+        let Ok(Value::Object(mut object)) = serde_json::from_slice::<Value>(body) else {
+            return None;
+        };
+        let alias = match object.get("model") {
+            Some(Value::String(model)) => aliases.get(model)?,
+            _ => return None,
+        };
+        debug!(
+            "Model alias matched: {:?} -> {:?} in group {:?}",
+            alias.name, alias.model, alias.group
+        );
+
+        object.insert("model".to_string(), Value::String(alias.model.clone()));
+        let body = serde_json::to_vec(&Value::Object(object)).ok()?;
+        Some(ResolvedModel {
+            target_group: alias.group.clone(),
+            body: Bytes::from(body),
+        })
+    }
+}
+
+fn build_alias_map(models: &[ModelAlias]) -> HashMap<String, ModelAlias> {
+    models
+        .iter()
+        .map(|alias| (alias.name.clone(), alias.clone()))
+        .collect()
+}
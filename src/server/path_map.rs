@@ -0,0 +1,263 @@
+use regex::Regex;
+use std::{collections::HashMap, str::Split};
+use thiserror::Error;
+
+// 路径模式错误
+#[derive(Error, Debug)]
+pub enum PathPatternError {
+    // 段正则表达式无效
+    #[error("invalid segment regex {0:?}: {1}")]
+    Regex(String, regex::Error),
+
+    // 通配符只能占据整个路径段
+    #[error("unsupported glob segment {0:?}, only a whole `*` segment is supported")]
+    Glob(String),
+}
+
+// 路径模式中的单个路径段
+#[derive(Clone)]
+enum Segment {
+    // 静态文本
+    Static(String),
+    // 正则段 {name:regex}，匹配整个路径段
+    Regex(String, Regex),
+    // 命名参数 :name，匹配一个非空路径段
+    Param(String),
+    // 通配符 *，位于中间时匹配一个路径段，位于末尾时匹配剩余的全部路径
+    Glob,
+}
+
+impl Segment {
+    fn parse(segment: &str) -> Result<Self, PathPatternError> {
+        if segment == "*" {
+            return Ok(Self::Glob);
+        }
+        if segment.contains('*') {
+            return Err(PathPatternError::Glob(segment.to_string()));
+        }
+        if segment.starts_with(':') {
+            return Ok(Self::Param(segment.to_string()));
+        }
+        if segment.len() >= 2 && segment.starts_with('{') && segment.ends_with('}') {
+            let inner = &segment[1..segment.len() - 1];
+            // 名称可省略，{\d+} 与 {:\d+} 等价
+            let expr = match inner.split_once(':') {
+                Some((_, expr)) => expr,
+                None => inner,
+            };
+            let regex = Regex::new(&format!("^(?:{})$", expr))
+                .map_err(|e| PathPatternError::Regex(segment.to_string(), e))?;
+            return Ok(Self::Regex(segment.to_string(), regex));
+        }
+        Ok(Self::Static(segment.to_string()))
+    }
+
+    // 匹配时的优先级，数值越小越优先
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Static(_) => 0,
+            Self::Regex(..) => 1,
+            Self::Param(_) => 2,
+            Self::Glob => 3,
+        }
+    }
+
+    // 段在模式中的原始文本，用于区分同一位置的不同段
+    fn origin(&self) -> &str {
+        match self {
+            Self::Static(text) | Self::Regex(text, _) | Self::Param(text) => text,
+            Self::Glob => "*",
+        }
+    }
+
+    fn matches(&self, segment: &str) -> bool {
+        match self {
+            Self::Static(text) => text == segment,
+            Self::Regex(_, regex) => regex.is_match(segment),
+            Self::Param(_) => !segment.is_empty(),
+            Self::Glob => true,
+        }
+    }
+}
+
+// 路径段树节点
+#[derive(Clone)]
+struct Node<V> {
+    // 模式在此节点结束时的值
+    value: Option<V>,
+    // 模式以通配符结尾时的值，匹配剩余的全部路径
+    tail: Option<V>,
+    // 静态子节点
+    statics: HashMap<String, Node<V>>,
+    // 正则、参数和中间通配符子节点，按匹配优先级排序
+    specials: Vec<(Segment, Node<V>)>,
+}
+
+impl<V> Node<V> {
+    fn new() -> Self {
+        Self {
+            value: None,
+            tail: None,
+            statics: HashMap::new(),
+            specials: Vec::new(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.value.is_none()
+            && self.tail.is_none()
+            && self.statics.is_empty()
+            && self.specials.is_empty()
+    }
+
+    // 获取或创建子节点
+    fn child(&mut self, segment: Segment) -> &mut Node<V> {
+        if let Segment::Static(text) = segment {
+            return self.statics.entry(text).or_insert_with(Node::new);
+        }
+
+        let index = match self
+            .specials
+            .iter()
+            .position(|(existing, _)| existing.origin() == segment.origin())
+        {
+            Some(index) => index,
+            None => {
+                // 同一优先级的段保持添加顺序
+                let index = self
+                    .specials
+                    .iter()
+                    .position(|(existing, _)| existing.rank() > segment.rank())
+                    .unwrap_or(self.specials.len());
+                self.specials.insert(index, (segment, Node::new()));
+                index
+            }
+        };
+        &mut self.specials[index].1
+    }
+
+    // 按 静态 > 正则 > 参数 > 通配符 的顺序深度优先匹配，失败时回溯
+    fn lookup(&self, mut segments: Split<'_, char>) -> Option<&V> {
+        let Some(first) = segments.next() else {
+            return self.value.as_ref();
+        };
+
+        if let Some(found) = self
+            .statics
+            .get(first)
+            .and_then(|node| node.lookup(segments.clone()))
+        {
+            return Some(found);
+        }
+
+        for (segment, node) in &self.specials {
+            if segment.matches(first) {
+                if let Some(found) = node.lookup(segments.clone()) {
+                    return Some(found);
+                }
+            }
+        }
+
+        self.tail.as_ref()
+    }
+
+    // 删除模式对应的值，并清理删除后为空的子节点
+    fn remove(&mut self, segments: &[&str]) -> Option<V> {
+        let Some((first, rest)) = segments.split_first() else {
+            return self.value.take();
+        };
+
+        if rest.is_empty() && *first == "*" {
+            return self.tail.take();
+        }
+
+        if let Some(node) = self.statics.get_mut(*first) {
+            let removed = node.remove(rest);
+            if node.is_empty() {
+                self.statics.remove(*first);
+            }
+            return removed;
+        }
+
+        let index = self
+            .specials
+            .iter()
+            .position(|(segment, _)| segment.origin() == *first)?;
+        let removed = self.specials[index].1.remove(rest);
+        if self.specials[index].1.is_empty() {
+            self.specials.remove(index);
+        }
+        removed
+    }
+}
+
+// 按路径段匹配的路由表
+// 支持静态段、命名参数 :name、正则段 {name:regex} 和通配符 *
+#[derive(Clone)]
+pub struct PathMap<V> {
+    root: Node<V>,
+    len: usize,
+}
+
+impl<V> PathMap<V> {
+    pub fn new() -> Self {
+        Self {
+            root: Node::new(),
+            len: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // 插入路径模式，返回被替换的旧值
+    pub fn insert(&mut self, pattern: String, value: V) -> Result<Option<V>, PathPatternError> {
+        let mut segments = pattern
+            .split('/')
+            .map(Segment::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let tail = matches!(segments.last(), Some(Segment::Glob));
+        if tail {
+            segments.pop();
+        }
+
+        let mut node = &mut self.root;
+        for segment in segments {
+            node = node.child(segment);
+        }
+
+        let slot = if tail {
+            &mut node.tail
+        } else {
+            &mut node.value
+        };
+        let replaced = slot.replace(value);
+        if replaced.is_none() {
+            self.len += 1;
+        }
+        Ok(replaced)
+    }
+
+    // 删除路径模式，返回被删除的值
+    pub fn remove(&mut self, pattern: &str) -> Option<V> {
+        let segments = pattern.split('/').collect::<Vec<_>>();
+        let removed = self.root.remove(&segments);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    // 匹配请求路径
+    pub fn get(&self, path: &str) -> Option<&V> {
+        self.root.lookup(path.split('/'))
+    }
+}
+
+impl<V> Default for PathMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,175 @@
+use bytes::Bytes;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::{
+    config::{PiiDetector, PiiRedactionConfig},
+    error::AppError,
+    r#const::pii,
+};
+
+// 匹配后的校验，返回 false 时不替换
+type PiiCheck = fn(&str) -> bool;
+
+// 单条脱敏规则
+#[derive(Debug)]
+struct PiiRule {
+    // 规则名称
+    name: String,
+    // 匹配的正则表达式
+    regex: Regex,
+    // 替换文本
+    replacement: String,
+    // 匹配后的校验
+    check: Option<PiiCheck>,
+}
+
+/// 单次请求的脱敏结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PiiRedacted {
+    /// 脱敏后的请求体
+    pub body: Bytes,
+    /// 每条规则的替换次数（只包含有替换的规则）
+    pub counts: Vec<(String, u64)>,
+}
+
+/// PII 脱敏器
+///
+/// 只处理提示词字段中的文本，其他字段（模型名称、参数、图片地址等）保持不变
+#[derive(Debug)]
+pub struct PiiRedactor {
+    // 按配置顺序应用的规则
+    rules: Vec<PiiRule>,
+}
+
+impl PiiRedactor {
+    /// 根据 PII 脱敏配置编译规则
+    pub fn new(config: &PiiRedactionConfig) -> Result<Self, AppError> {
+        let compile = |name: &str, pattern: &str| {
+            Regex::new(pattern).map_err(|e| {
+                AppError::Config(format!("Invalid PII pattern regex '{}': {}", name, e))
+            })
+        };
+        let mut rules = Vec::with_capacity(config.detectors.len() + config.patterns.len());
+        for detector in &config.detectors {
+            let (pattern, replacement, check): (_, _, Option<PiiCheck>) = match detector {
+                PiiDetector::Email => (pii::EMAIL_PATTERN, pii::EMAIL_REPLACEMENT, None),
+                PiiDetector::Phone => (pii::PHONE_PATTERN, pii::PHONE_REPLACEMENT, None),
+                PiiDetector::CreditCard => (
+                    pii::CREDIT_CARD_PATTERN,
+                    pii::CREDIT_CARD_REPLACEMENT,
+                    Some(luhn_valid),
+                ),
+            };
+            rules.push(PiiRule {
+                name: detector.as_str().to_string(),
+                regex: compile(detector.as_str(), pattern)?,
+                replacement: replacement.to_string(),
+                check,
+            });
+        }
+        for pattern in &config.patterns {
+            rules.push(PiiRule {
+                name: pattern.name.clone(),
+                regex: compile(&pattern.name, &pattern.regex)?,
+                replacement: pattern
+                    .replacement
+                    .clone()
+                    .unwrap_or_else(|| pii::DEFAULT_REPLACEMENT.to_string()),
+                check: None,
+            });
+        }
+        Ok(Self { rules })
+    }
+
+    /// 脱敏请求体中的提示词文本，请求体不是 JSON 对象或没有替换时返回 None
+    pub fn redact(&self, body: &[u8]) -> Option<PiiRedacted> {
+        let Ok(Value::Object(mut object)) = serde_json::from_slice::<Value>(body) else {
+            return None;
+        };
+        let mut counts = vec![0; self.rules.len()];
+        for field in pii::PROMPT_FIELDS {
+            if let Some(value) = object.get_mut(field) {
+                self.redact_value(value, &mut counts);
+            }
+        }
+        if counts.iter().all(|count| *count == 0) {
+            return None;
+        }
+
+        let body = serde_json::to_vec(&object).ok()?;
+        Some(PiiRedacted {
+            body: Bytes::from(body),
+            counts: self
+                .rules
+                .iter()
+                .zip(counts)
+                .filter(|(_, count)| *count > 0)
+                .map(|(rule, count)| (rule.name.clone(), count))
+                .collect(),
+        })
+    }
+
+    // 脱敏提示词字段的值，对象只继续处理文本字段
+    fn redact_value(&self, value: &mut Value, counts: &mut [u64]) {
+        match value {
+            Value::String(text) => {
+                if let Some(redacted) = self.redact_text(text, counts) {
+                    *text = redacted;
+                }
+            }
+            Value::Array(items) => items
+                .iter_mut()
+                .for_each(|item| self.redact_value(item, counts)),
+            Value::Object(map) => {
+                for field in pii::TEXT_FIELDS {
+                    if let Some(value) = map.get_mut(field) {
+                        self.redact_value(value, counts);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // 依次应用所有规则，没有替换时返回 None
+    fn redact_text(&self, text: &str, counts: &mut [u64]) -> Option<String> {
+        let mut result: Option<String> = None;
+        for (rule, count) in self.rules.iter().zip(counts.iter_mut()) {
+            let current = result.as_deref().unwrap_or(text);
+            let mut replaced = 0;
+            let redacted = rule.regex.replace_all(current, |caps: &regex::Captures| {
+                let matched = &caps[0];
+                if rule.check.is_some_and(|check| !check(matched)) {
+                    return matched.to_string();
+                }
+                replaced += 1;
+                rule.replacement.clone()
+            });
+            if replaced > 0 {
+                *count += replaced;
+                result = Some(redacted.into_owned());
+            }
+        }
+        result
+    }
+}
+
+// Luhn 校验，排除不是银行卡号的长数字
+fn luhn_valid(text: &str) -> bool {
+    let digits: Vec<u32> = text.chars().filter_map(|c| c.to_digit(10)).collect();
+    if !(13..=19).contains(&digits.len()) {
+        return false;
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, digit)| match i % 2 {
+            1 if digit * 2 > 9 => digit * 2 - 9,
+            1 => digit * 2,
+            _ => *digit,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
@@ -0,0 +1,563 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{
+        header::{CONTENT_LENGTH, CONTENT_TYPE},
+        HeaderMap, HeaderName, HeaderValue, Method, StatusCode,
+    },
+    response::{IntoResponse, Response},
+    Json,
+};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+use tracing::{debug, error, info, warn};
+use wasmi::{
+    Caller, Config, Engine, Extern, Linker, Module, Store, StoreLimits, StoreLimitsBuilder,
+};
+
+use crate::{
+    config::{PluginConfig, PolicyFailMode},
+    error::AppError,
+    metrics::METRICS,
+    r#const::{http_headers::content_types, plugin},
+};
+
+/// 插件的执行阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluginStage {
+    /// 读取请求体之后、其他请求处理之前
+    OnRequest,
+    /// 确定目标上游组之后、转发给上游之前
+    PreUpstream,
+    /// 收到非流式响应之后、返回给客户端之前
+    OnResponse,
+}
+
+impl PluginStage {
+    /// 阶段名称，即插件导出的函数名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::OnRequest => "on_request",
+            Self::PreUpstream => "pre_upstream",
+            Self::OnResponse => "on_response",
+        }
+    }
+
+    const ALL: [PluginStage; 3] = [Self::OnRequest, Self::PreUpstream, Self::OnResponse];
+}
+
+/// 插件调用的请求信息
+#[derive(Debug, Clone, Copy)]
+pub struct PluginContext<'a> {
+    /// 请求 ID
+    pub request_id: &'a str,
+    /// 请求方法
+    pub method: &'a Method,
+    /// 请求路径
+    pub path: &'a str,
+    /// 目标上游组，on_request 阶段为 None
+    pub group: Option<&'a str>,
+}
+
+// 发送给插件的 JSON 输入
+#[derive(Debug, Serialize)]
+struct PluginInput<'a> {
+    stage: &'static str,
+    forward: &'a str,
+    request_id: &'a str,
+    method: &'a str,
+    path: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    headers: BTreeMap<&'a str, &'a str>,
+    body: Value,
+}
+
+/// 插件返回的处理结果
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PluginOutput {
+    /// 处理方式
+    #[serde(default)]
+    pub action: PluginAction,
+    /// 拒绝请求时的响应状态码（默认为 403），on_response 阶段为新的响应状态码
+    #[serde(default)]
+    pub status: Option<u16>,
+    /// 设置的请求头或响应头
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// 新的请求体或响应体，字符串按文本发送，其他值按 JSON 发送
+    #[serde(default)]
+    pub body: Option<Value>,
+}
+
+/// 插件结果的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PluginAction {
+    /// 应用修改后继续处理
+    #[default]
+    Continue,
+    /// 拒绝请求，以 status 和 body 响应客户端
+    Reject,
+}
+
+/// 被插件拒绝的请求
+#[derive(Debug)]
+pub struct PluginRejected {
+    /// 拒绝请求的插件名称
+    pub plugin: String,
+    /// 响应状态码
+    pub status: StatusCode,
+    /// 响应体
+    pub body: Option<Value>,
+    /// 是否因插件执行出错而拒绝
+    pub error: bool,
+}
+
+impl IntoResponse for PluginRejected {
+    // 插件出错时返回 OpenAI 格式的错误，否则返回插件提供的响应体
+    fn into_response(self) -> Response {
+        let body = match self.body {
+            _ if self.error => json!({"error": {
+                "message": plugin::ERROR_MESSAGE,
+                "type": plugin::ERROR_TYPE,
+            }}),
+            Some(Value::String(text)) => return (self.status, text).into_response(),
+            Some(body) => body,
+            None => return self.status.into_response(),
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+// 插件实例的宿主数据
+struct PluginData {
+    // 插件名称
+    name: String,
+    // 内存限制
+    limits: StoreLimits,
+}
+
+// 已编译的插件
+struct Plugin {
+    // 插件名称
+    name: String,
+    // 插件模块
+    module: Module,
+    // 宿主函数
+    linker: Linker<PluginData>,
+    // 每次调用的燃料
+    fuel: u64,
+    // 最大线性内存（字节）
+    max_memory_bytes: usize,
+    // 执行出错时的处理方式
+    fail_mode: PolicyFailMode,
+    // 插件导出的阶段
+    stages: Vec<PluginStage>,
+}
+
+impl Plugin {
+    // 读取并编译插件，插件必须导出 memory、alloc 和至少一个阶段函数
+    fn load(engine: &Engine, config: &PluginConfig) -> Result<Self, AppError> {
+        let invalid = |reason: String| {
+            AppError::Config(format!(
+                "Invalid plugin '{}' ({}): {}",
+                config.name, config.path, reason
+            ))
+        };
+        let wasm = std::fs::read(&config.path).map_err(|e| invalid(e.to_string()))?;
+        let module = Module::new(engine, &wasm).map_err(|e| invalid(e.to_string()))?;
+
+        let exports: Vec<&str> = module.exports().map(|export| export.name()).collect();
+        let stages: Vec<PluginStage> = PluginStage::ALL
+            .into_iter()
+            .filter(|stage| exports.contains(&stage.as_str()))
+            .collect();
+        if stages.is_empty() {
+            return Err(invalid(
+                "no on_request, pre_upstream or on_response export".to_string(),
+            ));
+        }
+        for name in [plugin::MEMORY_EXPORT, plugin::ALLOC_EXPORT] {
+            if !exports.contains(&name) {
+                return Err(invalid(format!("missing {} export", name)));
+            }
+        }
+
+        let mut linker = Linker::new(engine);
+        linker
+            .func_wrap(plugin::HOST_MODULE, "log", host_log)
+            .map_err(|e| invalid(e.to_string()))?;
+        Ok(Self {
+            name: config.name.clone(),
+            module,
+            linker,
+            fuel: config.fuel,
+            max_memory_bytes: config.max_memory_bytes,
+            fail_mode: config.fail_mode,
+            stages,
+        })
+    }
+
+    // 在新的实例中调用阶段函数，插件未做修改时返回 None
+    fn call(&self, stage: PluginStage, input: &[u8]) -> Result<Option<PluginOutput>, String> {
+        let mut store = Store::new(
+            self.module.engine(),
+            PluginData {
+                name: self.name.clone(),
+                limits: StoreLimitsBuilder::new()
+                    .memory_size(self.max_memory_bytes)
+                    .build(),
+            },
+        );
+        store.limiter(|data| &mut data.limits);
+        store.set_fuel(self.fuel).map_err(|e| e.to_string())?;
+
+        let instance = self
+            .linker
+            .instantiate(&mut store, &self.module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(|e| e.to_string())?;
+        let memory = instance
+            .get_memory(&store, plugin::MEMORY_EXPORT)
+            .ok_or("memory export is not a memory")?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&store, plugin::ALLOC_EXPORT)
+            .map_err(|e| e.to_string())?;
+        let hook = instance
+            .get_typed_func::<(i32, i32), i64>(&store, stage.as_str())
+            .map_err(|e| e.to_string())?;
+
+        // 输入写入插件分配的内存，返回值的高 32 位为输出的地址，低 32 位为输出的长度
+        let len = i32::try_from(input.len()).map_err(|_| "input too large")?;
+        let ptr = alloc.call(&mut store, len).map_err(|e| e.to_string())?;
+        memory
+            .write(&mut store, ptr as u32 as usize, input)
+            .map_err(|e| e.to_string())?;
+        let result = hook
+            .call(&mut store, (ptr, len))
+            .map_err(|e| e.to_string())? as u64;
+        if result == 0 {
+            return Ok(None);
+        }
+        let (ptr, len) = ((result >> 32) as usize, (result & 0xffff_ffff) as usize);
+        let output = memory
+            .data(&store)
+            .get(ptr..ptr.saturating_add(len))
+            .ok_or("output out of bounds")?;
+        serde_json::from_slice(output)
+            .map(Some)
+            .map_err(|e| format!("invalid output: {}", e))
+    }
+}
+
+// 宿主函数 llmproxy.log(level, ptr, len)，level 为 0（debug）到 3（error）
+fn host_log(caller: Caller<'_, PluginData>, level: i32, ptr: i32, len: i32) {
+    let Some(memory) = caller
+        .get_export(plugin::MEMORY_EXPORT)
+        .and_then(Extern::into_memory)
+    else {
+        return;
+    };
+    let (ptr, len) = (ptr as u32 as usize, len as u32 as usize);
+    let Some(message) = memory.data(&caller).get(ptr..ptr.saturating_add(len)) else {
+        return;
+    };
+    let message = String::from_utf8_lossy(message);
+    let name = &caller.data().name;
+    match level {
+        0 => debug!("Plugin {:?}: {}", name, message),
+        1 => info!("Plugin {:?}: {}", name, message),
+        2 => warn!("Plugin {:?}: {}", name, message),
+        _ => error!("Plugin {:?}: {}", name, message),
+    }
+}
+
+/// 转发服务的 WASM 插件链
+///
+/// 按配置顺序在各阶段调用导出了该阶段函数的插件。插件以 JSON 读取请求或响应，
+/// 返回的 JSON 可以设置头部、替换请求体或响应体，或者拒绝请求。
+/// 插件在阻塞线程池中执行，燃料或内存用尽、返回无效结果时按 fail_mode 跳过插件或返回 500
+pub struct PluginChain {
+    // 转发服务名称
+    forward: String,
+    // 按配置顺序排列的插件
+    plugins: Vec<Arc<Plugin>>,
+}
+
+impl PluginChain {
+    /// 读取并编译转发服务配置的插件
+    pub fn new(forward: &str, configs: &[PluginConfig]) -> Result<Self, AppError> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let plugins = configs
+            .iter()
+            .map(|config| Plugin::load(&engine, config).map(Arc::new))
+            .collect::<Result<Vec<_>, _>>()?;
+        for plugin in &plugins {
+            info!(
+                "Loaded plugin {:?} of forwarding service {:?} with stages {:?}",
+                plugin.name,
+                forward,
+                plugin
+                    .stages
+                    .iter()
+                    .map(PluginStage::as_str)
+                    .collect::<Vec<_>>()
+            );
+        }
+        Ok(Self {
+            forward: forward.to_string(),
+            plugins,
+        })
+    }
+
+    /// 是否有插件处理该阶段
+    pub fn has_stage(&self, stage: PluginStage) -> bool {
+        self.plugins
+            .iter()
+            .any(|plugin| plugin.stages.contains(&stage))
+    }
+
+    /// 在请求阶段调用插件，修改请求头和请求体，插件拒绝请求时返回错误
+    pub async fn process_request(
+        &self,
+        stage: PluginStage,
+        context: &PluginContext<'_>,
+        headers: &mut HeaderMap,
+        body: &mut Option<Bytes>,
+    ) -> Result<(), PluginRejected> {
+        for plugin in self.plugins_for(stage) {
+            let input = self.input(stage, context, None, headers, body.as_deref());
+            let output = match self.run(plugin, stage, input).await {
+                Ok(Some(output)) => output,
+                Ok(None) => {
+                    self.record(plugin, stage, plugin::CONTINUE);
+                    continue;
+                }
+                Err(e) => {
+                    self.fail(plugin, stage, e)?;
+                    continue;
+                }
+            };
+            if output.action == PluginAction::Reject {
+                self.record(plugin, stage, plugin::REJECT);
+                let status = output
+                    .status
+                    .and_then(|status| StatusCode::from_u16(status).ok())
+                    .unwrap_or(StatusCode::from_u16(plugin::DEFAULT_REJECT_STATUS).unwrap());
+                return Err(PluginRejected {
+                    plugin: plugin.name.clone(),
+                    status,
+                    body: output.body,
+                    error: false,
+                });
+            }
+            let changed = match apply_headers(headers, output.headers) {
+                Ok(changed) => changed,
+                Err(e) => {
+                    self.fail(plugin, stage, e)?;
+                    continue;
+                }
+            };
+            let result = match output.body {
+                Some(new_body) => {
+                    *body = Some(body_bytes(new_body));
+                    headers.remove(CONTENT_LENGTH);
+                    plugin::MODIFY
+                }
+                None if changed => plugin::MODIFY,
+                None => plugin::CONTINUE,
+            };
+            self.record(plugin, stage, result);
+        }
+        Ok(())
+    }
+
+    /// 在响应阶段调用插件，修改非流式响应的状态码、响应头和响应体
+    pub async fn process_response(
+        &self,
+        context: &PluginContext<'_>,
+        response: Response,
+    ) -> Response {
+        let stage = PluginStage::OnResponse;
+        if !self.has_stage(stage) || super::utils::is_unbuffered_response(&response) {
+            return response;
+        }
+
+        let (mut parts, body) = response.into_parts();
+        let mut body = match to_bytes(body, usize::MAX).await {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("Failed to read response body for plugins: {}", e);
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+        for plugin in self.plugins_for(stage) {
+            let input = self.input(
+                stage,
+                context,
+                Some(parts.status),
+                &parts.headers,
+                Some(&body),
+            );
+            let output = match self.run(plugin, stage, input).await {
+                Ok(Some(output)) => output,
+                Ok(None) => {
+                    self.record(plugin, stage, plugin::CONTINUE);
+                    continue;
+                }
+                Err(e) => match self.fail(plugin, stage, e) {
+                    Ok(()) => continue,
+                    Err(rejected) => return rejected.into_response(),
+                },
+            };
+            if let Some(status) = output.status {
+                match StatusCode::from_u16(status) {
+                    Ok(status) => parts.status = status,
+                    Err(_) => {
+                        if let Err(rejected) =
+                            self.fail(plugin, stage, format!("invalid status {}", status))
+                        {
+                            return rejected.into_response();
+                        }
+                        continue;
+                    }
+                }
+            }
+            if let Err(e) = apply_headers(&mut parts.headers, output.headers) {
+                if let Err(rejected) = self.fail(plugin, stage, e) {
+                    return rejected.into_response();
+                }
+                continue;
+            }
+            if let Some(new_body) = output.body {
+                if !matches!(new_body, Value::String(_)) {
+                    parts
+                        .headers
+                        .insert(CONTENT_TYPE, HeaderValue::from_static(content_types::JSON));
+                }
+                body = body_bytes(new_body);
+                parts.headers.remove(CONTENT_LENGTH);
+            }
+            let result = match output.action {
+                PluginAction::Reject => plugin::REJECT,
+                PluginAction::Continue => plugin::MODIFY,
+            };
+            self.record(plugin, stage, result);
+        }
+        Response::from_parts(parts, Body::from(body))
+    }
+
+    // 导出了该阶段函数的插件
+    fn plugins_for(&self, stage: PluginStage) -> impl Iterator<Item = &Arc<Plugin>> {
+        self.plugins
+            .iter()
+            .filter(move |plugin| plugin.stages.contains(&stage))
+    }
+
+    // 序列化发送给插件的输入
+    fn input(
+        &self,
+        stage: PluginStage,
+        context: &PluginContext<'_>,
+        status: Option<StatusCode>,
+        headers: &HeaderMap,
+        body: Option<&[u8]>,
+    ) -> Vec<u8> {
+        let input = PluginInput {
+            stage: stage.as_str(),
+            forward: &self.forward,
+            request_id: context.request_id,
+            method: context.method.as_str(),
+            path: context.path,
+            group: context.group,
+            status: status.map(|status| status.as_u16()),
+            headers: headers
+                .iter()
+                .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)))
+                .collect(),
+            body: match body {
+                None | Some([]) => Value::Null,
+                Some(body) => serde_json::from_slice(body)
+                    .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(body).into_owned())),
+            },
+        };
+        serde_json::to_vec(&input).unwrap_or_default()
+    }
+
+    // 在阻塞线程池中调用插件，避免执行时间较长的插件阻塞异步任务
+    async fn run(
+        &self,
+        plugin: &Arc<Plugin>,
+        stage: PluginStage,
+        input: Vec<u8>,
+    ) -> Result<Option<PluginOutput>, String> {
+        let plugin = plugin.clone();
+        tokio::task::spawn_blocking(move || plugin.call(stage, &input))
+            .await
+            .map_err(|e| e.to_string())?
+    }
+
+    // 记录插件执行出错，fail_mode 为 closed 时拒绝请求
+    fn fail(
+        &self,
+        plugin: &Plugin,
+        stage: PluginStage,
+        error: String,
+    ) -> Result<(), PluginRejected> {
+        warn!(
+            "Plugin {:?} of forwarding service {:?} failed at {}: {}",
+            plugin.name,
+            self.forward,
+            stage.as_str(),
+            error
+        );
+        self.record(plugin, stage, plugin::ERROR);
+        match plugin.fail_mode {
+            PolicyFailMode::Open => Ok(()),
+            PolicyFailMode::Closed => Err(PluginRejected {
+                plugin: plugin.name.clone(),
+                status: StatusCode::INTERNAL_SERVER_ERROR,
+                body: None,
+                error: true,
+            }),
+        }
+    }
+
+    // 记录插件调用结果
+    fn record(&self, plugin: &Plugin, stage: PluginStage, result: &str) {
+        METRICS
+            .plugin_calls_total()
+            .with_label_values(&[&self.forward, &plugin.name, stage.as_str(), result])
+            .inc();
+    }
+}
+
+// 设置插件返回的头部，返回是否有头部
+fn apply_headers(headers: &mut HeaderMap, values: HashMap<String, String>) -> Result<bool, String> {
+    let changed = !values.is_empty();
+    for (name, value) in values {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| format!("invalid header name {:?}", name))?;
+        let value = HeaderValue::from_str(&value)
+            .map_err(|_| format!("invalid value of header {:?}", name))?;
+        headers.insert(name, value);
+    }
+    Ok(changed)
+}
+
+// 插件返回的请求体或响应体，字符串按文本发送，其他值按 JSON 发送
+fn body_bytes(body: Value) -> Bytes {
+    match body {
+        Value::String(text) => Bytes::from(text),
+        body => Bytes::from(body.to_string()),
+    }
+}
@@ -0,0 +1,282 @@
+use axum::{
+    http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{collections::HashMap, net::IpAddr, time::Duration};
+use tracing::warn;
+
+use crate::{
+    config::{PolicyConfig, PolicyFailMode, PolicyPayload},
+    error::AppError,
+    metrics::METRICS,
+    r#const::{pii, policy},
+};
+
+/// 发送给策略服务的请求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyRequest {
+    /// 转发服务名称
+    pub forward: String,
+    /// 请求 ID
+    pub request_id: String,
+    /// 客户端 IP
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_ip: Option<String>,
+    /// 请求方法
+    pub method: String,
+    /// 请求路径
+    pub path: String,
+    /// 请求体，payload 为 request 时发送，不是 JSON 时为文本
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+    /// 提示词文本，payload 为 prompt 时发送
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+}
+
+/// 策略服务的裁决
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyVerdict {
+    /// 处理方式
+    pub action: PolicyAction,
+    /// 拒绝原因，返回给客户端
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// 拒绝请求时的响应状态码，默认为 403
+    #[serde(default)]
+    pub status: Option<u16>,
+    /// 改写后的请求体，action 为 modify 时必填
+    #[serde(default)]
+    pub body: Option<Value>,
+    /// 添加到转发请求的请求头
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+/// 策略服务裁决的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyAction {
+    /// 放行请求
+    Allow,
+    /// 拒绝请求
+    Block,
+    /// 改写请求体后放行
+    Modify,
+}
+
+/// 放行的请求需要做的修改
+#[derive(Debug, Default)]
+pub struct PolicyAllowed {
+    /// 改写后的请求体
+    pub body: Option<Bytes>,
+    /// 添加到转发请求的请求头
+    pub headers: Vec<(HeaderName, HeaderValue)>,
+}
+
+/// 被拒绝的请求
+#[derive(Debug)]
+pub struct PolicyBlocked {
+    /// 响应状态码
+    pub status: StatusCode,
+    /// 错误信息
+    pub message: String,
+    /// 是否因策略服务不可用而拒绝
+    pub unavailable: bool,
+}
+
+impl IntoResponse for PolicyBlocked {
+    // 返回 OpenAI 格式的错误
+    fn into_response(self) -> Response {
+        let body = json!({"error": {
+            "message": self.message,
+            "type": policy::ERROR_TYPE,
+        }});
+        (self.status, Json(body)).into_response()
+    }
+}
+
+/// 外部策略服务客户端
+///
+/// 转发前将请求发送给策略服务，按返回的裁决放行、拒绝、改写请求体或添加请求头。
+/// 策略服务超时、返回非 2xx 状态码或无效的裁决时按 fail_mode 放行或返回 503
+pub struct PolicyClient {
+    // 转发服务名称
+    forward: String,
+    // HTTP 客户端
+    client: reqwest::Client,
+    // 策略服务配置
+    config: PolicyConfig,
+}
+
+impl PolicyClient {
+    /// 按策略服务配置创建客户端
+    pub fn new(forward: &str, config: &PolicyConfig) -> Result<Self, AppError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()
+            .map_err(|e| AppError::Config(format!("Failed to create policy client: {}", e)))?;
+        Ok(Self {
+            forward: forward.to_string(),
+            client,
+            config: config.clone(),
+        })
+    }
+
+    /// 创建发送给策略服务的请求，按配置附带请求体或提示词文本
+    pub fn request(
+        &self,
+        request_id: &str,
+        client_ip: Option<IpAddr>,
+        method: &Method,
+        path: &str,
+        body: Option<&Bytes>,
+    ) -> PolicyRequest {
+        let (body, prompt) =
+            match (self.config.payload, body) {
+                (PolicyPayload::Request, Some(body)) => (
+                    Some(serde_json::from_slice(body).unwrap_or_else(|_| {
+                        Value::String(String::from_utf8_lossy(body).into_owned())
+                    })),
+                    None,
+                ),
+                (PolicyPayload::Prompt, body) => {
+                    (None, Some(body.map(prompt_text).unwrap_or_default()))
+                }
+                (PolicyPayload::Request, None) => (None, None),
+            };
+        PolicyRequest {
+            forward: self.forward.clone(),
+            request_id: request_id.to_string(),
+            client_ip: client_ip.map(|ip| ip.to_string()),
+            method: method.to_string(),
+            path: path.to_string(),
+            body,
+            prompt,
+        }
+    }
+
+    /// 调用策略服务并返回裁决结果
+    pub async fn check(&self, request: &PolicyRequest) -> Result<PolicyAllowed, PolicyBlocked> {
+        let (label, result) = match self.call(request).await.and_then(Self::decide) {
+            Ok(Ok(allowed)) if allowed.body.is_some() => (policy::MODIFY, Ok(allowed)),
+            Ok(Ok(allowed)) => (policy::ALLOW, Ok(allowed)),
+            Ok(Err(blocked)) => (policy::BLOCK, Err(blocked)),
+            Err(e) => {
+                warn!(
+                    "Policy service of forwarding service {:?} failed for request {}: {}",
+                    self.forward, request.request_id, e
+                );
+                let result = match self.config.fail_mode {
+                    PolicyFailMode::Open => Ok(PolicyAllowed::default()),
+                    PolicyFailMode::Closed => Err(PolicyBlocked {
+                        status: StatusCode::SERVICE_UNAVAILABLE,
+                        message: policy::UNAVAILABLE_MESSAGE.to_string(),
+                        unavailable: true,
+                    }),
+                };
+                (policy::ERROR, result)
+            }
+        };
+        METRICS
+            .policy_decisions_total()
+            .with_label_values(&[&self.forward, label])
+            .inc();
+        result
+    }
+
+    // 发送请求并解析裁决
+    async fn call(&self, request: &PolicyRequest) -> Result<PolicyVerdict, String> {
+        let mut builder = self.client.post(self.config.url.as_str()).json(request);
+        if let Some(token) = &self.config.token {
+            builder = builder.bearer_auth(token);
+        }
+        let response = builder.send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("HTTP status {}", response.status()));
+        }
+        response
+            .json::<PolicyVerdict>()
+            .await
+            .map_err(|e| format!("invalid verdict: {}", e))
+    }
+
+    // 将裁决转换为放行或拒绝，裁决无效时返回错误
+    fn decide(verdict: PolicyVerdict) -> Result<Result<PolicyAllowed, PolicyBlocked>, String> {
+        if verdict.action == PolicyAction::Block {
+            let status = verdict
+                .status
+                .and_then(|status| StatusCode::from_u16(status).ok())
+                .filter(|status| status.is_client_error() || status.is_server_error())
+                .unwrap_or(StatusCode::from_u16(policy::DEFAULT_BLOCK_STATUS).unwrap());
+            return Ok(Err(PolicyBlocked {
+                status,
+                message: verdict
+                    .reason
+                    .unwrap_or_else(|| policy::DEFAULT_BLOCK_MESSAGE.to_string()),
+                unavailable: false,
+            }));
+        }
+
+        let body = match (verdict.action, verdict.body) {
+            (PolicyAction::Modify, Some(body)) => Some(Bytes::from(body.to_string())),
+            (PolicyAction::Modify, None) => return Err("modify verdict without body".to_string()),
+            _ => None,
+        };
+        let headers = verdict
+            .headers
+            .into_iter()
+            .map(|(name, value)| {
+                let name = HeaderName::from_bytes(name.as_bytes())
+                    .map_err(|_| format!("invalid header name {:?}", name))?;
+                let value = HeaderValue::from_str(&value)
+                    .map_err(|_| format!("invalid value of header {:?}", name))?;
+                Ok((name, value))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Ok(PolicyAllowed { body, headers }))
+    }
+}
+
+impl PolicyAllowed {
+    /// 将策略服务添加的请求头写入转发请求
+    pub fn apply_headers(&mut self, headers: &mut HeaderMap) {
+        for (name, value) in self.headers.drain(..) {
+            headers.insert(name, value);
+        }
+    }
+}
+
+// 提取请求体提示词字段中的文本，每段文本一行
+fn prompt_text(body: &Bytes) -> String {
+    let Ok(Value::Object(object)) = serde_json::from_slice::<Value>(body) else {
+        return String::from_utf8_lossy(body).into_owned();
+    };
+    let mut texts = Vec::new();
+    for field in pii::PROMPT_FIELDS {
+        if let Some(value) = object.get(field) {
+            collect_text(value, &mut texts);
+        }
+    }
+    texts.join("\n")
+}
+
+// 收集提示词字段中的文本，对象只继续查找文本字段
+fn collect_text<'a>(value: &'a Value, texts: &mut Vec<&'a str>) {
+    match value {
+        Value::String(text) => texts.push(text),
+        Value::Array(items) => items.iter().for_each(|item| collect_text(item, texts)),
+        Value::Object(map) => {
+            for field in pii::TEXT_FIELDS {
+                if let Some(value) = map.get(field) {
+                    collect_text(value, texts);
+                }
+            }
+        }
+        _ => {}
+    }
+}
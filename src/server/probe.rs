@@ -0,0 +1,39 @@
+use axum::{extract::State, http::StatusCode, routing::get, Router};
+use std::sync::Arc;
+
+use crate::r#const::forward_paths;
+
+use super::forward::ForwardState;
+
+/// 创建转发服务的探针路由
+///
+/// 存活探针在服务能够处理请求时返回 200。就绪探针在转发服务已启用、且默认上游组至少有一个
+/// 可用的上游服务时返回 200，否则返回 503，供 Kubernetes 等编排系统摘除流量
+pub(super) fn probe_router(state: Arc<ForwardState>) -> Router {
+    Router::new()
+        .route(forward_paths::HEALTHZ, get(healthz_handler))
+        .route(forward_paths::READYZ, get(readyz_handler))
+        .with_state(state)
+}
+
+// 存活探针
+async fn healthz_handler() -> &'static str {
+    "OK"
+}
+
+// 就绪探针
+async fn readyz_handler(State(state): State<Arc<ForwardState>>) -> (StatusCode, &'static str) {
+    if !state.is_enabled() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Forwarding service disabled",
+        );
+    }
+    if !state
+        .upstream_manager
+        .has_available_upstream(&state.config.default_group)
+    {
+        return (StatusCode::SERVICE_UNAVAILABLE, "No available upstream");
+    }
+    (StatusCode::OK, "OK")
+}
@@ -0,0 +1,286 @@
+use super::redis_bucket::{BucketState, RedisBuckets};
+use crate::{
+    config::{RateLimitConfig, RateLimitKey, RedisConfig},
+    error::AppError,
+    events::RateLimitReporter,
+    metrics::METRICS,
+    r#const::{api::auth::BEARER_PREFIX, rate_limit_headers, rate_limit_keys, rate_limit_limits},
+};
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, State},
+    http::{
+        header::{AUTHORIZATION, RETRY_AFTER},
+        HeaderMap, HeaderName, HeaderValue, Request, StatusCode,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use dashmap::DashMap;
+use std::{
+    fmt,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Instant,
+};
+use tower_governor::{key_extractor::KeyExtractor, GovernorError};
+use tracing::warn;
+use xxhash_rust::xxh3::xxh3_64;
+
+// 限流桶的键
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClientKey {
+    // 客户端 IP
+    Ip(IpAddr),
+    // 请求头或 API 密钥的哈希值，不在内存中保存原始密钥
+    Hash(u64),
+}
+
+impl fmt::Display for ClientKey {
+    // Redis 令牌桶的键，不同实例计算出的键相同
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientKey::Ip(ip) => write!(f, "ip:{}", ip),
+            ClientKey::Hash(hash) => write!(f, "key:{:016x}", hash),
+        }
+    }
+}
+
+/// 按客户端提取限流键
+///
+/// 请求头或 API 密钥缺失时回退为客户端 IP，因此匿名请求仍按 IP 独立限流
+#[derive(Debug, Clone)]
+pub struct ClientKeyExtractor {
+    // 限流键类型
+    key: RateLimitKey,
+    // 限流键为 header 时使用的请求头
+    header: Option<HeaderName>,
+}
+
+impl ClientKeyExtractor {
+    /// 根据限流键类型和请求头名称创建键提取器
+    pub fn new(key: RateLimitKey, header: Option<&str>) -> Self {
+        Self {
+            key,
+            header: header.and_then(|header| HeaderName::from_bytes(header.as_bytes()).ok()),
+        }
+    }
+
+    // 根据请求头和客户端 IP 计算限流键，两者都缺失时返回 None
+    pub(super) fn key(&self, headers: &HeaderMap, ip: Option<IpAddr>) -> Option<ClientKey> {
+        match self.value(headers) {
+            Some(value) => Some(ClientKey::Hash(xxh3_64(value.as_bytes()))),
+            None => ip.map(ClientKey::Ip),
+        }
+    }
+
+    // 读取请求头或 API 密钥的值，未携带时返回 None
+    pub(super) fn value<'a>(&self, headers: &'a HeaderMap) -> Option<&'a str> {
+        let value = match self.key {
+            RateLimitKey::Ip => return None,
+            RateLimitKey::Header => headers.get(self.header.as_ref()?)?.to_str().ok()?,
+            RateLimitKey::ApiKey => {
+                match headers
+                    .get(AUTHORIZATION)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.trim().strip_prefix(BEARER_PREFIX))
+                {
+                    Some(token) => token,
+                    None => rate_limit_keys::API_KEY_HEADERS
+                        .iter()
+                        .find_map(|name| headers.get(*name))?
+                        .to_str()
+                        .ok()?,
+                }
+            }
+        };
+        let value = value.trim();
+        (!value.is_empty()).then_some(value)
+    }
+}
+
+impl KeyExtractor for ClientKeyExtractor {
+    type Key = ClientKey;
+
+    fn extract<T>(&self, req: &Request<T>) -> Result<Self::Key, GovernorError> {
+        let ip = req
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        self.key(req.headers(), ip)
+            .ok_or(GovernorError::UnableToExtractKey)
+    }
+}
+
+// 本地令牌桶，Redis 不可用时按实例限流
+#[derive(Debug)]
+struct LocalBucket {
+    // 可用的令牌数
+    available: f64,
+    // 上次恢复的时间
+    updated: Instant,
+}
+
+/// 分布式请求速率限制器
+///
+/// 令牌桶保存在 Redis 中，多个实例共享同一个客户端的令牌桶。Redis 不可用时回退为本地令牌桶，
+/// 限流只在当前实例内生效
+#[derive(Debug)]
+pub struct DistributedRateLimiter {
+    // 转发服务名称
+    forward: String,
+    // 每秒请求数
+    per_second: f64,
+    // 突发请求上限
+    burst: f64,
+    // 限流键提取器
+    extractor: ClientKeyExtractor,
+    // Redis 令牌桶
+    redis: RedisBuckets,
+    // Redis 不可用时使用的本地令牌桶
+    local: DashMap<Option<ClientKey>, LocalBucket>,
+    // 限流事件上报器
+    reporter: RateLimitReporter,
+}
+
+impl DistributedRateLimiter {
+    /// 根据限流配置和 Redis 连接配置创建限流器，不建立连接
+    pub fn new(
+        forward: &str,
+        config: &RateLimitConfig,
+        redis: &RedisConfig,
+    ) -> Result<Self, AppError> {
+        Ok(Self {
+            forward: forward.to_string(),
+            per_second: config.per_second as f64,
+            burst: config.burst as f64,
+            extractor: ClientKeyExtractor::new(config.key, config.header.as_deref()),
+            redis: RedisBuckets::new(redis, format!("ratelimit:{}", forward))?,
+            local: DashMap::new(),
+            reporter: RateLimitReporter::default(),
+        })
+    }
+
+    /// 扣除一个请求，令牌桶不足时返回 false。客户端标识和客户端 IP 都缺失的请求共享同一个令牌桶
+    pub async fn check(&self, headers: &HeaderMap, client_ip: Option<IpAddr>) -> bool {
+        self.take(headers, client_ip).await.allowed
+    }
+
+    // 扣除一个请求，返回是否放行和令牌桶中剩余的令牌数
+    async fn take(&self, headers: &HeaderMap, client_ip: Option<IpAddr>) -> BucketState {
+        let key = self.extractor.key(headers, client_ip);
+        let redis_key = key.map_or_else(|| "-".to_string(), |key| key.to_string());
+        match self
+            .redis
+            .take(&redis_key, self.burst, self.per_second, 1.0, 1.0, false)
+            .await
+        {
+            Ok(state) => state,
+            Err(e) => {
+                warn!(
+                    "Distributed rate limit of forwarding service {:?} falls back to local: {}",
+                    self.forward, e
+                );
+                self.check_local(key)
+            }
+        }
+    }
+
+    // 使用本地令牌桶扣除一个请求
+    fn check_local(&self, key: Option<ClientKey>) -> BucketState {
+        let now = Instant::now();
+        // 令牌桶过多时清理已完全恢复的令牌桶，与新建的令牌桶等价
+        if self.local.len() >= rate_limit_limits::MAX_LOCAL_BUCKETS
+            && !self.local.contains_key(&key)
+        {
+            self.local.retain(|_, bucket| {
+                bucket.available
+                    + now.duration_since(bucket.updated).as_secs_f64() * self.per_second
+                    < self.burst
+            });
+        }
+        let mut bucket = self.local.entry(key).or_insert_with(|| LocalBucket {
+            available: self.burst,
+            updated: now,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.available = (bucket.available + elapsed * self.per_second).min(self.burst);
+        bucket.updated = now;
+        let allowed = bucket.available >= 1.0;
+        if allowed {
+            bucket.available -= 1.0;
+        }
+        BucketState {
+            allowed,
+            available: bucket.available,
+        }
+    }
+
+    // 添加限流响应头，被限流时 Retry-After 为令牌桶恢复一个请求所需的秒数
+    fn insert_headers(&self, headers: &mut HeaderMap, state: BucketState) {
+        headers.insert(
+            rate_limit_headers::LIMIT,
+            HeaderValue::from(self.burst as u64),
+        );
+        headers.insert(
+            rate_limit_headers::REMAINING,
+            HeaderValue::from(state.available.max(0.0) as u64),
+        );
+        if !state.allowed {
+            let retry_after = (((1.0 - state.available) / self.per_second).ceil() as u64).max(1);
+            headers.insert(RETRY_AFTER, HeaderValue::from(retry_after));
+            headers.insert(rate_limit_headers::AFTER, HeaderValue::from(retry_after));
+        }
+    }
+}
+
+/// 本地限流的 429 响应，保留限流器生成的限流响应头
+///
+/// Retry-After 按整秒向下取整，等待时间不足一秒时设为 1，避免客户端立即重试
+pub(super) fn rate_limited_response(error: GovernorError) -> Response {
+    match error {
+        GovernorError::TooManyRequests { wait_time, headers } => {
+            let mut response = StatusCode::TOO_MANY_REQUESTS.into_response();
+            if let Some(headers) = headers {
+                *response.headers_mut() = headers;
+            }
+            let retry_after = HeaderValue::from(wait_time.max(1));
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, retry_after.clone());
+            response
+                .headers_mut()
+                .insert(rate_limit_headers::AFTER, retry_after);
+            response
+        }
+        _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+/// 分布式请求速率限制中间件，超出限制时返回 429，放行和拒绝的响应都带有限流响应头
+pub(super) async fn distributed_rate_limit(
+    State(limiter): State<Arc<DistributedRateLimiter>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let client_ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let state = limiter.take(request.headers(), client_ip).await;
+    if state.allowed {
+        let mut response = next.run(request).await;
+        limiter.insert_headers(response.headers_mut(), state);
+        return response;
+    }
+
+    // 记录限流指标
+    METRICS
+        .ratelimit_total()
+        .with_label_values(&[&limiter.forward])
+        .inc();
+    limiter.reporter.record(&limiter.forward);
+    let mut response = StatusCode::TOO_MANY_REQUESTS.into_response();
+    limiter.insert_headers(response.headers_mut(), state);
+    response
+}
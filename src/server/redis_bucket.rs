@@ -0,0 +1,87 @@
+use crate::{config::RedisConfig, error::AppError, redis_client::RedisClient};
+
+// 令牌桶脚本，在 Redis 中原子地恢复和扣除令牌
+//
+// KEYS[1] 为令牌桶的键，ARGV 依次为容量、每秒恢复的令牌数、放行需要的令牌数、扣除的令牌数、是否强制扣除。
+// 强制扣除用于按实际用量修正，扣除数为负数时返还令牌。返回是否放行和扣除后可用的令牌数
+const TAKE_SCRIPT: &str = r#"
+local capacity = tonumber(ARGV[1])
+local rate = tonumber(ARGV[2])
+local needed = tonumber(ARGV[3])
+local cost = tonumber(ARGV[4])
+local force = ARGV[5] == '1'
+local time = redis.call('TIME')
+local now = tonumber(time[1]) + tonumber(time[2]) / 1000000
+local state = redis.call('HMGET', KEYS[1], 'tokens', 'updated')
+local tokens = tonumber(state[1]) or capacity
+local updated = tonumber(state[2]) or now
+tokens = math.min(capacity, tokens + math.max(0, now - updated) * rate)
+local allowed = force or (tokens > 0 and tokens >= needed)
+if allowed then
+  tokens = math.min(capacity, tokens - cost)
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated', tostring(now))
+redis.call('EXPIRE', KEYS[1], math.ceil((capacity - tokens) / rate) + 1)
+if allowed then
+  return {1, tostring(tokens)}
+end
+return {0, tostring(tokens)}
+"#;
+
+/// 令牌桶扣除结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct BucketState {
+    // 是否放行
+    pub allowed: bool,
+    // 扣除后可用的令牌数
+    pub available: f64,
+}
+
+/// Redis 令牌桶，多个实例共享同一个键的令牌桶
+///
+/// 令牌桶完全恢复后键自动过期，与新建的令牌桶等价
+#[derive(Debug)]
+pub(super) struct RedisBuckets {
+    // Redis 客户端
+    client: RedisClient,
+    // 键前缀，区分不同的转发服务和限流类型
+    scope: String,
+}
+
+impl RedisBuckets {
+    pub fn new(config: &RedisConfig, scope: String) -> Result<Self, AppError> {
+        Ok(Self {
+            client: RedisClient::new(config)?,
+            scope,
+        })
+    }
+
+    /// 恢复令牌后扣除 cost 个令牌，可用令牌不足 needed 时不扣除；force 为 true 时总是扣除
+    pub async fn take(
+        &self,
+        key: &str,
+        capacity: f64,
+        rate: f64,
+        needed: f64,
+        cost: f64,
+        force: bool,
+    ) -> Result<BucketState, AppError> {
+        let mut connection = self.client.connection().await?;
+        let (allowed, available): (i64, String) = redis::cmd("EVAL")
+            .arg(TAKE_SCRIPT)
+            .arg(1)
+            .arg(self.client.key(&format!("{}:{}", self.scope, key)))
+            .arg(capacity)
+            .arg(rate)
+            .arg(needed)
+            .arg(cost)
+            .arg(if force { 1 } else { 0 })
+            .query_async(&mut connection)
+            .await
+            .map_err(|e| AppError::Redis(format!("Redis token bucket failed: {}", e)))?;
+        Ok(BucketState {
+            allowed: allowed == 1,
+            available: available.parse().unwrap_or_default(),
+        })
+    }
+}
@@ -0,0 +1,79 @@
+use axum::{
+    http::{HeaderMap, HeaderName, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+use crate::{config::RequestHeadersConfig, error::AppError};
+
+/// 请求头数量或大小超出限制，返回 431
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeadersTooLarge {
+    /// 请求头数量
+    pub count: usize,
+    /// 请求头总字节数
+    pub bytes: usize,
+}
+
+impl IntoResponse for HeadersTooLarge {
+    fn into_response(self) -> Response {
+        StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE.into_response()
+    }
+}
+
+/// 入站请求头过滤器
+///
+/// 在读取请求体之前检查请求头的数量和大小，转发给上游前移除配置的请求头
+#[derive(Debug)]
+pub struct RequestHeaderFilter {
+    // 转发给上游前移除的请求头
+    strip: Vec<HeaderName>,
+    // 最大请求头数量
+    max_count: Option<usize>,
+    // 请求头的最大总字节数
+    max_bytes: Option<usize>,
+}
+
+impl RequestHeaderFilter {
+    /// 根据入站请求头配置创建过滤器
+    pub fn new(config: &RequestHeadersConfig) -> Result<Self, AppError> {
+        let strip = config
+            .strip
+            .iter()
+            .map(|name| {
+                HeaderName::from_bytes(name.as_bytes()).map_err(|e| {
+                    AppError::InvalidHeader(format!(
+                        "Invalid request header name to strip '{}': {}",
+                        name, e
+                    ))
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            strip,
+            max_count: config.max_count.map(|count| count as usize),
+            max_bytes: config.max_bytes.map(|bytes| bytes as usize),
+        })
+    }
+
+    /// 检查请求头的数量和总字节数（名称和值的长度之和）是否超出限制
+    pub fn check(&self, headers: &HeaderMap) -> Result<(), HeadersTooLarge> {
+        let count = headers.len();
+        let bytes = headers
+            .iter()
+            .map(|(name, value)| name.as_str().len() + value.len())
+            .sum();
+        let exceeded = self.max_count.is_some_and(|max| count > max)
+            || self.max_bytes.is_some_and(|max| bytes > max);
+        if exceeded {
+            return Err(HeadersTooLarge { count, bytes });
+        }
+        Ok(())
+    }
+
+    /// 移除配置的请求头
+    pub fn strip(&self, headers: &mut HeaderMap) {
+        for name in &self.strip {
+            headers.remove(name);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{header::CONTENT_LENGTH, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use std::{sync::Arc, time::Duration};
+use tracing::{debug, warn};
+
+use crate::{
+    cache::{create_store, CacheStore, CachedResponse},
+    config::CacheConfig,
+    error::AppError,
+    r#const::{cache_limits, http_headers},
+};
+
+use super::coalesce::CoalesceKey;
+
+/// 转发服务的响应缓存
+///
+/// 只缓存非流式请求的成功响应（2xx）。缓存存储出错时按未命中处理，请求照常转发
+pub struct ResponseCache {
+    // 存储后端
+    store: Arc<dyn CacheStore>,
+    // 缓存有效期
+    ttl: Duration,
+    // 可缓存的最大响应体（字节）
+    max_body_bytes: usize,
+}
+
+impl ResponseCache {
+    /// 按缓存配置创建响应缓存
+    pub fn new(config: &CacheConfig) -> Result<Self, AppError> {
+        Ok(Self::with_store(create_store(config)?, config))
+    }
+
+    /// 使用指定的存储后端创建响应缓存
+    pub fn with_store(store: Arc<dyn CacheStore>, config: &CacheConfig) -> Self {
+        Self {
+            store,
+            ttl: Duration::from_secs(config.ttl),
+            max_body_bytes: config.max_body_bytes,
+        }
+    }
+
+    /// 查找缓存的响应，命中时添加缓存结果响应头
+    pub async fn lookup(&self, key: &CoalesceKey) -> Option<Response> {
+        let value = match self.store.get(&key.digest()).await {
+            Ok(value) => value?,
+            Err(e) => {
+                warn!("Failed to read {} response cache: {}", self.store.name(), e);
+                return None;
+            }
+        };
+        let cached = CachedResponse::decode(&value)?;
+
+        let mut response = Response::new(Body::from(cached.body));
+        *response.status_mut() = StatusCode::from_u16(cached.status).ok()?;
+        let headers = response.headers_mut();
+        for (name, value) in cached.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(&value),
+            ) {
+                headers.append(name, value);
+            }
+        }
+        headers.insert(
+            http_headers::CACHE,
+            HeaderValue::from_static(cache_limits::HIT),
+        );
+        Some(response)
+    }
+
+    /// 缓存成功的非流式响应，在后台写入存储后端，不阻塞响应。超过缓冲上限的响应不缓存
+    pub async fn store(&self, key: &CoalesceKey, response: Response) -> Response {
+        if !response.status().is_success() || super::utils::is_unbuffered_response(&response) {
+            return response;
+        }
+
+        // 非流式响应已读取完整响应体
+        let (parts, body) = response.into_parts();
+        let body = match to_bytes(body, usize::MAX).await {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("Failed to read response body for response cache: {}", e);
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+        if body.len() <= self.max_body_bytes {
+            let cached = CachedResponse {
+                status: parts.status.as_u16(),
+                headers: cached_headers(&parts.headers),
+                body: body.clone(),
+            };
+            let (store, key, ttl) = (self.store.clone(), key.digest(), self.ttl);
+            tokio::spawn(async move {
+                if let Err(e) = store.set(&key, cached.encode(), ttl).await {
+                    warn!("Failed to write {} response cache: {}", store.name(), e);
+                }
+            });
+        } else {
+            debug!(
+                "Response body of {} bytes exceeds the cacheable size of {} bytes",
+                body.len(),
+                self.max_body_bytes
+            );
+        }
+        Response::from_parts(parts, Body::from(body))
+    }
+}
+
+// 需要缓存的响应头，响应体长度在读取缓存时重新计算
+fn cached_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter(|(name, _)| *name != CONTENT_LENGTH)
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
@@ -1,9 +1,22 @@
-use crate::{config::ForwardConfig, error::AppError};
-use radixmap::RadixMap;
-use std::collections::HashSet;
-use tokio::sync::RwLock;
+use crate::{
+    breaker::{create_upstream_circuit_breaker, UpstreamCircuitBreaker},
+    config::{
+        http_server::{RoutingRule, RoutingRuleType},
+        BreakerConfig, ForwardConfig,
+    },
+    error::AppError,
+    r#const::route_breaker,
+};
+use arc_swap::ArcSwap;
+use parking_lot::Mutex;
+use regex::Regex;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
 use tracing::debug;
 
+use super::path_map::PathMap;
+
 // 路由结果
 #[derive(Debug, Clone)]
 pub struct RoutingResult {
@@ -11,93 +24,347 @@ pub struct RoutingResult {
     pub target_group: String,
     // 是否使用了默认组
     pub is_default: bool,
+    // 匹配的路由规则路径，使用默认组时为 None
+    pub route: Option<String>,
+    // 路由熔断器，未配置路由熔断器时为 None
+    pub breaker: Option<Arc<UpstreamCircuitBreaker>>,
+    // 路由的最大提示词 token 数，未配置时为 None
+    pub max_prompt_tokens: Option<u64>,
+    // 路由的每分钟 token 数，未配置时为 None
+    pub tokens_per_minute: Option<u64>,
 }
 
-// 路由器结构
-pub struct Router {
-    // 路径映射表
-    path_map: RwLock<RadixMap<String>>,
-    // 默认上游组
-    default_group: String,
+// 正则路由规则
+#[derive(Clone)]
+struct RegexRoute {
+    // 原始正则表达式（同时作为规则标识）
+    pattern: String,
+    // 已编译的正则表达式
+    regex: Regex,
+    // 目标上游组
+    target_group: String,
 }
 
-impl Router {
-    // 创建新的路由器
-    pub fn new(config: &ForwardConfig) -> Result<Self, AppError> {
-        let mut path_map = RadixMap::new();
-        let default_group = config.default_group.clone();
-
-        // 处理路由规则
-        if let Some(routing_rules) = &config.routing {
-            let mut paths = HashSet::new();
-            // 明确使用 .iter() 来帮助编译器推断生命周期
-            for rule in routing_rules.iter() {
-                // 检查路径唯一性
-                if !paths.insert(&rule.path) {
-                    return Err(AppError::Config(format!(
-                        "Duplicate routing path found: {:?}",
-                        rule.path
-                    )));
-                }
+// 同一优先级下的路由规则
+#[derive(Clone)]
+struct RouteTier {
+    // 路径模式 -> (路由规则路径, 目标上游组)
+    paths: PathMap<(String, String)>,
+    // 正则规则（按添加顺序匹配）
+    regexes: Vec<RegexRoute>,
+}
+
+impl RouteTier {
+    fn new() -> Self {
+        Self {
+            paths: PathMap::new(),
+            regexes: Vec::new(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.paths.is_empty() && self.regexes.is_empty()
+    }
+
+    // 路径模式优先于同一优先级下的正则规则，返回匹配的路由规则路径和目标上游组
+    fn get(&self, path: &str) -> Option<(&str, &str)> {
+        match self.paths.get(path) {
+            Some((route, target_group)) => Some((route, target_group)),
+            None => self
+                .regexes
+                .iter()
+                .find(|route| route.regex.is_match(path))
+                .map(|route| (route.pattern.as_str(), route.target_group.as_str())),
+        }
+    }
+}
+
+// 路由熔断器的默认配置
+#[derive(Clone)]
+struct BreakerDefaults {
+    // 转发服务名称，用作路由熔断器名称的前缀
+    forward: String,
+    // 转发服务的路由熔断器配置，路由规则未配置 breaker 时使用
+    breaker: Option<BreakerConfig>,
+}
 
-                if let Err(e) = path_map.insert(rule.path.clone(), rule.target_group.clone()) {
+impl BreakerDefaults {
+    // 为路由创建熔断器，路由规则和转发服务都没有配置 breaker 时返回 None
+    fn create(
+        &self,
+        route: &str,
+        target_group: &str,
+        config: Option<&BreakerConfig>,
+    ) -> Option<Arc<UpstreamCircuitBreaker>> {
+        let config = config.or(self.breaker.as_ref())?;
+        Some(create_upstream_circuit_breaker(
+            format!("{}:{}", self.forward, route),
+            target_group.to_string(),
+            config,
+        ))
+    }
+}
+
+// 路由表
+// 按优先级分层，每层内部由 PathMap 处理静态/参数/通配符的匹配顺序
+#[derive(Clone)]
+struct RouteTable {
+    // 优先级 -> 路由规则（按优先级从高到低排序）
+    tiers: BTreeMap<Reverse<u32>, RouteTier>,
+    // 路径 -> 优先级
+    priorities: HashMap<String, u32>,
+    // 已编译的正则表达式缓存，避免更新规则时重复编译
+    regex_cache: HashMap<String, Regex>,
+    // 路径 -> 路由熔断器
+    breakers: HashMap<String, Arc<UpstreamCircuitBreaker>>,
+    // 路径 -> 最大提示词 token 数
+    prompt_limits: HashMap<String, u64>,
+    // 路径 -> 每分钟 token 数
+    token_limits: HashMap<String, u64>,
+    // 路由熔断器的默认配置
+    defaults: BreakerDefaults,
+}
+
+// 已校验、尚未生效的路由表，由 prepare_routes 构建，commit_routes 后生效
+pub struct PreparedRoutes(RouteTable);
+
+impl RouteTable {
+    fn new(defaults: BreakerDefaults) -> Self {
+        Self {
+            tiers: BTreeMap::new(),
+            priorities: HashMap::new(),
+            regex_cache: HashMap::new(),
+            breakers: HashMap::new(),
+            prompt_limits: HashMap::new(),
+            token_limits: HashMap::new(),
+            defaults,
+        }
+    }
+
+    // 获取或编译正则表达式
+    fn compile_regex(&mut self, pattern: &str) -> Result<Regex, AppError> {
+        if let Some(regex) = self.regex_cache.get(pattern) {
+            return Ok(regex.clone());
+        }
+
+        let regex = Regex::new(pattern).map_err(|e| {
+            AppError::Config(format!("Invalid path regex: {:?}, error: {}", pattern, e))
+        })?;
+        self.regex_cache.insert(pattern.to_string(), regex.clone());
+        Ok(regex)
+    }
+
+    // 插入或更新路由规则
+    fn insert(&mut self, rule: &RoutingRule) -> Result<(), AppError> {
+        // 先编译正则表达式，失败时不影响已有的路由表
+        let regex = match rule.r#type {
+            RoutingRuleType::Path => None,
+            RoutingRuleType::PathRegex => Some(self.compile_regex(&rule.path)?),
+        };
+
+        // 先从旧的层中移除，规则的优先级或类型可能已经变化
+        self.detach(&rule.path);
+
+        let tier = self
+            .tiers
+            .entry(Reverse(rule.priority))
+            .or_insert_with(RouteTier::new);
+
+        match regex {
+            Some(regex) => tier.regexes.push(RegexRoute {
+                pattern: rule.path.clone(),
+                regex,
+                target_group: rule.target_group.clone(),
+            }),
+            None => {
+                if let Err(e) = tier.paths.insert(
+                    rule.path.clone(),
+                    (rule.path.clone(), rule.target_group.clone()),
+                ) {
                     return Err(AppError::Config(format!(
                         "Error adding route: {:?} -> {:?}, error: {}",
                         rule.path, rule.target_group, e
                     )));
                 }
+            }
+        }
+
+        self.priorities.insert(rule.path.clone(), rule.priority);
+
+        // 更新规则后重新开始统计路由熔断器
+        match self
+            .defaults
+            .create(&rule.path, &rule.target_group, rule.breaker.as_ref())
+        {
+            Some(breaker) => self.breakers.insert(rule.path.clone(), breaker),
+            None => self.breakers.remove(&rule.path),
+        };
+        match rule.max_prompt_tokens {
+            Some(max) => self.prompt_limits.insert(rule.path.clone(), max),
+            None => self.prompt_limits.remove(&rule.path),
+        };
+        match rule.tokens_per_minute {
+            Some(limit) => self.token_limits.insert(rule.path.clone(), limit),
+            None => self.token_limits.remove(&rule.path),
+        };
+        Ok(())
+    }
 
-                debug!(
-                    "Added routing rule: {:?} -> {:?}",
-                    rule.path, rule.target_group
-                );
+    // 从所在的优先级层中移除路由规则
+    fn detach(&mut self, path: &str) {
+        if let Some(priority) = self.priorities.remove(path) {
+            let key = Reverse(priority);
+            if let Some(tier) = self.tiers.get_mut(&key) {
+                tier.paths.remove(path);
+                tier.regexes.retain(|route| route.pattern != path);
+                // 清理空的优先级层
+                if tier.is_empty() {
+                    self.tiers.remove(&key);
+                }
             }
         }
+    }
+
+    // 删除路由规则
+    fn remove(&mut self, path: &str) {
+        self.detach(path);
+        self.regex_cache.remove(path);
+        self.breakers.remove(path);
+        self.prompt_limits.remove(path);
+        self.token_limits.remove(path);
+    }
+
+    // 按优先级从高到低查找第一个匹配的路由
+    fn get(&self, path: &str) -> Option<(&str, &str)> {
+        self.tiers.values().find_map(|tier| tier.get(path))
+    }
+}
+
+// 根据路由规则构建路由表
+fn build_route_table(
+    rules: &[RoutingRule],
+    defaults: BreakerDefaults,
+) -> Result<RouteTable, AppError> {
+    let mut route_table = RouteTable::new(defaults);
+    let mut paths = HashSet::new();
+
+    for rule in rules {
+        // 检查路径唯一性
+        if !paths.insert(&rule.path) {
+            return Err(AppError::Config(format!(
+                "Duplicate routing path found: {:?}",
+                rule.path
+            )));
+        }
+
+        route_table.insert(rule)?;
+
+        debug!(
+            "Added routing rule: {:?} -> {:?}, type: {:?}, priority: {}",
+            rule.path, rule.target_group, rule.r#type, rule.priority
+        );
+    }
+
+    Ok(route_table)
+}
+
+// 路由器结构
+pub struct Router {
+    // 路由表快照，更新时复制后整体替换，请求路由时无需加锁
+    route_table: ArcSwap<RouteTable>,
+    // 串行化路由表的更新，避免并发更新相互覆盖
+    update_lock: Mutex<()>,
+    // 默认上游组
+    default_group: String,
+    // 使用默认组的请求的路由熔断器
+    default_breaker: Option<Arc<UpstreamCircuitBreaker>>,
+    // 路由熔断器的默认配置
+    defaults: BreakerDefaults,
+}
+
+impl Router {
+    // 创建新的路由器
+    pub fn new(config: &ForwardConfig) -> Result<Self, AppError> {
+        let defaults = BreakerDefaults {
+            forward: config.name.clone(),
+            breaker: config.breaker.clone(),
+        };
+        let route_table = build_route_table(
+            config.routing.as_deref().unwrap_or_default(),
+            defaults.clone(),
+        )?;
 
         Ok(Self {
-            path_map: RwLock::new(path_map),
-            default_group,
+            route_table: ArcSwap::from_pointee(route_table),
+            update_lock: Mutex::new(()),
+            default_group: config.default_group.clone(),
+            default_breaker: defaults.create(
+                route_breaker::DEFAULT_ROUTE,
+                &config.default_group,
+                None,
+            ),
+            defaults,
         })
     }
-    // 创建和更新路由规则
-    pub async fn insert_or_update_route(
-        &self,
-        path: String,
-        target_group: String,
-    ) -> Result<(), AppError> {
-        // 获取写锁
-        let mut path_map = self.path_map.write().await;
-        let _ = path_map.insert(path, target_group);
-        // 锁会在这里自动释放
+
+    // 整体替换路由规则，新规则全部有效后才会生效
+    pub fn replace_routes(&self, rules: &[RoutingRule]) -> Result<(), AppError> {
+        let routes = self.prepare_routes(rules)?;
+        self.commit_routes(routes);
         Ok(())
     }
 
+    // 根据路由规则构建新的路由表，不影响运行中的路由
+    pub fn prepare_routes(&self, rules: &[RoutingRule]) -> Result<PreparedRoutes, AppError> {
+        build_route_table(rules, self.defaults.clone()).map(PreparedRoutes)
+    }
+
+    // 替换为预先构建的路由表
+    pub fn commit_routes(&self, routes: PreparedRoutes) {
+        let _guard = self.update_lock.lock();
+        self.route_table.store(Arc::new(routes.0));
+    }
+
+    // 创建和更新路由规则
+    pub fn insert_or_update_route(&self, rule: &RoutingRule) -> Result<(), AppError> {
+        self.update(|route_table| route_table.insert(rule))
+    }
+
     // 删除路由规则
-    pub async fn remove_route(&self, path: &str) -> Result<(), AppError> {
-        // 获取写锁
-        let mut path_map = self.path_map.write().await;
-        path_map.remove(path.as_bytes());
-        // 锁会在这里自动释放
+    pub fn remove_route(&self, path: &str) -> Result<(), AppError> {
+        self.update(|route_table| {
+            route_table.remove(path);
+            Ok(())
+        })
+    }
+
+    // 在当前路由表的副本上执行修改，成功后替换路由表，失败时路由表保持不变
+    fn update(
+        &self,
+        modify: impl FnOnce(&mut RouteTable) -> Result<(), AppError>,
+    ) -> Result<(), AppError> {
+        let _guard = self.update_lock.lock();
+        let mut route_table = RouteTable::clone(&self.route_table.load());
+        modify(&mut route_table)?;
+        self.route_table.store(Arc::new(route_table));
         Ok(())
     }
 
     // 根据请求路径获取目标上游组
     #[inline(always)]
-    pub async fn get_target_group(&self, path: &str) -> RoutingResult {
-        // 获取读锁
-        let path_map_read = self.path_map.read().await;
+    pub fn get_target_group(&self, path: &str) -> RoutingResult {
+        let route_table = self.route_table.load();
 
         // 查找匹配的路由规则
-        // 使用 .as_bytes() 将 &str 转换为 &[u8]
-        if let Some(target_group) = path_map_read.get(path.as_bytes()) {
+        if let Some((route, target_group)) = route_table.get(path) {
             debug!("Routing matched: {:?} -> {:?}", path, target_group);
-            // 克隆目标上游组的值，避免生命周期问题
-            let target_group = target_group.to_owned();
-            drop(path_map_read);
-
             return RoutingResult {
-                target_group,
+                target_group: target_group.to_owned(),
                 is_default: false,
+                route: Some(route.to_owned()),
+                breaker: route_table.breakers.get(route).cloned(),
+                max_prompt_tokens: route_table.prompt_limits.get(route).copied(),
+                tokens_per_minute: route_table.token_limits.get(route).copied(),
             };
         }
 
@@ -107,11 +374,13 @@ impl Router {
             path, self.default_group
         );
 
-        drop(path_map_read);
-
         RoutingResult {
             target_group: self.default_group.clone(),
             is_default: true,
+            route: None,
+            breaker: self.default_breaker.clone(),
+            max_prompt_tokens: None,
+            tokens_per_minute: None,
         }
     }
 }
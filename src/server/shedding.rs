@@ -0,0 +1,245 @@
+use crate::{
+    config::{LoadSheddingConfig, RequestPriority},
+    error::AppError,
+    metrics::METRICS,
+    r#const::load_shedding,
+};
+use axum::{
+    http::{header::RETRY_AFTER, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde_json::json;
+use std::{
+    sync::atomic::{AtomicU8, Ordering},
+    time::{Duration, Instant},
+};
+use tokio_graceful_shutdown::{IntoSubsystem, SubsystemHandle};
+use tracing::{info, warn};
+
+/// 资源压力等级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Pressure {
+    /// 资源充足，接收所有请求
+    Normal,
+    /// 资源使用量接近上限，拒绝低优先级请求
+    Elevated,
+    /// 资源使用量达到上限，只接收高优先级请求
+    Critical,
+}
+
+impl Pressure {
+    /// 按资源使用量与上限的比例计算压力等级
+    pub fn from_ratio(ratio: f64) -> Self {
+        if ratio >= 1.0 {
+            Self::Critical
+        } else if ratio >= load_shedding::ELEVATED_RATIO {
+            Self::Elevated
+        } else {
+            Self::Normal
+        }
+    }
+
+    /// 该压力等级下是否接收指定优先级的请求
+    pub fn admits(self, priority: RequestPriority) -> bool {
+        match self {
+            Self::Normal => true,
+            Self::Elevated => priority > RequestPriority::Low,
+            Self::Critical => priority == RequestPriority::High,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Self::Normal,
+            1 => Self::Elevated,
+            _ => Self::Critical,
+        }
+    }
+}
+
+/// 资源紧张时拒绝的请求
+#[derive(Debug, Clone, Copy)]
+pub struct LoadShed {
+    /// 请求优先级
+    pub priority: RequestPriority,
+}
+
+impl IntoResponse for LoadShed {
+    // 返回 503、OpenAI 格式的错误和 Retry-After 头部
+    fn into_response(self) -> Response {
+        let body = json!({"error": {
+            "message": format!(
+                "Server is overloaded, {} priority requests are temporarily rejected, please retry later",
+                self.priority.as_str()
+            ),
+            "type": load_shedding::ERROR_TYPE,
+            "param": null,
+            "code": load_shedding::ERROR_CODE,
+        }});
+        let mut response = (StatusCode::SERVICE_UNAVAILABLE, Json(body)).into_response();
+        response.headers_mut().insert(
+            RETRY_AFTER,
+            HeaderValue::from(load_shedding::RETRY_AFTER_SECONDS),
+        );
+        response
+    }
+}
+
+/// 过载保护
+///
+/// 由资源监控更新压力等级，转发服务在读取请求体之前按请求优先级决定是否接收请求。
+/// 未启动资源监控时压力等级始终为 Normal
+#[derive(Default)]
+pub struct LoadShedder {
+    // 当前压力等级
+    pressure: AtomicU8,
+    // 携带请求优先级的请求头
+    priority_header: RwLock<Option<HeaderName>>,
+}
+
+impl LoadShedder {
+    /// 设置携带请求优先级的请求头
+    pub fn configure(&self, config: &LoadSheddingConfig) {
+        *self.priority_header.write() =
+            HeaderName::from_bytes(config.priority_header.as_bytes()).ok();
+    }
+
+    /// 当前压力等级
+    pub fn pressure(&self) -> Pressure {
+        Pressure::from_u8(self.pressure.load(Ordering::Relaxed))
+    }
+
+    /// 更新压力等级，返回之前的压力等级
+    pub fn set_pressure(&self, pressure: Pressure) -> Pressure {
+        Pressure::from_u8(self.pressure.swap(pressure as u8, Ordering::Relaxed))
+    }
+
+    /// 检查当前压力等级下是否接收请求
+    pub fn check(&self, headers: &HeaderMap) -> Result<(), LoadShed> {
+        let pressure = self.pressure();
+        if pressure == Pressure::Normal {
+            return Ok(());
+        }
+        let priority = self.priority(headers);
+        if pressure.admits(priority) {
+            Ok(())
+        } else {
+            Err(LoadShed { priority })
+        }
+    }
+
+    // 读取请求优先级，未携带或无法识别时为 normal
+    fn priority(&self, headers: &HeaderMap) -> RequestPriority {
+        self.priority_header
+            .read()
+            .as_ref()
+            .and_then(|name| headers.get(name))
+            .and_then(|value| value.to_str().ok())
+            .and_then(RequestPriority::parse)
+            .unwrap_or_default()
+    }
+}
+
+/// 全局过载保护
+pub static SHEDDER: Lazy<LoadShedder> = Lazy::new(LoadShedder::default);
+
+/// 资源监控
+///
+/// 按检查间隔读取进程内存（RSS）并测量事件循环延迟（定时器实际唤醒时间与预期时间之差），
+/// 取两者与上限比例中的较大者计算压力等级
+pub struct LoadWatchdog {
+    config: LoadSheddingConfig,
+}
+
+impl LoadWatchdog {
+    /// 创建资源监控
+    pub fn new(config: LoadSheddingConfig) -> Self {
+        Self { config }
+    }
+
+    /// 按进程内存和事件循环延迟计算压力等级，无法读取进程内存时只检查事件循环延迟
+    pub fn pressure(&self, rss_bytes: Option<u64>, lag: Duration) -> Pressure {
+        let memory = match (self.config.max_memory_mb, rss_bytes) {
+            (Some(max), Some(rss)) => rss as f64 / (max * load_shedding::BYTES_PER_MB) as f64,
+            _ => 0.0,
+        };
+        let lag = match self.config.max_event_loop_lag_ms {
+            Some(max) => lag.as_secs_f64() * 1000.0 / max as f64,
+            None => 0.0,
+        };
+        Pressure::from_ratio(memory.max(lag))
+    }
+}
+
+#[async_trait::async_trait]
+impl IntoSubsystem<AppError> for LoadWatchdog {
+    async fn run(self, subsys: SubsystemHandle) -> Result<(), AppError> {
+        SHEDDER.configure(&self.config);
+        if self.config.max_memory_mb.is_some() && process_rss().is_none() {
+            warn!("Process memory is not available on this platform, load shedding only checks event loop lag");
+        }
+        info!(
+            "Load shedding watchdog started, checking every {}ms",
+            self.config.interval_ms
+        );
+
+        let interval = Duration::from_millis(self.config.interval_ms);
+        loop {
+            let started = Instant::now();
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = subsys.on_shutdown_requested() => break,
+            }
+            let lag = started.elapsed().saturating_sub(interval);
+            let rss = process_rss();
+
+            let pressure = self.pressure(rss, lag);
+            let previous = SHEDDER.set_pressure(pressure);
+            if pressure != previous {
+                METRICS.load_shedding_pressure().set(pressure as i64);
+                let rss_mb = rss.unwrap_or_default() / load_shedding::BYTES_PER_MB;
+                if pressure > previous {
+                    warn!(
+                        "Load shedding pressure raised to {:?} (rss: {}MB, event loop lag: {}ms)",
+                        pressure,
+                        rss_mb,
+                        lag.as_millis()
+                    );
+                } else {
+                    info!(
+                        "Load shedding pressure lowered to {:?} (rss: {}MB, event loop lag: {}ms)",
+                        pressure,
+                        rss_mb,
+                        lag.as_millis()
+                    );
+                }
+            }
+        }
+
+        SHEDDER.set_pressure(Pressure::Normal);
+        Ok(())
+    }
+}
+
+// 读取进程的常驻内存（字节），仅 Linux 支持
+fn process_rss() -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let kb = status
+            .lines()
+            .find_map(|line| line.strip_prefix("VmRSS:"))?
+            .trim()
+            .trim_end_matches("kB")
+            .trim()
+            .parse::<u64>()
+            .ok()?;
+        Some(kb * 1024)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    None
+}
@@ -0,0 +1,166 @@
+use axum::serve::Listener;
+use openssl::{
+    pkey::PKey,
+    ssl::{Ssl, SslAcceptor, SslMethod, SslVerifyMode},
+    x509::{X509Name, X509},
+};
+use std::{fs, io, net::SocketAddr, pin::Pin, sync::Arc, time::Duration};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+use tokio_openssl::SslStream;
+use tracing::{debug, warn};
+
+use crate::{config::AdminTlsConfig, error::AppError, r#const::admin_tls};
+
+/// 根据管理服务的 HTTPS 配置创建 TLS 接收器，配置了 client_ca_file 时要求客户端证书
+pub fn tls_acceptor(config: &AdminTlsConfig) -> Result<SslAcceptor, AppError> {
+    let tls_error = |e: openssl::error::ErrorStack| {
+        AppError::Config(format!("Failed to configure admin TLS: {}", e))
+    };
+    let mut builder =
+        SslAcceptor::mozilla_intermediate_v5(SslMethod::tls_server()).map_err(tls_error)?;
+
+    // 服务端证书，第一个证书为服务端证书，其余为证书链
+    let cert = fs::read(&config.cert).map_err(|e| {
+        AppError::Config(format!(
+            "Unable to read admin TLS certificate {:?}: {}",
+            config.cert, e
+        ))
+    })?;
+    let chain = X509::stack_from_pem(&cert)
+        .ok()
+        .filter(|chain| !chain.is_empty())
+        .ok_or_else(|| {
+            AppError::Config(format!("Invalid admin TLS certificate {:?}", config.cert))
+        })?;
+    builder.set_certificate(&chain[0]).map_err(tls_error)?;
+    for cert in &chain[1..] {
+        builder
+            .add_extra_chain_cert(cert.clone())
+            .map_err(tls_error)?;
+    }
+
+    // 服务端私钥
+    let key = fs::read(&config.key).map_err(|e| {
+        AppError::Config(format!(
+            "Unable to read admin TLS key {:?}: {}",
+            config.key, e
+        ))
+    })?;
+    let key = match &config.passphrase {
+        Some(passphrase) => PKey::private_key_from_pem_passphrase(&key, passphrase.as_bytes()),
+        None => PKey::private_key_from_pem(&key),
+    }
+    .map_err(|e| AppError::Config(format!("Invalid admin TLS key {:?}: {}", config.key, e)))?;
+    if !chain[0]
+        .public_key()
+        .is_ok_and(|public_key| public_key.public_eq(&key))
+    {
+        return Err(AppError::Config(format!(
+            "Admin TLS key {:?} does not match certificate {:?}",
+            config.key, config.cert
+        )));
+    }
+    builder.set_private_key(&key).map_err(tls_error)?;
+
+    // 要求客户端提供由 client_ca_file 中的 CA 签发的证书
+    if let Some(ca_file) = &config.client_ca_file {
+        let names = X509Name::load_client_ca_file(ca_file).map_err(|e| {
+            AppError::Config(format!(
+                "Invalid admin TLS client CA file {:?}: {}",
+                ca_file, e
+            ))
+        })?;
+        builder.set_ca_file(ca_file).map_err(tls_error)?;
+        builder.set_client_ca_list(names);
+        builder.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+    }
+
+    Ok(builder.build())
+}
+
+/// HTTPS 监听器
+///
+/// 后台任务接受 TCP 连接并在独立的任务中完成 TLS 握手，握手失败或超时的连接直接关闭，
+/// 避免慢速客户端阻塞其他连接。监听器释放后后台任务退出
+pub struct TlsListener {
+    // 监听地址
+    local_addr: SocketAddr,
+    // 已完成握手的连接
+    receiver: mpsc::Receiver<(SslStream<TcpStream>, SocketAddr)>,
+}
+
+impl TlsListener {
+    /// 使用 TLS 接收器包装 TCP 监听器，必须在 tokio 运行时中调用
+    pub fn new(listener: TcpListener, acceptor: SslAcceptor) -> io::Result<Self> {
+        let local_addr = listener.local_addr()?;
+        let (sender, receiver) = mpsc::channel(admin_tls::ACCEPT_BACKLOG);
+        tokio::spawn(run_acceptor(listener, Arc::new(acceptor), sender));
+        Ok(Self {
+            local_addr,
+            receiver,
+        })
+    }
+}
+
+impl Listener for TlsListener {
+    type Io = SslStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.receiver.recv().await {
+            Some(connection) => connection,
+            // 后台任务只在监听器释放后退出
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
+
+// 接受 TCP 连接并完成 TLS 握手，监听器释放后退出
+async fn run_acceptor(
+    listener: TcpListener,
+    acceptor: Arc<SslAcceptor>,
+    sender: mpsc::Sender<(SslStream<TcpStream>, SocketAddr)>,
+) {
+    loop {
+        let (stream, addr) = tokio::select! {
+            _ = sender.closed() => return,
+            result = listener.accept() => match result {
+                Ok(connection) => connection,
+                Err(e) => {
+                    warn!("Failed to accept admin connection: {}", e);
+                    tokio::time::sleep(Duration::from_millis(admin_tls::ACCEPT_RETRY_DELAY_MS))
+                        .await;
+                    continue;
+                }
+            },
+        };
+
+        let (acceptor, sender) = (acceptor.clone(), sender.clone());
+        tokio::spawn(async move {
+            let handshake = async {
+                let ssl = Ssl::new(acceptor.context()).map_err(|e| e.to_string())?;
+                let mut stream = SslStream::new(ssl, stream).map_err(|e| e.to_string())?;
+                Pin::new(&mut stream)
+                    .accept()
+                    .await
+                    .map_err(|e| e.to_string())?;
+                Ok::<_, String>(stream)
+            };
+            let timeout = Duration::from_secs(admin_tls::HANDSHAKE_TIMEOUT);
+            match tokio::time::timeout(timeout, handshake).await {
+                Ok(Ok(stream)) => {
+                    let _ = sender.send((stream, addr)).await;
+                }
+                Ok(Err(e)) => debug!("Admin TLS handshake with {} failed: {}", addr, e),
+                Err(_) => debug!("Admin TLS handshake with {} timed out", addr),
+            }
+        });
+    }
+}
@@ -0,0 +1,267 @@
+use super::{
+    ratelimit::{ClientKey, ClientKeyExtractor},
+    redis_bucket::RedisBuckets,
+};
+use crate::{config::TokenLimitConfig, error::AppError, r#const::token_limits};
+use axum::{
+    http::{header::RETRY_AFTER, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use serde_json::json;
+use std::{net::IpAddr, sync::Arc, time::Instant};
+use tracing::warn;
+
+// 令牌桶键：路由前缀（未匹配路由时为空）、客户端
+type BucketKey = (String, Option<ClientKey>);
+
+/// 每分钟 token 数超出限制
+#[derive(Debug, Clone)]
+pub struct TokenLimitExceeded {
+    /// 每分钟 token 数
+    pub limit: u64,
+    /// 本次请求需要的 token 数（估算值）
+    pub requested: u64,
+    /// 令牌桶恢复到足够 token 数的秒数
+    pub retry_after: u64,
+}
+
+impl IntoResponse for TokenLimitExceeded {
+    // 返回 OpenAI 格式的 429 错误，Retry-After 为令牌桶恢复所需的秒数
+    fn into_response(self) -> Response {
+        let body = json!({"error": {
+            "message": format!(
+                "Rate limit reached: limit {} tokens per minute, requested {}, retry after {} seconds",
+                self.limit, self.requested, self.retry_after
+            ),
+            "type": token_limits::ERROR_TYPE,
+            "param": null,
+            "code": token_limits::ERROR_CODE,
+        }});
+        let mut response = (StatusCode::TOO_MANY_REQUESTS, Json(body)).into_response();
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, HeaderValue::from(self.retry_after));
+        response
+    }
+}
+
+// 令牌桶，每分钟恢复到限额，按实际用量修正后可能为负数
+#[derive(Debug)]
+struct TokenBucket {
+    // 每分钟 token 数
+    limit: u64,
+    // 可用的 token 数
+    available: f64,
+    // 上次恢复的时间
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: u64) -> Self {
+        Self {
+            limit,
+            available: limit as f64,
+            updated: Instant::now(),
+        }
+    }
+
+    // 每秒恢复的 token 数
+    fn rate(&self) -> f64 {
+        self.limit as f64 / token_limits::WINDOW_SECONDS
+    }
+
+    // 按经过的时间恢复 token，不超过限额
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.available = (self.available + elapsed * self.rate()).min(self.limit as f64);
+        self.updated = now;
+    }
+
+    // 是否已完全恢复
+    fn is_full(&mut self) -> bool {
+        self.refill();
+        self.available >= self.limit as f64
+    }
+}
+
+// 扣除 token 的令牌桶
+#[derive(Debug, Clone)]
+enum ChargedBucket {
+    // 本地令牌桶
+    Local(Arc<Mutex<TokenBucket>>),
+    // Redis 令牌桶
+    Redis {
+        buckets: Arc<RedisBuckets>,
+        key: String,
+        limit: u64,
+    },
+}
+
+/// 单个请求扣除的 token，收到上游响应后按实际用量修正
+#[derive(Debug, Clone)]
+pub struct TokenCharge {
+    // 扣除 token 的令牌桶
+    bucket: ChargedBucket,
+    // 转发前扣除的估算值
+    estimated: u64,
+}
+
+impl TokenCharge {
+    /// 按实际用量（提示词和生成内容）修正扣除的 token 数
+    ///
+    /// Redis 令牌桶在后台修正，不阻塞响应
+    pub fn settle(&self, actual: u64) {
+        let delta = actual as f64 - self.estimated as f64;
+        match &self.bucket {
+            ChargedBucket::Local(bucket) => bucket.lock().available -= delta,
+            ChargedBucket::Redis { .. } if delta == 0.0 => {}
+            ChargedBucket::Redis {
+                buckets,
+                key,
+                limit,
+            } => {
+                let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+                    return;
+                };
+                let (buckets, key, limit) = (buckets.clone(), key.clone(), *limit as f64);
+                runtime.spawn(async move {
+                    let rate = limit / token_limits::WINDOW_SECONDS;
+                    if let Err(e) = buckets.take(&key, limit, rate, 0.0, delta, true).await {
+                        warn!("Failed to settle token usage in Redis: {}", e);
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// 按每分钟 token 数限流
+///
+/// 配置了 Redis 时令牌桶保存在 Redis 中，多个实例共享限额；Redis 不可用时回退为本地令牌桶
+pub struct TokenLimiter {
+    // 令牌速率限制配置
+    config: TokenLimitConfig,
+    // 限流键提取器
+    extractor: ClientKeyExtractor,
+    // 每个路由和客户端的令牌桶
+    buckets: DashMap<BucketKey, Arc<Mutex<TokenBucket>>>,
+    // Redis 令牌桶，未配置 Redis 时为 None
+    redis: Option<Arc<RedisBuckets>>,
+}
+
+impl TokenLimiter {
+    /// 根据转发服务名称和令牌速率限制配置创建限流器，不建立 Redis 连接
+    pub fn new(forward: &str, config: &TokenLimitConfig) -> Result<Self, AppError> {
+        let redis = config
+            .redis
+            .as_ref()
+            .map(|redis| RedisBuckets::new(redis, format!("tokens:{}", forward)))
+            .transpose()?
+            .map(Arc::new);
+        Ok(Self {
+            config: config.clone(),
+            extractor: ClientKeyExtractor::new(config.key, config.header.as_deref()),
+            buckets: DashMap::new(),
+            redis,
+        })
+    }
+
+    /// 扣除请求的估算 token 数，令牌桶不足时返回错误
+    ///
+    /// route 为匹配的路由规则路径及其每分钟 token 数，配置了限额的路由拥有独立的令牌桶。
+    /// 超过限额的估算值按限额检查，避免大请求永远无法通过。客户端标识和客户端 IP 都缺失的请求共享同一个令牌桶
+    pub async fn acquire(
+        &self,
+        route: Option<(&str, u64)>,
+        headers: &HeaderMap,
+        client_ip: Option<IpAddr>,
+        estimated: u64,
+    ) -> Result<TokenCharge, TokenLimitExceeded> {
+        let client = match self.extractor.value(headers) {
+            Some(value) => Some(value.to_string()),
+            None => client_ip.map(|ip| ip.to_string()),
+        };
+        let limit = self
+            .config
+            .limit(route.map(|(_, limit)| limit), client.as_deref());
+        let key = (
+            route.map(|(path, _)| path.to_string()).unwrap_or_default(),
+            self.extractor.key(headers, client_ip),
+        );
+        let needed = estimated.min(limit) as f64;
+        let rate = limit as f64 / token_limits::WINDOW_SECONDS;
+
+        if let Some(buckets) = &self.redis {
+            let redis_key = format!(
+                "{}:{}",
+                key.0,
+                key.1
+                    .map_or_else(|| "-".to_string(), |client| client.to_string())
+            );
+            match buckets
+                .take(
+                    &redis_key,
+                    limit as f64,
+                    rate,
+                    needed,
+                    estimated as f64,
+                    false,
+                )
+                .await
+            {
+                Ok(state) if state.allowed => {
+                    return Ok(TokenCharge {
+                        bucket: ChargedBucket::Redis {
+                            buckets: buckets.clone(),
+                            key: redis_key,
+                            limit,
+                        },
+                        estimated,
+                    });
+                }
+                Ok(state) => {
+                    let retry_after = ((needed.max(1.0) - state.available) / rate).ceil();
+                    return Err(TokenLimitExceeded {
+                        limit,
+                        requested: estimated,
+                        retry_after: (retry_after as u64).max(1),
+                    });
+                }
+                Err(e) => warn!("Token limit falls back to local buckets: {}", e),
+            }
+        }
+
+        // 令牌桶过多时清理已完全恢复的令牌桶，与新建的令牌桶等价
+        if self.buckets.len() >= token_limits::MAX_BUCKETS && !self.buckets.contains_key(&key) {
+            self.buckets.retain(|_, bucket| !bucket.lock().is_full());
+        }
+        let bucket = self
+            .buckets
+            .entry(key)
+            .or_insert_with(|| Arc::new(Mutex::new(TokenBucket::new(limit))))
+            .clone();
+
+        {
+            let mut state = bucket.lock();
+            state.refill();
+            if state.available <= 0.0 || state.available < needed {
+                let retry_after = ((needed.max(1.0) - state.available) / rate).ceil();
+                return Err(TokenLimitExceeded {
+                    limit,
+                    requested: estimated,
+                    retry_after: (retry_after as u64).max(1),
+                });
+            }
+            state.available -= estimated as f64;
+        }
+
+        Ok(TokenCharge {
+            bucket: ChargedBucket::Local(bucket),
+            estimated,
+        })
+    }
+}
@@ -0,0 +1,96 @@
+use crate::r#const::prompt_tokens;
+use bytes::Bytes;
+use serde_json::Value;
+use tiktoken_rs::{
+    cl100k_base_singleton, o200k_base_singleton,
+    tokenizer::{get_tokenizer, Tokenizer},
+    CoreBPE,
+};
+
+/// 估算 JSON 请求体中的提示词 token 数
+///
+/// 支持 chat-completions（`messages`）和 completions（`prompt`）请求，其他请求体返回 None。
+/// 按 `model` 字段选择 OpenAI 的分词器，其他模型使用 cl100k_base 估算。
+pub fn count_prompt_tokens(body: &[u8]) -> Option<usize> {
+    let Ok(Value::Object(object)) = serde_json::from_slice::<Value>(body) else {
+        return None;
+    };
+    let bpe = bpe_for_model(
+        object
+            .get("model")
+            .and_then(Value::as_str)
+            .unwrap_or_default(),
+    );
+
+    if let Some(Value::Array(messages)) = object.get("messages") {
+        let mut tokens = prompt_tokens::REPLY_PRIMING;
+        for message in messages {
+            tokens += prompt_tokens::PER_MESSAGE;
+            tokens += count_content(bpe, &message["role"]);
+            tokens += count_content(bpe, &message["content"]);
+            if let Some(name) = message.get("name") {
+                tokens += prompt_tokens::PER_NAME + count_content(bpe, name);
+            }
+            if let Some(tool_calls) = message.get("tool_calls") {
+                tokens += count(bpe, &tool_calls.to_string());
+            }
+        }
+        // 工具定义同样占用提示词
+        if let Some(tools) = object.get("tools") {
+            tokens += count(bpe, &tools.to_string());
+        }
+        return Some(tokens);
+    }
+
+    match object.get("prompt")? {
+        Value::String(prompt) => Some(count(bpe, prompt)),
+        Value::Array(prompts) => Some(
+            prompts
+                .iter()
+                .map(|prompt| match prompt {
+                    // 已分词的提示词
+                    Value::Array(token_ids) => token_ids.len(),
+                    prompt => count_content(bpe, prompt),
+                })
+                .sum(),
+        ),
+        _ => None,
+    }
+}
+
+/// 估算请求体中的提示词 token 数，请求体较大时在阻塞线程池中估算
+pub async fn count_prompt_tokens_async(body: &Bytes) -> Option<usize> {
+    if body.len() <= prompt_tokens::BLOCKING_BODY_BYTES {
+        return count_prompt_tokens(body);
+    }
+    let body = body.clone();
+    tokio::task::spawn_blocking(move || count_prompt_tokens(&body))
+        .await
+        .ok()
+        .flatten()
+}
+
+// 按模型名称选择分词器
+fn bpe_for_model(model: &str) -> &'static CoreBPE {
+    match get_tokenizer(model) {
+        Some(Tokenizer::O200kBase) => o200k_base_singleton(),
+        _ => cl100k_base_singleton(),
+    }
+}
+
+// 统计消息内容的 token 数，内容为字符串或内容片段数组（只统计文本片段）
+fn count_content(bpe: &CoreBPE, content: &Value) -> usize {
+    match content {
+        Value::String(text) => count(bpe, text),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| part["text"].as_str())
+            .map(|text| count(bpe, text))
+            .sum(),
+        _ => 0,
+    }
+}
+
+fn count(bpe: &CoreBPE, text: &str) -> usize {
+    bpe.encode_ordinary(text).len()
+}
@@ -1,4 +1,4 @@
-use crate::{error::AppError, r#const::http_headers};
+use crate::{config::MiddlewareStage, error::AppError, r#const::http_headers, systemd};
 use axum::{
     body::{to_bytes, Body},
     http::HeaderMap,
@@ -36,6 +36,21 @@ pub(super) fn is_streaming_response(headers: &HeaderMap) -> bool {
     is_event_stream || is_chunked
 }
 
+/// 直接以流式转发的非流式响应的标记
+///
+/// 响应体超过转发服务的缓冲上限时添加到响应扩展中，后续阶段不读取完整响应体
+#[derive(Debug, Clone, Copy)]
+pub(super) struct UnbufferedBody;
+
+/// 检查响应体是否以流式转发
+///
+/// 流式响应和超过缓冲上限的非流式响应返回 true，这些响应不能读取完整响应体
+#[inline(always)]
+pub(super) fn is_unbuffered_response(response: &Response) -> bool {
+    is_streaming_response(response.headers())
+        || response.extensions().get::<UnbufferedBody>().is_some()
+}
+
 /// 标准化请求路径
 /// 将请求路径标准化为以斜杠开始的 Cow 字符串
 ///
@@ -97,7 +112,13 @@ pub(super) async fn extract_request_body(
 
 /// 创建 TCP 监听器
 /// 根据提供的地址和监听队列大小创建一个非阻塞的 TCP 监听器。
+/// systemd 套接字激活传入了该地址的监听器时直接使用。
 pub fn create_tcp_listener(addr: SocketAddr, backlog: i32) -> Result<TcpListener, AppError> {
+    if let Some(listener) = systemd::take_listener(addr) {
+        listener.set_nonblocking(true)?;
+        return TcpListener::from_std(listener).map_err(AppError::Io);
+    }
+
     // 根据地址类型确定域
     let domain = if addr.is_ipv6() {
         Domain::IPV6
@@ -154,69 +175,98 @@ pub(super) fn build_router(state: Arc<ForwardState>) -> Router {
 }
 
 /// 应用中间件配置
+///
+/// 请求超时位于最内层，入口阶段（访问日志、响应压缩、限流）按 middlewares 的顺序包裹在外，
+/// 排在前面的阶段位于外层，在途请求统计总是位于最外层
 pub(super) fn apply_middlewares(app: Router, state: &Arc<ForwardState>) -> Router {
     let mut app = app;
 
-    // 应用超时配置
-    if let Some(timeout_config) = &state.config.timeout {
-        // 创建服务构建器和层
-        let layers = tower::ServiceBuilder::new()
-            // 添加连接超时中间件
-            .layer(tower_http::timeout::TimeoutLayer::new(
-                std::time::Duration::from_secs(timeout_config.connect),
-            ));
-
-        // 应用所有中间件
-        app = app.layer(layers.into_inner());
-    } else {
-        // 使用默认超时配置
-        let default_timeout = crate::config::TimeoutConfig::default();
-        let layers = tower::ServiceBuilder::new()
-            // 添加连接超时中间件
-            .layer(tower_http::timeout::TimeoutLayer::new(
-                std::time::Duration::from_secs(default_timeout.connect),
-            ));
-
-        // 应用所有中间件
-        app = app.layer(layers.into_inner());
+    // 应用请求超时，只限制处理函数返回响应的时间，流式响应的响应体不受限制
+    let timeout = state.config.timeout.clone().unwrap_or_default();
+    app = app.layer(tower_http::timeout::TimeoutLayer::new(
+        std::time::Duration::from_secs(timeout.request),
+    ));
+
+    // 由内向外添加入口阶段
+    for stage in state.config.middleware_order().iter().rev() {
+        app = match stage {
+            MiddlewareStage::RateLimit => apply_rate_limit(app, state),
+            // 配置了响应压缩时按客户端的 Accept-Encoding 压缩非流式响应
+            MiddlewareStage::Compression => match &state.config.compression {
+                Some(compression) => app.layer(super::compression::compression_layer(compression)),
+                None => app,
+            },
+            // 配置了访问日志时记录每个请求，默认位于入口阶段的最外层（仅在在途请求统计之内），
+            // 位于内层的阶段产生的响应（如限流、超时）都会被记录，位于压缩之外时记录压缩后的字节数
+            MiddlewareStage::AccessLog => match &state.access_log {
+                Some(log) => app.layer(axum::middleware::from_fn_with_state(
+                    log.clone(),
+                    super::access_log::record_access,
+                )),
+                None => app,
+            },
+            // 其余阶段在处理函数中读取请求体之后执行
+            _ => app,
+        };
     }
 
-    // 如果存在限流配置，添加限流中间件
-    if let Some(ratelimit_config) = &state.config.ratelimit {
-        // 获取转发服务名称，用于指标记录
-        let forward_name = state.config.name.clone();
-
-        // 创建限流配置
-        let governor_conf = tower_governor::governor::GovernorConfigBuilder::default()
-            .per_second(ratelimit_config.per_second as u64)
-            .burst_size(ratelimit_config.burst)
-            // 添加自定义错误处理，记录限流指标
-            .error_handler(move |err: tower_governor::GovernorError| {
-                if let tower_governor::GovernorError::TooManyRequests { .. } = err {
-                    // 记录限流指标
-                    crate::metrics::METRICS
-                        .ratelimit_total()
-                        .with_label_values(&[&forward_name])
-                        .inc();
-                }
-
-                let status = match err {
-                    tower_governor::GovernorError::TooManyRequests { .. } => {
-                        axum::http::StatusCode::TOO_MANY_REQUESTS
-                    }
-                    _ => axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-                };
-
-                status.into_response()
-            })
-            .finish()
-            .unwrap();
-
-        // 创建限流中间件并应用
-        app = app.layer(tower_governor::GovernorLayer {
-            config: std::sync::Arc::new(governor_conf),
-        });
+    // 统计在途请求，位于最外层（入口阶段之外）以覆盖所有请求直到响应体发送完成，关闭时等待这些请求完成
+    // 关闭期间由监听器停止接收新连接并优雅关闭已有连接，中间件不会拒绝请求，已进入路由的请求照常记录访问日志
+    app.layer(axum::middleware::from_fn_with_state(
+        crate::metrics::METRICS
+            .inflight_requests()
+            .with_label_values(&[&state.config.name]),
+        super::inflight::track_inflight,
+    ))
+}
+
+// 添加请求限流中间件
+fn apply_rate_limit(app: Router, state: &Arc<ForwardState>) -> Router {
+    // 限流配置了 Redis 时添加分布式限流中间件，否则添加本地限流中间件
+    if let Some(limiter) = &state.ratelimiter {
+        return app.layer(axum::middleware::from_fn_with_state(
+            limiter.clone(),
+            super::ratelimit::distributed_rate_limit,
+        ));
     }
+    let Some(ratelimit_config) = &state.config.ratelimit else {
+        return app;
+    };
+
+    // 获取转发服务名称，用于指标记录
+    let forward_name = state.config.name.clone();
+    // 限流事件上报器
+    let reporter = std::sync::Arc::new(crate::events::RateLimitReporter::default());
+
+    // 创建限流配置
+    let governor_conf = tower_governor::governor::GovernorConfigBuilder::default()
+        .per_second(ratelimit_config.per_second as u64)
+        .burst_size(ratelimit_config.burst)
+        // 按限流键为每个客户端分配独立的令牌桶
+        .key_extractor(super::ratelimit::ClientKeyExtractor::new(
+            ratelimit_config.key,
+            ratelimit_config.header.as_deref(),
+        ))
+        // 放行和拒绝的响应都添加限流响应头
+        .use_headers()
+        // 添加自定义错误处理，记录限流指标
+        .error_handler(move |err: tower_governor::GovernorError| {
+            if let tower_governor::GovernorError::TooManyRequests { .. } = err {
+                // 记录限流指标
+                crate::metrics::METRICS
+                    .ratelimit_total()
+                    .with_label_values(&[&forward_name])
+                    .inc();
+                reporter.record(&forward_name);
+            }
+
+            super::ratelimit::rate_limited_response(err)
+        })
+        .finish()
+        .unwrap();
 
-    app
+    // 创建限流中间件并应用
+    app.layer(tower_governor::GovernorLayer {
+        config: std::sync::Arc::new(governor_conf),
+    })
 }
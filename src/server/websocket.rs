@@ -0,0 +1,263 @@
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{
+        header::{CONNECTION, UPGRADE},
+        HeaderMap, Method, StatusCode,
+    },
+    response::{IntoResponse, Response},
+};
+use hyper::upgrade::OnUpgrade;
+use hyper_util::rt::TokioIo;
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tracing::{debug, info, warn};
+
+use crate::{
+    config::WebSocketConfig,
+    metrics::METRICS,
+    r#const::websocket,
+    upstream::{RequestContext, ServedBy},
+};
+
+use super::{
+    concurrency::ConcurrencyPermit,
+    forward::ForwardState,
+    handler::{handle_request_error, handle_response},
+};
+
+/// 请求是否为 WebSocket 升级请求
+pub fn is_upgrade_request(method: &Method, headers: &HeaderMap) -> bool {
+    let connection_upgrade = headers
+        .get_all(CONNECTION)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|token| token.trim().eq_ignore_ascii_case("upgrade"));
+    let upgrade_websocket = headers
+        .get(UPGRADE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.trim().eq_ignore_ascii_case("websocket"));
+    method == Method::GET && connection_upgrade && upgrade_websocket
+}
+
+/// 将 WebSocket 升级请求转发给目标上游组，上游接受升级后在后台转发客户端与上游之间的数据
+///
+/// 升级请求按上游配置处理请求头和认证信息，不执行请求处理阶段。上游拒绝升级时原样返回上游的响应。
+/// 并发许可在连接关闭前一直持有
+pub async fn proxy_websocket(
+    state: &ForwardState,
+    config: &WebSocketConfig,
+    mut request: Request<Body>,
+    group: &str,
+    path: &str,
+    context: &RequestContext,
+    permit: Option<ConcurrencyPermit>,
+) -> Response {
+    let start_time = Instant::now();
+    let forward = state.config.name.clone();
+    let connections = METRICS.websocket_connections_total();
+
+    // 连接不支持升级（如 HTTP/2 连接）
+    let Some(on_upgrade) = request.extensions_mut().remove::<OnUpgrade>() else {
+        debug!(
+            "WebSocket upgrade request to forwarding service {:?} arrived on a connection that cannot be upgraded",
+            forward
+        );
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+
+    METRICS.record_route_match(&forward, group);
+    let headers = request.headers().clone();
+    let response = match state
+        .upstream_manager
+        .forward_upgrade(group, path, context, headers)
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            connections
+                .with_label_values(&[&forward, group, websocket::ERROR])
+                .inc();
+            return handle_request_error(
+                &e,
+                start_time,
+                state,
+                &Method::GET,
+                path,
+                group,
+                &context.request_id,
+            );
+        }
+    };
+
+    // 上游拒绝升级，按普通响应返回
+    if response.status() != StatusCode::SWITCHING_PROTOCOLS {
+        debug!(
+            "Upstream group {:?} rejected WebSocket upgrade with {}",
+            group,
+            response.status()
+        );
+        connections
+            .with_label_values(&[&forward, group, websocket::REJECTED])
+            .inc();
+        return handle_response(
+            response,
+            start_time,
+            &forward,
+            &Method::GET,
+            path,
+            group,
+            state.config.response_buffer_limit,
+        )
+        .await;
+    }
+
+    // 将上游的 101 响应返回给客户端，服务器发送响应后完成客户端连接的升级
+    let mut client_response = Response::new(Body::empty());
+    *client_response.status_mut() = StatusCode::SWITCHING_PROTOCOLS;
+    *client_response.headers_mut() = response.headers().clone();
+    if let Some(served_by) = response.extensions().get::<ServedBy>() {
+        client_response.extensions_mut().insert(served_by.clone());
+    }
+
+    let idle_timeout = Duration::from_secs(config.idle_timeout);
+    let group = group.to_string();
+    let path = path.to_string();
+    tokio::spawn(async move {
+        let _permit = permit;
+        let upstream = match response.upgrade().await {
+            Ok(upstream) => upstream,
+            Err(e) => {
+                warn!("Failed to upgrade upstream WebSocket connection: {}", e);
+                connections
+                    .with_label_values(&[&forward, &group, websocket::ERROR])
+                    .inc();
+                return;
+            }
+        };
+        let client = match on_upgrade.await {
+            Ok(client) => TokioIo::new(client),
+            Err(e) => {
+                warn!("Failed to upgrade client WebSocket connection: {}", e);
+                connections
+                    .with_label_values(&[&forward, &group, websocket::ERROR])
+                    .inc();
+                return;
+            }
+        };
+        connections
+            .with_label_values(&[&forward, &group, websocket::UPGRADED])
+            .inc();
+        info!(
+            "WebSocket connection established: {:?} to upstream group {:?}",
+            path, group
+        );
+
+        let active = METRICS
+            .websocket_active_connections()
+            .with_label_values(&[&forward, &group]);
+        active.inc();
+        let connected_at = Instant::now();
+        let closed_by = relay(client, upstream, idle_timeout, &forward, &group).await;
+        active.dec();
+
+        let duration = connected_at.elapsed();
+        METRICS
+            .websocket_duration_seconds()
+            .with_label_values(&[&forward, &group])
+            .observe(duration.as_secs_f64());
+        match closed_by {
+            Ok(closed_by) => info!(
+                "WebSocket connection closed by {}: {:?} to upstream group {:?}, time: {}ms",
+                closed_by,
+                path,
+                group,
+                duration.as_millis()
+            ),
+            Err(e) => info!(
+                "WebSocket connection closed with error: {:?} to upstream group {:?}, time: {}ms, error: {}",
+                path,
+                group,
+                duration.as_millis(),
+                e
+            ),
+        }
+    });
+
+    client_response
+}
+
+// 在客户端和上游之间转发数据，返回关闭连接的一方
+// 任一方关闭连接或两个方向在空闲超时内都没有数据时关闭两端的连接
+async fn relay<C, U>(
+    client: C,
+    upstream: U,
+    idle_timeout: Duration,
+    forward: &str,
+    group: &str,
+) -> io::Result<&'static str>
+where
+    C: AsyncRead + AsyncWrite,
+    U: AsyncRead + AsyncWrite,
+{
+    let (mut client_read, mut client_write) = tokio::io::split(client);
+    let (mut upstream_read, mut upstream_write) = tokio::io::split(upstream);
+    let mut client_buf = vec![0; websocket::BUFFER_SIZE];
+    let mut upstream_buf = vec![0; websocket::BUFFER_SIZE];
+    let sent = METRICS.websocket_bytes_total().with_label_values(&[
+        forward,
+        group,
+        websocket::CLIENT_TO_UPSTREAM,
+    ]);
+    let received = METRICS.websocket_bytes_total().with_label_values(&[
+        forward,
+        group,
+        websocket::UPSTREAM_TO_CLIENT,
+    ]);
+
+    let closed_by = loop {
+        let read = tokio::time::timeout(idle_timeout, async {
+            tokio::select! {
+                result = client_read.read(&mut client_buf) => (true, result),
+                result = upstream_read.read(&mut upstream_buf) => (false, result),
+            }
+        })
+        .await;
+        let Ok((from_client, result)) = read else {
+            break "idle timeout";
+        };
+        let len = result?;
+        if len == 0 {
+            break if from_client { "client" } else { "upstream" };
+        }
+        if from_client {
+            write_all(&mut upstream_write, &client_buf[..len], idle_timeout).await?;
+            sent.inc_by(len as u64);
+        } else {
+            write_all(&mut client_write, &upstream_buf[..len], idle_timeout).await?;
+            received.inc_by(len as u64);
+        }
+    };
+
+    let _ = client_write.shutdown().await;
+    let _ = upstream_write.shutdown().await;
+    Ok(closed_by)
+}
+
+// 写入数据，对端在空闲超时内没有读取时返回错误
+async fn write_all<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    data: &[u8],
+    timeout: Duration,
+) -> io::Result<()> {
+    tokio::time::timeout(timeout, async {
+        writer.write_all(data).await?;
+        writer.flush().await
+    })
+    .await
+    .unwrap_or_else(|_| Err(io::Error::new(io::ErrorKind::TimedOut, "write timed out")))
+}
@@ -0,0 +1,319 @@
+use crate::{
+    api::v1::routes::{API_V1_PREFIX, SUPPORT_BUNDLE_PATH},
+    args::SupportBundleArgs,
+    audit::config_snapshot,
+    config::Config,
+    error::AppError,
+    events::unix_millis,
+    logfile::LogFileWriter,
+    r#const::{api, log_file, redact::MASKED_VALUE, support_bundle},
+};
+use flate2::{write::GzEncoder, Compression};
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::Mutex;
+use reqwest::{header, Url};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    io::{self, Write},
+    path::PathBuf,
+    time::Duration,
+};
+use tracing_subscriber::fmt::MakeWriter;
+
+// 环境变量名称中包含这些关键字时脱敏
+const SECRET_ENV_KEYWORDS: [&str; 4] = ["TOKEN", "SECRET", "PASSWORD", "KEY"];
+
+// 最近的日志，用于生成支持包
+static RECENT_LOGS: Lazy<Mutex<VecDeque<String>>> =
+    Lazy::new(|| Mutex::new(VecDeque::with_capacity(support_bundle::RECENT_LOG_LINES)));
+
+// 日志文件写入器，以及是否同时输出到标准输出
+static LOG_FILE: OnceCell<(LogFileWriter, bool)> = OnceCell::new();
+
+/// 将日志写入日志文件，stdout 为 true 时同时输出到标准输出，只能设置一次
+pub fn set_log_file(writer: LogFileWriter, stdout: bool) -> bool {
+    LOG_FILE.set((writer, stdout)).is_ok()
+}
+
+/// 等待日志文件写完队列中的日志，用于直接退出进程前
+pub fn flush_log_file() {
+    if let Some((writer, _)) = LOG_FILE.get() {
+        writer.flush(Duration::from_millis(log_file::FLUSH_TIMEOUT_MS));
+    }
+}
+
+// 日志写入器，写入标准输出（或日志文件）的同时在内存中保留最近的日志
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogWriter;
+
+impl<'a> MakeWriter<'a> for LogWriter {
+    type Writer = LogLineWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LogLineWriter { line: Vec::new() }
+    }
+}
+
+// 单条日志的写入器，日志写完（被丢弃）时输出
+pub struct LogLineWriter {
+    line: Vec<u8>,
+}
+
+impl Write for LogLineWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.line.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for LogLineWriter {
+    fn drop(&mut self) {
+        if self.line.is_empty() {
+            return;
+        }
+
+        let line = String::from_utf8_lossy(&self.line).trim_end().to_string();
+        {
+            let mut logs = RECENT_LOGS.lock();
+            if logs.len() >= support_bundle::RECENT_LOG_LINES {
+                logs.pop_front();
+            }
+            logs.push_back(line);
+        }
+
+        match LOG_FILE.get() {
+            Some((writer, stdout)) => {
+                if *stdout {
+                    let _ = io::stdout().write_all(&self.line);
+                }
+                writer.write(std::mem::take(&mut self.line));
+            }
+            None => {
+                let _ = io::stdout().write_all(&self.line);
+            }
+        }
+    }
+}
+
+// 获取最近的日志
+pub fn recent_logs() -> String {
+    let logs = RECENT_LOGS.lock();
+    let mut text = logs.iter().fold(String::new(), |mut text, line| {
+        text.push_str(line);
+        text.push('\n');
+        text
+    });
+
+    if text.is_empty() {
+        text.push_str("# No log lines captured\n");
+    }
+
+    text
+}
+
+// 版本信息
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionInfo {
+    // 程序名称
+    pub name: &'static str,
+    // 程序版本
+    pub version: &'static str,
+    // 生成时间（Unix 毫秒时间戳）
+    pub generated_at: u64,
+}
+
+impl VersionInfo {
+    pub fn current() -> Self {
+        Self {
+            name: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            generated_at: unix_millis(),
+        }
+    }
+}
+
+// 运行环境信息
+#[derive(Debug, Clone, Serialize)]
+pub struct EnvironmentInfo {
+    // 操作系统
+    pub os: &'static str,
+    // 操作系统家族
+    pub family: &'static str,
+    // CPU 架构
+    pub arch: &'static str,
+    // 可用 CPU 数量
+    pub cpus: usize,
+    // 进程 ID
+    pub pid: u32,
+    // 相关环境变量（敏感值已脱敏）
+    pub env: BTreeMap<String, String>,
+}
+
+impl EnvironmentInfo {
+    pub fn current() -> Self {
+        let env = std::env::vars()
+            .filter(|(name, _)| {
+                name.starts_with(support_bundle::ENV_PREFIX)
+                    || support_bundle::EXTRA_ENV_VARS.contains(&name.as_str())
+            })
+            .map(|(name, value)| {
+                let value = if SECRET_ENV_KEYWORDS
+                    .iter()
+                    .any(|keyword| name.to_ascii_uppercase().contains(keyword))
+                {
+                    MASKED_VALUE.to_string()
+                } else {
+                    value
+                };
+                (name, value)
+            })
+            .collect();
+
+        Self {
+            os: std::env::consts::OS,
+            family: std::env::consts::FAMILY,
+            arch: std::env::consts::ARCH,
+            cpus: std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            pid: std::process::id(),
+            env,
+        }
+    }
+}
+
+// 生成脱敏后的 YAML 配置
+pub fn redacted_config_yaml(config: &Config) -> Result<String, AppError> {
+    serde_yaml::to_string(&config_snapshot(config))
+        .map_err(|e| AppError::Internal(format!("Failed to serialize configuration: {}", e)))
+}
+
+// 支持包，打包为 tar.gz 归档
+pub struct SupportBundle {
+    // 归档内的顶层目录名称
+    prefix: String,
+    // 文件名称和内容
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl Default for SupportBundle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SupportBundle {
+    pub fn new() -> Self {
+        Self {
+            prefix: format!("llmproxy-support-{}", unix_millis()),
+            files: Vec::new(),
+        }
+    }
+
+    // 归档文件名称
+    pub fn file_name(&self) -> String {
+        format!("{}.tar.gz", self.prefix)
+    }
+
+    // 添加文件
+    pub fn add(&mut self, name: &str, content: impl Into<Vec<u8>>) {
+        self.files.push((name.to_string(), content.into()));
+    }
+
+    // 以格式化 JSON 添加文件
+    pub fn add_json<T: Serialize>(&mut self, name: &str, value: &T) -> Result<(), AppError> {
+        let content = serde_json::to_vec_pretty(value)?;
+        self.add(name, content);
+        Ok(())
+    }
+
+    // 生成 tar.gz 归档
+    pub fn finish(self) -> Result<Vec<u8>, AppError> {
+        let mtime = unix_millis() / 1000;
+        let mut archive = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+
+        for (name, content) in &self.files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(mtime);
+            header.set_cksum();
+            archive.append_data(
+                &mut header,
+                format!("{}/{}", self.prefix, name),
+                content.as_slice(),
+            )?;
+        }
+
+        Ok(archive.into_inner()?.finish()?)
+    }
+}
+
+// 构建支持包下载地址
+pub fn build_bundle_url(admin: &str) -> Result<Url, AppError> {
+    let mut url = Url::parse(admin)
+        .map_err(|e| AppError::Config(format!("Invalid admin URL {:?}: {}", admin, e)))?;
+
+    url.set_path(&format!("{}{}", API_V1_PREFIX, SUPPORT_BUNDLE_PATH));
+    url.set_query(None);
+
+    Ok(url)
+}
+
+// 从管理服务下载支持包并保存到本地
+pub async fn run(args: &SupportBundleArgs) -> Result<PathBuf, AppError> {
+    let url = build_bundle_url(&args.admin)?;
+    let token = args
+        .token
+        .clone()
+        .or_else(|| std::env::var(api::ADMIN_AUTH_TOKEN_ENV).ok());
+
+    let mut request = reqwest::Client::new().get(url);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(AppError::Internal(format!(
+            "Admin server returned {}: {}",
+            status, body
+        )));
+    }
+
+    // 未指定输出路径时使用管理服务返回的文件名
+    let output = match args.output {
+        Some(ref output) => output.clone(),
+        None => response
+            .headers()
+            .get(header::CONTENT_DISPOSITION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(attachment_file_name)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(SupportBundle::new().file_name())),
+    };
+
+    let content = response.bytes().await?;
+    std::fs::write(&output, &content)?;
+
+    Ok(output)
+}
+
+// 从 Content-Disposition 头中提取文件名
+pub fn attachment_file_name(value: &str) -> Option<String> {
+    value
+        .split(';')
+        .filter_map(|part| part.trim().strip_prefix("filename="))
+        .map(|name| name.trim_matches('"'))
+        // 只保留文件名部分，避免写入其他目录
+        .filter(|name| !name.is_empty() && !name.contains(['/', '\\']) && *name != "..")
+        .map(str::to_string)
+        .next()
+}
@@ -0,0 +1,162 @@
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::{net::SocketAddr, time::Duration};
+use tokio_graceful_shutdown::SubsystemHandle;
+use tracing::{info, warn};
+
+use crate::{error::AppError, r#const::systemd, restart};
+
+// systemd 套接字激活传入、尚未被使用的监听器
+static INHERITED: Lazy<Mutex<Vec<std::net::TcpListener>>> =
+    Lazy::new(|| Mutex::new(inherit_listeners()));
+
+/// 取出 systemd 套接字激活传入的、绑定在指定地址上的监听器
+///
+/// 未使用套接字激活或没有绑定在该地址上的监听器时返回 None
+pub fn take_listener(addr: SocketAddr) -> Option<std::net::TcpListener> {
+    let mut listeners = INHERITED.lock();
+    let index = listeners
+        .iter()
+        .position(|listener| listener.local_addr().is_ok_and(|a| a == addr))?;
+    Some(listeners.swap_remove(index))
+}
+
+// 接管 LISTEN_FDS 指定的文件描述符，LISTEN_PID 不是本进程时忽略
+#[cfg(target_os = "linux")]
+fn inherit_listeners() -> Vec<std::net::TcpListener> {
+    use socket2::{Socket, Type};
+    use std::os::fd::{FromRawFd, IntoRawFd};
+
+    let pid = std::env::var(systemd::LISTEN_PID)
+        .ok()
+        .and_then(|pid| pid.parse::<u32>().ok());
+    if pid != Some(std::process::id()) {
+        return Vec::new();
+    }
+    let count = std::env::var(systemd::LISTEN_FDS)
+        .ok()
+        .and_then(|count| count.parse::<i32>().ok())
+        .unwrap_or(0);
+
+    (systemd::LISTEN_FDS_START..systemd::LISTEN_FDS_START + count)
+        .filter_map(|fd| {
+            // 重启启动的新进程不继承这些描述符
+            // SAFETY: fcntl 只修改描述符标志，不访问内存
+            unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) };
+            // SAFETY: systemd 传入的描述符归本进程所有，只在这里接管一次
+            let socket = unsafe { Socket::from_raw_fd(fd) };
+            let addr = socket.local_addr().ok().and_then(|addr| addr.as_socket());
+            match addr {
+                Some(addr) if socket.r#type().is_ok_and(|t| t == Type::STREAM) => {
+                    info!("Inherited listener on {:?} from systemd", addr);
+                    Some(socket.into())
+                }
+                _ => {
+                    warn!("Ignoring socket {} from systemd, not a TCP listener", fd);
+                    let _ = socket.into_raw_fd();
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn inherit_listeners() -> Vec<std::net::TcpListener> {
+    Vec::new()
+}
+
+/// 向 systemd 发送状态通知（sd_notify），未设置 NOTIFY_SOCKET 或发送失败时返回 false
+pub fn notify(state: &str) -> bool {
+    let Some(path) = std::env::var_os(systemd::NOTIFY_SOCKET) else {
+        return false;
+    };
+
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::{
+            linux::net::SocketAddrExt,
+            unix::{
+                ffi::OsStrExt,
+                net::{SocketAddr, UnixDatagram},
+            },
+        };
+
+        // 以 @ 开头的是抽象命名空间的套接字
+        let send = || {
+            let addr = match path.as_bytes().strip_prefix(b"@") {
+                Some(name) => SocketAddr::from_abstract_name(name)?,
+                None => SocketAddr::from_pathname(&path)?,
+            };
+            UnixDatagram::unbound()?.send_to_addr(state.as_bytes(), &addr)
+        };
+        match send() {
+            Ok(_) => true,
+            Err(e) => {
+                warn!("Failed to notify systemd via {:?}: {}", path, e);
+                false
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = state;
+        tracing::debug!(
+            "Ignoring {:?}, systemd notifications are only supported on Linux",
+            path
+        );
+        false
+    }
+}
+
+/// systemd 看门狗的通知间隔，为 WATCHDOG_USEC 的一半
+///
+/// 未启用看门狗或 WATCHDOG_PID 指定了其他进程时返回 None
+pub fn watchdog_interval() -> Option<Duration> {
+    let usec = std::env::var(systemd::WATCHDOG_USEC)
+        .ok()?
+        .parse::<u64>()
+        .ok()
+        .filter(|usec| *usec > 0)?;
+    if let Ok(pid) = std::env::var(systemd::WATCHDOG_PID) {
+        if pid.parse::<u32>().ok() != Some(std::process::id()) {
+            return None;
+        }
+    }
+    Some(Duration::from_micros(usec) / 2)
+}
+
+/// 通知 systemd 服务已就绪，按看门狗间隔发送心跳，关闭时通知服务正在停止
+///
+/// 未在 systemd 下以 Type=notify 运行时只等待关闭
+pub async fn run(subsys: SubsystemHandle) -> Result<(), AppError> {
+    // 重启启动的新进程同时报告自己是新的主进程
+    let ready = format!("{}\nMAINPID={}", systemd::READY, std::process::id());
+    if !notify(&ready) {
+        subsys.on_shutdown_requested().await;
+        return Ok(());
+    }
+    info!("Notified systemd that the service is ready");
+
+    match watchdog_interval() {
+        Some(interval) => {
+            info!("systemd watchdog enabled, notifying every {:?}", interval);
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(interval) => {
+                        notify(systemd::WATCHDOG);
+                    }
+                    _ = subsys.on_shutdown_requested() => break,
+                }
+            }
+        }
+        None => subsys.on_shutdown_requested().await,
+    }
+
+    // 重启时新进程已成为主进程，旧进程退出不代表服务停止
+    if !restart::is_restarting() {
+        notify(systemd::STOPPING);
+    }
+    Ok(())
+}
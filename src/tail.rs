@@ -0,0 +1,190 @@
+use crate::{
+    api::v1::routes::{ACCESS_LOG_STREAM_PATH, API_V1_PREFIX},
+    args::TailArgs,
+    error::AppError,
+    events::{AccessEvent, AccessFilter},
+    r#const::{api, event_types, http_headers::content_types},
+};
+use reqwest::{header, Url};
+use std::io::IsTerminal;
+
+// 终端颜色
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const CYAN: &str = "\x1b[36m";
+
+// 服务器推送事件帧
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SseFrame {
+    // 事件类型
+    pub event: Option<String>,
+    // 事件数据，多行 data 以换行连接
+    pub data: String,
+}
+
+// 增量解析服务器推送事件流
+#[derive(Debug, Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+}
+
+impl SseParser {
+    // 追加数据块，返回已完整接收的事件帧
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseFrame> {
+        self.buffer
+            .extend(chunk.iter().copied().filter(|byte| *byte != b'\r'));
+
+        let mut frames = Vec::new();
+        while let Some(pos) = self.buffer.windows(2).position(|w| w == b"\n\n") {
+            let raw: Vec<u8> = self.buffer.drain(..pos + 2).collect();
+            let text = String::from_utf8_lossy(&raw[..pos]);
+
+            let mut event = None;
+            let mut data = Vec::new();
+            for line in text.lines() {
+                if let Some(value) = line.strip_prefix("event:") {
+                    event = Some(value.trim_start().to_string());
+                } else if let Some(value) = line.strip_prefix("data:") {
+                    data.push(value.strip_prefix(' ').unwrap_or(value));
+                }
+                // 注释行（如保活心跳）和其他字段直接忽略
+            }
+
+            if event.is_some() || !data.is_empty() {
+                frames.push(SseFrame {
+                    event,
+                    data: data.join("\n"),
+                });
+            }
+        }
+
+        frames
+    }
+}
+
+// 构建访问日志流地址
+pub fn build_stream_url(admin: &str, filters: &[(String, String)]) -> Result<Url, AppError> {
+    let mut url = Url::parse(admin)
+        .map_err(|e| AppError::Config(format!("Invalid admin URL {:?}: {}", admin, e)))?;
+
+    url.set_path(&format!("{}{}", API_V1_PREFIX, ACCESS_LOG_STREAM_PATH));
+    url.set_query(None);
+    if !filters.is_empty() {
+        url.query_pairs_mut().extend_pairs(filters);
+    }
+
+    Ok(url)
+}
+
+// 为文本添加终端颜色
+#[inline(always)]
+fn paint(text: &str, style: &str, color: bool) -> String {
+    if color {
+        format!("{}{}{}", style, text, RESET)
+    } else {
+        text.to_string()
+    }
+}
+
+// 根据状态码选择颜色
+#[inline(always)]
+fn status_style(status: u16) -> &'static str {
+    match status {
+        200..=299 => GREEN,
+        300..=399 => CYAN,
+        400..=499 => YELLOW,
+        _ => RED,
+    }
+}
+
+// 格式化访问事件为单行文本，时间为 UTC
+pub fn format_access_event(event: &AccessEvent, color: bool) -> String {
+    let secs = event.timestamp / 1000;
+    let time = format!(
+        "{:02}:{:02}:{:02}.{:03}Z",
+        (secs / 3600) % 24,
+        (secs / 60) % 60,
+        secs % 60,
+        event.timestamp % 1000
+    );
+
+    let mut line = format!(
+        "{} {} {} {} -> {} {} {}ms",
+        paint(&time, DIM, color),
+        paint(&format!("[{}]", event.forward), CYAN, color),
+        paint(&event.method, BOLD, color),
+        event.path,
+        event.group,
+        paint(&event.status.to_string(), status_style(event.status), color),
+        event.duration_ms
+    );
+
+    if let Some(ref error) = event.error {
+        line.push(' ');
+        line.push_str(&paint(error, RED, color));
+    }
+
+    line
+}
+
+// 连接管理服务的访问日志流并实时打印
+pub async fn run(args: &TailArgs) -> Result<(), AppError> {
+    // 先在本地校验过滤条件，尽早给出错误提示
+    AccessFilter::parse(args.filters.iter().map(|(k, v)| (k, v)))
+        .map_err(AppError::ValidationError)?;
+
+    let url = build_stream_url(&args.admin, &args.filters)?;
+    let token = args
+        .token
+        .clone()
+        .or_else(|| std::env::var(api::ADMIN_AUTH_TOKEN_ENV).ok());
+
+    let mut request = reqwest::Client::new()
+        .get(url.clone())
+        .header(header::ACCEPT, content_types::EVENT_STREAM);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+
+    let mut response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(AppError::Internal(format!(
+            "Admin server returned {}: {}",
+            status, body
+        )));
+    }
+
+    let color = !args.no_color && std::io::stdout().is_terminal();
+    eprintln!("Tailing access log from {} (Ctrl+C to stop)", url);
+
+    let mut parser = SseParser::default();
+    while let Some(chunk) = response.chunk().await? {
+        for frame in parser.feed(&chunk) {
+            match frame.event.as_deref() {
+                Some(event_types::ACCESS) => match serde_json::from_str::<AccessEvent>(&frame.data)
+                {
+                    Ok(event) => println!("{}", format_access_event(&event, color)),
+                    Err(e) => eprintln!("Skipping malformed access event: {}", e),
+                },
+                Some(event_types::LAGGED) => eprintln!(
+                    "{}",
+                    paint(
+                        &format!("... {} events dropped (client too slow)", frame.data),
+                        YELLOW,
+                        color
+                    )
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    eprintln!("Access log stream closed by admin server");
+    Ok(())
+}
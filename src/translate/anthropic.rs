@@ -0,0 +1,320 @@
+use super::{
+    assistant_message, chunk, completion_id, created, error, max_tokens, parse_arguments,
+    parse_data_url, sse::SseEvent, stop_sequences, take_array, text_content, tool_call, usage,
+    StreamTranslator,
+};
+use crate::r#const::dialect;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+// 将 OpenAI 聊天请求转换为 Anthropic Messages 请求
+pub(super) fn request(mut request: Map<String, Value>) -> Value {
+    let mut system = Vec::new();
+    let mut messages: Vec<(&str, Vec<Value>)> = Vec::new();
+
+    for message in take_array(&mut request, "messages") {
+        let (role, blocks) = match message["role"].as_str().unwrap_or_default() {
+            // 系统消息合并为 system 参数
+            "system" | "developer" => {
+                system.push(text_content(&message["content"]));
+                continue;
+            }
+            "assistant" => {
+                let mut blocks = content_blocks(&message["content"]);
+                for call in message["tool_calls"].as_array().into_iter().flatten() {
+                    blocks.push(json!({
+                        "type": "tool_use",
+                        "id": call["id"],
+                        "name": call["function"]["name"],
+                        "input": parse_arguments(&call["function"]["arguments"]),
+                    }));
+                }
+                ("assistant", blocks)
+            }
+            // 工具结果作为用户消息中的 tool_result 内容块
+            "tool" => (
+                "user",
+                vec![json!({
+                    "type": "tool_result",
+                    "tool_use_id": message["tool_call_id"],
+                    "content": text_content(&message["content"]),
+                })],
+            ),
+            _ => ("user", content_blocks(&message["content"])),
+        };
+        if blocks.is_empty() {
+            continue;
+        }
+
+        // 相同角色的连续消息合并为一条
+        match messages.last_mut() {
+            Some((last_role, last_blocks)) if *last_role == role => last_blocks.extend(blocks),
+            _ => messages.push((role, blocks)),
+        }
+    }
+
+    let mut output = Map::new();
+    output.insert(
+        "model".to_string(),
+        request.remove("model").unwrap_or_default(),
+    );
+    output.insert(
+        "messages".to_string(),
+        messages
+            .into_iter()
+            .map(|(role, content)| json!({"role": role, "content": content}))
+            .collect(),
+    );
+    output.insert(
+        "max_tokens".to_string(),
+        max_tokens(&request).unwrap_or_else(|| json!(dialect::DEFAULT_MAX_TOKENS)),
+    );
+    if !system.is_empty() {
+        output.insert("system".to_string(), json!(system.join("\n\n")));
+    }
+    for key in ["temperature", "top_p", "stream"] {
+        if let Some(value) = request.remove(key) {
+            output.insert(key.to_string(), value);
+        }
+    }
+    if let Some(stop) = stop_sequences(&request) {
+        output.insert("stop_sequences".to_string(), stop);
+    }
+    if let Some(user) = request.remove("user") {
+        output.insert("metadata".to_string(), json!({"user_id": user}));
+    }
+
+    let tools: Vec<Value> = take_array(&mut request, "tools")
+        .iter()
+        .map(|tool| &tool["function"])
+        .filter(|function| function.is_object())
+        .map(|function| {
+            let mut tool = Map::new();
+            tool.insert("name".to_string(), function["name"].clone());
+            if let Some(description) = function.get("description") {
+                tool.insert("description".to_string(), description.clone());
+            }
+            tool.insert(
+                "input_schema".to_string(),
+                function
+                    .get("parameters")
+                    .cloned()
+                    .unwrap_or_else(|| json!({"type": "object"})),
+            );
+            Value::Object(tool)
+        })
+        .collect();
+    if !tools.is_empty() {
+        output.insert("tools".to_string(), Value::Array(tools));
+    }
+    let tool_choice = match request.get("tool_choice") {
+        Some(Value::String(choice)) => match choice.as_str() {
+            "auto" => Some(json!({"type": "auto"})),
+            "required" => Some(json!({"type": "any"})),
+            "none" => Some(json!({"type": "none"})),
+            _ => None,
+        },
+        Some(Value::Object(choice)) => choice
+            .get("function")
+            .map(|function| json!({"type": "tool", "name": function["name"]})),
+        _ => None,
+    };
+    if let Some(tool_choice) = tool_choice {
+        output.insert("tool_choice".to_string(), tool_choice);
+    }
+
+    Value::Object(output)
+}
+
+// 将 OpenAI 消息内容转换为 Anthropic 内容块
+fn content_blocks(content: &Value) -> Vec<Value> {
+    match content {
+        Value::String(text) if !text.is_empty() => vec![json!({"type": "text", "text": text})],
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| match part["type"].as_str()? {
+                "text" => Some(json!({"type": "text", "text": part["text"]})),
+                "image_url" => {
+                    let url = part["image_url"]["url"].as_str()?;
+                    let source = match parse_data_url(url) {
+                        Some((media_type, data)) => {
+                            json!({"type": "base64", "media_type": media_type, "data": data})
+                        }
+                        None => json!({"type": "url", "url": url}),
+                    };
+                    Some(json!({"type": "image", "source": source}))
+                }
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// 将 Anthropic 响应转换为 OpenAI chat.completion
+pub(super) fn response(body: Value, model: &str) -> Value {
+    match body["type"].as_str() {
+        Some("error") => return convert_error(&body),
+        Some("message") => {}
+        _ => return body,
+    }
+
+    let mut text = String::new();
+    let mut tool_calls = Vec::new();
+    for block in body["content"].as_array().into_iter().flatten() {
+        match block["type"].as_str() {
+            Some("text") => text.push_str(block["text"].as_str().unwrap_or_default()),
+            Some("tool_use") => tool_calls.push(tool_call(
+                block["id"].clone(),
+                block["name"].clone(),
+                &block["input"],
+            )),
+            _ => {}
+        }
+    }
+
+    json!({
+        "id": body["id"].as_str().map(str::to_string).unwrap_or_else(completion_id),
+        "object": "chat.completion",
+        "created": created(),
+        "model": body["model"].as_str().unwrap_or(model),
+        "choices": [{
+            "index": 0,
+            "message": assistant_message(text, tool_calls),
+            "finish_reason": finish_reason(body["stop_reason"].as_str()),
+        }],
+        "usage": usage(
+            body["usage"]["input_tokens"].as_u64().unwrap_or_default(),
+            body["usage"]["output_tokens"].as_u64().unwrap_or_default(),
+        ),
+    })
+}
+
+// 转换停止原因
+fn finish_reason(stop_reason: Option<&str>) -> Option<&'static str> {
+    stop_reason.map(|reason| match reason {
+        "max_tokens" => "length",
+        "tool_use" => "tool_calls",
+        "refusal" => "content_filter",
+        _ => "stop",
+    })
+}
+
+// 转换错误响应
+fn convert_error(body: &Value) -> Value {
+    error(
+        &body["error"]["message"],
+        &body["error"]["type"],
+        &Value::Null,
+    )
+}
+
+// Anthropic 事件流转换状态
+pub(super) struct StreamState {
+    // 响应 ID
+    id: String,
+    // 模型名称
+    model: String,
+    // 创建时间
+    created: u64,
+    // 内容块序号到工具调用序号的映射
+    tool_indexes: HashMap<u64, usize>,
+    // 输入 token 数
+    input_tokens: u64,
+}
+
+impl StreamState {
+    pub(super) fn new(model: &str) -> Self {
+        Self {
+            id: completion_id(),
+            model: model.to_string(),
+            created: created(),
+            tool_indexes: HashMap::new(),
+            input_tokens: 0,
+        }
+    }
+
+    fn chunk(&self, delta: Value, finish_reason: Option<&str>) -> Value {
+        chunk(&self.id, &self.model, self.created, 0, delta, finish_reason)
+    }
+}
+
+impl StreamTranslator for StreamState {
+    fn event(&mut self, event: SseEvent) -> Vec<Value> {
+        let Ok(data) = serde_json::from_str::<Value>(&event.data) else {
+            return Vec::new();
+        };
+
+        match data["type"].as_str().unwrap_or_default() {
+            "message_start" => {
+                let message = &data["message"];
+                if let Some(id) = message["id"].as_str() {
+                    self.id = id.to_string();
+                }
+                if let Some(model) = message["model"].as_str() {
+                    self.model = model.to_string();
+                }
+                self.input_tokens = message["usage"]["input_tokens"]
+                    .as_u64()
+                    .unwrap_or_default();
+                vec![self.chunk(json!({"role": "assistant", "content": ""}), None)]
+            }
+            "content_block_start" => {
+                let block = &data["content_block"];
+                match block["type"].as_str() {
+                    Some("tool_use") => {
+                        let index = self.tool_indexes.len();
+                        self.tool_indexes
+                            .insert(data["index"].as_u64().unwrap_or_default(), index);
+                        vec![self.chunk(
+                            json!({"tool_calls": [{
+                                "index": index,
+                                "id": block["id"],
+                                "type": "function",
+                                "function": {"name": block["name"], "arguments": ""},
+                            }]}),
+                            None,
+                        )]
+                    }
+                    Some("text") if block["text"].as_str().is_some_and(|t| !t.is_empty()) => {
+                        vec![self.chunk(json!({"content": block["text"]}), None)]
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            "content_block_delta" => {
+                let delta = &data["delta"];
+                match delta["type"].as_str() {
+                    Some("text_delta") => vec![self.chunk(json!({"content": delta["text"]}), None)],
+                    Some("input_json_delta") => {
+                        let Some(index) = data["index"]
+                            .as_u64()
+                            .and_then(|index| self.tool_indexes.get(&index))
+                        else {
+                            return Vec::new();
+                        };
+                        vec![self.chunk(
+                            json!({"tool_calls": [{
+                                "index": index,
+                                "function": {"arguments": delta["partial_json"]},
+                            }]}),
+                            None,
+                        )]
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            "message_delta" => {
+                let output_tokens = data["usage"]["output_tokens"].as_u64().unwrap_or_default();
+                let mut chunk = self.chunk(
+                    json!({}),
+                    finish_reason(data["delta"]["stop_reason"].as_str()),
+                );
+                chunk["usage"] = usage(self.input_tokens, output_tokens);
+                vec![chunk]
+            }
+            "error" => vec![convert_error(&data)],
+            _ => Vec::new(),
+        }
+    }
+}
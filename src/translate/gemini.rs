@@ -0,0 +1,357 @@
+use super::{
+    assistant_message, chunk, completion_id, created, error, max_tokens, parse_arguments,
+    parse_data_url, sse::SseEvent, stop_sequences, take_array, text_content, tool_call,
+    tool_call_id, usage, StreamTranslator,
+};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+// 将 OpenAI 聊天请求转换为 Gemini generateContent 请求
+pub(super) fn request(mut request: Map<String, Value>) -> Value {
+    let mut system = Vec::new();
+    let mut contents: Vec<(&str, Vec<Value>)> = Vec::new();
+    // 工具调用 ID 到函数名的映射，Gemini 的函数结果按函数名关联
+    let mut tool_names: HashMap<String, String> = HashMap::new();
+
+    for message in take_array(&mut request, "messages") {
+        let (role, parts) = match message["role"].as_str().unwrap_or_default() {
+            // 系统消息合并为 systemInstruction
+            "system" | "developer" => {
+                system.push(text_content(&message["content"]));
+                continue;
+            }
+            "assistant" => {
+                let mut parts = content_parts(&message["content"]);
+                for call in message["tool_calls"].as_array().into_iter().flatten() {
+                    let function = &call["function"];
+                    if let (Some(id), Some(name)) = (call["id"].as_str(), function["name"].as_str())
+                    {
+                        tool_names.insert(id.to_string(), name.to_string());
+                    }
+                    parts.push(json!({"functionCall": {
+                        "name": function["name"],
+                        "args": parse_arguments(&function["arguments"]),
+                    }}));
+                }
+                ("model", parts)
+            }
+            "tool" => {
+                let name = message["tool_call_id"]
+                    .as_str()
+                    .and_then(|id| tool_names.get(id))
+                    .map(String::as_str)
+                    .or_else(|| message["name"].as_str())
+                    .unwrap_or_default()
+                    .to_string();
+                // 函数结果必须是 JSON 对象，其他内容包装为 {"content": ...}
+                let content = text_content(&message["content"]);
+                let response = match serde_json::from_str::<Value>(&content) {
+                    Ok(Value::Object(object)) => Value::Object(object),
+                    _ => json!({"content": content}),
+                };
+                (
+                    "user",
+                    vec![json!({"functionResponse": {"name": name, "response": response}})],
+                )
+            }
+            _ => ("user", content_parts(&message["content"])),
+        };
+        if parts.is_empty() {
+            continue;
+        }
+
+        // 相同角色的连续消息合并为一条
+        match contents.last_mut() {
+            Some((last_role, last_parts)) if *last_role == role => last_parts.extend(parts),
+            _ => contents.push((role, parts)),
+        }
+    }
+
+    let mut output = Map::new();
+    output.insert(
+        "contents".to_string(),
+        contents
+            .into_iter()
+            .map(|(role, parts)| json!({"role": role, "parts": parts}))
+            .collect(),
+    );
+    if !system.is_empty() {
+        output.insert(
+            "systemInstruction".to_string(),
+            json!({"parts": [{"text": system.join("\n\n")}]}),
+        );
+    }
+
+    let mut config = Map::new();
+    for (from, to) in [
+        ("temperature", "temperature"),
+        ("top_p", "topP"),
+        ("n", "candidateCount"),
+        ("seed", "seed"),
+    ] {
+        if let Some(value) = request.get(from) {
+            config.insert(to.to_string(), value.clone());
+        }
+    }
+    if let Some(max_tokens) = max_tokens(&request) {
+        config.insert("maxOutputTokens".to_string(), max_tokens);
+    }
+    if let Some(stop) = stop_sequences(&request) {
+        config.insert("stopSequences".to_string(), stop);
+    }
+    if matches!(
+        request
+            .get("response_format")
+            .and_then(|format| format["type"].as_str()),
+        Some("json_object" | "json_schema")
+    ) {
+        config.insert("responseMimeType".to_string(), json!("application/json"));
+    }
+    if !config.is_empty() {
+        output.insert("generationConfig".to_string(), Value::Object(config));
+    }
+
+    let declarations: Vec<Value> = take_array(&mut request, "tools")
+        .iter()
+        .map(|tool| &tool["function"])
+        .filter(|function| function.is_object())
+        .map(|function| {
+            let mut declaration = Map::new();
+            for key in ["name", "description", "parameters"] {
+                if let Some(value) = function.get(key) {
+                    declaration.insert(key.to_string(), value.clone());
+                }
+            }
+            Value::Object(declaration)
+        })
+        .collect();
+    if !declarations.is_empty() {
+        output.insert(
+            "tools".to_string(),
+            json!([{"functionDeclarations": declarations}]),
+        );
+    }
+    let calling_config = match request.get("tool_choice") {
+        Some(Value::String(choice)) => match choice.as_str() {
+            "auto" => Some(json!({"mode": "AUTO"})),
+            "required" => Some(json!({"mode": "ANY"})),
+            "none" => Some(json!({"mode": "NONE"})),
+            _ => None,
+        },
+        Some(Value::Object(choice)) => choice
+            .get("function")
+            .map(|function| json!({"mode": "ANY", "allowedFunctionNames": [function["name"]]})),
+        _ => None,
+    };
+    if let Some(calling_config) = calling_config {
+        output.insert(
+            "toolConfig".to_string(),
+            json!({"functionCallingConfig": calling_config}),
+        );
+    }
+
+    Value::Object(output)
+}
+
+// 将 OpenAI 消息内容转换为 Gemini 内容片段
+fn content_parts(content: &Value) -> Vec<Value> {
+    match content {
+        Value::String(text) if !text.is_empty() => vec![json!({"text": text})],
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| match part["type"].as_str()? {
+                "text" => Some(json!({"text": part["text"]})),
+                "image_url" => {
+                    let url = part["image_url"]["url"].as_str()?;
+                    Some(match parse_data_url(url) {
+                        Some((mime_type, data)) => {
+                            json!({"inlineData": {"mimeType": mime_type, "data": data}})
+                        }
+                        None => json!({"fileData": {"fileUri": url}}),
+                    })
+                }
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// 将 Gemini 响应转换为 OpenAI chat.completion
+pub(super) fn response(body: Value, model: &str) -> Value {
+    if body.get("error").is_some() {
+        return convert_error(&body);
+    }
+    let Some(candidates) = body["candidates"].as_array() else {
+        return body;
+    };
+
+    let choices: Vec<Value> = candidates
+        .iter()
+        .enumerate()
+        .map(|(i, candidate)| {
+            let (text, tool_calls) = candidate_parts(candidate);
+            let finish_reason =
+                finish_reason(candidate["finishReason"].as_str(), !tool_calls.is_empty());
+            json!({
+                "index": candidate["index"].as_u64().unwrap_or(i as u64),
+                "message": assistant_message(text, tool_calls),
+                "finish_reason": finish_reason,
+            })
+        })
+        .collect();
+
+    json!({
+        "id": body["responseId"]
+            .as_str()
+            .map(|id| format!("chatcmpl-{}", id))
+            .unwrap_or_else(completion_id),
+        "object": "chat.completion",
+        "created": created(),
+        "model": body["modelVersion"].as_str().unwrap_or(model),
+        "choices": choices,
+        "usage": convert_usage(&body["usageMetadata"]),
+    })
+}
+
+// 提取候选结果中的文本和工具调用
+fn candidate_parts(candidate: &Value) -> (String, Vec<Value>) {
+    let mut text = String::new();
+    let mut tool_calls = Vec::new();
+    for part in candidate["content"]["parts"]
+        .as_array()
+        .into_iter()
+        .flatten()
+    {
+        // 跳过思考过程
+        if part["thought"].as_bool().unwrap_or_default() {
+            continue;
+        }
+        if let Some(part_text) = part["text"].as_str() {
+            text.push_str(part_text);
+        }
+        if let Some(call) = part.get("functionCall") {
+            tool_calls.push(tool_call(
+                json!(tool_call_id()),
+                call["name"].clone(),
+                call.get("args").unwrap_or(&json!({})),
+            ));
+        }
+    }
+    (text, tool_calls)
+}
+
+// 转换结束原因
+fn finish_reason(reason: Option<&str>, has_tool_calls: bool) -> Option<&'static str> {
+    reason.map(|reason| match reason {
+        "MAX_TOKENS" => "length",
+        "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII" | "IMAGE_SAFETY" => {
+            "content_filter"
+        }
+        _ if has_tool_calls => "tool_calls",
+        _ => "stop",
+    })
+}
+
+// 转换用量统计
+fn convert_usage(metadata: &Value) -> Value {
+    usage(
+        metadata["promptTokenCount"].as_u64().unwrap_or_default(),
+        metadata["candidatesTokenCount"]
+            .as_u64()
+            .unwrap_or_default(),
+    )
+}
+
+// 转换错误响应
+fn convert_error(body: &Value) -> Value {
+    error(
+        &body["error"]["message"],
+        &body["error"]["status"],
+        &body["error"]["code"],
+    )
+}
+
+// Gemini 事件流转换状态
+pub(super) struct StreamState {
+    // 响应 ID
+    id: String,
+    // 模型名称
+    model: String,
+    // 创建时间
+    created: u64,
+    // 已发送角色的候选结果
+    started: Vec<u64>,
+    // 已发送的工具调用数量
+    tool_calls: usize,
+}
+
+impl StreamState {
+    pub(super) fn new(model: &str) -> Self {
+        Self {
+            id: completion_id(),
+            model: model.to_string(),
+            created: created(),
+            started: Vec::new(),
+            tool_calls: 0,
+        }
+    }
+}
+
+impl StreamTranslator for StreamState {
+    fn event(&mut self, event: SseEvent) -> Vec<Value> {
+        let Ok(data) = serde_json::from_str::<Value>(&event.data) else {
+            return Vec::new();
+        };
+        if data.get("error").is_some() {
+            return vec![convert_error(&data)];
+        }
+        if let Some(model) = data["modelVersion"].as_str() {
+            self.model = model.to_string();
+        }
+
+        let mut chunks = Vec::new();
+        for (i, candidate) in data["candidates"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            let index = candidate["index"].as_u64().unwrap_or(i as u64);
+            let mut delta = Map::new();
+            if !self.started.contains(&index) {
+                self.started.push(index);
+                delta.insert("role".to_string(), json!("assistant"));
+            }
+
+            let (text, mut tool_calls) = candidate_parts(candidate);
+            if !text.is_empty() {
+                delta.insert("content".to_string(), json!(text));
+            }
+            if !tool_calls.is_empty() {
+                // Gemini 一次返回完整的函数调用，按顺序编号
+                for call in &mut tool_calls {
+                    call["index"] = json!(self.tool_calls);
+                    self.tool_calls += 1;
+                }
+                delta.insert("tool_calls".to_string(), Value::Array(tool_calls));
+            }
+
+            let finish_reason =
+                finish_reason(candidate["finishReason"].as_str(), self.tool_calls > 0);
+            let mut chunk = chunk(
+                &self.id,
+                &self.model,
+                self.created,
+                index,
+                Value::Object(delta),
+                finish_reason,
+            );
+            if finish_reason.is_some() && data.get("usageMetadata").is_some() {
+                chunk["usage"] = convert_usage(&data["usageMetadata"]);
+            }
+            chunks.push(chunk);
+        }
+        chunks
+    }
+}
@@ -0,0 +1,413 @@
+//! 上游 API 格式转换
+//!
+//! 客户端始终使用 OpenAI chat-completions 格式，上游配置了其他 `dialect` 时，
+//! 请求体转换为上游格式，响应体（包括事件流中的每个事件）转换回 OpenAI 格式。
+
+mod anthropic;
+mod gemini;
+mod normalize;
+pub(crate) mod sse;
+
+use crate::{
+    config::{Dialect, StreamNormalizeConfig},
+    error::AppError,
+    events::unix_millis,
+    r#const::{dialect, http_headers::content_types},
+};
+use bytes::Bytes;
+use futures_util::{stream::BoxStream, Stream, StreamExt};
+use reqwest::{
+    header::{
+        HeaderMap, HeaderValue, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE,
+        TRANSFER_ENCODING,
+    },
+    Response, Url,
+};
+use serde_json::{json, Map, Value};
+use sse::{SseDecoder, SseEvent};
+use tracing::debug;
+use uuid::Uuid;
+
+// 需要转换的上游格式
+#[derive(Debug, Clone, Copy)]
+enum Target {
+    Anthropic,
+    Gemini,
+}
+
+/// 单个请求的格式转换
+#[derive(Debug, Clone)]
+pub struct Translation {
+    // 上游格式
+    target: Target,
+    // 请求的模型名称
+    model: String,
+    // 是否为流式请求
+    stream: bool,
+}
+
+impl Translation {
+    /// 将 OpenAI chat-completions 请求转换为上游格式
+    ///
+    /// 上游使用 OpenAI 格式，或请求体不是包含 `messages` 的 JSON 对象（如 embeddings 等其他接口）时
+    /// 不做转换，返回 None。转换后移除原 Content-Length，并移除 Accept-Encoding 以便读取未压缩的上游响应。
+    pub fn request(
+        dialect: Dialect,
+        headers: &mut HeaderMap,
+        body: &[u8],
+    ) -> Option<(Self, Bytes)> {
+        let target = match dialect {
+            Dialect::OpenAI => return None,
+            Dialect::Anthropic => Target::Anthropic,
+            Dialect::Gemini => Target::Gemini,
+        };
+        let Ok(Value::Object(request)) = serde_json::from_slice::<Value>(body) else {
+            return None;
+        };
+        if !request.get("messages").is_some_and(Value::is_array) {
+            return None;
+        }
+
+        let model = request
+            .get("model")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let stream = request
+            .get("stream")
+            .and_then(Value::as_bool)
+            .unwrap_or_default();
+        let translated = match target {
+            Target::Anthropic => {
+                if !headers.contains_key(dialect::ANTHROPIC_VERSION_HEADER) {
+                    headers.insert(
+                        dialect::ANTHROPIC_VERSION_HEADER,
+                        HeaderValue::from_static(dialect::ANTHROPIC_VERSION),
+                    );
+                }
+                anthropic::request(request)
+            }
+            Target::Gemini => gemini::request(request),
+        };
+        debug!("Translated chat request to {:?} format", target);
+
+        headers.remove(CONTENT_LENGTH);
+        headers.remove(ACCEPT_ENCODING);
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_types::JSON));
+        Some((
+            Self {
+                target,
+                model,
+                stream,
+            },
+            Bytes::from(translated.to_string()),
+        ))
+    }
+
+    /// 调整上游请求 URL
+    ///
+    /// Gemini 的模型和生成方法位于路径中，上游 URL 配置为模型集合地址（如 `.../v1beta/models`），
+    /// 转换后为 `.../v1beta/models/<model>:generateContent`，流式请求使用 `streamGenerateContent?alt=sse`。
+    pub fn prepare_url(&self, url: &mut Url) {
+        if !matches!(self.target, Target::Gemini) {
+            return;
+        }
+        let method = if self.stream {
+            dialect::GEMINI_STREAM_GENERATE
+        } else {
+            dialect::GEMINI_GENERATE
+        };
+        let path = format!(
+            "{}/{}:{}",
+            url.path().trim_end_matches('/'),
+            self.model,
+            method
+        );
+        url.set_path(&path);
+        if self.stream {
+            url.query_pairs_mut().append_pair("alt", "sse");
+        }
+    }
+
+    /// 将上游响应转换为 OpenAI chat-completions 格式
+    ///
+    /// 事件流响应逐个事件转换，其他响应读取完整响应体后转换，不是 JSON 的响应体原样返回
+    pub async fn response(&self, response: Response) -> Result<Response, AppError> {
+        let status = response.status();
+        let version = response.version();
+        let mut headers = response.headers().clone();
+        for name in [CONTENT_LENGTH, CONTENT_ENCODING, TRANSFER_ENCODING] {
+            headers.remove(name);
+        }
+
+        let is_event_stream = headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.contains(content_types::EVENT_STREAM));
+        let body = if is_event_stream {
+            reqwest::Body::wrap_stream(transform_stream(response, self.stream_translator()))
+        } else {
+            let bytes = response.bytes().await?;
+            match serde_json::from_slice::<Value>(&bytes) {
+                Ok(body) => {
+                    headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_types::JSON));
+                    reqwest::Body::from(self.translate_body(body).to_string())
+                }
+                Err(_) => reqwest::Body::from(bytes),
+            }
+        };
+
+        let mut translated = hyper::Response::new(body);
+        *translated.status_mut() = status;
+        *translated.version_mut() = version;
+        *translated.headers_mut() = headers;
+        Ok(Response::from(translated))
+    }
+
+    // 转换非流式响应体
+    fn translate_body(&self, body: Value) -> Value {
+        match self.target {
+            Target::Anthropic => anthropic::response(body, &self.model),
+            Target::Gemini => gemini::response(body, &self.model),
+        }
+    }
+
+    // 上游格式的事件转换器
+    fn stream_translator(&self) -> Box<dyn StreamTranslator> {
+        match self.target {
+            Target::Anthropic => Box::new(anthropic::StreamState::new(&self.model)),
+            Target::Gemini => Box::new(gemini::StreamState::new(&self.model)),
+        }
+    }
+}
+
+/// 规范化 OpenAI 格式的事件流响应
+///
+/// 每个事件重新编码为单个 data 行，移除配置的字段并按需汇总用量，流结束时发送且只发送一次结束标记。
+/// 不是事件流或响应体经过压缩时原样返回。
+pub fn normalize_stream(response: Response, config: &StreamNormalizeConfig) -> Response {
+    let headers = response.headers();
+    let is_event_stream = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains(content_types::EVENT_STREAM));
+    if !is_event_stream || headers.contains_key(CONTENT_ENCODING) {
+        return response;
+    }
+
+    let status = response.status();
+    let version = response.version();
+    let mut headers = response.headers().clone();
+    for name in [CONTENT_LENGTH, TRANSFER_ENCODING] {
+        headers.remove(name);
+    }
+    let normalizer = Box::new(normalize::Normalizer::new(config));
+    let mut normalized = hyper::Response::new(reqwest::Body::wrap_stream(transform_stream(
+        response, normalizer,
+    )));
+    *normalized.status_mut() = status;
+    *normalized.version_mut() = version;
+    *normalized.headers_mut() = headers;
+    Response::from(normalized)
+}
+
+// 逐个事件转换事件流并编码为 OpenAI 事件流，上游流结束时追加结束标记
+fn transform_stream(
+    response: Response,
+    translator: Box<dyn StreamTranslator>,
+) -> impl Stream<Item = reqwest::Result<Bytes>> + Send + 'static {
+    let state = StreamState {
+        upstream: response.bytes_stream().boxed(),
+        decoder: SseDecoder::default(),
+        translator,
+        done: false,
+    };
+
+    futures_util::stream::unfold(state, |mut state| async move {
+        if state.done {
+            return None;
+        }
+        let output = match state.upstream.next().await {
+            Some(Ok(chunk)) => {
+                let events = state.decoder.feed(&chunk);
+                state.encode(events)
+            }
+            Some(Err(e)) => {
+                state.done = true;
+                return Some((Err(e), state));
+            }
+            None => {
+                state.done = true;
+                let event = state.decoder.finish();
+                let mut output = state.encode(event);
+                for chunk in state.translator.finish() {
+                    output.push_str(&format!("data: {}\n\n", chunk));
+                }
+                output.push_str(&format!("data: {}\n\n", dialect::STREAM_DONE));
+                output
+            }
+        };
+        Some((Ok(Bytes::from(output)), state))
+    })
+}
+
+// 事件流转换状态
+struct StreamState {
+    // 上游响应流
+    upstream: BoxStream<'static, reqwest::Result<Bytes>>,
+    // 事件解码器
+    decoder: SseDecoder,
+    // 事件转换器
+    translator: Box<dyn StreamTranslator>,
+    // 上游流是否已结束
+    done: bool,
+}
+
+impl StreamState {
+    // 转换事件并编码为 OpenAI 事件流
+    fn encode(&mut self, events: impl IntoIterator<Item = SseEvent>) -> String {
+        let mut output = String::new();
+        for event in events {
+            for chunk in self.translator.event(event) {
+                output.push_str(&format!("data: {}\n\n", chunk));
+            }
+        }
+        output
+    }
+}
+
+// 上游事件转换器
+trait StreamTranslator: Send {
+    // 转换一个上游事件，返回 OpenAI chat.completion.chunk 数据
+    fn event(&mut self, event: SseEvent) -> Vec<Value>;
+
+    // 上游流结束，返回结束标记之前需要追加的数据
+    fn finish(&mut self) -> Vec<Value> {
+        Vec::new()
+    }
+}
+
+// 当前 Unix 时间戳（秒）
+fn created() -> u64 {
+    unix_millis() / 1000
+}
+
+// 生成响应 ID
+fn completion_id() -> String {
+    format!("chatcmpl-{}", Uuid::new_v4().simple())
+}
+
+// 生成工具调用 ID
+fn tool_call_id() -> String {
+    format!("call_{}", Uuid::new_v4().simple())
+}
+
+// 构建 chat.completion.chunk
+fn chunk(
+    id: &str,
+    model: &str,
+    created: u64,
+    index: u64,
+    delta: Value,
+    finish_reason: Option<&str>,
+) -> Value {
+    json!({
+        "id": id,
+        "object": "chat.completion.chunk",
+        "created": created,
+        "model": model,
+        "choices": [{"index": index, "delta": delta, "finish_reason": finish_reason}],
+    })
+}
+
+// 构建助手消息，只有工具调用时 content 为 null
+fn assistant_message(text: String, tool_calls: Vec<Value>) -> Value {
+    let mut message = Map::new();
+    message.insert("role".to_string(), json!("assistant"));
+    let content = if text.is_empty() && !tool_calls.is_empty() {
+        Value::Null
+    } else {
+        Value::String(text)
+    };
+    message.insert("content".to_string(), content);
+    if !tool_calls.is_empty() {
+        message.insert("tool_calls".to_string(), Value::Array(tool_calls));
+    }
+    Value::Object(message)
+}
+
+// 构建工具调用
+fn tool_call(id: Value, name: Value, arguments: &Value) -> Value {
+    json!({
+        "id": id,
+        "type": "function",
+        "function": {"name": name, "arguments": arguments.to_string()},
+    })
+}
+
+// 构建用量统计
+fn usage(prompt_tokens: u64, completion_tokens: u64) -> Value {
+    json!({
+        "prompt_tokens": prompt_tokens,
+        "completion_tokens": completion_tokens,
+        "total_tokens": prompt_tokens + completion_tokens,
+    })
+}
+
+// 构建错误响应
+fn error(message: &Value, error_type: &Value, code: &Value) -> Value {
+    json!({"error": {"message": message, "type": error_type, "code": code}})
+}
+
+// 取出数组字段
+fn take_array(object: &mut Map<String, Value>, key: &str) -> Vec<Value> {
+    match object.remove(key) {
+        Some(Value::Array(values)) => values,
+        _ => Vec::new(),
+    }
+}
+
+// 提取消息内容中的文本，内容为字符串或 text 类型的内容片段数组
+fn text_content(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| part["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+// 读取请求中的最大生成 token 数
+fn max_tokens(request: &Map<String, Value>) -> Option<Value> {
+    request
+        .get("max_completion_tokens")
+        .or_else(|| request.get("max_tokens"))
+        .filter(|value| value.is_u64())
+        .cloned()
+}
+
+// 读取停止序列，字符串转换为单元素数组
+fn stop_sequences(request: &Map<String, Value>) -> Option<Value> {
+    match request.get("stop")? {
+        Value::String(stop) => Some(json!([stop])),
+        Value::Array(stops) => Some(Value::Array(stops.clone())),
+        _ => None,
+    }
+}
+
+// 解析 data URL，返回媒体类型和 base64 数据
+fn parse_data_url(url: &str) -> Option<(&str, &str)> {
+    url.strip_prefix("data:")?.split_once(";base64,")
+}
+
+// 解析工具调用参数，OpenAI 中为 JSON 字符串
+fn parse_arguments(arguments: &Value) -> Value {
+    match arguments {
+        Value::String(arguments) => serde_json::from_str(arguments).unwrap_or_else(|_| json!({})),
+        Value::Object(_) => arguments.clone(),
+        _ => json!({}),
+    }
+}
@@ -0,0 +1,78 @@
+use super::{sse::SseEvent, StreamTranslator};
+use crate::config::StreamNormalizeConfig;
+use serde_json::{json, Value};
+
+// OpenAI 格式事件流的规范化
+// 结束标记和无法解析的事件被丢弃，由事件流转换在流结束时统一追加结束标记
+pub(super) struct Normalizer {
+    // 从每个事件中移除的顶层字段
+    strip_fields: Vec<String>,
+    // 是否汇总用量
+    aggregate_usage: bool,
+    // 最近一次出现的用量（事件流中的用量为累计值）
+    usage: Option<Value>,
+    // 最近一个事件的 id、created 和 model，用于构建用量事件
+    last: Option<(Value, Value, Value)>,
+}
+
+impl Normalizer {
+    pub(super) fn new(config: &StreamNormalizeConfig) -> Self {
+        Self {
+            strip_fields: config.strip_fields.clone(),
+            aggregate_usage: config.aggregate_usage,
+            usage: None,
+            last: None,
+        }
+    }
+}
+
+impl StreamTranslator for Normalizer {
+    fn event(&mut self, event: SseEvent) -> Vec<Value> {
+        let Ok(mut data) = serde_json::from_str::<Value>(&event.data) else {
+            return Vec::new();
+        };
+        let Some(object) = data.as_object_mut() else {
+            return vec![data];
+        };
+
+        for field in &self.strip_fields {
+            object.remove(field);
+        }
+        if !self.aggregate_usage {
+            return vec![data];
+        }
+
+        self.last = Some((
+            data["id"].clone(),
+            data["created"].clone(),
+            data["model"].clone(),
+        ));
+        if let Some(usage) = data
+            .as_object_mut()
+            .and_then(|object| object.remove("usage"))
+            .filter(|usage| !usage.is_null())
+        {
+            self.usage = Some(usage);
+            // 只包含用量的事件在流结束时重新发送
+            if data["choices"].as_array().is_some_and(Vec::is_empty) {
+                return Vec::new();
+            }
+        }
+        vec![data]
+    }
+
+    fn finish(&mut self) -> Vec<Value> {
+        let Some(usage) = self.usage.take() else {
+            return Vec::new();
+        };
+        let (id, created, model) = self.last.take().unwrap_or_default();
+        vec![json!({
+            "id": id,
+            "object": "chat.completion.chunk",
+            "created": created,
+            "model": model,
+            "choices": [],
+            "usage": usage,
+        })]
+    }
+}
@@ -0,0 +1,66 @@
+// Server-Sent Events 事件
+#[derive(Debug, Default)]
+pub(crate) struct SseEvent {
+    // 事件数据（多行 data 以换行连接）
+    pub data: String,
+}
+
+// Server-Sent Events 解码器
+// 上游数据块可能在任意位置截断，未完成的行保留到下一个数据块。
+// 事件类型由数据中的 type 字段给出，event 字段不需要解析
+#[derive(Debug, Default)]
+pub(crate) struct SseDecoder {
+    // 未完成的行
+    pending: Vec<u8>,
+    // 当前事件的数据行
+    data: Vec<String>,
+}
+
+impl SseDecoder {
+    // 解码数据块，返回其中完整的事件
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.pending.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+        while let Some(pos) = self.pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            // 空行表示事件结束
+            if line.is_empty() {
+                events.extend(self.take_event());
+                continue;
+            }
+            // 注释行
+            if line.starts_with(':') {
+                continue;
+            }
+
+            if let Some(value) = line.strip_prefix("data:") {
+                self.data
+                    .push(value.strip_prefix(' ').unwrap_or(value).to_string());
+            }
+        }
+        events
+    }
+
+    // 上游流结束时返回未以空行结束的最后一个事件
+    pub fn finish(&mut self) -> Option<SseEvent> {
+        if !self.pending.is_empty() {
+            let mut rest = std::mem::take(&mut self.pending);
+            rest.extend_from_slice(b"\n\n");
+            return self.feed(&rest).pop();
+        }
+        self.take_event()
+    }
+
+    fn take_event(&mut self) -> Option<SseEvent> {
+        if self.data.is_empty() {
+            return None;
+        }
+        Some(SseEvent {
+            data: std::mem::take(&mut self.data).join("\n"),
+        })
+    }
+}
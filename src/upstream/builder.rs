@@ -1,10 +1,13 @@
 use crate::{
+    adaptive::AdaptiveLimiter,
     balancer::ManagedUpstream,
     breaker::create_upstream_circuit_breaker,
     config::{UpstreamConfig, UpstreamRef},
+    discovery::Endpoint,
     error::AppError,
 };
 use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use tracing::debug;
 
@@ -24,11 +27,37 @@ pub(super) fn build_upstream_map(upstreams: &[UpstreamConfig]) -> HashMap<String
     upstream_map
 }
 
+/// 为每个上游服务创建排空标志，同一上游在所有组中共享
+pub(super) fn build_drain_flags(
+    upstreams: &HashMap<String, UpstreamConfig>,
+) -> HashMap<String, Arc<AtomicBool>> {
+    upstreams
+        .iter()
+        .map(|(name, config)| (name.clone(), Arc::new(AtomicBool::new(!config.enabled))))
+        .collect()
+}
+
+/// 为配置了自适应并发的上游服务创建限制器，同一上游在所有组中共享
+pub(super) fn build_adaptive_limiters(
+    upstreams: &HashMap<String, UpstreamConfig>,
+) -> HashMap<String, Arc<AdaptiveLimiter>> {
+    upstreams
+        .iter()
+        .filter_map(|(name, config)| {
+            let adaptive = config.adaptive.as_ref()?;
+            Some((name.clone(), AdaptiveLimiter::new(name, adaptive)))
+        })
+        .collect()
+}
+
 /// 创建托管上游
 pub(super) fn create_managed_upstream(
     upstream_ref: &UpstreamRef,
     upstream_config: &UpstreamConfig,
     group_name: &str,
+    drained: Arc<AtomicBool>,
+    adaptive: Option<Arc<AdaptiveLimiter>>,
+    endpoint: Option<Arc<Endpoint>>,
 ) -> Result<ManagedUpstream, AppError> {
     // 创建熔断器（如果上游配置了熔断器）
     let breaker = match &upstream_config.breaker {
@@ -47,7 +76,45 @@ pub(super) fn create_managed_upstream(
     let managed_upstream = ManagedUpstream {
         upstream_ref: Arc::new(upstream_ref.clone()),
         breaker,
+        drained,
+        adaptive,
+        endpoint,
     };
 
     Ok(managed_upstream)
 }
+
+/// 为服务发现得到的每个上游地址创建托管上游，没有地址时使用上游 URL 创建一个
+pub(super) fn create_managed_upstreams(
+    upstream_ref: &UpstreamRef,
+    upstream_config: &UpstreamConfig,
+    group_name: &str,
+    drained: Arc<AtomicBool>,
+    adaptive: Option<Arc<AdaptiveLimiter>>,
+    endpoints: &[Arc<Endpoint>],
+) -> Result<Vec<ManagedUpstream>, AppError> {
+    if endpoints.is_empty() {
+        return Ok(vec![create_managed_upstream(
+            upstream_ref,
+            upstream_config,
+            group_name,
+            drained,
+            adaptive,
+            None,
+        )?]);
+    }
+
+    endpoints
+        .iter()
+        .map(|endpoint| {
+            create_managed_upstream(
+                upstream_ref,
+                upstream_config,
+                group_name,
+                drained.clone(),
+                adaptive.clone(),
+                Some(endpoint.clone()),
+            )
+        })
+        .collect()
+}
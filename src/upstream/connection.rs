@@ -0,0 +1,199 @@
+use crate::{
+    metrics::METRICS,
+    r#const::{connection_labels, connection_phase_labels},
+};
+use async_trait::async_trait;
+use futures_util::future::BoxFuture;
+use hyper::http::Extensions;
+use reqwest::{
+    dns::{Addrs, Name, Resolve, Resolving},
+    Request, Response,
+};
+use reqwest_middleware::{Middleware, Next};
+use std::{
+    io,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tower::{Layer, Service};
+
+tokio::task_local! {
+    // 当前连接建立过程中的 DNS 解析耗时（纳秒）
+    static DNS_ELAPSED: Arc<AtomicU64>;
+    // 当前请求是否建立了新连接
+    static NEW_CONNECTION: Arc<AtomicBool>;
+}
+
+/// 记录 DNS 解析耗时的解析器
+///
+/// 使用系统解析器（与 reqwest 默认的解析器相同），解析耗时记录到
+/// `llmproxy_upstream_connect_seconds{phase="dns"}`。主机为 IP 地址时不会调用解析器。
+pub(super) struct TimedResolver {
+    group: Arc<str>,
+}
+
+impl TimedResolver {
+    pub(super) fn new(group: Arc<str>) -> Self {
+        Self { group }
+    }
+}
+
+impl Resolve for TimedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let group = self.group.clone();
+        Box::pin(async move {
+            let started = Instant::now();
+            let addrs = tokio::net::lookup_host((name.as_str(), 0)).await;
+            let elapsed = started.elapsed();
+
+            METRICS
+                .upstream_connect_seconds()
+                .with_label_values(&[&group, connection_phase_labels::DNS])
+                .observe(elapsed.as_secs_f64());
+            // 连接层从建立连接的总耗时中扣除解析耗时
+            let _ = DNS_ELAPSED
+                .try_with(|dns| dns.fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed));
+
+            let addrs: Vec<SocketAddr> = addrs?.collect();
+            if addrs.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no addresses resolved for {}", name.as_str()),
+                )
+                .into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// 记录上游连接建立情况的连接器层
+///
+/// 统计新建和失败的连接数，并将除 DNS 解析外的建立耗时（TCP 连接和 TLS 握手）记录到
+/// `llmproxy_upstream_connect_seconds{phase="handshake"}`。reqwest 的连接器类型不公开，
+/// 无法分别统计 TCP 连接和 TLS 握手的耗时。
+#[derive(Clone)]
+pub(super) struct ConnectionMetricsLayer {
+    group: Arc<str>,
+}
+
+impl ConnectionMetricsLayer {
+    pub(super) fn new(group: Arc<str>) -> Self {
+        Self { group }
+    }
+}
+
+impl<S> Layer<S> for ConnectionMetricsLayer {
+    type Service = ConnectionMetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConnectionMetricsService {
+            inner,
+            group: self.group.clone(),
+        }
+    }
+}
+
+/// [`ConnectionMetricsLayer`] 包装的连接器
+#[derive(Clone)]
+pub(super) struct ConnectionMetricsService<S> {
+    inner: S,
+    group: Arc<str>,
+}
+
+impl<S, R> Service<R> for ConnectionMetricsService<S>
+where
+    S: Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<S::Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        let group = self.group.clone();
+        let dns = Arc::new(AtomicU64::new(0));
+        let connecting = DNS_ELAPSED.scope(dns.clone(), self.inner.call(request));
+
+        Box::pin(async move {
+            let started = Instant::now();
+            let result = connecting.await;
+
+            let metrics = &*METRICS;
+            if result.is_ok() {
+                let handshake = started
+                    .elapsed()
+                    .saturating_sub(Duration::from_nanos(dns.load(Ordering::Relaxed)));
+                metrics
+                    .upstream_connect_seconds()
+                    .with_label_values(&[&group, connection_phase_labels::HANDSHAKE])
+                    .observe(handshake.as_secs_f64());
+                metrics
+                    .upstream_connections_total()
+                    .with_label_values(&[&group, connection_labels::OPENED])
+                    .inc();
+                // 连接由等待中的请求建立时标记该请求使用了新连接，
+                // 连接池在后台建立的连接不属于任何请求
+                let _ = NEW_CONNECTION.try_with(|new| new.store(true, Ordering::Relaxed));
+            } else {
+                metrics
+                    .upstream_connections_total()
+                    .with_label_values(&[&group, connection_labels::FAILED])
+                    .inc();
+            }
+
+            result
+        })
+    }
+}
+
+/// 统计请求使用新连接还是复用连接池中连接的中间件
+///
+/// 放在重试中间件之后，每次尝试分别统计。没有收到响应的尝试不统计。
+pub(super) struct ConnectionReuseMiddleware {
+    group: Arc<str>,
+}
+
+impl ConnectionReuseMiddleware {
+    pub(super) fn new(group: Arc<str>) -> Self {
+        Self { group }
+    }
+}
+
+#[async_trait]
+impl Middleware for ConnectionReuseMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let new_connection = Arc::new(AtomicBool::new(false));
+        let result = NEW_CONNECTION
+            .scope(new_connection.clone(), next.run(req, extensions))
+            .await;
+
+        if result.is_ok() {
+            let connection = if new_connection.load(Ordering::Relaxed) {
+                connection_labels::NEW
+            } else {
+                connection_labels::REUSED
+            };
+            METRICS
+                .upstream_connection_requests_total()
+                .with_label_values(&[&self.group, connection])
+                .inc();
+        }
+
+        result
+    }
+}
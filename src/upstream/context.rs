@@ -0,0 +1,35 @@
+use crate::{r#const::header_placeholders, server::TokenCharge};
+use std::net::IpAddr;
+
+/// 客户端请求上下文，用于展开上游请求头值中的占位符
+#[derive(Debug, Clone, Default)]
+pub struct RequestContext {
+    /// 客户端 IP 地址
+    pub client_ip: Option<IpAddr>,
+    /// 请求 ID
+    pub request_id: String,
+    /// 转发服务名称
+    pub forward_name: String,
+    /// 客户端标识（转发服务配置了客户端预算时），用于累计客户端的费用
+    pub client_id: Option<String>,
+    /// 令牌速率限制扣除的 token（转发服务配置了令牌速率限制时），按响应中的实际用量修正
+    pub token_charge: Option<TokenCharge>,
+}
+
+/// 处理请求的上游名称，附加在上游响应的扩展中
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServedBy(pub String);
+
+impl RequestContext {
+    // 解析占位符的值，未设置的环境变量返回 None
+    pub(super) fn resolve(&self, name: &str) -> Option<String> {
+        match name {
+            header_placeholders::CLIENT_IP => self.client_ip.map(|ip| ip.to_string()),
+            header_placeholders::REQUEST_ID => Some(self.request_id.clone()),
+            header_placeholders::FORWARD_NAME => Some(self.forward_name.clone()),
+            _ => name
+                .strip_prefix(header_placeholders::ENV_PREFIX)
+                .and_then(|var| std::env::var(var).ok()),
+        }
+    }
+}
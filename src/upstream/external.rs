@@ -0,0 +1,84 @@
+use super::token_cache::TokenCache;
+use crate::{config::ExternalAuthConfig, error::AppError};
+use once_cell::sync::Lazy;
+use std::time::Duration;
+use tokio::process::Command;
+use tracing::{info, warn};
+
+// 外部命令令牌缓存，按命令及参数索引，同一命令的并发请求只执行一次
+static TOKENS: Lazy<TokenCache> = Lazy::new(TokenCache::new);
+
+/// 获取外部命令生成的令牌，超过刷新间隔或令牌被上游拒绝后重新执行命令
+pub(super) async fn token(config: &ExternalAuthConfig) -> Result<String, AppError> {
+    TOKENS
+        .get_or_fetch(cache_key(config), || async {
+            let token = run_command(config).await?;
+            info!(
+                "Obtained token from external auth command {:?}, refreshing in {}s",
+                config.command, config.refresh_interval
+            );
+            Ok((token, Duration::from_secs(config.refresh_interval)))
+        })
+        .await
+}
+
+/// 外部命令生成并随请求发送的令牌，记录在响应扩展中，上游拒绝时只丢弃该令牌
+#[derive(Clone)]
+pub(super) struct ExternalToken(pub(super) String);
+
+/// 丢弃被上游拒绝的令牌，下一次请求时重新执行命令
+pub(super) async fn invalidate(config: &ExternalAuthConfig, rejected: &str) {
+    if TOKENS.invalidate(cache_key(config), rejected).await {
+        warn!(
+            "Upstream rejected the token from external auth command {:?}, it will be refreshed on the next request",
+            config.command
+        );
+    }
+}
+
+// 缓存键，命令及参数相同时共享令牌
+fn cache_key(config: &ExternalAuthConfig) -> String {
+    format!("{}\0{}", config.command, config.args.join("\0"))
+}
+
+// 执行命令，标准输出去除首尾空白后作为令牌
+async fn run_command(config: &ExternalAuthConfig) -> Result<String, AppError> {
+    let output = Command::new(&config.command)
+        .args(&config.args)
+        .kill_on_drop(true)
+        .output();
+
+    let output = tokio::time::timeout(Duration::from_secs(config.timeout), output)
+        .await
+        .map_err(|_| {
+            AppError::AuthError(format!(
+                "External auth command {:?} timed out after {}s",
+                config.command, config.timeout
+            ))
+        })?
+        .map_err(|e| {
+            AppError::AuthError(format!(
+                "Failed to run external auth command {:?}: {}",
+                config.command, e
+            ))
+        })?;
+
+    if !output.status.success() {
+        return Err(AppError::AuthError(format!(
+            "External auth command {:?} exited with {}: {}",
+            config.command,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if token.is_empty() {
+        return Err(AppError::AuthError(format!(
+            "External auth command {:?} produced an empty token",
+            config.command
+        )));
+    }
+
+    Ok(token)
+}
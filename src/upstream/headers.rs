@@ -0,0 +1,83 @@
+use crate::{
+    config::{HeaderOpType, UpstreamConfig},
+    error::AppError,
+};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT};
+
+use super::context::RequestContext;
+
+/// 处理转发到上游的请求头
+///
+/// 依次添加客户端请求中没有的默认请求头、用 agent（上游的 agent，未配置时为上游组的
+/// http_client.agent）替换 User-Agent，最后执行上游的请求头操作。头部名称和不含占位符的值
+/// 在加载配置时预解析，包含占位符的值按请求上下文展开。
+pub fn process_headers(
+    headers: HeaderMap,
+    upstream: &UpstreamConfig,
+    agent: Option<&str>,
+    context: &RequestContext,
+) -> Result<HeaderMap, AppError> {
+    // 如果没有需要处理的头部，直接返回原始headers
+    if upstream.headers.is_empty() && upstream.default_headers.is_empty() && agent.is_none() {
+        return Ok(headers);
+    }
+
+    // 创建新的 HeaderMap 而不是克隆
+    let mut result = HeaderMap::with_capacity(headers.len() + upstream.default_headers.len() + 1);
+
+    // 先复制所有原始头
+    for (key, value) in headers.iter() {
+        result.insert(key, value.clone());
+    }
+
+    // 添加客户端请求中没有的默认请求头，名称和值在加载配置时已校验
+    for (key, value) in &upstream.default_headers {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            result.entry(name).or_insert(value);
+        }
+    }
+
+    // 替换客户端的 User-Agent
+    if let Some(agent) = agent {
+        let value = HeaderValue::from_str(agent).map_err(|e| {
+            AppError::InvalidHeader(format!(
+                "Invalid agent '{}' for upstream '{}': {}",
+                agent, upstream.name, e
+            ))
+        })?;
+        result.insert(USER_AGENT, value);
+    }
+
+    // 处理请求头操作
+    for op in &upstream.headers {
+        match op.op {
+            HeaderOpType::Insert | HeaderOpType::Replace => {
+                let Some(name) = &op.parsed_name else {
+                    continue;
+                };
+                if let Some(value) = &op.parsed_value {
+                    result.insert(name.clone(), value.clone());
+                } else if let Some(rendered) = op.render_value(|name| context.resolve(name)) {
+                    // 展开占位符后的值
+                    let value = HeaderValue::from_str(&rendered).map_err(|e| {
+                        AppError::InvalidHeader(format!(
+                            "Invalid header value for key '{}' in upstream '{}': {}",
+                            op.key, upstream.name, e
+                        ))
+                    })?;
+                    result.insert(name.clone(), value);
+                }
+            }
+            HeaderOpType::Remove => {
+                if let Some(name) = &op.parsed_name {
+                    result.remove(name);
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
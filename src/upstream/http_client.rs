@@ -1,39 +1,123 @@
 use crate::{
-    config::{AuthConfig, AuthType, HttpClientConfig, UpstreamGroupConfig},
+    config::{
+        defaults::default_user_agent, AuthConfig, AuthType, HttpClientConfig, HttpVersion,
+        TlsConfig, TlsVersion, UpstreamConfig, UpstreamGroupConfig,
+    },
     error::AppError,
-    r#const::retry_limits,
 };
+use openssl::pkey::PKey;
 use reqwest_middleware::ClientWithMiddleware;
-use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
-use retry_policies::Jitter;
-use std::{collections::HashMap, time::Duration};
-use tracing::debug;
+use std::{collections::HashMap, fs, sync::Arc, time::Duration};
+use tracing::{debug, warn};
+
+use super::{
+    connection::{ConnectionMetricsLayer, ConnectionReuseMiddleware, TimedResolver},
+    external::{self, ExternalToken},
+    oauth2,
+    retry::RetryMiddleware,
+    unix::UnixSocketMiddleware,
+};
+
+/// 上游组的HTTP客户端
+pub(super) struct GroupClients {
+    // 按组配置创建的客户端
+    proxied: ClientWithMiddleware,
+    // 不使用代理的客户端，组内存在 proxy: false 的上游时创建
+    direct: Option<ClientWithMiddleware>,
+    // 是否配置了重试，重试需要重新发送完整的请求体
+    retries: bool,
+    // 响应体空闲超时
+    stream_idle: Option<Duration>,
+    // 替换客户端 User-Agent 的值
+    agent: Option<String>,
+}
+
+impl GroupClients {
+    /// 获取上游使用的客户端
+    pub(super) fn for_upstream(&self, upstream: &UpstreamConfig) -> &ClientWithMiddleware {
+        match &self.direct {
+            Some(direct) if !upstream.proxy => direct,
+            _ => &self.proxied,
+        }
+    }
+
+    /// 是否配置了重试
+    pub(super) fn retries(&self) -> bool {
+        self.retries
+    }
+
+    /// 响应体空闲超时
+    pub(super) fn stream_idle(&self) -> Option<Duration> {
+        self.stream_idle
+    }
+
+    /// 发送到上游的 User-Agent，上游的 agent 优先于组的配置
+    pub(super) fn agent<'a>(&'a self, upstream: &'a UpstreamConfig) -> Option<&'a str> {
+        upstream.agent.as_deref().or(self.agent.as_deref())
+    }
+}
 
 /// 为多个上游组创建HTTP客户端映射
 pub(super) fn create_group_clients(
     groups: &[UpstreamGroupConfig],
-) -> Result<HashMap<String, ClientWithMiddleware>, AppError> {
+    upstreams: &HashMap<String, UpstreamConfig>,
+) -> Result<HashMap<String, GroupClients>, AppError> {
     let mut group_clients = HashMap::with_capacity(groups.len());
 
     for group in groups {
         // 创建该组的HTTP客户端
-        let client = create_http_client(&group.http_client)?;
-        group_clients.insert(group.name.clone(), client);
+        let proxied = create_http_client(&group.name, &group.http_client, true)?;
+
+        // 组内有上游绕过代理时，额外创建直连客户端
+        let bypass = group.upstreams.iter().any(|upstream_ref| {
+            upstreams
+                .get(&upstream_ref.name)
+                .is_some_and(|upstream| !upstream.proxy)
+        });
+        let direct = if bypass {
+            Some(create_http_client(&group.name, &group.http_client, false)?)
+        } else {
+            None
+        };
+
+        group_clients.insert(
+            group.name.clone(),
+            GroupClients {
+                proxied,
+                direct,
+                retries: group.http_client.retry.is_some(),
+                stream_idle: group
+                    .http_client
+                    .timeout
+                    .stream_idle
+                    .map(Duration::from_secs),
+                agent: group.http_client.agent.clone(),
+            },
+        );
     }
 
     Ok(group_clients)
 }
 
-/// 创建HTTP客户端
+/// 创建HTTP客户端，use_proxy 为 false 时不使用任何代理
+///
+/// 客户端的连接池指标（新建连接、连接复用、DNS 解析和握手耗时）按组名称记录。
 pub(super) fn create_http_client(
+    group: &str,
     config: &HttpClientConfig,
+    use_proxy: bool,
 ) -> Result<ClientWithMiddleware, AppError> {
     debug!("Creating HTTP client, config: {:?}", config);
 
-    // 创建 reqwest 客户端
+    let group: Arc<str> = group.into();
+
+    // 创建 reqwest 客户端，记录 DNS 解析和连接建立情况
     let mut client_builder = reqwest::Client::builder()
         .tcp_keepalive(Some(Duration::from_secs(config.keepalive.into())))
-        .connect_timeout(Duration::from_secs(config.timeout.connect));
+        .connect_timeout(Duration::from_secs(config.timeout.connect))
+        .user_agent(default_user_agent())
+        .dns_resolver(Arc::new(TimedResolver::new(group.clone())))
+        .connector_layer(ConnectionMetricsLayer::new(group.clone()));
 
     // 如果未启用流式模式，则设置请求超时
     if !config.stream_mode {
@@ -46,59 +130,187 @@ pub(super) fn create_http_client(
             client_builder.pool_idle_timeout(Some(Duration::from_secs(config.timeout.idle)));
     }
 
-    // 配置代理（如果启用）
-    if let Some(proxy_config) = &config.proxy {
+    // 设置 HTTP 协议版本
+    match config.http_version {
+        HttpVersion::Auto => {}
+        HttpVersion::Http1 => client_builder = client_builder.http1_only(),
+        HttpVersion::Http2PriorKnowledge => client_builder = client_builder.http2_prior_knowledge(),
+    }
+
+    // 设置 HTTP/2 keepalive ping
+    if let Some(http2) = &config.http2 {
+        client_builder = client_builder
+            .http2_keep_alive_interval(Duration::from_secs(http2.keepalive_interval))
+            .http2_keep_alive_timeout(Duration::from_secs(http2.keepalive_timeout))
+            .http2_keep_alive_while_idle(http2.keepalive_while_idle);
+    }
+
+    // 配置代理（如果启用），no_proxy 中的目标直连
+    if !use_proxy {
+        client_builder = client_builder.no_proxy();
+    } else if let Some(proxy_config) = &config.proxy {
         if let Ok(proxy) = reqwest::Proxy::all(&proxy_config.url) {
-            client_builder = client_builder.proxy(proxy);
+            let no_proxy = reqwest::NoProxy::from_string(&proxy_config.no_proxy.join(","));
+            client_builder = client_builder.proxy(proxy.no_proxy(no_proxy));
         }
     }
 
+    // 配置 TLS（客户端证书、信任的 CA、证书校验和最低版本）
+    if let Some(tls_config) = &config.tls {
+        client_builder = configure_tls(client_builder, tls_config)?;
+    }
+
     // 创建基础HTTP客户端
     let client = client_builder.build()?;
 
+    // Unix 域套接字上游（unix://）的请求由中间件直接发送，放在重试中间件之后以支持重试
+    let unix_socket = UnixSocketMiddleware::new(
+        Duration::from_secs(config.timeout.connect),
+        (!config.stream_mode).then(|| Duration::from_secs(config.timeout.request)),
+    );
+
+    // 连接复用统计放在 Unix 域套接字中间件之后，只统计经过连接池的请求
+    let connection_reuse = ConnectionReuseMiddleware::new(group);
+
     // 配置重试策略（根据组的重试配置）
     let middleware_client = if let Some(retry_config) = &config.retry {
         // 使用指数退避策略，基于组的重试配置
-        let retry_policy = ExponentialBackoff::builder()
-            .retry_bounds(
-                Duration::from_millis(retry_config.initial.into()),
-                Duration::from_secs(retry_limits::MAX_DELAY.into()),
-            )
-            .base(2)
-            .jitter(Jitter::Bounded)
-            .build_with_max_retries(retry_config.attempts);
-
         reqwest_middleware::ClientBuilder::new(client)
-            .with(RetryTransientMiddleware::new_with_policy(retry_policy))
+            .with(RetryMiddleware::new(retry_config))
+            .with(unix_socket)
+            .with(connection_reuse)
             .build()
     } else {
         // 不进行重试
-        reqwest_middleware::ClientBuilder::new(client).build()
+        reqwest_middleware::ClientBuilder::new(client)
+            .with(unix_socket)
+            .with(connection_reuse)
+            .build()
     };
 
     Ok(middleware_client)
 }
 
-/// 添加认证信息到请求
-pub(super) fn add_auth(
+/// 根据 TLS 配置设置客户端
+fn configure_tls(
+    mut client_builder: reqwest::ClientBuilder,
+    config: &TlsConfig,
+) -> Result<reqwest::ClientBuilder, AppError> {
+    // 配置客户端证书（mTLS）
+    if let (Some(cert), Some(key)) = (&config.cert, &config.key) {
+        client_builder = client_builder.identity(load_identity(cert, key, &config.passphrase)?);
+    }
+
+    // 信任额外的 CA 证书
+    if let Some(ca_file) = &config.ca_file {
+        let bundle = fs::read(ca_file).map_err(|e| {
+            AppError::Config(format!("Unable to read TLS CA file {:?}: {}", ca_file, e))
+        })?;
+        let certs = reqwest::Certificate::from_pem_bundle(&bundle)
+            .map_err(|e| AppError::Config(format!("Invalid TLS CA file {:?}: {}", ca_file, e)))?;
+        if certs.is_empty() {
+            return Err(AppError::Config(format!(
+                "TLS CA file {:?} contains no certificates",
+                ca_file
+            )));
+        }
+        for cert in certs {
+            client_builder = client_builder.add_root_certificate(cert);
+        }
+    }
+
+    if config.insecure_skip_verify {
+        warn!("TLS certificate verification is disabled for upstream connections, do not use this in production");
+        client_builder = client_builder.danger_accept_invalid_certs(true);
+    }
+
+    if let Some(min_version) = config.min_version {
+        client_builder = client_builder.min_tls_version(match min_version {
+            TlsVersion::Tls10 => reqwest::tls::Version::TLS_1_0,
+            TlsVersion::Tls11 => reqwest::tls::Version::TLS_1_1,
+            TlsVersion::Tls12 => reqwest::tls::Version::TLS_1_2,
+        });
+    }
+
+    Ok(client_builder)
+}
+
+/// 加载客户端证书和私钥，私钥统一转换为 native-tls 要求的 PKCS#8 格式
+fn load_identity(
+    cert_path: &str,
+    key_path: &str,
+    passphrase: &Option<String>,
+) -> Result<reqwest::Identity, AppError> {
+    let cert = fs::read(cert_path).map_err(|e| {
+        AppError::Config(format!(
+            "Unable to read TLS client certificate {:?}: {}",
+            cert_path, e
+        ))
+    })?;
+    let key = fs::read(key_path).map_err(|e| {
+        AppError::Config(format!(
+            "Unable to read TLS client key {:?}: {}",
+            key_path, e
+        ))
+    })?;
+
+    let key = match passphrase {
+        Some(passphrase) => PKey::private_key_from_pem_passphrase(&key, passphrase.as_bytes()),
+        None => PKey::private_key_from_pem(&key),
+    }
+    .and_then(|key| key.private_key_to_pem_pkcs8())
+    .map_err(|e| AppError::Config(format!("Invalid TLS client key {:?}: {}", key_path, e)))?;
+
+    reqwest::Identity::from_pkcs8_pem(&cert, &key).map_err(|e| {
+        AppError::Config(format!(
+            "Invalid TLS client certificate {:?}: {}",
+            cert_path, e
+        ))
+    })
+}
+
+/// 添加认证信息到请求，同时返回外部命令生成的令牌（其他认证类型为 None）
+pub(super) async fn add_auth(
     request: reqwest_middleware::RequestBuilder,
     auth: &AuthConfig,
-) -> Result<reqwest_middleware::RequestBuilder, AppError> {
+) -> Result<(reqwest_middleware::RequestBuilder, Option<ExternalToken>), AppError> {
     match auth.r#type {
         AuthType::Basic => {
-            if let (Some(username), Some(password)) = (&auth.username, &auth.password) {
-                Ok(request.basic_auth(username, Some(password)))
+            if let (Some(username), Some(password)) = (&auth.username, auth.resolve_password()?) {
+                Ok((request.basic_auth(username, Some(password)), None))
             } else {
                 Err(AppError::AuthError("Basic auth config missing".to_string()))
             }
         }
         AuthType::Bearer => {
-            if let Some(token) = &auth.token {
-                Ok(request.bearer_auth(token))
+            if let Some(token) = auth.resolve_token()? {
+                Ok((request.bearer_auth(token), None))
             } else {
                 Err(AppError::AuthError("Bearer auth token missing".to_string()))
             }
         }
-        AuthType::None => Ok(request),
+        AuthType::OAuth2 => {
+            if let Some(oauth2) = &auth.oauth2 {
+                Ok((
+                    request.bearer_auth(oauth2::access_token(oauth2).await?),
+                    None,
+                ))
+            } else {
+                Err(AppError::AuthError(
+                    "OAuth2 auth config missing".to_string(),
+                ))
+            }
+        }
+        AuthType::External => {
+            if let Some(external) = &auth.external {
+                let token = external::token(external).await?;
+                Ok((request.bearer_auth(&token), Some(ExternalToken(token))))
+            } else {
+                Err(AppError::AuthError(
+                    "External auth config missing".to_string(),
+                ))
+            }
+        }
+        AuthType::None => Ok((request, None)),
     }
 }
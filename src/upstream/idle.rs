@@ -0,0 +1,83 @@
+use crate::{metrics::METRICS, r#const::error_labels};
+use futures_util::{stream::BoxStream, StreamExt};
+use reqwest::Response;
+use std::{io, time::Duration};
+use tracing::warn;
+
+// 响应体空闲检查状态
+struct IdleState {
+    // 上游响应流
+    upstream: BoxStream<'static, reqwest::Result<bytes::Bytes>>,
+    // 上游组名称
+    group: String,
+    // 上游名称
+    upstream_name: String,
+    // 空闲超时
+    timeout: Duration,
+    // 上游流是否已结束
+    done: bool,
+}
+
+// 为上游响应体设置空闲超时
+// 上游在超时时间内没有发送任何数据时以错误结束响应体，并释放上游连接，避免挂起的事件流一直占用连接
+pub(super) fn limit_idle(
+    response: Response,
+    timeout: Duration,
+    group: &str,
+    upstream: &str,
+) -> Response {
+    let status = response.status();
+    let version = response.version();
+    let headers = response.headers().clone();
+    let state = IdleState {
+        upstream: response.bytes_stream().boxed(),
+        group: group.to_string(),
+        upstream_name: upstream.to_string(),
+        timeout,
+        done: false,
+    };
+
+    let stream = futures_util::stream::unfold(state, |mut state| async move {
+        if state.done {
+            return None;
+        }
+        match tokio::time::timeout(state.timeout, state.upstream.next()).await {
+            Ok(Some(Ok(chunk))) => Some((Ok(chunk), state)),
+            Ok(Some(Err(e))) => {
+                state.done = true;
+                Some((Err(io::Error::other(e)), state))
+            }
+            Ok(None) => None,
+            Err(_) => {
+                warn!(
+                    "Upstream '{}' in group '{}' sent no data for {}s, aborting response",
+                    state.upstream_name,
+                    state.group,
+                    state.timeout.as_secs()
+                );
+                METRICS
+                    .upstream_errors_total()
+                    .with_label_values(&[
+                        error_labels::STREAM_IDLE_TIMEOUT,
+                        &state.group,
+                        &state.upstream_name,
+                    ])
+                    .inc();
+                state.done = true;
+                Some((
+                    Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        format!("Upstream sent no data for {}s", state.timeout.as_secs()),
+                    )),
+                    state,
+                ))
+            }
+        }
+    });
+
+    let mut limited = hyper::Response::new(reqwest::Body::wrap_stream(stream));
+    *limited.status_mut() = status;
+    *limited.version_mut() = version;
+    *limited.headers_mut() = headers;
+    Response::from(limited)
+}
@@ -1,35 +1,120 @@
 use crate::{
-    balancer::{create_load_balancer, LoadBalancer},
+    adaptive::AdaptiveLimiter,
+    balancer::{create_load_balancer, is_upstream_healthy, LoadBalancer, ManagedUpstream},
     breaker::UpstreamError,
-    config::{HeaderOpType, UpstreamConfig, UpstreamGroupConfig, UpstreamRef},
+    config::{
+        AuthType, Dialect, HeaderOpType, StickyConfig, UpstreamConfig, UpstreamGroupConfig,
+        UpstreamRef,
+    },
+    discovery::Endpoint,
     error::AppError,
-    metrics::METRICS,
-    r#const::{balance_strategy_labels, breaker_result_labels, error_labels, upstream_labels},
+    events::{unix_millis, SystemEvent, EVENTS},
+    metrics::{status_class, METRICS},
+    r#const::{
+        balance_strategy_labels, breaker_result_labels, breaker_state_labels, error_labels,
+        status_class_labels, upstream_labels, warmup_labels,
+    },
+    translate::{self, Translation},
 };
 use bytes::Bytes;
-use reqwest::{header::HeaderMap, Method, Response, Url};
-use reqwest_middleware::ClientWithMiddleware;
+use circuitbreaker_rs::State;
+use futures_util::future::join_all;
+use parking_lot::RwLock;
+use reqwest::{
+    header::{HeaderMap, HeaderValue, ACCEPT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE},
+    Method, Response, StatusCode, Url,
+};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     future::Future,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     time::{Duration, Instant},
 };
 use tracing::{debug, error, info, warn};
 
 use super::{
-    builder::{build_upstream_map, create_managed_upstream},
-    http_client::{add_auth, create_group_clients},
+    builder::{
+        build_adaptive_limiters, build_drain_flags, build_upstream_map, create_managed_upstream,
+        create_managed_upstreams,
+    },
+    context::{RequestContext, ServedBy},
+    external::{self, ExternalToken},
+    headers::process_headers,
+    http_client::{add_auth, create_group_clients, GroupClients},
+    idle,
+    stats::{UpstreamGroupStatus, UpstreamStatsRegistry, UpstreamStatus},
+    sticky::{StickyEntry, StickySessions},
+    timing, unix, usage,
 };
 
+// 转发到上游的请求体
+enum RequestBody {
+    // 已读取的完整请求体，可以转换格式和重试
+    Buffered(Option<Bytes>),
+    // 客户端的请求体流
+    Streaming(reqwest::Body),
+}
+
+// 负载均衡器中选中上游后的待处理请求
+// 收到上游响应前转发被取消（如客户端断开连接导致请求处理被丢弃）时，释放时减少待处理请求计数，
+// 避免响应时间感知的负载均衡器持续高估该上游的负载
+struct PendingSelection<'a> {
+    // 上游组负载均衡器
+    load_balancer: Option<&'a Arc<dyn LoadBalancer>>,
+    // 选中的上游
+    upstream: &'a ManagedUpstream,
+}
+
+impl PendingSelection<'_> {
+    // 收到上游响应或请求失败，待处理请求计数由负载均衡器更新
+    fn settle(mut self) {
+        self.load_balancer = None;
+    }
+}
+
+impl Drop for PendingSelection<'_> {
+    fn drop(&mut self) {
+        if let Some(response_aware) = self.load_balancer.and_then(|load_balancer| {
+            load_balancer
+                .as_any()
+                .downcast_ref::<crate::balancer::ResponseAwareBalancer>()
+        }) {
+            debug!(
+                "Request to upstream '{}' was cancelled before a response was received",
+                self.upstream.upstream_ref.name
+            );
+            response_aware.cancel_request(self.upstream);
+        }
+    }
+}
+
 // 上游管理器
 pub struct UpstreamManager {
     // 上游配置映射
     upstreams: HashMap<String, UpstreamConfig>,
+    // 上游排空标志
+    drain_flags: HashMap<String, Arc<AtomicBool>>,
+    // 上游自适应并发限制器
+    adaptive_limiters: HashMap<String, Arc<AdaptiveLimiter>>,
     // 上游组负载均衡器
     groups: HashMap<String, Arc<dyn LoadBalancer>>,
     // 上游组客户端
-    group_clients: HashMap<String, ClientWithMiddleware>,
+    group_clients: HashMap<String, GroupClients>,
+    // 上游组重试时间预算
+    retry_budgets: HashMap<String, Duration>,
+    // 上游组会话粘滞配置
+    sticky_configs: HashMap<String, StickyConfig>,
+    // 会话粘滞记录
+    sticky_sessions: StickySessions,
+    // 上游运行统计
+    stats: UpstreamStatsRegistry,
+    // 服务发现得到的上游地址
+    endpoints: RwLock<HashMap<String, Vec<Arc<Endpoint>>>>,
+    // 上游组服务发现得到的地址，优先于上游自身的地址
+    group_endpoints: RwLock<HashMap<String, Vec<Arc<Endpoint>>>>,
 }
 
 impl UpstreamManager {
@@ -39,8 +124,13 @@ impl UpstreamManager {
         groups: Vec<UpstreamGroupConfig>,
     ) -> Result<Self, AppError> {
         let upstream_map = build_upstream_map(&upstreams);
+        let drain_flags = build_drain_flags(&upstream_map);
+        let adaptive_limiters = build_adaptive_limiters(&upstream_map);
         let mut group_map = HashMap::with_capacity(groups.len());
-        let group_clients = create_group_clients(&groups)?;
+        let mut sticky_configs = HashMap::new();
+        let mut retry_budgets = HashMap::new();
+        let mut stats = UpstreamStatsRegistry::default();
+        let group_clients = create_group_clients(&groups, &upstream_map)?;
 
         // 为每个组创建负载均衡器和HTTP客户端
         for group in groups {
@@ -64,8 +154,14 @@ impl UpstreamManager {
                 };
 
                 // 创建托管上游
-                let managed_upstream =
-                    create_managed_upstream(upstream_ref, upstream_config, group_name)?;
+                let managed_upstream = create_managed_upstream(
+                    upstream_ref,
+                    upstream_config,
+                    group_name,
+                    drain_flags[&upstream_ref.name].clone(),
+                    adaptive_limiters.get(&upstream_ref.name).cloned(),
+                    None,
+                )?;
 
                 managed_upstreams.push(managed_upstream);
             }
@@ -74,30 +170,107 @@ impl UpstreamManager {
             let lb = create_load_balancer(&group.balance.strategy, managed_upstreams);
 
             group_map.insert(group.name.clone(), lb);
+            stats.add_group(group_name);
+
+            if let Some(max_elapsed_ms) = group
+                .http_client
+                .retry
+                .as_ref()
+                .and_then(|retry| retry.max_elapsed_ms)
+            {
+                retry_budgets.insert(group.name.clone(), Duration::from_millis(max_elapsed_ms));
+            }
+
+            if let Some(sticky) = group.sticky {
+                sticky_configs.insert(group.name, sticky);
+            }
         }
 
         info!("Initialized {} upstream groups", group_map.len());
 
         Ok(Self {
             upstreams: upstream_map,
+            drain_flags,
+            adaptive_limiters,
             groups: group_map,
             group_clients,
+            retry_budgets,
+            sticky_configs,
+            sticky_sessions: StickySessions::default(),
+            stats,
+            endpoints: RwLock::new(HashMap::new()),
+            group_endpoints: RwLock::new(HashMap::new()),
         })
     }
 
+    /// 获取上游组的会话粘滞配置及请求中的会话 ID
+    fn sticky_session(
+        &self,
+        group_name: &str,
+        headers: &HeaderMap,
+    ) -> Option<(&StickyConfig, String)> {
+        let config = self.sticky_configs.get(group_name)?;
+        let session_id = headers.get(config.session_header.as_str())?.to_str().ok()?;
+
+        if session_id.is_empty() {
+            return None;
+        }
+
+        Some((config, session_id.to_string()))
+    }
+
+    /// 判断上游是否匹配会话粘滞记录（路由提示相同或即为返回提示的上游）
+    #[inline(always)]
+    fn matches_sticky_entry(&self, upstream: &ManagedUpstream, entry: &StickyEntry) -> bool {
+        let name = &upstream.upstream_ref.name;
+        if *name == entry.upstream {
+            return true;
+        }
+
+        self.upstreams
+            .get(name)
+            .and_then(|config| config.hint.as_deref())
+            .is_some_and(|hint| hint == entry.hint)
+    }
+
     /// 构建请求URL
+    ///
+    /// Unix 域套接字上游的套接字路径通过请求扩展传递；上游由服务发现展开时，
+    /// 使用发现的地址替换上游 URL 的主机和端口；配置了路径重写时，
+    /// 使用重写后的请求路径替换上游 URL 的路径；最后执行查询参数操作
     #[inline(always)]
-    fn build_request_url(&self, upstream_url: &str) -> Result<Url, AppError> {
-        Url::parse(upstream_url).map_err(|e| {
-            AppError::Upstream(format!("Invalid upstream URL: {:?} - {}", upstream_url, e))
-        })
+    fn build_request_url(
+        &self,
+        upstream: &UpstreamConfig,
+        endpoint: Option<&Endpoint>,
+        path: &str,
+    ) -> Result<(Url, Option<unix::UnixSocket>), AppError> {
+        let url = Url::parse(&upstream.url).map_err(|e| {
+            AppError::Upstream(format!(
+                "Invalid upstream URL: {:?} - {}",
+                upstream.url.as_str(),
+                e
+            ))
+        })?;
+        let (mut url, unix_socket) = unix::resolve(url);
+
+        if let Some(endpoint) = endpoint {
+            endpoint.apply(&mut url);
+        }
+        if let Some(rewrite) = &upstream.path {
+            url.set_path(&rewrite.rewrite(path));
+        }
+        self.process_query_params(&mut url, upstream);
+
+        Ok((url, unix_socket))
     }
 
     /// 从上游组中选择上游服务器并获取其配置
     async fn select_upstream_server(
         &self,
         group_name: &str,
-    ) -> Result<(crate::balancer::ManagedUpstream, &UpstreamConfig), AppError> {
+        pinned: Option<&StickyEntry>,
+    ) -> Result<(Arc<ManagedUpstream>, &UpstreamConfig), AppError> {
         // 获取上游组的负载均衡器
         let load_balancer = match self.groups.get(group_name) {
             Some(lb) => lb,
@@ -107,24 +280,44 @@ impl UpstreamManager {
             }
         };
 
-        // 选择一个上游服务器
-        let managed_upstream = match load_balancer.select_upstream().await {
-            Ok(s) => s,
-            Err(e) => {
-                error!("Failed to select upstream server: {}", e);
-
-                // 记录上游错误指标
-                METRICS
-                    .upstream_errors_total()
-                    .with_label_values(&[
-                        error_labels::SELECT_ERROR,
-                        group_name,
-                        upstream_labels::UNKNOWN,
-                    ])
-                    .inc();
+        // 优先选择与会话粘滞记录匹配的健康上游
+        let pinned_upstream = match pinned {
+            Some(entry) => {
+                load_balancer
+                    .select_upstream_by(&|upstream| self.matches_sticky_entry(upstream, entry))
+                    .await
+            }
+            None => None,
+        };
 
-                return Err(e);
+        // 选择一个上游服务器
+        let managed_upstream = match pinned_upstream {
+            Some(upstream) => {
+                debug!(
+                    "Selected pinned upstream {:?} for hint {:?}",
+                    upstream.upstream_ref.name,
+                    pinned.map(|entry| entry.hint.as_str())
+                );
+                upstream
             }
+            None => match load_balancer.select_upstream().await {
+                Ok(s) => s,
+                Err(e) => {
+                    error!("Failed to select upstream server: {}", e);
+
+                    // 记录上游错误指标
+                    METRICS
+                        .upstream_errors_total()
+                        .with_label_values(&[
+                            error_labels::SELECT_ERROR,
+                            group_name,
+                            upstream_labels::UNKNOWN,
+                        ])
+                        .inc();
+
+                    return Err(e);
+                }
+            },
         };
 
         // 获取上游配置
@@ -144,19 +337,13 @@ impl UpstreamManager {
 
         debug!("Selected upstream server: {:?}", upstream_config.url);
 
-        // 记录上游请求指标
-        METRICS
-            .upstream_requests_total()
-            .with_label_values(&[group_name, &upstream_config.url])
-            .inc();
-
         Ok((managed_upstream, upstream_config))
     }
 
     /// 执行HTTP请求
     async fn execute_request(
         &self,
-        managed_upstream: &crate::balancer::ManagedUpstream,
+        managed_upstream: &ManagedUpstream,
         upstream_url: &str,
         request_future: impl Future<Output = Result<Response, UpstreamError>> + Send,
         group_name: &str,
@@ -177,7 +364,6 @@ impl UpstreamManager {
                             .with_label_values(&[
                                 group_name,
                                 &managed_upstream.upstream_ref.name,
-                                upstream_url,
                                 breaker_result_labels::REJECTED,
                             ])
                             .inc();
@@ -211,7 +397,7 @@ impl UpstreamManager {
     fn update_balancer_metrics(
         &self,
         load_balancer: &Arc<dyn LoadBalancer>,
-        managed_upstream: &crate::balancer::ManagedUpstream,
+        managed_upstream: &ManagedUpstream,
         duration: Duration,
     ) {
         // 检查是否为响应时间感知的负载均衡器，更新指标
@@ -226,52 +412,300 @@ impl UpstreamManager {
         }
     }
 
-    // 转发请求到指定上游组
+    // 转发请求到指定上游组，path 为客户端请求路径，用于上游的路径重写
+    // context 为客户端请求上下文，用于展开请求头值中的占位符和累计客户端的费用
     pub async fn forward_request(
         &self,
         group_name: &str,
+        path: &str,
+        context: &RequestContext,
         method: &Method,
         headers: HeaderMap,
         body: Option<Bytes>,
+    ) -> Result<Response, AppError> {
+        self.forward(
+            group_name,
+            path,
+            context,
+            method,
+            headers,
+            RequestBody::Buffered(body),
+        )
+        .await
+    }
+
+    // 转发请求到指定上游组，客户端请求体不经缓冲直接转发给上游
+    // 调用前需要通过 accepts_streaming_body 确认上游组不需要读取完整的请求体
+    pub async fn forward_request_stream(
+        &self,
+        group_name: &str,
+        path: &str,
+        context: &RequestContext,
+        method: &Method,
+        headers: HeaderMap,
+        body: reqwest::Body,
+    ) -> Result<Response, AppError> {
+        self.forward(
+            group_name,
+            path,
+            context,
+            method,
+            headers,
+            RequestBody::Streaming(body),
+        )
+        .await
+    }
+
+    /// 上游组是否可以直接转发流式请求体
+    ///
+    /// 组内所有上游都未配置请求体转换和 API 格式转换，且组未配置重试（流式请求体无法重新发送）时返回 true
+    pub fn accepts_streaming_body(&self, group_name: &str) -> bool {
+        let (Some(load_balancer), Some(clients)) = (
+            self.groups.get(group_name),
+            self.group_clients.get(group_name),
+        ) else {
+            return false;
+        };
+        !clients.retries()
+            && load_balancer.upstreams().iter().all(|managed_upstream| {
+                self.upstreams
+                    .get(&managed_upstream.upstream_ref.name)
+                    .is_some_and(|config| {
+                        config.body_transform.is_none()
+                            && matches!(config.dialect, None | Some(Dialect::OpenAI))
+                    })
+            })
+    }
+
+    /// 转发 WebSocket 升级请求到指定上游组
+    ///
+    /// 按上游配置处理请求头、查询参数和认证信息，不转换请求体和 API 格式。升级请求固定使用 HTTP/1.1，
+    /// 上游返回 101 后由调用方通过 reqwest::Response::upgrade 取得上游连接
+    pub async fn forward_upgrade(
+        &self,
+        group_name: &str,
+        path: &str,
+        context: &RequestContext,
+        headers: HeaderMap,
+    ) -> Result<Response, AppError> {
+        debug!(
+            "Forwarding WebSocket upgrade request to upstream group: {:?}",
+            group_name
+        );
+
+        // 选择一个上游服务器
+        let (managed_upstream, upstream_config) =
+            self.select_upstream_server(group_name, None).await?;
+        let (url, unix_socket) =
+            self.build_request_url(upstream_config, managed_upstream.endpoint.as_deref(), path)?;
+        let Some(clients) = self.group_clients.get(group_name) else {
+            error!("HTTP client not found: {:?}", group_name);
+            return Err(AppError::UpstreamGroupNotFound(group_name.to_string()));
+        };
+        let client = clients.for_upstream(upstream_config);
+        let agent = clients.agent(upstream_config);
+
+        let upstream_url = &upstream_config.url;
+        let breaker = managed_upstream.breaker.as_deref();
+        let request_future = async move {
+            let mut request_builder = client
+                .request(Method::GET, url)
+                .version(reqwest::Version::HTTP_11);
+            if let Some(unix_socket) = unix_socket {
+                request_builder = request_builder.with_extension(unix_socket);
+            }
+            request_builder =
+                request_builder.headers(process_headers(headers, upstream_config, agent, context)?);
+            if let Some(ref auth) = upstream_config.auth {
+                request_builder = add_auth(request_builder, auth).await?.0;
+            }
+
+            match request_builder.send().await {
+                Ok(response) => {
+                    if let Some(breaker) = breaker {
+                        breaker.record_connect_success();
+                    }
+                    Ok(response)
+                }
+                Err(e) => {
+                    if e.is_connect() || unix::is_connect_error(&e) {
+                        if let Some(breaker) = breaker {
+                            breaker.record_connect_failure();
+                        }
+                    }
+                    Err(UpstreamError(format!(
+                        "WebSocket upgrade request to {:?} failed: {}",
+                        upstream_url.as_str(),
+                        e
+                    )))
+                }
+            }
+        };
+
+        let start_time = Instant::now();
+        let response = self
+            .execute_request(
+                &managed_upstream,
+                upstream_url.as_str(),
+                request_future,
+                group_name,
+            )
+            .await;
+        let upstream_label = METRICS.upstream_label(&upstream_config.name, upstream_url.as_str());
+        METRICS
+            .upstream_duration_seconds()
+            .with_label_values(&[group_name, upstream_label])
+            .observe(start_time.elapsed().as_secs_f64());
+        METRICS.record_upstream_request(
+            group_name,
+            upstream_label,
+            upstream_status_class(&response),
+            "",
+        );
+
+        if let Err(ref err) = response {
+            warn!(
+                "WebSocket upgrade request failed, reporting failure. Group: '{}', Upstream: '{}', Error: {}",
+                group_name, &managed_upstream.upstream_ref.name, err
+            );
+            if let Some(load_balancer) = self.groups.get(group_name) {
+                load_balancer.report_failure(&managed_upstream).await;
+            }
+            METRICS
+                .upstream_errors_total()
+                .with_label_values(&[
+                    error_labels::UPSTREAM_ERROR,
+                    group_name,
+                    &managed_upstream.upstream_ref.name,
+                ])
+                .inc();
+        }
+        response.map(|mut response| {
+            response
+                .extensions_mut()
+                .insert(ServedBy(managed_upstream.upstream_ref.name.clone()));
+            response
+        })
+    }
+
+    // 转发请求到指定上游组
+    async fn forward(
+        &self,
+        group_name: &str,
+        path: &str,
+        context: &RequestContext,
+        method: &Method,
+        headers: HeaderMap,
+        body: RequestBody,
     ) -> Result<Response, AppError> {
         debug!("Forwarding request to upstream group: {:?}", group_name);
 
+        // 查找会话粘滞记录
+        let sticky = self.sticky_session(group_name, &headers);
+        let pinned = sticky
+            .as_ref()
+            .and_then(|(_, session_id)| self.sticky_sessions.get(group_name, session_id));
+
         // 选择一个上游服务器
-        let (managed_upstream, upstream_config) = self.select_upstream_server(group_name).await?;
+        let (managed_upstream, upstream_config) = self
+            .select_upstream_server(group_name, pinned.as_ref())
+            .await?;
+
+        // 转发在收到上游响应前被丢弃时（客户端断开连接），上游请求随之中止，并减少负载均衡器的待处理请求计数
+        let pending = PendingSelection {
+            load_balancer: self.groups.get(group_name),
+            upstream: &managed_upstream,
+        };
 
         // 记录开始时间
         let start_time = Instant::now();
+        let in_flight = self
+            .stats
+            .get(group_name, &managed_upstream.upstream_ref.name)
+            .map(|stats| stats.begin());
+        let adaptive = managed_upstream
+            .adaptive
+            .as_ref()
+            .map(|limiter| limiter.start());
+
+        // 请求的模型名称，用作上游请求指标的标签，流式请求体不读取
+        let model = match &body {
+            RequestBody::Buffered(body) => METRICS.model_label(body.as_deref()),
+            RequestBody::Streaming(_) => String::new(),
+        };
+
+        // 按上游配置转换 JSON 请求体，并转换为上游的 API 格式，流式请求体原样转发
+        let (mut headers, body, translation) = match body {
+            RequestBody::Buffered(body) => {
+                let (mut headers, mut body) = self.transform_body(headers, body, upstream_config);
+                let translation = match (upstream_config.dialect, &body) {
+                    (Some(dialect), Some(data)) => {
+                        Translation::request(dialect, &mut headers, data).map(
+                            |(translation, data)| {
+                                body = Some(data);
+                                translation
+                            },
+                        )
+                    }
+                    _ => None,
+                };
+                (headers, body.map(reqwest::Body::from), translation)
+            }
+            RequestBody::Streaming(body) => (headers, Some(body), None),
+        };
+
+        // 规范化事件流需要解析响应体，要求上游不压缩响应
+        if upstream_config.stream_normalize.is_some() {
+            headers.remove(ACCEPT_ENCODING);
+        }
 
         // 构建请求URL
-        let url = self.build_request_url(&upstream_config.url)?;
+        let (mut url, unix_socket) =
+            self.build_request_url(upstream_config, managed_upstream.endpoint.as_deref(), path)?;
+        if let Some(translation) = &translation {
+            translation.prepare_url(&mut url);
+        }
 
         // 获取组的HTTP客户端
-        let client = match self.group_clients.get(group_name) {
+        let clients = match self.group_clients.get(group_name) {
             Some(c) => c,
             None => {
                 error!("HTTP client not found: {:?}", group_name);
                 return Err(AppError::UpstreamGroupNotFound(group_name.to_string()));
             }
         };
+        let client = clients.for_upstream(upstream_config);
 
         // 定义请求执行闭包 - 使用引用捕获以减少克隆
         let upstream_url = &upstream_config.url;
-        let request_future = |headers: HeaderMap, body: Option<Bytes>| {
+        let breaker = managed_upstream.breaker.as_deref();
+        let retry_budget = self.retry_budgets.get(group_name).copied();
+        let request_future = |headers: HeaderMap, body: Option<reqwest::Body>| {
             let url = url.clone();
             let method = method.clone(); // 使用引用的方法，克隆更轻量
             let client = client.clone();
+            let unix_socket = unix_socket.clone();
 
             async move {
                 // 创建请求构建器
                 let mut request_builder = client.request(method, url);
+                if let Some(unix_socket) = unix_socket {
+                    request_builder = request_builder.with_extension(unix_socket);
+                }
 
                 // 处理请求头
-                let processed_headers = self.process_headers(headers, upstream_config)?;
+                let processed_headers = process_headers(
+                    headers,
+                    upstream_config,
+                    clients.agent(upstream_config),
+                    context,
+                )?;
                 request_builder = request_builder.headers(processed_headers);
 
                 // 添加认证信息
+                let mut external_token = None;
                 if let Some(ref auth) = upstream_config.auth {
-                    request_builder = add_auth(request_builder, auth)?;
+                    (request_builder, external_token) = add_auth(request_builder, auth).await?;
                 }
 
                 // 添加请求体（如果有）
@@ -279,14 +713,59 @@ impl UpstreamManager {
                     request_builder = request_builder.body(body_data);
                 }
 
-                // 发送请求
-                match request_builder.send().await {
-                    Ok(response) => Ok(response),
-                    Err(e) => Err(UpstreamError(format!(
-                        "Request to {:?} failed: {}",
-                        upstream_url.as_str(),
-                        e
-                    ))),
+                // 发送请求，首次请求与所有重试的总耗时不超过重试时间预算
+                let result = match retry_budget {
+                    Some(budget) => {
+                        match tokio::time::timeout(budget, request_builder.send()).await {
+                            Ok(result) => result,
+                            Err(_) => {
+                                warn!(
+                                    "Request to {:?} in group '{}' exceeded retry budget of {}ms",
+                                    upstream_url.as_str(),
+                                    group_name,
+                                    budget.as_millis()
+                                );
+                                return Err(UpstreamError(format!(
+                                    "Request to {:?} exceeded retry budget of {}ms",
+                                    upstream_url.as_str(),
+                                    budget.as_millis()
+                                )));
+                            }
+                        }
+                    }
+                    None => request_builder.send().await,
+                };
+
+                match result {
+                    Ok(mut response) => {
+                        if let Some(breaker) = breaker {
+                            breaker.record_connect_success();
+                        }
+                        // 记录发送的外部命令令牌，上游拒绝时只丢弃该令牌
+                        if let Some(token) = external_token {
+                            response.extensions_mut().insert(token);
+                        }
+                        Ok(response)
+                    }
+                    Err(e) => {
+                        // 区分连接阶段失败（DNS、TCP、TLS）与应用层错误
+                        if e.is_connect() || unix::is_connect_error(&e) {
+                            warn!(
+                                "Connect failure to {:?} in group '{}': {}",
+                                upstream_url.as_str(),
+                                group_name,
+                                e
+                            );
+                            if let Some(breaker) = breaker {
+                                breaker.record_connect_failure();
+                            }
+                        }
+                        Err(UpstreamError(format!(
+                            "Request to {:?} failed: {}",
+                            upstream_url.as_str(),
+                            e
+                        )))
+                    }
                 }
             }
         };
@@ -301,19 +780,44 @@ impl UpstreamManager {
             )
             .await;
 
-        // 记录上游请求耗时
+        // 记录上游请求计数和耗时
         let duration = start_time.elapsed();
+        let upstream_label = METRICS.upstream_label(&upstream_config.name, upstream_url.as_str());
+        METRICS.record_upstream_request(
+            group_name,
+            upstream_label,
+            upstream_status_class(&response),
+            &model,
+        );
         METRICS
             .upstream_duration_seconds()
-            .with_label_values(&[group_name, upstream_url.as_str()])
+            .with_label_values(&[group_name, upstream_label])
             .observe(duration.as_secs_f64());
 
         // 获取上游组的负载均衡器
         let load_balancer = self.groups.get(group_name).unwrap();
+        pending.settle();
 
         // 更新响应时间感知的负载均衡器指标
         self.update_balancer_metrics(load_balancer, &managed_upstream, duration);
 
+        // 记录上游运行统计，上游返回 5xx 也视为失败
+        if let Some(in_flight) = in_flight {
+            in_flight.finish(matches!(response, Ok(ref resp) if !resp.status().is_server_error()));
+        }
+
+        // 按响应延迟和上游是否过载（429、5xx、请求失败）调整自适应并发上限，熔断器拒绝的请求不计入
+        if let Some(adaptive) = &adaptive {
+            match &response {
+                Ok(resp) => adaptive.finish(
+                    resp.status() == StatusCode::TOO_MANY_REQUESTS
+                        || resp.status().is_server_error(),
+                ),
+                Err(AppError::CircuitBreakerOpen(_)) => {}
+                Err(_) => adaptive.finish(true),
+            }
+        }
+
         // 错误处理和指标记录
         if let Err(ref err) = response {
             warn!(
@@ -343,47 +847,423 @@ impl UpstreamManager {
                 status,
                 upstream_url.as_str()
             );
+
+            // 上游拒绝外部命令生成的令牌时丢弃缓存，下一次请求重新执行命令
+            if response.status() == StatusCode::UNAUTHORIZED {
+                if let (Some(external), Some(ExternalToken(token))) = (
+                    upstream_config
+                        .auth
+                        .as_ref()
+                        .filter(|auth| auth.r#type == AuthType::External)
+                        .and_then(|auth| auth.external.as_ref()),
+                    response.extensions().get::<ExternalToken>(),
+                ) {
+                    external::invalidate(external, token).await;
+                }
+            }
+
+            // 记录上游返回的路由提示，后续同一会话的请求优先发往匹配的上游
+            if let Some((config, session_id)) = sticky {
+                if let Some(hint) = response
+                    .headers()
+                    .get(config.hint_header.as_str())
+                    .and_then(|value| value.to_str().ok())
+                    .filter(|hint| !hint.is_empty())
+                {
+                    self.sticky_sessions.record(
+                        group_name,
+                        &session_id,
+                        &managed_upstream.upstream_ref.name,
+                        hint,
+                        config.ttl,
+                    );
+                }
+            }
         }
 
+        // 上游长时间不发送数据时中断响应体
+        let response = match (response, clients.stream_idle()) {
+            (Ok(response), Some(timeout)) => Ok(idle::limit_idle(
+                response,
+                timeout,
+                group_name,
+                &managed_upstream.upstream_ref.name,
+            )),
+            (response, _) => response,
+        };
+
+        // 将上游响应转换回 OpenAI 格式
+        let response = match (response, translation) {
+            (Ok(response), Some(translation)) => translation.response(response).await?,
+            (response, _) => response?,
+        };
+
+        // 规范化事件流响应
+        let response = match &upstream_config.stream_normalize {
+            Some(normalize) => translate::normalize_stream(response, normalize),
+            None => response,
+        };
+
+        // 记录首字节耗时和事件流的输出速度
+        let response = timing::observe_timing(
+            response,
+            start_time,
+            group_name,
+            &managed_upstream.upstream_ref.name,
+        );
+
+        // 记录响应中的 token 用量和费用
+        let response = usage::track_usage(
+            response,
+            group_name,
+            &managed_upstream.upstream_ref.name,
+            &upstream_config.pricing,
+            context,
+        )
+        .await?;
+
+        // 响应体传输结束前该请求仍计入上游的在途请求
+        let mut response = match adaptive {
+            Some(adaptive) => adaptive.hold(response),
+            None => response,
+        };
+
+        // 记录处理请求的上游，用于访问日志
         response
+            .extensions_mut()
+            .insert(ServedBy(managed_upstream.upstream_ref.name.clone()));
+        Ok(response)
     }
 
-    // 处理请求头
-    fn process_headers(
+    // 转换 JSON 请求体，请求体变化后移除原 Content-Length，由客户端重新计算
+    fn transform_body(
         &self,
-        headers: HeaderMap,
+        mut headers: HeaderMap,
+        body: Option<Bytes>,
         upstream: &UpstreamConfig,
-    ) -> Result<HeaderMap, AppError> {
-        // 如果没有头部操作需要执行，直接返回原始headers
-        if upstream.headers.is_empty() {
-            return Ok(headers);
+    ) -> (HeaderMap, Option<Bytes>) {
+        let (Some(transform), Some(data)) = (&upstream.body_transform, &body) else {
+            return (headers, body);
+        };
+        match transform.apply(data) {
+            Some(transformed) => {
+                debug!("Transformed request body for upstream: {:?}", upstream.name);
+                headers.remove(CONTENT_LENGTH);
+                (headers, Some(Bytes::from(transformed)))
+            }
+            None => (headers, body),
         }
+    }
 
-        // 创建新的 HeaderMap 而不是克隆
-        let mut result = HeaderMap::with_capacity(headers.len());
-
-        // 先复制所有原始头
-        for (key, value) in headers.iter() {
-            result.insert(key, value.clone());
+    /// 处理查询参数操作
+    fn process_query_params(&self, url: &mut Url, upstream: &UpstreamConfig) {
+        if upstream.query_params.is_empty() {
+            return;
         }
 
-        // 处理请求头操作
-        for op in &upstream.headers {
+        let mut pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        for op in &upstream.query_params {
+            let value = op.value.clone().unwrap_or_default();
             match op.op {
-                HeaderOpType::Insert | HeaderOpType::Replace => {
-                    if let (Some(name), Some(value)) = (&op.parsed_name, &op.parsed_value) {
-                        result.insert(name.clone(), value.clone());
+                HeaderOpType::Insert => {
+                    if !pairs.iter().any(|(key, _)| key == &op.key) {
+                        pairs.push((op.key.clone(), value));
                     }
                 }
-                HeaderOpType::Remove => {
-                    if let Some(name) = &op.parsed_name {
-                        result.remove(name);
-                    }
+                HeaderOpType::Replace => {
+                    pairs.retain(|(key, _)| key != &op.key);
+                    pairs.push((op.key.clone(), value));
                 }
+                HeaderOpType::Remove => pairs.retain(|(key, _)| key != &op.key),
             }
         }
 
-        Ok(result)
+        if pairs.is_empty() {
+            url.set_query(None);
+        } else {
+            url.query_pairs_mut().clear().extend_pairs(&pairs);
+        }
+    }
+
+    /// 获取所有上游组的运行状态
+    ///
+    /// 包括每个上游服务的熔断器状态、正在处理的请求数量、近期错误率和最近一次被选中的时间
+    pub fn group_status(&self) -> Vec<UpstreamGroupStatus> {
+        let mut groups: Vec<UpstreamGroupStatus> = self
+            .groups
+            .iter()
+            .map(|(group_name, load_balancer)| {
+                let upstreams = load_balancer
+                    .upstreams()
+                    .iter()
+                    .map(|managed_upstream| {
+                        let name = &managed_upstream.upstream_ref.name;
+                        let breaker = managed_upstream.breaker.as_ref().map(|breaker| {
+                            match breaker.current_state() {
+                                State::Closed => breaker_state_labels::CLOSED,
+                                State::Open => breaker_state_labels::OPEN,
+                                State::HalfOpen => breaker_state_labels::HALF_OPEN,
+                            }
+                            .to_string()
+                        });
+
+                        let mut status = UpstreamStatus {
+                            name: name.clone(),
+                            url: self
+                                .upstreams
+                                .get(name)
+                                .map(|config| config.url.to_string())
+                                .unwrap_or_default(),
+                            endpoint: managed_upstream
+                                .endpoint
+                                .as_ref()
+                                .map(|endpoint| endpoint.to_string()),
+                            weight: managed_upstream.upstream_ref.weight,
+                            enabled: !managed_upstream.drained.load(Ordering::Relaxed),
+                            healthy: is_upstream_healthy(managed_upstream),
+                            breaker,
+                            concurrency_limit: managed_upstream
+                                .adaptive
+                                .as_ref()
+                                .map(|adaptive| adaptive.limit()),
+                            pending_requests: 0,
+                            total_requests: 0,
+                            total_errors: 0,
+                            error_rate: 0.0,
+                            last_selected: None,
+                        };
+                        if let Some(stats) = self.stats.get(group_name, name) {
+                            stats.fill(&mut status);
+                        }
+
+                        status
+                    })
+                    .collect();
+
+                UpstreamGroupStatus {
+                    name: group_name.clone(),
+                    strategy: load_balancer.as_str().to_string(),
+                    upstreams,
+                }
+            })
+            .collect();
+
+        groups.sort_by(|a, b| a.name.cmp(&b.name));
+        groups
+    }
+
+    /// 上游组是否有可用的上游服务
+    ///
+    /// 上游服务已启用且熔断器允许请求时可用，上游组不存在时返回 false
+    pub fn has_available_upstream(&self, group_name: &str) -> bool {
+        self.groups.get(group_name).is_some_and(|load_balancer| {
+            load_balancer.upstreams().iter().any(|managed_upstream| {
+                !managed_upstream.drained.load(Ordering::Relaxed)
+                    && managed_upstream
+                        .breaker
+                        .as_ref()
+                        .is_none_or(|breaker| breaker.is_call_permitted())
+            })
+        })
+    }
+
+    /// 配置了预热请求的上游组和上游，按上游组名称和上游名称排序
+    pub fn warmup_targets(&self) -> Vec<(String, String)> {
+        let mut targets: Vec<(String, String)> = self
+            .groups
+            .iter()
+            .flat_map(|(group_name, load_balancer)| {
+                load_balancer
+                    .upstreams()
+                    .into_iter()
+                    .map(move |managed_upstream| {
+                        (
+                            group_name.clone(),
+                            managed_upstream.upstream_ref.name.clone(),
+                        )
+                    })
+            })
+            .filter(|(_, upstream_name)| {
+                self.upstreams
+                    .get(upstream_name)
+                    .is_some_and(|config| config.warmup.is_some())
+            })
+            .collect();
+        targets.sort();
+        targets.dedup();
+        targets
+    }
+
+    /// 向上游组中指定上游的每个地址发送预热请求，返回成功的请求数
+    ///
+    /// 每个地址同时发送配置数量的请求以预先建立相同数量的连接。预热请求按上游配置处理请求头、
+    /// 查询参数和认证信息，不转换请求体和 API 格式，不经过熔断器，也不计入上游请求指标。
+    /// 上游组使用响应时间感知的负载均衡策略时，按预热请求的结果更新其指标。上游未配置预热请求时返回 0
+    pub async fn warm_up(&self, group_name: &str, upstream_name: &str) -> Result<usize, AppError> {
+        let (Some(load_balancer), Some(clients)) = (
+            self.groups.get(group_name),
+            self.group_clients.get(group_name),
+        ) else {
+            return Err(AppError::UpstreamGroupNotFound(group_name.to_string()));
+        };
+        let upstream_config = self.upstreams.get(upstream_name).ok_or_else(|| {
+            AppError::Upstream(format!(
+                "Upstream configuration not found: {:?}",
+                upstream_name
+            ))
+        })?;
+        let Some(warmup) = &upstream_config.warmup else {
+            return Ok(0);
+        };
+        let response_aware = load_balancer
+            .as_any()
+            .downcast_ref::<crate::balancer::ResponseAwareBalancer>();
+
+        let managed_upstreams: Vec<_> = load_balancer
+            .upstreams()
+            .into_iter()
+            .filter(|managed_upstream| managed_upstream.upstream_ref.name == upstream_name)
+            .collect();
+        let requests = managed_upstreams.iter().flat_map(|managed_upstream| {
+            (0..warmup.requests).map(move |_| async move {
+                let result = self
+                    .send_warmup(clients, upstream_config, managed_upstream)
+                    .await;
+                (managed_upstream, result)
+            })
+        });
+
+        let mut succeeded = 0;
+        for (managed_upstream, result) in join_all(requests).await {
+            let response_time = match result {
+                Ok(duration) => {
+                    succeeded += 1;
+                    Some(duration.as_millis() as usize)
+                }
+                Err(e) => {
+                    warn!(
+                        "Warm-up request to upstream '{}' in group '{}' failed: {}",
+                        managed_upstream.key(),
+                        group_name,
+                        e
+                    );
+                    None
+                }
+            };
+            if let Some(response_aware) = response_aware {
+                response_aware.record_unselected(managed_upstream, response_time);
+            }
+            METRICS
+                .upstream_warmup_requests_total()
+                .with_label_values(&[
+                    group_name,
+                    upstream_name,
+                    if response_time.is_some() {
+                        warmup_labels::SUCCESS
+                    } else {
+                        warmup_labels::FAILURE
+                    },
+                ])
+                .inc();
+        }
+
+        Ok(succeeded)
+    }
+
+    // 发送一个预热请求并读完响应体，使连接回到连接池，返回收到响应头的耗时
+    async fn send_warmup(
+        &self,
+        clients: &GroupClients,
+        upstream_config: &UpstreamConfig,
+        managed_upstream: &ManagedUpstream,
+    ) -> Result<Duration, AppError> {
+        let Some(warmup) = &upstream_config.warmup else {
+            return Err(AppError::Config(format!(
+                "Warm-up is not configured for upstream '{}'",
+                upstream_config.name
+            )));
+        };
+        let method = Method::from_bytes(warmup.method.as_bytes()).map_err(|e| {
+            AppError::Config(format!(
+                "Invalid warmup request method '{}': {}",
+                warmup.method, e
+            ))
+        })?;
+        let (mut url, unix_socket) =
+            self.build_request_url(upstream_config, managed_upstream.endpoint.as_deref(), "/")?;
+        if let Some(path) = &warmup.path {
+            url.set_path(path);
+        }
+
+        let mut headers = HeaderMap::new();
+        if warmup.body.is_some() {
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        }
+        let headers = process_headers(
+            headers,
+            upstream_config,
+            clients.agent(upstream_config),
+            &RequestContext::default(),
+        )?;
+
+        let mut request_builder = clients
+            .for_upstream(upstream_config)
+            .request(method, url)
+            .headers(headers);
+        if let Some(unix_socket) = unix_socket {
+            request_builder = request_builder.with_extension(unix_socket);
+        }
+        if let Some(body) = &warmup.body {
+            request_builder = request_builder.body(body.to_string());
+        }
+        if let Some(ref auth) = upstream_config.auth {
+            request_builder = add_auth(request_builder, auth).await?.0;
+        }
+
+        let start_time = Instant::now();
+        let exchange = async {
+            let response = request_builder.send().await?;
+            let duration = start_time.elapsed();
+            let status = response.status();
+            response.bytes().await?;
+            if !status.is_success() {
+                return Err(AppError::Upstream(format!("Upstream returned {}", status)));
+            }
+            Ok(duration)
+        };
+        tokio::time::timeout(Duration::from_secs(warmup.timeout), exchange)
+            .await
+            .map_err(|_| {
+                AppError::Upstream(format!(
+                    "Warm-up request timed out after {}s",
+                    warmup.timeout
+                ))
+            })?
+    }
+
+    /// 启用或禁用（排空）上游服务
+    ///
+    /// 禁用后所有上游组的负载均衡器都不再选择该上游，正在处理的请求不受影响。
+    /// 上游服务不存在时返回 false
+    pub fn set_upstream_enabled(&self, upstream_name: &str, enabled: bool) -> bool {
+        match self.drain_flags.get(upstream_name) {
+            Some(drained) => {
+                if drained.swap(!enabled, Ordering::Relaxed) == enabled {
+                    EVENTS.publish_system(SystemEvent::UpstreamStateChanged {
+                        timestamp: unix_millis(),
+                        upstream: upstream_name.to_string(),
+                        enabled,
+                    });
+                    info!(
+                        "Upstream '{}' is now {}",
+                        upstream_name,
+                        if enabled { "enabled" } else { "drained" }
+                    );
+                }
+                true
+            }
+            None => false,
+        }
     }
 
     /// 更新上游组的负载均衡器
@@ -394,14 +1274,28 @@ impl UpstreamManager {
         group_name: &str,
         upstream_refs: &[UpstreamRef],
     ) -> Result<(), AppError> {
-        // 获取组的负载均衡器
-        let load_balancer = match self.groups.get(group_name) {
-            Some(lb) => lb,
-            None => {
-                error!("Upstream group not found: {:?}", group_name);
-                return Err(AppError::UpstreamGroupNotFound(group_name.to_string()));
-            }
-        };
+        let managed_upstreams = self.prepare_group_upstreams(group_name, upstream_refs)?;
+        self.commit_group_upstreams(group_name, managed_upstreams)
+            .await
+    }
+
+    /// 是否存在指定的上游组
+    pub fn has_group(&self, group_name: &str) -> bool {
+        self.groups.contains_key(group_name)
+    }
+
+    /// 为上游组创建新的托管上游列表，不影响运行中的负载均衡器
+    ///
+    /// 与 commit_group_upstreams 配合使用，可以先为多个上游组创建上游列表，全部成功后再替换
+    pub fn prepare_group_upstreams(
+        &self,
+        group_name: &str,
+        upstream_refs: &[UpstreamRef],
+    ) -> Result<Vec<Arc<ManagedUpstream>>, AppError> {
+        if !self.groups.contains_key(group_name) {
+            error!("Upstream group not found: {:?}", group_name);
+            return Err(AppError::UpstreamGroupNotFound(group_name.to_string()));
+        }
 
         // 创建新的ManagedUpstream列表
         let mut managed_upstreams = Vec::with_capacity(upstream_refs.len());
@@ -414,7 +1308,10 @@ impl UpstreamManager {
             .map(|(name, config)| (name.as_str(), config))
             .collect();
 
-        // 为每个上游引用创建托管上游
+        let endpoints = self.endpoints.read().clone();
+        let group_endpoints = self.group_endpoints.read().get(group_name).cloned();
+
+        // 为每个上游引用创建托管上游，由服务发现展开的上游按地址创建多个
         for upstream_ref in upstream_refs {
             // 获取上游配置
             let upstream_config = match upstream_map.get(upstream_ref.name.as_str()) {
@@ -432,15 +1329,219 @@ impl UpstreamManager {
             };
 
             // 创建托管上游
-            let managed_upstream =
-                super::builder::create_managed_upstream(upstream_ref, upstream_config, group_name)?;
-            managed_upstreams.push(managed_upstream);
+            managed_upstreams.extend(
+                create_managed_upstreams(
+                    upstream_ref,
+                    upstream_config,
+                    group_name,
+                    self.drain_flags[&upstream_ref.name].clone(),
+                    self.adaptive_limiters.get(&upstream_ref.name).cloned(),
+                    group_endpoints
+                        .as_deref()
+                        .or_else(|| endpoints.get(&upstream_ref.name).map(Vec::as_slice))
+                        .unwrap_or_default(),
+                )?
+                .into_iter()
+                .map(Arc::new),
+            );
         }
 
+        Ok(managed_upstreams)
+    }
+
+    /// 替换上游组负载均衡器中的上游列表
+    pub async fn commit_group_upstreams(
+        &self,
+        group_name: &str,
+        managed_upstreams: Vec<Arc<ManagedUpstream>>,
+    ) -> Result<(), AppError> {
+        let Some(load_balancer) = self.groups.get(group_name) else {
+            error!("Upstream group not found: {:?}", group_name);
+            return Err(AppError::UpstreamGroupNotFound(group_name.to_string()));
+        };
+
         // 更新负载均衡器的上游列表
         load_balancer.update_upstreams(managed_upstreams).await;
         info!("Updated load balancer for upstream group '{}'", group_name);
 
         Ok(())
     }
+
+    /// 更新服务发现得到的上游地址
+    ///
+    /// 包含该上游的每个上游组为每个地址创建一个托管上游，仍然存在的地址保留原有的托管上游及其熔断器。
+    /// 启用了服务发现的上游组使用组的地址，不受影响。地址没有变化时返回 false
+    pub async fn set_upstream_endpoints(
+        &self,
+        upstream_name: &str,
+        endpoints: Vec<Endpoint>,
+    ) -> Result<bool, AppError> {
+        if !self.upstreams.contains_key(upstream_name) {
+            return Err(AppError::Config(format!(
+                "Upstream '{}' not found",
+                upstream_name
+            )));
+        }
+        let Some(endpoints) = replace_endpoints(&self.endpoints, upstream_name, endpoints) else {
+            return Ok(false);
+        };
+
+        for (group_name, load_balancer) in &self.groups {
+            if self.group_endpoints.read().contains_key(group_name) {
+                continue;
+            }
+            let current = load_balancer.upstreams();
+            let Some(position) = current
+                .iter()
+                .position(|upstream| upstream.upstream_ref.name == upstream_name)
+            else {
+                continue;
+            };
+
+            // 在原位置替换该上游的托管上游，其余上游保持不变
+            let mut managed_upstreams: Vec<Arc<ManagedUpstream>> = current
+                .iter()
+                .filter(|upstream| upstream.upstream_ref.name != upstream_name)
+                .cloned()
+                .collect();
+            let expanded = self.expand_upstream(
+                group_name,
+                &current,
+                &current[position].upstream_ref,
+                &endpoints,
+            )?;
+            managed_upstreams.splice(position..position, expanded);
+
+            load_balancer.update_upstreams(managed_upstreams).await;
+        }
+
+        info!(
+            "Upstream '{}' now has {} endpoints: {}",
+            upstream_name,
+            endpoints.len(),
+            format_endpoints(&endpoints)
+        );
+        Ok(true)
+    }
+
+    /// 更新上游组服务发现得到的地址
+    ///
+    /// 组内的每个上游为每个地址创建一个托管上游，仍然存在的地址保留原有的托管上游及其熔断器。
+    /// 地址没有变化时返回 false
+    pub async fn set_group_endpoints(
+        &self,
+        group_name: &str,
+        endpoints: Vec<Endpoint>,
+    ) -> Result<bool, AppError> {
+        let load_balancer = self
+            .groups
+            .get(group_name)
+            .ok_or_else(|| AppError::UpstreamGroupNotFound(group_name.to_string()))?;
+        let Some(endpoints) = replace_endpoints(&self.group_endpoints, group_name, endpoints)
+        else {
+            return Ok(false);
+        };
+
+        // 按原有顺序展开组内的每个上游
+        let current = load_balancer.upstreams();
+        let mut managed_upstreams = Vec::with_capacity(current.len() * endpoints.len());
+        let mut seen = HashSet::new();
+        for upstream in &current {
+            if seen.insert(upstream.upstream_ref.name.as_str()) {
+                managed_upstreams.extend(self.expand_upstream(
+                    group_name,
+                    &current,
+                    &upstream.upstream_ref,
+                    &endpoints,
+                )?);
+            }
+        }
+        load_balancer.update_upstreams(managed_upstreams).await;
+
+        info!(
+            "Upstream group '{}' now has {} endpoints: {}",
+            group_name,
+            endpoints.len(),
+            format_endpoints(&endpoints)
+        );
+        Ok(true)
+    }
+
+    // 为上游的每个地址创建托管上游，没有地址时使用上游 URL 创建一个，已有相同地址的托管上游时直接复用
+    fn expand_upstream(
+        &self,
+        group_name: &str,
+        current: &[Arc<ManagedUpstream>],
+        upstream_ref: &UpstreamRef,
+        endpoints: &[Arc<Endpoint>],
+    ) -> Result<Vec<Arc<ManagedUpstream>>, AppError> {
+        let upstream_config = self.upstreams.get(&upstream_ref.name).ok_or_else(|| {
+            AppError::Config(format!("Upstream '{}' not found", upstream_ref.name))
+        })?;
+        let targets: Vec<Option<&Arc<Endpoint>>> = if endpoints.is_empty() {
+            vec![None]
+        } else {
+            endpoints.iter().map(Some).collect()
+        };
+
+        targets
+            .into_iter()
+            .map(|endpoint| {
+                let existing = current.iter().find(|upstream| {
+                    upstream.upstream_ref.name == upstream_ref.name
+                        && upstream.endpoint.as_ref() == endpoint
+                });
+                match existing {
+                    Some(upstream) => Ok(upstream.clone()),
+                    None => create_managed_upstream(
+                        upstream_ref,
+                        upstream_config,
+                        group_name,
+                        self.drain_flags[&upstream_ref.name].clone(),
+                        self.adaptive_limiters.get(&upstream_ref.name).cloned(),
+                        endpoint.cloned(),
+                    )
+                    .map(Arc::new),
+                }
+            })
+            .collect()
+    }
+}
+
+// 排序去重后替换记录的地址，地址没有变化时返回 None
+fn replace_endpoints(
+    current: &RwLock<HashMap<String, Vec<Arc<Endpoint>>>>,
+    name: &str,
+    mut endpoints: Vec<Endpoint>,
+) -> Option<Vec<Arc<Endpoint>>> {
+    endpoints.sort();
+    endpoints.dedup();
+
+    let mut current = current.write();
+    if current
+        .get(name)
+        .is_some_and(|previous| previous.iter().map(Arc::as_ref).eq(endpoints.iter()))
+    {
+        return None;
+    }
+    let endpoints: Vec<Arc<Endpoint>> = endpoints.into_iter().map(Arc::new).collect();
+    current.insert(name.to_string(), endpoints.clone());
+    Some(endpoints)
+}
+
+// 用于日志的地址列表
+fn format_endpoints(endpoints: &[Arc<Endpoint>]) -> String {
+    endpoints
+        .iter()
+        .map(|endpoint| endpoint.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// 上游响应的状态码类别，没有收到响应时为 error
+fn upstream_status_class(response: &Result<Response, AppError>) -> &'static str {
+    match response {
+        Ok(response) => status_class(response.status()),
+        Err(_) => status_class_labels::ERROR,
+    }
 }
@@ -1,5 +1,24 @@
 mod builder;
+mod connection;
+mod context;
+mod external;
+mod headers;
 mod http_client;
+mod idle;
 mod manager;
+mod oauth2;
+mod retry;
+mod stats;
+mod sticky;
+mod timing;
+mod token_cache;
+mod unix;
+mod usage;
+mod warmup;
 
+pub use context::{RequestContext, ServedBy};
+pub use headers::process_headers;
 pub use manager::UpstreamManager;
+pub use stats::{UpstreamGroupStatus, UpstreamStatus};
+pub(crate) use unix::split_url as split_unix_socket_url;
+pub use warmup::UpstreamWarmup;
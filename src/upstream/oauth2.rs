@@ -0,0 +1,221 @@
+use crate::{
+    config::{OAuth2Config, OAuth2Grant},
+    error::AppError,
+    events::unix_millis,
+    r#const::oauth2,
+    secret,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use once_cell::sync::Lazy;
+use openssl::{hash::MessageDigest, pkey::PKey, sign::Signer};
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+use tracing::{debug, info};
+use xxhash_rust::xxh3::xxh3_64;
+
+use super::token_cache::TokenCache;
+
+// 访问令牌缓存，按 OAuth2 配置索引，同一配置的并发请求只获取一次令牌
+static TOKENS: Lazy<TokenCache> = Lazy::new(TokenCache::new);
+
+// 请求令牌端点的 HTTP 客户端
+static TOKEN_CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(oauth2::REQUEST_TIMEOUT))
+        .build()
+        .unwrap_or_default()
+});
+
+// 令牌端点响应
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+// GCP 服务账号密钥文件中使用的字段
+#[derive(Deserialize)]
+struct ServiceAccountKey {
+    client_email: String,
+    private_key: String,
+    #[serde(default)]
+    private_key_id: Option<String>,
+    #[serde(default)]
+    token_uri: Option<String>,
+}
+
+/// 获取访问令牌，缓存的令牌即将过期时自动刷新
+pub(super) async fn access_token(config: &OAuth2Config) -> Result<String, AppError> {
+    TOKENS
+        .get_or_fetch(cache_key(config), || async {
+            let response = match config.grant {
+                OAuth2Grant::ClientCredentials => fetch_client_credentials(config).await?,
+                OAuth2Grant::ServiceAccount => fetch_service_account(config).await?,
+            };
+
+            let expires_in = response.expires_in.unwrap_or(oauth2::DEFAULT_EXPIRES_IN);
+            info!(
+                "Obtained OAuth2 access token ({:?} grant), expires in {}s",
+                config.grant, expires_in
+            );
+
+            let refresh_after =
+                Duration::from_secs(expires_in.saturating_sub(config.refresh_before));
+            Ok((response.access_token, refresh_after))
+        })
+        .await
+}
+
+// 缓存键，获取令牌所用的参数相同时共享令牌，直接配置的客户端密钥以哈希值参与区分
+fn cache_key(config: &OAuth2Config) -> String {
+    format!(
+        "{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{}",
+        config.grant,
+        config.token_url,
+        config.client_id,
+        config
+            .client_secret
+            .as_deref()
+            .map(|s| xxh3_64(s.as_bytes())),
+        config.client_secret_file,
+        config.service_account_file,
+        config.scopes.join(" ")
+    )
+}
+
+// 客户端凭证模式获取令牌
+async fn fetch_client_credentials(config: &OAuth2Config) -> Result<TokenResponse, AppError> {
+    let token_url = config
+        .token_url
+        .as_deref()
+        .ok_or_else(|| AppError::AuthError("OAuth2 token_url missing".to_string()))?;
+    let client_id = config
+        .client_id
+        .as_deref()
+        .ok_or_else(|| AppError::AuthError("OAuth2 client_id missing".to_string()))?;
+    let client_secret = match &config.client_secret_file {
+        Some(path) => secret::read_secret_file(path)?,
+        None => config
+            .client_secret
+            .clone()
+            .ok_or_else(|| AppError::AuthError("OAuth2 client_secret missing".to_string()))?,
+    };
+
+    let mut form = vec![(
+        "grant_type",
+        oauth2::CLIENT_CREDENTIALS_GRANT_TYPE.to_string(),
+    )];
+    if !config.scopes.is_empty() {
+        form.push(("scope", config.scopes.join(" ")));
+    }
+
+    let request = TOKEN_CLIENT
+        .post(token_url)
+        .basic_auth(client_id, Some(client_secret))
+        .form(&form);
+    send_token_request(token_url, request).await
+}
+
+// GCP 服务账号模式获取令牌，使用私钥签名的 JWT 断言换取访问令牌
+async fn fetch_service_account(config: &OAuth2Config) -> Result<TokenResponse, AppError> {
+    let path = config
+        .service_account_file
+        .as_deref()
+        .ok_or_else(|| AppError::AuthError("OAuth2 service_account_file missing".to_string()))?;
+    let key: ServiceAccountKey =
+        serde_json::from_str(&secret::read_secret_file(path)?).map_err(|e| {
+            AppError::AuthError(format!("Invalid service account file {:?}: {}", path, e))
+        })?;
+
+    let token_url = config
+        .token_url
+        .clone()
+        .or_else(|| key.token_uri.clone())
+        .unwrap_or_else(|| oauth2::GOOGLE_TOKEN_URL.to_string());
+    let scope = if config.scopes.is_empty() {
+        oauth2::DEFAULT_SERVICE_ACCOUNT_SCOPE.to_string()
+    } else {
+        config.scopes.join(" ")
+    };
+    let assertion = sign_jwt(&key, &token_url, &scope)?;
+    debug!(
+        "Requesting OAuth2 access token for service account {:?}",
+        key.client_email
+    );
+
+    let form = [
+        ("grant_type", oauth2::JWT_BEARER_GRANT_TYPE),
+        ("assertion", assertion.as_str()),
+    ];
+    let request = TOKEN_CLIENT.post(&token_url).form(&form);
+    send_token_request(&token_url, request).await
+}
+
+// 生成 RS256 签名的 JWT 断言
+fn sign_jwt(key: &ServiceAccountKey, audience: &str, scope: &str) -> Result<String, AppError> {
+    let issued_at = unix_millis() / 1000;
+    let header = match &key.private_key_id {
+        Some(kid) => json!({ "alg": "RS256", "typ": "JWT", "kid": kid }),
+        None => json!({ "alg": "RS256", "typ": "JWT" }),
+    };
+    let claims = json!({
+        "iss": key.client_email,
+        "scope": scope,
+        "aud": audience,
+        "iat": issued_at,
+        "exp": issued_at + oauth2::JWT_LIFETIME,
+    });
+
+    let signing_input = format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(header.to_string()),
+        URL_SAFE_NO_PAD.encode(claims.to_string())
+    );
+
+    let signature = PKey::private_key_from_pem(key.private_key.as_bytes())
+        .and_then(|pkey| {
+            let mut signer = Signer::new(MessageDigest::sha256(), &pkey)?;
+            signer.update(signing_input.as_bytes())?;
+            signer.sign_to_vec()
+        })
+        .map_err(|e| {
+            AppError::AuthError(format!("Failed to sign service account assertion: {}", e))
+        })?;
+
+    Ok(format!(
+        "{}.{}",
+        signing_input,
+        URL_SAFE_NO_PAD.encode(signature)
+    ))
+}
+
+// 请求令牌端点并解析响应
+async fn send_token_request(
+    token_url: &str,
+    request: reqwest::RequestBuilder,
+) -> Result<TokenResponse, AppError> {
+    let response = request.send().await.map_err(|e| {
+        AppError::AuthError(format!(
+            "OAuth2 token request to {:?} failed: {}",
+            token_url, e
+        ))
+    })?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(AppError::AuthError(format!(
+            "OAuth2 token endpoint {:?} returned {}: {}",
+            token_url, status, body
+        )));
+    }
+
+    response.json::<TokenResponse>().await.map_err(|e| {
+        AppError::AuthError(format!(
+            "Invalid OAuth2 token response from {:?}: {}",
+            token_url, e
+        ))
+    })
+}
@@ -0,0 +1,160 @@
+use async_trait::async_trait;
+use hyper::http::Extensions;
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next};
+use reqwest_retry::{
+    default_on_request_failure, policies::ExponentialBackoff, RetryTransientMiddleware, Retryable,
+    RetryableStrategy,
+};
+use retry_policies::{Jitter, RetryDecision, RetryPolicy};
+use std::time::{Duration, SystemTime};
+
+use crate::{config::RetryConfig, r#const::retry_limits};
+
+use super::unix;
+
+/// 带时间预算的指数退避重试策略
+///
+/// 在重试次数限制之外，如果下一次重试的开始时间超出时间预算，则不再重试
+pub struct BudgetedBackoff {
+    // 指数退避策略
+    backoff: ExponentialBackoff,
+    // 时间预算，从首次请求开始计算
+    max_elapsed: Option<Duration>,
+}
+
+impl BudgetedBackoff {
+    pub fn new(backoff: ExponentialBackoff, max_elapsed: Option<Duration>) -> Self {
+        Self {
+            backoff,
+            max_elapsed,
+        }
+    }
+}
+
+impl RetryPolicy for BudgetedBackoff {
+    fn should_retry(&self, request_start_time: SystemTime, n_past_retries: u32) -> RetryDecision {
+        let decision = self
+            .backoff
+            .should_retry(request_start_time, n_past_retries);
+
+        match (decision, self.max_elapsed) {
+            // 退避等待结束时已超出时间预算，放弃重试
+            (RetryDecision::Retry { execute_after }, Some(max_elapsed))
+                if execute_after >= request_start_time + max_elapsed =>
+            {
+                RetryDecision::DoNotRetry
+            }
+            (decision, _) => decision,
+        }
+    }
+}
+
+/// 按上游响应状态码判断是否重试
+///
+/// 连接错误和超时按 reqwest-retry 的默认规则处理，上游响应只在状态码属于配置的列表时重试
+pub struct StatusRetryStrategy {
+    // 需要重试的状态码
+    statuses: Vec<u16>,
+}
+
+impl RetryableStrategy for StatusRetryStrategy {
+    fn handle(&self, res: &reqwest_middleware::Result<Response>) -> Option<Retryable> {
+        match res {
+            Ok(response) if self.statuses.contains(&response.status().as_u16()) => {
+                Some(Retryable::Transient)
+            }
+            Ok(response) if response.status().is_success() => None,
+            Ok(_) => Some(Retryable::Fatal),
+            Err(error) => default_on_request_failure(error),
+        }
+    }
+}
+
+/// 只重试连接阶段的错误
+///
+/// 连接失败时请求尚未发送给上游，重试不会重复处理，用于非幂等请求
+pub struct ConnectRetryStrategy;
+
+impl RetryableStrategy for ConnectRetryStrategy {
+    fn handle(&self, res: &reqwest_middleware::Result<Response>) -> Option<Retryable> {
+        match res {
+            Ok(response) if response.status().is_success() => None,
+            Err(error) if is_connect_error(error) => Some(Retryable::Transient),
+            _ => Some(Retryable::Fatal),
+        }
+    }
+}
+
+// 是否为建立连接时的错误（包括连接 Unix 域套接字失败）
+fn is_connect_error(error: &reqwest_middleware::Error) -> bool {
+    error.is_connect() || unix::is_connect_error(error)
+}
+
+/// 重试中间件
+///
+/// 连接错误总是重试。其他错误和上游响应默认只对幂等请求重试，非幂等请求（如 POST）可能已被上游处理，
+/// 重试会重复计费，需要配置 `retry_non_idempotent` 才会重试。请求携带 `x-llmproxy-no-retry` 头部时不重试，
+/// 该头部不会转发给上游
+pub struct RetryMiddleware {
+    // 指数退避重试
+    inner: RetryTransientMiddleware<BudgetedBackoff, StatusRetryStrategy>,
+    // 非幂等请求只重试连接错误
+    connect_only: RetryTransientMiddleware<BudgetedBackoff, ConnectRetryStrategy>,
+    // 是否重试非幂等请求
+    retry_non_idempotent: bool,
+}
+
+impl RetryMiddleware {
+    pub fn new(config: &RetryConfig) -> Self {
+        let backoff = ExponentialBackoff::builder()
+            .retry_bounds(
+                Duration::from_millis(config.initial.into()),
+                Duration::from_secs(retry_limits::MAX_DELAY.into()),
+            )
+            .base(2)
+            .jitter(Jitter::Bounded)
+            .build_with_max_retries(config.attempts);
+
+        // 超出重试时间预算时不再重试
+        let max_elapsed = config.max_elapsed_ms.map(Duration::from_millis);
+        let strategy = StatusRetryStrategy {
+            statuses: config.retry_on_status.clone(),
+        };
+
+        Self {
+            inner: RetryTransientMiddleware::new_with_policy_and_strategy(
+                BudgetedBackoff::new(backoff, max_elapsed),
+                strategy,
+            ),
+            connect_only: RetryTransientMiddleware::new_with_policy_and_strategy(
+                BudgetedBackoff::new(backoff, max_elapsed),
+                ConnectRetryStrategy,
+            ),
+            retry_non_idempotent: config.retry_non_idempotent,
+        }
+    }
+}
+
+#[async_trait]
+impl Middleware for RetryMiddleware {
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let opted_out = req
+            .headers_mut()
+            .remove(retry_limits::NO_RETRY_HEADER)
+            .is_some();
+        if opted_out {
+            return next.run(req, extensions).await;
+        }
+        if self.retry_non_idempotent || req.method().is_idempotent() {
+            self.inner.handle(req, extensions, next).await
+        } else {
+            self.connect_only.handle(req, extensions, next).await
+        }
+    }
+}
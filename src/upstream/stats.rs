@@ -0,0 +1,153 @@
+use crate::{events::unix_millis, r#const::upstream_stats_limits};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use utoipa::ToSchema;
+
+/// 上游服务运行状态
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpstreamStatus {
+    /// 上游服务名称
+    pub name: String,
+    /// 上游服务地址
+    pub url: String,
+    /// 服务发现得到的上游地址（主机:端口），未启用服务发现时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    /// 组内权重
+    pub weight: u32,
+    /// 是否启用（禁用时处于维护/排空状态，不接收新请求）
+    pub enabled: bool,
+    /// 是否可以接收请求（已启用且熔断器未开启）
+    pub healthy: bool,
+    /// 熔断器状态（closed、open、half_open），未启用熔断器时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub breaker: Option<String>,
+    /// 自适应并发上限，未启用自适应并发时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency_limit: Option<u32>,
+    /// 正在处理的请求数量
+    pub pending_requests: usize,
+    /// 请求总数
+    pub total_requests: u64,
+    /// 失败请求总数
+    pub total_errors: u64,
+    /// 近期错误率（0-1，指数加权移动平均）
+    pub error_rate: f64,
+    /// 最近一次被选中的时间（Unix 毫秒时间戳），从未被选中时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_selected: Option<u64>,
+}
+
+/// 上游组运行状态
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpstreamGroupStatus {
+    /// 上游组名称
+    pub name: String,
+    /// 负载均衡策略
+    pub strategy: String,
+    /// 上游服务运行状态列表
+    pub upstreams: Vec<UpstreamStatus>,
+}
+
+// 上游服务运行统计
+#[derive(Default)]
+pub(super) struct UpstreamStats {
+    // 正在处理的请求数量
+    pending: AtomicUsize,
+    // 请求总数
+    requests: AtomicU64,
+    // 失败请求总数
+    errors: AtomicU64,
+    // 近期错误率（千分比，指数加权移动平均）
+    error_rate: AtomicU32,
+    // 最近一次被选中的时间（Unix 毫秒时间戳），0 表示从未被选中
+    last_selected: AtomicU64,
+}
+
+impl UpstreamStats {
+    // 记录一次请求开始，请求结束（或提前返回）时自动减少正在处理的请求数量
+    pub(super) fn begin(self: &Arc<Self>) -> InFlightRequest {
+        self.pending.fetch_add(1, Ordering::Relaxed);
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.last_selected.store(unix_millis(), Ordering::Relaxed);
+
+        InFlightRequest {
+            stats: self.clone(),
+        }
+    }
+
+    // 更新近期错误率
+    fn record(&self, success: bool) {
+        if !success {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let sample = if success { 0 } else { 1000 };
+        let decay = upstream_stats_limits::ERROR_RATE_DECAY;
+        let _ = self
+            .error_rate
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |rate| {
+                Some((rate * (decay - 1) + sample) / decay)
+            });
+    }
+
+    // 填充运行状态中的统计字段
+    pub(super) fn fill(&self, status: &mut UpstreamStatus) {
+        status.pending_requests = self.pending.load(Ordering::Relaxed);
+        status.total_requests = self.requests.load(Ordering::Relaxed);
+        status.total_errors = self.errors.load(Ordering::Relaxed);
+        status.error_rate = f64::from(self.error_rate.load(Ordering::Relaxed)) / 1000.0;
+        status.last_selected = match self.last_selected.load(Ordering::Relaxed) {
+            0 => None,
+            timestamp => Some(timestamp),
+        };
+    }
+}
+
+// 正在处理的请求
+pub(super) struct InFlightRequest {
+    stats: Arc<UpstreamStats>,
+}
+
+impl InFlightRequest {
+    // 记录请求结果
+    pub(super) fn finish(self, success: bool) {
+        self.stats.record(success);
+    }
+}
+
+impl Drop for InFlightRequest {
+    fn drop(&mut self) {
+        self.stats.pending.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+// 所有上游组的运行统计，按上游组和上游服务名称索引
+#[derive(Default)]
+pub(super) struct UpstreamStatsRegistry {
+    groups: HashMap<String, DashMap<String, Arc<UpstreamStats>>>,
+}
+
+impl UpstreamStatsRegistry {
+    // 为上游组创建统计表
+    pub(super) fn add_group(&mut self, group_name: &str) {
+        self.groups.entry(group_name.to_string()).or_default();
+    }
+
+    // 获取上游服务的运行统计，不存在时创建
+    pub(super) fn get(&self, group_name: &str, upstream_name: &str) -> Option<Arc<UpstreamStats>> {
+        let group = self.groups.get(group_name)?;
+        if let Some(stats) = group.get(upstream_name) {
+            return Some(stats.clone());
+        }
+
+        Some(group.entry(upstream_name.to_string()).or_default().clone())
+    }
+}
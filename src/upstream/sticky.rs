@@ -0,0 +1,68 @@
+use crate::r#const::sticky_limits;
+use dashmap::DashMap;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+// 会话粘滞记录
+#[derive(Debug, Clone)]
+pub(super) struct StickyEntry {
+    // 返回路由提示的上游名称
+    pub upstream: String,
+    // 上游返回的路由提示（如区域或分片）
+    pub hint: String,
+    // 过期时间
+    expires_at: Instant,
+}
+
+// 会话粘滞表，键为 (上游组名称, 会话 ID)
+#[derive(Default)]
+pub(super) struct StickySessions {
+    entries: DashMap<(String, String), StickyEntry>,
+}
+
+impl StickySessions {
+    // 查找未过期的会话粘滞记录
+    pub fn get(&self, group: &str, session_id: &str) -> Option<StickyEntry> {
+        let key = (group.to_string(), session_id.to_string());
+        let entry = self.entries.get(&key)?.clone();
+
+        if entry.expires_at <= Instant::now() {
+            // 移除已过期的记录
+            self.entries.remove(&key);
+            return None;
+        }
+
+        Some(entry)
+    }
+
+    // 记录会话对应的路由提示
+    pub fn record(&self, group: &str, session_id: &str, upstream: &str, hint: &str, ttl: u64) {
+        // 会话过多时清理过期记录，避免无限增长
+        if self.entries.len() >= sticky_limits::MAX_SESSIONS {
+            let now = Instant::now();
+            self.entries.retain(|_, entry| entry.expires_at > now);
+
+            if self.entries.len() >= sticky_limits::MAX_SESSIONS {
+                debug!(
+                    "Sticky session table is full, skip pinning session {:?} in group {:?}",
+                    session_id, group
+                );
+                return;
+            }
+        }
+
+        debug!(
+            "Pinned session {:?} in group {:?} to hint {:?} (upstream: {:?})",
+            session_id, group, hint, upstream
+        );
+
+        self.entries.insert(
+            (group.to_string(), session_id.to_string()),
+            StickyEntry {
+                upstream: upstream.to_string(),
+                hint: hint.to_string(),
+                expires_at: Instant::now() + Duration::from_secs(ttl),
+            },
+        );
+    }
+}
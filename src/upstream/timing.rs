@@ -0,0 +1,120 @@
+use crate::{
+    metrics::METRICS,
+    r#const::{dialect, http_headers::content_types},
+    translate::sse::SseDecoder,
+};
+use futures_util::StreamExt;
+use reqwest::{header::CONTENT_TYPE, Response};
+use std::time::Instant;
+
+// 记录上游响应的首字节耗时，事件流响应同时记录事件数和输出速度
+// 平均请求耗时无法反映流式响应的质量，首字节耗时和输出速度能更早发现服务商的性能下降。
+// 输出速度按每个事件一个 token 估算（不包括结束标记），从首字节开始计时
+pub(super) fn observe_timing(
+    response: Response,
+    start_time: Instant,
+    group: &str,
+    upstream: &str,
+) -> Response {
+    let is_event_stream = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains(content_types::EVENT_STREAM));
+
+    let status = response.status();
+    let version = response.version();
+    let headers = response.headers().clone();
+    let mut timer = StreamTimer {
+        group: group.to_string(),
+        upstream: upstream.to_string(),
+        start_time,
+        first_byte: None,
+        last_event: None,
+        decoder: is_event_stream.then(SseDecoder::default),
+        events: 0,
+    };
+    // 计时器随响应流释放，此时记录事件数和输出速度
+    let stream = response.bytes_stream().map(move |chunk| {
+        if let Ok(chunk) = &chunk {
+            timer.observe(chunk);
+        }
+        chunk
+    });
+
+    let mut timed = hyper::Response::new(reqwest::Body::wrap_stream(stream));
+    *timed.status_mut() = status;
+    *timed.version_mut() = version;
+    *timed.headers_mut() = headers;
+    Response::from(timed)
+}
+
+// 单个响应的计时状态
+struct StreamTimer {
+    // 上游组名称
+    group: String,
+    // 上游名称
+    upstream: String,
+    // 开始转发请求的时间
+    start_time: Instant,
+    // 收到首字节的时间
+    first_byte: Option<Instant>,
+    // 收到最后一个事件的时间
+    last_event: Option<Instant>,
+    // 事件解码器，只有事件流响应需要
+    decoder: Option<SseDecoder>,
+    // 事件数
+    events: u64,
+}
+
+impl StreamTimer {
+    // 记录一个数据块
+    fn observe(&mut self, chunk: &[u8]) {
+        if chunk.is_empty() {
+            return;
+        }
+        let now = Instant::now();
+        if self.first_byte.is_none() {
+            self.first_byte = Some(now);
+            METRICS
+                .upstream_ttfb_seconds()
+                .with_label_values(&[&self.group, &self.upstream])
+                .observe(now.duration_since(self.start_time).as_secs_f64());
+        }
+
+        if let Some(decoder) = &mut self.decoder {
+            let events = decoder
+                .feed(chunk)
+                .iter()
+                .filter(|event| event.data != dialect::STREAM_DONE)
+                .count() as u64;
+            if events > 0 {
+                self.events += events;
+                self.last_event = Some(now);
+            }
+        }
+    }
+}
+
+impl Drop for StreamTimer {
+    fn drop(&mut self) {
+        if self.events == 0 {
+            return;
+        }
+        METRICS
+            .stream_chunks_total()
+            .with_label_values(&[&self.group, &self.upstream])
+            .inc_by(self.events);
+
+        // 首个事件与首字节同时到达，之后的事件数除以经过的时间即为输出速度
+        if let (Some(first_byte), Some(last_event)) = (self.first_byte, self.last_event) {
+            let elapsed = last_event.duration_since(first_byte).as_secs_f64();
+            if self.events > 1 && elapsed > 0.0 {
+                METRICS
+                    .stream_tokens_per_second()
+                    .with_label_values(&[&self.group, &self.upstream])
+                    .observe((self.events - 1) as f64 / elapsed);
+            }
+        }
+    }
+}
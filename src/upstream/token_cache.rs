@@ -0,0 +1,81 @@
+use crate::error::AppError;
+use dashmap::DashMap;
+use std::{
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+
+// 缓存的令牌
+struct CachedToken {
+    // 令牌
+    token: String,
+    // 需要刷新的时间
+    refresh_at: Instant,
+}
+
+// 令牌缓存槽，持有锁期间获取令牌，同一键的并发请求只获取一次
+type Slot = Arc<Mutex<Option<CachedToken>>>;
+
+/// 按键缓存令牌，供 OAuth2 和外部命令认证共用
+pub(super) struct TokenCache {
+    slots: DashMap<String, Slot>,
+}
+
+impl TokenCache {
+    pub(super) fn new() -> Self {
+        Self {
+            slots: DashMap::new(),
+        }
+    }
+
+    /// 返回缓存的令牌，没有令牌或已到刷新时间时调用 fetch 获取令牌及其刷新间隔
+    pub(super) async fn get_or_fetch<F, Fut>(
+        &self,
+        key: String,
+        fetch: F,
+    ) -> Result<String, AppError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<(String, Duration), AppError>>,
+    {
+        let slot = self.slot(key);
+        let mut cached = slot.lock().await;
+
+        if let Some(token) = cached.as_ref() {
+            if Instant::now() < token.refresh_at {
+                return Ok(token.token.clone());
+            }
+        }
+
+        let (token, refresh_after) = fetch().await?;
+        *cached = Some(CachedToken {
+            token: token.clone(),
+            refresh_at: Instant::now() + refresh_after,
+        });
+
+        Ok(token)
+    }
+
+    /// 丢弃被拒绝的令牌，返回是否丢弃了缓存的令牌
+    ///
+    /// 只在缓存的仍是被拒绝的令牌时丢弃，同一令牌的多个拒绝响应不会丢弃已经刷新的令牌
+    pub(super) async fn invalidate(&self, key: String, rejected: &str) -> bool {
+        let slot = self.slot(key);
+        let mut cached = slot.lock().await;
+        if cached.as_ref().is_some_and(|token| token.token == rejected) {
+            *cached = None;
+            return true;
+        }
+        false
+    }
+
+    // 获取键对应的缓存槽
+    fn slot(&self, key: String) -> Slot {
+        self.slots
+            .entry(key)
+            .or_insert_with(|| Arc::new(Mutex::new(None)))
+            .clone()
+    }
+}
@@ -0,0 +1,177 @@
+use crate::r#const::unix_socket;
+use async_trait::async_trait;
+use hyper::{http::Extensions, Uri};
+use hyper_util::rt::TokioIo;
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next};
+use std::{
+    io,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::net::UnixStream;
+use tracing::debug;
+use url::Url;
+
+/// 请求扩展：目标 Unix 域套接字路径
+#[derive(Debug, Clone)]
+pub(super) struct UnixSocket(Arc<str>);
+
+/// 将上游 URL 解析为请求 URL
+///
+/// 上游 URL 形如 `unix:///var/run/vllm.sock:/v1/chat/completions`，第一个 `:` 之前为套接字路径，
+/// 之后为 HTTP 路径。reqwest 不接受没有主机的 URL，因此改写为 `http://localhost/<HTTP 路径>`，
+/// 套接字路径通过请求扩展传递给 [`UnixSocketMiddleware`]。其他 URL 原样返回。
+pub(super) fn resolve(url: Url) -> (Url, Option<UnixSocket>) {
+    let Some((socket_path, path_and_query)) = split_url(&url) else {
+        return (url, None);
+    };
+    match Url::parse(&format!("http://{}{}", unix_socket::HOST, path_and_query)) {
+        Ok(http_url) => (http_url, Some(UnixSocket(socket_path.into()))),
+        Err(_) => (url, None),
+    }
+}
+
+/// 通过 Unix 域套接字发送请求的中间件
+///
+/// 带有 [`UnixSocket`] 扩展的请求直接发送到套接字，其他请求交给后续中间件处理。
+/// 每个请求使用独立的 HTTP/1.1 连接，本地套接字的连接开销可以忽略。
+pub struct UnixSocketMiddleware {
+    // 连接超时
+    connect_timeout: Duration,
+    // 请求超时（流式模式下不设置）
+    request_timeout: Option<Duration>,
+}
+
+impl UnixSocketMiddleware {
+    pub fn new(connect_timeout: Duration, request_timeout: Option<Duration>) -> Self {
+        Self {
+            connect_timeout,
+            request_timeout,
+        }
+    }
+
+    // 建立连接并发送请求，返回响应头接收完成的响应
+    async fn send(
+        &self,
+        mut req: Request,
+        socket_path: &str,
+    ) -> reqwest_middleware::Result<Response> {
+        let deadline = self.request_timeout.map(|timeout| Instant::now() + timeout);
+
+        let stream = tokio::time::timeout(self.connect_timeout, UnixStream::connect(socket_path))
+            .await
+            .unwrap_or_else(|_| Err(io::Error::from(io::ErrorKind::TimedOut)))
+            .map_err(|source| {
+                reqwest_middleware::Error::middleware(ConnectError {
+                    path: socket_path.to_string(),
+                    source,
+                })
+            })?;
+
+        let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+            .await
+            .map_err(reqwest_middleware::Error::middleware)?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                debug!("Unix socket connection closed with error: {}", e);
+            }
+        });
+
+        // 构建 HTTP 请求，未设置 Host 时使用固定值
+        let path_and_query = match req.url().query() {
+            Some(query) => format!("{}?{}", req.url().path(), query),
+            None => req.url().path().to_string(),
+        };
+        let uri: Uri = path_and_query
+            .parse()
+            .map_err(|e| invalid_input(format!("Invalid unix socket HTTP path: {}", e)))?;
+        let mut builder = hyper::Request::builder()
+            .method(req.method().clone())
+            .uri(uri);
+        for (name, value) in req.headers() {
+            builder = builder.header(name, value);
+        }
+        if !req.headers().contains_key(hyper::header::HOST) {
+            builder = builder.header(hyper::header::HOST, unix_socket::HOST);
+        }
+        let body = req.body_mut().take().unwrap_or_else(|| Vec::new().into());
+        let request = builder
+            .body(body)
+            .map_err(|e| invalid_input(format!("Invalid unix socket request: {}", e)))?;
+
+        let response = match deadline {
+            Some(deadline) => {
+                tokio::time::timeout_at(deadline.into(), sender.send_request(request))
+                    .await
+                    .map_err(|_| {
+                        reqwest_middleware::Error::middleware(io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            format!("Request to unix socket {:?} timed out", socket_path),
+                        ))
+                    })?
+            }
+            None => sender.send_request(request).await,
+        }
+        .map_err(reqwest_middleware::Error::middleware)?;
+
+        Ok(Response::from(response.map(reqwest::Body::wrap)))
+    }
+}
+
+#[async_trait]
+impl Middleware for UnixSocketMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        match extensions.get::<UnixSocket>().cloned() {
+            Some(UnixSocket(socket_path)) => self.send(req, &socket_path).await,
+            None => next.run(req, extensions).await,
+        }
+    }
+}
+
+/// 连接 Unix 域套接字失败
+#[derive(Debug, thiserror::Error)]
+#[error("Unable to connect to unix socket {path:?}: {source}")]
+struct ConnectError {
+    path: String,
+    source: io::Error,
+}
+
+/// 是否为连接 Unix 域套接字失败的错误
+pub(super) fn is_connect_error(err: &reqwest_middleware::Error) -> bool {
+    match err {
+        reqwest_middleware::Error::Middleware(err) => err.downcast_ref::<ConnectError>().is_some(),
+        reqwest_middleware::Error::Reqwest(_) => false,
+    }
+}
+
+// 构造请求参数错误
+fn invalid_input(message: String) -> reqwest_middleware::Error {
+    reqwest_middleware::Error::middleware(io::Error::new(io::ErrorKind::InvalidInput, message))
+}
+
+/// 拆分 Unix 域套接字 URL，返回套接字路径和 HTTP 路径（含查询参数）
+pub(crate) fn split_url(url: &Url) -> Option<(&str, String)> {
+    if url.scheme() != unix_socket::SCHEME || url.host_str().is_some_and(|host| !host.is_empty()) {
+        return None;
+    }
+
+    let (socket_path, http_path) = match url.path().split_once(unix_socket::PATH_SEPARATOR) {
+        Some((socket_path, http_path)) => (socket_path, http_path),
+        None => (url.path(), "/"),
+    };
+    if socket_path.is_empty() || socket_path == "/" || !http_path.starts_with('/') {
+        return None;
+    }
+
+    let path_and_query = match url.query() {
+        Some(query) => format!("{}?{}", http_path, query),
+        None => http_path.to_string(),
+    };
+    Some((socket_path, path_and_query))
+}
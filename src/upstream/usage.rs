@@ -0,0 +1,195 @@
+use super::context::RequestContext;
+use crate::{
+    config::ModelPriceConfig,
+    error::AppError,
+    metrics::METRICS,
+    r#const::{http_headers::content_types, upstream_labels},
+    server::TokenCharge,
+    translate::sse::SseDecoder,
+    usage::USAGE,
+};
+use futures_util::StreamExt;
+use reqwest::{header::CONTENT_TYPE, Response};
+use serde_json::Value;
+
+// 用量字段名称：OpenAI、Anthropic、Gemini
+const PROMPT_FIELDS: [&str; 3] = ["prompt_tokens", "input_tokens", "promptTokenCount"];
+const COMPLETION_FIELDS: [&str; 3] = ["completion_tokens", "output_tokens", "candidatesTokenCount"];
+
+// 记录上游响应中的 token 用量和费用，请求上下文中有客户端标识时同时累计客户端的费用
+// JSON 响应读取完整响应体后解析，事件流响应在转发过程中逐个事件解析（OpenAI 需要客户端开启
+// stream_options.include_usage），事件流结束后记录，其他响应原样返回
+pub(super) async fn track_usage(
+    response: Response,
+    group: &str,
+    upstream: &str,
+    pricing: &[ModelPriceConfig],
+    context: &RequestContext,
+) -> Result<Response, AppError> {
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let is_event_stream = content_type.contains(content_types::EVENT_STREAM);
+    if !is_event_stream && !content_type.contains(content_types::JSON) {
+        return Ok(response);
+    }
+
+    let status = response.status();
+    let version = response.version();
+    let headers = response.headers().clone();
+    let mut tracker = UsageTracker::new(group, upstream, pricing, context);
+    let body = if is_event_stream {
+        let mut decoder = SseDecoder::default();
+        // 跟踪器随响应流释放，此时记录用量
+        let stream = response.bytes_stream().map(move |chunk| {
+            if let Ok(chunk) = &chunk {
+                for event in decoder.feed(chunk) {
+                    if let Ok(data) = serde_json::from_str::<Value>(&event.data) {
+                        tracker.observe(&data);
+                    }
+                }
+            }
+            chunk
+        });
+        reqwest::Body::wrap_stream(stream)
+    } else {
+        let bytes = response.bytes().await?;
+        if let Ok(data) = serde_json::from_slice::<Value>(&bytes) {
+            tracker.observe(&data);
+        }
+        tracker.finish();
+        reqwest::Body::from(bytes)
+    };
+
+    let mut tracked = hyper::Response::new(body);
+    *tracked.status_mut() = status;
+    *tracked.version_mut() = version;
+    *tracked.headers_mut() = headers;
+    Ok(Response::from(tracked))
+}
+
+// 单个响应的用量记录状态
+struct UsageTracker {
+    // 上游组名称
+    group: String,
+    // 上游名称
+    upstream: String,
+    // 上游的模型价格表
+    pricing: Vec<ModelPriceConfig>,
+    // 转发服务名称和客户端标识
+    client: Option<(String, String)>,
+    // 令牌速率限制扣除的 token
+    token_charge: Option<TokenCharge>,
+    // 模型名称，事件流中只有部分事件携带
+    model: Option<String>,
+    // 提示词 token 数
+    prompt_tokens: u64,
+    // 生成内容 token 数
+    completion_tokens: u64,
+}
+
+impl UsageTracker {
+    fn new(
+        group: &str,
+        upstream: &str,
+        pricing: &[ModelPriceConfig],
+        context: &RequestContext,
+    ) -> Self {
+        Self {
+            group: group.to_string(),
+            upstream: upstream.to_string(),
+            pricing: pricing.to_vec(),
+            client: context
+                .client_id
+                .clone()
+                .map(|client| (context.forward_name.clone(), client)),
+            token_charge: context.token_charge.clone(),
+            model: None,
+            prompt_tokens: 0,
+            completion_tokens: 0,
+        }
+    }
+
+    // 解析响应体或事件数据中的用量
+    // 事件流中的用量为累计值（Gemini 每个事件都携带），只保留最新的值
+    fn observe(&mut self, data: &Value) {
+        // Anthropic 事件流：message_start 携带提示词用量，message_delta 携带生成用量
+        let (message, has_prompt, has_completion) = match data["type"].as_str() {
+            Some("message_start") => (&data["message"], true, false),
+            Some("message_delta") => (data, false, true),
+            _ => (data, true, true),
+        };
+
+        if let Some(model) = ["model", "modelVersion"]
+            .iter()
+            .find_map(|field| message[field].as_str())
+        {
+            self.model = Some(model.to_string());
+        }
+
+        let usage = match (&message["usage"], &message["usageMetadata"]) {
+            (Value::Object(_), _) => &message["usage"],
+            (_, Value::Object(_)) => &message["usageMetadata"],
+            _ => return,
+        };
+        let tokens = |fields: [&str; 3]| fields.iter().find_map(|field| usage[field].as_u64());
+        if has_prompt {
+            if let Some(prompt_tokens) = tokens(PROMPT_FIELDS) {
+                self.prompt_tokens = prompt_tokens;
+            }
+        }
+        if has_completion {
+            if let Some(completion_tokens) = tokens(COMPLETION_FIELDS) {
+                self.completion_tokens = completion_tokens;
+            }
+        }
+    }
+
+    // 记录用量指标、费用和按天汇总的用量，按实际用量修正令牌速率限制扣除的 token，没有用量时不记录
+    fn finish(&mut self) {
+        let (prompt_tokens, completion_tokens) = (
+            std::mem::take(&mut self.prompt_tokens),
+            std::mem::take(&mut self.completion_tokens),
+        );
+        if prompt_tokens == 0 && completion_tokens == 0 {
+            return;
+        }
+        if let Some(charge) = self.token_charge.take() {
+            charge.settle(prompt_tokens + completion_tokens);
+        }
+
+        let model = self.model.as_deref().unwrap_or(upstream_labels::UNKNOWN);
+        METRICS.record_tokens(
+            &self.group,
+            &self.upstream,
+            model,
+            prompt_tokens,
+            completion_tokens,
+        );
+        let cost = ModelPriceConfig::find(&self.pricing, model)
+            .map(|price| price.cost(prompt_tokens, completion_tokens))
+            .unwrap_or_default();
+        if cost > 0.0 {
+            METRICS.record_cost(&self.group, &self.upstream, model, cost);
+            if let Some((forward, client)) = &self.client {
+                USAGE.record_client(forward, client, cost);
+            }
+        }
+        USAGE.record(
+            &self.group,
+            &self.upstream,
+            model,
+            prompt_tokens,
+            completion_tokens,
+            cost,
+        );
+    }
+}
+
+impl Drop for UsageTracker {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
@@ -0,0 +1,111 @@
+use futures_util::future::join_all;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_graceful_shutdown::{IntoSubsystem, SubsystemHandle};
+use tracing::{debug, info, warn};
+
+use super::UpstreamManager;
+use crate::{
+    error::AppError,
+    events::{SystemEvent, EVENTS},
+    r#const::breaker_state_labels,
+};
+
+/// 上游预热
+///
+/// 开始服务前向配置了预热请求的上游发送预热请求，预先建立连接并初始化响应时间感知负载均衡器的指标；
+/// 之后上游的熔断器恢复（关闭）时再次预热该上游
+pub struct UpstreamWarmup {
+    // 上游管理器
+    manager: Arc<UpstreamManager>,
+}
+
+impl UpstreamWarmup {
+    /// 创建上游预热，没有上游配置预热请求时返回 None
+    pub fn new(manager: Arc<UpstreamManager>) -> Option<Self> {
+        if manager.warmup_targets().is_empty() {
+            return None;
+        }
+        Some(Self { manager })
+    }
+
+    /// 预热所有配置了预热请求的上游，返回成功的请求数
+    pub async fn warm_up_all(&self) -> usize {
+        let targets = self.manager.warmup_targets();
+        let succeeded: usize = join_all(
+            targets
+                .iter()
+                .map(|(group, upstream)| self.warm_up(group, upstream)),
+        )
+        .await
+        .into_iter()
+        .sum();
+
+        info!(
+            "Warmed up {} upstreams with {} successful requests",
+            targets.len(),
+            succeeded
+        );
+        succeeded
+    }
+
+    // 预热上游组中的一个上游，返回成功的请求数
+    async fn warm_up(&self, group: &str, upstream: &str) -> usize {
+        match self.manager.warm_up(group, upstream).await {
+            Ok(succeeded) => {
+                debug!(
+                    "Warm-up of upstream '{}' in group '{}' completed with {} successful requests",
+                    upstream, group, succeeded
+                );
+                succeeded
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to warm up upstream '{}' in group '{}': {}",
+                    upstream, group, e
+                );
+                0
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl IntoSubsystem<AppError> for UpstreamWarmup {
+    async fn run(self, subsys: SubsystemHandle) -> Result<(), AppError> {
+        let mut events = EVENTS.subscribe_system();
+
+        loop {
+            let event = tokio::select! {
+                event = events.recv() => event,
+                _ = subsys.on_shutdown_requested() => break,
+            };
+            match event {
+                Ok(SystemEvent::BreakerStateChanged {
+                    group,
+                    upstream,
+                    to,
+                    ..
+                }) if to == breaker_state_labels::CLOSED => {
+                    info!(
+                        "Circuit breaker of upstream '{}' in group '{}' recovered, warming up",
+                        upstream, group
+                    );
+                    tokio::select! {
+                        _ = self.warm_up(&group, &upstream) => {}
+                        _ = subsys.on_shutdown_requested() => break,
+                    }
+                }
+                Ok(_) => {}
+                // 错过的事件无法补发，继续处理之后的事件
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Upstream warm-up skipped {} system events", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+
+        debug!("Upstream warm-up stopped");
+        Ok(())
+    }
+}
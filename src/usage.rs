@@ -0,0 +1,222 @@
+use crate::{events::unix_millis, r#const::usage_limits};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use utoipa::ToSchema;
+
+// 一天的秒数
+const SECONDS_PER_DAY: u64 = 86_400;
+// 一天的毫秒数
+const MILLIS_PER_DAY: u64 = SECONDS_PER_DAY * 1000;
+
+// 单个上游和模型的用量汇总
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct UsageEntry {
+    // 上游组名称
+    pub group: String,
+    // 上游名称
+    pub upstream: String,
+    // 模型名称
+    pub model: String,
+    // 返回了用量的响应数
+    pub requests: u64,
+    // 提示词 token 数
+    pub prompt_tokens: u64,
+    // 生成内容 token 数
+    pub completion_tokens: u64,
+    // 费用（美元），未配置价格的模型为 0
+    pub cost_usd: f64,
+}
+
+// 单日用量汇总
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DailyUsage {
+    // 日期（UTC），格式为 YYYY-MM-DD
+    pub date: String,
+    // 当日提示词 token 总数
+    pub prompt_tokens: u64,
+    // 当日生成内容 token 总数
+    pub completion_tokens: u64,
+    // 当日费用总计（美元）
+    pub cost_usd: f64,
+    // 按上游组、上游和模型排序的用量明细
+    pub entries: Vec<UsageEntry>,
+}
+
+// 客户端在当前时间窗口内的费用
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ClientSpend {
+    // 当日费用（美元）
+    pub daily: f64,
+    // 当月费用（美元）
+    pub monthly: f64,
+}
+
+// 客户端费用记录，时间窗口变化时清零
+#[derive(Debug, Default)]
+struct ClientWindow {
+    // 自 Unix 纪元起的天数
+    day: u64,
+    // 自公元 0 年起的月数
+    month: u64,
+    // 当前窗口内的费用
+    spend: ClientSpend,
+}
+
+// 用量汇总键：上游组、上游、模型
+type UsageKey = (String, String, String);
+
+// 客户端费用键：转发服务、客户端标识
+type ClientKey = (String, String);
+
+// 按天汇总的用量记录，只保存在内存中，保留最近的若干天
+#[derive(Default)]
+pub struct UsageLedger {
+    // 自 Unix 纪元起的天数到当日用量的映射
+    days: Mutex<BTreeMap<u64, HashMap<UsageKey, UsageEntry>>>,
+    // 客户端在当日和当月的费用
+    clients: Mutex<HashMap<ClientKey, ClientWindow>>,
+}
+
+impl UsageLedger {
+    // 记录一次响应的用量
+    pub fn record(
+        &self,
+        group: &str,
+        upstream: &str,
+        model: &str,
+        prompt_tokens: u64,
+        completion_tokens: u64,
+        cost: f64,
+    ) {
+        let day = unix_millis() / MILLIS_PER_DAY;
+        let mut days = self.days.lock();
+        let entry = days
+            .entry(day)
+            .or_default()
+            .entry((group.to_string(), upstream.to_string(), model.to_string()))
+            .or_insert_with(|| UsageEntry {
+                group: group.to_string(),
+                upstream: upstream.to_string(),
+                model: model.to_string(),
+                ..Default::default()
+            });
+        entry.requests += 1;
+        entry.prompt_tokens += prompt_tokens;
+        entry.completion_tokens += completion_tokens;
+        entry.cost_usd += cost;
+
+        // 清理超出保留天数的记录
+        while days.len() > usage_limits::RETENTION_DAYS {
+            days.pop_first();
+        }
+    }
+
+    // 累计客户端的费用
+    pub fn record_client(&self, forward: &str, client: &str, cost: f64) {
+        let (day, month) = current_window();
+        let mut clients = self.clients.lock();
+        let window = clients
+            .entry((forward.to_string(), client.to_string()))
+            .or_default();
+        window.roll(day, month);
+        window.spend.daily += cost;
+        window.spend.monthly += cost;
+    }
+
+    // 获取客户端在当日和当月的费用
+    pub fn client_spend(&self, forward: &str, client: &str) -> ClientSpend {
+        let (day, month) = current_window();
+        let mut clients = self.clients.lock();
+        match clients.get_mut(&(forward.to_string(), client.to_string())) {
+            Some(window) => {
+                window.roll(day, month);
+                window.spend
+            }
+            None => ClientSpend::default(),
+        }
+    }
+
+    // 获取最近若干天的用量汇总，最新的在前
+    pub fn daily(&self, limit: usize) -> Vec<DailyUsage> {
+        let days = self.days.lock();
+        days.iter()
+            .rev()
+            .take(limit)
+            .map(|(day, entries)| {
+                let mut entries: Vec<UsageEntry> = entries.values().cloned().collect();
+                entries.sort_by(|a, b| {
+                    (&a.group, &a.upstream, &a.model).cmp(&(&b.group, &b.upstream, &b.model))
+                });
+                DailyUsage {
+                    date: format_date(*day),
+                    prompt_tokens: entries.iter().map(|e| e.prompt_tokens).sum(),
+                    completion_tokens: entries.iter().map(|e| e.completion_tokens).sum(),
+                    cost_usd: entries.iter().map(|e| e.cost_usd).sum(),
+                    entries,
+                }
+            })
+            .collect()
+    }
+}
+
+impl ClientWindow {
+    // 进入新的日或月时清零对应的费用
+    fn roll(&mut self, day: u64, month: u64) {
+        if self.day != day {
+            self.day = day;
+            self.spend.daily = 0.0;
+        }
+        if self.month != month {
+            self.month = month;
+            self.spend.monthly = 0.0;
+        }
+    }
+}
+
+// 当前的天数（自 Unix 纪元起）和月数（自公元 0 年起），均按 UTC 计算
+fn current_window() -> (u64, u64) {
+    let day = unix_millis() / MILLIS_PER_DAY;
+    let (year, month, _) = civil_date(day);
+    (day, year * 12 + month - 1)
+}
+
+// 距离下一个 UTC 自然日和自然月开始的秒数
+pub fn seconds_until_reset() -> (u64, u64) {
+    let now = unix_millis() / 1000;
+    let day = now / SECONDS_PER_DAY;
+    let month = civil_date(day).1;
+    // 下个月的第一天，一个月最多 31 天
+    let next_month = (day + 1..=day + 31)
+        .find(|d| civil_date(*d).1 != month)
+        .unwrap_or(day + 1);
+    (
+        (day + 1) * SECONDS_PER_DAY - now,
+        next_month * SECONDS_PER_DAY - now,
+    )
+}
+
+// 将自 Unix 纪元起的天数格式化为 YYYY-MM-DD（公历）
+fn format_date(day: u64) -> String {
+    let (y, m, d) = civil_date(day);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+// 将自 Unix 纪元起的天数转换为公历的年、月、日
+pub(crate) fn civil_date(day: u64) -> (u64, u64, u64) {
+    // 以 0000-03-01 为起点按 400 年周期计算，闰日位于每年末尾
+    let z = day + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + u64::from(m <= 2);
+    (y, m, d)
+}
+
+// 全局用量记录
+pub static USAGE: Lazy<UsageLedger> = Lazy::new(UsageLedger::default);
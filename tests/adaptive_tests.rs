@@ -0,0 +1,96 @@
+use llmproxy::{
+    adaptive::AdaptiveLimiter,
+    balancer::{is_upstream_healthy, ManagedUpstream},
+    config::{AdaptiveConfig, UpstreamRef},
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+// 辅助函数：创建测试用的自适应并发配置
+fn create_test_adaptive_config(initial: u32, min: u32, max: u32) -> AdaptiveConfig {
+    AdaptiveConfig {
+        initial,
+        min,
+        max,
+        latency_tolerance: 2.0,
+        backoff: 0.5,
+    }
+}
+
+#[test]
+fn test_adaptive_limit_shrinks_on_overload() {
+    let limiter = AdaptiveLimiter::new("shrink_upstream", &create_test_adaptive_config(8, 2, 16));
+    assert_eq!(limiter.limit(), 8);
+
+    // 上游过载时乘性收缩
+    limiter.start().finish(true);
+    assert_eq!(limiter.limit(), 4);
+    limiter.start().finish(true);
+    assert_eq!(limiter.limit(), 2);
+
+    // 不低于最小值
+    limiter.start().finish(true);
+    assert_eq!(limiter.limit(), 2);
+}
+
+#[test]
+fn test_adaptive_limit_grows_when_healthy() {
+    let limiter = AdaptiveLimiter::new("grow_upstream", &create_test_adaptive_config(2, 1, 3));
+
+    // 在途请求不足上限的一半时不增长
+    for _ in 0..10 {
+        limiter.start().finish(false);
+    }
+    assert_eq!(limiter.limit(), 2);
+
+    // 在途请求接近上限时加性增长，不超过最大值
+    for _ in 0..20 {
+        let first = limiter.start();
+        let second = limiter.start();
+        second.finish(false);
+        first.finish(false);
+    }
+    assert_eq!(limiter.limit(), 3);
+    assert_eq!(limiter.in_flight(), 0);
+}
+
+#[test]
+fn test_adaptive_limit_shrinks_on_latency() {
+    let limiter = AdaptiveLimiter::new("latency_upstream", &create_test_adaptive_config(8, 1, 16));
+
+    // 第一个请求建立基线延迟
+    limiter.start().finish(false);
+    assert_eq!(limiter.limit(), 8);
+
+    // 延迟超过基线的容忍倍数时收缩
+    let slow = limiter.start();
+    std::thread::sleep(Duration::from_millis(50));
+    slow.finish(false);
+    assert_eq!(limiter.limit(), 4);
+}
+
+#[test]
+fn test_adaptive_saturated_upstream_is_skipped() {
+    let limiter = AdaptiveLimiter::new("saturated_upstream", &create_test_adaptive_config(2, 1, 4));
+    let upstream = ManagedUpstream {
+        upstream_ref: Arc::new(UpstreamRef {
+            name: "saturated_upstream".to_string(),
+            weight: 1,
+        }),
+        breaker: None,
+        drained: Default::default(),
+        adaptive: Some(limiter.clone()),
+        endpoint: None,
+    };
+    assert!(is_upstream_healthy(&upstream));
+
+    // 在途请求达到上限时不再被选中，请求结束后恢复
+    let first = limiter.start();
+    let second = limiter.start();
+    assert!(limiter.is_saturated());
+    assert!(!is_upstream_healthy(&upstream));
+    drop(first);
+    assert!(is_upstream_healthy(&upstream));
+    drop(second);
+    assert_eq!(limiter.in_flight(), 0);
+}
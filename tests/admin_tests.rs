@@ -0,0 +1,568 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Request, StatusCode},
+    Router,
+};
+use llmproxy::{
+    admin::{AdminServer, MetricsServer},
+    alert::AlertEvaluator,
+    args::HealthcheckArgs,
+    audit::AuditLog,
+    config::{
+        AdminConfig, AlertMetric, AlertRuleConfig, AlertWebhookConfig, AlertsConfig, Config,
+        HttpServerConfig, MetricsConfig, MetricsExportConfig, MetricsExportProtocol,
+    },
+    events::{SystemEvent, EVENTS},
+    export::MetricsExporter,
+    healthcheck,
+    metrics::METRICS,
+    reload::ConfigReloader,
+};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{net::UdpSocket, sync::RwLock};
+use tower::ServiceExt;
+use wiremock::{
+    matchers::{header as header_matcher, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+// 创建管理服务路由
+async fn admin_router(admin: AdminConfig) -> Router {
+    let config = Arc::new(RwLock::new(Config {
+        http_server: Some(HttpServerConfig {
+            forwards: vec![],
+            admin,
+            load_shedding: None,
+        }),
+        upstreams: vec![],
+        upstream_groups: vec![],
+        models: vec![],
+    }));
+    let forward_states = Arc::new(HashMap::new());
+    let reloader = Arc::new(ConfigReloader::new(
+        "non-existent-config.yaml",
+        config.clone(),
+        forward_states.clone(),
+    ));
+
+    AdminServer::new(
+        false,
+        "127.0.0.1:0".parse().unwrap(),
+        config,
+        forward_states,
+        Arc::new(AuditLog::default()),
+        reloader,
+    )
+    .router()
+    .await
+}
+
+async fn get(router: Router, path: &str) -> axum::response::Response {
+    let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+    router.oneshot(request).await.unwrap()
+}
+
+async fn get_with_token(router: Router, path: &str, token: &str) -> axum::response::Response {
+    let request = Request::builder()
+        .uri(path)
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    router.oneshot(request).await.unwrap()
+}
+
+async fn body_text(response: axum::response::Response) -> String {
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn test_dashboard_served() {
+    let response = get(admin_router(AdminConfig::default()).await, "/dashboard").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(header::CONTENT_TYPE).unwrap(),
+        "text/html; charset=utf-8"
+    );
+
+    let html = body_text(response).await;
+    assert!(html.contains("<title>LLMProxy Dashboard</title>"));
+    // 面板通过 API v1 获取数据
+    assert!(html.contains("/api/v1"));
+    assert!(html.contains(r#"const METRICS_PATH = "/metrics";"#));
+}
+
+#[tokio::test]
+async fn test_dashboard_disabled() {
+    let router = admin_router(AdminConfig {
+        dashboard: false,
+        ..Default::default()
+    })
+    .await;
+    let response = get(router.clone(), "/dashboard").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // 其他管理路由不受影响
+    let response = get(router, "/health").await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_metrics_default_path() {
+    let response = get(admin_router(AdminConfig::default()).await, "/metrics").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(header::CONTENT_TYPE).unwrap(),
+        "text/plain; charset=utf-8"
+    );
+}
+
+#[tokio::test]
+async fn test_metrics_custom_path_with_token() {
+    let router = admin_router(AdminConfig {
+        metrics: MetricsConfig {
+            path: "/internal/metrics".to_string(),
+            token: Some("scrape-token".to_string()),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    // 默认路径不再提供指标
+    let response = get(router.clone(), "/metrics").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = get(router.clone(), "/internal/metrics").await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = get_with_token(router.clone(), "/internal/metrics", "wrong-token").await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = get_with_token(router.clone(), "/internal/metrics", "scrape-token").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // 指标令牌不影响其他管理路由
+    let response = get(router.clone(), "/health").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // 管理面板使用配置的指标路径
+    let html = body_text(get(router, "/dashboard").await).await;
+    assert!(html.contains(r#"const METRICS_PATH = "/internal/metrics";"#));
+}
+
+#[tokio::test]
+async fn test_metrics_separate_listener() {
+    let metrics = MetricsConfig {
+        port: Some(9100),
+        ..Default::default()
+    };
+    let router = admin_router(AdminConfig {
+        metrics: metrics.clone(),
+        ..Default::default()
+    })
+    .await;
+
+    // 管理服务端口不再提供指标
+    let response = get(router.clone(), "/metrics").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // 管理面板不显示请求速率
+    let html = body_text(get(router, "/dashboard").await).await;
+    assert!(html.contains(r#"const METRICS_PATH = "";"#));
+
+    // 独立的指标服务只提供指标
+    let server = MetricsServer::new("127.0.0.1:0".parse().unwrap(), metrics);
+    let response = get(server.router(), "/metrics").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = get(server.router(), "/health").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+// 创建指标推送配置
+fn export_config(
+    protocol: MetricsExportProtocol,
+    endpoint: String,
+    prefix: &str,
+) -> MetricsExportConfig {
+    MetricsExportConfig {
+        protocol,
+        endpoint,
+        interval: 15,
+        prefix: prefix.to_string(),
+        headers: BTreeMap::new(),
+    }
+}
+
+// 接收一次推送的全部 StatsD 指标行
+async fn receive_lines(socket: &UdpSocket) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut buf = [0u8; 2048];
+    while let Ok(Ok(len)) =
+        tokio::time::timeout(Duration::from_millis(200), socket.recv(&mut buf)).await
+    {
+        assert!(len <= 1432, "packet of {} bytes exceeds the MTU limit", len);
+        let packet = String::from_utf8(buf[..len].to_vec()).unwrap();
+        lines.extend(packet.lines().map(str::to_string));
+    }
+    lines
+}
+
+#[tokio::test]
+async fn test_metrics_export_statsd() {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let exporter = MetricsExporter::new(&export_config(
+        MetricsExportProtocol::Statsd,
+        socket.local_addr().unwrap().to_string(),
+        "app.",
+    ))
+    .unwrap();
+    let records = METRICS
+        .access_log_records_total()
+        .with_label_values(&["export_statsd_forward", "written"]);
+    let active = METRICS
+        .websocket_active_connections()
+        .with_label_values(&["export_statsd_forward", "export_statsd_group"]);
+
+    // 标签值按顺序追加到指标名称后，首次推送计数器的当前值
+    records.inc_by(3);
+    active.set(-2);
+    exporter.export().await.unwrap();
+    let lines = receive_lines(&socket).await;
+    assert!(lines.contains(
+        &"app.llmproxy_access_log_records_total.export_statsd_forward.written:3|c".to_string()
+    ));
+    // 负的仪表值先归零再设置
+    let gauge =
+        "app.llmproxy_websocket_active_connections.export_statsd_forward.export_statsd_group";
+    let position = lines
+        .iter()
+        .position(|line| *line == format!("{}:0|g", gauge))
+        .unwrap();
+    assert_eq!(lines[position + 1], format!("{}:-2|g", gauge));
+
+    // 之后只推送两次推送之间的增量，没有变化的计数器不推送
+    records.inc_by(2);
+    exporter.export().await.unwrap();
+    let lines = receive_lines(&socket).await;
+    assert!(lines.contains(
+        &"app.llmproxy_access_log_records_total.export_statsd_forward.written:2|c".to_string()
+    ));
+    exporter.export().await.unwrap();
+    let lines = receive_lines(&socket).await;
+    assert!(!lines
+        .iter()
+        .any(|line| line.contains("export_statsd_forward.written")));
+    // 仪表每次都推送当前值
+    assert!(lines.contains(&format!("{}:-2|g", gauge)));
+}
+
+#[tokio::test]
+async fn test_metrics_export_dogstatsd() {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let exporter = MetricsExporter::new(&export_config(
+        MetricsExportProtocol::Dogstatsd,
+        socket.local_addr().unwrap().to_string(),
+        "",
+    ))
+    .unwrap();
+    METRICS
+        .access_log_records_total()
+        .with_label_values(&["export_dogstatsd_forward", "dropped"])
+        .inc_by(5);
+
+    // 标签作为 tag 发送
+    exporter.export().await.unwrap();
+    let lines = receive_lines(&socket).await;
+    assert!(lines.contains(
+        &"llmproxy_access_log_records_total:5|c|#forward:export_dogstatsd_forward,result:dropped"
+            .to_string()
+    ));
+}
+
+#[tokio::test]
+async fn test_metrics_export_otlp() {
+    let collector = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/metrics"))
+        .and(header_matcher("authorization", "Bearer otlp-token"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&collector)
+        .await;
+    let mut config = export_config(
+        MetricsExportProtocol::Otlp,
+        format!("{}/v1/metrics", collector.uri()),
+        "app_",
+    );
+    config
+        .headers
+        .insert("authorization".to_string(), "Bearer otlp-token".to_string());
+    let exporter = MetricsExporter::new(&config).unwrap();
+
+    METRICS
+        .access_log_records_total()
+        .with_label_values(&["export_otlp_forward", "written"])
+        .inc_by(4);
+    METRICS
+        .websocket_duration_seconds()
+        .with_label_values(&["export_otlp_forward", "export_otlp_group"])
+        .observe(0.5);
+    exporter.export().await.unwrap();
+
+    let requests = collector.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 1);
+    let body: Value = serde_json::from_slice(&requests[0].body).unwrap();
+    let metrics = body["resourceMetrics"][0]["scopeMetrics"][0]["metrics"]
+        .as_array()
+        .unwrap();
+    let metric = |name: &str| {
+        metrics
+            .iter()
+            .find(|metric| metric["name"] == name)
+            .unwrap_or_else(|| panic!("metric {} not exported", name))
+    };
+    let point = |data: &Value| {
+        data["dataPoints"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|point| {
+                point["attributes"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .any(|a| a["value"]["stringValue"] == "export_otlp_forward")
+            })
+            .unwrap()
+            .clone()
+    };
+
+    // 计数器按单调累计和推送
+    let sum = &metric("app_llmproxy_access_log_records_total")["sum"];
+    assert_eq!(sum["isMonotonic"], true);
+    assert_eq!(sum["aggregationTemporality"], 2);
+    assert_eq!(point(sum)["asDouble"], 4.0);
+
+    // 直方图的桶计数比边界多一个 +Inf 桶
+    let histogram = &metric("app_llmproxy_websocket_duration_seconds")["histogram"];
+    let point = point(histogram);
+    assert_eq!(point["count"], "1");
+    assert_eq!(
+        point["bucketCounts"].as_array().unwrap().len(),
+        point["explicitBounds"].as_array().unwrap().len() + 1
+    );
+    let total: u64 = point["bucketCounts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|count| count.as_str().unwrap().parse::<u64>().unwrap())
+        .sum();
+    assert_eq!(total, 1);
+
+    // 接收端返回错误状态码时推送失败
+    let exporter = MetricsExporter::new(&export_config(
+        MetricsExportProtocol::Otlp,
+        format!("{}/v1/unknown", collector.uri()),
+        "",
+    ))
+    .unwrap();
+    assert!(exporter.export().await.is_err());
+}
+
+// 创建只评估指定上游组的告警规则
+fn alert_rule(name: &str, metric: AlertMetric, group: &str, threshold: f64) -> AlertRuleConfig {
+    AlertRuleConfig {
+        name: name.to_string(),
+        metric,
+        group: Some(group.to_string()),
+        threshold,
+        window: 60,
+        quantile: 0.95,
+        min_requests: 4,
+    }
+}
+
+// 创建告警配置
+fn alerts_config(rules: Vec<AlertRuleConfig>, webhooks: Vec<AlertWebhookConfig>) -> AlertsConfig {
+    AlertsConfig {
+        interval: 15,
+        webhooks,
+        rules,
+    }
+}
+
+#[tokio::test]
+async fn test_alert_error_rate() {
+    let group = "alert_error_group";
+    let evaluator = AlertEvaluator::new(&alerts_config(
+        vec![alert_rule("errors", AlertMetric::ErrorRate, group, 0.2)],
+        vec![],
+    ))
+    .unwrap();
+    let requests = |class: &str, count: u64| {
+        METRICS
+            .upstream_requests_total()
+            .with_label_values(&[group, "alert_upstream", class, ""])
+            .inc_by(count);
+    };
+    let start = Instant::now();
+
+    // 请求数不足时不评估
+    requests("5xx", 2);
+    assert!(evaluator.evaluate(start).is_empty());
+
+    // 窗口内 5 个请求中 2 个为 5xx，错误率超过阈值后触发告警，之后不重复通知
+    requests("2xx", 3);
+    requests("error", 2);
+    let notifications = evaluator.evaluate(start + Duration::from_secs(10));
+    assert_eq!(notifications.len(), 1);
+    assert_eq!(notifications[0].status, "firing");
+    assert_eq!(notifications[0].rule, "errors");
+    assert_eq!(notifications[0].metric, "error_rate");
+    assert_eq!(notifications[0].group, group);
+    assert_eq!(notifications[0].value, Some(0.4));
+    assert!(evaluator
+        .evaluate(start + Duration::from_secs(20))
+        .is_empty());
+
+    // 统计窗口滑过错误请求后恢复
+    requests("2xx", 10);
+    let notifications = evaluator.evaluate(start + Duration::from_secs(80));
+    assert_eq!(notifications.len(), 1);
+    assert_eq!(notifications[0].status, "resolved");
+    assert_eq!(notifications[0].value, Some(0.0));
+}
+
+#[tokio::test]
+async fn test_alert_latency() {
+    let group = "alert_latency_group";
+    let evaluator = AlertEvaluator::new(&alerts_config(
+        vec![alert_rule("slow", AlertMetric::Latency, group, 30.0)],
+        vec![],
+    ))
+    .unwrap();
+    let start = Instant::now();
+    assert!(evaluator.evaluate(start).is_empty());
+
+    // 一半请求耗时 40 秒，p95 在 (30, 60] 分桶内插值为 57 秒
+    let durations = METRICS
+        .upstream_duration_seconds()
+        .with_label_values(&[group, "alert_upstream"]);
+    for _ in 0..10 {
+        durations.observe(0.05);
+        durations.observe(40.0);
+    }
+    let notifications = evaluator.evaluate(start + Duration::from_secs(10));
+    assert_eq!(notifications.len(), 1);
+    assert_eq!(notifications[0].status, "firing");
+    assert_eq!(notifications[0].metric, "latency");
+    assert_eq!(notifications[0].value, Some(57.0));
+
+    // 统计窗口内没有请求时恢复
+    let notifications = evaluator.evaluate(start + Duration::from_secs(100));
+    assert_eq!(notifications.len(), 1);
+    assert_eq!(notifications[0].status, "resolved");
+    assert_eq!(notifications[0].value, None);
+}
+
+#[tokio::test]
+async fn test_alert_notify() {
+    let receiver = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/alerts"))
+        .and(header_matcher("authorization", "Bearer hook-token"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&receiver)
+        .await;
+    let group = "alert_notify_group";
+    let evaluator = AlertEvaluator::new(&alerts_config(
+        vec![alert_rule("notify", AlertMetric::ErrorRate, group, 0.5)],
+        vec![AlertWebhookConfig {
+            url: format!("{}/alerts", receiver.uri()),
+            headers: BTreeMap::from([(
+                "authorization".to_string(),
+                "Bearer hook-token".to_string(),
+            )]),
+        }],
+    ))
+    .unwrap();
+    let mut events = EVENTS.subscribe_system();
+
+    METRICS
+        .upstream_requests_total()
+        .with_label_values(&[group, "alert_upstream", "5xx", ""])
+        .inc_by(4);
+    let notifications = evaluator.evaluate(Instant::now());
+    assert_eq!(notifications.len(), 1);
+    evaluator.notify(&notifications).await;
+
+    // 告警以 JSON 格式发送给 Webhook
+    let requests = receiver.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 1);
+    let body: Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["status"], "firing");
+    assert_eq!(body["rule"], "notify");
+    assert_eq!(body["metric"], "error_rate");
+    assert_eq!(body["group"], group);
+    assert_eq!(body["value"], 1.0);
+    assert_eq!(body["threshold"], 0.5);
+    assert_eq!(body["window"], 60);
+
+    // 同时发布系统事件
+    loop {
+        if let SystemEvent::AlertFiring {
+            rule, group, value, ..
+        } = events.recv().await.unwrap()
+        {
+            if rule == "notify" {
+                assert_eq!(group, "alert_notify_group");
+                assert_eq!(value, 1.0);
+                break;
+            }
+        }
+    }
+}
+
+/// 测试 healthcheck 子命令按管理服务健康检查端点的状态码返回结果
+#[tokio::test]
+async fn test_healthcheck_command() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("OK"))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&mock_server)
+        .await;
+
+    let args = |admin_url: String| HealthcheckArgs {
+        admin_url,
+        timeout: 1,
+    };
+    assert!(healthcheck::run(&args(format!("{}/", mock_server.uri())))
+        .await
+        .is_ok());
+    assert!(healthcheck::run(&args(mock_server.uri())).await.is_err());
+
+    // 管理服务不可达
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    assert!(
+        healthcheck::run(&args(format!("http://127.0.0.1:{}", port)))
+            .await
+            .is_err()
+    );
+}
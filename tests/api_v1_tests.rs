@@ -6,11 +6,27 @@ mod api_v1_tests {
     pub mod helpers;
     // 测试模块
     #[cfg(test)]
+    mod access_log;
+    #[cfg(test)]
+    mod audit;
+    #[cfg(test)]
+    mod events;
+    #[cfg(test)]
     mod forwards;
     #[cfg(test)]
+    mod reload;
+    #[cfg(test)]
     mod routing;
     #[cfg(test)]
+    mod status;
+    #[cfg(test)]
+    mod support;
+    #[cfg(test)]
     mod upstream_groups;
     #[cfg(test)]
     mod upstreams;
+    #[cfg(test)]
+    mod usage;
+    #[cfg(test)]
+    mod validate;
 }
@@ -0,0 +1,75 @@
+//! Access Log API 测试模块
+use super::helpers::spawn_app;
+use axum::http::{header, StatusCode};
+use futures_util::StreamExt;
+use llmproxy::{
+    events::{AccessEvent, EVENTS},
+    tail::SseParser,
+};
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_access_log_stream_invalid_filter() {
+    let mut app = spawn_app().await;
+
+    let response = app.get("/api/v1/access-log/stream?status=abc").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = app.get("/api/v1/access-log/stream?client=1.2.3.4").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_access_log_stream_filters_events() {
+    let mut app = spawn_app().await;
+
+    let response = app
+        .get("/api/v1/access-log/stream?forward=tail_forward&status=5xx")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()[header::CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .starts_with("text/event-stream"));
+
+    // 订阅已建立，发布一条不匹配和一条匹配的事件
+    EVENTS.publish_access(AccessEvent::new(
+        "tail_forward",
+        "GET",
+        "/v1/models",
+        "default_group",
+        200,
+        Duration::from_millis(5),
+    ));
+    EVENTS.publish_access(
+        AccessEvent::new(
+            "tail_forward",
+            "POST",
+            "/v1/chat/completions",
+            "default_group",
+            502,
+            Duration::from_millis(42),
+        )
+        .with_error("upstream failed"),
+    );
+
+    let mut body = response.into_body().into_data_stream();
+    let mut parser = SseParser::default();
+    let frame = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let chunk = body.next().await.unwrap().unwrap();
+            if let Some(frame) = parser.feed(&chunk).into_iter().next() {
+                return frame;
+            }
+        }
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(frame.event.as_deref(), Some("access"));
+    let event: AccessEvent = serde_json::from_str(&frame.data).unwrap();
+    assert_eq!(event.status, 502);
+    assert_eq!(event.path, "/v1/chat/completions");
+    assert_eq!(event.duration_ms, 42);
+    assert_eq!(event.error.as_deref(), Some("upstream failed"));
+}
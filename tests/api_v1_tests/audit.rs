@@ -0,0 +1,158 @@
+//! Audit API 测试模块
+use super::helpers::spawn_app;
+use axum::{
+    body::{to_bytes, Body},
+    http::{Method, Request, StatusCode},
+};
+use llmproxy::api::v1::models::{AuditPage, ErrorResponse, SuccessResponse};
+use serde_json::json;
+use tower::ServiceExt;
+
+// 辅助函数：获取审计记录分页
+async fn get_audit_page(app: &mut super::helpers::TestApp, query: &str) -> AuditPage {
+    let response = app.get(&format!("/api/v1/audit{}", query)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let success_response: SuccessResponse<AuditPage> = serde_json::from_slice(&body).unwrap();
+    success_response.data.unwrap()
+}
+
+#[tokio::test]
+async fn test_audit_records_successful_mutations() {
+    let mut app = spawn_app().await;
+
+    // 创建上游服务
+    let response = app
+        .post(
+            "/api/v1/upstreams",
+            json!({
+                "name": "audited_upstream",
+                "url": "http://localhost:8080",
+                "auth": { "type": "bearer", "token": "super-secret" }
+            }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    // 更新上游服务权重
+    let response = app
+        .put(
+            "/api/v1/upstreams/audited_upstream",
+            json!({
+                "name": "audited_upstream",
+                "url": "http://localhost:8080",
+                "weight": 5,
+                "auth": { "type": "bearer", "token": "super-secret" }
+            }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // 失败的变更和只读请求不会被审计
+    let response = app.delete("/api/v1/upstreams/missing_upstream").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    app.get("/api/v1/upstreams").await;
+
+    let page = get_audit_page(&mut app, "").await;
+    assert_eq!(page.total, 2);
+    assert_eq!(page.page, 1);
+
+    // 最新的记录在前
+    let update = &page.entries[0];
+    assert_eq!(update.method, "PUT");
+    assert_eq!(update.endpoint, "/api/v1/upstreams/audited_upstream");
+    assert_eq!(update.actor, "anonymous");
+    assert_eq!(update.changes.len(), 1);
+    assert_eq!(update.changes[0].path, "upstreams[audited_upstream].weight");
+    assert_eq!(update.changes[0].before, Some(json!(1)));
+    assert_eq!(update.changes[0].after, Some(json!(5)));
+
+    let create = &page.entries[1];
+    assert_eq!(create.method, "POST");
+    assert!(create.id < update.id);
+    assert_eq!(create.changes[0].path, "upstreams[audited_upstream]");
+    assert!(create.changes[0].before.is_none());
+
+    // 审计记录中不包含令牌明文
+    let serialized = serde_json::to_string(&page).unwrap();
+    assert!(!serialized.contains("super-secret"));
+}
+
+#[tokio::test]
+async fn test_audit_pagination() {
+    let mut app = spawn_app().await;
+
+    for i in 0..3 {
+        let response = app
+            .post(
+                "/api/v1/upstreams",
+                json!({ "name": format!("paged_upstream_{}", i), "url": "http://localhost:8080" }),
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
+    let page = get_audit_page(&mut app, "?page=2&page_size=2").await;
+    assert_eq!(page.total, 3);
+    assert_eq!(page.page_size, 2);
+    assert_eq!(page.entries.len(), 1);
+    assert_eq!(
+        page.entries[0].endpoint, "/api/v1/upstreams",
+        "the oldest entry should be on the last page"
+    );
+    assert_eq!(
+        page.entries[0].changes[0].path,
+        "upstreams[paged_upstream_0]"
+    );
+
+    // 无效的分页参数
+    let response = app.get("/api/v1/audit?page=0").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let error_response: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error_response.error.r#type, "BadRequest");
+
+    let response = app.get("/api/v1/audit?page_size=1000").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_audit_concurrent_mutations_are_isolated() {
+    let mut app = spawn_app().await;
+
+    // 并发创建多个上游服务
+    let requests: Vec<_> = (0..32)
+        .map(|i| {
+            let router = app.router.clone();
+            tokio::spawn(async move {
+                let request = Request::builder()
+                    .method(Method::POST)
+                    .uri("/api/v1/upstreams")
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(
+                        json!({
+                            "name": format!("concurrent_upstream_{}", i),
+                            "url": "http://localhost:8080"
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap();
+                router.oneshot(request).await.unwrap().status()
+            })
+        })
+        .collect();
+    for request in requests {
+        assert_eq!(request.await.unwrap(), StatusCode::CREATED);
+    }
+
+    // 每条审计记录只包含本次请求创建的上游服务
+    let page = get_audit_page(&mut app, "").await;
+    assert_eq!(page.total, 32);
+    for entry in &page.entries {
+        assert_eq!(entry.changes.len(), 1, "{:?}", entry.changes);
+        assert!(entry.changes[0]
+            .path
+            .starts_with("upstreams[concurrent_upstream_"));
+    }
+}
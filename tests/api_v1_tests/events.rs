@@ -0,0 +1,135 @@
+//! Events API 测试模块
+use super::helpers::spawn_app;
+use axum::{
+    body::Body,
+    http::{header, StatusCode},
+};
+use futures_util::StreamExt;
+use llmproxy::{
+    events::{RateLimitReporter, SystemEvent, EVENTS},
+    tail::{SseFrame, SseParser},
+};
+use serde_json::json;
+use std::time::Duration;
+use tokio::sync::broadcast::Receiver;
+
+// 读取事件流，直到找到满足条件的系统事件
+async fn next_matching_event(
+    body: Body,
+    predicate: impl Fn(&SystemEvent) -> bool,
+) -> (SseFrame, SystemEvent) {
+    let mut body = body.into_data_stream();
+    let mut parser = SseParser::default();
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let chunk = body.next().await.unwrap().unwrap();
+            for frame in parser.feed(&chunk) {
+                // 其他测试可能同时产生事件，跳过不相关的事件
+                if let Ok(event) = serde_json::from_str::<SystemEvent>(&frame.data) {
+                    if predicate(&event) {
+                        return (frame, event);
+                    }
+                }
+            }
+        }
+    })
+    .await
+    .unwrap()
+}
+
+// 从订阅中读取指定转发服务的限流事件
+async fn next_rate_limited(receiver: &mut Receiver<SystemEvent>, name: &str) -> u64 {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            if let SystemEvent::RateLimited {
+                forward, rejected, ..
+            } = receiver.recv().await.unwrap()
+            {
+                if forward == name {
+                    return rejected;
+                }
+            }
+        }
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_events_stream_config_changed() {
+    let mut app = spawn_app().await;
+
+    let response = app
+        .post(
+            "/api/v1/upstreams",
+            json!({ "name": "events_upstream", "url": "http://localhost:8080" }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let stream = app.get("/api/v1/events").await;
+    assert_eq!(stream.status(), StatusCode::OK);
+    assert!(stream.headers()[header::CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .starts_with("text/event-stream"));
+
+    // 订阅已建立，修改配置
+    let response = app
+        .patch(
+            "/api/v1/upstreams/events_upstream",
+            json!({ "enabled": false }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (frame, event) = next_matching_event(stream.into_body(), |event| {
+        matches!(
+            event,
+            SystemEvent::ConfigChanged { endpoint, .. }
+                if endpoint == "/api/v1/upstreams/events_upstream"
+        )
+    })
+    .await;
+
+    assert_eq!(frame.event.as_deref(), Some("system"));
+    let value: serde_json::Value = serde_json::from_str(&frame.data).unwrap();
+    assert_eq!(value["type"], "config_changed");
+    match event {
+        SystemEvent::ConfigChanged {
+            actor,
+            method,
+            changes,
+            ..
+        } => {
+            assert_eq!(actor, "anonymous");
+            assert_eq!(method, "PATCH");
+            assert_eq!(changes, 1);
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[tokio::test]
+async fn test_rate_limit_reporter_coalesces_events() {
+    let mut receiver = EVENTS.subscribe_system();
+    let reporter = RateLimitReporter::default();
+
+    // 首次限流立即上报
+    reporter.record("events_rate_limited");
+    assert_eq!(
+        next_rate_limited(&mut receiver, "events_rate_limited").await,
+        1
+    );
+
+    // 最小间隔内的限流请求合并到下一次事件中
+    reporter.record("events_rate_limited");
+    reporter.record("events_rate_limited");
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    reporter.record("events_rate_limited");
+    assert_eq!(
+        next_rate_limited(&mut receiver, "events_rate_limited").await,
+        3
+    );
+}
@@ -1,10 +1,11 @@
 //! Forwards API 测试模块
-use super::helpers::spawn_app;
+use super::helpers::{spawn_app, spawn_app_with_forwards};
 use axum::{body::to_bytes, http::StatusCode};
 use llmproxy::{
-    api::v1::models::{ErrorResponse, SuccessResponse},
+    api::v1::models::{ErrorResponse, ForwardStatus, RuntimeStatus, SuccessResponse},
     config::ForwardConfig,
 };
+use serde_json::json;
 
 #[tokio::test]
 async fn test_list_forwards_success() {
@@ -50,3 +51,48 @@ async fn test_get_forward_not_found() {
     assert_eq!(error_response.code, 404);
     assert_eq!(error_response.error.r#type, "NotFound");
 }
+
+#[tokio::test]
+async fn test_disable_and_enable_forward() {
+    let mut app = spawn_app_with_forwards().await;
+    let state = app.forward_states["default_forward"].clone();
+
+    let response = app
+        .post("/api/v1/forwards/default_forward/disable", json!({}))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let success_response: SuccessResponse<ForwardStatus> = serde_json::from_slice(&body).unwrap();
+    let status = success_response.data.unwrap();
+    assert_eq!(status.name, "default_forward");
+    assert!(!status.enabled);
+    assert!(!state.is_enabled());
+
+    // 运行状态中同样反映禁用状态
+    let response = app.get("/api/v1/status").await;
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let success_response: SuccessResponse<RuntimeStatus> = serde_json::from_slice(&body).unwrap();
+    assert!(!success_response.data.unwrap().forwards[0].enabled);
+
+    let response = app
+        .post("/api/v1/forwards/default_forward/enable", json!({}))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(state.is_enabled());
+}
+
+#[tokio::test]
+async fn test_disable_forward_not_running() {
+    // 没有运行中的转发服务
+    let mut app = spawn_app().await;
+
+    let response = app
+        .post("/api/v1/forwards/default_forward/disable", json!({}))
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let error_response: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error_response.error.r#type, "NotFound");
+}
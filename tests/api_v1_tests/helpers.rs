@@ -8,12 +8,18 @@ use axum::{
 };
 use llmproxy::{
     api::v1,
+    audit::AuditLog,
     config::{
         self, serializer::SerializableArcString, Config, ForwardConfig, HttpServerConfig,
         TimeoutConfig,
     },
+    r#const::response_buffer,
+    reload::ConfigReloader,
+    server::{ForwardServer, ForwardState},
+    upstream::UpstreamManager,
 };
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tower::ServiceExt;
@@ -22,6 +28,7 @@ use tower::ServiceExt;
 pub struct TestApp {
     pub router: Router,
     pub config: Arc<RwLock<Config>>,
+    pub forward_states: Arc<HashMap<String, Arc<ForwardState>>>,
     pub address: String,
 }
 
@@ -38,6 +45,19 @@ impl TestApp {
         self.router.clone().oneshot(request).await.unwrap()
     }
 
+    // 辅助函数：携带管理令牌发送 GET 请求
+    pub async fn get_with_token(&mut self, path: &str, token: &str) -> Response {
+        let request = Request::builder()
+            .method(Method::GET)
+            .uri(path)
+            .header("Authorization", format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap();
+
+        // 发送请求
+        self.router.clone().oneshot(request).await.unwrap()
+    }
+
     // 辅助函数：发送 POST 请求
     pub async fn post(&mut self, path: &str, body: serde_json::Value) -> Response {
         let request = Request::builder()
@@ -51,6 +71,19 @@ impl TestApp {
         self.router.clone().oneshot(request).await.unwrap()
     }
 
+    // 辅助函数：发送文本请求体的 POST 请求
+    pub async fn post_text(&mut self, path: &str, content_type: &str, body: &str) -> Response {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(path)
+            .header("Content-Type", content_type)
+            .body(Body::from(body.to_string()))
+            .unwrap();
+
+        // 发送请求
+        self.router.clone().oneshot(request).await.unwrap()
+    }
+
     // 辅助函数：发送 PUT 请求
     pub async fn put(&mut self, path: &str, body: serde_json::Value) -> Response {
         let request = Request::builder()
@@ -90,10 +123,56 @@ impl TestApp {
     }
 }
 
+// 测试应用默认的配置文件路径（不存在，重载时会失败）
+pub const MISSING_CONFIG_PATH: &str = "/nonexistent/llmproxy/config.yaml";
+
 // 启动并配置测试应用实例
 pub async fn spawn_app() -> TestApp {
-    // 创建一个用于测试的默认配置
-    let config = Config {
+    spawn_app_with_config_path(MISSING_CONFIG_PATH).await
+}
+
+// 启动测试应用实例，配置重载时从指定文件读取
+pub async fn spawn_app_with_config_path(config_path: impl AsRef<Path>) -> TestApp {
+    // 创建一个空的forward_states
+    build_app(test_config(), config_path, HashMap::new(), None)
+}
+
+// 启动需要管理令牌认证的测试应用实例
+pub async fn spawn_app_with_token(token: &str) -> TestApp {
+    build_app(
+        test_config(),
+        MISSING_CONFIG_PATH,
+        HashMap::new(),
+        Some(token.to_string()),
+    )
+}
+
+// 启动测试应用实例，并根据测试配置创建运行中的转发服务状态（不监听端口）
+pub async fn spawn_app_with_forwards() -> TestApp {
+    spawn_app_with_forwards_and_config_path(MISSING_CONFIG_PATH).await
+}
+
+// 启动带有运行中转发服务状态的测试应用实例，配置重载时从指定文件读取
+pub async fn spawn_app_with_forwards_and_config_path(config_path: impl AsRef<Path>) -> TestApp {
+    let config = test_config();
+    let upstream_manager = Arc::new(
+        UpstreamManager::new(config.upstreams.clone(), config.upstream_groups.clone())
+            .await
+            .unwrap(),
+    );
+
+    let mut forward_states = HashMap::new();
+    for forward in &config.http_server.as_ref().unwrap().forwards {
+        let server = ForwardServer::new(forward.clone(), upstream_manager.clone(), &[]).unwrap();
+        forward_states.insert(forward.name.clone(), server.get_state().clone());
+    }
+
+    build_app(config, config_path, forward_states, None)
+}
+
+// 创建一个用于测试的默认配置
+fn test_config() -> Config {
+    Config {
         http_server: Some(HttpServerConfig {
             admin: config::AdminConfig {
                 ..Default::default()
@@ -106,7 +185,32 @@ pub async fn spawn_app() -> TestApp {
                 ratelimit: None,
                 timeout: Some(TimeoutConfig::default()),
                 routing: None,
+                limits: None,
+                count_tokens: false,
+                budget: None,
+                token_limit: None,
+                max_concurrent: None,
+                queue: None,
+                sse_heartbeat: None,
+                coalesce: false,
+                cache: None,
+                audit_sink: None,
+                pii_redaction: None,
+                policy: None,
+                plugins: vec![],
+                middlewares: None,
+                listener: None,
+                websocket: None,
+                compression: None,
+                access_log: None,
+                error_response: None,
+                response_buffer_limit: response_buffer::DEFAULT_LIMIT,
+                allowed_methods: vec![],
+                allowed_paths: vec![],
+                request_headers: None,
+                breaker: None,
             }],
+            load_shedding: None,
         }),
         upstreams: vec![config::UpstreamConfig {
             name: "default_upstream".to_string(),
@@ -114,13 +218,31 @@ pub async fn spawn_app() -> TestApp {
             auth: Some(config::AuthConfig {
                 r#type: config::AuthType::None,
                 token: None,
+                token_file: None,
                 username: None,
                 password: None,
+                password_file: None,
+                oauth2: None,
+                external: None,
             }),
             weight: 1,
             http_client: config::HttpClientConfig::default(),
             headers: Vec::new(),
             breaker: None,
+            adaptive: None,
+            hint: None,
+            enabled: true,
+            proxy: true,
+            path: None,
+            query_params: vec![],
+            body_transform: None,
+            dialect: None,
+            stream_normalize: None,
+            agent: None,
+            default_headers: Default::default(),
+            pricing: vec![],
+            dns: None,
+            warmup: None,
         }],
         upstream_groups: vec![config::UpstreamGroupConfig {
             name: "default_group".to_string(),
@@ -130,22 +252,48 @@ pub async fn spawn_app() -> TestApp {
             }],
             balance: config::BalanceConfig::default(),
             http_client: config::HttpClientConfig::default(),
+            sticky: None,
+            discovery: None,
         }],
-    };
+        models: vec![],
+    }
+}
 
+// 创建测试应用实例
+fn build_app(
+    config: Config,
+    config_path: impl AsRef<Path>,
+    forward_states: HashMap<String, Arc<ForwardState>>,
+    auth_token: Option<String>,
+) -> TestApp {
     // 将配置包装在 Arc<RwLock<>> 中以实现共享和可变性
     let shared_config = Arc::new(RwLock::new(config));
+    let forward_states = Arc::new(forward_states);
 
-    // 创建一个空的forward_states
-    let forward_states = Arc::new(HashMap::new());
+    // 创建内存审计日志
+    let audit = Arc::new(AuditLog::default());
+
+    // 创建配置重载器
+    let reloader = Arc::new(ConfigReloader::new(
+        config_path,
+        shared_config.clone(),
+        forward_states.clone(),
+    ));
 
     // 获取 API v1 路由并应用共享配置状态
-    let app_router = v1::api_routes(shared_config.clone(), forward_states);
+    let app_router = v1::api_routes_with_token(
+        shared_config.clone(),
+        forward_states.clone(),
+        audit,
+        reloader,
+        auth_token,
+    );
 
     // 返回 TestApp 实例，添加一个测试用的地址
     TestApp {
         router: app_router,
         config: shared_config,
+        forward_states,
         address: "http://127.0.0.1:8080".to_string(),
     }
 }
@@ -0,0 +1,188 @@
+//! Config reload API 测试模块
+use super::helpers::{
+    spawn_app, spawn_app_with_config_path, spawn_app_with_forwards_and_config_path,
+    MISSING_CONFIG_PATH,
+};
+use axum::{body::to_bytes, http::StatusCode};
+use llmproxy::{
+    api::v1::models::{ErrorResponse, SuccessResponse},
+    reload::ReloadStatus,
+};
+use serde_json::json;
+use std::fs;
+
+// 可以被成功加载的配置文件内容
+const VALID_CONFIG: &str = r#"
+http_server:
+  forwards:
+    - name: "default_forward"
+      port: 8080
+      default_group: "reloaded_group"
+  admin:
+    port: 9000
+upstreams:
+  - name: "reloaded_upstream"
+    url: "http://127.0.0.1:2"
+upstream_groups:
+  - name: "reloaded_group"
+    upstreams:
+      - name: "reloaded_upstream"
+"#;
+
+// 辅助函数：获取配置重载状态
+async fn get_reload_status(app: &mut super::helpers::TestApp) -> ReloadStatus {
+    let response = app.get("/api/v1/config/reload").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let success_response: SuccessResponse<ReloadStatus> = serde_json::from_slice(&body).unwrap();
+    success_response.data.unwrap()
+}
+
+#[tokio::test]
+async fn test_reload_status_before_any_reload() {
+    let mut app = spawn_app().await;
+
+    let status = get_reload_status(&mut app).await;
+    assert_eq!(status.path, MISSING_CONFIG_PATH);
+    assert!(!status.failed);
+    assert!(status.error.is_none());
+    assert!(status.last_attempt.is_none());
+    assert_eq!(status.successes, 0);
+    assert_eq!(status.failures, 0);
+}
+
+#[tokio::test]
+async fn test_reload_missing_file_keeps_last_known_good_config() {
+    let mut app = spawn_app().await;
+
+    let response = app.post("/api/v1/config/reload", json!({})).await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let error_response: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert!(error_response
+        .error
+        .message
+        .contains("keeping last-known-good configuration"));
+
+    // 继续使用原有配置
+    let config = app.config.read().await;
+    assert_eq!(config.upstreams[0].name, "default_upstream");
+    drop(config);
+
+    // 失败原因可以通过管理接口查询
+    let status = get_reload_status(&mut app).await;
+    assert!(status.failed);
+    assert!(status
+        .error
+        .as_deref()
+        .unwrap()
+        .contains("Unable to open configuration file"));
+    assert!(status.last_attempt.is_some());
+    assert!(status.last_success.is_none());
+    assert_eq!(status.failures, 1);
+}
+
+#[tokio::test]
+async fn test_reload_recovers_after_file_is_deleted() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.yaml");
+    fs::write(&path, VALID_CONFIG).unwrap();
+
+    let mut app = spawn_app_with_config_path(&path).await;
+
+    // 首次重载成功，应用新配置
+    let response = app.post("/api/v1/config/reload", json!({})).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        app.config.read().await.upstreams[0].name,
+        "reloaded_upstream"
+    );
+
+    // 配置文件被删除后重载失败，保留上一次有效的配置
+    fs::remove_file(&path).unwrap();
+    let response = app.post("/api/v1/config/reload", json!({})).await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        app.config.read().await.upstreams[0].name,
+        "reloaded_upstream"
+    );
+
+    let status = get_reload_status(&mut app).await;
+    assert!(status.failed);
+    assert!(status.last_success.is_some());
+    assert_eq!(status.successes, 1);
+    assert_eq!(status.failures, 1);
+
+    // 配置文件内容无效时同样保留上一次有效的配置
+    fs::write(&path, "upstreams: [").unwrap();
+    let response = app.post("/api/v1/config/reload", json!({})).await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        app.config.read().await.upstreams[0].name,
+        "reloaded_upstream"
+    );
+
+    // 配置文件恢复后重载成功，清除失败状态
+    fs::write(&path, VALID_CONFIG).unwrap();
+    let response = app.post("/api/v1/config/reload", json!({})).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let status = get_reload_status(&mut app).await;
+    assert!(!status.failed);
+    assert!(status.error.is_none());
+    assert_eq!(status.successes, 2);
+    assert_eq!(status.failures, 2);
+}
+
+#[tokio::test]
+async fn test_reload_apply_failure_leaves_running_services_unchanged() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.yaml");
+    // 路由规则有效，但上游组引用了运行中不存在的新上游，应用时失败
+    fs::write(
+        &path,
+        r#"
+http_server:
+  forwards:
+    - name: "default_forward"
+      port: 8080
+      default_group: "default_group"
+      routing:
+        - path: "/v1/chat"
+          target_group: "default_group"
+  admin:
+    port: 9000
+upstreams:
+  - name: "default_upstream"
+    url: "http://127.0.0.1:1"
+  - name: "added_upstream"
+    url: "http://127.0.0.1:2"
+upstream_groups:
+  - name: "default_group"
+    upstreams:
+      - name: "default_upstream"
+      - name: "added_upstream"
+"#,
+    )
+    .unwrap();
+
+    let mut app = spawn_app_with_forwards_and_config_path(&path).await;
+
+    let response = app.post("/api/v1/config/reload", json!({})).await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    // 路由规则和配置都保持不变
+    let state = &app.forward_states["default_forward"];
+    assert_eq!(state.router.get_target_group("/v1/chat").route, None);
+    assert_eq!(app.config.read().await.upstreams.len(), 1);
+
+    let status = get_reload_status(&mut app).await;
+    assert!(status.failed);
+    assert!(status
+        .error
+        .as_deref()
+        .unwrap()
+        .contains("Referenced upstream 'added_upstream' not found"));
+}
@@ -154,6 +154,58 @@ async fn test_create_route_conflict() {
     assert!(error_response.error.message.contains("already exists"));
 }
 
+#[tokio::test]
+async fn test_create_route_ambiguous() {
+    // 初始化测试环境
+    let mut app = spawn_app().await;
+    setup_test_upstream_groups(&mut app).await;
+
+    // 添加测试路由规则
+    let forward_name = "default_forward";
+    add_test_route(&mut app, forward_name, "/api/items/:id", "test_group").await;
+
+    // 准备请求数据，参数名不同但匹配相同的请求路径，且优先级相同
+    let payload = json!({
+        "path": "/api/items/:name",
+        "target_group": "another_group"
+    });
+
+    // 发送请求
+    let response = app
+        .post(
+            &format!("/api/v1/forwards/{}/routes", forward_name),
+            payload,
+        )
+        .await;
+
+    // 验证响应
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    // 解析响应体
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let error_response: ErrorResponse = serde_json::from_slice(&body).unwrap();
+
+    // 验证错误信息
+    assert_eq!(error_response.error.r#type, "Conflict");
+    assert!(error_response.error.message.contains("same priority"));
+
+    // 指定不同的优先级后可以创建成功
+    let payload = json!({
+        "path": "/api/items/:name",
+        "target_group": "another_group",
+        "priority": 10
+    });
+
+    let response = app
+        .post(
+            &format!("/api/v1/forwards/{}/routes", forward_name),
+            payload,
+        )
+        .await;
+
+    assert_eq!(response.status(), StatusCode::CREATED);
+}
+
 #[tokio::test]
 async fn test_update_route_not_found() {
     // 初始化测试环境
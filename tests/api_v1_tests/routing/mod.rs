@@ -11,7 +11,10 @@ use axum::{body::to_bytes, http::StatusCode};
 use base64::{engine::general_purpose::URL_SAFE, Engine};
 use llmproxy::{
     api::v1::models::{ErrorResponse, SuccessResponse},
-    config::{http_server::RoutingRule, UpstreamGroupConfig, UpstreamRef},
+    config::{
+        http_server::{RoutingRule, RoutingRuleType},
+        UpstreamGroupConfig, UpstreamRef,
+    },
 };
 use serde_json::json;
 
@@ -39,6 +42,8 @@ pub async fn setup_test_upstream_groups(app: &mut super::helpers::TestApp) {
             }],
             balance: llmproxy::config::BalanceConfig::default(),
             http_client: llmproxy::config::HttpClientConfig::default(),
+            sticky: None,
+            discovery: None,
         });
     }
 
@@ -56,6 +61,8 @@ pub async fn setup_test_upstream_groups(app: &mut super::helpers::TestApp) {
             }],
             balance: llmproxy::config::BalanceConfig::default(),
             http_client: llmproxy::config::HttpClientConfig::default(),
+            sticky: None,
+            discovery: None,
         });
     }
 }
@@ -87,7 +94,12 @@ pub async fn add_test_route(
             // 添加路由规则
             routing.push(RoutingRule {
                 path: path.to_string(),
+                r#type: RoutingRuleType::Path,
                 target_group: target_group.to_string(),
+                priority: 0,
+                breaker: None,
+                max_prompt_tokens: None,
+                tokens_per_minute: None,
             });
 
             return true;
@@ -130,3 +130,51 @@ async fn test_different_route_path_types() {
     assert!(routes.iter().any(|r| r.path == mixed_path));
     assert!(routes.iter().any(|r| r.path == tail_wildcard_path));
 }
+
+#[tokio::test]
+async fn test_create_path_regex_route() {
+    // 初始化测试环境
+    let mut app = spawn_app().await;
+    setup_test_upstream_groups(&mut app).await;
+
+    let forward_name = "default_forward";
+
+    // 完整的正则表达式规则
+    let payload = json!({
+        "path": r"^/api/products/[A-Z]{3}\d{3}$",
+        "type": "path_regex",
+        "target_group": "test_group"
+    });
+    let response = app
+        .post(
+            &format!("/api/v1/forwards/{}/routes", forward_name),
+            payload,
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let success_response: SuccessResponse<RoutingRule> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        success_response.data.unwrap().r#type,
+        RoutingRuleType::PathRegex
+    );
+
+    // 无效的正则表达式
+    let payload = json!({
+        "path": "^/api/products/[A-Z",
+        "type": "path_regex",
+        "target_group": "test_group"
+    });
+    let response = app
+        .post(
+            &format!("/api/v1/forwards/{}/routes", forward_name),
+            payload,
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let error_response: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert!(error_response.error.message.contains("Invalid path regex"));
+}
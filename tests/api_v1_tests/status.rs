@@ -0,0 +1,88 @@
+//! Status API 测试模块
+use super::helpers::spawn_app;
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use llmproxy::{
+    api::v1::{self, models::RuntimeStatus, models::SuccessResponse},
+    audit::AuditLog,
+    reload::ConfigReloader,
+    server::ForwardServer,
+    upstream::UpstreamManager,
+};
+use std::{collections::HashMap, sync::Arc};
+use tower::ServiceExt;
+
+#[tokio::test]
+async fn test_status_without_forward_states() {
+    let mut app = spawn_app().await;
+
+    let response = app.get("/api/v1/status").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let success_response: SuccessResponse<RuntimeStatus> = serde_json::from_slice(&body).unwrap();
+    let status = success_response.data.unwrap();
+
+    assert_eq!(status.admin_listen, "0.0.0.0:9000");
+    assert!(status.forwards.is_empty());
+    assert!(status.upstream_groups.is_empty());
+}
+
+#[tokio::test]
+async fn test_status_with_running_forwards() {
+    let app = spawn_app().await;
+    let config = app.config.read().await.clone();
+
+    // 根据测试配置创建转发服务状态
+    let upstream_manager = Arc::new(
+        UpstreamManager::new(config.upstreams.clone(), config.upstream_groups.clone())
+            .await
+            .unwrap(),
+    );
+    let forward_config = config.http_server.as_ref().unwrap().forwards[0].clone();
+    let server = ForwardServer::new(forward_config.clone(), upstream_manager, &[]).unwrap();
+
+    let mut forward_states = HashMap::new();
+    forward_states.insert(forward_config.name.clone(), server.get_state().clone());
+    let forward_states = Arc::new(forward_states);
+
+    let reloader = Arc::new(ConfigReloader::new(
+        super::helpers::MISSING_CONFIG_PATH,
+        app.config.clone(),
+        forward_states.clone(),
+    ));
+    let router = v1::api_routes(
+        app.config.clone(),
+        forward_states,
+        Arc::new(AuditLog::default()),
+        reloader,
+    );
+
+    let request = Request::builder()
+        .uri("/api/v1/status")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let success_response: SuccessResponse<RuntimeStatus> = serde_json::from_slice(&body).unwrap();
+    let status = success_response.data.unwrap();
+
+    assert_eq!(status.forwards.len(), 1);
+    assert_eq!(status.forwards[0].name, "default_forward");
+    assert_eq!(status.forwards[0].listen, "0.0.0.0:8080");
+    assert_eq!(status.forwards[0].default_group, "default_group");
+
+    assert_eq!(status.upstream_groups.len(), 1);
+    let group = &status.upstream_groups[0];
+    assert_eq!(group.name, "default_group");
+    assert_eq!(group.upstreams.len(), 1);
+    assert_eq!(group.upstreams[0].name, "default_upstream");
+    assert_eq!(group.upstreams[0].weight, 100);
+    assert!(group.upstreams[0].healthy);
+    assert!(group.upstreams[0].breaker.is_none());
+    assert_eq!(group.upstreams[0].pending_requests, 0);
+}
@@ -0,0 +1,122 @@
+//! Support Bundle API 测试模块
+use super::helpers::spawn_app;
+use axum::{
+    body::to_bytes,
+    http::{header, StatusCode},
+};
+use flate2::read::GzDecoder;
+use serde_json::json;
+use std::{collections::HashMap, io::Read};
+
+// 解压支持包，返回文件名（去掉顶层目录）到内容的映射
+fn unpack_bundle(content: &[u8]) -> HashMap<String, String> {
+    let mut archive = tar::Archive::new(GzDecoder::new(content));
+    archive
+        .entries()
+        .unwrap()
+        .map(|entry| {
+            let mut entry = entry.unwrap();
+            let path = entry.path().unwrap().to_string_lossy().to_string();
+            let (prefix, name) = path.split_once('/').unwrap();
+            assert!(prefix.starts_with("llmproxy-support-"));
+
+            let mut text = String::new();
+            entry.read_to_string(&mut text).unwrap();
+            (name.to_string(), text)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_download_support_bundle() {
+    let mut app = spawn_app().await;
+
+    let response = app.get("/api/v1/support-bundle").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/gzip"
+    );
+    let disposition = response
+        .headers()
+        .get(header::CONTENT_DISPOSITION)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(disposition.starts_with("attachment; filename=\"llmproxy-support-"));
+    assert!(disposition.ends_with(".tar.gz\""));
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let files = unpack_bundle(&body);
+
+    for name in [
+        "version.json",
+        "environment.json",
+        "config.yaml",
+        "status.json",
+        "reload.json",
+        "metrics.txt",
+        "logs.txt",
+    ] {
+        assert!(files.contains_key(name), "missing {}", name);
+    }
+
+    let version: serde_json::Value = serde_json::from_str(&files["version.json"]).unwrap();
+    assert_eq!(version["version"], env!("CARGO_PKG_VERSION"));
+
+    let status: serde_json::Value = serde_json::from_str(&files["status.json"]).unwrap();
+    assert_eq!(status["admin_listen"], "0.0.0.0:9000");
+
+    assert!(files["config.yaml"].contains("default_upstream"));
+}
+
+#[tokio::test]
+async fn test_support_bundle_masks_secrets() {
+    let mut app = spawn_app().await;
+
+    let upstream_payload = json!({
+        "name": "secret-upstream",
+        "url": "http://localhost:8080",
+        "auth": { "type": "bearer", "token": "sk-very-secret-token" }
+    });
+    let response = app.post("/api/v1/upstreams", upstream_payload).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let response = app.get("/api/v1/support-bundle").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let files = unpack_bundle(&body);
+
+    let config = &files["config.yaml"];
+    assert!(config.contains("secret-upstream"));
+    assert!(config.contains("******"));
+    assert!(!config.contains("sk-very-secret-token"));
+}
+
+#[tokio::test]
+async fn test_support_bundle_masks_header_secrets() {
+    let mut app = spawn_app().await;
+
+    let upstream_payload = json!({
+        "name": "anthropic-upstream",
+        "url": "https://api.anthropic.com/v1/messages",
+        "dialect": "anthropic",
+        "headers": [{ "op": "insert", "key": "x-api-key", "value": "sk-ant-header-secret" }],
+        "default_headers": { "x-org-key": "org-default-secret" }
+    });
+    let response = app.post("/api/v1/upstreams", upstream_payload).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let response = app.get("/api/v1/support-bundle").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let files = unpack_bundle(&body);
+
+    let config = &files["config.yaml"];
+    assert!(config.contains("x-api-key"));
+    assert!(!config.contains("sk-ant-header-secret"));
+    assert!(!config.contains("org-default-secret"));
+}
@@ -302,3 +302,43 @@ async fn test_patch_upstream_group_with_duplicate_upstream() {
     assert_eq!(error_response.error.r#type, "BadRequest");
     assert!(error_response.error.message.contains("Duplicate"));
 }
+
+// 测试更新上游组时一次返回全部问题
+#[tokio::test]
+async fn test_patch_upstream_group_reports_all_errors() {
+    let mut app = spawn_app().await;
+
+    app.post(
+        "/api/v1/upstreams",
+        json!({ "name": "test-upstream1", "url": "http://127.0.0.1:1" }),
+    )
+    .await;
+
+    // 权重无效、重复引用和引用不存在的上游同时出现
+    let patch_payload = json!({
+        "upstreams": [
+            { "name": "test-upstream1", "weight": 0 },
+            { "name": "test-upstream1", "weight": 1 },
+            { "name": "missing-upstream", "weight": 1 }
+        ]
+    });
+
+    let response = app
+        .patch("/api/v1/upstream-groups/default_group", patch_payload)
+        .await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let error_response: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    let messages: Vec<&str> = error_response
+        .details
+        .iter()
+        .map(|detail| detail.message.as_str())
+        .collect();
+    assert_eq!(messages.len(), 3, "{:?}", messages);
+    assert!(messages[0].starts_with("upstreams[0].weight: "));
+    assert!(messages[1].contains("Duplicate upstream 'test-upstream1'"));
+    assert!(messages[2].contains("Upstream 'missing-upstream' not found"));
+    assert!(error_response.error.message.contains("Duplicate"));
+}
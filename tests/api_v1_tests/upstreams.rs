@@ -1,9 +1,9 @@
 //! Upstreams API 测试模块
-use super::helpers::spawn_app;
+use super::helpers::{spawn_app, spawn_app_with_token, TestApp};
 use axum::{body::to_bytes, http::StatusCode};
 use llmproxy::{
     api::v1::models::{ErrorResponse, SuccessResponse},
-    config::UpstreamConfig,
+    config::{AuthConfig, AuthType, UpstreamConfig},
 };
 use serde_json::json;
 
@@ -191,3 +191,246 @@ async fn test_delete_upstream_in_use_conflict() {
     assert_eq!(error_response.error.r#type, "Conflict");
     assert!(error_response.error.message.contains("default_group"));
 }
+
+// 测试通过 PATCH 禁用（排空）并重新启用 Upstream
+#[tokio::test]
+async fn test_patch_upstream_enabled() {
+    let mut app = spawn_app().await;
+
+    let response = app
+        .patch(
+            "/api/v1/upstreams/default_upstream",
+            json!({ "enabled": false }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let success_response: SuccessResponse<UpstreamConfig> = serde_json::from_slice(&body).unwrap();
+    let upstream = success_response.data.unwrap();
+    assert_eq!(upstream.name, "default_upstream");
+    assert!(!upstream.enabled);
+    assert!(!app.config.read().await.upstreams[0].enabled);
+
+    let response = app
+        .patch(
+            "/api/v1/upstreams/default_upstream",
+            json!({ "enabled": true }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(app.config.read().await.upstreams[0].enabled);
+}
+
+// 测试 PATCH 一个不存在的 Upstream
+#[tokio::test]
+async fn test_patch_upstream_not_found() {
+    let mut app = spawn_app().await;
+
+    let response = app
+        .patch(
+            "/api/v1/upstreams/non_existent",
+            json!({ "enabled": false }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+// 辅助函数：为默认上游服务设置 Bearer 令牌
+async fn set_default_upstream_token(app: &TestApp, token: &str) {
+    let mut config = app.config.write().await;
+    let upstream = config
+        .upstreams
+        .iter_mut()
+        .find(|u| u.name == "default_upstream")
+        .unwrap();
+    upstream.auth = Some(AuthConfig {
+        r#type: AuthType::Bearer,
+        token: Some(token.to_string()),
+        token_file: None,
+        username: None,
+        password: None,
+        password_file: None,
+        oauth2: None,
+        external: None,
+    });
+}
+
+#[tokio::test]
+async fn test_upstream_secrets_redacted() {
+    let mut app = spawn_app().await;
+    set_default_upstream_token(&app, "sk-secret").await;
+
+    let response = app.get("/api/v1/upstreams").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(!String::from_utf8_lossy(&body).contains("sk-secret"));
+    let success_response: SuccessResponse<Vec<UpstreamConfig>> =
+        serde_json::from_slice(&body).unwrap();
+    let auth = success_response.data.unwrap()[0].auth.clone().unwrap();
+    assert_eq!(auth.token.as_deref(), Some("******"));
+    // 未配置的密码保持为空
+    assert!(auth.password.is_none());
+
+    let response = app.get("/api/v1/upstreams/default_upstream").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(!String::from_utf8_lossy(&body).contains("sk-secret"));
+
+    // 上游组详情同样脱敏
+    let response = app.get("/api/v1/upstream-groups/default_group").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(!String::from_utf8_lossy(&body).contains("sk-secret"));
+}
+
+#[tokio::test]
+async fn test_upstream_reveal_requires_admin_auth() {
+    let mut app = spawn_app().await;
+    set_default_upstream_token(&app, "sk-secret").await;
+
+    let response = app.get("/api/v1/upstreams?reveal=true").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let error_response: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error_response.error.r#type, "Forbidden");
+
+    let response = app
+        .get("/api/v1/upstreams/default_upstream?reveal=true")
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_upstream_reveal_with_admin_token() {
+    let mut app = spawn_app_with_token("admin-token").await;
+    set_default_upstream_token(&app, "sk-secret").await;
+
+    // 未携带令牌时被认证中间件拒绝
+    let response = app
+        .get("/api/v1/upstreams/default_upstream?reveal=true")
+        .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = app
+        .get_with_token(
+            "/api/v1/upstreams/default_upstream?reveal=true",
+            "admin-token",
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let success_response: SuccessResponse<UpstreamConfig> = serde_json::from_slice(&body).unwrap();
+    let auth = success_response.data.unwrap().auth.unwrap();
+    assert_eq!(auth.token.as_deref(), Some("sk-secret"));
+
+    // 未请求显示原文时仍然脱敏
+    let response = app.get_with_token("/api/v1/upstreams", "admin-token").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(!String::from_utf8_lossy(&body).contains("sk-secret"));
+}
+
+#[tokio::test]
+async fn test_update_upstream_keeps_masked_secrets() {
+    let mut app = spawn_app().await;
+    set_default_upstream_token(&app, "sk-secret").await;
+
+    // 回传 GET 返回的脱敏配置
+    let payload = json!({
+        "name": "default_upstream",
+        "url": "http://127.0.0.1:9999",
+        "auth": { "type": "bearer", "token": "******" }
+    });
+    let response = app.put("/api/v1/upstreams/default_upstream", payload).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(!String::from_utf8_lossy(&body).contains("sk-secret"));
+
+    {
+        let config = app.config.read().await;
+        let upstream = config
+            .upstreams
+            .iter()
+            .find(|u| u.name == "default_upstream")
+            .unwrap();
+        assert_eq!(
+            upstream.auth.as_ref().unwrap().token.as_deref(),
+            Some("sk-secret")
+        );
+        assert_eq!(upstream.url.as_ref() as &str, "http://127.0.0.1:9999");
+    }
+
+    // 提供新的令牌时替换
+    let payload = json!({
+        "name": "default_upstream",
+        "url": "http://127.0.0.1:9999",
+        "auth": { "type": "bearer", "token": "sk-rotated" }
+    });
+    let response = app.put("/api/v1/upstreams/default_upstream", payload).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let config = app.config.read().await;
+    let upstream = config
+        .upstreams
+        .iter()
+        .find(|u| u.name == "default_upstream")
+        .unwrap();
+    assert_eq!(
+        upstream.auth.as_ref().unwrap().token.as_deref(),
+        Some("sk-rotated")
+    );
+}
+
+#[tokio::test]
+async fn test_upstream_header_values_redacted_and_restored() {
+    let mut app = spawn_app().await;
+    let payload = json!({
+        "name": "default_upstream",
+        "url": "http://127.0.0.1:9999",
+        "headers": [{ "op": "insert", "key": "x-api-key", "value": "sk-ant-secret" }],
+        "default_headers": { "x-goog-api-key": "goog-secret" },
+        "query_params": [{ "op": "insert", "key": "key", "value": "query-secret" }]
+    });
+    let response = app.put("/api/v1/upstreams/default_upstream", payload).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // 未请求显示原文时请求头和查询参数的值均脱敏
+    let response = app.get("/api/v1/upstreams/default_upstream").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let text = String::from_utf8_lossy(&body);
+    assert!(!text.contains("sk-ant-secret"));
+    assert!(!text.contains("goog-secret"));
+    assert!(!text.contains("query-secret"));
+    let success_response: SuccessResponse<UpstreamConfig> = serde_json::from_slice(&body).unwrap();
+    let upstream = success_response.data.unwrap();
+    assert_eq!(upstream.headers[0].key, "x-api-key");
+    assert_eq!(upstream.headers[0].value.as_deref(), Some("******"));
+
+    // 回传 GET 返回的脱敏配置时保留现有的值
+    let response = app
+        .put(
+            "/api/v1/upstreams/default_upstream",
+            serde_json::to_value(&upstream).unwrap(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let config = app.config.read().await;
+    let upstream = config
+        .upstreams
+        .iter()
+        .find(|u| u.name == "default_upstream")
+        .unwrap();
+    assert_eq!(upstream.headers[0].value.as_deref(), Some("sk-ant-secret"));
+    assert_eq!(
+        upstream.headers[0].parsed_value.as_ref().unwrap(),
+        "sk-ant-secret"
+    );
+    assert_eq!(upstream.default_headers["x-goog-api-key"], "goog-secret");
+    assert_eq!(
+        upstream.query_params[0].value.as_deref(),
+        Some("query-secret")
+    );
+}
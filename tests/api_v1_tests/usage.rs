@@ -0,0 +1,73 @@
+//! Usage API 测试模块
+use super::helpers::spawn_app;
+use axum::{body::to_bytes, http::StatusCode};
+use llmproxy::{
+    api::v1::models::{ErrorResponse, SuccessResponse},
+    usage::{DailyUsage, USAGE},
+};
+
+#[tokio::test]
+async fn test_get_usage() {
+    let mut app = spawn_app().await;
+
+    USAGE.record(
+        "usage_api_group",
+        "usage_api_upstream",
+        "usage-api-model",
+        100,
+        20,
+        0.5,
+    );
+    USAGE.record(
+        "usage_api_group",
+        "usage_api_upstream",
+        "usage-api-model",
+        50,
+        10,
+        0.25,
+    );
+
+    let response = app.get("/api/v1/usage?days=1").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let success_response: SuccessResponse<Vec<DailyUsage>> = serde_json::from_slice(&body).unwrap();
+    let days = success_response.data.unwrap();
+    assert_eq!(days.len(), 1);
+
+    // 日期格式为 YYYY-MM-DD
+    let today = &days[0];
+    assert_eq!(today.date.len(), 10);
+    assert_eq!(today.date.matches('-').count(), 2);
+
+    let entry = today
+        .entries
+        .iter()
+        .find(|entry| entry.model == "usage-api-model")
+        .unwrap();
+    assert_eq!(entry.group, "usage_api_group");
+    assert_eq!(entry.upstream, "usage_api_upstream");
+    assert_eq!(entry.requests, 2);
+    assert_eq!(entry.prompt_tokens, 150);
+    assert_eq!(entry.completion_tokens, 30);
+    assert_eq!(entry.cost_usd, 0.75);
+    assert!(today.prompt_tokens >= 150);
+    assert!(today.cost_usd >= 0.75);
+}
+
+#[tokio::test]
+async fn test_get_usage_invalid_days() {
+    let mut app = spawn_app().await;
+
+    for days in ["0", "32"] {
+        let response = app.get(&format!("/api/v1/usage?days={}", days)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error_response: ErrorResponse = serde_json::from_slice(&body).unwrap();
+        assert!(error_response
+            .error
+            .message
+            .contains("Invalid days: must be between 1 and 31"));
+    }
+}
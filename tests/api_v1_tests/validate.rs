@@ -0,0 +1,198 @@
+//! Config validate API 测试模块
+use super::helpers::{spawn_app, TestApp};
+use axum::{body::to_bytes, http::StatusCode};
+use llmproxy::api::v1::models::{AuditPage, ConfigValidationResult, SuccessResponse};
+use serde_json::json;
+
+const VALIDATE_PATH: &str = "/api/v1/config/validate";
+
+// 完整且有效的配置文档
+const VALID_CONFIG: &str = r#"
+http_server:
+  forwards:
+    - name: "chat"
+      port: 8081
+      default_group: "chat_group"
+      routing:
+        - path: "/v1/embeddings"
+          target_group: "chat_group"
+  admin:
+    port: 9000
+upstreams:
+  - name: "chat_upstream"
+    url: "http://127.0.0.1:2"
+upstream_groups:
+  - name: "chat_group"
+    upstreams:
+      - name: "chat_upstream"
+"#;
+
+// 辅助函数：校验配置文档
+async fn validate(app: &mut TestApp, content_type: &str, document: &str) -> ConfigValidationResult {
+    let response = app.post_text(VALIDATE_PATH, content_type, document).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let success_response: SuccessResponse<ConfigValidationResult> =
+        serde_json::from_slice(&body).unwrap();
+    success_response.data.unwrap()
+}
+
+#[tokio::test]
+async fn test_validate_full_config() {
+    let mut app = spawn_app().await;
+
+    let result = validate(&mut app, "application/yaml", VALID_CONFIG).await;
+    assert!(result.valid, "unexpected errors: {:?}", result.errors);
+    assert!(result.errors.is_empty());
+    assert_eq!(
+        result.sections,
+        vec!["http_server", "upstreams", "upstream_groups"]
+    );
+
+    // 校验不会应用配置
+    let config = app.config.read().await;
+    assert!(config.upstreams.iter().all(|u| u.name != "chat_upstream"));
+}
+
+#[tokio::test]
+async fn test_validate_json_document() {
+    let mut app = spawn_app().await;
+
+    let document = json!({
+        "upstreams": [
+            { "name": "default_upstream", "url": "http://127.0.0.1:3" }
+        ]
+    });
+    let result = validate(&mut app, "application/json", &document.to_string()).await;
+    assert!(result.valid, "unexpected errors: {:?}", result.errors);
+    assert_eq!(result.sections, vec!["upstreams"]);
+}
+
+#[tokio::test]
+async fn test_validate_partial_config_uses_running_sections() {
+    let mut app = spawn_app().await;
+
+    // 引用运行中配置里的上游服务
+    let document = r#"
+upstream_groups:
+  - name: "default_group"
+    upstreams:
+      - name: "default_upstream"
+"#;
+    let result = validate(&mut app, "application/yaml", document).await;
+    assert!(result.valid, "unexpected errors: {:?}", result.errors);
+    assert_eq!(result.sections, vec!["upstream_groups"]);
+
+    // 引用不存在的上游服务
+    let document = r#"
+upstream_groups:
+  - name: "default_group"
+    upstreams:
+      - name: "missing_upstream"
+"#;
+    let result = validate(&mut app, "application/yaml", document).await;
+    assert!(!result.valid);
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].r#type, "ValidationError");
+    assert!(result.errors[0].message.contains("missing_upstream"));
+}
+
+#[tokio::test]
+async fn test_validate_reports_field_paths() {
+    let mut app = spawn_app().await;
+
+    let document = r#"
+upstreams:
+  - name: "default_upstream"
+    url: "not a url"
+  - name: "weighted_upstream"
+    url: "http://127.0.0.1:4"
+    weight: 0
+"#;
+    let result = validate(&mut app, "application/yaml", document).await;
+    assert!(!result.valid);
+    assert_eq!(result.errors.len(), 2, "errors: {:?}", result.errors);
+    assert!(result.errors.iter().all(|e| e.r#type == "ValidationError"));
+    assert!(result
+        .errors
+        .iter()
+        .any(|e| e.message.starts_with("upstreams[0].url: ")));
+    assert!(result
+        .errors
+        .iter()
+        .any(|e| e.message.starts_with("upstreams[1].weight: ")));
+}
+
+#[tokio::test]
+async fn test_validate_invalid_header() {
+    let mut app = spawn_app().await;
+
+    let document = r#"
+upstreams:
+  - name: "default_upstream"
+    url: "http://127.0.0.1:4"
+    headers:
+      - op: "insert"
+        key: "bad header"
+        value: "value"
+"#;
+    let result = validate(&mut app, "application/yaml", document).await;
+    assert!(!result.valid);
+    assert_eq!(result.errors[0].r#type, "ConfigError");
+    assert!(result.errors[0].message.contains("bad header"));
+}
+
+#[tokio::test]
+async fn test_validate_parse_errors() {
+    let mut app = spawn_app().await;
+
+    // 不是配置文档
+    let result = validate(&mut app, "application/yaml", "- just\n- a list\n").await;
+    assert!(!result.valid);
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].r#type, "ParseError");
+    assert!(result.sections.is_empty());
+
+    // 配置段类型错误
+    let result = validate(&mut app, "application/yaml", "upstreams: 42\n").await;
+    assert!(!result.valid);
+    assert_eq!(result.errors[0].r#type, "ParseError");
+    assert!(result.errors[0].message.starts_with("upstreams: "));
+}
+
+#[tokio::test]
+async fn test_validate_is_not_audited() {
+    let mut app = spawn_app().await;
+
+    let result = validate(&mut app, "application/yaml", VALID_CONFIG).await;
+    assert!(result.valid);
+
+    let response = app.get("/api/v1/audit").await;
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let page: SuccessResponse<AuditPage> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(page.data.unwrap().total, 0);
+}
+
+#[tokio::test]
+async fn test_get_config_schema() {
+    let mut app = spawn_app().await;
+
+    let response = app.get("/api/v1/config/schema").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-type"],
+        "application/schema+json"
+    );
+
+    // 返回 Schema 本身，不带响应外层结构
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let schema: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(schema, llmproxy::config::json_schema().unwrap());
+    assert_eq!(
+        schema["$schema"],
+        "https://json-schema.org/draft/2020-12/schema"
+    );
+    assert!(schema["properties"]["upstreams"].is_object());
+    assert!(schema["$defs"]["UpstreamConfig"].is_object());
+}
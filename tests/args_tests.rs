@@ -0,0 +1,228 @@
+use clap::Parser;
+use clap_complete::Shell;
+use llmproxy::args::{Args, Command, ConfigCommand, LogFormat, LogRotation, ValidateArgs};
+use llmproxy::config::ConfigFormat;
+
+#[test]
+fn test_log_format_and_level_defaults() {
+    let args = Args::try_parse_from(["llmproxyd"]).unwrap();
+    assert_eq!(args.log_format, LogFormat::Text);
+    assert!(args.log_level.is_none());
+    assert!(args.validation().is_ok());
+    assert_eq!(args.log_filter().to_string(), "info");
+
+    // 调试模式输出调试信息
+    let args = Args::try_parse_from(["llmproxyd", "--debug"]).unwrap();
+    assert_eq!(args.log_filter().to_string(), "debug");
+}
+
+#[test]
+fn test_log_format_json() {
+    let args = Args::try_parse_from(["llmproxyd", "--log-format", "json"]).unwrap();
+    assert_eq!(args.log_format, LogFormat::Json);
+
+    assert!(Args::try_parse_from(["llmproxyd", "--log-format", "xml"]).is_err());
+}
+
+#[test]
+fn test_log_level_per_module() {
+    // 日志级别优先于调试模式
+    let args = Args::try_parse_from([
+        "llmproxyd",
+        "--debug",
+        "--log-level",
+        "warn,llmproxy::upstream=debug",
+    ])
+    .unwrap();
+    assert!(args.validation().is_ok());
+    let filter = args.log_filter().to_string();
+    assert!(filter.contains("llmproxy::upstream=debug"), "{}", filter);
+    assert!(filter.contains("warn"), "{}", filter);
+
+    let args = Args::try_parse_from(["llmproxyd", "--log-level", "llmproxy=loud"]).unwrap();
+    assert!(args.validation().is_err());
+}
+
+#[test]
+fn test_log_file_options() {
+    let args = Args::try_parse_from(["llmproxyd"]).unwrap();
+    assert!(args.log_file.is_none());
+    assert_eq!(args.log_max_size, 100);
+    assert_eq!(args.log_max_files, 5);
+    assert_eq!(args.log_rotation, LogRotation::Never);
+    assert!(!args.log_stdout);
+
+    let args = Args::try_parse_from([
+        "llmproxyd",
+        "--log-file",
+        "/var/log/llmproxy/llmproxy.log",
+        "--log-max-size",
+        "50",
+        "--log-rotation",
+        "daily",
+        "--log-max-files",
+        "7",
+        "--log-stdout",
+    ])
+    .unwrap();
+    assert!(args.validation().is_ok());
+    assert_eq!(args.log_rotation, LogRotation::Daily);
+    assert_eq!(args.log_max_files, 7);
+    assert!(args.log_stdout);
+
+    // 轮转参数超出范围
+    for extra in [["--log-max-size", "0"], ["--log-max-files", "0"]] {
+        let args = Args::try_parse_from(["llmproxyd", extra[0], extra[1]]).unwrap();
+        assert!(args.validation().is_err(), "{:?} should be rejected", extra);
+    }
+}
+
+#[test]
+fn test_subcommands() {
+    // 未指定子命令时运行服务
+    let args = Args::try_parse_from(["llmproxyd", "-c", "a.yaml"]).unwrap();
+    assert!(args.command.is_none());
+
+    // 全局参数可以写在子命令之后
+    let args = Args::try_parse_from(["llmproxyd", "run", "-c", "a.yaml", "--debug"]).unwrap();
+    assert!(matches!(args.command, Some(Command::Run)));
+    assert_eq!(args.config.to_str(), Some("a.yaml"));
+    assert!(args.debug);
+
+    let args = Args::try_parse_from(["llmproxyd", "validate", "-c", "b.yaml"]).unwrap();
+    assert!(matches!(
+        args.command,
+        Some(Command::Validate(ValidateArgs { strict: false }))
+    ));
+    assert_eq!(args.config.to_str(), Some("b.yaml"));
+
+    let args = Args::try_parse_from(["llmproxyd", "config", "dump", "-c", "c.yaml"]).unwrap();
+    match args.command {
+        Some(Command::Config(ref config)) => {
+            assert!(matches!(config.command, ConfigCommand::Dump))
+        }
+        ref other => panic!("unexpected command: {:?}", other),
+    }
+    assert_eq!(args.config.to_str(), Some("c.yaml"));
+    assert!(Args::try_parse_from(["llmproxyd", "config"]).is_err());
+
+    let args = Args::try_parse_from(["llmproxyd", "schema"]).unwrap();
+    assert!(matches!(args.command, Some(Command::Schema)));
+
+    let args = Args::try_parse_from(["llmproxyd", "completions", "zsh"]).unwrap();
+    match args.command {
+        Some(Command::Completions(completions)) => assert_eq!(completions.shell, Shell::Zsh),
+        other => panic!("unexpected command: {:?}", other),
+    }
+    assert!(Args::try_parse_from(["llmproxyd", "completions", "cmd"]).is_err());
+}
+
+#[test]
+fn test_healthcheck_args() {
+    let args = Args::try_parse_from(["llmproxyd", "healthcheck"]).unwrap();
+    match args.command {
+        Some(Command::Healthcheck(ref healthcheck)) => {
+            assert_eq!(healthcheck.admin_url, "http://localhost:9000");
+            assert_eq!(healthcheck.timeout, 5);
+        }
+        ref other => panic!("unexpected command: {:?}", other),
+    }
+    assert!(args.validation().is_ok());
+
+    let args = Args::try_parse_from([
+        "llmproxyd",
+        "healthcheck",
+        "--admin-url",
+        "http://127.0.0.1:9001",
+        "--timeout",
+        "0",
+    ])
+    .unwrap();
+    assert!(args.validation().is_err());
+}
+
+#[test]
+fn test_config_format() {
+    // 未指定时按扩展名识别
+    let args = Args::try_parse_from(["llmproxyd", "-c", "config.toml"]).unwrap();
+    assert!(args.config_format.is_none());
+
+    let args = Args::try_parse_from([
+        "llmproxyd",
+        "validate",
+        "-c",
+        "llmproxy.conf",
+        "--format",
+        "toml",
+    ])
+    .unwrap();
+    assert_eq!(args.config_format, Some(ConfigFormat::Toml));
+    assert!(Args::try_parse_from(["llmproxyd", "--format", "ini"]).is_err());
+
+    let path = std::path::Path::new;
+    assert_eq!(ConfigFormat::from_path(path("a.yml")), ConfigFormat::Yaml);
+    assert_eq!(ConfigFormat::from_path(path("a.TOML")), ConfigFormat::Toml);
+    assert_eq!(ConfigFormat::from_path(path("a.json")), ConfigFormat::Json);
+    assert_eq!(ConfigFormat::from_path(path("a.conf")), ConfigFormat::Yaml);
+    assert_eq!(ConfigFormat::from_extension(path("a.conf")), None);
+}
+
+#[test]
+fn test_watch_config() {
+    let args = Args::try_parse_from(["llmproxyd"]).unwrap();
+    assert!(!args.watch_config);
+    assert_eq!(args.watch_debounce, 2000);
+
+    let args = Args::try_parse_from([
+        "llmproxyd",
+        "run",
+        "--watch-config",
+        "--watch-debounce",
+        "500",
+    ])
+    .unwrap();
+    assert!(args.watch_config);
+    assert_eq!(args.watch_debounce, 500);
+}
+
+#[test]
+fn test_runtime_args() {
+    let args = Args::try_parse_from(["llmproxyd"]).unwrap();
+    assert_eq!(args.worker_threads, None);
+    assert_eq!(args.max_blocking_threads, None);
+    assert_eq!(args.cpu_affinity, None);
+
+    let args = Args::try_parse_from([
+        "llmproxyd",
+        "run",
+        "--worker-threads",
+        "2",
+        "--max-blocking-threads",
+        "16",
+        "--cpu-affinity",
+        "4-6,0,5",
+    ])
+    .unwrap();
+    assert_eq!(args.worker_threads, Some(2));
+    assert_eq!(args.max_blocking_threads, Some(16));
+    let cpus = args.cpu_affinity.as_ref().unwrap();
+    assert_eq!(cpus.cpus(), &[0, 4, 5, 6]);
+    assert_eq!(cpus.to_string(), "0,4,5,6");
+
+    for cpus in ["", "a", "3-1", "1,,2", "1024"] {
+        assert!(
+            Args::try_parse_from(["llmproxyd", "--cpu-affinity", cpus]).is_err(),
+            "{:?} should be rejected",
+            cpus
+        );
+    }
+    for extra in [["--worker-threads", "0"], ["--max-blocking-threads", "0"]] {
+        let args = Args::try_parse_from(["llmproxyd", extra[0], extra[1]]).unwrap();
+        assert!(args.validation().is_err(), "{:?} should be rejected", extra);
+    }
+
+    // 按参数创建运行时
+    let args = Args::try_parse_from(["llmproxyd", "--worker-threads", "3"]).unwrap();
+    let runtime = llmproxy::runtime::build(&args).unwrap();
+    assert_eq!(runtime.metrics().num_workers(), 3);
+}
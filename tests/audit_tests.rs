@@ -0,0 +1,81 @@
+use llmproxy::{
+    audit::{actor_from_authorization, diff_config, AuditLog},
+    config::AuditConfig,
+};
+use serde_json::json;
+
+#[test]
+fn test_diff_config_by_element_name() {
+    let before = json!({
+        "upstreams": [
+            { "name": "a", "weight": 1 },
+            { "name": "b", "weight": 1 }
+        ],
+        "http_server": { "admin": { "port": 9000 } }
+    });
+    let after = json!({
+        "upstreams": [
+            { "name": "b", "weight": 2 }
+        ],
+        "http_server": { "admin": { "port": 9000 } }
+    });
+
+    let changes = diff_config(&before, &after);
+    assert_eq!(changes.len(), 2);
+
+    // 删除的元素按名称记录，而不是按下标错位比较
+    assert_eq!(changes[0].path, "upstreams[a]");
+    assert_eq!(changes[0].before, Some(json!({ "name": "a", "weight": 1 })));
+    assert!(changes[0].after.is_none());
+
+    assert_eq!(changes[1].path, "upstreams[b].weight");
+    assert_eq!(changes[1].before, Some(json!(1)));
+    assert_eq!(changes[1].after, Some(json!(2)));
+
+    assert!(diff_config(&before, &before).is_empty());
+}
+
+#[test]
+fn test_actor_from_authorization() {
+    assert_eq!(actor_from_authorization(None), "anonymous");
+    assert_eq!(actor_from_authorization(Some("Basic abc")), "anonymous");
+
+    let actor = actor_from_authorization(Some("Bearer my-token"));
+    assert!(actor.starts_with("token:"));
+    assert!(!actor.contains("my-token"));
+    assert_eq!(actor, actor_from_authorization(Some("Bearer my-token")));
+    assert_ne!(actor, actor_from_authorization(Some("Bearer other-token")));
+}
+
+#[test]
+fn test_audit_log_file_sink_and_retention() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.log");
+
+    let audit = AuditLog::new(&AuditConfig {
+        file: Some(path.to_string_lossy().to_string()),
+        max_entries: 2,
+    })
+    .unwrap();
+
+    for i in 0..3 {
+        audit.record(
+            "anonymous".to_string(),
+            "POST".to_string(),
+            format!("/api/v1/upstreams/{}", i),
+            vec![],
+        );
+    }
+
+    // 内存中只保留最近的记录
+    let (total, entries) = audit.page(1, 10);
+    assert_eq!(total, 2);
+    assert_eq!(entries[0].endpoint, "/api/v1/upstreams/2");
+    assert_eq!(entries[1].endpoint, "/api/v1/upstreams/1");
+
+    // 审计文件保留全部记录
+    let content = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].contains("/api/v1/upstreams/0"));
+}
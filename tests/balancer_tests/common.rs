@@ -15,6 +15,9 @@ pub fn create_test_managed_upstreams() -> Vec<ManagedUpstream> {
                 weight: 1,
             }),
             breaker: None,
+            drained: Default::default(),
+            adaptive: None,
+            endpoint: None,
         },
         ManagedUpstream {
             upstream_ref: Arc::new(UpstreamRef {
@@ -22,6 +25,9 @@ pub fn create_test_managed_upstreams() -> Vec<ManagedUpstream> {
                 weight: 2,
             }),
             breaker: None,
+            drained: Default::default(),
+            adaptive: None,
+            endpoint: None,
         },
         ManagedUpstream {
             upstream_ref: Arc::new(UpstreamRef {
@@ -29,6 +35,9 @@ pub fn create_test_managed_upstreams() -> Vec<ManagedUpstream> {
                 weight: 3,
             }),
             breaker: None,
+            drained: Default::default(),
+            adaptive: None,
+            endpoint: None,
         },
     ]
 }
@@ -43,6 +43,9 @@ async fn test_failover_balancer_with_unavailable_upstream() {
                 weight: 1,
             }),
             breaker: None,
+            drained: Default::default(),
+            adaptive: None,
+            endpoint: None,
         },
         ManagedUpstream {
             upstream_ref: Arc::new(UpstreamRef {
@@ -50,12 +53,19 @@ async fn test_failover_balancer_with_unavailable_upstream() {
                 weight: 1,
             }),
             breaker: None,
+            drained: Default::default(),
+            adaptive: None,
+            endpoint: None,
         },
     ];
 
     let breaker_config = llmproxy::config::BreakerConfig {
         threshold: 0.5,
         cooldown: 30,
+        connect_failures: 3,
+        window: None,
+        min_requests: None,
+        half_open_probes: None,
     };
     let breaker = llmproxy::breaker::create_upstream_circuit_breaker(
         "test_upstream".to_string(),
@@ -88,20 +98,26 @@ async fn test_failover_balancer_update_upstreams() {
 
     // 创建新的上游列表，按优先级排序
     let new_upstreams = vec![
-        ManagedUpstream {
+        Arc::new(ManagedUpstream {
             upstream_ref: Arc::new(UpstreamRef {
                 name: "primary".to_string(),
                 weight: 1,
             }),
             breaker: None,
-        },
-        ManagedUpstream {
+            drained: Default::default(),
+            adaptive: None,
+            endpoint: None,
+        }),
+        Arc::new(ManagedUpstream {
             upstream_ref: Arc::new(UpstreamRef {
                 name: "secondary".to_string(),
                 weight: 1,
             }),
             breaker: None,
-        },
+            drained: Default::default(),
+            adaptive: None,
+            endpoint: None,
+        }),
     ];
 
     // 更新上游列表
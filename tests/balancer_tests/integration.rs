@@ -7,7 +7,7 @@ use llmproxy::{
         BalanceConfig, BalanceStrategy, HttpClientConfig, UpstreamConfig, UpstreamGroupConfig,
         UpstreamRef,
     },
-    upstream::UpstreamManager,
+    upstream::{RequestContext, UpstreamManager},
 };
 use wiremock::{
     matchers::{method, path},
@@ -39,6 +39,20 @@ async fn test_load_balancer_with_upstream_manager() {
             auth: None,
             headers: vec![],
             breaker: None,
+            adaptive: None,
+            hint: None,
+            enabled: true,
+            proxy: true,
+            path: None,
+            query_params: vec![],
+            body_transform: None,
+            dialect: None,
+            stream_normalize: None,
+            agent: None,
+            default_headers: Default::default(),
+            pricing: vec![],
+            dns: None,
+            warmup: None,
         },
         UpstreamConfig {
             name: "upstream2".to_string(),
@@ -48,6 +62,20 @@ async fn test_load_balancer_with_upstream_manager() {
             auth: None,
             headers: vec![],
             breaker: None,
+            adaptive: None,
+            hint: None,
+            enabled: true,
+            proxy: true,
+            path: None,
+            query_params: vec![],
+            body_transform: None,
+            dialect: None,
+            stream_normalize: None,
+            agent: None,
+            default_headers: Default::default(),
+            pricing: vec![],
+            dns: None,
+            warmup: None,
         },
     ];
 
@@ -67,6 +95,8 @@ async fn test_load_balancer_with_upstream_manager() {
             strategy: BalanceStrategy::RoundRobin,
         },
         http_client: llmproxy::config::HttpClientConfig::default(),
+        sticky: None,
+        discovery: None,
     }];
 
     let upstream_manager = UpstreamManager::new(upstream_configs, group_configs)
@@ -76,6 +106,8 @@ async fn test_load_balancer_with_upstream_manager() {
     let response1 = upstream_manager
         .forward_request(
             "test_group",
+            "/",
+            &RequestContext::default(),
             &reqwest::Method::GET,
             reqwest::header::HeaderMap::new(),
             None,
@@ -86,6 +118,8 @@ async fn test_load_balancer_with_upstream_manager() {
     let response2 = upstream_manager
         .forward_request(
             "test_group",
+            "/",
+            &RequestContext::default(),
             &reqwest::Method::GET,
             reqwest::header::HeaderMap::new(),
             None,
@@ -116,6 +150,20 @@ async fn test_load_balancer_with_unavailable_upstream() {
             auth: None,
             headers: vec![],
             breaker: None,
+            adaptive: None,
+            hint: None,
+            enabled: true,
+            proxy: true,
+            path: None,
+            query_params: vec![],
+            body_transform: None,
+            dialect: None,
+            stream_normalize: None,
+            agent: None,
+            default_headers: Default::default(),
+            pricing: vec![],
+            dns: None,
+            warmup: None,
         },
         UpstreamConfig {
             name: "unavailable".to_string(),
@@ -125,6 +173,20 @@ async fn test_load_balancer_with_unavailable_upstream() {
             auth: None,
             headers: vec![],
             breaker: None,
+            adaptive: None,
+            hint: None,
+            enabled: true,
+            proxy: true,
+            path: None,
+            query_params: vec![],
+            body_transform: None,
+            dialect: None,
+            stream_normalize: None,
+            agent: None,
+            default_headers: Default::default(),
+            pricing: vec![],
+            dns: None,
+            warmup: None,
         },
     ];
 
@@ -144,6 +206,8 @@ async fn test_load_balancer_with_unavailable_upstream() {
             strategy: BalanceStrategy::RoundRobin,
         },
         http_client: llmproxy::config::HttpClientConfig::default(),
+        sticky: None,
+        discovery: None,
     }];
 
     let upstream_manager = UpstreamManager::new(upstream_configs, group_configs)
@@ -153,6 +217,8 @@ async fn test_load_balancer_with_unavailable_upstream() {
     let response = upstream_manager
         .forward_request(
             "test_group",
+            "/",
+            &RequestContext::default(),
             &reqwest::Method::GET,
             reqwest::header::HeaderMap::new(),
             Some("/test".to_string().into()),
@@ -31,13 +31,16 @@ async fn test_random_balancer_update_upstreams() {
     let balancer = RandomBalancer::new(initial_upstreams);
 
     // 创建新的上游列表
-    let new_upstreams = vec![ManagedUpstream {
+    let new_upstreams = vec![Arc::new(ManagedUpstream {
         upstream_ref: Arc::new(UpstreamRef {
             name: "new_random_upstream".to_string(),
             weight: 1,
         }),
         breaker: None,
-    }];
+        drained: Default::default(),
+        adaptive: None,
+        endpoint: None,
+    })];
 
     // 更新上游列表
     balancer.update_upstreams(new_upstreams).await;
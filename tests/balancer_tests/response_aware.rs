@@ -7,9 +7,9 @@ use llmproxy::{
     balancer::{LoadBalancer, ManagedUpstream, ResponseAwareBalancer},
     config::{
         BalanceConfig, BalanceStrategy, HttpClientConfig, UpstreamConfig, UpstreamGroupConfig,
-        UpstreamRef,
+        UpstreamRef, WarmupConfig,
     },
-    upstream::UpstreamManager,
+    upstream::{RequestContext, UpstreamManager},
 };
 use std::{sync::Arc, time::Duration};
 
@@ -141,6 +141,20 @@ async fn test_response_aware_with_upstream_manager() {
             auth: None,
             headers: vec![],
             breaker: None,
+            adaptive: None,
+            hint: None,
+            enabled: true,
+            proxy: true,
+            path: None,
+            query_params: vec![],
+            body_transform: None,
+            dialect: None,
+            stream_normalize: None,
+            agent: None,
+            default_headers: Default::default(),
+            pricing: vec![],
+            dns: None,
+            warmup: None,
         },
         UpstreamConfig {
             name: "slow".to_string(),
@@ -150,6 +164,20 @@ async fn test_response_aware_with_upstream_manager() {
             auth: None,
             headers: vec![],
             breaker: None,
+            adaptive: None,
+            hint: None,
+            enabled: true,
+            proxy: true,
+            path: None,
+            query_params: vec![],
+            body_transform: None,
+            dialect: None,
+            stream_normalize: None,
+            agent: None,
+            default_headers: Default::default(),
+            pricing: vec![],
+            dns: None,
+            warmup: None,
         },
     ];
 
@@ -169,6 +197,8 @@ async fn test_response_aware_with_upstream_manager() {
             strategy: BalanceStrategy::ResponseAware,
         },
         http_client: llmproxy::config::HttpClientConfig::default(),
+        sticky: None,
+        discovery: None,
     }];
 
     let upstream_manager = UpstreamManager::new(upstream_configs, group_configs)
@@ -179,6 +209,8 @@ async fn test_response_aware_with_upstream_manager() {
         let response = upstream_manager
             .forward_request(
                 "test_group",
+                "/",
+                &RequestContext::default(),
                 &reqwest::Method::GET,
                 reqwest::header::HeaderMap::new(),
                 None,
@@ -195,6 +227,8 @@ async fn test_response_aware_with_upstream_manager() {
         let response = upstream_manager
             .forward_request(
                 "test_group",
+                "/",
+                &RequestContext::default(),
                 &reqwest::Method::GET,
                 reqwest::header::HeaderMap::new(),
                 None,
@@ -305,20 +339,26 @@ async fn test_response_aware_balancer_update_upstreams() {
 
     // 创建新的上游列表，基于模拟服务器
     let new_upstreams = vec![
-        ManagedUpstream {
+        Arc::new(ManagedUpstream {
             upstream_ref: Arc::new(UpstreamRef {
                 name: "fast_upstream".to_string(),
                 weight: 1,
             }),
             breaker: None,
-        },
-        ManagedUpstream {
+            drained: Default::default(),
+            adaptive: None,
+            endpoint: None,
+        }),
+        Arc::new(ManagedUpstream {
             upstream_ref: Arc::new(UpstreamRef {
                 name: "slow_upstream".to_string(),
                 weight: 1,
             }),
             breaker: None,
-        },
+            drained: Default::default(),
+            adaptive: None,
+            endpoint: None,
+        }),
     ];
 
     // 更新上游列表
@@ -404,20 +444,26 @@ async fn test_response_aware_balancer_update_upstreams() {
 
     // 再次更新上游列表，添加一个新的最快上游
     let newest_upstreams = vec![
-        ManagedUpstream {
+        Arc::new(ManagedUpstream {
             upstream_ref: Arc::new(UpstreamRef {
                 name: "fastest_upstream".to_string(),
                 weight: 1,
             }),
             breaker: None,
-        },
-        ManagedUpstream {
+            drained: Default::default(),
+            adaptive: None,
+            endpoint: None,
+        }),
+        Arc::new(ManagedUpstream {
             upstream_ref: Arc::new(UpstreamRef {
                 name: "slow_upstream".to_string(), // 保留之前的慢速上游
                 weight: 1,
             }),
             breaker: None,
-        },
+            drained: Default::default(),
+            adaptive: None,
+            endpoint: None,
+        }),
     ];
 
     // 更新上游列表
@@ -499,27 +545,36 @@ async fn test_response_aware_balancer_update_upstreams() {
 
     // 创建包含三个上游的新列表
     let concurrent_upstreams = vec![
-        ManagedUpstream {
+        Arc::new(ManagedUpstream {
             upstream_ref: Arc::new(UpstreamRef {
                 name: "upstream1".to_string(),
                 weight: 1,
             }),
             breaker: None,
-        },
-        ManagedUpstream {
+            drained: Default::default(),
+            adaptive: None,
+            endpoint: None,
+        }),
+        Arc::new(ManagedUpstream {
             upstream_ref: Arc::new(UpstreamRef {
                 name: "upstream2".to_string(),
                 weight: 1,
             }),
             breaker: None,
-        },
-        ManagedUpstream {
+            drained: Default::default(),
+            adaptive: None,
+            endpoint: None,
+        }),
+        Arc::new(ManagedUpstream {
             upstream_ref: Arc::new(UpstreamRef {
                 name: "upstream3".to_string(),
                 weight: 1,
             }),
             breaker: None,
-        },
+            drained: Default::default(),
+            adaptive: None,
+            endpoint: None,
+        }),
     ];
 
     // 创建两个任务：一个进行更新，一个进行选择
@@ -546,3 +601,121 @@ async fn test_response_aware_balancer_update_upstreams() {
     assert!(["upstream1", "upstream2", "upstream3"]
         .contains(&final_upstream.upstream_ref.name.as_str()));
 }
+
+#[tokio::test]
+async fn test_response_aware_balancer_cancel_request() {
+    let managed_upstreams = create_test_managed_upstreams();
+    let balancer = ResponseAwareBalancer::new(managed_upstreams);
+
+    let selected = balancer.select_upstream().await.unwrap();
+    assert_eq!(balancer.pending_requests(&selected), 1);
+
+    // 请求在收到响应前被取消，只减少待处理请求计数
+    balancer.cancel_request(&selected);
+    assert_eq!(balancer.pending_requests(&selected), 0);
+}
+
+#[tokio::test]
+async fn test_response_aware_balancer_record_unselected() {
+    let managed_upstreams = create_test_managed_upstreams();
+    let balancer = ResponseAwareBalancer::new(managed_upstreams);
+    let upstream = balancer.upstreams()[0].clone();
+
+    // 第一次记录直接替换初始估计值，不改变待处理请求计数
+    balancer.record_unselected(&upstream, Some(120));
+    assert_eq!(balancer.response_time(&upstream), 120);
+    assert_eq!(balancer.pending_requests(&upstream), 0);
+
+    balancer.record_unselected(&upstream, Some(220));
+    assert_eq!(balancer.response_time(&upstream), 135);
+
+    // 失败的请求不改变响应时间
+    balancer.record_unselected(&upstream, None);
+    assert_eq!(balancer.response_time(&upstream), 135);
+    assert_eq!(balancer.pending_requests(&upstream), 0);
+}
+
+#[tokio::test]
+async fn test_response_aware_warm_up() {
+    let mock_server1 = setup_mock_server("Fast Server", 0).await;
+    let mock_server2 = setup_mock_server("Slow Server", 300).await;
+
+    let upstream = |name: &str, url: String| UpstreamConfig {
+        name: name.to_string(),
+        url: url.into(),
+        weight: 1,
+        http_client: HttpClientConfig::default(),
+        auth: None,
+        headers: vec![],
+        breaker: None,
+        adaptive: None,
+        hint: None,
+        enabled: true,
+        proxy: true,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect: None,
+        stream_normalize: None,
+        agent: None,
+        default_headers: Default::default(),
+        pricing: vec![],
+        dns: None,
+        warmup: Some(WarmupConfig {
+            method: "GET".to_string(),
+            path: None,
+            body: None,
+            requests: 1,
+            timeout: 5,
+        }),
+    };
+    let upstream_configs = vec![
+        upstream("fast", format!("{}/test", mock_server1.uri())),
+        upstream("slow", format!("{}/test", mock_server2.uri())),
+    ];
+    let group_configs = vec![UpstreamGroupConfig {
+        name: "test_group".to_string(),
+        upstreams: vec![
+            UpstreamRef {
+                name: "fast".to_string(),
+                weight: 1,
+            },
+            UpstreamRef {
+                name: "slow".to_string(),
+                weight: 1,
+            },
+        ],
+        balance: BalanceConfig {
+            strategy: BalanceStrategy::ResponseAware,
+        },
+        http_client: HttpClientConfig::default(),
+        sticky: None,
+        discovery: None,
+    }];
+
+    let upstream_manager = UpstreamManager::new(upstream_configs, group_configs)
+        .await
+        .unwrap();
+    for name in ["fast", "slow"] {
+        assert_eq!(
+            upstream_manager.warm_up("test_group", name).await.unwrap(),
+            1
+        );
+    }
+
+    // 预热后第一个请求即发往响应更快的上游
+    for _ in 0..3 {
+        let response = upstream_manager
+            .forward_request(
+                "test_group",
+                "/",
+                &RequestContext::default(),
+                &reqwest::Method::GET,
+                reqwest::header::HeaderMap::new(),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.text().await.unwrap(), "Fast Server");
+    }
+}
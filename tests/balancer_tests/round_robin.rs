@@ -93,13 +93,16 @@ async fn test_round_robin_balancer_update_upstreams() {
     assert_ne!(first.upstream_ref.name, second.upstream_ref.name);
 
     // 创建新的上游列表
-    let new_upstreams = vec![ManagedUpstream {
+    let new_upstreams = vec![Arc::new(ManagedUpstream {
         upstream_ref: Arc::new(UpstreamRef {
             name: "new_upstream".to_string(),
             weight: 1,
         }),
         breaker: None,
-    }];
+        drained: Default::default(),
+        adaptive: None,
+        endpoint: None,
+    })];
 
     // 更新上游列表
     balancer.update_upstreams(new_upstreams).await;
@@ -117,20 +120,26 @@ async fn test_weighted_round_robin_balancer_update_upstreams() {
 
     // 创建新的上游列表，具有不同的权重
     let new_upstreams = vec![
-        ManagedUpstream {
+        Arc::new(ManagedUpstream {
             upstream_ref: Arc::new(UpstreamRef {
                 name: "upstream1".to_string(),
                 weight: 1,
             }),
             breaker: None,
-        },
-        ManagedUpstream {
+            drained: Default::default(),
+            adaptive: None,
+            endpoint: None,
+        }),
+        Arc::new(ManagedUpstream {
             upstream_ref: Arc::new(UpstreamRef {
                 name: "upstream2".to_string(),
                 weight: 3,
             }),
             breaker: None,
-        },
+            drained: Default::default(),
+            adaptive: None,
+            endpoint: None,
+        }),
     ];
 
     // 更新上游列表
@@ -152,3 +161,47 @@ async fn test_weighted_round_robin_balancer_update_upstreams() {
     // upstream2的权重是upstream1的3倍，所以应该被选择更多次
     assert!(upstream2_count > upstream1_count);
 }
+
+#[tokio::test]
+async fn test_round_robin_balancer_skips_drained_upstream() {
+    let managed_upstreams = create_test_managed_upstreams();
+    managed_upstreams[1]
+        .drained
+        .store(true, std::sync::atomic::Ordering::Relaxed);
+    let balancer = RoundRobinBalancer::new(managed_upstreams.clone());
+
+    for _ in 0..6 {
+        let selected = balancer.select_upstream().await.unwrap();
+        assert_ne!(selected.upstream_ref.name, "upstream2");
+    }
+
+    // 所有上游都被排空时没有可用的上游
+    for upstream in &managed_upstreams {
+        upstream
+            .drained
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+    assert!(balancer.select_upstream().await.is_err());
+}
+
+#[tokio::test]
+async fn test_weighted_round_robin_balancer_shares_upstreams() {
+    let balancer = WeightedRoundRobinBalancer::new(create_test_managed_upstreams());
+
+    // 选择结果与上游列表共享同一个托管上游，权重副本只列出一次
+    let upstreams = balancer.upstreams();
+    assert_eq!(upstreams.len(), 3);
+    for _ in 0..6 {
+        let selected = balancer.select_upstream().await.unwrap();
+        assert!(upstreams
+            .iter()
+            .any(|upstream| Arc::ptr_eq(upstream, &selected)));
+    }
+
+    // 更新后保留的托管上游仍是同一个实例
+    balancer.update_upstreams(upstreams[..2].to_vec()).await;
+    let updated = balancer.upstreams();
+    assert_eq!(updated.len(), 2);
+    assert!(Arc::ptr_eq(&updated[0], &upstreams[0]));
+    assert!(Arc::ptr_eq(&updated[1], &upstreams[1]));
+}
@@ -2,8 +2,9 @@ use circuitbreaker_rs::State;
 use llmproxy::{
     balancer::{create_load_balancer, ManagedUpstream},
     breaker::{create_upstream_circuit_breaker, UpstreamCircuitBreaker, UpstreamError},
-    config::{BalanceStrategy, BreakerConfig, UpstreamRef},
+    config::{BalanceStrategy, BreakerConfig, BreakerWindowConfig, BreakerWindowType, UpstreamRef},
     error::AppError,
+    r#const::breaker_limits,
 };
 use std::sync::Arc;
 use std::time::Duration;
@@ -18,6 +19,10 @@ fn create_test_breaker_config(threshold: f64, cooldown: u64) -> BreakerConfig {
     BreakerConfig {
         threshold,
         cooldown,
+        connect_failures: breaker_limits::DEFAULT_CONNECT_FAILURES,
+        window: None,
+        min_requests: None,
+        half_open_probes: None,
     }
 }
 
@@ -74,6 +79,96 @@ async fn test_breaker_basic_functionality() {
     assert!(breaker.is_call_permitted());
 }
 
+#[tokio::test]
+async fn test_breaker_connect_failures() {
+    // 失败率阈值很高，连接阶段失败阈值为 2
+    let config = BreakerConfig {
+        threshold: 1.0,
+        cooldown: 1,
+        connect_failures: 2,
+        window: None,
+        min_requests: None,
+        half_open_probes: None,
+    };
+    let breaker = create_upstream_circuit_breaker(
+        "connect_upstream".to_string(),
+        "group".to_string(),
+        &config,
+    );
+
+    // 单次连接失败不会触发熔断
+    breaker.record_connect_failure();
+    assert_eq!(breaker.connect_failures(), 1);
+    assert_eq!(breaker.current_state(), State::Closed);
+
+    // 成功建立连接后重新计数
+    breaker.record_connect_success();
+    assert_eq!(breaker.connect_failures(), 0);
+    breaker.record_connect_failure();
+    assert_eq!(breaker.current_state(), State::Closed);
+
+    // 连续失败达到阈值后立即熔断
+    breaker.record_connect_failure();
+    assert_eq!(breaker.current_state(), State::Open);
+    assert!(!breaker.is_call_permitted());
+
+    // 冷却后进入半开状态，允许探测请求
+    sleep(Duration::from_secs(2)).await;
+    assert!(breaker.is_call_permitted());
+}
+
+#[tokio::test]
+async fn test_breaker_request_count_window() {
+    // 统计最近 10 个请求，至少 5 个请求后才按失败率熔断
+    let config = BreakerConfig {
+        threshold: 0.5,
+        cooldown: 1,
+        connect_failures: breaker_limits::DEFAULT_CONNECT_FAILURES,
+        window: Some(BreakerWindowConfig {
+            r#type: BreakerWindowType::Count,
+            size: 10,
+        }),
+        min_requests: Some(5),
+        half_open_probes: Some(1),
+    };
+    let breaker = create_upstream_circuit_breaker(
+        "window_upstream".to_string(),
+        "group".to_string(),
+        &config,
+    );
+    let fail = || async { Err::<(), _>(UpstreamError("test failure".to_string())) };
+    let succeed = || async { Ok::<_, UpstreamError>(()) };
+
+    // 低流量时少量失败不会触发熔断
+    for _ in 0..4 {
+        let _ = breaker.call_async(fail).await;
+    }
+    assert_eq!(breaker.current_state(), State::Closed);
+
+    // 请求数达到最小值后按失败率熔断
+    let _ = breaker.call_async(succeed).await;
+    assert_eq!(breaker.current_state(), State::Open);
+
+    // 冷却后进入半开状态，探测成功后关闭并重新统计
+    sleep(Duration::from_secs(2)).await;
+    assert!(breaker.is_call_permitted());
+    let _ = breaker.call_async(succeed).await;
+    assert_eq!(breaker.current_state(), State::Closed);
+
+    // 窗口内的失败率低于阈值
+    for _ in 0..6 {
+        let _ = breaker.call_async(succeed).await;
+    }
+    for _ in 0..4 {
+        let _ = breaker.call_async(fail).await;
+    }
+    assert_eq!(breaker.current_state(), State::Closed);
+
+    // 最早的成功请求移出窗口后，失败率达到阈值
+    let _ = breaker.call_async(fail).await;
+    assert_eq!(breaker.current_state(), State::Open);
+}
+
 #[tokio::test]
 async fn test_breaker_with_mock_server() {
     // 启动模拟服务器
@@ -188,6 +283,9 @@ async fn test_load_balancer_with_circuit_breaker() {
             weight: 1,
         }),
         breaker: Some(breaker1),
+        drained: Default::default(),
+        adaptive: None,
+        endpoint: None,
     };
 
     let managed_upstream2 = ManagedUpstream {
@@ -196,6 +294,9 @@ async fn test_load_balancer_with_circuit_breaker() {
             weight: 1,
         }),
         breaker: Some(breaker2),
+        drained: Default::default(),
+        adaptive: None,
+        endpoint: None,
     };
 
     let upstreams = vec![managed_upstream1, managed_upstream2];
@@ -311,11 +412,17 @@ async fn test_all_upstreams_circuit_open() {
     let managed_upstream1 = ManagedUpstream {
         upstream_ref: Arc::new(upstream_ref1),
         breaker: Some(breaker1.clone()),
+        drained: Default::default(),
+        adaptive: None,
+        endpoint: None,
     };
 
     let managed_upstream2 = ManagedUpstream {
         upstream_ref: Arc::new(upstream_ref2),
         breaker: Some(breaker2.clone()),
+        drained: Default::default(),
+        adaptive: None,
+        endpoint: None,
     };
 
     let upstreams = vec![managed_upstream1, managed_upstream2];
@@ -328,3 +435,34 @@ async fn test_all_upstreams_circuit_open() {
     assert!(result.is_err());
     assert!(matches!(result, Err(AppError::NoHealthyUpstreamAvailable)));
 }
+
+#[tokio::test]
+async fn test_breaker_time_window() {
+    // 统计最近 1 秒内的请求，至少 2 个请求后才按失败率熔断
+    let config = BreakerConfig {
+        threshold: 0.5,
+        cooldown: 1,
+        connect_failures: breaker_limits::DEFAULT_CONNECT_FAILURES,
+        window: Some(BreakerWindowConfig {
+            r#type: BreakerWindowType::Time,
+            size: 1,
+        }),
+        min_requests: Some(2),
+        half_open_probes: None,
+    };
+    let breaker = create_upstream_circuit_breaker(
+        "time_window_upstream".to_string(),
+        "group".to_string(),
+        &config,
+    );
+    let fail = || async { Err::<(), _>(UpstreamError("test failure".to_string())) };
+
+    // 窗口过期的失败不再计入
+    let _ = breaker.call_async(fail).await;
+    sleep(Duration::from_millis(1100)).await;
+    let _ = breaker.call_async(fail).await;
+    assert_eq!(breaker.current_state(), State::Closed);
+
+    let _ = breaker.call_async(fail).await;
+    assert_eq!(breaker.current_state(), State::Open);
+}
@@ -0,0 +1,71 @@
+use bytes::Bytes;
+use llmproxy::{
+    cache::{CacheStore, CachedResponse, MemoryStore, RedisStore},
+    config::RedisConfig,
+};
+use std::time::Duration;
+
+/// 测试缓存响应的编码和解码
+#[test]
+fn test_cached_response_encode() {
+    let cached = CachedResponse {
+        status: 200,
+        headers: vec![("content-type".to_string(), "application/json".to_string())],
+        body: Bytes::from_static(b"{\"object\":\"list\"}\xff"),
+    };
+    assert_eq!(CachedResponse::decode(&cached.encode()), Some(cached));
+    assert_eq!(CachedResponse::decode(b"not json"), None);
+}
+
+/// 测试内存缓存的过期和淘汰
+#[tokio::test]
+async fn test_memory_store() {
+    let store = MemoryStore::new(2);
+    let ttl = Duration::from_secs(60);
+    store.set("a", Bytes::from("1"), ttl).await.unwrap();
+    store.set("b", Bytes::from("2"), ttl).await.unwrap();
+    assert_eq!(store.get("a").await.unwrap(), Some(Bytes::from("1")));
+    assert_eq!(store.get("c").await.unwrap(), None);
+
+    // 条目数达到上限时淘汰最早过期的条目
+    store.set("c", Bytes::from("3"), ttl * 2).await.unwrap();
+    assert_eq!(store.len(), 2);
+    assert_eq!(store.get("a").await.unwrap(), None);
+    assert_eq!(store.get("c").await.unwrap(), Some(Bytes::from("3")));
+
+    // 过期的条目不再返回
+    store
+        .set("d", Bytes::from("4"), Duration::from_millis(50))
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(store.get("d").await.unwrap(), None);
+}
+
+/// 测试 Redis 不可用时返回错误并在重试间隔内快速失败
+#[tokio::test]
+async fn test_redis_store_unavailable() {
+    let store = RedisStore::new(&RedisConfig {
+        url: "redis://127.0.0.1:1/0".to_string(),
+        password: None,
+        key_prefix: "llmproxy:".to_string(),
+    })
+    .unwrap();
+    assert_eq!(store.name(), "redis");
+    assert!(store.get("a").await.is_err());
+
+    let start = std::time::Instant::now();
+    assert!(store
+        .set("a", Bytes::from("1"), Duration::from_secs(60))
+        .await
+        .is_err());
+    assert!(start.elapsed() < Duration::from_millis(100));
+
+    // 地址无效时创建失败
+    assert!(RedisStore::new(&RedisConfig {
+        url: "http://127.0.0.1:6379".to_string(),
+        password: None,
+        key_prefix: "llmproxy:".to_string(),
+    })
+    .is_err());
+}
@@ -15,7 +15,11 @@ mod config_tests {
     #[cfg(test)]
     mod routing;
     #[cfg(test)]
+    mod source;
+    #[cfg(test)]
     mod upstream;
     #[cfg(test)]
     mod validation;
+    #[cfg(test)]
+    mod watch;
 }
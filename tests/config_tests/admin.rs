@@ -2,6 +2,9 @@
 
 // This module contains tests for the AdminConfig struct.
 use super::common::{create_temp_config_file, TestConfigBuilder};
+use llmproxy::config::{
+    AlertMetric, AlertsConfig, MetricsExportConfig, MetricsExportProtocol, MetricsUpstreamLabel,
+};
 use validator::Validate;
 
 #[test]
@@ -20,3 +23,317 @@ fn test_admin_with_none_options() {
     let admin = &deserialized.http_server.unwrap().admin;
     assert!(admin.timeout.is_none());
 }
+
+#[test]
+fn test_admin_metrics_defaults() {
+    let config = TestConfigBuilder::new().build();
+    let (_dir, file_path) = create_temp_config_file(&config);
+    let deserialized = llmproxy::config::Config::from_file(file_path).unwrap();
+
+    let metrics = &deserialized.http_server.unwrap().admin.metrics;
+    assert_eq!(metrics.path, "/metrics");
+    assert!(metrics.token.is_none());
+    assert!(metrics.port.is_none());
+    assert!(metrics.address.is_none());
+}
+
+#[test]
+fn test_admin_metrics_custom_path_and_listener() {
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            let metrics = &mut c.http_server.as_mut().unwrap().admin.metrics;
+            metrics.path = "/internal/metrics".to_string();
+            metrics.token = Some("scrape-token".to_string());
+            metrics.port = Some(9100);
+            metrics.address = Some("127.0.0.1".to_string());
+        })
+        .build();
+
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_admin_metrics_invalid_path() {
+    for path in [
+        "metrics",
+        "/metrics/{name}",
+        "/metrics/*rest",
+        "/my metrics",
+    ] {
+        let config = TestConfigBuilder::new()
+            .map_config(|c| {
+                c.http_server.as_mut().unwrap().admin.metrics.path = path.to_string();
+            })
+            .build();
+
+        assert!(
+            config.validate().is_err(),
+            "path {:?} should be rejected",
+            path
+        );
+    }
+}
+
+#[test]
+fn test_admin_metrics_path_conflicts_with_admin_endpoints() {
+    for path in ["/health", "/dashboard", "/api/v1", "/api/v1/metrics"] {
+        let config = TestConfigBuilder::new()
+            .map_config(|c| {
+                c.http_server.as_mut().unwrap().admin.metrics.path = path.to_string();
+            })
+            .build();
+
+        assert!(
+            config.validate().is_err(),
+            "path {:?} should be rejected",
+            path
+        );
+    }
+
+    // 使用独立端口时不会与管理路径冲突
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            let metrics = &mut c.http_server.as_mut().unwrap().admin.metrics;
+            metrics.path = "/health".to_string();
+            metrics.port = Some(9100);
+        })
+        .build();
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_admin_metrics_invalid_listener() {
+    // 指标端口与管理服务端口相同
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            let admin = &mut c.http_server.as_mut().unwrap().admin;
+            admin.metrics.port = Some(admin.port);
+        })
+        .build();
+    assert!(config.validate().is_err());
+
+    // 只配置了指标地址，未配置指标端口
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            c.http_server.as_mut().unwrap().admin.metrics.address = Some("127.0.0.1".to_string());
+        })
+        .build();
+    assert!(config.validate().is_err());
+
+    // 空令牌
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            c.http_server.as_mut().unwrap().admin.metrics.token = Some(String::new());
+        })
+        .build();
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn test_admin_tls() {
+    let validate = |yaml: &str| {
+        let tls: llmproxy::config::AdminTlsConfig = serde_yaml::from_str(yaml).unwrap();
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.http_server.as_mut().unwrap().admin.tls = Some(tls);
+            })
+            .build()
+            .validate()
+    };
+
+    assert!(validate("cert: admin.crt\nkey: admin.key").is_ok());
+    assert!(validate("cert: admin.crt\nkey: admin.key\nclient_ca_file: clients.pem").is_ok());
+
+    // 证书和私钥不能为空
+    assert!(validate("cert: ''\nkey: admin.key")
+        .unwrap_err()
+        .to_string()
+        .contains("Admin TLS cert cannot be empty"));
+    assert!(
+        validate("cert: admin.crt\nkey: admin.key\nclient_ca_file: ''")
+            .unwrap_err()
+            .to_string()
+            .contains("Admin TLS client_ca_file cannot be empty")
+    );
+
+    // 证书和私钥必填
+    assert!(serde_yaml::from_str::<llmproxy::config::AdminTlsConfig>("cert: admin.crt").is_err());
+}
+
+#[test]
+fn test_admin_metrics_export() {
+    let yaml = r#"
+export:
+  protocol: dogstatsd
+  endpoint: "127.0.0.1:8125"
+  prefix: "llm."
+"#;
+    let metrics: llmproxy::config::MetricsConfig = serde_yaml::from_str(yaml).unwrap();
+    assert!(metrics.validate().is_ok());
+    let export = metrics.export.unwrap();
+    assert_eq!(export.protocol, MetricsExportProtocol::Dogstatsd);
+    assert_eq!(export.interval, 15);
+    assert_eq!(export.prefix, "llm.");
+    assert!(export.headers.is_empty());
+
+    let valid = |protocol, endpoint: &str| MetricsExportConfig {
+        protocol,
+        endpoint: endpoint.to_string(),
+        interval: 15,
+        prefix: String::new(),
+        headers: Default::default(),
+    };
+    assert!(valid(
+        MetricsExportProtocol::Otlp,
+        "http://collector:4318/v1/metrics"
+    )
+    .validate()
+    .is_ok());
+    assert!(valid(MetricsExportProtocol::Statsd, "statsd:8125")
+        .validate()
+        .is_ok());
+
+    // StatsD 地址不是 host:port，OTLP 地址不是 HTTP(S) 地址
+    for (protocol, endpoint) in [
+        (MetricsExportProtocol::Statsd, "http://statsd:8125"),
+        (MetricsExportProtocol::Statsd, "statsd"),
+        (MetricsExportProtocol::Dogstatsd, ":8125"),
+        (MetricsExportProtocol::Otlp, "collector:4318"),
+    ] {
+        assert!(
+            valid(protocol, endpoint).validate().is_err(),
+            "endpoint {:?} should be rejected",
+            endpoint
+        );
+    }
+
+    // 推送间隔超出范围、前缀包含非法字符、无效的请求头
+    let mut export = valid(MetricsExportProtocol::Statsd, "statsd:8125");
+    export.interval = 0;
+    assert!(export.validate().is_err());
+    let mut export = valid(MetricsExportProtocol::Statsd, "statsd:8125");
+    export.prefix = "llm proxy".to_string();
+    assert!(export.validate().is_err());
+    let mut export = valid(
+        MetricsExportProtocol::Otlp,
+        "http://collector:4318/v1/metrics",
+    );
+    export
+        .headers
+        .insert("bad header".to_string(), "value".to_string());
+    assert!(export.validate().is_err());
+
+    // 指标配置校验推送配置
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            c.http_server.as_mut().unwrap().admin.metrics.export =
+                Some(valid(MetricsExportProtocol::Otlp, "collector:4318"));
+        })
+        .build();
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn test_admin_metrics_labels() {
+    let metrics: llmproxy::config::MetricsConfig = serde_yaml::from_str("path: /metrics").unwrap();
+    assert!(!metrics.labels.model);
+    assert_eq!(metrics.labels.upstream, MetricsUpstreamLabel::Url);
+    assert!(metrics.labels.disabled.is_empty());
+    assert!(metrics.duration_buckets.is_none());
+
+    let yaml = r#"
+labels:
+  upstream: name
+  disabled: ["model", "method"]
+duration_buckets: [0.1, 0.5, 1, 5]
+"#;
+    let metrics: llmproxy::config::MetricsConfig = serde_yaml::from_str(yaml).unwrap();
+    assert!(metrics.validate().is_ok());
+    assert_eq!(metrics.labels.upstream, MetricsUpstreamLabel::Name);
+    assert_eq!(metrics.labels.disabled, vec!["model", "method"]);
+    assert_eq!(metrics.duration_buckets, Some(vec![0.1, 0.5, 1.0, 5.0]));
+
+    // 无效的标签名称
+    let mut invalid = metrics.clone();
+    invalid.labels.disabled = vec!["status-class".to_string()];
+    assert!(invalid.validate().is_err());
+
+    // 分桶为空、不是严格递增、包含非正数
+    for buckets in [vec![], vec![1.0, 0.5], vec![0.5, 0.5], vec![0.0, 1.0]] {
+        let mut invalid = metrics.clone();
+        invalid.duration_buckets = Some(buckets.clone());
+        assert!(
+            invalid.validate().is_err(),
+            "buckets {:?} should be rejected",
+            buckets
+        );
+    }
+    let mut invalid = metrics.clone();
+    invalid.duration_buckets = Some((1..=65).map(f64::from).collect());
+    assert!(invalid.validate().is_err());
+}
+
+#[test]
+fn test_admin_metrics_alerts() {
+    let yaml = r#"
+alerts:
+  webhooks:
+    - url: "https://hooks.example.com/llmproxy"
+      headers:
+        Authorization: "Bearer secret"
+  rules:
+    - name: high_error_rate
+      metric: error_rate
+      threshold: 0.2
+      min_requests: 20
+    - name: slow_upstream
+      metric: latency
+      group: test_group
+      threshold: 30
+      window: 600
+"#;
+    let metrics: llmproxy::config::MetricsConfig = serde_yaml::from_str(yaml).unwrap();
+    assert!(metrics.validate().is_ok());
+    let alerts = metrics.alerts.clone().unwrap();
+    assert_eq!(alerts.interval, 15);
+    assert_eq!(alerts.webhooks.len(), 1);
+    assert_eq!(alerts.rules[0].metric, AlertMetric::ErrorRate);
+    assert_eq!(alerts.rules[0].window, 300);
+    assert_eq!(alerts.rules[0].min_requests, 20);
+    assert!(alerts.rules[0].group.is_none());
+    assert_eq!(alerts.rules[1].metric, AlertMetric::Latency);
+    assert_eq!(alerts.rules[1].quantile, 0.95);
+    assert_eq!(alerts.rules[1].min_requests, 1);
+
+    // 错误率阈值超出范围、耗时阈值不是正数、分位数超出范围、窗口过短、规则名称重复
+    let invalid = |update: fn(&mut AlertsConfig)| {
+        let mut alerts = alerts.clone();
+        update(&mut alerts);
+        alerts.validate().is_err()
+    };
+    assert!(invalid(|a| a.rules[0].threshold = 1.5));
+    assert!(invalid(|a| a.rules[1].threshold = 0.0));
+    assert!(invalid(|a| a.rules[1].quantile = 1.0));
+    assert!(invalid(|a| a.rules[0].window = 1));
+    assert!(invalid(|a| a.rules[1].name = "high_error_rate".to_string()));
+    assert!(invalid(|a| a.rules.clear()));
+    assert!(invalid(
+        |a| a.webhooks[0].url = "hooks.example.com".to_string()
+    ));
+
+    // 规则引用的上游组必须存在
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            c.http_server.as_mut().unwrap().admin.metrics = metrics.clone();
+        })
+        .build();
+    assert!(config.validate().is_ok());
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            let mut metrics = metrics.clone();
+            metrics.alerts.as_mut().unwrap().rules[1].group = Some("missing_group".to_string());
+            c.http_server.as_mut().unwrap().admin.metrics = metrics;
+        })
+        .build();
+    assert!(config.validate().is_err());
+}
@@ -4,8 +4,9 @@
 
 use llmproxy::config::{
     AdminConfig, BalanceConfig, BalanceStrategy, Config, ForwardConfig, HttpClientConfig,
-    RateLimitConfig, TimeoutConfig, UpstreamConfig, UpstreamGroupConfig, UpstreamRef,
+    RateLimitConfig, RateLimitKey, TimeoutConfig, UpstreamConfig, UpstreamGroupConfig, UpstreamRef,
 };
+use llmproxy::r#const::response_buffer;
 
 // A builder for creating `Config` instances for testing purposes.
 pub struct TestConfigBuilder {
@@ -23,6 +24,20 @@ impl TestConfigBuilder {
             auth: None,
             headers: vec![],
             breaker: None,
+            adaptive: None,
+            hint: None,
+            enabled: true,
+            proxy: true,
+            path: None,
+            query_params: vec![],
+            body_transform: None,
+            dialect: None,
+            stream_normalize: None,
+            agent: None,
+            default_headers: Default::default(),
+            pricing: vec![],
+            dns: None,
+            warmup: None,
         };
 
         let upstream_ref = UpstreamRef {
@@ -37,6 +52,8 @@ impl TestConfigBuilder {
                 strategy: BalanceStrategy::RoundRobin,
             },
             http_client: HttpClientConfig::default(),
+            sticky: None,
+            discovery: None,
         };
 
         let forward_config = ForwardConfig {
@@ -47,9 +64,40 @@ impl TestConfigBuilder {
             ratelimit: Some(RateLimitConfig {
                 per_second: 100,
                 burst: 200,
+                key: RateLimitKey::Ip,
+                header: None,
+                redis: None,
+            }),
+            timeout: Some(TimeoutConfig {
+                connect: 5,
+                request: 300,
+                header: None,
             }),
-            timeout: Some(TimeoutConfig { connect: 5 }),
             routing: None,
+            limits: None,
+            count_tokens: false,
+            budget: None,
+            token_limit: None,
+            max_concurrent: None,
+            queue: None,
+            sse_heartbeat: None,
+            coalesce: false,
+            cache: None,
+            audit_sink: None,
+            pii_redaction: None,
+            policy: None,
+            plugins: vec![],
+            middlewares: None,
+            listener: None,
+            websocket: None,
+            compression: None,
+            access_log: None,
+            error_response: None,
+            response_buffer_limit: response_buffer::DEFAULT_LIMIT,
+            allowed_methods: vec![],
+            allowed_paths: vec![],
+            request_headers: None,
+            breaker: None,
         };
 
         let config = Config {
@@ -58,11 +106,21 @@ impl TestConfigBuilder {
                 admin: AdminConfig {
                     port: 9000,
                     address: "127.0.0.1".to_string(),
-                    timeout: Some(TimeoutConfig { connect: 5 }),
+                    timeout: Some(TimeoutConfig {
+                        connect: 5,
+                        request: 300,
+                        header: None,
+                    }),
+                    audit: llmproxy::config::AuditConfig::default(),
+                    dashboard: true,
+                    metrics: llmproxy::config::MetricsConfig::default(),
+                    tls: None,
                 },
+                load_shedding: None,
             }),
             upstreams: vec![upstream_config],
             upstream_groups: vec![group_config],
+            models: vec![],
         };
 
         Self { config }
@@ -3,7 +3,7 @@
 // This module contains tests for config file loading and parsing.
 
 use super::common::{create_temp_config_file, TestConfigBuilder};
-use llmproxy::config::Config;
+use llmproxy::config::{Config, ConfigFormat};
 use std::fs::File;
 use std::io::Write;
 use tempfile::tempdir;
@@ -88,3 +88,225 @@ fn test_config_without_http_server() {
     // Verify http_server is still None after deserialization
     assert!(loaded_config.http_server.is_none());
 }
+
+#[test]
+fn test_config_json_schema() {
+    let schema = llmproxy::config::json_schema().unwrap();
+    assert_eq!(
+        schema["$schema"],
+        "https://json-schema.org/draft/2020-12/schema"
+    );
+    assert!(schema["properties"]["upstreams"].is_object());
+
+    // 嵌套的配置类型放在 $defs 中，引用全部指向 $defs
+    let defs = schema["$defs"].as_object().unwrap();
+    assert!(defs.contains_key("UpstreamConfig"));
+    assert!(defs.contains_key("ForwardConfig"));
+    let text = schema.to_string();
+    assert!(!text.contains("#/components/schemas/"));
+    for (name, _) in defs {
+        assert!(text.contains(&format!("\"#/$defs/{}\"", name)) || name == "Config");
+    }
+}
+
+#[test]
+fn test_config_from_directory() {
+    let dir = tempdir().unwrap();
+    let write = |name: &str, content: &str| std::fs::write(dir.path().join(name), content).unwrap();
+
+    write(
+        "00-server.yaml",
+        r#"
+http_server:
+  forwards:
+    - name: chat
+      port: 3000
+      default_group: openai
+  admin:
+    port: 9000
+"#,
+    );
+    write(
+        "20-anthropic.yml",
+        r#"
+upstreams:
+  - name: anthropic
+    url: https://api.anthropic.com/v1
+upstream_groups:
+  - name: anthropic
+    upstreams:
+      - name: anthropic
+http_server:
+  forwards:
+    - name: claude
+      port: 3001
+      default_group: anthropic
+"#,
+    );
+    write(
+        "10-openai.yaml",
+        r#"
+upstreams:
+  - name: openai
+    url: https://api.openai.com/v1
+upstream_groups:
+  - name: openai
+    upstreams:
+      - name: openai
+"#,
+    );
+    // 隐藏文件、其他扩展名和子目录不参与合并
+    write(
+        ".backup.yaml",
+        "upstreams: [{name: hidden, url: http://localhost}]",
+    );
+    write("README.md", "not yaml: [");
+    std::fs::create_dir(dir.path().join("..data")).unwrap();
+
+    // 按文件名顺序拼接
+    let config = Config::from_file(dir.path()).unwrap();
+    let names = |items: Vec<&str>| items.join(",");
+    assert_eq!(
+        names(config.upstreams.iter().map(|u| u.name.as_str()).collect()),
+        "openai,anthropic"
+    );
+    assert_eq!(
+        names(
+            config
+                .upstream_groups
+                .iter()
+                .map(|g| g.name.as_str())
+                .collect()
+        ),
+        "openai,anthropic"
+    );
+    let http_server = config.http_server.unwrap();
+    assert_eq!(
+        names(
+            http_server
+                .forwards
+                .iter()
+                .map(|f| f.name.as_str())
+                .collect()
+        ),
+        "chat,claude"
+    );
+    assert_eq!(http_server.admin.port, 9000);
+
+    // 名称在多个文件中重复
+    write(
+        "30-duplicate.yaml",
+        "upstreams: [{name: openai, url: http://localhost}]",
+    );
+    let error = Config::from_file(dir.path()).unwrap_err().to_string();
+    assert!(
+        error.contains("upstreams.openai is defined in both 10-openai.yaml and 30-duplicate.yaml"),
+        "{}",
+        error
+    );
+
+    // 非列表的配置段只能定义一次
+    write("30-duplicate.yaml", "http_server: {admin: {port: 9001}}");
+    let error = Config::from_file(dir.path()).unwrap_err().to_string();
+    assert!(
+        error.contains("http_server.admin is defined in both 00-server.yaml and 30-duplicate.yaml"),
+        "{}",
+        error
+    );
+
+    // 没有配置文件的目录
+    let empty = tempdir().unwrap();
+    assert!(Config::from_file(empty.path())
+        .unwrap_err()
+        .to_string()
+        .contains("No configuration files"));
+}
+
+#[test]
+fn test_config_from_toml_and_json() {
+    let dir = tempdir().unwrap();
+    let toml_path = dir.path().join("config.toml");
+    std::fs::write(
+        &toml_path,
+        r#"
+[[upstreams]]
+name = "openai"
+url = "https://api.openai.com/v1"
+
+[upstreams.auth]
+type = "bearer"
+token = "sk-test"
+
+[[upstream_groups]]
+name = "openai"
+upstreams = [{ name = "openai", weight = 2 }]
+
+[upstream_groups.balance]
+strategy = "weighted_roundrobin"
+
+[[http_server.forwards]]
+name = "chat"
+port = 3000
+default_group = "openai"
+
+[http_server.admin]
+port = 9001
+"#,
+    )
+    .unwrap();
+
+    let config = Config::from_file(&toml_path).unwrap();
+    assert_eq!(config.upstreams[0].name, "openai");
+    assert_eq!(config.upstream_groups[0].upstreams[0].weight, 2);
+    let http_server = config.http_server.as_ref().unwrap();
+    assert_eq!(http_server.forwards[0].default_group, "openai");
+    assert_eq!(http_server.admin.port, 9001);
+
+    // 同一配置的 JSON 格式得到相同的结果
+    let json_path = dir.path().join("config.json");
+    std::fs::write(&json_path, serde_json::to_string(&config).unwrap()).unwrap();
+    let from_json = Config::from_file(&json_path).unwrap();
+    assert_eq!(
+        serde_json::to_value(&from_json).unwrap(),
+        serde_json::to_value(&config).unwrap()
+    );
+
+    // 无法识别的扩展名按 YAML 解析，可以通过 --format 指定格式
+    let conf_path = dir.path().join("llmproxy.conf");
+    std::fs::copy(&toml_path, &conf_path).unwrap();
+    assert!(Config::from_file(&conf_path).is_err());
+    let config = Config::from_file_with_format(&conf_path, Some(ConfigFormat::Toml)).unwrap();
+    assert_eq!(config.upstreams[0].name, "openai");
+
+    // 解析错误包含文件和格式
+    std::fs::write(&toml_path, "upstreams = [").unwrap();
+    let error = Config::from_file(&toml_path).unwrap_err().to_string();
+    assert!(error.contains("config.toml\" (toml)"), "{}", error);
+}
+
+#[test]
+fn test_config_directory_with_mixed_formats() {
+    let dir = tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("00-server.toml"),
+        r#"
+[[http_server.forwards]]
+name = "chat"
+port = 3000
+default_group = "openai"
+"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("10-openai.json"),
+        r#"{
+  "upstreams": [{"name": "openai", "url": "https://api.openai.com/v1"}],
+  "upstream_groups": [{"name": "openai", "upstreams": [{"name": "openai"}]}]
+}"#,
+    )
+    .unwrap();
+
+    let config = Config::from_file(dir.path()).unwrap();
+    assert_eq!(config.upstreams[0].name, "openai");
+    assert_eq!(config.http_server.unwrap().forwards[0].name, "chat");
+}
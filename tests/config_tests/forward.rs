@@ -2,6 +2,13 @@
 
 // This module contains tests for the ForwardConfig struct.
 use super::common::{create_temp_config_file, TestConfigBuilder};
+use llmproxy::config::{
+    AccessLogConfig, AccessLogFormat, AuditSinkConfig, BudgetConfig, CacheBackend, CacheConfig,
+    ClientBudgetConfig, CompressionAlgorithm, CompressionConfig, FairQueueConfig, ListenerConfig,
+    MiddlewareStage, ParamLimitAction, ParamLimitsConfig, PiiRedactionConfig, PluginConfig,
+    PolicyConfig, PolicyFailMode, PolicyPayload, QueueConfig, QueueTierConfig, RateLimitConfig,
+    RateLimitKey, RequestHeadersConfig, TokenLimitConfig, WebSocketConfig,
+};
 use validator::Validate;
 
 #[test]
@@ -23,3 +30,804 @@ fn test_forward_with_none_options() {
     assert!(forward.ratelimit.is_none());
     assert!(forward.timeout.is_none());
 }
+
+#[test]
+fn test_forward_validation_param_limits() {
+    let validate = |limits: ParamLimitsConfig| {
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.http_server.as_mut().unwrap().forwards[0].limits = Some(limits);
+            })
+            .build()
+            .validate()
+    };
+
+    assert!(validate(ParamLimitsConfig::default())
+        .unwrap_err()
+        .to_string()
+        .contains("at least one limit"));
+    assert!(validate(ParamLimitsConfig {
+        max_tokens: Some(4096),
+        action: ParamLimitAction::Reject,
+        ..Default::default()
+    })
+    .is_ok());
+    assert!(validate(ParamLimitsConfig {
+        max_prompt_tokens: Some(32000),
+        ..Default::default()
+    })
+    .is_ok());
+    assert!(validate(ParamLimitsConfig {
+        temperature: Some(-1.0),
+        ..Default::default()
+    })
+    .unwrap_err()
+    .to_string()
+    .contains("temperature must be a non-negative number"));
+}
+
+#[test]
+fn test_forward_validation_budget() {
+    let validate = |budget: BudgetConfig| {
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.http_server.as_mut().unwrap().forwards[0].budget = Some(budget);
+            })
+            .build()
+            .validate()
+    };
+    let client = |id: &str, daily: Option<f64>| ClientBudgetConfig {
+        id: id.to_string(),
+        daily,
+        monthly: None,
+    };
+
+    // 未配置请求头时使用 Authorization
+    let budget: BudgetConfig = serde_yaml::from_str("daily: 10.0\nmonthly: 200.0").unwrap();
+    assert_eq!(budget.header, "authorization");
+    assert!(validate(budget.clone()).is_ok());
+
+    // 只配置指定客户端的预算即可，未指定的预算使用默认值
+    let budget = BudgetConfig {
+        clients: vec![client("team-a", Some(50.0))],
+        ..budget
+    };
+    assert_eq!(budget.limits("team-a"), (Some(50.0), Some(200.0)));
+    assert_eq!(budget.limits("team-b"), (Some(10.0), Some(200.0)));
+    assert!(validate(BudgetConfig {
+        daily: None,
+        monthly: None,
+        ..budget.clone()
+    })
+    .is_ok());
+
+    assert!(validate(BudgetConfig {
+        daily: None,
+        monthly: None,
+        clients: vec![],
+        ..budget.clone()
+    })
+    .unwrap_err()
+    .to_string()
+    .contains("Budget requires at least one daily or monthly limit"));
+    assert!(validate(BudgetConfig {
+        header: "bad header".to_string(),
+        ..budget.clone()
+    })
+    .unwrap_err()
+    .to_string()
+    .contains("Invalid budget header name"));
+    assert!(validate(BudgetConfig {
+        daily: Some(-1.0),
+        ..budget.clone()
+    })
+    .unwrap_err()
+    .to_string()
+    .contains("Budget limits must be non-negative numbers"));
+    assert!(validate(BudgetConfig {
+        clients: vec![client("team-a", None), client("team-a", Some(1.0))],
+        ..budget.clone()
+    })
+    .unwrap_err()
+    .to_string()
+    .contains("Duplicate client budget: team-a"));
+    assert!(validate(BudgetConfig {
+        clients: vec![client("", Some(1.0))],
+        ..budget
+    })
+    .unwrap_err()
+    .to_string()
+    .contains("Client budget id cannot be empty"));
+}
+
+#[test]
+fn test_forward_validation_ratelimit_key() {
+    let validate = |ratelimit: RateLimitConfig| {
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.http_server.as_mut().unwrap().forwards[0].ratelimit = Some(ratelimit);
+            })
+            .build()
+            .validate()
+    };
+
+    // 未配置限流键时按客户端 IP 限流
+    let ratelimit: RateLimitConfig = serde_yaml::from_str("per_second: 10\nburst: 20").unwrap();
+    assert_eq!(ratelimit.key, RateLimitKey::Ip);
+    assert!(validate(ratelimit.clone()).is_ok());
+
+    let ratelimit: RateLimitConfig = serde_yaml::from_str("key: api_key").unwrap();
+    assert_eq!(ratelimit.key, RateLimitKey::ApiKey);
+    assert!(validate(ratelimit.clone()).is_ok());
+
+    let ratelimit = RateLimitConfig {
+        key: RateLimitKey::Header,
+        header: Some("x-tenant-id".to_string()),
+        ..ratelimit
+    };
+    assert!(validate(ratelimit.clone()).is_ok());
+    assert!(validate(RateLimitConfig {
+        header: None,
+        ..ratelimit.clone()
+    })
+    .unwrap_err()
+    .to_string()
+    .contains("requires a header name"));
+    assert!(validate(RateLimitConfig {
+        header: Some("bad header".to_string()),
+        ..ratelimit
+    })
+    .unwrap_err()
+    .to_string()
+    .contains("Invalid rate limit header name"));
+
+    // 配置 Redis 时多个实例共享限额，地址无效时验证失败
+    let ratelimit: RateLimitConfig =
+        serde_yaml::from_str("per_second: 10\nredis:\n  url: redis://127.0.0.1:6379/0").unwrap();
+    assert_eq!(ratelimit.redis.as_ref().unwrap().key_prefix, "llmproxy:");
+    assert!(validate(ratelimit.clone()).is_ok());
+    let mut invalid = ratelimit;
+    invalid.redis.as_mut().unwrap().url = "http://127.0.0.1:6379".to_string();
+    assert!(validate(invalid)
+        .unwrap_err()
+        .to_string()
+        .contains("Invalid Redis URL"));
+}
+
+#[test]
+fn test_forward_validation_token_limit() {
+    let validate = |token_limit: TokenLimitConfig| {
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.http_server.as_mut().unwrap().forwards[0].token_limit = Some(token_limit);
+            })
+            .build()
+            .validate()
+    };
+    let token_limit: TokenLimitConfig = serde_yaml::from_str(
+        "tokens_per_minute: 100000\nclients:\n  - id: sk-vip\n    tokens_per_minute: 1000000",
+    )
+    .unwrap();
+    assert_eq!(token_limit.key, RateLimitKey::Ip);
+    assert!(validate(token_limit.clone()).is_ok());
+
+    // 生效的限额依次为指定客户端、匹配的路由规则、默认值
+    assert_eq!(token_limit.limit(Some(500_000), Some("sk-vip")), 1_000_000);
+    assert_eq!(token_limit.limit(Some(500_000), Some("sk-a")), 500_000);
+    assert_eq!(token_limit.limit(None, Some("sk-a")), 100_000);
+
+    assert!(validate(TokenLimitConfig {
+        tokens_per_minute: 0,
+        ..token_limit.clone()
+    })
+    .is_err());
+    assert!(validate(TokenLimitConfig {
+        key: RateLimitKey::Header,
+        ..token_limit.clone()
+    })
+    .unwrap_err()
+    .to_string()
+    .contains("requires a header name"));
+
+    // 配置 Redis 时多个实例共享令牌桶
+    let redis: TokenLimitConfig = serde_yaml::from_str(
+        "tokens_per_minute: 100000\nredis:\n  url: rediss://cache.internal:6380\n  key_prefix: \"gw:\"",
+    )
+    .unwrap();
+    assert_eq!(redis.redis.as_ref().unwrap().key_prefix, "gw:");
+    assert!(validate(redis).is_ok());
+    assert!(validate(TokenLimitConfig {
+        redis: serde_yaml::from_str("url: 127.0.0.1:6379").unwrap(),
+        ..token_limit
+    })
+    .unwrap_err()
+    .to_string()
+    .contains("Invalid Redis URL"));
+}
+
+#[test]
+fn test_forward_validation_concurrency() {
+    let validate = |max_concurrent: Option<u32>, queue: Option<QueueConfig>| {
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                let forward = &mut c.http_server.as_mut().unwrap().forwards[0];
+                forward.max_concurrent = max_concurrent;
+                forward.queue = queue;
+            })
+            .build()
+            .validate()
+    };
+
+    // 未配置的队列参数使用默认值
+    let queue: QueueConfig = serde_yaml::from_str("{}").unwrap();
+    assert_eq!(queue.max_depth, 100);
+    assert_eq!(queue.max_wait_ms, 30_000);
+
+    assert!(validate(None, None).is_ok());
+    assert!(validate(Some(64), None).is_ok());
+    assert!(validate(Some(64), Some(queue.clone())).is_ok());
+    assert!(validate(Some(0), None).is_err());
+    assert!(validate(
+        Some(64),
+        Some(QueueConfig {
+            max_depth: 0,
+            ..queue.clone()
+        })
+    )
+    .is_err());
+    assert!(validate(None, Some(queue))
+        .unwrap_err()
+        .to_string()
+        .contains("configures a queue without max_concurrent"));
+}
+
+#[test]
+fn test_forward_validation_request_headers() {
+    let validate = |request_headers: RequestHeadersConfig| {
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.http_server.as_mut().unwrap().forwards[0].request_headers = Some(request_headers);
+            })
+            .build()
+            .validate()
+    };
+
+    let config: RequestHeadersConfig =
+        serde_yaml::from_str("strip: [Authorization, x-b3-traceid]\nmax_count: 64").unwrap();
+    assert_eq!(config.max_bytes, None);
+    assert!(validate(config.clone()).is_ok());
+    assert!(validate(RequestHeadersConfig {
+        strip: vec!["bad header".to_string()],
+        ..config.clone()
+    })
+    .unwrap_err()
+    .to_string()
+    .contains("Invalid request header name to strip: bad header"));
+    assert!(validate(RequestHeadersConfig {
+        max_count: Some(0),
+        ..config.clone()
+    })
+    .is_err());
+    assert!(validate(RequestHeadersConfig {
+        max_bytes: Some(16),
+        ..config
+    })
+    .is_err());
+}
+
+#[test]
+fn test_forward_validation_allowlist() {
+    let validate = |methods: &[&str], paths: &[&str]| {
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                let forward = &mut c.http_server.as_mut().unwrap().forwards[0];
+                forward.allowed_methods = methods.iter().map(|m| m.to_string()).collect();
+                forward.allowed_paths = paths.iter().map(|p| p.to_string()).collect();
+            })
+            .build()
+            .validate()
+    };
+
+    assert!(validate(&[], &[]).is_ok());
+    assert!(validate(&["POST", "get"], &["/v1/chat/completions", "/v1/**"]).is_ok());
+    assert!(validate(&["PO ST"], &[])
+        .unwrap_err()
+        .to_string()
+        .contains("Invalid allowed method 'PO ST'"));
+    assert!(validate(&[], &["v1/*"])
+        .unwrap_err()
+        .to_string()
+        .contains("Invalid allowed path 'v1/*'"));
+}
+
+#[test]
+fn test_forward_validation_fair_queue() {
+    let validate = |fair: FairQueueConfig| {
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                let forward = &mut c.http_server.as_mut().unwrap().forwards[0];
+                forward.max_concurrent = Some(8);
+                forward.queue = Some(QueueConfig {
+                    max_depth: 100,
+                    max_wait_ms: 1000,
+                    fair: Some(fair),
+                });
+            })
+            .build()
+            .validate()
+    };
+    let tier = |name: &str, weight: u32, clients: &[&str]| QueueTierConfig {
+        name: name.to_string(),
+        weight,
+        clients: clients.iter().map(|c| c.to_string()).collect(),
+    };
+
+    let fair: FairQueueConfig = serde_yaml::from_str(
+        "key: api_key\ntiers:\n  - name: interactive\n    weight: 10\n    clients: [sk-a, sk-b]",
+    )
+    .unwrap();
+    assert_eq!(fair.default_weight, 1);
+    assert_eq!(fair.weight(Some("sk-b")), 10);
+    assert_eq!(fair.weight(Some("sk-c")), 1);
+    assert_eq!(fair.weight(None), 1);
+    assert!(validate(fair.clone()).is_ok());
+
+    assert!(validate(FairQueueConfig {
+        default_weight: 0,
+        ..fair.clone()
+    })
+    .is_err());
+    assert!(validate(FairQueueConfig {
+        tiers: vec![tier("batch", 0, &[])],
+        ..fair.clone()
+    })
+    .is_err());
+    assert!(validate(FairQueueConfig {
+        tiers: vec![tier("batch", 1, &[]), tier("batch", 2, &[])],
+        ..fair.clone()
+    })
+    .unwrap_err()
+    .to_string()
+    .contains("Duplicate queue tier"));
+    assert!(validate(FairQueueConfig {
+        tiers: vec![
+            tier("batch", 1, &["sk-a"]),
+            tier("interactive", 10, &["sk-a"])
+        ],
+        ..fair
+    })
+    .unwrap_err()
+    .to_string()
+    .contains("Client belongs to multiple queue tiers"));
+}
+
+#[test]
+fn test_forward_validation_sse_heartbeat() {
+    let validate = |sse_heartbeat: Option<u64>| {
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.http_server.as_mut().unwrap().forwards[0].sse_heartbeat = sse_heartbeat;
+            })
+            .build()
+            .validate()
+    };
+
+    assert!(validate(None).is_ok());
+    assert!(validate(Some(15)).is_ok());
+    assert!(validate(Some(0)).is_err());
+    assert!(validate(Some(301)).is_err());
+}
+
+#[test]
+fn test_forward_validation_cache() {
+    let validate = |cache: CacheConfig| {
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.http_server.as_mut().unwrap().forwards[0].cache = Some(cache);
+            })
+            .build()
+            .validate()
+    };
+
+    // 未配置的字段使用默认值
+    let cache: CacheConfig = serde_yaml::from_str("ttl: 60").unwrap();
+    assert_eq!(cache.backend, CacheBackend::Memory);
+    assert_eq!(cache.max_entries, 1000);
+    assert!(validate(cache.clone()).is_ok());
+    assert!(validate(CacheConfig {
+        ttl: 0,
+        ..cache.clone()
+    })
+    .is_err());
+
+    // Redis 后端需要配置 Redis 地址
+    let redis: CacheConfig = serde_yaml::from_str(
+        "backend: redis\nredis:\n  url: redis://127.0.0.1:6379/0\n  password: secret",
+    )
+    .unwrap();
+    assert_eq!(redis.redis.as_ref().unwrap().key_prefix, "llmproxy:");
+    assert!(validate(redis.clone()).is_ok());
+    assert!(validate(CacheConfig {
+        redis: None,
+        ..redis.clone()
+    })
+    .unwrap_err()
+    .to_string()
+    .contains("Redis cache backend requires redis configuration"));
+
+    let mut invalid = redis;
+    invalid.redis.as_mut().unwrap().url = "http://127.0.0.1:6379".to_string();
+    assert!(validate(invalid)
+        .unwrap_err()
+        .to_string()
+        .contains("Invalid Redis URL"));
+}
+
+#[test]
+fn test_forward_validation_audit_sink() {
+    let validate = |audit_sink: AuditSinkConfig| {
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.http_server.as_mut().unwrap().forwards[0].audit_sink = Some(audit_sink);
+            })
+            .build()
+            .validate()
+    };
+
+    // 未配置的字段使用默认值
+    let file: AuditSinkConfig =
+        serde_yaml::from_str("file: /var/log/llmproxy/audit.jsonl").unwrap();
+    assert_eq!(file.max_body_bytes, 64 * 1024);
+    assert_eq!(file.buffer, 10_000);
+    assert!(file.redact_fields.is_empty());
+    assert!(validate(file.clone()).is_ok());
+    assert!(validate(AuditSinkConfig {
+        buffer: 0,
+        ..file.clone()
+    })
+    .is_err());
+    assert!(validate(AuditSinkConfig {
+        file: Some(" ".to_string()),
+        ..file.clone()
+    })
+    .unwrap_err()
+    .to_string()
+    .contains("Audit sink file cannot be empty"));
+
+    // 文件和 HTTP 接收端必须且只能配置一个
+    let http: AuditSinkConfig =
+        serde_yaml::from_str("url: https://audit.example.com/ingest\ntoken: secret").unwrap();
+    assert!(validate(http.clone()).is_ok());
+    assert!(validate(AuditSinkConfig {
+        file: file.file.clone(),
+        ..http.clone()
+    })
+    .unwrap_err()
+    .to_string()
+    .contains("exactly one of file or url"));
+    assert!(validate(AuditSinkConfig {
+        url: None,
+        ..http.clone()
+    })
+    .unwrap_err()
+    .to_string()
+    .contains("exactly one of file or url"));
+    assert!(validate(AuditSinkConfig {
+        url: Some("ftp://audit.example.com".to_string()),
+        ..http
+    })
+    .unwrap_err()
+    .to_string()
+    .contains("Invalid audit sink URL"));
+}
+
+#[test]
+fn test_forward_validation_pii_redaction() {
+    let validate = |yaml: &str| {
+        let pii: PiiRedactionConfig = serde_yaml::from_str(yaml).unwrap();
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.http_server.as_mut().unwrap().forwards[0].pii_redaction = Some(pii);
+            })
+            .build()
+            .validate()
+    };
+
+    assert!(validate("detectors: [email, phone, credit_card]").is_ok());
+    assert!(
+        validate("patterns: [{name: ticket, regex: 'TICKET-\\d+', replacement: '[TICKET]'}]")
+            .is_ok()
+    );
+
+    // 至少配置一个内置检测器或自定义规则
+    assert!(validate("detectors: []")
+        .unwrap_err()
+        .to_string()
+        .contains("at least one detector or pattern"));
+    // 检测器和规则名称不能重复
+    assert!(validate("detectors: [email, email]")
+        .unwrap_err()
+        .to_string()
+        .contains("Duplicate PII detector"));
+    assert!(
+        validate("detectors: [email]\npatterns: [{name: email, regex: 'x'}]")
+            .unwrap_err()
+            .to_string()
+            .contains("Duplicate PII pattern name: email")
+    );
+    // 正则表达式必须合法
+    assert!(validate("patterns: [{name: broken, regex: '(['}]")
+        .unwrap_err()
+        .to_string()
+        .contains("Invalid PII pattern regex 'broken'"));
+    assert!(serde_yaml::from_str::<PiiRedactionConfig>("detectors: [ssn]").is_err());
+}
+
+#[test]
+fn test_forward_validation_policy() {
+    let validate = |policy: PolicyConfig| {
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.http_server.as_mut().unwrap().forwards[0].policy = Some(policy);
+            })
+            .build()
+            .validate()
+    };
+
+    // 未配置的字段使用默认值，策略服务不可用时默认拒绝请求
+    let policy: PolicyConfig =
+        serde_yaml::from_str("url: https://policy.example.com/check").unwrap();
+    assert_eq!(policy.timeout_ms, 2_000);
+    assert_eq!(policy.fail_mode, PolicyFailMode::Closed);
+    assert_eq!(policy.payload, PolicyPayload::Request);
+    assert!(policy.token.is_none());
+    assert!(validate(policy.clone()).is_ok());
+
+    assert!(validate(PolicyConfig {
+        url: "ftp://policy.example.com".to_string(),
+        ..policy.clone()
+    })
+    .unwrap_err()
+    .to_string()
+    .contains("Invalid policy service URL"));
+    assert!(validate(PolicyConfig {
+        timeout_ms: 0,
+        ..policy.clone()
+    })
+    .is_err());
+    assert!(validate(PolicyConfig {
+        timeout_ms: 60_001,
+        ..policy
+    })
+    .is_err());
+}
+
+#[test]
+fn test_forward_validation_plugins() {
+    let validate = |plugins: Vec<PluginConfig>| {
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.http_server.as_mut().unwrap().forwards[0].plugins = plugins;
+            })
+            .build()
+            .validate()
+    };
+
+    // 未配置的字段使用默认值，插件出错时默认拒绝请求
+    let plugin: PluginConfig =
+        serde_yaml::from_str("name: guard\npath: /etc/llmproxy/guard.wasm").unwrap();
+    assert_eq!(plugin.fuel, 100_000_000);
+    assert_eq!(plugin.max_memory_bytes, 64 * 1024 * 1024);
+    assert_eq!(plugin.fail_mode, PolicyFailMode::Closed);
+    assert!(validate(vec![plugin.clone()]).is_ok());
+
+    assert!(validate(vec![PluginConfig {
+        name: String::new(),
+        ..plugin.clone()
+    }])
+    .unwrap_err()
+    .to_string()
+    .contains("Plugin name cannot be empty"));
+    assert!(validate(vec![PluginConfig {
+        path: String::new(),
+        ..plugin.clone()
+    }])
+    .unwrap_err()
+    .to_string()
+    .contains("Plugin path cannot be empty"));
+    assert!(validate(vec![PluginConfig {
+        fuel: 10,
+        ..plugin.clone()
+    }])
+    .is_err());
+    assert!(validate(vec![PluginConfig {
+        max_memory_bytes: 1024,
+        ..plugin.clone()
+    }])
+    .is_err());
+
+    // 同一转发服务中的插件名称不能重复
+    assert!(validate(vec![plugin.clone(), plugin])
+        .unwrap_err()
+        .to_string()
+        .contains("Duplicate plugin name found in forward"));
+}
+
+#[test]
+fn test_forward_validation_middlewares() {
+    let cache: CacheConfig = serde_yaml::from_str("ttl: 60").unwrap();
+    let validate = |middlewares: Option<Vec<MiddlewareStage>>| {
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                let forward = &mut c.http_server.as_mut().unwrap().forwards[0];
+                forward.cache = Some(cache.clone());
+                forward.middlewares = middlewares;
+            })
+            .build()
+            .validate()
+    };
+
+    // 未配置时按默认顺序执行
+    assert!(validate(None).is_ok());
+    let stages: Vec<MiddlewareStage> =
+        serde_yaml::from_str("[cache, pii_redaction, audit_sink]").unwrap();
+    assert_eq!(
+        stages,
+        vec![
+            MiddlewareStage::Cache,
+            MiddlewareStage::PiiRedaction,
+            MiddlewareStage::AuditSink
+        ]
+    );
+    // 列出未配置的功能时不执行，默认配置了限流，需要列出 rate_limit
+    assert!(validate(Some(stages.clone()))
+        .unwrap_err()
+        .to_string()
+        .contains("configures rate_limit but does not list it in middlewares"));
+    let stages = [vec![MiddlewareStage::RateLimit], stages].concat();
+    assert!(validate(Some(stages)).is_ok());
+    assert!(serde_yaml::from_str::<Vec<MiddlewareStage>>("[auth]").is_err());
+
+    // 阶段不能重复
+    assert!(
+        validate(Some(vec![MiddlewareStage::Cache, MiddlewareStage::Cache]))
+            .unwrap_err()
+            .to_string()
+            .contains("Duplicate middleware stage found in forward")
+    );
+    // 已配置的功能必须列出
+    assert!(validate(Some(vec![MiddlewareStage::Policy]))
+        .unwrap_err()
+        .to_string()
+        .contains("configures cache but does not list it in middlewares"));
+
+    // 入口阶段可以调整顺序，但必须排在请求体阶段之前
+    let stages: Vec<MiddlewareStage> =
+        serde_yaml::from_str("[rate_limit, access_log, compression, cache]").unwrap();
+    assert_eq!(
+        stages[..3],
+        [
+            MiddlewareStage::RateLimit,
+            MiddlewareStage::AccessLog,
+            MiddlewareStage::Compression
+        ]
+    );
+    assert!(validate(Some(stages)).is_ok());
+    assert!(validate(Some(vec![
+        MiddlewareStage::Cache,
+        MiddlewareStage::AccessLog
+    ]))
+    .unwrap_err()
+    .to_string()
+    .contains("lists access_log after cache"));
+}
+
+#[test]
+fn test_forward_validation_listener() {
+    let validate = |yaml: &str| {
+        let listener: ListenerConfig = serde_yaml::from_str(yaml).unwrap();
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.http_server.as_mut().unwrap().forwards[0].listener = Some(listener);
+            })
+            .build()
+            .validate()
+    };
+
+    // 默认值与之前的硬编码行为一致
+    let listener: ListenerConfig = serde_yaml::from_str("{}").unwrap();
+    assert_eq!(listener.backlog, 65535);
+    assert!(!listener.http2);
+    assert!(!listener.tcp_nodelay);
+    assert!(validate("{}").is_ok());
+    assert!(validate(
+        "backlog: 1024\ntcp_nodelay: true\nhttp2: true\nmax_concurrent_streams: 100\nmax_header_bytes: 65536\nidle_timeout: 60"
+    )
+    .is_ok());
+
+    // 最大并发流数需要开启 http2
+    assert!(validate("max_concurrent_streams: 100")
+        .unwrap_err()
+        .to_string()
+        .contains("Listener max_concurrent_streams requires http2"));
+
+    // 超出范围
+    assert!(validate("backlog: 0").is_err());
+    assert!(validate("backlog: 65536").is_err());
+    assert!(validate("http2: true\nmax_concurrent_streams: 0").is_err());
+    assert!(validate("max_header_bytes: 1024").is_err());
+    assert!(validate("idle_timeout: 0").is_err());
+    assert!(validate("idle_timeout: 3601").is_err());
+}
+
+#[test]
+fn test_forward_validation_websocket() {
+    let validate = |yaml: &str| {
+        let websocket: WebSocketConfig = serde_yaml::from_str(yaml).unwrap();
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.http_server.as_mut().unwrap().forwards[0].websocket = Some(websocket);
+            })
+            .build()
+            .validate()
+    };
+
+    let websocket: WebSocketConfig = serde_yaml::from_str("{}").unwrap();
+    assert_eq!(websocket.idle_timeout, 300);
+    assert!(validate("{}").is_ok());
+    assert!(validate("idle_timeout: 86400").is_ok());
+    assert!(validate("idle_timeout: 0").is_err());
+    assert!(validate("idle_timeout: 86401").is_err());
+}
+
+#[test]
+fn test_forward_validation_compression() {
+    let validate = |yaml: &str| {
+        let compression: CompressionConfig = serde_yaml::from_str(yaml).unwrap();
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.http_server.as_mut().unwrap().forwards[0].compression = Some(compression);
+            })
+            .build()
+            .validate()
+    };
+
+    let compression: CompressionConfig = serde_yaml::from_str("{}").unwrap();
+    assert_eq!(
+        compression.algorithms,
+        vec![CompressionAlgorithm::Gzip, CompressionAlgorithm::Br]
+    );
+    assert_eq!(compression.min_size, 1024);
+    assert!(validate("{}").is_ok());
+    assert!(validate("{algorithms: [br], min_size: 1}").is_ok());
+    assert!(validate("algorithms: []").is_err());
+    assert!(validate("min_size: 0").is_err());
+    assert!(serde_yaml::from_str::<CompressionConfig>("algorithms: [zstd]").is_err());
+}
+
+#[test]
+fn test_forward_validation_access_log() {
+    let validate = |yaml: &str| {
+        let access_log: AccessLogConfig = serde_yaml::from_str(yaml).unwrap();
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.http_server.as_mut().unwrap().forwards[0].access_log = Some(access_log);
+            })
+            .build()
+            .validate()
+    };
+
+    let access_log: AccessLogConfig = serde_yaml::from_str("{}").unwrap();
+    assert_eq!(access_log.format, AccessLogFormat::Json);
+    assert_eq!(access_log.file, None);
+    assert_eq!(access_log.max_size, 100);
+    assert_eq!(access_log.max_files, 5);
+    assert!(validate("{}").is_ok());
+    assert!(validate("{format: combined, file: /var/log/llmproxy/access.log}").is_ok());
+    assert!(validate("file: ''").is_err());
+    assert!(validate("max_size: 0").is_err());
+    assert!(validate("max_files: 0").is_err());
+    assert!(validate("max_files: 101").is_err());
+    assert!(validate("buffer: 0").is_err());
+    assert!(serde_yaml::from_str::<AccessLogConfig>("format: common").is_err());
+}
@@ -3,7 +3,13 @@
 // This module contains tests for the UpstreamGroupConfig struct.
 
 use super::common::TestConfigBuilder;
-use llmproxy::config::{HttpClientConfig, HttpClientTimeoutConfig, ProxyConfig};
+use llmproxy::{
+    config::{
+        GroupDiscoveryConfig, GroupDiscoveryType, HttpClientConfig, HttpClientTimeoutConfig,
+        HttpVersion, ProxyConfig, RetryConfig,
+    },
+    r#const::{http_client_limits, retry_limits},
+};
 use validator::Validate;
 
 #[test]
@@ -17,8 +23,13 @@ fn test_config_with_proxy() {
                 retry: None,
                 proxy: Some(ProxyConfig {
                     url: "http://proxy.example.com:8080".to_string(),
+                    no_proxy: vec![],
                 }),
                 stream_mode: false,
+                http_version: HttpVersion::Auto,
+                http2: None,
+                tls: None,
+                agent: None,
             };
             c.upstream_groups[0].http_client = http_client_config;
         })
@@ -39,3 +50,164 @@ fn test_config_with_proxy() {
         "http://proxy.example.com:8080"
     );
 }
+
+#[test]
+fn test_config_with_retry_max_elapsed() {
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            c.upstream_groups[0].http_client.retry = Some(RetryConfig {
+                attempts: 5,
+                initial: 500,
+                max_elapsed_ms: Some(10_000),
+                retry_on_status: vec![429, 500, 502, 503],
+                retry_non_idempotent: false,
+            });
+        })
+        .build();
+
+    assert!(config.validate().is_ok());
+
+    // Test serialization and deserialization
+    let (_dir, file_path) = super::common::create_temp_config_file(&config);
+    let deserialized_config = llmproxy::config::Config::from_file(file_path).unwrap();
+
+    let retry = deserialized_config.upstream_groups[0]
+        .http_client
+        .retry
+        .as_ref()
+        .unwrap();
+    assert_eq!(retry.max_elapsed_ms, Some(10_000));
+
+    // The budget must stay within the allowed range
+    for max_elapsed_ms in [
+        retry_limits::MIN_MAX_ELAPSED_MS - 1,
+        retry_limits::MAX_MAX_ELAPSED_MS + 1,
+    ] {
+        let config = TestConfigBuilder::new()
+            .map_config(|c| {
+                c.upstream_groups[0].http_client.retry = Some(RetryConfig {
+                    max_elapsed_ms: Some(max_elapsed_ms),
+                    ..RetryConfig::default()
+                });
+            })
+            .build();
+        assert!(config.validate().is_err());
+    }
+}
+
+#[test]
+fn test_config_with_retry_rules() {
+    // By default only idempotent requests are retried, on the common transient statuses
+    let retry: RetryConfig = serde_yaml::from_str("attempts: 2").unwrap();
+    assert_eq!(retry.retry_on_status, vec![429, 500, 502, 503]);
+    assert!(!retry.retry_non_idempotent);
+
+    let retry: RetryConfig =
+        serde_yaml::from_str("retry_on_status: [503, 529]\nretry_non_idempotent: true").unwrap();
+    assert_eq!(retry.retry_on_status, vec![503, 529]);
+    assert!(retry.retry_non_idempotent);
+    assert!(retry.validate().is_ok());
+
+    // Only error statuses can be retried
+    for status in [200, 302, 600] {
+        let retry = RetryConfig {
+            retry_on_status: vec![503, status],
+            ..RetryConfig::default()
+        };
+        assert!(retry.validate().is_err());
+    }
+}
+
+#[test]
+fn test_config_with_stream_idle_timeout() {
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            c.upstream_groups[0].http_client.stream_mode = true;
+            c.upstream_groups[0].http_client.timeout.stream_idle = Some(30);
+        })
+        .build();
+
+    assert!(config.validate().is_ok());
+
+    // Test serialization and deserialization
+    let (_dir, file_path) = super::common::create_temp_config_file(&config);
+    let deserialized_config = llmproxy::config::Config::from_file(file_path).unwrap();
+    assert_eq!(
+        deserialized_config.upstream_groups[0]
+            .http_client
+            .timeout
+            .stream_idle,
+        Some(30)
+    );
+
+    // The timeout must stay within the allowed range
+    for stream_idle in [
+        http_client_limits::MIN_STREAM_IDLE_TIMEOUT - 1,
+        http_client_limits::MAX_STREAM_IDLE_TIMEOUT + 1,
+    ] {
+        let config = TestConfigBuilder::new()
+            .map_config(|c| {
+                c.upstream_groups[0].http_client.timeout.stream_idle = Some(stream_idle);
+            })
+            .build();
+        assert!(config.validate().is_err());
+    }
+}
+
+#[test]
+fn test_config_with_kubernetes_discovery() {
+    let discovery: GroupDiscoveryConfig = serde_yaml::from_str(
+        "type: kubernetes\nkubernetes:\n  service: vllm\n  namespace: inference\n  port: 8000",
+    )
+    .unwrap();
+    assert_eq!(discovery.r#type, GroupDiscoveryType::Kubernetes);
+    let kubernetes = discovery.kubernetes.as_ref().unwrap();
+    assert_eq!(kubernetes.service, "vllm");
+    assert_eq!(kubernetes.namespace.as_deref(), Some("inference"));
+    assert_eq!(kubernetes.port, Some(8000));
+
+    let validate = |discovery: &str| {
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.upstream_groups[0].discovery = Some(serde_yaml::from_str(discovery).unwrap());
+            })
+            .build()
+            .validate()
+    };
+
+    assert!(validate("type: kubernetes\nkubernetes:\n  service: vllm").is_ok());
+    assert!(validate("type: kubernetes")
+        .unwrap_err()
+        .to_string()
+        .contains("Kubernetes discovery requires a kubernetes configuration"));
+    assert!(validate("type: kubernetes\nkubernetes:\n  service: ''").is_err());
+    assert!(validate("type: kubernetes\nkubernetes:\n  service: vllm\n  port: 0").is_err());
+}
+
+#[test]
+fn test_config_with_consul_discovery() {
+    let discovery: GroupDiscoveryConfig =
+        serde_yaml::from_str("type: consul\nconsul:\n  service: vllm\n  tags: [gpu]").unwrap();
+    assert_eq!(discovery.r#type, GroupDiscoveryType::Consul);
+    let consul = discovery.consul.as_ref().unwrap();
+    assert_eq!(consul.address, "http://127.0.0.1:8500");
+    assert_eq!(consul.tags, vec!["gpu".to_string()]);
+    assert!(consul.passing);
+    assert!(consul.datacenter.is_none());
+
+    let validate = |discovery: &str| {
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.upstream_groups[0].discovery = Some(serde_yaml::from_str(discovery).unwrap());
+            })
+            .build()
+            .validate()
+    };
+
+    assert!(validate("type: consul\nconsul:\n  service: vllm").is_ok());
+    assert!(validate("type: consul")
+        .unwrap_err()
+        .to_string()
+        .contains("Consul discovery requires a consul configuration"));
+    assert!(validate("type: consul\nconsul:\n  service: vllm\n  address: not a url").is_err());
+}
@@ -4,7 +4,8 @@
 
 use super::common::TestConfigBuilder;
 use llmproxy::config::{
-    http_server::RoutingRule, BalanceConfig, BalanceStrategy, UpstreamGroupConfig, UpstreamRef,
+    http_server::{RoutingRule, RoutingRuleType},
+    BalanceConfig, BalanceStrategy, UpstreamGroupConfig, UpstreamRef,
 };
 use validator::Validate;
 
@@ -20,11 +21,18 @@ fn test_config_with_routing() {
             strategy: BalanceStrategy::RoundRobin,
         },
         http_client: Default::default(),
+        sticky: None,
+        discovery: None,
     };
 
     let routing_rules = vec![RoutingRule {
         path: "/api".to_string(),
+        r#type: RoutingRuleType::Path,
         target_group: "api_group".to_string(),
+        priority: 0,
+        breaker: None,
+        max_prompt_tokens: None,
+        tokens_per_minute: None,
     }];
 
     let config = TestConfigBuilder::new()
@@ -42,7 +50,12 @@ fn test_config_with_routing() {
 fn test_config_validation_invalid_routing_target_group() {
     let routing_rules = vec![RoutingRule {
         path: "/api".to_string(),
+        r#type: RoutingRuleType::Path,
         target_group: "non_existent_group".to_string(),
+        priority: 0,
+        breaker: None,
+        max_prompt_tokens: None,
+        tokens_per_minute: None,
     }];
 
     let config = TestConfigBuilder::new()
@@ -61,6 +74,33 @@ fn test_config_validation_invalid_routing_target_group() {
     }
 }
 
+#[test]
+fn test_config_validation_invalid_path_regex() {
+    let routing_rules = vec![RoutingRule {
+        path: "^/api/products/[A-Z{3}$".to_string(),
+        r#type: RoutingRuleType::PathRegex,
+        target_group: "test_group".to_string(),
+        priority: 0,
+        breaker: None,
+        max_prompt_tokens: None,
+        tokens_per_minute: None,
+    }];
+
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            c.http_server.as_mut().unwrap().forwards[0].routing = Some(routing_rules);
+        })
+        .build();
+
+    let result = config.validate();
+    assert!(result.is_err());
+    if let Err(e) = result {
+        assert!(e.to_string().contains("Invalid path regex"));
+    } else {
+        panic!("Expected Config error for invalid path regex");
+    }
+}
+
 #[test]
 fn test_config_with_various_routing_paths() {
     let static_group = UpstreamGroupConfig {
@@ -73,6 +113,8 @@ fn test_config_with_various_routing_paths() {
             strategy: BalanceStrategy::RoundRobin,
         },
         http_client: Default::default(),
+        sticky: None,
+        discovery: None,
     };
     let param_group = UpstreamGroupConfig {
         name: "param_group".to_string(),
@@ -84,6 +126,8 @@ fn test_config_with_various_routing_paths() {
             strategy: BalanceStrategy::RoundRobin,
         },
         http_client: Default::default(),
+        sticky: None,
+        discovery: None,
     };
     let regex_group = UpstreamGroupConfig {
         name: "regex_group".to_string(),
@@ -95,6 +139,8 @@ fn test_config_with_various_routing_paths() {
             strategy: BalanceStrategy::RoundRobin,
         },
         http_client: Default::default(),
+        sticky: None,
+        discovery: None,
     };
     let wildcard_group = UpstreamGroupConfig {
         name: "wildcard_group".to_string(),
@@ -106,36 +152,73 @@ fn test_config_with_various_routing_paths() {
             strategy: BalanceStrategy::RoundRobin,
         },
         http_client: Default::default(),
+        sticky: None,
+        discovery: None,
     };
 
     let routing_rules = vec![
         RoutingRule {
             path: "/api/users/admin".to_string(),
+            r#type: RoutingRuleType::Path,
             target_group: "static_group".to_string(),
+            priority: 0,
+            breaker: None,
+            max_prompt_tokens: None,
+            tokens_per_minute: None,
         },
         RoutingRule {
             path: "/api/users/:id".to_string(),
+            r#type: RoutingRuleType::Path,
             target_group: "param_group".to_string(),
+            priority: 0,
+            breaker: None,
+            max_prompt_tokens: None,
+            tokens_per_minute: None,
         },
         RoutingRule {
             path: "/api/items/{id:[0-9]+}".to_string(),
+            r#type: RoutingRuleType::Path,
             target_group: "regex_group".to_string(),
+            priority: 0,
+            breaker: None,
+            max_prompt_tokens: None,
+            tokens_per_minute: None,
         },
         RoutingRule {
             path: "/api/products/{code:[A-Z][A-Z][A-Z][0-9][0-9][0-9]}".to_string(),
+            r#type: RoutingRuleType::Path,
             target_group: "regex_group".to_string(),
+            priority: 0,
+            breaker: None,
+            max_prompt_tokens: None,
+            tokens_per_minute: None,
         },
         RoutingRule {
             path: "/api/*/docs".to_string(),
+            r#type: RoutingRuleType::Path,
             target_group: "wildcard_group".to_string(),
+            priority: 0,
+            breaker: None,
+            max_prompt_tokens: None,
+            tokens_per_minute: None,
         },
         RoutingRule {
             path: "/files/*".to_string(),
+            r#type: RoutingRuleType::Path,
             target_group: "wildcard_group".to_string(),
+            priority: 0,
+            breaker: None,
+            max_prompt_tokens: None,
+            tokens_per_minute: None,
         },
         RoutingRule {
             path: "/api/:version/users/{id:[0-9]+}/profile".to_string(),
+            r#type: RoutingRuleType::Path,
             target_group: "regex_group".to_string(),
+            priority: 0,
+            breaker: None,
+            max_prompt_tokens: None,
+            tokens_per_minute: None,
         },
     ];
 
@@ -155,3 +238,64 @@ fn test_config_with_various_routing_paths() {
     let result = config.validate();
     assert!(result.is_ok());
 }
+
+#[test]
+fn test_config_validation_route_max_prompt_tokens() {
+    let validate = |max_prompt_tokens: Option<u64>| {
+        let routing_rules = vec![RoutingRule {
+            path: "/v1/chat/completions".to_string(),
+            r#type: RoutingRuleType::Path,
+            target_group: "test_group".to_string(),
+            priority: 0,
+            breaker: None,
+            max_prompt_tokens,
+            tokens_per_minute: None,
+        }];
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.http_server.as_mut().unwrap().forwards[0].routing = Some(routing_rules);
+            })
+            .build()
+            .validate()
+    };
+
+    assert!(validate(None).is_ok());
+    assert!(validate(Some(8000)).is_ok());
+    assert!(validate(Some(0))
+        .unwrap_err()
+        .to_string()
+        .contains("Route max_prompt_tokens must be at least 1"));
+}
+
+#[test]
+fn test_config_validation_route_tokens_per_minute() {
+    let validate = |tokens_per_minute: Option<u64>, token_limit: bool| {
+        let routing_rules = vec![RoutingRule {
+            path: "/v1/embeddings".to_string(),
+            r#type: RoutingRuleType::Path,
+            target_group: "test_group".to_string(),
+            priority: 0,
+            breaker: None,
+            max_prompt_tokens: None,
+            tokens_per_minute,
+        }];
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                let forward = &mut c.http_server.as_mut().unwrap().forwards[0];
+                forward.routing = Some(routing_rules);
+                forward.token_limit =
+                    token_limit.then(|| serde_yaml::from_str("tokens_per_minute: 100000").unwrap());
+            })
+            .build()
+            .validate()
+    };
+
+    assert!(validate(None, false).is_ok());
+    assert!(validate(Some(500_000), true).is_ok());
+    assert!(validate(Some(0), true).is_err());
+    // 路由的限额需要转发服务配置 token_limit
+    assert!(validate(Some(500_000), false)
+        .unwrap_err()
+        .to_string()
+        .contains("configures tokens_per_minute without token_limit"));
+}
@@ -3,7 +3,12 @@
 // This module contains tests for the UpstreamConfig struct.
 
 use super::common::TestConfigBuilder;
-use llmproxy::config::{AuthConfig, AuthType, BreakerConfig};
+use llmproxy::config::{
+    AdaptiveConfig, AuthConfig, AuthType, BodyTransformConfig, BreakerConfig, BreakerWindowConfig,
+    BreakerWindowType, DnsDiscoveryConfig, DnsRecordType, ExternalAuthConfig, HeaderOp,
+    HeaderOpType, ModelPriceConfig, OAuth2Config, OAuth2Grant, PathRewriteConfig, QueryParamOp,
+    StreamNormalizeConfig, SystemPromptConfig, SystemPromptMode, WarmupConfig,
+};
 use llmproxy::r#const::breaker_limits;
 use validator::Validate;
 
@@ -31,6 +36,10 @@ fn test_config_validation_invalid_breaker_config() {
             c.upstreams[0].breaker = Some(BreakerConfig {
                 threshold: breaker_limits::MAX_THRESHOLD + 1.0, // Out of valid range
                 cooldown: breaker_limits::DEFAULT_COOLDOWN,
+                connect_failures: breaker_limits::DEFAULT_CONNECT_FAILURES,
+                window: None,
+                min_requests: None,
+                half_open_probes: None,
             });
         })
         .build();
@@ -44,6 +53,60 @@ fn test_config_validation_invalid_breaker_config() {
     }
 }
 
+#[test]
+fn test_config_breaker_window() {
+    let breaker: BreakerConfig = serde_yaml::from_str(
+        "threshold: 0.5\nwindow:\n  type: count\n  size: 50\nmin_requests: 20\nhalf_open_probes: 3",
+    )
+    .unwrap();
+    assert!(breaker.validate().is_ok());
+    let window = breaker.window.as_ref().unwrap();
+    assert_eq!(window.r#type, BreakerWindowType::Count);
+    assert_eq!(window.size, 50);
+    assert_eq!(breaker.min_requests, Some(20));
+    assert_eq!(breaker.half_open_probes, Some(3));
+
+    // 默认按时间统计
+    let breaker: BreakerConfig = serde_yaml::from_str("window:\n  size: 60").unwrap();
+    assert_eq!(breaker.window.unwrap().r#type, BreakerWindowType::Time);
+
+    let invalid = [
+        // 窗口大小超出范围
+        (
+            BreakerWindowType::Time,
+            breaker_limits::MAX_WINDOW_SECS + 1,
+            None,
+        ),
+        (
+            BreakerWindowType::Count,
+            breaker_limits::MAX_WINDOW_REQUESTS + 1,
+            None,
+        ),
+        (BreakerWindowType::Count, 0, None),
+        // 请求数窗口装不下最小请求数
+        (BreakerWindowType::Count, 10, Some(11)),
+    ];
+    for (r#type, size, min_requests) in invalid {
+        let breaker = BreakerConfig {
+            window: Some(BreakerWindowConfig { r#type, size }),
+            min_requests,
+            ..BreakerConfig::default()
+        };
+        assert!(breaker.validate().is_err(), "{:?}", breaker);
+    }
+
+    // 按时间统计时最小请求数不受窗口大小限制
+    let breaker = BreakerConfig {
+        window: Some(BreakerWindowConfig {
+            r#type: BreakerWindowType::Time,
+            size: 10,
+        }),
+        min_requests: Some(100),
+        ..BreakerConfig::default()
+    };
+    assert!(breaker.validate().is_ok());
+}
+
 #[test]
 fn test_config_validation_invalid_auth_config() {
     let config = TestConfigBuilder::new()
@@ -51,8 +114,12 @@ fn test_config_validation_invalid_auth_config() {
             c.upstreams[0].auth = Some(AuthConfig {
                 r#type: AuthType::Bearer,
                 token: None, // Bearer auth requires a token
+                token_file: None,
                 username: None,
                 password: None,
+                password_file: None,
+                oauth2: None,
+                external: None,
             });
         })
         .build();
@@ -65,3 +132,627 @@ fn test_config_validation_invalid_auth_config() {
         panic!("Expected Config error for invalid auth config");
     }
 }
+
+#[test]
+fn test_config_validation_auth_secret_files() {
+    // 令牌文件可以替代内联令牌
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            c.upstreams[0].auth = Some(AuthConfig {
+                r#type: AuthType::Bearer,
+                token: None,
+                token_file: Some("/var/run/secrets/openai/token".to_string()),
+                username: None,
+                password: None,
+                password_file: None,
+                oauth2: None,
+                external: None,
+            });
+        })
+        .build();
+    assert!(config.validate().is_ok());
+
+    // 令牌和令牌文件不能同时配置
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            c.upstreams[0].auth = Some(AuthConfig {
+                r#type: AuthType::Bearer,
+                token: Some("inline-token".to_string()),
+                token_file: Some("/var/run/secrets/openai/token".to_string()),
+                username: None,
+                password: None,
+                password_file: None,
+                oauth2: None,
+                external: None,
+            });
+        })
+        .build();
+    let result = config.validate();
+    assert!(result.unwrap_err().to_string().contains("token_file"));
+
+    // 密码文件可以替代内联密码
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            c.upstreams[0].auth = Some(AuthConfig {
+                r#type: AuthType::Basic,
+                token: None,
+                token_file: None,
+                username: Some("service_user".to_string()),
+                password: None,
+                password_file: Some("/var/run/secrets/service/password".to_string()),
+                oauth2: None,
+                external: None,
+            });
+        })
+        .build();
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_config_post_process_missing_secret_file() {
+    let mut config = TestConfigBuilder::new()
+        .map_config(|c| {
+            c.upstreams[0].auth = Some(AuthConfig {
+                r#type: AuthType::Bearer,
+                token: None,
+                token_file: Some("non-existent-token-file".to_string()),
+                username: None,
+                password: None,
+                password_file: None,
+                oauth2: None,
+                external: None,
+            });
+        })
+        .build();
+
+    // 启动时即发现无法读取的密钥文件
+    let result = config.post_process();
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("non-existent-token-file"));
+}
+
+#[test]
+fn test_config_post_process_agent_and_default_headers() {
+    let post_process = |f: fn(&mut llmproxy::config::Config)| {
+        TestConfigBuilder::new()
+            .map_config(f)
+            .build()
+            .post_process()
+    };
+
+    assert!(post_process(|c| {
+        c.upstream_groups[0].http_client.agent = Some("LLMProxy/1.0 (Group)".to_string());
+        c.upstreams[0].agent = Some("LLMProxy/1.0 (Upstream)".to_string());
+        c.upstreams[0]
+            .default_headers
+            .insert("x-api-version".to_string(), "2024-06-01".to_string());
+    })
+    .is_ok());
+
+    let result = post_process(|c| {
+        c.upstream_groups[0].http_client.agent = Some("LLMProxy\n".to_string());
+    });
+    assert!(result.unwrap_err().to_string().contains("Invalid agent"));
+
+    let result = post_process(|c| {
+        c.upstreams[0]
+            .default_headers
+            .insert("x api version".to_string(), "2024-06-01".to_string());
+    });
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Invalid default header name"));
+}
+
+// 创建 OAuth2 认证配置
+fn oauth2_auth(oauth2: Option<OAuth2Config>) -> AuthConfig {
+    AuthConfig {
+        r#type: AuthType::OAuth2,
+        token: None,
+        token_file: None,
+        username: None,
+        password: None,
+        password_file: None,
+        oauth2,
+        external: None,
+    }
+}
+
+#[test]
+fn test_config_validation_oauth2() {
+    let client_credentials = OAuth2Config {
+        grant: OAuth2Grant::ClientCredentials,
+        token_url: Some("https://auth.example.com/oauth2/token".to_string()),
+        client_id: Some("llmproxy".to_string()),
+        client_secret: Some("client-secret".to_string()),
+        client_secret_file: None,
+        service_account_file: None,
+        scopes: vec![],
+        refresh_before: 60,
+    };
+
+    let config = TestConfigBuilder::new()
+        .map_config(|c| c.upstreams[0].auth = Some(oauth2_auth(Some(client_credentials.clone()))))
+        .build();
+    assert!(config.validate().is_ok());
+
+    // OAuth2 认证必须提供 oauth2 配置
+    let config = TestConfigBuilder::new()
+        .map_config(|c| c.upstreams[0].auth = Some(oauth2_auth(None)))
+        .build();
+    assert!(config
+        .validate()
+        .unwrap_err()
+        .to_string()
+        .contains("oauth2"));
+
+    // 客户端凭证模式必须提供令牌端点
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            c.upstreams[0].auth = Some(oauth2_auth(Some(OAuth2Config {
+                token_url: None,
+                ..client_credentials.clone()
+            })))
+        })
+        .build();
+    assert!(config
+        .validate()
+        .unwrap_err()
+        .to_string()
+        .contains("token_url"));
+
+    // 服务账号模式只需要密钥文件
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            c.upstreams[0].auth = Some(oauth2_auth(Some(OAuth2Config {
+                grant: OAuth2Grant::ServiceAccount,
+                token_url: None,
+                client_id: None,
+                client_secret: None,
+                service_account_file: Some("/var/run/secrets/gcp/key.json".to_string()),
+                ..client_credentials.clone()
+            })))
+        })
+        .build();
+    assert!(config.validate().is_ok());
+
+    // 提前刷新时间超出范围
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            c.upstreams[0].auth = Some(oauth2_auth(Some(OAuth2Config {
+                refresh_before: 7200,
+                ..client_credentials
+            })))
+        })
+        .build();
+    assert!(config
+        .validate()
+        .unwrap_err()
+        .to_string()
+        .contains("refresh_before"));
+}
+
+#[test]
+fn test_config_validation_external_auth() {
+    let external_auth = |external: Option<ExternalAuthConfig>| AuthConfig {
+        r#type: AuthType::External,
+        token: None,
+        token_file: None,
+        username: None,
+        password: None,
+        password_file: None,
+        oauth2: None,
+        external,
+    };
+    let external = ExternalAuthConfig {
+        command: "/usr/local/bin/mint-token".to_string(),
+        args: vec!["--audience".to_string(), "llm".to_string()],
+        refresh_interval: 300,
+        timeout: 10,
+    };
+
+    let config = TestConfigBuilder::new()
+        .map_config(|c| c.upstreams[0].auth = Some(external_auth(Some(external.clone()))))
+        .build();
+    assert!(config.validate().is_ok());
+
+    // External 认证必须提供 external 配置
+    let config = TestConfigBuilder::new()
+        .map_config(|c| c.upstreams[0].auth = Some(external_auth(None)))
+        .build();
+    assert!(config
+        .validate()
+        .unwrap_err()
+        .to_string()
+        .contains("external"));
+
+    // 命令不能为空
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            c.upstreams[0].auth = Some(external_auth(Some(ExternalAuthConfig {
+                command: String::new(),
+                ..external.clone()
+            })))
+        })
+        .build();
+    assert!(config
+        .validate()
+        .unwrap_err()
+        .to_string()
+        .contains("command"));
+
+    // 执行超时超出范围
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            c.upstreams[0].auth = Some(external_auth(Some(ExternalAuthConfig {
+                timeout: 0,
+                ..external
+            })))
+        })
+        .build();
+    assert!(config
+        .validate()
+        .unwrap_err()
+        .to_string()
+        .contains("timeout"));
+}
+
+#[test]
+fn test_path_rewrite() {
+    let strip = PathRewriteConfig {
+        strip_prefix: Some("/openai/".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        strip.rewrite("/openai/v1/chat/completions"),
+        "/v1/chat/completions"
+    );
+    assert_eq!(strip.rewrite("/openai"), "/");
+    // 只在路径段边界去除前缀
+    assert_eq!(strip.rewrite("/openaiv1/models"), "/openaiv1/models");
+
+    let add = PathRewriteConfig {
+        strip_prefix: Some("/v1".to_string()),
+        add_prefix: Some("/api/v2/".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        add.rewrite("/v1/chat/completions"),
+        "/api/v2/chat/completions"
+    );
+
+    let template = PathRewriteConfig {
+        strip_prefix: Some("/v1".to_string()),
+        template: Some("/openai/deployments/{deployment}{path}".to_string()),
+        params: [("deployment".to_string(), "gpt-4o".to_string())].into(),
+        ..Default::default()
+    };
+    assert_eq!(
+        template.rewrite("/v1/chat/completions"),
+        "/openai/deployments/gpt-4o/chat/completions"
+    );
+}
+
+#[test]
+fn test_config_validation_path_rewrite() {
+    let validate = |rewrite: PathRewriteConfig| {
+        TestConfigBuilder::new()
+            .map_config(|c| c.upstreams[0].path = Some(rewrite))
+            .build()
+            .validate()
+    };
+
+    assert!(validate(PathRewriteConfig {
+        template: Some("/openai/deployments/{deployment}{path}".to_string()),
+        params: [("deployment".to_string(), "gpt-4o".to_string())].into(),
+        ..Default::default()
+    })
+    .is_ok());
+
+    // 至少配置一条规则
+    assert!(validate(PathRewriteConfig::default()).is_err());
+
+    // 路径必须以 / 开头
+    let result = validate(PathRewriteConfig {
+        add_prefix: Some("api".to_string()),
+        ..Default::default()
+    });
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("must start with '/'"));
+
+    // 模板占位符必须有对应的参数
+    let result = validate(PathRewriteConfig {
+        template: Some("/openai/deployments/{deployment}{path}".to_string()),
+        ..Default::default()
+    });
+    assert!(result.unwrap_err().to_string().contains("deployment"));
+}
+
+#[test]
+fn test_config_validation_query_param_op() {
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            c.upstreams[0].query_params = vec![QueryParamOp {
+                op: HeaderOpType::Insert,
+                key: "api-version".to_string(),
+                value: None, // insert 操作缺少参数值
+            }];
+        })
+        .build();
+
+    let result = config.validate();
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Query parameter value is required"));
+}
+
+#[test]
+fn test_config_validation_header_placeholders() {
+    let config = |value: &str| {
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.upstreams[0].headers = vec![HeaderOp {
+                    op: HeaderOpType::Insert,
+                    key: "x-correlation-id".to_string(),
+                    value: Some(value.to_string()),
+                    parsed_name: None,
+                    parsed_value: None,
+                }];
+            })
+            .build()
+    };
+
+    assert!(
+        config("${request_id}-${client_ip}-${forward_name}-${env:HOME}")
+            .validate()
+            .is_ok()
+    );
+
+    for value in ["${session_id}", "${env:}"] {
+        let result = config(value).validate();
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Unknown placeholders"));
+    }
+}
+
+#[test]
+fn test_config_validation_warmup() {
+    let validate = |warmup: WarmupConfig| {
+        TestConfigBuilder::new()
+            .map_config(|c| c.upstreams[0].warmup = Some(warmup))
+            .build()
+            .validate()
+    };
+    let warmup = WarmupConfig {
+        method: "POST".to_string(),
+        path: Some("/v1/chat/completions".to_string()),
+        body: Some(serde_json::json!({"model": "gpt-4o", "max_tokens": 1})),
+        requests: 2,
+        timeout: 10,
+    };
+    assert!(validate(warmup.clone()).is_ok());
+
+    for (warmup, message) in [
+        (
+            WarmupConfig {
+                method: "GET /".to_string(),
+                ..warmup.clone()
+            },
+            "Invalid warmup request method",
+        ),
+        (
+            WarmupConfig {
+                path: Some("v1/models".to_string()),
+                ..warmup.clone()
+            },
+            "Warmup path must start with '/'",
+        ),
+        (
+            WarmupConfig {
+                requests: 0,
+                ..warmup.clone()
+            },
+            "Warmup requests must be between 1 and 64",
+        ),
+    ] {
+        assert!(validate(warmup).unwrap_err().to_string().contains(message));
+    }
+
+    // 未配置的字段使用默认值
+    let warmup: WarmupConfig = serde_yaml::from_str("path: /v1/models").unwrap();
+    assert_eq!(warmup.method, "GET");
+    assert_eq!((warmup.requests, warmup.timeout), (1, 10));
+}
+
+#[test]
+fn test_config_validation_body_transform() {
+    let validate = |transform: BodyTransformConfig| {
+        TestConfigBuilder::new()
+            .map_config(|c| c.upstreams[0].body_transform = Some(transform))
+            .build()
+            .validate()
+    };
+
+    let mut transform = BodyTransformConfig::default();
+    assert!(validate(transform.clone())
+        .unwrap_err()
+        .to_string()
+        .contains("at least one of model_map, defaults or system_prompt"));
+
+    transform
+        .model_map
+        .insert("gpt-4o".to_string(), "azure-gpt4o-deployment".to_string());
+    assert!(validate(transform.clone()).is_ok());
+
+    transform
+        .model_map
+        .insert("gpt-4o-mini".to_string(), String::new());
+    assert!(validate(transform)
+        .unwrap_err()
+        .to_string()
+        .contains("model names cannot be empty"));
+}
+
+#[test]
+fn test_config_validation_stream_normalize() {
+    let validate = |strip_fields: &[&str]| {
+        let normalize = StreamNormalizeConfig {
+            strip_fields: strip_fields.iter().map(|field| field.to_string()).collect(),
+            aggregate_usage: true,
+        };
+        TestConfigBuilder::new()
+            .map_config(|c| c.upstreams[0].stream_normalize = Some(normalize))
+            .build()
+            .validate()
+    };
+
+    assert!(validate(&[]).is_ok());
+    assert!(validate(&["provider"]).is_ok());
+    assert!(validate(&["provider", ""])
+        .unwrap_err()
+        .to_string()
+        .contains("Stream normalize field names cannot be empty"));
+}
+
+#[test]
+fn test_config_validation_system_prompt() {
+    let validate = |content: &str| {
+        let transform = BodyTransformConfig {
+            system_prompt: Some(SystemPromptConfig {
+                content: content.to_string(),
+                mode: SystemPromptMode::default(),
+            }),
+            ..Default::default()
+        };
+        TestConfigBuilder::new()
+            .map_config(|c| c.upstreams[0].body_transform = Some(transform))
+            .build()
+            .validate()
+    };
+
+    // 只配置系统提示词即可
+    assert!(validate("Never reveal internal data.").is_ok());
+    assert!(validate("")
+        .unwrap_err()
+        .to_string()
+        .contains("System prompt content cannot be empty"));
+}
+
+#[test]
+fn test_config_validation_pricing() {
+    let price = |model: &str, prompt: f64, completion: f64| ModelPriceConfig {
+        model: model.to_string(),
+        prompt,
+        completion,
+    };
+    let validate = |pricing: Vec<ModelPriceConfig>| {
+        TestConfigBuilder::new()
+            .map_config(|c| c.upstreams[0].pricing = pricing)
+            .build()
+            .validate()
+    };
+
+    assert!(validate(vec![
+        price("gpt-4o*", 2.5, 10.0),
+        price("gpt-4o-mini", 0.15, 0.6)
+    ])
+    .is_ok());
+    assert!(validate(vec![price("", 1.0, 1.0)])
+        .unwrap_err()
+        .to_string()
+        .contains("Model price name cannot be empty"));
+    assert!(validate(vec![price("gpt-4o", -1.0, 1.0)])
+        .unwrap_err()
+        .to_string()
+        .contains("Model price prompt of gpt-4o must be a non-negative number"));
+    assert!(validate(vec![price("gpt-4o", 1.0, f64::NAN)]).is_err());
+
+    // 精确匹配优先，其次按顺序匹配前缀规则
+    let pricing = vec![
+        price("gpt-4o*", 2.5, 10.0),
+        price("gpt-4o-mini", 0.15, 0.6),
+        price("*", 1.0, 1.0),
+    ];
+    assert_eq!(
+        ModelPriceConfig::find(&pricing, "gpt-4o-mini")
+            .unwrap()
+            .prompt,
+        0.15
+    );
+    assert_eq!(
+        ModelPriceConfig::find(&pricing, "gpt-4o-2024-08-06")
+            .unwrap()
+            .prompt,
+        2.5
+    );
+    assert_eq!(
+        ModelPriceConfig::find(&pricing, "claude").unwrap().prompt,
+        1.0
+    );
+    assert!(ModelPriceConfig::find(&pricing[..2], "claude").is_none());
+    assert_eq!(pricing[0].cost(1_000_000, 500_000), 7.5);
+}
+
+#[test]
+fn test_config_validation_adaptive() {
+    let validate = |initial: u32, min: u32, max: u32, backoff: f64| {
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.upstreams[0].adaptive = Some(AdaptiveConfig {
+                    initial,
+                    min,
+                    max,
+                    backoff,
+                    ..Default::default()
+                })
+            })
+            .build()
+            .validate()
+    };
+
+    assert!(validate(16, 1, 256, 0.9).is_ok());
+    assert!(validate(4, 4, 4, 0.5).is_ok());
+    assert!(validate(0, 1, 256, 0.9).is_err());
+    assert!(validate(16, 1, 256, 1.0).is_err());
+    assert!(validate(300, 1, 256, 0.9)
+        .unwrap_err()
+        .to_string()
+        .contains("Adaptive concurrency limits must satisfy min <= initial <= max"));
+    assert!(validate(16, 32, 256, 0.9).is_err());
+}
+
+#[test]
+fn test_config_validation_dns_discovery() {
+    let dns: DnsDiscoveryConfig = serde_yaml::from_str("record: srv").unwrap();
+    assert_eq!(dns.record, DnsRecordType::Srv);
+    assert_eq!(dns.interval, 30);
+
+    let validate = |url: &str, interval: u64| {
+        TestConfigBuilder::new()
+            .map_config(|c| {
+                c.upstreams[0].url = url.to_string().into();
+                c.upstreams[0].dns = Some(DnsDiscoveryConfig {
+                    record: DnsRecordType::A,
+                    interval,
+                });
+            })
+            .build()
+            .validate()
+    };
+
+    assert!(validate("http://llm.service.internal:8000/v1", 30).is_ok());
+    assert!(validate("http://llm.service.internal:8000/v1", 0).is_err());
+    assert!(validate("unix:///run/llm.sock", 30)
+        .unwrap_err()
+        .to_string()
+        .contains("requires an HTTP(S) URL with a host name"));
+}
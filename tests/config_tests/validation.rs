@@ -4,8 +4,9 @@
 
 use super::common::TestConfigBuilder;
 use llmproxy::config::{
-    http_server::RoutingRule, BalanceConfig, BalanceStrategy, HttpClientConfig, UpstreamConfig,
-    UpstreamGroupConfig, UpstreamRef,
+    http_server::{RoutingRule, RoutingRuleType},
+    BalanceConfig, BalanceStrategy, Http2Config, HttpClientConfig, HttpVersion, LoadSheddingConfig,
+    ModelAlias, StickyConfig, TlsConfig, UpstreamConfig, UpstreamGroupConfig, UpstreamRef,
 };
 use validator::Validate;
 
@@ -26,6 +27,20 @@ fn test_config_validation_duplicate_names() {
         auth: None,
         headers: vec![],
         breaker: None,
+        adaptive: None,
+        hint: None,
+        enabled: true,
+        proxy: true,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect: None,
+        stream_normalize: None,
+        agent: None,
+        default_headers: Default::default(),
+        pricing: vec![],
+        dns: None,
+        warmup: None,
     };
 
     let config = TestConfigBuilder::new()
@@ -59,6 +74,8 @@ fn test_config_validation_duplicate_upstreams_in_group() {
             strategy: BalanceStrategy::RoundRobin,
         },
         http_client: HttpClientConfig::default(),
+        sticky: None,
+        discovery: None,
     };
 
     let config = TestConfigBuilder::new().with_group(duplicate_group).build();
@@ -84,6 +101,8 @@ fn test_config_validation_missing_upstream_reference() {
             strategy: llmproxy::config::BalanceStrategy::RoundRobin,
         },
         http_client: HttpClientConfig::default(),
+        sticky: None,
+        discovery: None,
     };
 
     let config = TestConfigBuilder::new().with_group(invalid_group).build();
@@ -102,11 +121,21 @@ fn test_config_validation_duplicate_routing_paths() {
     let duplicate_routing_rules = vec![
         RoutingRule {
             path: "/api/v1/chat".to_string(),
+            r#type: RoutingRuleType::Path,
             target_group: "test_group".to_string(),
+            priority: 0,
+            breaker: None,
+            max_prompt_tokens: None,
+            tokens_per_minute: None,
         },
         RoutingRule {
             path: "/api/v1/chat".to_string(), // 重复的路径
+            r#type: RoutingRuleType::Path,
             target_group: "another_group".to_string(),
+            priority: 0,
+            breaker: None,
+            max_prompt_tokens: None,
+            tokens_per_minute: None,
         },
     ];
 
@@ -128,3 +157,289 @@ fn test_config_validation_duplicate_routing_paths() {
         panic!("Expected Config error for duplicate routing paths");
     }
 }
+
+#[test]
+fn test_config_validation_ambiguous_routing_rules() {
+    let ambiguous_routing_rules = vec![
+        RoutingRule {
+            path: "/api/users/:id".to_string(),
+            r#type: RoutingRuleType::Path,
+            target_group: "test_group".to_string(),
+            priority: 0,
+            breaker: None,
+            max_prompt_tokens: None,
+            tokens_per_minute: None,
+        },
+        RoutingRule {
+            path: "/api/users/:name".to_string(), // 与上一条规则匹配相同的路径
+            r#type: RoutingRuleType::Path,
+            target_group: "test_group".to_string(),
+            priority: 0,
+            breaker: None,
+            max_prompt_tokens: None,
+            tokens_per_minute: None,
+        },
+    ];
+
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            if let Some(http_server) = c.http_server.as_mut() {
+                if !http_server.forwards.is_empty() {
+                    http_server.forwards[0].routing = Some(ambiguous_routing_rules.clone());
+                }
+            }
+        })
+        .build();
+
+    let result = config.validate();
+    assert!(result.is_err());
+    if let Err(e) = result {
+        assert!(e.to_string().contains("same priority"));
+    } else {
+        panic!("Expected Config error for ambiguous routing rules");
+    }
+
+    // 不同的优先级可以消除歧义
+    let mut prioritized_rules = ambiguous_routing_rules;
+    prioritized_rules[1].priority = 10;
+
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            if let Some(http_server) = c.http_server.as_mut() {
+                if !http_server.forwards.is_empty() {
+                    http_server.forwards[0].routing = Some(prioritized_rules);
+                }
+            }
+        })
+        .build();
+
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_config_validation_invalid_sticky_header() {
+    let sticky_group = UpstreamGroupConfig {
+        name: "sticky_group".to_string(),
+        upstreams: vec![UpstreamRef {
+            name: "test_upstream".to_string(),
+            weight: 1,
+        }],
+        balance: BalanceConfig {
+            strategy: BalanceStrategy::RoundRobin,
+        },
+        http_client: HttpClientConfig::default(),
+        sticky: Some(StickyConfig {
+            session_header: "x-session-id".to_string(),
+            hint_header: "x-region hint".to_string(), // 非法的头部名称
+            ttl: 60,
+        }),
+        discovery: None,
+    };
+
+    let config = TestConfigBuilder::new().with_group(sticky_group).build();
+
+    let result = config.validate();
+    assert!(result.is_err());
+    if let Err(e) = result {
+        assert!(e.to_string().contains("Invalid sticky header"));
+    } else {
+        panic!("Expected Config error for invalid sticky header");
+    }
+}
+
+#[test]
+fn test_config_validation_incomplete_tls_identity() {
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            c.upstream_groups[0].http_client.tls = Some(TlsConfig {
+                cert: Some("/etc/llmproxy/client.crt".to_string()), // 缺少私钥
+                ..Default::default()
+            });
+        })
+        .build();
+
+    let result = config.validate();
+    assert!(result.is_err());
+    if let Err(e) = result {
+        assert!(e
+            .to_string()
+            .contains("cert and key must be configured together"));
+    } else {
+        panic!("Expected Config error for incomplete TLS identity");
+    }
+}
+
+#[test]
+fn test_config_validation_http2_with_http1() {
+    let config = TestConfigBuilder::new()
+        .map_config(|c| {
+            c.upstream_groups[0].http_client.http_version = HttpVersion::Http1;
+            c.upstream_groups[0].http_client.http2 = Some(Http2Config::default());
+        })
+        .build();
+
+    let result = config.validate();
+    assert!(result.is_err());
+    if let Err(e) = result {
+        assert!(e.to_string().contains("HTTP/2 settings cannot be used"));
+    } else {
+        panic!("Expected Config error for HTTP/2 settings with http1");
+    }
+}
+
+#[test]
+fn test_config_validation_model_aliases() {
+    let alias = |name: &str, group: &str| ModelAlias {
+        name: name.to_string(),
+        group: group.to_string(),
+        model: "gpt-4o".to_string(),
+    };
+    let validate = |models: Vec<ModelAlias>| {
+        TestConfigBuilder::new()
+            .map_config(|c| c.models = models)
+            .build()
+            .validate()
+    };
+
+    assert!(validate(vec![alias("smart", "test_group")]).is_ok());
+
+    let result = validate(vec![alias("smart", "missing_group")]);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Model alias 'smart' references an unknown upstream group"));
+
+    let result = validate(vec![
+        alias("smart", "test_group"),
+        alias("smart", "test_group"),
+    ]);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Duplicate model alias"));
+}
+
+#[test]
+fn test_config_validation_load_shedding() {
+    let validate = |yaml: &str| {
+        let shedding: LoadSheddingConfig = serde_yaml::from_str(yaml).unwrap();
+        TestConfigBuilder::new()
+            .map_config(|c| c.http_server.as_mut().unwrap().load_shedding = Some(shedding))
+            .build()
+            .validate()
+    };
+
+    // 未配置的参数使用默认值
+    let shedding: LoadSheddingConfig = serde_yaml::from_str("max_memory_mb: 512").unwrap();
+    assert_eq!(shedding.interval_ms, 500);
+    assert_eq!(shedding.priority_header, "x-priority");
+
+    assert!(validate("max_memory_mb: 512").is_ok());
+    assert!(validate("max_event_loop_lag_ms: 200\ninterval_ms: 100").is_ok());
+    assert!(validate("{}")
+        .unwrap_err()
+        .to_string()
+        .contains("Load shedding requires max_memory_mb or max_event_loop_lag_ms"));
+    assert!(validate("max_memory_mb: 1").is_err());
+    assert!(validate("max_memory_mb: 512\ninterval_ms: 1").is_err());
+    assert!(
+        validate("max_memory_mb: 512\npriority_header: \"bad header\"")
+            .unwrap_err()
+            .to_string()
+            .contains("Invalid load shedding priority header name")
+    );
+}
+
+#[test]
+fn test_config_warnings() {
+    assert!(
+        llmproxy::config::validation::config_warnings(&TestConfigBuilder::new().build()).is_empty()
+    );
+
+    let config = TestConfigBuilder::new()
+        .with_upstream(UpstreamConfig {
+            name: "unused_upstream".to_string(),
+            ..TestConfigBuilder::new().build().upstreams[0].clone()
+        })
+        .with_group(UpstreamGroupConfig {
+            name: "failover_group".to_string(),
+            upstreams: vec![UpstreamRef {
+                name: "test_upstream".to_string(),
+                weight: 1,
+            }],
+            balance: BalanceConfig {
+                strategy: BalanceStrategy::Failover,
+            },
+            http_client: HttpClientConfig {
+                stream_mode: true,
+                timeout: llmproxy::config::HttpClientTimeoutConfig {
+                    stream_idle: Some(5),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            sticky: None,
+            discovery: None,
+        })
+        .build();
+    assert!(config.validate().is_ok());
+
+    // 全部问题一次列出
+    let warnings = llmproxy::config::validation::config_warnings(&config);
+    assert_eq!(warnings.len(), 4, "{:?}", warnings);
+    assert!(warnings[0].contains("'failover_group' uses the failover strategy"));
+    assert!(warnings[1].contains("'failover_group' enables stream_mode"));
+    assert!(warnings[2].contains("Upstream 'unused_upstream' is not referenced"));
+    assert!(warnings[3].contains("Upstream group 'failover_group' is not referenced"));
+
+    // 被模型别名引用的上游组不再报告
+    let config = llmproxy::config::Config {
+        models: vec![ModelAlias {
+            name: "fast".to_string(),
+            group: "failover_group".to_string(),
+            model: "gpt-4o-mini".to_string(),
+        }],
+        ..config
+    };
+    let warnings = llmproxy::config::validation::config_warnings(&config);
+    assert_eq!(warnings.len(), 3, "{:?}", warnings);
+}
+
+#[test]
+fn test_config_validation_reports_all_errors() {
+    let upstream = TestConfigBuilder::new().build().upstreams[0].clone();
+    let config = TestConfigBuilder::new()
+        .with_upstream(upstream)
+        .with_group(UpstreamGroupConfig {
+            name: "broken_group".to_string(),
+            upstreams: vec![UpstreamRef {
+                name: "missing_upstream".to_string(),
+                weight: 1,
+            }],
+            balance: BalanceConfig::default(),
+            http_client: HttpClientConfig::default(),
+            sticky: None,
+            discovery: None,
+        })
+        .map_config(|c| {
+            let http_server = c.http_server.as_mut().unwrap();
+            http_server.forwards[0].default_group = "missing_group".to_string();
+            http_server.load_shedding = Some(serde_yaml::from_str("max_memory_mb: 1").unwrap());
+        })
+        .build();
+
+    // 字段错误和引用错误一次全部列出
+    let errors = llmproxy::config::validation::error_messages(&config.validate().unwrap_err());
+    assert_eq!(errors.len(), 4, "{:?}", errors);
+    assert!(errors[0].contains("Duplicate upstream name found: test_upstream"));
+    assert!(errors[1].contains("Forward 'test_forward' references an unknown upstream group"));
+    assert!(errors[2].contains("references an unknown upstream: missing_upstream"));
+    assert!(errors[3].starts_with("http_server.load_shedding.max_memory_mb: "));
+
+    let (_dir, path) = super::common::create_temp_config_file(&config);
+    let message = llmproxy::config::Config::from_file(path)
+        .unwrap_err()
+        .to_string();
+    assert!(message.contains("failed with 4 errors"), "{}", message);
+    assert!(message.contains("\n  - Duplicate upstream name found"));
+}
@@ -0,0 +1,87 @@
+// tests/config/watch.rs
+
+// This module contains tests for watching configuration files for changes.
+
+use llmproxy::config::FileWatcher;
+use std::{fs, time::Duration};
+use tempfile::tempdir;
+
+// 等待变化的最长时间
+const CHANGE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// 在防抖时间内是否报告了变化
+async fn changed_within(watcher: &mut FileWatcher, timeout: Duration) -> bool {
+    tokio::time::timeout(timeout, watcher.changed())
+        .await
+        .is_ok()
+}
+
+#[tokio::test]
+async fn test_file_watcher_reports_changes() {
+    let dir = tempdir().unwrap();
+    let config = dir.path().join("config.yaml");
+    let secret = dir.path().join("token");
+    fs::write(&config, "upstreams: []\n").unwrap();
+    fs::write(&secret, "old").unwrap();
+
+    let mut watcher = FileWatcher::new(Duration::from_millis(100)).unwrap();
+    watcher.watch(&[config.clone(), secret.clone()]);
+
+    // 同一目录中的其他文件不触发重新加载
+    fs::write(dir.path().join("notes.txt"), "unrelated").unwrap();
+    assert!(!changed_within(&mut watcher, Duration::from_millis(500)).await);
+
+    fs::write(&config, "upstreams: []\nmodels: []\n").unwrap();
+    assert!(changed_within(&mut watcher, CHANGE_TIMEOUT).await);
+
+    fs::write(&secret, "new").unwrap();
+    assert!(changed_within(&mut watcher, CHANGE_TIMEOUT).await);
+
+    // 不再监听的文件不触发重新加载
+    watcher.watch(std::slice::from_ref(&config));
+    fs::write(&secret, "newer").unwrap();
+    assert!(!changed_within(&mut watcher, Duration::from_millis(500)).await);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_file_watcher_kubernetes_configmap() {
+    use std::os::unix::fs::symlink;
+
+    // 模拟 kubelet 挂载的 ConfigMap：config.yaml -> ..data/config.yaml -> ..2024_01/config.yaml
+    let dir = tempdir().unwrap();
+    let first = dir.path().join("..2024_01");
+    fs::create_dir(&first).unwrap();
+    fs::write(first.join("config.yaml"), "upstreams: []\n").unwrap();
+    symlink("..2024_01", dir.path().join("..data")).unwrap();
+    symlink("..data/config.yaml", dir.path().join("config.yaml")).unwrap();
+
+    let mut watcher = FileWatcher::new(Duration::from_millis(100)).unwrap();
+    watcher.watch(&[dir.path().join("config.yaml")]);
+
+    // kubelet 写入新目录后原子地替换 ..data 符号链接，config.yaml 本身不变
+    let second = dir.path().join("..2024_02");
+    fs::create_dir(&second).unwrap();
+    fs::write(second.join("config.yaml"), "upstreams: []\nmodels: []\n").unwrap();
+    symlink("..2024_02", dir.path().join("..data_tmp")).unwrap();
+    fs::rename(dir.path().join("..data_tmp"), dir.path().join("..data")).unwrap();
+
+    assert!(changed_within(&mut watcher, CHANGE_TIMEOUT).await);
+    assert_eq!(
+        fs::read_to_string(dir.path().join("config.yaml")).unwrap(),
+        "upstreams: []\nmodels: []\n"
+    );
+}
+
+#[tokio::test]
+async fn test_file_watcher_directory() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("00-server.yaml"), "upstreams: []\n").unwrap();
+
+    let mut watcher = FileWatcher::new(Duration::from_millis(100)).unwrap();
+    watcher.watch(&[dir.path().to_path_buf()]);
+
+    // 目录中新增的文件同样触发重新加载
+    fs::write(dir.path().join("10-openai.yaml"), "models: []\n").unwrap();
+    assert!(changed_within(&mut watcher, CHANGE_TIMEOUT).await);
+}
@@ -0,0 +1,361 @@
+use llmproxy::{
+    config::{
+        BalanceConfig, BalanceStrategy, ConsulDiscoveryConfig, DnsDiscoveryConfig,
+        GroupDiscoveryConfig, GroupDiscoveryType, HttpClientConfig, KubernetesDiscoveryConfig,
+        UpstreamConfig, UpstreamGroupConfig, UpstreamRef,
+    },
+    discovery::{ConsulDiscovery, DnsDiscovery, Endpoint, KubernetesApi, KubernetesDiscovery},
+    upstream::{RequestContext, UpstreamManager},
+};
+use reqwest::{header::HeaderMap, Method};
+use serde_json::json;
+use std::sync::Arc;
+use wiremock::{
+    matchers::{method, path, query_param},
+    Mock, MockServer, ResponseTemplate,
+};
+
+// 创建单个上游及其上游组配置
+fn discovery_configs(
+    url: &str,
+    dns: Option<DnsDiscoveryConfig>,
+) -> (Vec<UpstreamConfig>, Vec<UpstreamGroupConfig>) {
+    let upstream = UpstreamConfig {
+        name: "discovered_upstream".to_string(),
+        url: url.to_string().into(),
+        weight: 1,
+        http_client: HttpClientConfig::default(),
+        auth: None,
+        headers: vec![],
+        breaker: None,
+        adaptive: None,
+        hint: None,
+        enabled: true,
+        proxy: true,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect: None,
+        stream_normalize: None,
+        agent: None,
+        default_headers: Default::default(),
+        pricing: vec![],
+        dns,
+        warmup: None,
+    };
+
+    let group = UpstreamGroupConfig {
+        name: "discovered_group".to_string(),
+        upstreams: vec![UpstreamRef {
+            name: "discovered_upstream".to_string(),
+            weight: 1,
+        }],
+        balance: BalanceConfig {
+            strategy: BalanceStrategy::RoundRobin,
+        },
+        http_client: HttpClientConfig::default(),
+        sticky: None,
+        discovery: None,
+    };
+
+    (vec![upstream], vec![group])
+}
+
+// 启动返回固定响应的模拟服务器
+async fn mock_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn endpoint_of(server: &MockServer) -> Endpoint {
+    Endpoint::new(server.address().ip().to_string(), server.address().port())
+}
+
+// 上游组中各托管上游的地址
+fn group_endpoints(manager: &UpstreamManager) -> Vec<Option<String>> {
+    manager.group_status()[0]
+        .upstreams
+        .iter()
+        .map(|status| status.endpoint.clone())
+        .collect()
+}
+
+async fn send(manager: &UpstreamManager, count: usize) {
+    for _ in 0..count {
+        let response = manager
+            .forward_request(
+                "discovered_group",
+                "/v1/chat",
+                &RequestContext::default(),
+                &Method::GET,
+                HeaderMap::new(),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+    }
+}
+
+#[test]
+fn test_endpoint_apply() {
+    let mut url = reqwest::Url::parse("https://llm.internal/v1/chat").unwrap();
+    Endpoint::new("10.0.0.7", 8443).apply(&mut url);
+    assert_eq!(url.as_str(), "https://10.0.0.7:8443/v1/chat");
+
+    let endpoint = Endpoint::new("fd00::7", 8000);
+    endpoint.apply(&mut url);
+    assert_eq!(url.as_str(), "https://[fd00::7]:8000/v1/chat");
+    assert_eq!(endpoint.to_string(), "[fd00::7]:8000");
+}
+
+#[tokio::test]
+async fn test_upstream_endpoints_expand_balancer_targets() {
+    let server1 = mock_server().await;
+    let server2 = mock_server().await;
+
+    // 上游 URL 中的地址在发现地址后不再使用
+    let (upstreams, groups) = discovery_configs("http://127.0.0.1:1/v1", None);
+    let manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+    assert_eq!(group_endpoints(&manager), vec![None]);
+
+    let endpoints = vec![endpoint_of(&server1), endpoint_of(&server2)];
+    assert!(manager
+        .set_upstream_endpoints("discovered_upstream", endpoints.clone())
+        .await
+        .unwrap());
+    assert_eq!(group_endpoints(&manager).len(), 2);
+    // 地址没有变化时不更新
+    assert!(!manager
+        .set_upstream_endpoints("discovered_upstream", endpoints.into_iter().rev().collect())
+        .await
+        .unwrap());
+
+    send(&manager, 4).await;
+    assert_eq!(server1.received_requests().await.unwrap().len(), 2);
+    assert_eq!(server2.received_requests().await.unwrap().len(), 2);
+
+    // 地址消失后不再接收请求
+    assert!(manager
+        .set_upstream_endpoints("discovered_upstream", vec![endpoint_of(&server2)])
+        .await
+        .unwrap());
+    assert_eq!(
+        group_endpoints(&manager),
+        vec![Some(endpoint_of(&server2).to_string())]
+    );
+    send(&manager, 2).await;
+    assert_eq!(server1.received_requests().await.unwrap().len(), 2);
+    assert_eq!(server2.received_requests().await.unwrap().len(), 4);
+
+    assert!(manager
+        .set_upstream_endpoints("unknown_upstream", vec![])
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_dns_discovery_resolves_upstream_host() {
+    let server = mock_server().await;
+    let port = server.address().port();
+
+    // 未启用服务发现时不创建
+    let (upstreams, groups) = discovery_configs(&server.uri(), None);
+    let manager = Arc::new(
+        UpstreamManager::new(upstreams.clone(), groups)
+            .await
+            .unwrap(),
+    );
+    assert!(DnsDiscovery::new(manager, &upstreams).unwrap().is_none());
+
+    let url = format!("http://localhost:{}/v1", port);
+    let (upstreams, groups) = discovery_configs(&url, Some(DnsDiscoveryConfig::default()));
+    let manager = Arc::new(
+        UpstreamManager::new(upstreams.clone(), groups)
+            .await
+            .unwrap(),
+    );
+    let discovery = DnsDiscovery::new(manager.clone(), &upstreams)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(discovery.refresh().await, 1);
+    assert!(group_endpoints(&manager).contains(&Some(format!("127.0.0.1:{}", port))));
+    // 解析结果没有变化
+    assert_eq!(discovery.refresh().await, 0);
+}
+
+#[tokio::test]
+async fn test_kubernetes_discovery_lists_ready_endpoints() {
+    let server1 = mock_server().await;
+    let server2 = mock_server().await;
+
+    // 每个 EndpointSlice 使用各自的第一个端口，未就绪的地址不使用
+    let slice = |name: &str, server: &MockServer, ready: bool| {
+        json!({
+            "metadata": { "name": name },
+            "addressType": "IPv4",
+            "endpoints": [{
+                "addresses": [server.address().ip().to_string()],
+                "conditions": { "ready": ready },
+            }],
+            "ports": [{ "name": "http", "port": server.address().port() }],
+        })
+    };
+    let api_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(
+            "/apis/discovery.k8s.io/v1/namespaces/inference/endpointslices",
+        ))
+        .and(query_param(
+            "labelSelector",
+            "kubernetes.io/service-name=vllm",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "metadata": { "resourceVersion": "42" },
+            "items": [
+                slice("vllm-a", &server1, true),
+                slice("vllm-b", &server2, true),
+                slice("vllm-c", &server2, false),
+            ],
+        })))
+        .mount(&api_server)
+        .await;
+    let api = || {
+        KubernetesApi::new(
+            api_server.uri().parse().unwrap(),
+            Some("token".to_string()),
+            "inference",
+        )
+    };
+
+    // 未启用服务发现时不创建
+    let (upstreams, mut groups) = discovery_configs("http://127.0.0.1:1/v1", None);
+    let manager = Arc::new(
+        UpstreamManager::new(upstreams.clone(), groups.clone())
+            .await
+            .unwrap(),
+    );
+    assert!(KubernetesDiscovery::with_api(manager, &groups, api()).is_none());
+
+    groups[0].discovery = Some(GroupDiscoveryConfig {
+        r#type: GroupDiscoveryType::Kubernetes,
+        kubernetes: Some(KubernetesDiscoveryConfig {
+            service: "vllm".to_string(),
+            namespace: None,
+            port: None,
+        }),
+        consul: None,
+    });
+    let manager = Arc::new(
+        UpstreamManager::new(upstreams, groups.clone())
+            .await
+            .unwrap(),
+    );
+    let discovery = KubernetesDiscovery::with_api(manager.clone(), &groups, api()).unwrap();
+
+    assert_eq!(discovery.refresh().await, 1);
+    let mut endpoints = group_endpoints(&manager);
+    endpoints.sort();
+    let mut expected = vec![
+        Some(endpoint_of(&server1).to_string()),
+        Some(endpoint_of(&server2).to_string()),
+    ];
+    expected.sort();
+    assert_eq!(endpoints, expected);
+    // 地址没有变化
+    assert_eq!(discovery.refresh().await, 0);
+
+    // 上游组的地址优先于上游自身的地址
+    assert!(manager
+        .set_upstream_endpoints("discovered_upstream", vec![Endpoint::new("127.0.0.1", 1)])
+        .await
+        .unwrap());
+    assert_eq!(group_endpoints(&manager).len(), 2);
+
+    send(&manager, 4).await;
+    assert_eq!(server1.received_requests().await.unwrap().len(), 2);
+    assert_eq!(server2.received_requests().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_consul_discovery_filters_instances() {
+    let server1 = mock_server().await;
+    let server2 = mock_server().await;
+
+    // 实例未注册地址时使用节点地址，缺少标签的实例不使用
+    let entry = |service_address: &str, server: &MockServer, tags: &[&str]| {
+        json!({
+            "Node": { "Node": "node", "Address": server.address().ip().to_string() },
+            "Service": {
+                "Service": "vllm",
+                "Address": service_address,
+                "Port": server.address().port(),
+                "Tags": tags,
+            },
+            "Checks": [],
+        })
+    };
+    let consul = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/health/service/vllm"))
+        .and(query_param("passing", "true"))
+        .and(query_param("dc", "dc1"))
+        .and(query_param("tag", "gpu"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("X-Consul-Index", "7")
+                .set_body_json(json!([
+                    entry("", &server1, &["gpu"]),
+                    entry(
+                        &server2.address().ip().to_string(),
+                        &server2,
+                        &["gpu", "a100"]
+                    ),
+                    entry("", &server2, &[]),
+                ])),
+        )
+        .mount(&consul)
+        .await;
+
+    let (upstreams, mut groups) = discovery_configs("http://127.0.0.1:1/v1", None);
+    let manager = Arc::new(
+        UpstreamManager::new(upstreams.clone(), groups.clone())
+            .await
+            .unwrap(),
+    );
+    // 未启用服务发现时不创建
+    assert!(ConsulDiscovery::new(manager, &groups).unwrap().is_none());
+
+    groups[0].discovery = Some(GroupDiscoveryConfig {
+        r#type: GroupDiscoveryType::Consul,
+        kubernetes: None,
+        consul: Some(ConsulDiscoveryConfig {
+            service: "vllm".to_string(),
+            address: consul.uri(),
+            datacenter: Some("dc1".to_string()),
+            tags: vec!["gpu".to_string()],
+            passing: true,
+        }),
+    });
+    let manager = Arc::new(
+        UpstreamManager::new(upstreams, groups.clone())
+            .await
+            .unwrap(),
+    );
+    let discovery = ConsulDiscovery::new(manager.clone(), &groups)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(discovery.refresh().await, 1);
+    assert_eq!(group_endpoints(&manager).len(), 2);
+    assert_eq!(discovery.refresh().await, 0);
+
+    send(&manager, 4).await;
+    assert_eq!(server1.received_requests().await.unwrap().len(), 2);
+    assert_eq!(server2.received_requests().await.unwrap().len(), 2);
+}
@@ -0,0 +1,228 @@
+use llmproxy::{
+    config::{
+        AuthConfig, AuthType, BalanceConfig, BalanceStrategy, ExternalAuthConfig, HttpClientConfig,
+        UpstreamConfig, UpstreamGroupConfig, UpstreamRef,
+    },
+    upstream::{RequestContext, UpstreamManager},
+};
+use reqwest::{header::HeaderMap, Method};
+use std::{fs, path::Path, sync::Arc, time::Duration};
+use wiremock::{
+    matchers::{header, method},
+    Mock, MockServer, ResponseTemplate,
+};
+
+// 创建使用外部命令认证的上游管理器
+async fn external_manager(upstream_url: &str, script: &str, timeout: u64) -> UpstreamManager {
+    let upstream = UpstreamConfig {
+        name: "external_upstream".to_string(),
+        url: format!("{}/v1/chat", upstream_url).into(),
+        weight: 1,
+        http_client: HttpClientConfig::default(),
+        auth: Some(AuthConfig {
+            r#type: AuthType::External,
+            token: None,
+            token_file: None,
+            username: None,
+            password: None,
+            password_file: None,
+            oauth2: None,
+            external: Some(ExternalAuthConfig {
+                command: "sh".to_string(),
+                args: vec!["-c".to_string(), script.to_string()],
+                refresh_interval: 300,
+                timeout,
+            }),
+        }),
+        headers: vec![],
+        breaker: None,
+        adaptive: None,
+        hint: None,
+        enabled: true,
+        proxy: true,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect: None,
+        stream_normalize: None,
+        agent: None,
+        default_headers: Default::default(),
+        pricing: vec![],
+        dns: None,
+        warmup: None,
+    };
+
+    let group = UpstreamGroupConfig {
+        name: "external_group".to_string(),
+        upstreams: vec![UpstreamRef {
+            name: "external_upstream".to_string(),
+            weight: 1,
+        }],
+        balance: BalanceConfig {
+            strategy: BalanceStrategy::RoundRobin,
+        },
+        http_client: HttpClientConfig::default(),
+        sticky: None,
+        discovery: None,
+    };
+
+    UpstreamManager::new(vec![upstream], vec![group])
+        .await
+        .unwrap()
+}
+
+// 每次执行都递增计数并输出 token-<计数> 的脚本
+fn counting_script(counter: &Path) -> String {
+    format!(
+        "n=$(( $(cat {0} 2>/dev/null || echo 0) + 1 )); echo $n > {0}; echo token-$n",
+        counter.display()
+    )
+}
+
+async fn forward(manager: &UpstreamManager) -> reqwest::Response {
+    manager
+        .forward_request(
+            "external_group",
+            "/",
+            &RequestContext::default(),
+            &Method::GET,
+            HeaderMap::new(),
+            None,
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_external_auth_token_cached() {
+    let upstream_server = MockServer::start().await;
+    let dir = tempfile::tempdir().unwrap();
+    let counter = dir.path().join("counter");
+
+    Mock::given(method("GET"))
+        .and(header("authorization", "Bearer token-1"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(2)
+        .mount(&upstream_server)
+        .await;
+
+    let manager = external_manager(&upstream_server.uri(), &counting_script(&counter), 10).await;
+
+    // 刷新间隔内只执行一次命令
+    for _ in 0..2 {
+        assert_eq!(forward(&manager).await.status(), 200);
+    }
+    assert_eq!(fs::read_to_string(&counter).unwrap().trim(), "1");
+}
+
+#[tokio::test]
+async fn test_external_auth_refreshed_after_unauthorized() {
+    let upstream_server = MockServer::start().await;
+    let dir = tempfile::tempdir().unwrap();
+    let counter = dir.path().join("counter");
+
+    Mock::given(method("GET"))
+        .and(header("authorization", "Bearer token-1"))
+        .respond_with(ResponseTemplate::new(401))
+        .mount(&upstream_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(header("authorization", "Bearer token-2"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&upstream_server)
+        .await;
+
+    let manager = external_manager(&upstream_server.uri(), &counting_script(&counter), 10).await;
+
+    // 上游拒绝令牌后，下一次请求重新执行命令
+    assert_eq!(forward(&manager).await.status(), 401);
+    assert_eq!(forward(&manager).await.status(), 200);
+    assert_eq!(fs::read_to_string(&counter).unwrap().trim(), "2");
+}
+
+#[tokio::test]
+async fn test_external_auth_concurrent_unauthorized_refreshes_once() {
+    let upstream_server = MockServer::start().await;
+    let dir = tempfile::tempdir().unwrap();
+    let counter = dir.path().join("counter");
+
+    // 前三个携带旧令牌的请求延迟返回 401，其余立即返回 401
+    Mock::given(method("GET"))
+        .and(header("authorization", "Bearer token-1"))
+        .respond_with(ResponseTemplate::new(401).set_delay(Duration::from_millis(500)))
+        .up_to_n_times(3)
+        .mount(&upstream_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(header("authorization", "Bearer token-1"))
+        .respond_with(ResponseTemplate::new(401))
+        .mount(&upstream_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(header("authorization", "Bearer token-2"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&upstream_server)
+        .await;
+
+    let manager =
+        Arc::new(external_manager(&upstream_server.uri(), &counting_script(&counter), 10).await);
+    let slow: Vec<_> = (0..3)
+        .map(|_| {
+            let manager = manager.clone();
+            tokio::spawn(async move { forward(&manager).await.status() })
+        })
+        .collect();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // 第一个 401 丢弃旧令牌，下一次请求获取新令牌
+    assert_eq!(forward(&manager).await.status(), 401);
+    assert_eq!(forward(&manager).await.status(), 200);
+
+    // 之后到达的旧令牌的 401 不会丢弃新令牌
+    for request in slow {
+        assert_eq!(request.await.unwrap(), 401);
+    }
+    assert_eq!(forward(&manager).await.status(), 200);
+    assert_eq!(fs::read_to_string(&counter).unwrap().trim(), "2");
+}
+
+#[tokio::test]
+async fn test_external_auth_command_failure() {
+    let manager = external_manager(
+        "http://127.0.0.1:1",
+        "echo 'credentials expired' >&2; exit 3",
+        10,
+    )
+    .await;
+
+    let err = manager
+        .forward_request(
+            "external_group",
+            "/",
+            &RequestContext::default(),
+            &Method::GET,
+            HeaderMap::new(),
+            None,
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("credentials expired"));
+}
+
+#[tokio::test]
+async fn test_external_auth_command_timeout() {
+    let manager = external_manager("http://127.0.0.1:1", "sleep 5; echo late-token", 1).await;
+
+    let err = manager
+        .forward_request(
+            "external_group",
+            "/",
+            &RequestContext::default(),
+            &Method::GET,
+            HeaderMap::new(),
+            None,
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("timed out"));
+}
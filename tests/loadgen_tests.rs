@@ -0,0 +1,152 @@
+use clap::Parser;
+use llmproxy::{
+    args::{Args, Command, LoadgenArgs},
+    loadgen::{build_request_body, execute, is_stream_request, percentile},
+};
+use serde_json::json;
+use std::time::Duration;
+use wiremock::{
+    matchers::{body_partial_json, header, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+// 辅助函数：解析 loadgen 子命令参数
+fn parse_loadgen_args(extra: &[&str]) -> LoadgenArgs {
+    let args = Args::try_parse_from(["llmproxyd", "loadgen"].iter().chain(extra)).unwrap();
+    assert!(args.validation().is_ok());
+    match args.command {
+        Some(Command::Loadgen(loadgen)) => loadgen,
+        other => panic!("unexpected command: {:?}", other),
+    }
+}
+
+#[test]
+fn test_loadgen_args() {
+    let args = parse_loadgen_args(&[]);
+    assert_eq!(args.url, "http://localhost:3000");
+    assert_eq!(args.path, "/v1/chat/completions");
+    assert_eq!(args.requests, 100);
+    assert_eq!(args.concurrency, 10);
+    assert_eq!(args.stream_ratio, 0.0);
+    assert!(args.headers.is_empty());
+
+    let args = parse_loadgen_args(&["-n", "5", "-C", "2", "-H", "Authorization: Bearer sk-test"]);
+    assert_eq!(args.requests, 5);
+    assert_eq!(args.concurrency, 2);
+    assert_eq!(
+        args.headers,
+        vec![("Authorization".to_string(), "Bearer sk-test".to_string())]
+    );
+
+    assert!(Args::try_parse_from(["llmproxyd", "loadgen", "-H", "no-separator"]).is_err());
+    for extra in [
+        ["-n", "0"],
+        ["-C", "0"],
+        ["--stream-ratio", "1.5"],
+        ["--timeout", "0"],
+    ] {
+        let args = Args::try_parse_from(["llmproxyd", "loadgen", extra[0], extra[1]]).unwrap();
+        assert!(args.validation().is_err(), "{:?} should be rejected", extra);
+    }
+}
+
+#[test]
+fn test_is_stream_request() {
+    let count = |ratio: f64| (0..100).filter(|i| is_stream_request(*i, ratio)).count();
+    assert_eq!(count(0.0), 0);
+    assert_eq!(count(0.25), 25);
+    assert_eq!(count(1.0), 100);
+
+    // 流式请求均匀分布在请求序列中
+    let streams: Vec<bool> = (0..4).map(|i| is_stream_request(i, 0.5)).collect();
+    assert_eq!(streams, vec![false, true, false, true]);
+}
+
+#[test]
+fn test_percentile() {
+    let sorted: Vec<Duration> = (1..=10).map(Duration::from_millis).collect();
+    assert_eq!(percentile(&sorted, 50.0), Some(Duration::from_millis(5)));
+    assert_eq!(percentile(&sorted, 90.0), Some(Duration::from_millis(9)));
+    assert_eq!(percentile(&sorted, 99.0), Some(Duration::from_millis(10)));
+    assert_eq!(percentile(&sorted, 100.0), Some(Duration::from_millis(10)));
+    assert_eq!(percentile(&[], 50.0), None);
+}
+
+#[test]
+fn test_build_request_body() {
+    let args = parse_loadgen_args(&["--model", "test-model", "--prompt-words", "3"]);
+
+    let body = build_request_body(&args, 0, false);
+    assert_eq!(body["model"], "test-model");
+    assert_eq!(body["max_tokens"], 16);
+    assert_eq!(body["messages"][0]["role"], "user");
+    assert_eq!(
+        body["messages"][0]["content"]
+            .as_str()
+            .unwrap()
+            .split(' ')
+            .count(),
+        3
+    );
+    assert!(body.get("stream").is_none());
+
+    let body = build_request_body(&args, 1, true);
+    assert_eq!(body["stream"], true);
+    assert_eq!(body["stream_options"]["include_usage"], true);
+}
+
+#[tokio::test]
+async fn test_loadgen_execute() {
+    let mock_server = MockServer::start().await;
+
+    let events = "data: {\"choices\":[{\"delta\":{\"content\":\"a\"}}]}\n\n\
+                  data: {\"choices\":[{\"delta\":{\"content\":\"b\"}}]}\n\n\
+                  data: [DONE]\n\n";
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(header("authorization", "Bearer sk-test"))
+        .and(body_partial_json(json!({"stream": true})))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(events, "text/event-stream"))
+        .with_priority(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(header("authorization", "Bearer sk-test"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"choices": []})))
+        .mount(&mock_server)
+        .await;
+
+    let uri = mock_server.uri();
+    let args = parse_loadgen_args(&[
+        "--url",
+        &uri,
+        "-n",
+        "8",
+        "-C",
+        "3",
+        "--stream-ratio",
+        "0.5",
+        "-H",
+        "Authorization: Bearer sk-test",
+    ]);
+    let report = execute(&args).await.unwrap();
+
+    assert_eq!(report.total, 8);
+    assert_eq!(report.succeeded, 8);
+    assert_eq!(report.streamed, 4);
+    assert_eq!(report.statuses.get("200"), Some(&8));
+    assert_eq!(report.latencies.len(), 8);
+    assert_eq!(report.ttfbs.len(), 4);
+    // 结束标记不计入事件数
+    assert_eq!(report.events, 8);
+    assert!(report
+        .summary()
+        .contains("8 total, 8 succeeded, 0 failed, 4 streamed"));
+
+    // 没有认证头部时上游返回 404，请求计为失败
+    let args = parse_loadgen_args(&["--url", &uri, "-n", "2", "-C", "1"]);
+    let report = execute(&args).await.unwrap();
+    assert_eq!(report.succeeded, 0);
+    assert_eq!(report.statuses.get("404"), Some(&2));
+}
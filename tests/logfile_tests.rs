@@ -0,0 +1,77 @@
+use llmproxy::{
+    args::LogRotation,
+    logfile::{LogFileWriter, RotatingFile},
+};
+use std::{
+    fs,
+    time::{Duration, UNIX_EPOCH},
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_rotating_file_by_size() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("llmproxy.log");
+    let mut file = RotatingFile::open(&path, 10, 2, LogRotation::Never).unwrap();
+
+    // 写入后超出最大大小时先轮转，只保留配置数量的轮转文件
+    for line in ["line-1\n", "line-2\n", "line-3\n", "line-4\n"] {
+        file.write(line.as_bytes()).unwrap();
+    }
+    assert_eq!(fs::read_to_string(&path).unwrap(), "line-4\n");
+    assert_eq!(
+        fs::read_to_string(dir.path().join("llmproxy.log.1")).unwrap(),
+        "line-3\n"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.path().join("llmproxy.log.2")).unwrap(),
+        "line-2\n"
+    );
+    assert!(!dir.path().join("llmproxy.log.3").exists());
+}
+
+#[test]
+fn test_rotating_file_by_time() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("llmproxy.log");
+    let mut file = RotatingFile::open(&path, 1024, 5, LogRotation::Hourly).unwrap();
+    let hour = |n: u64| UNIX_EPOCH + Duration::from_secs(3600 * 1000 + 3600 * n);
+
+    // 同一小时内不轮转，进入新的小时时轮转
+    file.write_at(b"first\n", hour(0)).unwrap();
+    file.write_at(b"second\n", hour(0) + Duration::from_secs(60))
+        .unwrap();
+    file.write_at(b"third\n", hour(1)).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "third\n");
+    assert_eq!(
+        fs::read_to_string(dir.path().join("llmproxy.log.1")).unwrap(),
+        "first\nsecond\n"
+    );
+
+    // 按天轮转时同一天的不同小时写入同一个文件
+    let path = dir.path().join("daily.log");
+    let mut file = RotatingFile::open(&path, 1024, 5, LogRotation::Daily).unwrap();
+    let day = UNIX_EPOCH + Duration::from_secs(86400 * 100);
+    file.write_at(b"morning\n", day).unwrap();
+    file.write_at(b"evening\n", day + Duration::from_secs(3600 * 20))
+        .unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "morning\nevening\n");
+    assert!(!dir.path().join("daily.log.1").exists());
+}
+
+#[test]
+fn test_log_file_writer() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("llmproxy.log");
+    let file = RotatingFile::open(&path, 1024 * 1024, 5, LogRotation::Never).unwrap();
+    let (writer, guard) = LogFileWriter::spawn(file).unwrap();
+
+    // 日志行由后台线程写入，守卫丢弃时等待写完
+    for n in 0..100 {
+        assert!(writer.write(format!("line {}\n", n).into_bytes()));
+    }
+    drop(guard);
+    let content = fs::read_to_string(&path).unwrap();
+    assert_eq!(content.lines().count(), 100);
+    assert_eq!(content.lines().last(), Some("line 99"));
+}
@@ -0,0 +1,111 @@
+use llmproxy::metrics::{self, drop_labels, METRICS};
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry};
+
+#[test]
+fn test_drop_labels() {
+    let registry = Registry::new();
+    let requests = IntCounterVec::new(
+        Opts::new("requests_total", "Requests."),
+        &["group", "upstream", "model"],
+    )
+    .unwrap();
+    let duration = HistogramVec::new(
+        HistogramOpts::new("duration_seconds", "Duration.").buckets(vec![1.0, 10.0]),
+        &["group", "upstream"],
+    )
+    .unwrap();
+    registry.register(Box::new(requests.clone())).unwrap();
+    registry.register(Box::new(duration.clone())).unwrap();
+
+    requests.with_label_values(&["a", "u1", "m1"]).inc_by(2);
+    requests.with_label_values(&["a", "u2", "m1"]).inc_by(3);
+    requests.with_label_values(&["a", "u2", "m2"]).inc();
+    requests.with_label_values(&["b", "u1", "m1"]).inc();
+    duration.with_label_values(&["a", "u1"]).observe(0.5);
+    duration.with_label_values(&["a", "u2"]).observe(5.0);
+
+    // 未去掉标签时原样返回
+    assert_eq!(drop_labels(registry.gather(), &[])[1].get_metric().len(), 4);
+
+    let families = drop_labels(
+        registry.gather(),
+        &["upstream".to_string(), "model".to_string()],
+    );
+    let requests = families
+        .iter()
+        .find(|family| family.get_name() == "requests_total")
+        .unwrap();
+    let values: Vec<(String, f64)> = requests
+        .get_metric()
+        .iter()
+        .map(|metric| {
+            let labels: Vec<String> = metric
+                .get_label()
+                .iter()
+                .map(|pair| format!("{}={}", pair.get_name(), pair.get_value()))
+                .collect();
+            (labels.join(","), metric.get_counter().get_value())
+        })
+        .collect();
+    assert_eq!(
+        values,
+        vec![("group=a".to_string(), 6.0), ("group=b".to_string(), 1.0)]
+    );
+
+    // 直方图按分桶合并
+    let duration = families
+        .iter()
+        .find(|family| family.get_name() == "duration_seconds")
+        .unwrap();
+    assert_eq!(duration.get_metric().len(), 1);
+    let histogram = duration.get_metric()[0].get_histogram();
+    assert_eq!(histogram.get_sample_count(), 2);
+    assert_eq!(histogram.get_sample_sum(), 5.5);
+    let counts: Vec<u64> = histogram
+        .get_bucket()
+        .iter()
+        .map(|bucket| bucket.get_cumulative_count())
+        .collect();
+    assert_eq!(counts, vec![1, 2]);
+}
+
+#[test]
+fn test_metrics_label_settings() {
+    // 分桶在创建指标前设置，之后不能修改
+    assert!(metrics::set_duration_buckets(vec![0.5, 2.0]));
+    assert!(!metrics::set_duration_buckets(vec![1.0]));
+    METRICS
+        .http_request_duration_seconds()
+        .with_label_values(&["buckets_forward", "POST"])
+        .observe(1.0);
+    let families = METRICS.gather();
+    let duration = families
+        .iter()
+        .find(|family| family.get_name() == "llmproxy_http_request_duration_seconds")
+        .unwrap();
+    let bounds: Vec<f64> = duration.get_metric()[0]
+        .get_histogram()
+        .get_bucket()
+        .iter()
+        .map(|bucket| bucket.get_upper_bound())
+        .collect();
+    assert_eq!(bounds, vec![0.5, 2.0]);
+
+    // upstream 标签按配置使用上游地址或名称
+    assert_eq!(
+        METRICS.upstream_label("openai", "https://api.openai.com/v1"),
+        "https://api.openai.com/v1"
+    );
+    METRICS.set_upstream_name_label(true);
+    assert_eq!(
+        METRICS.upstream_label("openai", "https://api.openai.com/v1"),
+        "openai"
+    );
+
+    // 关闭的标签不出现在输出中
+    METRICS.set_disabled_labels(vec!["method".to_string()]);
+    let text = String::from_utf8(METRICS.encode_text().unwrap()).unwrap();
+    assert!(text
+        .contains("llmproxy_http_request_duration_seconds_count{forward=\"buckets_forward\"} 1"));
+    assert!(!text.contains("method=\"POST\""));
+}
@@ -0,0 +1,120 @@
+use llmproxy::server::path_map::PathMap;
+
+// ========== 与 radixmap 0.2 的匹配一致性 ==========
+// 以下期望值取自 radixmap 0.2.4 对同一组路由的匹配结果（release 构建下运行，
+// debug 构建下其匹配器会因非对齐指针读取而中止进程）
+
+/// 创建与 radixmap 对照使用的路由表，值为路由模式本身
+fn create_parity_map() -> PathMap<String> {
+    let mut map = PathMap::new();
+    for pattern in [
+        "/api/users",
+        "/api/:id",
+        "/api/*",
+        "/v1/{id:\\d+}/info",
+        "/v1/:name/info",
+        "/files/*",
+        "/a/*/c",
+        "/a/b/c",
+        "/static",
+        "/chat/:model/completions",
+    ] {
+        assert!(map
+            .insert(pattern.to_string(), pattern.to_string())
+            .unwrap()
+            .is_none());
+    }
+    map
+}
+
+/// 测试与 radixmap 结果一致的路径
+#[test]
+fn test_path_map_matches_radixmap() {
+    let map = create_parity_map();
+
+    let cases = [
+        // 静态段优先于参数和通配符
+        ("/api/users", Some("/api/users")),
+        ("/api/42", Some("/api/:id")),
+        ("/api/42/x", Some("/api/*")),
+        ("/api/", Some("/api/*")),
+        // 正则段优先于参数
+        ("/v1/123/info", Some("/v1/{id:\\d+}/info")),
+        ("/v1/abc/info", Some("/v1/:name/info")),
+        // 末尾通配符匹配剩余的全部路径
+        ("/files/a/b/c", Some("/files/*")),
+        ("/files/", Some("/files/*")),
+        // 中间通配符只匹配一个路径段，静态段优先
+        ("/a/x/c", Some("/a/*/c")),
+        ("/a/b/c", Some("/a/b/c")),
+        ("/static", Some("/static")),
+        ("/static/x", None),
+        ("/chat/gpt/completions", Some("/chat/:model/completions")),
+        ("/none", None),
+    ];
+
+    for (path, expected) in cases {
+        assert_eq!(
+            map.get(path).map(String::as_str),
+            expected,
+            "path {:?}",
+            path
+        );
+    }
+}
+
+/// 测试与 radixmap 结果不同的路径
+/// radixmap 按字节前缀匹配且不回溯，PathMap 按完整路径段匹配并在失败时回溯
+#[test]
+fn test_path_map_radixmap_differences() {
+    let map = create_parity_map();
+
+    // radixmap: Some("/files/*")，末尾通配符不再匹配缺少分隔符的父路径
+    assert_eq!(map.get("/files"), None);
+    // radixmap: Some("/api/*")
+    assert_eq!(map.get("/api"), None);
+    // radixmap: Some("/chat/:model/completions")，命名参数不再匹配空路径段
+    assert_eq!(map.get("/chat//completions"), None);
+    // radixmap: None，静态段 /api/users 匹配失败后回溯到通配符
+    assert_eq!(map.get("/api/users/x").map(String::as_str), Some("/api/*"));
+}
+
+/// 测试插入相同模式时替换旧值
+#[test]
+fn test_path_map_insert_replaces_value() {
+    let mut map = PathMap::new();
+    assert!(map.insert("/users/:id".to_string(), 1).unwrap().is_none());
+    assert_eq!(map.insert("/users/:id".to_string(), 2).unwrap(), Some(1));
+    assert_eq!(map.get("/users/7"), Some(&2));
+}
+
+/// 测试删除路由后清理空节点
+#[test]
+fn test_path_map_remove() {
+    let mut map = PathMap::new();
+    map.insert("/users/:id".to_string(), "user").unwrap();
+    map.insert("/files/*".to_string(), "files").unwrap();
+    map.insert("/items/{id:[0-9]+}".to_string(), "item")
+        .unwrap();
+
+    assert_eq!(map.remove("/files/*"), Some("files"));
+    assert_eq!(map.get("/files/a"), None);
+    assert_eq!(map.remove("/files/*"), None);
+
+    assert_eq!(map.remove("/items/{id:[0-9]+}"), Some("item"));
+    assert_eq!(map.get("/items/1"), None);
+
+    assert_eq!(map.remove("/users/:id"), Some("user"));
+    assert!(map.is_empty());
+}
+
+/// 测试无效的路径模式
+#[test]
+fn test_path_map_invalid_patterns() {
+    let mut map = PathMap::new();
+    // 无效的段正则表达式
+    assert!(map.insert("/items/{id:[0-9}".to_string(), ()).is_err());
+    // 通配符只能占据整个路径段
+    assert!(map.insert("/files/*.txt".to_string(), ()).is_err());
+    assert!(map.is_empty());
+}
@@ -0,0 +1,132 @@
+use llmproxy::{
+    config::{
+        BalanceConfig, BalanceStrategy, HttpClientConfig, ProxyConfig, UpstreamConfig,
+        UpstreamGroupConfig, UpstreamRef,
+    },
+    upstream::{RequestContext, UpstreamManager},
+};
+use reqwest::{header::HeaderMap, Method};
+use validator::Validate;
+use wiremock::{matchers::method, Mock, MockServer, ResponseTemplate};
+
+fn upstream(name: &str, url: String, proxy: bool) -> UpstreamConfig {
+    UpstreamConfig {
+        name: name.to_string(),
+        url: url.into(),
+        weight: 1,
+        http_client: HttpClientConfig::default(),
+        auth: None,
+        headers: vec![],
+        breaker: None,
+        adaptive: None,
+        hint: None,
+        enabled: true,
+        proxy,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect: None,
+        stream_normalize: None,
+        agent: None,
+        default_headers: Default::default(),
+        pricing: vec![],
+        dns: None,
+        warmup: None,
+    }
+}
+
+// 每个上游单独成组，组内使用相同的代理配置
+fn group(upstream: &UpstreamConfig, proxy: &ProxyConfig) -> UpstreamGroupConfig {
+    UpstreamGroupConfig {
+        name: format!("{}_group", upstream.name),
+        upstreams: vec![UpstreamRef {
+            name: upstream.name.clone(),
+            weight: 1,
+        }],
+        balance: BalanceConfig {
+            strategy: BalanceStrategy::RoundRobin,
+        },
+        http_client: HttpClientConfig {
+            proxy: Some(proxy.clone()),
+            ..HttpClientConfig::default()
+        },
+        sticky: None,
+        discovery: None,
+    }
+}
+
+async fn forward_body(manager: &UpstreamManager, group_name: &str) -> String {
+    manager
+        .forward_request(
+            group_name,
+            "/",
+            &RequestContext::default(),
+            &Method::GET,
+            HeaderMap::new(),
+            None,
+        )
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_proxy_bypass_rules() {
+    let proxy_server = MockServer::start().await;
+    let upstream_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("via-proxy"))
+        .mount(&proxy_server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("direct"))
+        .mount(&upstream_server)
+        .await;
+
+    let proxy = ProxyConfig {
+        url: proxy_server.uri(),
+        no_proxy: vec!["internal.example.com".to_string(), "10.0.0.0/8".to_string()],
+    };
+    let upstream_url = format!("{}/v1/chat", upstream_server.uri());
+    let upstreams = vec![
+        upstream("external", upstream_url.clone(), true),
+        upstream("internal", upstream_url, false),
+    ];
+    let groups = upstreams.iter().map(|u| group(u, &proxy)).collect();
+    let manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+
+    // 默认通过组代理访问，proxy: false 的上游直连
+    assert_eq!(forward_body(&manager, "external_group").await, "via-proxy");
+    assert_eq!(forward_body(&manager, "internal_group").await, "direct");
+
+    // 匹配 no_proxy 的目标直连
+    let proxy = ProxyConfig {
+        no_proxy: vec!["127.0.0.0/8".to_string()],
+        ..proxy
+    };
+    let upstreams = vec![upstream(
+        "loopback",
+        format!("{}/v1/chat", upstream_server.uri()),
+        true,
+    )];
+    let groups = upstreams.iter().map(|u| group(u, &proxy)).collect();
+    let manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+    assert_eq!(forward_body(&manager, "loopback_group").await, "direct");
+}
+
+#[test]
+fn test_proxy_no_proxy_validation() {
+    let proxy = |no_proxy: &[&str]| ProxyConfig {
+        url: "http://proxy.example.com:8080".to_string(),
+        no_proxy: no_proxy.iter().map(|s| s.to_string()).collect(),
+    };
+
+    assert!(proxy(&["internal.example.com", ".corp", "10.0.0.0/8"])
+        .validate()
+        .is_ok());
+    assert!(proxy(&[""]).validate().is_err());
+    assert!(proxy(&["a.example.com,b.example.com"]).validate().is_err());
+}
@@ -0,0 +1,133 @@
+use llmproxy::{
+    config::{AuthConfig, AuthType, HttpClientConfig, OAuth2Config, OAuth2Grant, TlsConfig},
+    redact::{redact_secrets, to_redacted_json},
+};
+use serde_json::json;
+
+#[test]
+fn test_redact_secrets_nested() {
+    let mut value = json!({
+        "upstreams": [
+            { "name": "openai", "auth": { "type": "bearer", "token": "sk-secret" } },
+            { "name": "basic", "auth": { "type": "basic", "username": "user", "password": "pass" } },
+            { "name": "none", "auth": { "type": "none", "token": null } }
+        ]
+    });
+    redact_secrets(&mut value);
+
+    assert_eq!(value["upstreams"][0]["auth"]["token"], "******");
+    assert_eq!(value["upstreams"][1]["auth"]["username"], "user");
+    assert_eq!(value["upstreams"][1]["auth"]["password"], "******");
+    // 未配置的值保持为空
+    assert!(value["upstreams"][2]["auth"]["token"].is_null());
+}
+
+#[test]
+fn test_auth_config_redacted() {
+    let auth = AuthConfig {
+        r#type: AuthType::Basic,
+        token: None,
+        token_file: None,
+        username: Some("user".to_string()),
+        password: Some("pass".to_string()),
+        password_file: None,
+        oauth2: None,
+        external: None,
+    };
+
+    let redacted = auth.redacted();
+    assert_eq!(redacted.username.as_deref(), Some("user"));
+    assert_eq!(redacted.password.as_deref(), Some("******"));
+    assert!(redacted.token.is_none());
+
+    let json = to_redacted_json(&auth).unwrap();
+    assert_eq!(json["password"], "******");
+}
+
+#[test]
+fn test_oauth2_client_secret_redacted() {
+    let auth = AuthConfig {
+        r#type: AuthType::OAuth2,
+        token: None,
+        token_file: None,
+        username: None,
+        password: None,
+        password_file: None,
+        oauth2: Some(OAuth2Config {
+            grant: OAuth2Grant::ClientCredentials,
+            token_url: Some("https://auth.example.com/oauth2/token".to_string()),
+            client_id: Some("llmproxy".to_string()),
+            client_secret: Some("client-secret".to_string()),
+            client_secret_file: None,
+            service_account_file: None,
+            scopes: vec![],
+            refresh_before: 60,
+        }),
+        external: None,
+    };
+
+    let redacted = auth.redacted().oauth2.unwrap();
+    assert_eq!(redacted.client_id.as_deref(), Some("llmproxy"));
+    assert_eq!(redacted.client_secret.as_deref(), Some("******"));
+
+    let json = to_redacted_json(&auth).unwrap();
+    assert_eq!(json["oauth2"]["client_secret"], "******");
+}
+
+#[test]
+fn test_tls_passphrase_redacted() {
+    let http_client = HttpClientConfig {
+        tls: Some(TlsConfig {
+            cert: Some("/etc/llmproxy/client.crt".to_string()),
+            key: Some("/etc/llmproxy/client.key".to_string()),
+            passphrase: Some("key-passphrase".to_string()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let tls = http_client.redacted().tls.unwrap();
+    assert_eq!(tls.cert.as_deref(), Some("/etc/llmproxy/client.crt"));
+    assert_eq!(tls.passphrase.as_deref(), Some("******"));
+
+    // 回传脱敏占位值时保留现有的密码
+    let mut updated = http_client.redacted();
+    updated.restore_secrets(&http_client);
+    assert_eq!(
+        updated.tls.unwrap().passphrase.as_deref(),
+        Some("key-passphrase")
+    );
+}
+
+#[test]
+fn test_redact_header_values_and_url_credentials() {
+    let mut value = json!({
+        "upstreams": [{
+            "name": "anthropic",
+            "url": "https://api.anthropic.com/v1/messages",
+            "headers": [
+                { "op": "insert", "key": "x-api-key", "value": "sk-ant-secret" },
+                { "op": "remove", "key": "x-remove", "value": null }
+            ],
+            "default_headers": { "x-goog-api-key": "goog-secret" },
+            "query_params": [{ "op": "insert", "key": "key", "value": "query-secret" }]
+        }],
+        "metrics_export": { "headers": { "authorization": "Bearer otlp-secret" } },
+        "redis": { "url": "redis://:redis-secret@127.0.0.1:6379/0" }
+    });
+    redact_secrets(&mut value);
+
+    let upstream = &value["upstreams"][0];
+    assert_eq!(upstream["headers"][0]["key"], "x-api-key");
+    assert_eq!(upstream["headers"][0]["value"], "******");
+    assert!(upstream["headers"][1]["value"].is_null());
+    assert_eq!(upstream["default_headers"]["x-goog-api-key"], "******");
+    assert_eq!(upstream["query_params"][0]["value"], "******");
+    assert_eq!(
+        value["metrics_export"]["headers"]["authorization"],
+        "******"
+    );
+    // URL 去除用户信息，不包含用户信息的 URL 保持不变
+    assert_eq!(value["redis"]["url"], "redis://127.0.0.1:6379/0");
+    assert_eq!(upstream["url"], "https://api.anthropic.com/v1/messages");
+}
@@ -1,7 +1,12 @@
 use llmproxy::{
-    config::{http_server::RoutingRule, ForwardConfig},
+    config::{
+        http_server::{RoutingRule, RoutingRuleType},
+        BreakerConfig, ForwardConfig,
+    },
+    r#const::response_buffer,
     server::router::Router,
 };
+use std::sync::Arc;
 
 // ========== 精确路径匹配 ==========
 
@@ -15,15 +20,49 @@ fn create_test_forward_config() -> ForwardConfig {
         routing: Some(vec![
             RoutingRule {
                 path: "/api".to_string(),
+                r#type: RoutingRuleType::Path,
                 target_group: "api_group".to_string(),
+                priority: 0,
+                breaker: None,
+                max_prompt_tokens: None,
+                tokens_per_minute: None,
             },
             RoutingRule {
                 path: "/api/v1".to_string(),
+                r#type: RoutingRuleType::Path,
                 target_group: "v1_group".to_string(),
+                priority: 0,
+                breaker: None,
+                max_prompt_tokens: None,
+                tokens_per_minute: None,
             },
         ]),
         ratelimit: None,
         timeout: None,
+        limits: None,
+        count_tokens: false,
+        budget: None,
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
     }
 }
 
@@ -42,7 +81,12 @@ async fn test_router_creation_duplicate_paths() {
     if let Some(ref mut routing) = config.routing {
         routing.push(RoutingRule {
             path: "/api".to_string(), // 重复的路径
+            r#type: RoutingRuleType::Path,
             target_group: "another_group".to_string(),
+            priority: 0,
+            breaker: None,
+            max_prompt_tokens: None,
+            tokens_per_minute: None,
         });
     }
 
@@ -57,13 +101,14 @@ async fn test_router_exact_path_match() {
     let router = Router::new(&config).unwrap();
 
     // 测试精确路径匹配
-    let result = router.get_target_group("/api").await;
+    let result = router.get_target_group("/api");
     assert_eq!(result.target_group, "api_group");
     assert!(!result.is_default);
 
-    let result = router.get_target_group("/api/v1").await;
+    let result = router.get_target_group("/api/v1");
     assert_eq!(result.target_group, "v1_group");
     assert!(!result.is_default);
+    assert_eq!(result.route.as_deref(), Some("/api/v1"));
 }
 
 /// 测试没有匹配时回退到默认组
@@ -73,11 +118,12 @@ async fn test_router_no_match_fallback() {
     let router = Router::new(&config).unwrap();
 
     // 测试不匹配时的默认回退
-    let result = router.get_target_group("/non_existent_path").await;
+    let result = router.get_target_group("/non_existent_path");
     assert_eq!(result.target_group, "default");
     assert!(result.is_default);
+    assert_eq!(result.route, None);
 
-    let result = router.get_target_group("/api/v3").await; // 不存在的API版本
+    let result = router.get_target_group("/api/v3"); // 不存在的API版本
     assert_eq!(result.target_group, "default");
     assert!(result.is_default);
 }
@@ -93,12 +139,36 @@ async fn test_router_empty_routing_rules() {
         routing: None,
         ratelimit: None,
         timeout: None,
+        limits: None,
+        count_tokens: false,
+        budget: None,
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
     };
 
     let router = Router::new(&config).unwrap();
 
     // 当没有路由规则时，所有请求都应该使用默认组
-    let result = router.get_target_group("/any/path").await;
+    let result = router.get_target_group("/any/path");
     assert_eq!(result.target_group, "default");
     assert!(result.is_default);
 }
@@ -112,28 +182,38 @@ async fn test_router_path_variations() {
     if let Some(ref mut routing) = config.routing {
         routing.push(RoutingRule {
             path: "/".to_string(),
+            r#type: RoutingRuleType::Path,
             target_group: "root_group".to_string(),
+            priority: 0,
+            breaker: None,
+            max_prompt_tokens: None,
+            tokens_per_minute: None,
         });
         routing.push(RoutingRule {
             path: "/api/v1/users".to_string(),
+            r#type: RoutingRuleType::Path,
             target_group: "users_group".to_string(),
+            priority: 0,
+            breaker: None,
+            max_prompt_tokens: None,
+            tokens_per_minute: None,
         });
     }
 
     let router = Router::new(&config).unwrap();
 
     // 测试根路径
-    let result = router.get_target_group("/").await;
+    let result = router.get_target_group("/");
     assert_eq!(result.target_group, "root_group");
     assert!(!result.is_default);
 
     // 测试嵌套路径
-    let result = router.get_target_group("/api/v1/users").await;
+    let result = router.get_target_group("/api/v1/users");
     assert_eq!(result.target_group, "users_group");
     assert!(!result.is_default);
 
     // 测试不存在的嵌套路径
-    let result = router.get_target_group("/api/v1/posts").await;
+    let result = router.get_target_group("/api/v1/posts");
     assert_eq!(result.target_group, "default");
     assert!(result.is_default);
 }
@@ -157,39 +237,98 @@ fn create_extended_routing_config() -> ForwardConfig {
             // 命名参数
             RoutingRule {
                 path: "/users/:id".to_string(),
+                r#type: RoutingRuleType::Path,
                 target_group: "user_detail".to_string(),
+                priority: 0,
+                breaker: None,
+                max_prompt_tokens: None,
+                tokens_per_minute: None,
             },
             RoutingRule {
                 path: "/posts/:category/:id".to_string(),
+                r#type: RoutingRuleType::Path,
                 target_group: "categorized_post".to_string(),
+                priority: 0,
+                breaker: None,
+                max_prompt_tokens: None,
+                tokens_per_minute: None,
             },
             // 通配符
             RoutingRule {
                 path: "/files/*".to_string(),
+                r#type: RoutingRuleType::Path,
                 target_group: "file_server".to_string(),
+                priority: 0,
+                breaker: None,
+                max_prompt_tokens: None,
+                tokens_per_minute: None,
             },
             RoutingRule {
                 path: "/api/*/docs".to_string(),
+                r#type: RoutingRuleType::Path,
                 target_group: "api_docs".to_string(),
+                priority: 0,
+                breaker: None,
+                max_prompt_tokens: None,
+                tokens_per_minute: None,
             },
             // 正则表达式
             RoutingRule {
                 path: "/items/{id:[0-9]+}".to_string(),
+                r#type: RoutingRuleType::Path,
                 target_group: "item_by_id".to_string(),
+                priority: 0,
+                breaker: None,
+                max_prompt_tokens: None,
+                tokens_per_minute: None,
             },
             // 注意：这里很蠢，他不支持 [A-Z]{3}\d{3} 这种正则表达式。是依赖库的问题
             RoutingRule {
                 path: "/products/{code:[A-Z][A-Z][A-Z][0-9][0-9][0-9]}".to_string(),
+                r#type: RoutingRuleType::Path,
                 target_group: "product_by_code".to_string(),
+                priority: 0,
+                breaker: None,
+                max_prompt_tokens: None,
+                tokens_per_minute: None,
             },
             // 混合模式
             RoutingRule {
                 path: "/api/:version/users/{id:[0-9]+}/profile".to_string(),
+                r#type: RoutingRuleType::Path,
                 target_group: "user_profile".to_string(),
+                priority: 0,
+                breaker: None,
+                max_prompt_tokens: None,
+                tokens_per_minute: None,
             },
         ]),
         ratelimit: None,
         timeout: None,
+        limits: None,
+        count_tokens: false,
+        budget: None,
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
     }
 }
 
@@ -201,22 +340,22 @@ async fn test_named_parameters_matching() {
     let router = Router::new(&config).unwrap();
 
     // 基本参数匹配测试
-    let result = router.get_target_group("/users/123").await;
+    let result = router.get_target_group("/users/123");
     assert_eq!(result.target_group, "user_detail");
     assert!(!result.is_default);
 
     // 多参数匹配测试
-    let result = router.get_target_group("/posts/tech/42").await;
+    let result = router.get_target_group("/posts/tech/42");
     assert_eq!(result.target_group, "categorized_post");
     assert!(!result.is_default);
 
     // 不匹配的参数路径（参数不足）
-    let result = router.get_target_group("/posts/tech").await;
+    let result = router.get_target_group("/posts/tech");
     assert_eq!(result.target_group, "default");
     assert!(result.is_default);
 
     // 不匹配的参数路径（参数过多）
-    let result = router.get_target_group("/users/123/extra").await;
+    let result = router.get_target_group("/users/123/extra");
     assert_eq!(result.target_group, "default");
     assert!(result.is_default);
 }
@@ -228,28 +367,26 @@ async fn test_wildcard_matching() {
     let router = Router::new(&config).unwrap();
 
     // 基本通配符匹配
-    let result = router.get_target_group("/files/document.pdf").await;
+    let result = router.get_target_group("/files/document.pdf");
     assert_eq!(result.target_group, "file_server");
     assert!(!result.is_default);
 
     // 通配符匹配多级路径
-    let result = router
-        .get_target_group("/files/documents/report.docx")
-        .await;
+    let result = router.get_target_group("/files/documents/report.docx");
     assert_eq!(result.target_group, "file_server");
     assert!(!result.is_default);
 
     // 中间部分通配符匹配
-    let result = router.get_target_group("/api/v1/docs").await;
+    let result = router.get_target_group("/api/v1/docs");
     assert_eq!(result.target_group, "api_docs");
     assert!(!result.is_default);
 
-    let result = router.get_target_group("/api/v2/docs").await;
+    let result = router.get_target_group("/api/v2/docs");
     assert_eq!(result.target_group, "api_docs");
     assert!(!result.is_default);
 
     // 通配符不匹配
-    let result = router.get_target_group("/api/v1/documents").await; // 不是 /docs 结尾
+    let result = router.get_target_group("/api/v1/documents"); // 不是 /docs 结尾
     assert_eq!(result.target_group, "default");
     assert!(result.is_default);
 }
@@ -261,29 +398,54 @@ async fn test_regex_matching() {
     let router = Router::new(&config).unwrap();
 
     // 数字ID匹配
-    let result = router.get_target_group("/items/42").await;
+    let result = router.get_target_group("/items/42");
     assert_eq!(result.target_group, "item_by_id");
     assert!(!result.is_default);
 
     // 产品代码匹配（格式：3个大写字母+3个数字）
-    let result = router.get_target_group("/products/ABC123").await;
+    let result = router.get_target_group("/products/ABC123");
     assert_eq!(result.target_group, "product_by_code");
     assert!(!result.is_default);
 
     // 不匹配的正则表达式
-    let result = router.get_target_group("/items/abc").await; // 不是数字ID
+    let result = router.get_target_group("/items/abc"); // 不是数字ID
     assert_eq!(result.target_group, "default");
     assert!(result.is_default);
 
-    let result = router.get_target_group("/products/abc123").await; // 小写字母
+    let result = router.get_target_group("/products/abc123"); // 小写字母
     assert_eq!(result.target_group, "default");
     assert!(result.is_default);
 
-    let result = router.get_target_group("/products/ABC12").await; // 数字不够
+    let result = router.get_target_group("/products/ABC12"); // 数字不够
     assert_eq!(result.target_group, "default");
     assert!(result.is_default);
 }
 
+/// 测试删除参数和通配符规则不影响其他规则
+#[tokio::test]
+async fn test_remove_pattern_routes() {
+    let config = create_extended_routing_config();
+    let router = Router::new(&config).unwrap();
+
+    router.remove_route("/users/:id").unwrap();
+    let result = router.get_target_group("/users/123");
+    assert!(result.is_default);
+    let result = router.get_target_group("/posts/tech/42");
+    assert_eq!(result.target_group, "categorized_post");
+
+    router.remove_route("/files/*").unwrap();
+    let result = router.get_target_group("/files/document.pdf");
+    assert!(result.is_default);
+    let result = router.get_target_group("/api/v1/docs");
+    assert_eq!(result.target_group, "api_docs");
+
+    router.remove_route("/items/{id:[0-9]+}").unwrap();
+    let result = router.get_target_group("/items/42");
+    assert!(result.is_default);
+    let result = router.get_target_group("/api/v1/users/42/profile");
+    assert_eq!(result.target_group, "user_profile");
+}
+
 /// 测试路由优先级
 #[tokio::test]
 async fn test_routing_priority() {
@@ -297,39 +459,388 @@ async fn test_routing_priority() {
             // 静态路径
             RoutingRule {
                 path: "/api/users/admin".to_string(),
+                r#type: RoutingRuleType::Path,
                 target_group: "static_admin".to_string(),
+                priority: 0,
+                breaker: None,
+                max_prompt_tokens: None,
+                tokens_per_minute: None,
             },
             // 命名参数
             RoutingRule {
                 path: "/api/users/:id".to_string(),
+                r#type: RoutingRuleType::Path,
                 target_group: "user_param".to_string(),
+                priority: 0,
+                breaker: None,
+                max_prompt_tokens: None,
+                tokens_per_minute: None,
             },
             // 通配符
             RoutingRule {
                 path: "/api/*".to_string(),
+                r#type: RoutingRuleType::Path,
                 target_group: "api_wildcard".to_string(),
+                priority: 0,
+                breaker: None,
+                max_prompt_tokens: None,
+                tokens_per_minute: None,
             },
         ]),
         ratelimit: None,
         timeout: None,
+        limits: None,
+        count_tokens: false,
+        budget: None,
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
     };
 
     let router = Router::new(&config).unwrap();
 
     // 测试静态路径优先级
-    let result = router.get_target_group("/api/users/admin").await;
+    let result = router.get_target_group("/api/users/admin");
     assert_eq!(result.target_group, "static_admin");
     assert!(!result.is_default);
 
     // 测试命名参数优先级
-    let result = router.get_target_group("/api/users/123").await;
+    let result = router.get_target_group("/api/users/123");
     assert_eq!(result.target_group, "user_param");
     assert!(!result.is_default);
 
     // 测试通配符优先级
-    let result = router.get_target_group("/api/products").await;
+    let result = router.get_target_group("/api/products");
+    assert_eq!(result.target_group, "api_wildcard");
+    assert!(!result.is_default);
+}
+
+/// 测试显式指定的路由优先级
+#[tokio::test]
+async fn test_explicit_routing_priority() {
+    // 通过优先级覆盖默认的匹配顺序
+    let config = ForwardConfig {
+        name: "explicit_priority_test".to_string(),
+        port: 3000,
+        address: "127.0.0.1".to_string(),
+        default_group: "default".to_string(),
+        routing: Some(vec![
+            RoutingRule {
+                path: "/api/users/admin".to_string(),
+                r#type: RoutingRuleType::Path,
+                target_group: "static_admin".to_string(),
+                priority: 0,
+                breaker: None,
+                max_prompt_tokens: None,
+                tokens_per_minute: None,
+            },
+            RoutingRule {
+                path: "/api/users/:id".to_string(),
+                r#type: RoutingRuleType::Path,
+                target_group: "user_param".to_string(),
+                priority: 10,
+                breaker: None,
+                max_prompt_tokens: None,
+                tokens_per_minute: None,
+            },
+            RoutingRule {
+                path: "/api/*".to_string(),
+                r#type: RoutingRuleType::Path,
+                target_group: "api_wildcard".to_string(),
+                priority: 20,
+                breaker: None,
+                max_prompt_tokens: None,
+                tokens_per_minute: None,
+            },
+            RoutingRule {
+                path: "/health".to_string(),
+                r#type: RoutingRuleType::Path,
+                target_group: "health".to_string(),
+                priority: 0,
+                breaker: None,
+                max_prompt_tokens: None,
+                tokens_per_minute: None,
+            },
+        ]),
+        ratelimit: None,
+        timeout: None,
+        limits: None,
+        count_tokens: false,
+        budget: None,
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
+    };
+
+    let router = Router::new(&config).unwrap();
+
+    // 高优先级的通配符优先于静态路径和命名参数
+    let result = router.get_target_group("/api/users/admin");
     assert_eq!(result.target_group, "api_wildcard");
+
+    let result = router.get_target_group("/api/users/123");
+    assert_eq!(result.target_group, "api_wildcard");
+
+    // 高优先级规则不匹配时，回退到低优先级规则
+    let result = router.get_target_group("/health");
+    assert_eq!(result.target_group, "health");
+
+    // 运行时调整优先级后，静态路径重新生效
+    router
+        .insert_or_update_route(&RoutingRule {
+            path: "/api/users/admin".to_string(),
+            r#type: RoutingRuleType::Path,
+            target_group: "static_admin".to_string(),
+            priority: 30,
+            breaker: None,
+            max_prompt_tokens: None,
+            tokens_per_minute: None,
+        })
+        .unwrap();
+    let result = router.get_target_group("/api/users/admin");
+    assert_eq!(result.target_group, "static_admin");
+
+    // 删除高优先级的通配符后，命名参数生效
+    router.remove_route("/api/*").unwrap();
+    let result = router.get_target_group("/api/users/123");
+    assert_eq!(result.target_group, "user_param");
+}
+
+/// 测试正则表达式路由规则
+#[tokio::test]
+async fn test_path_regex_routing() {
+    let config = ForwardConfig {
+        name: "path_regex_test".to_string(),
+        port: 3000,
+        address: "127.0.0.1".to_string(),
+        default_group: "default".to_string(),
+        routing: Some(vec![
+            RoutingRule {
+                path: r"^/products/[A-Z]{3}\d{3}$".to_string(),
+                r#type: RoutingRuleType::PathRegex,
+                target_group: "product_by_code".to_string(),
+                priority: 0,
+                breaker: None,
+                max_prompt_tokens: None,
+                tokens_per_minute: None,
+            },
+            RoutingRule {
+                path: r"^/v\d+/(chat|completions)(/.*)?$".to_string(),
+                r#type: RoutingRuleType::PathRegex,
+                target_group: "versioned_chat".to_string(),
+                priority: 0,
+                breaker: None,
+                max_prompt_tokens: None,
+                tokens_per_minute: None,
+            },
+            // 同一优先级下，路径模式优先于正则规则
+            RoutingRule {
+                path: "/v1/chat".to_string(),
+                r#type: RoutingRuleType::Path,
+                target_group: "static_chat".to_string(),
+                priority: 0,
+                breaker: None,
+                max_prompt_tokens: None,
+                tokens_per_minute: None,
+            },
+        ]),
+        ratelimit: None,
+        timeout: None,
+        limits: None,
+        count_tokens: false,
+        budget: None,
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
+    };
+
+    let router = Router::new(&config).unwrap();
+
+    let result = router.get_target_group("/products/ABC123");
+    assert_eq!(result.target_group, "product_by_code");
     assert!(!result.is_default);
+
+    let result = router.get_target_group("/products/ABC12"); // 数字不够
+    assert!(result.is_default);
+
+    let result = router.get_target_group("/products/abc123"); // 小写字母
+    assert!(result.is_default);
+
+    let result = router.get_target_group("/v2/completions/stream");
+    assert_eq!(result.target_group, "versioned_chat");
+
+    let result = router.get_target_group("/v1/chat");
+    assert_eq!(result.target_group, "static_chat");
+
+    // 运行时提升正则规则的优先级
+    router
+        .insert_or_update_route(&RoutingRule {
+            path: r"^/v\d+/(chat|completions)(/.*)?$".to_string(),
+            r#type: RoutingRuleType::PathRegex,
+            target_group: "versioned_chat".to_string(),
+            priority: 10,
+            breaker: None,
+            max_prompt_tokens: None,
+            tokens_per_minute: None,
+        })
+        .unwrap();
+    let result = router.get_target_group("/v1/chat");
+    assert_eq!(result.target_group, "versioned_chat");
+    assert_eq!(
+        result.route.as_deref(),
+        Some(r"^/v\d+/(chat|completions)(/.*)?$")
+    );
+
+    // 删除正则规则后回退到路径模式
+    router
+        .remove_route(r"^/v\d+/(chat|completions)(/.*)?$")
+        .unwrap();
+    let result = router.get_target_group("/v1/chat");
+    assert_eq!(result.target_group, "static_chat");
+
+    // 未锚定的正则表达式匹配路径中的任意位置
+    router
+        .insert_or_update_route(&RoutingRule {
+            path: r"/embeddings$".to_string(),
+            r#type: RoutingRuleType::PathRegex,
+            target_group: "embeddings".to_string(),
+            priority: 0,
+            breaker: None,
+            max_prompt_tokens: None,
+            tokens_per_minute: None,
+        })
+        .unwrap();
+    let result = router.get_target_group("/v1/embeddings");
+    assert_eq!(result.target_group, "embeddings");
+    let result = router.get_target_group("/v1/embeddings/batch");
+    assert!(result.is_default);
+}
+
+/// 测试无效的正则表达式路由规则
+#[test]
+fn test_invalid_path_regex_routing() {
+    let config = ForwardConfig {
+        name: "invalid_path_regex_test".to_string(),
+        port: 3000,
+        address: "127.0.0.1".to_string(),
+        default_group: "default".to_string(),
+        routing: Some(vec![RoutingRule {
+            path: "^/products/[A-Z".to_string(),
+            r#type: RoutingRuleType::PathRegex,
+            target_group: "product_by_code".to_string(),
+            priority: 0,
+            breaker: None,
+            max_prompt_tokens: None,
+            tokens_per_minute: None,
+        }]),
+        ratelimit: None,
+        timeout: None,
+        limits: None,
+        count_tokens: false,
+        budget: None,
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
+    };
+
+    assert!(Router::new(&config).is_err());
+}
+
+/// 测试更新失败时路由表保持不变
+#[test]
+fn test_failed_route_update_keeps_routes() {
+    let router = Router::new(&create_test_forward_config()).unwrap();
+    let invalid = RoutingRule {
+        path: "^/products/[A-Z".to_string(),
+        r#type: RoutingRuleType::PathRegex,
+        target_group: "product_by_code".to_string(),
+        priority: 0,
+        breaker: None,
+        max_prompt_tokens: None,
+        tokens_per_minute: None,
+    };
+
+    // 更新单条规则失败
+    assert!(router.insert_or_update_route(&invalid).is_err());
+    // 整体替换的规则无效
+    assert!(router.replace_routes(&[invalid]).is_err());
+
+    // 原有路由仍然有效
+    assert_eq!(router.get_target_group("/api").target_group, "api_group");
+    assert_eq!(router.get_target_group("/api/v1").target_group, "v1_group");
+    assert!(router.get_target_group("/products/A").is_default);
 }
 
 /// 测试混合路由模式
@@ -339,12 +850,52 @@ async fn test_mixed_routing_patterns() {
     let router = Router::new(&config).unwrap();
 
     // 测试混合模式匹配
-    let result = router.get_target_group("/api/v2/users/42/profile").await;
+    let result = router.get_target_group("/api/v2/users/42/profile");
     assert_eq!(result.target_group, "user_profile");
     assert!(!result.is_default);
 
     // 测试混合模式不匹配
-    let result = router.get_target_group("/api/v2/users/abc/profile").await; // 非数字ID
+    let result = router.get_target_group("/api/v2/users/abc/profile"); // 非数字ID
     assert_eq!(result.target_group, "default");
     assert!(result.is_default);
 }
+
+/// 测试路由熔断器
+#[tokio::test]
+async fn test_route_breakers() {
+    let mut config = create_test_forward_config();
+    let router = Router::new(&config).unwrap();
+    // 未配置 breaker 时不创建路由熔断器
+    assert!(router.get_target_group("/api").breaker.is_none());
+    assert!(router.get_target_group("/other").breaker.is_none());
+
+    // 路由规则的 breaker 只作用于该路由
+    config.routing.as_mut().unwrap()[0].breaker = Some(BreakerConfig::default());
+    let router = Router::new(&config).unwrap();
+    let breaker = router.get_target_group("/api").breaker.unwrap();
+    assert_eq!(breaker.name(), "test_forward:/api");
+    assert_eq!(breaker.group(), "api_group");
+    assert!(router.get_target_group("/api/v1").breaker.is_none());
+    assert!(router.get_target_group("/other").breaker.is_none());
+
+    // 转发服务的 breaker 作用于其他路由和使用默认组的请求，每个路由使用独立的熔断器
+    config.breaker = Some(BreakerConfig::default());
+    let router = Router::new(&config).unwrap();
+    let api = router.get_target_group("/api").breaker.unwrap();
+    let v1 = router.get_target_group("/api/v1").breaker.unwrap();
+    let default = router.get_target_group("/other").breaker.unwrap();
+    assert_eq!(v1.name(), "test_forward:/api/v1");
+    assert_eq!(default.name(), "test_forward:default");
+    assert_eq!(default.group(), "default");
+    assert!(!Arc::ptr_eq(&api, &v1));
+    assert!(Arc::ptr_eq(
+        &api,
+        &router.get_target_group("/api").breaker.unwrap()
+    ));
+
+    // 删除路由规则时移除路由熔断器
+    router.remove_route("/api/v1").unwrap();
+    let result = router.get_target_group("/api/v1");
+    assert!(result.is_default);
+    assert!(Arc::ptr_eq(&result.breaker.unwrap(), &default));
+}
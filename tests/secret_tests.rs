@@ -0,0 +1,143 @@
+use llmproxy::{
+    config::{
+        AuthConfig, AuthType, BalanceConfig, BalanceStrategy, HttpClientConfig, UpstreamConfig,
+        UpstreamGroupConfig, UpstreamRef,
+    },
+    error::AppError,
+    secret::{read_secret_file, read_secret_file_with_interval},
+    upstream::{RequestContext, UpstreamManager},
+};
+use reqwest::{header::HeaderMap, Method};
+use std::{fs, time::Duration};
+use wiremock::{
+    matchers::{header, method},
+    Mock, MockServer, ResponseTemplate,
+};
+
+// 创建使用令牌文件认证的上游及上游组配置
+fn token_file_configs(
+    url: &str,
+    token_file: &str,
+) -> (Vec<UpstreamConfig>, Vec<UpstreamGroupConfig>) {
+    let upstream = UpstreamConfig {
+        name: "secret_upstream".to_string(),
+        url: format!("{}/v1/chat", url).into(),
+        weight: 1,
+        http_client: HttpClientConfig::default(),
+        auth: Some(AuthConfig {
+            r#type: AuthType::Bearer,
+            token: None,
+            token_file: Some(token_file.to_string()),
+            username: None,
+            password: None,
+            password_file: None,
+            oauth2: None,
+            external: None,
+        }),
+        headers: vec![],
+        breaker: None,
+        adaptive: None,
+        hint: None,
+        enabled: true,
+        proxy: true,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect: None,
+        stream_normalize: None,
+        agent: None,
+        default_headers: Default::default(),
+        pricing: vec![],
+        dns: None,
+        warmup: None,
+    };
+
+    let group = UpstreamGroupConfig {
+        name: "secret_group".to_string(),
+        upstreams: vec![UpstreamRef {
+            name: "secret_upstream".to_string(),
+            weight: 1,
+        }],
+        balance: BalanceConfig {
+            strategy: BalanceStrategy::RoundRobin,
+        },
+        http_client: HttpClientConfig::default(),
+        sticky: None,
+        discovery: None,
+    };
+
+    (vec![upstream], vec![group])
+}
+
+#[test]
+fn test_secret_file_trimmed_and_reread() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("token");
+    let path = path.to_str().unwrap();
+
+    // 挂载的密钥文件通常以换行符结尾
+    fs::write(path, "first-token\n").unwrap();
+    assert_eq!(read_secret_file(path).unwrap(), "first-token");
+
+    // 刷新间隔内使用缓存的内容
+    fs::write(path, "second-token\n").unwrap();
+    assert_eq!(read_secret_file(path).unwrap(), "first-token");
+
+    // 超过刷新间隔后重新读取
+    assert_eq!(
+        read_secret_file_with_interval(path, Duration::ZERO).unwrap(),
+        "second-token"
+    );
+
+    // 重新读取失败时继续使用上一次读取的内容
+    fs::remove_file(path).unwrap();
+    assert_eq!(
+        read_secret_file_with_interval(path, Duration::ZERO).unwrap(),
+        "second-token"
+    );
+}
+
+#[test]
+fn test_secret_file_missing_or_empty() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let missing = dir.path().join("missing");
+    let result = read_secret_file(missing.to_str().unwrap());
+    assert!(matches!(result, Err(AppError::Config(_))));
+
+    let empty = dir.path().join("empty");
+    fs::write(&empty, " \n").unwrap();
+    let result = read_secret_file(empty.to_str().unwrap());
+    assert!(matches!(result, Err(AppError::Config(msg)) if msg.contains("empty")));
+}
+
+#[tokio::test]
+async fn test_upstream_bearer_token_from_file() {
+    let mock_server = MockServer::start().await;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("token");
+    let path = path.to_str().unwrap();
+    fs::write(path, "file-token\n").unwrap();
+
+    Mock::given(method("GET"))
+        .and(header("authorization", "Bearer file-token"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("OK"))
+        .mount(&mock_server)
+        .await;
+
+    let (upstreams, groups) = token_file_configs(&mock_server.uri(), path);
+    let upstream_manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+
+    let response = upstream_manager
+        .forward_request(
+            "secret_group",
+            "/",
+            &RequestContext::default(),
+            &Method::GET,
+            HeaderMap::new(),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+}
@@ -1,17 +1,33 @@
 use llmproxy::{
     config::{
-        BalanceConfig, BalanceStrategy, ForwardConfig, HttpClientConfig, RateLimitConfig,
-        TimeoutConfig, UpstreamConfig, UpstreamGroupConfig, UpstreamRef,
+        http_server::{RoutingRule, RoutingRuleType},
+        AccessLogConfig, AuditSinkConfig, BalanceConfig, BalanceStrategy, BreakerConfig,
+        BreakerWindowConfig, BreakerWindowType, BudgetConfig, CacheBackend, CacheConfig,
+        ClientBudgetConfig, ClientTokenLimitConfig, CompressionConfig, ErrorResponseConfig,
+        ErrorResponseFormat, FairQueueConfig, ForwardConfig, HttpClientConfig, ListenerConfig,
+        LoadSheddingConfig, MiddlewareStage, ModelAlias, ModelPriceConfig, ParamLimitAction,
+        ParamLimitsConfig, PiiRedactionConfig, PluginConfig, PolicyConfig, QueueConfig,
+        QueueTierConfig, RateLimitConfig, RateLimitKey, RedisConfig, RequestHeadersConfig,
+        TimeoutConfig, TokenLimitConfig, UpstreamConfig, UpstreamGroupConfig, UpstreamRef,
     },
     error::AppError,
-    server::ForwardServer,
+    metrics::METRICS,
+    r#const, restart,
+    server::{
+        compression_layer, count_prompt_tokens, forward_handler, record_access, AccessRecord,
+        AuditRecord, ClientKey, ClientKeyExtractor, ConcurrencyLimiter, DistributedRateLimiter,
+        ForwardServer, LoadShedder, LoadWatchdog, PiiRedactor, PluginChain, Pressure, ProxyError,
+        TokenLimiter,
+    },
     upstream::UpstreamManager,
 };
 use std::sync::Arc;
 use tokio::time::Duration;
+use tokio_graceful_shutdown::{IntoSubsystem, SubsystemBuilder, Toplevel};
+use tower::ServiceExt;
 
 use wiremock::{
-    matchers::{method, path},
+    matchers::{body_json, body_partial_json, header, method, path},
     Mock, MockServer, ResponseTemplate,
 };
 
@@ -36,6 +52,20 @@ async fn create_test_upstream_manager() -> (Arc<UpstreamManager>, MockServer) {
         auth: None,
         headers: vec![],
         breaker: None,
+        adaptive: None,
+        hint: None,
+        enabled: true,
+        proxy: true,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect: None,
+        stream_normalize: None,
+        agent: None,
+        default_headers: Default::default(),
+        pricing: vec![],
+        dns: None,
+        warmup: None,
     }];
 
     // 创建上游组配置
@@ -49,6 +79,8 @@ async fn create_test_upstream_manager() -> (Arc<UpstreamManager>, MockServer) {
             strategy: BalanceStrategy::RoundRobin,
         },
         http_client: Default::default(),
+        sticky: None,
+        discovery: None,
     }];
 
     // 创建上游管理器
@@ -72,10 +104,34 @@ async fn test_forward_server_creation() {
         ratelimit: None,
         timeout: Some(TimeoutConfig::default()),
         routing: None,
+        limits: None,
+        count_tokens: false,
+        budget: None,
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: r#const::response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
     };
 
     // 只验证能否成功创建服务器
-    let result = ForwardServer::new(config, upstream_manager);
+    let result = ForwardServer::new(config, upstream_manager, &[]);
     assert!(result.is_ok());
 }
 
@@ -93,19 +149,90 @@ async fn test_rate_limiting() -> Result<(), AppError> {
         ratelimit: Some(RateLimitConfig {
             per_second: 1,
             burst: 2,
+            key: RateLimitKey::Ip,
+            header: None,
+            redis: None,
         }),
         timeout: Some(TimeoutConfig::default()),
         routing: None,
+        limits: None,
+        count_tokens: false,
+        budget: None,
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: r#const::response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
     };
 
     // 只验证能否成功创建服务器
-    let server = ForwardServer::new(config, upstream_manager)?;
+    let server = ForwardServer::new(config, upstream_manager, &[])?;
     // 确保创建成功
     assert!(server.get_addr().is_ipv4());
 
     Ok(())
 }
 
+/// 测试 Redis 不可用时分布式限流回退为本地令牌桶
+#[tokio::test]
+async fn test_distributed_rate_limit_fallback() {
+    let redis = RedisConfig {
+        url: "redis://127.0.0.1:1/0".to_string(),
+        password: None,
+        key_prefix: "llmproxy:".to_string(),
+    };
+    let headers = axum::http::HeaderMap::new();
+    let ip = |last: u8| Some(std::net::IpAddr::from([10, 0, 0, last]));
+
+    // 请求速率限制：突发上限 2 个，每个客户端 IP 使用独立的令牌桶
+    let ratelimit = RateLimitConfig {
+        per_second: 1,
+        burst: 2,
+        key: RateLimitKey::Ip,
+        header: None,
+        redis: Some(redis.clone()),
+    };
+    let limiter = DistributedRateLimiter::new("fallback_forward", &ratelimit, &redis).unwrap();
+    assert!(limiter.check(&headers, ip(1)).await);
+    assert!(limiter.check(&headers, ip(1)).await);
+    assert!(!limiter.check(&headers, ip(1)).await);
+    assert!(limiter.check(&headers, ip(2)).await);
+
+    // 令牌速率限制：按实际用量修正后令牌桶不足
+    let token_limit = TokenLimitConfig {
+        tokens_per_minute: 2000,
+        key: RateLimitKey::Ip,
+        header: None,
+        clients: vec![],
+        redis: Some(redis),
+    };
+    let limiter = TokenLimiter::new("fallback_forward", &token_limit).unwrap();
+    let charge = limiter.acquire(None, &headers, ip(1), 100).await.unwrap();
+    charge.settle(2000);
+    let exceeded = limiter
+        .acquire(None, &headers, ip(1), 100)
+        .await
+        .unwrap_err();
+    assert_eq!(exceeded.limit, 2000);
+    assert!(limiter.acquire(None, &headers, ip(2), 100).await.is_ok());
+}
+
 /// 测试服务器超时配置
 #[tokio::test]
 async fn test_server_timeout() -> Result<(), AppError> {
@@ -127,18 +254,173 @@ async fn test_server_timeout() -> Result<(), AppError> {
         ratelimit: None,
         timeout: Some(TimeoutConfig {
             connect: 1, // 1秒连接超时
+            request: 1, // 1秒请求超时
+            header: None,
         }),
         routing: None,
+        limits: None,
+        count_tokens: false,
+        budget: None,
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: r#const::response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
     };
 
     // 只验证能否成功创建服务器
-    let server = ForwardServer::new(config, upstream_manager)?;
+    let server = ForwardServer::new(config, upstream_manager, &[])?;
     // 确保服务器创建成功
     assert!(server.get_addr().is_ipv4());
 
     Ok(())
 }
 
+/// 测试资源紧张时按优先级拒绝请求
+#[tokio::test]
+async fn test_load_shedding() {
+    use axum::response::IntoResponse;
+
+    let config: LoadSheddingConfig =
+        serde_yaml::from_str("max_memory_mb: 100\nmax_event_loop_lag_ms: 200").unwrap();
+    let watchdog = LoadWatchdog::new(config.clone());
+    let mb = 1024 * 1024;
+
+    // 取内存和事件循环延迟中压力较大的一项
+    assert_eq!(
+        watchdog.pressure(Some(50 * mb), Duration::from_millis(10)),
+        Pressure::Normal
+    );
+    assert_eq!(
+        watchdog.pressure(Some(90 * mb), Duration::from_millis(10)),
+        Pressure::Elevated
+    );
+    assert_eq!(
+        watchdog.pressure(Some(50 * mb), Duration::from_millis(250)),
+        Pressure::Critical
+    );
+    // 无法读取进程内存时只检查事件循环延迟
+    assert_eq!(
+        watchdog.pressure(None, Duration::from_millis(10)),
+        Pressure::Normal
+    );
+
+    let shedder = LoadShedder::default();
+    shedder.configure(&config);
+    let headers = |priority: Option<&str>| {
+        let mut headers = axum::http::HeaderMap::new();
+        if let Some(priority) = priority {
+            headers.insert("x-priority", priority.parse().unwrap());
+        }
+        headers
+    };
+
+    // 资源充足时接收所有请求
+    assert!(shedder.check(&headers(Some("low"))).is_ok());
+
+    // 接近上限时拒绝低优先级请求，未携带优先级的请求为 normal
+    assert_eq!(shedder.set_pressure(Pressure::Elevated), Pressure::Normal);
+    let shed = shedder.check(&headers(Some("LOW"))).unwrap_err();
+    assert!(shedder.check(&headers(None)).is_ok());
+    assert!(shedder.check(&headers(Some("unknown"))).is_ok());
+
+    let response = shed.into_response();
+    assert_eq!(
+        response.status(),
+        axum::http::StatusCode::SERVICE_UNAVAILABLE
+    );
+    assert_eq!(response.headers()["retry-after"], "5");
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"]["code"], "overloaded");
+
+    // 达到上限时只接收高优先级请求
+    shedder.set_pressure(Pressure::Critical);
+    assert!(shedder.check(&headers(None)).is_err());
+    assert!(shedder.check(&headers(Some("high"))).is_ok());
+}
+
+/// 测试按客户端提取限流键
+#[test]
+fn test_rate_limit_client_key() {
+    use tower_governor::key_extractor::KeyExtractor;
+
+    let request =
+        |ip: [u8; 4], headers: &[(&str, &str)]| {
+            let mut builder = axum::http::Request::builder().uri("/v1/chat/completions");
+            for (name, value) in headers {
+                builder = builder.header(*name, *value);
+            }
+            let mut request = builder.body(()).unwrap();
+            request.extensions_mut().insert(axum::extract::ConnectInfo(
+                std::net::SocketAddr::from((ip, 8080)),
+            ));
+            request
+        };
+    let extractor = |key: RateLimitKey, header: Option<&str>| ClientKeyExtractor::new(key, header);
+
+    // 按 IP 限流时忽略请求头
+    let ip = extractor(RateLimitKey::Ip, None);
+    let key = ip
+        .extract(&request([10, 0, 0, 1], &[("authorization", "Bearer sk-a")]))
+        .unwrap();
+    assert_eq!(key, ClientKey::Ip([10, 0, 0, 1].into()));
+
+    // 按 API 密钥限流时，同一密钥在不同 IP 上共享令牌桶，不同密钥使用独立的令牌桶
+    let api_key = extractor(RateLimitKey::ApiKey, None);
+    let a1 = api_key
+        .extract(&request([10, 0, 0, 1], &[("authorization", "Bearer sk-a")]))
+        .unwrap();
+    let a2 = api_key
+        .extract(&request([10, 0, 0, 2], &[("x-api-key", "sk-a")]))
+        .unwrap();
+    let b = api_key
+        .extract(&request([10, 0, 0, 1], &[("authorization", "Bearer sk-b")]))
+        .unwrap();
+    assert_eq!(a1, a2);
+    assert_ne!(a1, b);
+    assert!(matches!(a1, ClientKey::Hash(_)));
+    // 未携带密钥时回退为客户端 IP
+    let anonymous = api_key.extract(&request([10, 0, 0, 3], &[])).unwrap();
+    assert_eq!(anonymous, ClientKey::Ip([10, 0, 0, 3].into()));
+
+    // 按请求头限流
+    let header = extractor(RateLimitKey::Header, Some("x-tenant-id"));
+    let t1 = header
+        .extract(&request([10, 0, 0, 1], &[("x-tenant-id", "team-a")]))
+        .unwrap();
+    let t2 = header
+        .extract(&request([10, 0, 0, 2], &[("x-tenant-id", "team-a")]))
+        .unwrap();
+    let t3 = header
+        .extract(&request([10, 0, 0, 1], &[("x-tenant-id", "team-b")]))
+        .unwrap();
+    assert_eq!(t1, t2);
+    assert_ne!(t1, t3);
+    let anonymous = header
+        .extract(&request([10, 0, 0, 4], &[("authorization", "Bearer sk-a")]))
+        .unwrap();
+    assert_eq!(anonymous, ClientKey::Ip([10, 0, 0, 4].into()));
+}
+
 /// 测试同时处理多个并发请求
 #[tokio::test]
 async fn test_concurrent_requests() -> Result<(), AppError> {
@@ -153,13 +435,40 @@ async fn test_concurrent_requests() -> Result<(), AppError> {
         ratelimit: Some(RateLimitConfig {
             per_second: 5, // 每秒5个请求
             burst: 10,     // 突发上限10个
+            key: RateLimitKey::Ip,
+            header: None,
+            redis: None,
         }),
         timeout: Some(TimeoutConfig::default()),
         routing: None,
+        limits: None,
+        count_tokens: false,
+        budget: None,
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: r#const::response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
     };
 
     // 只验证能否成功创建服务器
-    let server = ForwardServer::new(config, upstream_manager)?;
+    let server = ForwardServer::new(config, upstream_manager, &[])?;
     // 验证创建成功
     assert!(server.get_addr().is_ipv4());
 
@@ -191,12 +500,3512 @@ async fn test_server_graceful_shutdown() -> Result<(), AppError> {
         ratelimit: None,
         timeout: Some(TimeoutConfig::default()),
         routing: None,
+        limits: None,
+        count_tokens: false,
+        budget: None,
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: r#const::response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
     };
 
     // 只验证能否成功创建服务器
-    let server = ForwardServer::new(config, upstream_manager)?;
+    let server = ForwardServer::new(config, upstream_manager, &[])?;
     // 验证创建成功
     assert!(server.get_addr().is_ipv4());
 
     Ok(())
 }
+
+/// 测试禁用转发服务后返回 503，重新启用后恢复转发
+#[tokio::test]
+async fn test_forward_server_disable() -> Result<(), AppError> {
+    let (upstream_manager, mock_server) = create_test_upstream_manager().await;
+
+    // 请求转发到上游地址本身
+    Mock::given(method("GET"))
+        .and(path("/"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    let config = ForwardConfig {
+        name: "toggled_forward".to_string(),
+        port: 0, // 使用系统分配的端口
+        address: "127.0.0.1".to_string(),
+        default_group: "test_group".to_string(),
+        ratelimit: None,
+        timeout: Some(TimeoutConfig::default()),
+        routing: None,
+        limits: None,
+        count_tokens: false,
+        budget: None,
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: r#const::response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
+    };
+
+    let server = ForwardServer::new(config, upstream_manager, &[])?;
+    let state = server.get_state().clone();
+    let app = axum::Router::new()
+        .route("/{*path}", axum::routing::any(forward_handler))
+        .with_state(state.clone());
+
+    let request = || {
+        axum::http::Request::builder()
+            .uri("/test")
+            .body(axum::body::Body::empty())
+            .unwrap()
+    };
+
+    assert!(state.is_enabled());
+    let response = app.clone().oneshot(request()).await.unwrap();
+    assert_eq!(response.status(), 200);
+
+    // 禁用后直接返回 503
+    assert!(state.set_enabled(false));
+    assert!(!state.set_enabled(false));
+    let response = app.clone().oneshot(request()).await.unwrap();
+    assert_eq!(response.status(), 503);
+
+    // 重新启用后恢复转发
+    assert!(state.set_enabled(true));
+    let response = app.oneshot(request()).await.unwrap();
+    assert_eq!(response.status(), 200);
+
+    Ok(())
+}
+
+/// 测试请求体中的模型别名决定目标上游组，并改写为服务商的模型名称
+#[tokio::test]
+async fn test_forward_server_model_alias() -> Result<(), AppError> {
+    let default_server = MockServer::start().await;
+    let alias_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(body_json(serde_json::json!({"model": "gpt-4o-mini"})))
+        .respond_with(ResponseTemplate::new(200).set_body_string("default"))
+        .mount(&default_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_json(
+            serde_json::json!({"model": "azure-gpt4o-deployment", "stream": false}),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_string("alias"))
+        .mount(&alias_server)
+        .await;
+
+    let upstream = |name: &str, server: &MockServer| UpstreamConfig {
+        name: name.to_string(),
+        url: server.uri().into(),
+        weight: 1,
+        http_client: HttpClientConfig::default(),
+        auth: None,
+        headers: vec![],
+        breaker: None,
+        adaptive: None,
+        hint: None,
+        enabled: true,
+        proxy: true,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect: None,
+        stream_normalize: None,
+        agent: None,
+        default_headers: Default::default(),
+        pricing: vec![],
+        dns: None,
+        warmup: None,
+    };
+    let group = |name: &str, upstream: &str| UpstreamGroupConfig {
+        name: name.to_string(),
+        upstreams: vec![UpstreamRef {
+            name: upstream.to_string(),
+            weight: 1,
+        }],
+        balance: BalanceConfig {
+            strategy: BalanceStrategy::RoundRobin,
+        },
+        http_client: Default::default(),
+        sticky: None,
+        discovery: None,
+    };
+    let upstream_manager = UpstreamManager::new(
+        vec![
+            upstream("default_upstream", &default_server),
+            upstream("azure_upstream", &alias_server),
+        ],
+        vec![
+            group("default_group", "default_upstream"),
+            group("azure_group", "azure_upstream"),
+        ],
+    )
+    .await?;
+
+    let config = ForwardConfig {
+        name: "model_forward".to_string(),
+        port: 0, // 使用系统分配的端口
+        address: "127.0.0.1".to_string(),
+        default_group: "default_group".to_string(),
+        ratelimit: None,
+        timeout: Some(TimeoutConfig::default()),
+        routing: None,
+        limits: None,
+        count_tokens: false,
+        budget: None,
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: r#const::response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
+    };
+    let models = [ModelAlias {
+        name: "smart".to_string(),
+        group: "azure_group".to_string(),
+        model: "azure-gpt4o-deployment".to_string(),
+    }];
+    let server = ForwardServer::new(config, Arc::new(upstream_manager), &models)?;
+    let app = axum::Router::new()
+        .route("/{*path}", axum::routing::any(forward_handler))
+        .with_state(server.get_state().clone());
+
+    let request = |body: &'static str| {
+        axum::http::Request::builder()
+            .method("POST")
+            .uri("/v1/chat/completions")
+            .header("content-length", body.len())
+            .body(axum::body::Body::from(body))
+            .unwrap()
+    };
+    let response_body = |response: axum::response::Response| async move {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    };
+
+    // 命中别名
+    let response = app
+        .clone()
+        .oneshot(request(r#"{"model":"smart","stream":false}"#))
+        .await
+        .unwrap();
+    assert_eq!(response_body(response).await, "alias");
+
+    // 未命中别名时按路由规则转发，请求体不变
+    let response = app
+        .oneshot(request(r#"{"model":"gpt-4o-mini"}"#))
+        .await
+        .unwrap();
+    assert_eq!(response_body(response).await, "default");
+
+    Ok(())
+}
+
+/// 测试参数上限：超出上限的参数被改写为上限值，或直接拒绝并返回 400
+#[tokio::test]
+async fn test_forward_server_param_limits() -> Result<(), AppError> {
+    let (upstream_manager, mock_server) = create_test_upstream_manager().await;
+
+    Mock::given(method("POST"))
+        .and(body_json(
+            serde_json::json!({"model": "gpt-4o", "max_tokens": 1024, "temperature": 1.0, "n": 1}),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_string("clamped"))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let app = |action: ParamLimitAction| {
+        let config = ForwardConfig {
+            name: "limited_forward".to_string(),
+            port: 0, // 使用系统分配的端口
+            address: "127.0.0.1".to_string(),
+            default_group: "test_group".to_string(),
+            ratelimit: None,
+            timeout: Some(TimeoutConfig::default()),
+            routing: None,
+            limits: Some(ParamLimitsConfig {
+                max_tokens: Some(1024),
+                temperature: Some(1.0),
+                top_p: None,
+                n: Some(1),
+                max_prompt_tokens: None,
+                action,
+            }),
+            count_tokens: false,
+            budget: None,
+            token_limit: None,
+            max_concurrent: None,
+            queue: None,
+            sse_heartbeat: None,
+            coalesce: false,
+            cache: None,
+            audit_sink: None,
+            pii_redaction: None,
+            policy: None,
+            plugins: vec![],
+            middlewares: None,
+            listener: None,
+            websocket: None,
+            compression: None,
+            access_log: None,
+            error_response: None,
+            response_buffer_limit: r#const::response_buffer::DEFAULT_LIMIT,
+            allowed_methods: vec![],
+            allowed_paths: vec![],
+            request_headers: None,
+            breaker: None,
+        };
+        let server = ForwardServer::new(config, upstream_manager.clone(), &[]).unwrap();
+        axum::Router::new()
+            .route("/{*path}", axum::routing::any(forward_handler))
+            .with_state(server.get_state().clone())
+    };
+    let request = |body: &'static str| {
+        axum::http::Request::builder()
+            .method("POST")
+            .uri("/v1/chat/completions")
+            .header("content-length", body.len())
+            .body(axum::body::Body::from(body))
+            .unwrap()
+    };
+    let response_body = |response: axum::response::Response| async move {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    };
+
+    // 改写为上限值后转发
+    let clamp = app(ParamLimitAction::Clamp);
+    let response = clamp
+        .clone()
+        .oneshot(request(
+            r#"{"model":"gpt-4o","max_tokens":100000,"temperature":1.8,"n":4}"#,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response_body(response).await, "clamped");
+
+    // 未超出上限的请求原样转发
+    let reject = app(ParamLimitAction::Reject);
+    let response = reject
+        .clone()
+        .oneshot(request(
+            r#"{"model":"gpt-4o","max_tokens":1024,"temperature":1.0,"n":1}"#,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response_body(response).await, "clamped");
+
+    // 超出上限时返回 OpenAI 格式的错误
+    let response = reject
+        .oneshot(request(
+            r#"{"model":"gpt-4o","max_completion_tokens":4096}"#,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+    let error: serde_json::Value = serde_json::from_str(&response_body(response).await).unwrap();
+    assert_eq!(error["error"]["type"], "invalid_request_error");
+    assert_eq!(error["error"]["param"], "max_completion_tokens");
+    assert_eq!(error["error"]["code"], "parameter_limit_exceeded");
+
+    Ok(())
+}
+
+/// 测试提示词 token 估算
+#[test]
+fn test_count_prompt_tokens() {
+    let count = |body: serde_json::Value| count_prompt_tokens(body.to_string().as_bytes());
+
+    // 每条消息 3 个格式 token，回复起始 3 个 token，"user" 1 个，"hello world" 2 个
+    for model in ["gpt-4", "gpt-4o", "claude-sonnet"] {
+        assert_eq!(
+            count(serde_json::json!({
+                "model": model,
+                "messages": [{"role": "user", "content": "hello world"}],
+            })),
+            Some(9),
+            "model {}",
+            model
+        );
+    }
+    assert_eq!(
+        count(serde_json::json!({
+            "model": "gpt-4o",
+            "messages": [{"role": "user", "content": [{"type": "text", "text": "hello world"}]}],
+        })),
+        Some(9)
+    );
+
+    // completions 请求
+    assert_eq!(
+        count(serde_json::json!({"model": "gpt-4", "prompt": "hello world"})),
+        Some(2)
+    );
+    assert_eq!(
+        count(serde_json::json!({"model": "gpt-4", "prompt": [[1, 2, 3], "hello world"]})),
+        Some(5)
+    );
+
+    // 其他请求不估算
+    assert_eq!(
+        count(serde_json::json!({"model": "gpt-4", "input": "hello"})),
+        None
+    );
+    assert_eq!(count_prompt_tokens(b"not json"), None);
+}
+
+/// 测试提示词 token 数通过响应头返回，超出上限时在转发前拒绝
+#[tokio::test]
+async fn test_forward_server_prompt_tokens() -> Result<(), AppError> {
+    let (upstream_manager, mock_server) = create_test_upstream_manager().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("OK"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let config = ForwardConfig {
+        name: "token_forward".to_string(),
+        port: 0, // 使用系统分配的端口
+        address: "127.0.0.1".to_string(),
+        default_group: "test_group".to_string(),
+        ratelimit: None,
+        timeout: Some(TimeoutConfig::default()),
+        routing: None,
+        limits: Some(ParamLimitsConfig {
+            max_prompt_tokens: Some(16),
+            ..Default::default()
+        }),
+        count_tokens: false,
+        budget: None,
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: r#const::response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
+    };
+    let server = ForwardServer::new(config, upstream_manager, &[])?;
+    let app = axum::Router::new()
+        .route("/{*path}", axum::routing::any(forward_handler))
+        .with_state(server.get_state().clone());
+
+    let request = |content: &str| {
+        let body = serde_json::json!({
+            "model": "gpt-4",
+            "messages": [{"role": "user", "content": content}],
+        })
+        .to_string();
+        axum::http::Request::builder()
+            .method("POST")
+            .uri("/v1/chat/completions")
+            .body(axum::body::Body::from(body))
+            .unwrap()
+    };
+
+    let response = app.clone().oneshot(request("hello world")).await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["x-llmproxy-prompt-tokens"], "9");
+
+    // 超出上限的请求不会到达上游
+    let response = app
+        .oneshot(request(&"hello world ".repeat(20)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+    assert!(response.headers().contains_key("x-llmproxy-prompt-tokens"));
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let error: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(error["error"]["code"], "context_length_exceeded");
+
+    Ok(())
+}
+
+/// 测试路由规则的提示词 token 数上限只对匹配该路由的请求生效
+#[tokio::test]
+async fn test_forward_server_route_prompt_tokens() -> Result<(), AppError> {
+    let (upstream_manager, mock_server) = create_test_upstream_manager().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("OK"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let config = ForwardConfig {
+        name: "route_token_forward".to_string(),
+        port: 0, // 使用系统分配的端口
+        address: "127.0.0.1".to_string(),
+        default_group: "test_group".to_string(),
+        ratelimit: None,
+        timeout: Some(TimeoutConfig::default()),
+        routing: Some(
+            serde_yaml::from_str(
+                "[{path: /v1/chat/completions, target_group: test_group, max_prompt_tokens: 16}]",
+            )
+            .unwrap(),
+        ),
+        limits: None,
+        count_tokens: false,
+        budget: None,
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: r#const::response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
+    };
+    let server = ForwardServer::new(config, upstream_manager, &[])?;
+    let app = axum::Router::new()
+        .route("/{*path}", axum::routing::any(forward_handler))
+        .with_state(server.get_state().clone());
+
+    let request = |uri: &str, content: &str| {
+        let body = serde_json::json!({
+            "model": "gpt-4",
+            "messages": [{"role": "user", "content": content}],
+        })
+        .to_string();
+        axum::http::Request::builder()
+            .method("POST")
+            .uri(uri)
+            .body(axum::body::Body::from(body))
+            .unwrap()
+    };
+
+    // 匹配路由的超长请求不会到达上游，较大的请求体在阻塞线程池中估算
+    for repeat in [20, 10_000] {
+        let content = "hello world ".repeat(repeat);
+        let response = app
+            .clone()
+            .oneshot(request("/v1/chat/completions", &content))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400);
+        assert!(response.headers().contains_key("x-llmproxy-prompt-tokens"));
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let error: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(error["error"]["code"], "context_length_exceeded");
+    }
+
+    // 使用默认组的请求不受路由的上限限制
+    let response = app
+        .oneshot(request("/v1/completions", &"hello world ".repeat(20)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_forward_server_budget() -> Result<(), AppError> {
+    let mock_server = MockServer::start().await;
+
+    // 每次响应的费用为 (1000 * 2.0 + 500 * 8.0) / 1_000_000 = 0.006 美元
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "object": "chat.completion",
+            "model": "budget-model",
+            "choices": [],
+            "usage": {"prompt_tokens": 1000, "completion_tokens": 500, "total_tokens": 1500},
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let upstream = UpstreamConfig {
+        name: "budget_upstream".to_string(),
+        url: mock_server.uri().into(),
+        weight: 1,
+        http_client: HttpClientConfig::default(),
+        auth: None,
+        headers: vec![],
+        breaker: None,
+        adaptive: None,
+        hint: None,
+        enabled: true,
+        proxy: true,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect: None,
+        stream_normalize: None,
+        agent: None,
+        default_headers: Default::default(),
+        pricing: vec![ModelPriceConfig {
+            model: "budget-model".to_string(),
+            prompt: 2.0,
+            completion: 8.0,
+        }],
+        dns: None,
+        warmup: None,
+    };
+    let group = UpstreamGroupConfig {
+        name: "budget_group".to_string(),
+        upstreams: vec![UpstreamRef {
+            name: "budget_upstream".to_string(),
+            weight: 1,
+        }],
+        balance: BalanceConfig {
+            strategy: BalanceStrategy::RoundRobin,
+        },
+        http_client: HttpClientConfig::default(),
+        sticky: None,
+        discovery: None,
+    };
+    let upstream_manager = Arc::new(UpstreamManager::new(vec![upstream], vec![group]).await?);
+
+    let config = ForwardConfig {
+        name: "budget_forward".to_string(),
+        port: 0, // 使用系统分配的端口
+        address: "127.0.0.1".to_string(),
+        default_group: "budget_group".to_string(),
+        ratelimit: None,
+        timeout: Some(TimeoutConfig::default()),
+        routing: None,
+        limits: None,
+        count_tokens: false,
+        budget: Some(BudgetConfig {
+            header: "authorization".to_string(),
+            daily: Some(0.01),
+            monthly: None,
+            clients: vec![ClientBudgetConfig {
+                id: "sk-team-b".to_string(),
+                daily: Some(1.0),
+                monthly: None,
+            }],
+        }),
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: r#const::response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
+    };
+    let server = ForwardServer::new(config, upstream_manager, &[])?;
+    let app = axum::Router::new()
+        .route("/{*path}", axum::routing::any(forward_handler))
+        .with_state(server.get_state().clone());
+
+    let client_addr = std::net::SocketAddr::from(([127, 0, 0, 1], 40000));
+    let request = move |key: Option<&str>| {
+        let mut builder = axum::http::Request::builder()
+            .method("POST")
+            .uri("/v1/chat/completions");
+        if let Some(key) = key {
+            builder = builder.header("authorization", format!("Bearer {}", key));
+        }
+        let mut request = builder.body(axum::body::Body::from("{}")).unwrap();
+        request
+            .extensions_mut()
+            .insert(axum::extract::ConnectInfo(client_addr));
+        request
+    };
+    let send = |key: Option<&'static str>| {
+        let app = app.clone();
+        async move {
+            let response = app.oneshot(request(key)).await.unwrap();
+            let status = response.status();
+            let retry_after = response.headers().get("retry-after").cloned();
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            (status, retry_after, bytes)
+        }
+    };
+
+    // 两次请求后费用为 0.012 美元，超出每日预算
+    assert_eq!(send(Some("sk-team-a")).await.0, 200);
+    assert_eq!(send(Some("sk-team-a")).await.0, 200);
+    let (status, retry_after, bytes) = send(Some("sk-team-a")).await;
+    assert_eq!(status, 429);
+    let retry_after: u64 = retry_after.unwrap().to_str().unwrap().parse().unwrap();
+    assert!(retry_after > 0 && retry_after <= 86_400);
+    let error: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(error["error"]["type"], "insufficient_quota");
+    assert_eq!(error["error"]["code"], "budget_exceeded");
+    assert!(error["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("daily budget of $0.01"));
+
+    // 其他客户端使用各自的预算
+    for _ in 0..3 {
+        assert_eq!(send(Some("sk-team-b")).await.0, 200);
+    }
+    assert_eq!(send(Some("sk-team-c")).await.0, 200);
+
+    // 未携带请求头的请求按客户端 IP 使用默认预算，无法绕过限制
+    assert_eq!(send(None).await.0, 200);
+    assert_eq!(send(None).await.0, 200);
+    assert_eq!(send(None).await.0, 429);
+
+    Ok(())
+}
+
+/// 测试按每分钟 token 数限流
+#[tokio::test]
+async fn test_forward_server_token_limit() -> Result<(), AppError> {
+    let mock_server = MockServer::start().await;
+
+    // 每次响应的实际用量为 1500 个 token
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "object": "chat.completion",
+            "model": "tpm-model",
+            "choices": [],
+            "usage": {"prompt_tokens": 1000, "completion_tokens": 500, "total_tokens": 1500},
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let upstream = UpstreamConfig {
+        name: "tpm_upstream".to_string(),
+        url: mock_server.uri().into(),
+        weight: 1,
+        http_client: HttpClientConfig::default(),
+        auth: None,
+        headers: vec![],
+        breaker: None,
+        adaptive: None,
+        hint: None,
+        enabled: true,
+        proxy: true,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect: None,
+        stream_normalize: None,
+        agent: None,
+        default_headers: Default::default(),
+        pricing: vec![],
+        dns: None,
+        warmup: None,
+    };
+    let group = UpstreamGroupConfig {
+        name: "tpm_group".to_string(),
+        upstreams: vec![UpstreamRef {
+            name: "tpm_upstream".to_string(),
+            weight: 1,
+        }],
+        balance: BalanceConfig {
+            strategy: BalanceStrategy::RoundRobin,
+        },
+        http_client: HttpClientConfig::default(),
+        sticky: None,
+        discovery: None,
+    };
+    let upstream_manager = Arc::new(UpstreamManager::new(vec![upstream], vec![group]).await?);
+
+    let config = ForwardConfig {
+        name: "tpm_forward".to_string(),
+        port: 0, // 使用系统分配的端口
+        address: "127.0.0.1".to_string(),
+        default_group: "tpm_group".to_string(),
+        ratelimit: None,
+        timeout: Some(TimeoutConfig::default()),
+        routing: Some(vec![RoutingRule {
+            path: "/v1/embeddings".to_string(),
+            r#type: RoutingRuleType::Path,
+            target_group: "tpm_group".to_string(),
+            priority: 0,
+            breaker: None,
+            max_prompt_tokens: None,
+            tokens_per_minute: Some(5),
+        }]),
+        limits: None,
+        count_tokens: false,
+        budget: None,
+        token_limit: Some(TokenLimitConfig {
+            tokens_per_minute: 2000,
+            key: RateLimitKey::ApiKey,
+            header: None,
+            clients: vec![ClientTokenLimitConfig {
+                id: "sk-vip".to_string(),
+                tokens_per_minute: 1_000_000,
+            }],
+            redis: None,
+        }),
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: r#const::response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
+    };
+    let server = ForwardServer::new(config, upstream_manager, &[])?;
+    let app = axum::Router::new()
+        .route("/{*path}", axum::routing::any(forward_handler))
+        .with_state(server.get_state().clone());
+
+    let send = |uri: &'static str, key: &'static str| {
+        let app = app.clone();
+        async move {
+            let body = serde_json::json!({
+                "model": "tpm-model",
+                "messages": [{"role": "user", "content": "Tell me a story about rate limits"}],
+            });
+            let request = axum::http::Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("authorization", format!("Bearer {}", key))
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap();
+            let response = app.oneshot(request).await.unwrap();
+            let status = response.status();
+            let retry_after = response.headers().get("retry-after").cloned();
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            (status, retry_after, bytes)
+        }
+    };
+
+    // 转发前只扣除估算的提示词 token 数，响应后按实际用量 1500 修正，第三次请求时令牌桶已不足
+    assert_eq!(send("/v1/chat/completions", "sk-a").await.0, 200);
+    assert_eq!(send("/v1/chat/completions", "sk-a").await.0, 200);
+    let (status, retry_after, bytes) = send("/v1/chat/completions", "sk-a").await;
+    assert_eq!(status, 429);
+    let retry_after: u64 = retry_after.unwrap().to_str().unwrap().parse().unwrap();
+    assert!(retry_after > 0 && retry_after <= 60);
+    let error: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(error["error"]["type"], "tokens");
+    assert_eq!(error["error"]["code"], "rate_limit_exceeded");
+    assert!(error["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("limit 2000 tokens per minute"));
+
+    // 其他客户端使用各自的令牌桶，指定客户端使用自己的限额
+    assert_eq!(send("/v1/chat/completions", "sk-b").await.0, 200);
+    for _ in 0..3 {
+        assert_eq!(send("/v1/chat/completions", "sk-vip").await.0, 200);
+    }
+
+    // 匹配路由规则的请求使用路由的限额和独立的令牌桶
+    assert_eq!(send("/v1/embeddings", "sk-b").await.0, 200);
+    let (status, _, bytes) = send("/v1/embeddings", "sk-b").await;
+    assert_eq!(status, 429);
+    let error: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert!(error["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("limit 5 tokens per minute"));
+    assert_eq!(send("/v1/chat/completions", "sk-b").await.0, 200);
+
+    Ok(())
+}
+
+/// 测试并发请求限制和等待队列
+#[tokio::test]
+async fn test_forward_server_concurrency_limit() -> Result<(), AppError> {
+    let (upstream_manager, mock_server) = create_test_upstream_manager().await;
+
+    // 上游 URL 不带路径，按请求头区分流式响应和慢响应
+    Mock::given(method("GET"))
+        .and(header("x-mode", "stream"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw("data: {}\n\ndata: [DONE]\n\n", "text/event-stream"),
+        )
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(500)))
+        .mount(&mock_server)
+        .await;
+
+    let app = |max_concurrent: u32, queue: Option<QueueConfig>| {
+        let config = ForwardConfig {
+            name: "concurrency_forward".to_string(),
+            port: 0, // 使用系统分配的端口
+            address: "127.0.0.1".to_string(),
+            default_group: "test_group".to_string(),
+            ratelimit: None,
+            timeout: Some(TimeoutConfig::default()),
+            routing: None,
+            limits: None,
+            count_tokens: false,
+            budget: None,
+            token_limit: None,
+            max_concurrent: Some(max_concurrent),
+            queue,
+            sse_heartbeat: None,
+            coalesce: false,
+            cache: None,
+            audit_sink: None,
+            pii_redaction: None,
+            policy: None,
+            plugins: vec![],
+            middlewares: None,
+            listener: None,
+            websocket: None,
+            compression: None,
+            access_log: None,
+            error_response: None,
+            response_buffer_limit: r#const::response_buffer::DEFAULT_LIMIT,
+            allowed_methods: vec![],
+            allowed_paths: vec![],
+            request_headers: None,
+            breaker: None,
+        };
+        let server = ForwardServer::new(config, upstream_manager.clone(), &[]).unwrap();
+        axum::Router::new()
+            .route("/{*path}", axum::routing::any(forward_handler))
+            .with_state(server.get_state().clone())
+    };
+    let request = |mode: &str| {
+        axum::http::Request::builder()
+            .uri("/v1/chat/completions")
+            .header("x-mode", mode)
+            .body(axum::body::Body::empty())
+            .unwrap()
+    };
+    // 依次发起并发请求，返回各请求的状态码和 Retry-After
+    let concurrent = |app: axum::Router, count: usize| async move {
+        let mut handles = Vec::new();
+        for _ in 0..count {
+            let app = app.clone();
+            handles.push(tokio::spawn(async move {
+                let response = app.oneshot(request("slow")).await.unwrap();
+                let retry_after = response
+                    .headers()
+                    .get("retry-after")
+                    .map(|value| value.to_str().unwrap().to_string());
+                (response.status().as_u16(), retry_after)
+            }));
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        let mut results = Vec::new();
+        for handle in handles {
+            results.push(handle.await.unwrap());
+        }
+        results
+    };
+
+    // 第二个请求排队等待后成功，队列已满时第三个请求返回 429
+    let queue = QueueConfig {
+        max_depth: 1,
+        max_wait_ms: 2000,
+        fair: None,
+    };
+    let results = concurrent(app(1, Some(queue)), 3).await;
+    assert_eq!(results[0], (200, None));
+    assert_eq!(results[1], (200, None));
+    assert_eq!(results[2], (429, Some("2".to_string())));
+
+    // 排队等待超时返回 503
+    let queue = QueueConfig {
+        max_depth: 10,
+        max_wait_ms: 100,
+        fair: None,
+    };
+    let results = concurrent(app(1, Some(queue)), 2).await;
+    assert_eq!(results[0], (200, None));
+    assert_eq!(results[1], (503, Some("1".to_string())));
+
+    // 未配置等待队列时直接拒绝
+    let results = concurrent(app(1, None), 2).await;
+    assert_eq!(results[0], (200, None));
+    assert_eq!(results[1].0, 429);
+
+    // 流式响应在响应体发送完成前持有许可
+    let app = app(1, None);
+    let stream = app.clone().oneshot(request("stream")).await.unwrap();
+    assert_eq!(stream.status(), 200);
+    let response = app.clone().oneshot(request("slow")).await.unwrap();
+    assert_eq!(response.status(), 429);
+    let error: serde_json::Value = serde_json::from_slice(
+        &axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(error["error"]["code"], "concurrency_limit_exceeded");
+    let body = axum::body::to_bytes(stream.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert!(body.ends_with(b"data: [DONE]\n\n"));
+    let response = app.oneshot(request("slow")).await.unwrap();
+    assert_eq!(response.status(), 200);
+
+    Ok(())
+}
+
+/// 测试客户端在流式响应完成前断开连接
+#[tokio::test]
+async fn test_forward_server_client_disconnect() -> Result<(), AppError> {
+    let (upstream_manager, mock_server) = create_test_upstream_manager().await;
+
+    Mock::given(method("GET"))
+        .and(header("x-mode", "stream"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw("data: {}\n\ndata: [DONE]\n\n", "text/event-stream"),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let config = ForwardConfig {
+        name: "disconnect_forward".to_string(),
+        port: 0, // 使用系统分配的端口
+        address: "127.0.0.1".to_string(),
+        default_group: "test_group".to_string(),
+        ratelimit: None,
+        timeout: Some(TimeoutConfig::default()),
+        routing: None,
+        limits: None,
+        count_tokens: false,
+        budget: None,
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: r#const::response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
+    };
+    let server = ForwardServer::new(config, upstream_manager, &[]).unwrap();
+    let app = axum::Router::new()
+        .route("/{*path}", axum::routing::any(forward_handler))
+        .with_state(server.get_state().clone());
+    let request = || {
+        axum::http::Request::builder()
+            .uri("/v1/chat/completions")
+            .header("x-mode", "stream")
+            .body(axum::body::Body::empty())
+            .unwrap()
+    };
+    let disconnects = || {
+        METRICS
+            .http_request_errors_total()
+            .with_label_values(&["disconnect_forward", "client_disconnected", "200"])
+            .get()
+    };
+
+    // 读取完整响应体不计为断开连接
+    let response = app.clone().oneshot(request()).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert!(body.ends_with(b"data: [DONE]\n\n"));
+    assert_eq!(disconnects(), 0);
+
+    // 响应体发送完成前被丢弃
+    let response = app.oneshot(request()).await.unwrap();
+    assert_eq!(response.status(), 200);
+    drop(response);
+    assert_eq!(disconnects(), 1);
+
+    Ok(())
+}
+
+/// 测试上游空闲时向事件流响应注入心跳
+#[tokio::test]
+async fn test_forward_server_sse_heartbeat() -> Result<(), AppError> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // 上游发送第一个事件后空闲 1.5 秒再发送第二个事件
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = [0u8; 4096];
+        let _ = socket.read(&mut buf).await.unwrap();
+        socket
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ntransfer-encoding: chunked\r\n\r\n9\r\ndata: 1\n\n\r\n")
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        socket
+            .write_all(b"9\r\ndata: 2\n\n\r\n0\r\n\r\n")
+            .await
+            .unwrap();
+    });
+
+    let upstream = UpstreamConfig {
+        name: "heartbeat_upstream".to_string(),
+        url: url.into(),
+        weight: 1,
+        http_client: HttpClientConfig::default(),
+        auth: None,
+        headers: vec![],
+        breaker: None,
+        adaptive: None,
+        hint: None,
+        enabled: true,
+        proxy: true,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect: None,
+        stream_normalize: None,
+        agent: None,
+        default_headers: Default::default(),
+        pricing: vec![],
+        dns: None,
+        warmup: None,
+    };
+    let group = UpstreamGroupConfig {
+        name: "heartbeat_group".to_string(),
+        upstreams: vec![UpstreamRef {
+            name: "heartbeat_upstream".to_string(),
+            weight: 1,
+        }],
+        balance: BalanceConfig {
+            strategy: BalanceStrategy::RoundRobin,
+        },
+        http_client: Default::default(),
+        sticky: None,
+        discovery: None,
+    };
+    let upstream_manager = Arc::new(
+        UpstreamManager::new(vec![upstream], vec![group])
+            .await
+            .unwrap(),
+    );
+
+    let config = ForwardConfig {
+        name: "heartbeat_forward".to_string(),
+        port: 0, // 使用系统分配的端口
+        address: "127.0.0.1".to_string(),
+        default_group: "heartbeat_group".to_string(),
+        ratelimit: None,
+        timeout: Some(TimeoutConfig::default()),
+        routing: None,
+        limits: None,
+        count_tokens: false,
+        budget: None,
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: Some(1),
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: r#const::response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
+    };
+    let server = ForwardServer::new(config, upstream_manager, &[]).unwrap();
+    let app = axum::Router::new()
+        .route("/{*path}", axum::routing::any(forward_handler))
+        .with_state(server.get_state().clone());
+
+    let request = axum::http::Request::builder()
+        .uri("/v1/chat/completions")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), 200);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+
+    // 心跳只插入在事件之间
+    assert_eq!(body.as_ref(), b"data: 1\n\n: ping\n\ndata: 2\n\n");
+
+    Ok(())
+}
+
+/// 测试转发服务的连接超时、请求超时和响应头超时，请求超时不限制流式响应的响应体
+#[tokio::test]
+async fn test_forward_server_timeouts() -> Result<(), AppError> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // 上游按请求头 x-mode 返回：stream 立即返回事件流并在 2.5 秒后结束，
+    // buffered 立即返回响应头并在 2.5 秒后返回响应体，slow 在 1.5 秒后返回响应头
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            tokio::spawn(async move {
+                let mut buf = [0u8; 4096];
+                let n = socket.read(&mut buf).await.unwrap();
+                let request = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+                if request.contains("x-mode: stream") {
+                    let _ = socket
+                        .write_all(b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ntransfer-encoding: chunked\r\n\r\n9\r\ndata: 1\n\n\r\n")
+                        .await;
+                    tokio::time::sleep(Duration::from_millis(2500)).await;
+                    let _ = socket.write_all(b"9\r\ndata: 2\n\n\r\n0\r\n\r\n").await;
+                } else if request.contains("x-mode: buffered") {
+                    let _ = socket
+                        .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\n")
+                        .await;
+                    tokio::time::sleep(Duration::from_millis(2500)).await;
+                    let _ = socket.write_all(b"ok").await;
+                } else {
+                    tokio::time::sleep(Duration::from_millis(1500)).await;
+                    let _ = socket
+                        .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nok")
+                        .await;
+                }
+            });
+        }
+    });
+
+    let upstream = UpstreamConfig {
+        name: "timeouts_upstream".to_string(),
+        url: url.into(),
+        weight: 1,
+        http_client: HttpClientConfig::default(),
+        auth: None,
+        headers: vec![],
+        breaker: None,
+        adaptive: None,
+        hint: None,
+        enabled: true,
+        proxy: true,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect: None,
+        stream_normalize: None,
+        agent: None,
+        default_headers: Default::default(),
+        pricing: vec![],
+        dns: None,
+        warmup: None,
+    };
+    let group = UpstreamGroupConfig {
+        name: "timeouts_group".to_string(),
+        upstreams: vec![UpstreamRef {
+            name: "timeouts_upstream".to_string(),
+            weight: 1,
+        }],
+        balance: BalanceConfig {
+            strategy: BalanceStrategy::RoundRobin,
+        },
+        http_client: Default::default(),
+        sticky: None,
+        discovery: None,
+    };
+    let upstream_manager = Arc::new(
+        UpstreamManager::new(vec![upstream], vec![group])
+            .await
+            .unwrap(),
+    );
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let config = ForwardConfig {
+        name: "timeouts_forward".to_string(),
+        port,
+        address: "127.0.0.1".to_string(),
+        default_group: "timeouts_group".to_string(),
+        ratelimit: None,
+        timeout: Some(TimeoutConfig {
+            connect: 1,
+            request: 2,
+            header: Some(1),
+        }),
+        routing: None,
+        limits: None,
+        count_tokens: false,
+        budget: None,
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: r#const::response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
+    };
+    let server = ForwardServer::new(config, upstream_manager, &[])?;
+    spawn_forward_server(server, port).await;
+
+    let client = reqwest::Client::new();
+    let url = format!("http://127.0.0.1:{}/v1/chat/completions", port);
+    let send = |mode: &'static str| client.get(&url).header("x-mode", mode).send();
+
+    // 流式响应开始发送后不受请求超时限制
+    let response = send("stream").await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response.bytes().await.unwrap().as_ref(),
+        b"data: 1\n\ndata: 2\n\n"
+    );
+
+    // 非流式响应在请求超时内没有读完响应体
+    let response = send("buffered").await.unwrap();
+    assert_eq!(response.status(), 408);
+
+    // 响应头超时内没有收到上游响应头
+    let response = send("slow").await.unwrap();
+    assert_eq!(response.status(), 500);
+
+    // 建立连接后在连接超时内没有发送数据的连接被关闭
+    let mut idle = tokio::net::TcpStream::connect(("127.0.0.1", port))
+        .await
+        .unwrap();
+    let mut buf = [0u8; 16];
+    let read = tokio::time::timeout(Duration::from_secs(3), idle.read(&mut buf))
+        .await
+        .expect("idle connection should be closed by the connect timeout");
+    assert!(matches!(read, Ok(0) | Err(_)));
+
+    Ok(())
+}
+
+/// 创建转发到 embedding 上游的转发服务路由，configure 修改转发服务配置
+async fn embeddings_app(
+    mock_server: &MockServer,
+    name: &str,
+    configure: impl FnOnce(&mut ForwardConfig),
+) -> axum::Router {
+    let server = embeddings_server(mock_server, name, configure).await;
+    axum::Router::new()
+        .route("/{*path}", axum::routing::any(forward_handler))
+        .with_state(server.get_state().clone())
+}
+
+/// 创建转发到 embedding 上游的转发服务，configure 修改转发服务配置
+async fn embeddings_server(
+    mock_server: &MockServer,
+    name: &str,
+    configure: impl FnOnce(&mut ForwardConfig),
+) -> ForwardServer {
+    embeddings_server_at(&mock_server.uri(), name, configure).await
+}
+
+/// 创建转发到指定地址的 embedding 上游的转发服务
+async fn embeddings_server_at(
+    base_url: &str,
+    name: &str,
+    configure: impl FnOnce(&mut ForwardConfig),
+) -> ForwardServer {
+    let upstream = UpstreamConfig {
+        name: format!("{}_upstream", name),
+        url: format!("{}/v1/embeddings", base_url).into(),
+        weight: 1,
+        http_client: HttpClientConfig::default(),
+        auth: None,
+        headers: vec![],
+        breaker: None,
+        adaptive: None,
+        hint: None,
+        enabled: true,
+        proxy: true,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect: None,
+        stream_normalize: None,
+        agent: None,
+        default_headers: Default::default(),
+        pricing: vec![],
+        dns: None,
+        warmup: None,
+    };
+    let group = UpstreamGroupConfig {
+        name: format!("{}_group", name),
+        upstreams: vec![UpstreamRef {
+            name: format!("{}_upstream", name),
+            weight: 1,
+        }],
+        balance: BalanceConfig {
+            strategy: BalanceStrategy::RoundRobin,
+        },
+        http_client: Default::default(),
+        sticky: None,
+        discovery: None,
+    };
+    let upstream_manager = Arc::new(
+        UpstreamManager::new(vec![upstream], vec![group])
+            .await
+            .unwrap(),
+    );
+
+    let mut config = ForwardConfig {
+        name: format!("{}_forward", name),
+        port: 0, // 使用系统分配的端口
+        address: "127.0.0.1".to_string(),
+        default_group: format!("{}_group", name),
+        ratelimit: None,
+        timeout: Some(TimeoutConfig::default()),
+        routing: None,
+        limits: None,
+        count_tokens: false,
+        budget: None,
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: None,
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: r#const::response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
+    };
+    configure(&mut config);
+    ForwardServer::new(config, upstream_manager, &[]).unwrap()
+}
+
+/// 创建 embedding 请求
+fn embeddings_request(input: &str, key: &str) -> axum::http::Request<axum::body::Body> {
+    axum::http::Request::builder()
+        .method("POST")
+        .uri("/v1/embeddings")
+        .header("content-type", "application/json")
+        .header("authorization", format!("Bearer {}", key))
+        .body(axum::body::Body::from(
+            serde_json::json!({"model": "embed", "input": input}).to_string(),
+        ))
+        .unwrap()
+}
+
+/// 测试转发服务只接受白名单中的请求方法和路径，其他请求不转发给上游
+#[tokio::test]
+async fn test_forward_server_allowlist() -> Result<(), AppError> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({"object": "list"})),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let app = embeddings_app(&mock_server, "allowlist", |c| {
+        c.allowed_methods = vec!["post".to_string()];
+        c.allowed_paths = vec!["/v1/embeddings".to_string(), "/v1/chat/*".to_string()];
+    })
+    .await;
+    let request = |method: &str, uri: &str| {
+        axum::http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(axum::body::Body::empty())
+            .unwrap()
+    };
+
+    let response = app
+        .clone()
+        .oneshot(embeddings_request("hello", "a"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    // 路径允许但方法不允许时返回 405 和允许的方法
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/v1/embeddings"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 405);
+    assert_eq!(response.headers()["allow"], "POST");
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/v1/chat/completions"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 405);
+
+    // 路径不在白名单中时返回 404，* 不匹配 /
+    let response = app
+        .clone()
+        .oneshot(request("POST", "/v1/models"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+    let response = app
+        .oneshot(request("POST", "/v1/chat/completions/extra"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+    assert_eq!(
+        METRICS
+            .http_request_errors_total()
+            .with_label_values(&["allowlist_forward", "not_allowed", "404"])
+            .get(),
+        2
+    );
+
+    Ok(())
+}
+
+/// 测试转发给上游前移除配置的请求头，请求头数量或大小超出限制时返回 431
+#[tokio::test]
+async fn test_forward_server_request_headers() -> Result<(), AppError> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({"object": "list"})),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let app = embeddings_app(&mock_server, "request_headers", |c| {
+        c.request_headers = Some(RequestHeadersConfig {
+            strip: vec!["Authorization".to_string(), "x-internal-trace".to_string()],
+            max_count: Some(8),
+            max_bytes: Some(1024),
+        });
+    })
+    .await;
+
+    // 配置的请求头不转发给上游，其他请求头原样转发
+    let mut request = embeddings_request("hello", "secret");
+    request
+        .headers_mut()
+        .insert("x-internal-trace", "trace-1".parse().unwrap());
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), 200);
+    let received = mock_server.received_requests().await.unwrap();
+    assert_eq!(received.len(), 1);
+    assert!(!received[0].headers.contains_key("authorization"));
+    assert!(!received[0].headers.contains_key("x-internal-trace"));
+    assert_eq!(received[0].headers["content-type"], "application/json");
+
+    // 请求头数量超出限制
+    let mut request = embeddings_request("hello", "secret");
+    for i in 0..8 {
+        request.headers_mut().insert(
+            format!("x-extra-{}", i)
+                .parse::<axum::http::HeaderName>()
+                .unwrap(),
+            "1".parse().unwrap(),
+        );
+    }
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), 431);
+
+    // 请求头总字节数超出限制
+    let mut request = embeddings_request("hello", "secret");
+    request
+        .headers_mut()
+        .insert("x-large", "a".repeat(1024).parse().unwrap());
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), 431);
+    assert_eq!(
+        METRICS
+            .http_request_errors_total()
+            .with_label_values(&["request_headers_forward", "headers_too_large", "431"])
+            .get(),
+        2
+    );
+
+    Ok(())
+}
+
+/// 测试相同的并发请求合并为一个上游请求
+#[tokio::test]
+async fn test_forward_server_coalesce() -> Result<(), AppError> {
+    let mock_server = MockServer::start().await;
+    // 上游延迟响应，保证并发请求到达时第一个请求仍在途
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({"object": "list", "data": []}))
+                .set_delay(Duration::from_millis(300)),
+        )
+        .expect(3)
+        .mount(&mock_server)
+        .await;
+
+    let app = embeddings_app(&mock_server, "coalesce", |c| c.coalesce = true).await;
+
+    // 三个相同的请求合并为一个上游请求，输入或凭证不同的请求单独转发
+    let responses = futures_util::future::join_all([
+        app.clone().oneshot(embeddings_request("hello", "a")),
+        app.clone().oneshot(embeddings_request("hello", "a")),
+        app.clone().oneshot(embeddings_request("hello", "a")),
+        app.clone().oneshot(embeddings_request("world", "a")),
+        app.clone().oneshot(embeddings_request("hello", "b")),
+    ])
+    .await;
+
+    let mut coalesced = 0;
+    for response in responses {
+        let response = response.unwrap();
+        assert_eq!(response.status(), 200);
+        if response.headers().contains_key("x-llmproxy-coalesced") {
+            coalesced += 1;
+        }
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()["object"],
+            "list"
+        );
+    }
+    assert_eq!(coalesced, 2);
+    assert_eq!(
+        METRICS
+            .coalesced_requests_total()
+            .with_label_values(&["coalesce_forward"])
+            .get(),
+        2
+    );
+
+    Ok(())
+}
+
+/// 测试请求指标的状态码类别和模型标签
+#[tokio::test]
+async fn test_forward_server_request_metric_labels() -> Result<(), AppError> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .respond_with(ResponseTemplate::new(429))
+        .mount(&mock_server)
+        .await;
+
+    let app = embeddings_app(&mock_server, "metric_labels", |_| {}).await;
+    let upstream_url = format!("{}/v1/embeddings", mock_server.uri());
+    let http_requests = |status_class: &str, model: &str| {
+        METRICS
+            .http_requests_total()
+            .with_label_values(&["metric_labels_forward", "POST", status_class, model])
+            .get()
+    };
+    let upstream_requests = |status_class: &str, model: &str| {
+        METRICS
+            .upstream_requests_total()
+            .with_label_values(&["metric_labels_group", &upstream_url, status_class, model])
+            .get()
+    };
+
+    // 开启模型标签后，按请求体中的模型名称记录
+    METRICS.set_model_label(true);
+    let response = app
+        .clone()
+        .oneshot(embeddings_request("hello", "a"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 429);
+    assert_eq!(http_requests("4xx", "embed"), 1);
+    assert_eq!(upstream_requests("4xx", "embed"), 1);
+
+    // 关闭模型标签后，模型标签为空
+    METRICS.set_model_label(false);
+    let response = app.oneshot(embeddings_request("hello", "a")).await.unwrap();
+    assert_eq!(response.status(), 429);
+    assert_eq!(http_requests("4xx", ""), 1);
+    assert_eq!(upstream_requests("4xx", ""), 1);
+    assert_eq!(http_requests("4xx", "embed"), 1);
+
+    Ok(())
+}
+
+/// 测试代理错误的响应体格式
+#[tokio::test]
+async fn test_forward_server_error_response() {
+    // 上游地址无法连接
+    let app = |name: &'static str, error_response: Option<ErrorResponseConfig>| async move {
+        let server = embeddings_server_at("http://127.0.0.1:1", name, |c| {
+            c.error_response = error_response
+        })
+        .await;
+        axum::Router::new()
+            .route("/{*path}", axum::routing::any(forward_handler))
+            .with_state(server.get_state().clone())
+    };
+    let openai = app("error_openai", Some(ErrorResponseConfig::default())).await;
+    let flat = app(
+        "error_json",
+        Some(ErrorResponseConfig {
+            format: ErrorResponseFormat::Json,
+            details: true,
+        }),
+    )
+    .await;
+    let plain = app("error_plain", None).await;
+    let send = |app: axum::Router| async move {
+        let mut request = embeddings_request("hello", "a");
+        request
+            .headers_mut()
+            .insert("x-request-id", "error-request".parse().unwrap());
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, body)
+    };
+
+    // OpenAI 格式，使用通用的错误消息，非幂等请求重试不安全
+    let (status, body) = send(openai).await;
+    assert_eq!(status, 500);
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({"error": {
+            "message": "Failed to get a response from the upstream service",
+            "type": "proxy_error",
+            "param": null,
+            "code": "upstream_error",
+            "request_id": "error-request",
+            "retryable": false,
+        }})
+    );
+
+    // 扁平的 JSON 格式，错误消息包含错误详情
+    let (status, body) = send(flat).await;
+    assert_eq!(status, 500);
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["code"], "upstream_error");
+    assert_eq!(body["request_id"], "error-request");
+    assert_eq!(body["retryable"], false);
+    assert!(body["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("Upstream error:"));
+
+    // 未配置时只返回状态码
+    let (status, body) = send(plain).await;
+    assert_eq!(status, 500);
+    assert!(body.is_empty());
+}
+
+/// 测试路由熔断器在上游组整体失败时直接拒绝请求
+#[tokio::test]
+async fn test_forward_server_route_breaker() {
+    let mock_server = MockServer::start().await;
+    // 组内共用的凭据失效，上游返回 401
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(401).set_body_string("invalid api key"))
+        .mount(&mock_server)
+        .await;
+    let server = embeddings_server(&mock_server, "route_breaker", |c| {
+        c.error_response = Some(ErrorResponseConfig::default());
+        c.breaker = Some(BreakerConfig {
+            window: Some(BreakerWindowConfig {
+                r#type: BreakerWindowType::Count,
+                size: 2,
+            }),
+            ..BreakerConfig::default()
+        });
+    })
+    .await;
+    let app = axum::Router::new()
+        .route("/{*path}", axum::routing::any(forward_handler))
+        .with_state(server.get_state().clone());
+
+    // 熔断前原样返回上游的响应
+    for _ in 0..2 {
+        let response = app
+            .clone()
+            .oneshot(embeddings_request("hello", "a"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 401);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"invalid api key");
+    }
+
+    // 路由熔断器开启后不再请求上游组
+    let response = app
+        .clone()
+        .oneshot(embeddings_request("hello", "a"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 500);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"]["code"], "circuit_open");
+    assert_eq!(body["error"]["retryable"], true);
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 2);
+    assert_eq!(
+        METRICS
+            .circuitbreaker_calls_total()
+            .with_label_values(&[
+                "route_breaker_group",
+                "route_breaker_forward:default",
+                "rejected"
+            ])
+            .get(),
+        1
+    );
+}
+
+/// 测试代理错误的错误码和重试是否安全
+#[test]
+fn test_proxy_error_classification() {
+    let post = axum::http::Method::POST;
+    let get = axum::http::Method::GET;
+
+    // 请求没有发送到上游时重试总是安全的
+    let error = ProxyError::new(
+        &AppError::CircuitBreakerOpen(Arc::new("u".into())),
+        &post,
+        "r",
+        false,
+    );
+    assert_eq!(error.code, "circuit_open");
+    assert!(error.retryable);
+    let error = ProxyError::new(&AppError::NoHealthyUpstreamAvailable, &post, "r", false);
+    assert_eq!(error.code, "no_upstream_available");
+    assert!(error.retryable);
+
+    // 上游请求失败时只有幂等请求可以安全重试
+    let upstream = AppError::Upstream("connection reset".to_string());
+    assert!(!ProxyError::new(&upstream, &post, "r", false).retryable);
+    assert!(ProxyError::new(&upstream, &get, "r", false).retryable);
+
+    // 配置错误重试无效
+    let error = ProxyError::new(
+        &AppError::UpstreamGroupNotFound("g".into()),
+        &get,
+        "r",
+        true,
+    );
+    assert_eq!(error.code, "configuration_error");
+    assert!(!error.retryable);
+    assert_eq!(error.message, "Upstream group not found: g");
+}
+
+/// 测试响应缓存
+#[tokio::test]
+async fn test_forward_server_response_cache() -> Result<(), AppError> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({"object": "list", "data": []})),
+        )
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let cache: CacheConfig = serde_yaml::from_str("ttl: 60").unwrap();
+    let app = embeddings_app(&mock_server, "cache", |c| c.cache = Some(cache)).await;
+
+    // 第二个相同的请求命中缓存，输入不同的请求转发给上游
+    let mut results = Vec::new();
+    for input in ["hello", "hello", "world"] {
+        let response = app
+            .clone()
+            .oneshot(embeddings_request(input, "a"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        results.push(response.headers().get("x-llmproxy-cache").cloned());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()["object"],
+            "list"
+        );
+        // 等待后台写入缓存
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(results, [None, Some("hit".parse().unwrap()), None]);
+    let lookups = |result: &str| {
+        METRICS
+            .cache_requests_total()
+            .with_label_values(&["cache_forward", result])
+            .get()
+    };
+    assert_eq!(lookups("hit"), 1);
+    assert_eq!(lookups("miss"), 2);
+
+    Ok(())
+}
+
+/// 测试超过缓冲上限的非流式响应直接以流式转发，不写入响应缓存
+#[tokio::test]
+async fn test_forward_server_unbuffered_response() -> Result<(), AppError> {
+    let mock_server = MockServer::start().await;
+    let embedding = vec![0.5; 1024];
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(
+            serde_json::json!({"object": "list", "data": [{"embedding": embedding}]}),
+        ))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let cache: CacheConfig = serde_yaml::from_str("ttl: 60").unwrap();
+    let app = embeddings_app(&mock_server, "unbuffered", |c| {
+        c.cache = Some(cache);
+        c.response_buffer_limit = 1024;
+    })
+    .await;
+
+    // 两个相同的请求都转发给上游，响应体完整
+    for _ in 0..2 {
+        let response = app
+            .clone()
+            .oneshot(embeddings_request("hello", "a"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(body.len() > 1024);
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()["data"][0]["embedding"]
+                .as_array()
+                .map(Vec::len),
+            Some(1024)
+        );
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    Ok(())
+}
+
+/// 测试 Redis 缓存不可用时请求照常转发
+#[tokio::test]
+async fn test_forward_server_response_cache_unavailable() -> Result<(), AppError> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({"object": "list", "data": []})),
+        )
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let cache = CacheConfig {
+        backend: CacheBackend::Redis,
+        redis: Some(RedisConfig {
+            url: "redis://127.0.0.1:1/0".to_string(),
+            password: None,
+            key_prefix: "llmproxy:".to_string(),
+        }),
+        ..serde_yaml::from_str("ttl: 60").unwrap()
+    };
+    let app = embeddings_app(&mock_server, "cache_unavailable", |c| c.cache = Some(cache)).await;
+
+    for _ in 0..2 {
+        let response = app
+            .clone()
+            .oneshot(embeddings_request("hello", "a"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert!(!response.headers().contains_key("x-llmproxy-cache"));
+    }
+
+    Ok(())
+}
+
+/// 测试请求审计日志写入 JSONL 文件并脱敏指定字段
+#[tokio::test]
+async fn test_forward_server_audit_sink_file() -> Result<(), AppError> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({"object": "list", "data": [], "model": "embed"})),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("audit.jsonl");
+    let audit_sink: AuditSinkConfig = serde_yaml::from_str(&format!(
+        "file: {:?}\nredact_fields: [input, model]",
+        file.to_string_lossy()
+    ))
+    .unwrap();
+    let app = embeddings_app(&mock_server, "audit_file", |c| {
+        c.audit_sink = Some(audit_sink)
+    })
+    .await;
+
+    for input in ["secret prompt", "another prompt"] {
+        let response = app
+            .clone()
+            .oneshot(embeddings_request(input, "a"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        // 响应体发送完成后生成审计记录
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+    }
+
+    // 等待后台写入
+    let mut records = Vec::new();
+    for _ in 0..50 {
+        let content = std::fs::read_to_string(&file).unwrap_or_default();
+        records = content
+            .lines()
+            .map(|line| serde_json::from_str::<AuditRecord>(line).unwrap())
+            .collect();
+        if records.len() == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(records.len(), 2);
+    let record = &records[0];
+    assert_eq!(record.forward, "audit_file_forward");
+    assert_eq!(record.method, "POST");
+    assert_eq!(record.path, "/v1/embeddings");
+    assert_eq!(record.group, "audit_file_group");
+    assert_eq!(record.status, 200);
+    assert!(!record.request_id.is_empty());
+    assert_eq!(record.request.body["input"], "******");
+    assert_eq!(record.request.body["model"], "******");
+    assert!(!record.request.truncated);
+    assert_eq!(record.response.body["object"], "list");
+    assert_eq!(record.response.body["model"], "******");
+    assert_eq!(
+        METRICS
+            .audit_records_total()
+            .with_label_values(&["audit_file_forward", "written"])
+            .get(),
+        2
+    );
+
+    Ok(())
+}
+
+/// 测试请求审计日志按批次发送到 HTTP 接收端并截断超出上限的内容
+#[tokio::test]
+async fn test_forward_server_audit_sink_http() -> Result<(), AppError> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({"object": "list", "data": []})),
+        )
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/audit"))
+        .and(header("authorization", "Bearer sink-token"))
+        .and(header("content-type", "application/x-ndjson"))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&mock_server)
+        .await;
+
+    let audit_sink: AuditSinkConfig = serde_yaml::from_str(&format!(
+        "url: {}/audit\ntoken: sink-token\nmax_body_bytes: 16",
+        mock_server.uri()
+    ))
+    .unwrap();
+    let app = embeddings_app(&mock_server, "audit_http", |c| {
+        c.audit_sink = Some(audit_sink)
+    })
+    .await;
+
+    let response = app
+        .clone()
+        .oneshot(embeddings_request("hello", "a"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+
+    // 等待后台发送
+    let mut received = Vec::new();
+    for _ in 0..50 {
+        received = mock_server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|request| request.url.path() == "/audit")
+            .collect();
+        if !received.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(received.len(), 1);
+    let body = String::from_utf8(received[0].body.clone()).unwrap();
+    assert!(body.ends_with('\n'));
+    let record: AuditRecord = serde_json::from_str(body.trim_end()).unwrap();
+    assert_eq!(record.forward, "audit_http_forward");
+    // 超出记录上限的内容截断为文本
+    assert!(record.request.truncated);
+    assert_eq!(record.request.body.as_str().unwrap().len(), 16);
+    assert!(record.response.truncated);
+
+    Ok(())
+}
+
+/// 测试等待队列按客户端加权公平调度
+#[tokio::test]
+async fn test_concurrency_fair_queue() {
+    let queue = QueueConfig {
+        max_depth: 10,
+        max_wait_ms: 5000,
+        fair: Some(FairQueueConfig {
+            key: RateLimitKey::Header,
+            header: Some("x-client".to_string()),
+            default_weight: 1,
+            tiers: vec![QueueTierConfig {
+                name: "interactive".to_string(),
+                weight: 4,
+                clients: vec!["alice".to_string()],
+            }],
+        }),
+    };
+    assert_eq!(queue.fair.as_ref().unwrap().weight(Some("alice")), 4);
+    assert_eq!(queue.fair.as_ref().unwrap().weight(Some("batch")), 1);
+    let limiter = Arc::new(ConcurrencyLimiter::new(1, Some(&queue)));
+    let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    // 占用唯一的许可，之后批处理客户端先排队 4 个请求，交互客户端再排队 2 个请求
+    let holder = limiter
+        .acquire(&axum::http::HeaderMap::new(), None)
+        .await
+        .unwrap();
+    let mut handles = Vec::new();
+    for client in ["batch", "batch", "batch", "batch", "alice", "alice"] {
+        let limiter = limiter.clone();
+        let order = order.clone();
+        handles.push(tokio::spawn(async move {
+            let mut headers = axum::http::HeaderMap::new();
+            headers.insert("x-client", client.parse().unwrap());
+            let _permit = limiter.acquire(&headers, None).await.unwrap();
+            order.lock().unwrap().push(client);
+        }));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    drop(holder);
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    // 交互客户端的权重更高，不必等待批处理客户端的全部请求
+    assert_eq!(
+        *order.lock().unwrap(),
+        ["batch", "alice", "alice", "batch", "batch", "batch"]
+    );
+
+    // 未配置公平调度时按到达顺序调度
+    let limiter = Arc::new(ConcurrencyLimiter::new(
+        1,
+        Some(&QueueConfig {
+            fair: None,
+            ..queue
+        }),
+    ));
+    let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let holder = limiter
+        .acquire(&axum::http::HeaderMap::new(), None)
+        .await
+        .unwrap();
+    let mut handles = Vec::new();
+    for client in ["batch", "batch", "alice"] {
+        let limiter = limiter.clone();
+        let order = order.clone();
+        handles.push(tokio::spawn(async move {
+            let _permit = limiter
+                .acquire(&axum::http::HeaderMap::new(), None)
+                .await
+                .unwrap();
+            order.lock().unwrap().push(client);
+        }));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    drop(holder);
+    for handle in handles {
+        handle.await.unwrap();
+    }
+    assert_eq!(*order.lock().unwrap(), ["batch", "batch", "alice"]);
+}
+
+/// 测试 PII 脱敏器只替换提示词字段中的文本
+#[test]
+fn test_pii_redactor() {
+    let config: PiiRedactionConfig = serde_yaml::from_str(
+        r#"
+detectors: [email, phone, credit_card]
+patterns:
+  - name: employee_id
+    regex: "EMP-\\d{6}"
+"#,
+    )
+    .unwrap();
+    let redactor = PiiRedactor::new(&config).unwrap();
+
+    let body = serde_json::json!({
+        "model": "alice@example.com",
+        "messages": [
+            {"role": "system", "content": "Reply to bob@example.com"},
+            {"role": "user", "content": [
+                {"type": "text", "text": "Call +1 415-555-0123, card 4111 1111 1111 1111, id EMP-123456"},
+                {"type": "image_url", "image_url": {"url": "https://example.com/a@b.com"}}
+            ]}
+        ]
+    });
+    let redacted = redactor
+        .redact(serde_json::to_vec(&body).unwrap().as_slice())
+        .unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&redacted.body).unwrap();
+    assert_eq!(value["model"], "alice@example.com");
+    assert_eq!(value["messages"][0]["content"], "Reply to [EMAIL]");
+    assert_eq!(
+        value["messages"][1]["content"][0]["text"],
+        "Call [PHONE], card [CREDIT_CARD], id [REDACTED]"
+    );
+    assert_eq!(
+        value["messages"][1]["content"][1]["image_url"]["url"],
+        "https://example.com/a@b.com"
+    );
+    assert_eq!(
+        redacted.counts,
+        vec![
+            ("email".to_string(), 1),
+            ("phone".to_string(), 1),
+            ("credit_card".to_string(), 1),
+            ("employee_id".to_string(), 1),
+        ]
+    );
+
+    // 不满足 Luhn 校验的数字不是银行卡号，没有替换时不改写请求体
+    assert!(redactor
+        .redact(br#"{"prompt": "order 1234 5678 9012 3456"}"#)
+        .is_none());
+    assert!(redactor.redact(b"not json").is_none());
+}
+
+/// 测试转发前脱敏请求体中的个人信息
+#[tokio::test]
+async fn test_forward_server_pii_redaction() -> Result<(), AppError> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .and(body_json(
+            serde_json::json!({"model": "embed", "input": "mail [EMAIL] or [EMAIL]"}),
+        ))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({"object": "list", "data": []})),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let pii: PiiRedactionConfig = serde_yaml::from_str("detectors: [email]").unwrap();
+    let app = embeddings_app(&mock_server, "pii", |c| c.pii_redaction = Some(pii)).await;
+
+    let response = app
+        .oneshot(embeddings_request(
+            "mail a@example.com or b@example.org",
+            "a",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        METRICS
+            .pii_redactions_total()
+            .with_label_values(&["pii_forward", "email"])
+            .get(),
+        2
+    );
+
+    Ok(())
+}
+
+/// 测试按外部策略服务的裁决放行、拒绝和改写请求
+#[tokio::test]
+async fn test_forward_server_policy() -> Result<(), AppError> {
+    let mock_server = MockServer::start().await;
+    // 策略服务：放行并添加请求头、拒绝、改写请求体
+    let policy_mock = |input: &str, verdict: serde_json::Value| {
+        Mock::given(method("POST"))
+            .and(path("/policy"))
+            .and(header("authorization", "Bearer policy-secret"))
+            .and(body_partial_json(serde_json::json!({
+                "forward": "policy_forward",
+                "method": "POST",
+                "path": "/v1/embeddings",
+                "body": {"model": "embed", "input": input},
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(verdict))
+            .expect(1)
+    };
+    policy_mock(
+        "hello",
+        serde_json::json!({"action": "allow", "headers": {"x-policy-tag": "reviewed"}}),
+    )
+    .mount(&mock_server)
+    .await;
+    policy_mock(
+        "forbidden",
+        serde_json::json!({"action": "block", "reason": "Prompt violates policy", "status": 451}),
+    )
+    .mount(&mock_server)
+    .await;
+    policy_mock(
+        "rewrite me",
+        serde_json::json!({"action": "modify", "body": {"model": "embed", "input": "rewritten"}}),
+    )
+    .mount(&mock_server)
+    .await;
+
+    // 上游只收到放行的请求，改写的请求使用新的请求体
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .and(header("x-policy-tag", "reviewed"))
+        .and(body_json(
+            serde_json::json!({"model": "embed", "input": "hello"}),
+        ))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({"object": "list", "data": []})),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .and(body_json(
+            serde_json::json!({"model": "embed", "input": "rewritten"}),
+        ))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({"object": "list", "data": []})),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let policy: PolicyConfig = serde_yaml::from_str(&format!(
+        "url: {}/policy\ntoken: policy-secret",
+        mock_server.uri()
+    ))
+    .unwrap();
+    let app = embeddings_app(&mock_server, "policy", |c| c.policy = Some(policy)).await;
+
+    let response = app
+        .clone()
+        .oneshot(embeddings_request("hello", "a"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    let response = app
+        .clone()
+        .oneshot(embeddings_request("forbidden", "a"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 451);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"]["message"], "Prompt violates policy");
+    assert_eq!(body["error"]["type"], "policy_violation");
+
+    let response = app
+        .oneshot(embeddings_request("rewrite me", "a"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    for decision in ["allow", "block", "modify"] {
+        assert_eq!(
+            METRICS
+                .policy_decisions_total()
+                .with_label_values(&["policy_forward", decision])
+                .get(),
+            1
+        );
+    }
+
+    Ok(())
+}
+
+/// 测试策略服务不可用时按 fail_mode 放行或拒绝请求
+#[tokio::test]
+async fn test_forward_server_policy_fail_mode() -> Result<(), AppError> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/policy"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({"object": "list", "data": []})),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    for (name, fail_mode, status) in [
+        ("policy_closed", "closed", 503),
+        ("policy_open", "open", 200),
+    ] {
+        let policy: PolicyConfig = serde_yaml::from_str(&format!(
+            "url: {}/policy\nfail_mode: {}",
+            mock_server.uri(),
+            fail_mode
+        ))
+        .unwrap();
+        let app = embeddings_app(&mock_server, name, |c| c.policy = Some(policy)).await;
+        let response = app.oneshot(embeddings_request("hello", "a")).await.unwrap();
+        assert_eq!(response.status(), status);
+        assert_eq!(
+            METRICS
+                .policy_decisions_total()
+                .with_label_values(&[&format!("{}_forward", name), "error"])
+                .get(),
+            1
+        );
+    }
+
+    Ok(())
+}
+
+/// 测试请求处理阶段的执行顺序：响应缓存在策略服务之前时，命中缓存的请求不调用策略服务
+#[tokio::test]
+async fn test_forward_server_middleware_order() -> Result<(), AppError> {
+    for (middlewares, policy_calls) in [
+        (None, 2),
+        (
+            Some(vec![MiddlewareStage::Cache, MiddlewareStage::Policy]),
+            1,
+        ),
+    ] {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/policy"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({"action": "allow"})),
+            )
+            .expect(policy_calls)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/embeddings"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"object": "list", "data": []})),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let cache: CacheConfig = serde_yaml::from_str("ttl: 60").unwrap();
+        let policy: PolicyConfig =
+            serde_yaml::from_str(&format!("url: {}/policy", mock_server.uri())).unwrap();
+        let app = embeddings_app(&mock_server, "middleware_order", |c| {
+            c.cache = Some(cache);
+            c.policy = Some(policy);
+            c.middlewares = middlewares;
+        })
+        .await;
+
+        // 第二个相同的请求命中响应缓存
+        for _ in 0..2 {
+            let response = app
+                .clone()
+                .oneshot(embeddings_request("hello", "a"))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), 200);
+            // 等待后台写入缓存
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        mock_server.verify().await;
+    }
+
+    Ok(())
+}
+
+/// 测试入口阶段的执行顺序：限流在访问日志之外时，被限流的请求不记录访问日志
+#[tokio::test]
+async fn test_forward_server_layer_middleware_order() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({"object": "list"})),
+        )
+        .mount(&mock_server)
+        .await;
+    let body = serde_json::json!({"model": "embed", "input": "hello"});
+    let client = reqwest::Client::new();
+    let dir = tempfile::tempdir().unwrap();
+
+    for (name, middlewares, lines) in [
+        ("layer_order_default", None, 2),
+        (
+            "layer_order_ratelimit_first",
+            Some(vec![MiddlewareStage::RateLimit, MiddlewareStage::AccessLog]),
+            1,
+        ),
+    ] {
+        let file = dir.path().join(format!("{}.log", name));
+        let access_log: AccessLogConfig =
+            serde_yaml::from_str(&format!("file: {:?}", file.to_str().unwrap())).unwrap();
+        let url = run_embeddings_server(&mock_server, name, |c| {
+            c.ratelimit = Some(RateLimitConfig {
+                per_second: 1,
+                burst: 1,
+                key: RateLimitKey::Ip,
+                header: None,
+                redis: None,
+            });
+            c.access_log = Some(access_log);
+            c.middlewares = middlewares;
+        })
+        .await;
+
+        let statuses = [
+            client.post(&url).json(&body).send().await.unwrap().status(),
+            client.post(&url).json(&body).send().await.unwrap().status(),
+        ];
+        assert_eq!(statuses, [200, 429], "{}", name);
+
+        // 等待后台线程写入访问日志
+        let mut text = String::new();
+        for _ in 0..50 {
+            text = std::fs::read_to_string(&file).unwrap_or_default();
+            if text.lines().count() >= lines {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        text = std::fs::read_to_string(&file).unwrap_or(text);
+        assert_eq!(text.lines().count(), lines, "{}", name);
+    }
+}
+
+/// 在后台运行使用指定入站连接配置的转发服务，返回 embedding 请求地址
+async fn run_embeddings_server(
+    mock_server: &MockServer,
+    name: &str,
+    configure: impl FnOnce(&mut ForwardConfig),
+) -> String {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let server = embeddings_server(mock_server, name, |c| {
+        c.port = port;
+        configure(c);
+    })
+    .await;
+    spawn_forward_server(server, port).await;
+    format!("http://127.0.0.1:{}/v1/embeddings", port)
+}
+
+/// 在后台运行转发服务，等待开始监听
+async fn spawn_forward_server(server: ForwardServer, port: u16) {
+    tokio::spawn(
+        Toplevel::new(move |s| async move {
+            s.start(SubsystemBuilder::new("forward", move |s| async move {
+                server.run(s).await
+            }));
+        })
+        .handle_shutdown_requests(Duration::from_secs(1)),
+    );
+
+    // 等待转发服务开始监听
+    for _ in 0..100 {
+        if tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_ok()
+        {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+/// 测试本地限流和分布式限流（Redis 不可用时回退为本地令牌桶）的限流响应头
+#[tokio::test]
+async fn test_forward_server_rate_limit_headers() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({"object": "list"})),
+        )
+        .mount(&mock_server)
+        .await;
+    let body = serde_json::json!({"model": "embed", "input": "hello"});
+    let client = reqwest::Client::new();
+    let header = |response: &reqwest::Response, name: &str| {
+        response
+            .headers()
+            .get(name)
+            .map(|value| value.to_str().unwrap().to_string())
+    };
+
+    let redis = RedisConfig {
+        url: "redis://127.0.0.1:1/0".to_string(),
+        password: None,
+        key_prefix: "llmproxy:".to_string(),
+    };
+    for (name, redis) in [("ratelimit_local", None), ("ratelimit_redis", Some(redis))] {
+        let url = run_embeddings_server(&mock_server, name, |c| {
+            c.ratelimit = Some(RateLimitConfig {
+                per_second: 1,
+                burst: 2,
+                key: RateLimitKey::Ip,
+                header: None,
+                redis,
+            })
+        })
+        .await;
+
+        // 放行的响应带有令牌桶容量和剩余请求数
+        for remaining in ["1", "0"] {
+            let response = client.post(&url).json(&body).send().await.unwrap();
+            assert_eq!(response.status(), 200, "{}", name);
+            assert_eq!(header(&response, "x-ratelimit-limit").as_deref(), Some("2"));
+            assert_eq!(
+                header(&response, "x-ratelimit-remaining").as_deref(),
+                Some(remaining),
+                "{}",
+                name
+            );
+            assert_eq!(header(&response, "retry-after"), None);
+        }
+
+        // 被限流的响应带有 Retry-After
+        let response = client.post(&url).json(&body).send().await.unwrap();
+        assert_eq!(response.status(), 429, "{}", name);
+        assert_eq!(header(&response, "x-ratelimit-limit").as_deref(), Some("2"));
+        assert_eq!(
+            header(&response, "x-ratelimit-remaining").as_deref(),
+            Some("0")
+        );
+        assert_eq!(
+            header(&response, "retry-after").as_deref(),
+            Some("1"),
+            "{}",
+            name
+        );
+        assert_eq!(header(&response, "x-ratelimit-after").as_deref(), Some("1"));
+    }
+}
+
+/// 测试入站连接配置：开启 http2 后接受 HTTP/2 连接，请求头超过上限时拒绝
+#[tokio::test]
+async fn test_forward_server_listener() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({"object": "list", "data": []})),
+        )
+        .mount(&mock_server)
+        .await;
+    let body = serde_json::json!({"model": "embed", "input": "hello"});
+    let h2_client = reqwest::Client::builder()
+        .http2_prior_knowledge()
+        .build()
+        .unwrap();
+
+    let listener: ListenerConfig = serde_yaml::from_str(
+        "backlog: 128\ntcp_nodelay: true\nhttp2: true\nmax_concurrent_streams: 16\nmax_header_bytes: 8192\nidle_timeout: 30",
+    )
+    .unwrap();
+    let url =
+        run_embeddings_server(&mock_server, "listener_h2", |c| c.listener = Some(listener)).await;
+
+    // HTTP/2 和 HTTP/1.1 客户端都可以访问
+    let response = h2_client.post(&url).json(&body).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.version(), reqwest::Version::HTTP_2);
+    let response = reqwest::Client::new()
+        .post(&url)
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.version(), reqwest::Version::HTTP_11);
+
+    // 请求头超过上限时拒绝请求
+    let result = reqwest::Client::new()
+        .post(&url)
+        .header("x-large", "a".repeat(16 * 1024))
+        .json(&body)
+        .send()
+        .await;
+    assert!(result.map_or(true, |response| response.status() == 431));
+
+    // 默认只接受 HTTP/1 连接
+    let url = run_embeddings_server(&mock_server, "listener_h1", |c| {
+        c.listener = Some(ListenerConfig::default())
+    })
+    .await;
+    assert!(h2_client.post(&url).json(&body).send().await.is_err());
+    let response = reqwest::Client::new()
+        .post(&url)
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+}
+
+/// 测试响应压缩
+#[tokio::test]
+async fn test_forward_server_compression() -> Result<(), AppError> {
+    use std::io::Read;
+
+    let mock_server = MockServer::start().await;
+    let large = serde_json::json!({"object": "list", "data": [{"embedding": vec![0.125; 512]}]});
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({"input": "large"})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&large))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({"input": "small"})))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({"object": "list"})),
+        )
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({"input": "encoded"})))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "application/json")
+                .insert_header("content-encoding", "gzip")
+                .set_body_bytes(vec![0x1f; 2048]),
+        )
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({"input": "stream"})))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw("data: x\n\n".repeat(512), "text/event-stream"),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let compression: CompressionConfig = serde_yaml::from_str("min_size: 1024").unwrap();
+    let app = embeddings_app(&mock_server, "compression", |_| {})
+        .await
+        .layer(compression_layer(&compression));
+    let send = |input: &str, accept_encoding: Option<&str>| {
+        let mut request = embeddings_request(input, "a");
+        if let Some(encoding) = accept_encoding {
+            request
+                .headers_mut()
+                .insert("accept-encoding", encoding.parse().unwrap());
+        }
+        app.clone().oneshot(request)
+    };
+    let content_encoding = |response: &axum::response::Response| {
+        response
+            .headers()
+            .get("content-encoding")
+            .map(|value| value.to_str().unwrap().to_string())
+    };
+
+    // 按客户端的 Accept-Encoding 压缩较大的非流式响应
+    let response = send("large", Some("gzip")).await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(content_encoding(&response).as_deref(), Some("gzip"));
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let mut decoded = String::new();
+    flate2::read::GzDecoder::new(body.as_ref())
+        .read_to_string(&mut decoded)
+        .unwrap();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&decoded).unwrap(),
+        large
+    );
+    let response = send("large", Some("br")).await.unwrap();
+    assert_eq!(content_encoding(&response).as_deref(), Some("br"));
+
+    // 客户端不接受压缩、响应小于 min_size 或为事件流时不压缩
+    let response = send("large", None).await.unwrap();
+    assert_eq!(content_encoding(&response), None);
+    let response = send("small", Some("gzip")).await.unwrap();
+    assert_eq!(content_encoding(&response), None);
+    let response = send("stream", Some("gzip")).await.unwrap();
+    assert_eq!(content_encoding(&response), None);
+
+    // 上游已压缩的响应原样转发
+    let response = send("encoded", Some("gzip, br")).await.unwrap();
+    assert_eq!(content_encoding(&response).as_deref(), Some("gzip"));
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert_eq!(body.as_ref(), vec![0x1f; 2048].as_slice());
+
+    Ok(())
+}
+
+/// 测试访问日志
+#[tokio::test]
+async fn test_forward_server_access_log() -> Result<(), AppError> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({"object": "list"})),
+        )
+        .mount(&mock_server)
+        .await;
+    let dir = tempfile::tempdir().unwrap();
+
+    // 按格式创建开启访问日志的转发服务，返回日志文件路径和应用
+    let app = |format: &'static str| {
+        let file = dir.path().join(format!("{}.log", format));
+        let mock_server = &mock_server;
+        async move {
+            let config: AccessLogConfig = serde_yaml::from_str(&format!(
+                "{{format: {}, file: {:?}}}",
+                format,
+                file.to_str().unwrap()
+            ))
+            .unwrap();
+            let name = format!("access_log_{}", format);
+            let server = embeddings_server(mock_server, &name, |c| {
+                c.routing = Some(
+                    serde_yaml::from_str(&format!(
+                        "[{{path: /v1/embeddings, target_group: {}_group}}]",
+                        name
+                    ))
+                    .unwrap(),
+                );
+                c.access_log = Some(config);
+            })
+            .await;
+            let state = server.get_state().clone();
+            let log = state.access_log.clone().unwrap();
+            let app = axum::Router::new()
+                .route("/{*path}", axum::routing::any(forward_handler))
+                .with_state(state)
+                .layer(axum::middleware::from_fn_with_state(log, record_access));
+            (file, app)
+        }
+    };
+    // 等待后台线程写入访问日志
+    let read_lines = |file: &std::path::Path, count: usize| {
+        let file = file.to_path_buf();
+        async move {
+            for _ in 0..100 {
+                let text = std::fs::read_to_string(&file).unwrap_or_default();
+                if text.lines().count() >= count {
+                    return text.lines().map(str::to_string).collect::<Vec<_>>();
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            panic!("access log {:?} has fewer than {} lines", file, count);
+        }
+    };
+
+    // JSON 格式记录请求 ID、路由、上游组、上游、状态码和响应体字节数
+    let (file, app_json) = app("json").await;
+    let mut request = embeddings_request("hello", "a");
+    request
+        .headers_mut()
+        .insert("x-request-id", "req-access-1".parse().unwrap());
+    request
+        .headers_mut()
+        .insert("user-agent", "access-test/1.0".parse().unwrap());
+    let response = app_json.clone().oneshot(request).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let lines = read_lines(&file, 1).await;
+    let record: AccessRecord = serde_json::from_str(&lines[0]).unwrap();
+    assert_eq!(record.forward, "access_log_json_forward");
+    assert_eq!(record.request_id.as_deref(), Some("req-access-1"));
+    assert_eq!(record.method, "POST");
+    assert_eq!(record.path, "/v1/embeddings");
+    assert_eq!(record.protocol, "HTTP/1.1");
+    assert_eq!(record.route.as_deref(), Some("/v1/embeddings"));
+    assert_eq!(record.group.as_deref(), Some("access_log_json_group"));
+    assert_eq!(record.upstream.as_deref(), Some("access_log_json_upstream"));
+    assert_eq!(record.status, 200);
+    assert_eq!(record.bytes, body.len() as u64);
+    assert_eq!(record.user_agent.as_deref(), Some("access-test/1.0"));
+    assert!(record.ttfb_ms <= record.duration_ms);
+
+    // 未携带请求 ID 时记录自动生成的请求 ID，不匹配路由的请求没有路由
+    let mut request = embeddings_request("hello", "a");
+    *request.uri_mut() = "/v1/other".parse().unwrap();
+    app_json.oneshot(request).await.unwrap();
+    let lines = read_lines(&file, 2).await;
+    let record: AccessRecord = serde_json::from_str(&lines[1]).unwrap();
+    assert!(record.request_id.is_some_and(|id| !id.is_empty()));
+    assert_eq!(record.route, None);
+    assert_eq!(record.group.as_deref(), Some("access_log_json_group"));
+
+    // combined 格式
+    let (file, app_combined) = app("combined").await;
+    let response = app_combined
+        .oneshot(embeddings_request("hello", "a"))
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let lines = read_lines(&file, 1).await;
+    assert!(
+        lines[0].starts_with("- - - ["),
+        "unexpected combined line: {}",
+        lines[0]
+    );
+    assert!(lines[0].contains(&format!(
+        "] \"POST /v1/embeddings HTTP/1.1\" 200 {} \"-\" \"-\" request_id=\"",
+        body.len()
+    )));
+    assert!(lines[0].contains(
+        " route=\"/v1/embeddings\" group=\"access_log_combined_group\" upstream=\"access_log_combined_upstream\" duration_ms="
+    ));
+
+    Ok(())
+}
+
+/// 编译测试用的 WASM 插件，hooks 为各阶段函数固定返回的 JSON（None 表示不做修改），
+/// "echo" 返回插件收到的输入，"loop" 进入死循环
+fn plugin_wasm(hooks: &[(&str, Option<&str>)]) -> Vec<u8> {
+    let mut wat = String::from(
+        r#"(module
+  (import "llmproxy" "log" (func $log (param i32 i32 i32)))
+  (memory (export "memory") 1)
+  (global $next (mut i32) (i32.const 32768))
+  (func (export "alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $next))
+    (global.set $next (i32.add (global.get $next) (local.get $len)))
+    (local.get $ptr))
+  (data (i32.const 0) "plugin called")
+"#,
+    );
+    let mut offset = 1024;
+    for (stage, output) in hooks {
+        let result = match output {
+            Some("loop") => "(loop $spin (br $spin)) (i64.const 0)".to_string(),
+            Some("echo") => "(i64.or (i64.shl (i64.extend_i32_u (local.get 0)) (i64.const 32)) (i64.extend_i32_u (local.get 1)))".to_string(),
+            Some(output) => {
+                wat.push_str(&format!(
+                    "  (data (i32.const {}) \"{}\")\n",
+                    offset,
+                    output.replace('"', "\\\"")
+                ));
+                let result = format!("(i64.const {})", ((offset as u64) << 32) | output.len() as u64);
+                offset += 1024;
+                result
+            }
+            None => "(i64.const 0)".to_string(),
+        };
+        wat.push_str(&format!(
+            "  (func (export \"{}\") (param i32 i32) (result i64)\n    (call $log (i32.const 1) (i32.const 0) (i32.const 13))\n    {})\n",
+            stage, result
+        ));
+    }
+    wat.push(')');
+    wat::parse_str(&wat).unwrap()
+}
+
+/// 创建插件配置，插件写入临时目录
+fn plugin_config(dir: &tempfile::TempDir, name: &str, wasm: Vec<u8>, extra: &str) -> PluginConfig {
+    let path = dir.path().join(format!("{}.wasm", name));
+    std::fs::write(&path, wasm).unwrap();
+    serde_yaml::from_str(&format!(
+        "name: {}\npath: {}\n{}",
+        name,
+        path.display(),
+        extra
+    ))
+    .unwrap()
+}
+
+/// 测试 WASM 插件在各阶段修改请求和响应
+#[tokio::test]
+async fn test_forward_server_plugins() -> Result<(), AppError> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .and(header("x-plugin-request", "tagged"))
+        .and(header("x-plugin-group", "plugins_group"))
+        .and(body_json(
+            serde_json::json!({"model": "embed", "input": "rewritten"}),
+        ))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({"object": "list", "data": []})),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let plugins = vec![
+        plugin_config(
+            &dir,
+            "rewrite",
+            plugin_wasm(&[
+                (
+                    "on_request",
+                    Some(
+                        r#"{"headers": {"x-plugin-request": "tagged"}, "body": {"model": "embed", "input": "rewritten"}}"#,
+                    ),
+                ),
+                (
+                    "pre_upstream",
+                    Some(r#"{"headers": {"x-plugin-group": "plugins_group"}}"#),
+                ),
+                (
+                    "on_response",
+                    Some(r#"{"status": 201, "headers": {"x-plugin-response": "done"}}"#),
+                ),
+            ]),
+            "",
+        ),
+        // 返回收到的输入，请求头和请求体保持不变
+        plugin_config(
+            &dir,
+            "echo",
+            plugin_wasm(&[("on_request", Some("echo"))]),
+            "",
+        ),
+        plugin_config(&dir, "noop", plugin_wasm(&[("pre_upstream", None)]), ""),
+    ];
+    let app = embeddings_app(&mock_server, "plugins", |c| c.plugins = plugins).await;
+
+    let response = app.oneshot(embeddings_request("hello", "a")).await.unwrap();
+    assert_eq!(response.status(), 201);
+    assert_eq!(response.headers()["x-plugin-response"], "done");
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert_eq!(
+        serde_json::from_slice::<serde_json::Value>(&body).unwrap()["object"],
+        "list"
+    );
+
+    for (plugin, stage, result) in [
+        ("rewrite", "on_request", "modify"),
+        ("rewrite", "pre_upstream", "modify"),
+        ("rewrite", "on_response", "modify"),
+        ("echo", "on_request", "modify"),
+        ("noop", "pre_upstream", "continue"),
+    ] {
+        assert_eq!(
+            METRICS
+                .plugin_calls_total()
+                .with_label_values(&["plugins_forward", plugin, stage, result])
+                .get(),
+            1,
+            "{} {}",
+            plugin,
+            stage
+        );
+    }
+
+    Ok(())
+}
+
+/// 测试插件拒绝请求和执行出错时按 fail_mode 处理
+#[tokio::test]
+async fn test_forward_server_plugin_reject() -> Result<(), AppError> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({"object": "list", "data": []})),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    let dir = tempfile::tempdir().unwrap();
+
+    // 插件拒绝请求时以插件返回的状态码和响应体响应，不转发给上游
+    let reject = plugin_config(
+        &dir,
+        "reject",
+        plugin_wasm(&[(
+            "on_request",
+            Some(r#"{"action": "reject", "status": 418, "body": {"error": "blocked by plugin"}}"#),
+        )]),
+        "",
+    );
+    let app = embeddings_app(&mock_server, "plugin_reject", |c| c.plugins = vec![reject]).await;
+    let response = app.oneshot(embeddings_request("hello", "a")).await.unwrap();
+    assert_eq!(response.status(), 418);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert_eq!(
+        serde_json::from_slice::<serde_json::Value>(&body).unwrap()["error"],
+        "blocked by plugin"
+    );
+
+    // 死循环的插件耗尽燃料后调用失败，fail_mode 为 closed 时返回 500，为 open 时跳过插件
+    for (name, fail_mode, status) in [
+        ("plugin_closed", "closed", 500),
+        ("plugin_open", "open", 200),
+    ] {
+        let spin = plugin_config(
+            &dir,
+            name,
+            plugin_wasm(&[("pre_upstream", Some("loop"))]),
+            &format!("fuel: 10000\nfail_mode: {}", fail_mode),
+        );
+        let app = embeddings_app(&mock_server, name, |c| c.plugins = vec![spin]).await;
+        let response = app.oneshot(embeddings_request("hello", "a")).await.unwrap();
+        assert_eq!(response.status(), status);
+        assert_eq!(
+            METRICS
+                .plugin_calls_total()
+                .with_label_values(&[&format!("{}_forward", name), name, "pre_upstream", "error"])
+                .get(),
+            1
+        );
+    }
+
+    Ok(())
+}
+
+/// 测试插件缺少必需的导出时加载失败
+#[tokio::test]
+async fn test_plugin_chain_load_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let no_hooks = plugin_config(&dir, "no_hooks", plugin_wasm(&[]), "");
+    let invalid = plugin_config(&dir, "invalid", b"not wasm".to_vec(), "");
+    let missing = PluginConfig {
+        path: dir.path().join("missing.wasm").display().to_string(),
+        ..no_hooks.clone()
+    };
+
+    for (config, message) in [
+        (
+            no_hooks,
+            "no on_request, pre_upstream or on_response export",
+        ),
+        (invalid, "Invalid plugin 'invalid'"),
+        (missing, "Invalid plugin 'no_hooks'"),
+    ] {
+        let err = PluginChain::new("test", &[config]).err().unwrap();
+        assert!(err.to_string().contains(message), "{}", err);
+    }
+}
+
+/// 测试转发服务的存活和就绪探针：不经过路由、限流和指标，就绪状态反映默认上游组是否可用
+#[tokio::test]
+async fn test_forward_server_probes() {
+    let mock_server = MockServer::start().await;
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let server = embeddings_server(&mock_server, "probes", |c| {
+        c.port = port;
+        c.ratelimit = Some(RateLimitConfig {
+            per_second: 1,
+            burst: 1,
+            key: RateLimitKey::Ip,
+            header: None,
+            redis: None,
+        });
+    })
+    .await;
+    let state = server.get_state().clone();
+    spawn_forward_server(server, port).await;
+    let client = reqwest::Client::new();
+    let probe = |path: &'static str| {
+        let client = client.clone();
+        async move {
+            client
+                .get(format!("http://127.0.0.1:{}{}", port, path))
+                .send()
+                .await
+                .unwrap()
+                .status()
+        }
+    };
+
+    // 探针不受限流影响，也不转发到上游
+    for _ in 0..3 {
+        assert_eq!(probe("/__llmproxy/healthz").await, 200);
+        assert_eq!(probe("/__llmproxy/readyz").await, 200);
+    }
+    assert!(mock_server.received_requests().await.unwrap().is_empty());
+    assert_eq!(
+        METRICS
+            .http_requests_total()
+            .with_label_values(&["probes_forward", "GET", "2xx", ""])
+            .get(),
+        0
+    );
+
+    // 默认上游组没有可用的上游服务时未就绪，存活探针不受影响
+    assert!(state
+        .upstream_manager
+        .set_upstream_enabled("probes_upstream", false));
+    assert_eq!(probe("/__llmproxy/readyz").await, 503);
+    assert_eq!(probe("/__llmproxy/healthz").await, 200);
+    state
+        .upstream_manager
+        .set_upstream_enabled("probes_upstream", true);
+    assert_eq!(probe("/__llmproxy/readyz").await, 200);
+
+    // 转发服务禁用时未就绪
+    state.set_enabled(false);
+    assert_eq!(probe("/__llmproxy/readyz").await, 503);
+}
+
+/// 测试关闭时停止接受新连接，等待在途的流式响应发送完成
+#[tokio::test]
+async fn test_forward_server_drain() {
+    // 每 100ms 发送一个事件的流式上游
+    let upstream = axum::Router::new().route(
+        "/v1/embeddings",
+        axum::routing::post(|| async {
+            let events = futures_util::stream::unfold(0, |i| async move {
+                if i == 5 {
+                    return None;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                Some((Ok::<_, std::io::Error>(format!("data: {}\n\n", i)), i + 1))
+            });
+            (
+                [(axum::http::header::CONTENT_TYPE, "text/event-stream")],
+                axum::body::Body::from_stream(events),
+            )
+        }),
+    );
+    let upstream_listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let upstream_addr = upstream_listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(upstream_listener, upstream).await });
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let server = embeddings_server_at(&format!("http://{}", upstream_addr), "drain", |c| {
+        c.port = port;
+    })
+    .await;
+    let (trigger, triggered) = tokio::sync::oneshot::channel::<()>();
+    let toplevel = tokio::spawn(
+        Toplevel::new(move |s| async move {
+            s.start(SubsystemBuilder::new("forward", move |s| async move {
+                server.run(s).await
+            }));
+            s.start(SubsystemBuilder::new(
+                "trigger",
+                move |s: tokio_graceful_shutdown::SubsystemHandle| async move {
+                    let _ = triggered.await;
+                    s.request_shutdown();
+                    Ok::<(), AppError>(())
+                },
+            ));
+        })
+        .handle_shutdown_requests(Duration::from_secs(5)),
+    );
+    for _ in 0..100 {
+        if tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_ok()
+        {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let inflight = METRICS
+        .inflight_requests()
+        .with_label_values(&["drain_forward"]);
+    let response = reqwest::Client::new()
+        .post(format!("http://127.0.0.1:{}/v1/embeddings", port))
+        .json(&serde_json::json!({"model": "embed", "input": "hello"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(inflight.get(), 1);
+
+    // 关闭后不再接受新连接，转发服务等待在途请求完成后才结束
+    trigger.send(()).unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(tokio::net::TcpStream::connect(("127.0.0.1", port))
+        .await
+        .is_err());
+    assert!(!toplevel.is_finished());
+
+    // 在途的流式响应完整发送
+    let body = response.text().await.unwrap();
+    assert_eq!(
+        body,
+        "data: 0\n\ndata: 1\n\ndata: 2\n\ndata: 3\n\ndata: 4\n\n"
+    );
+    assert!(toplevel.await.unwrap().is_ok());
+    assert_eq!(inflight.get(), 0);
+}
+
+/// 测试重启时新旧转发服务重叠监听同一端口，旧服务退出后请求不中断
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_forward_server_handover() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({"object": "list"})),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut old = embeddings_server_at(&mock_server.uri(), "handover_old", |c| c.port = port).await;
+    let mut new = embeddings_server_at(&mock_server.uri(), "handover_new", |c| c.port = port).await;
+
+    // 旧服务运行时，新服务可以绑定同一端口
+    old.listen().unwrap();
+    let (trigger, triggered) = tokio::sync::oneshot::channel::<()>();
+    let toplevel = tokio::spawn(
+        Toplevel::new(move |s| async move {
+            s.start(SubsystemBuilder::new("forward", move |s| async move {
+                old.run(s).await
+            }));
+            s.start(SubsystemBuilder::new(
+                "trigger",
+                move |s: tokio_graceful_shutdown::SubsystemHandle| async move {
+                    let _ = triggered.await;
+                    s.request_shutdown();
+                    Ok::<(), AppError>(())
+                },
+            ));
+        })
+        .handle_shutdown_requests(Duration::from_secs(5)),
+    );
+    new.listen().unwrap();
+    spawn_forward_server(new, port).await;
+
+    // 旧服务退出后，请求由新服务处理
+    trigger.send(()).unwrap();
+    assert!(toplevel.await.unwrap().is_ok());
+    let client = reqwest::Client::new();
+    for _ in 0..5 {
+        let response = client
+            .post(format!("http://127.0.0.1:{}/v1/embeddings", port))
+            .json(&serde_json::json!({"model": "embed", "input": "hello"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+    }
+}
+
+/// 测试环境变量中的进程不是父进程时不通知其退出
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_restart_notify_parent_skips_unrelated_process() {
+    let mut child = std::process::Command::new("sleep")
+        .arg("5")
+        .spawn()
+        .unwrap();
+    std::env::set_var(r#const::restart::PARENT_ENV, child.id().to_string());
+    restart::notify_parent();
+    std::env::remove_var(r#const::restart::PARENT_ENV);
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(child.try_wait().unwrap().is_none());
+    assert!(!restart::is_restarting());
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
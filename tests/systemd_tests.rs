@@ -0,0 +1,79 @@
+#![cfg(target_os = "linux")]
+
+use llmproxy::{error::AppError, r#const::systemd as env, systemd};
+use std::{os::unix::net::UnixDatagram, time::Duration};
+use tempfile::tempdir;
+use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+
+// 接收一条 systemd 通知
+fn recv(socket: &UnixDatagram) -> String {
+    let mut buf = [0u8; 256];
+    let len = socket.recv(&mut buf).unwrap();
+    String::from_utf8_lossy(&buf[..len]).to_string()
+}
+
+/// 测试就绪、看门狗心跳和停止通知
+#[tokio::test(flavor = "multi_thread")]
+async fn test_systemd_notify() {
+    // 未设置 NOTIFY_SOCKET 时不发送通知
+    assert!(!systemd::notify(env::READY));
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("notify.sock");
+    let socket = UnixDatagram::bind(&path).unwrap();
+    socket
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    std::env::set_var(env::NOTIFY_SOCKET, &path);
+
+    // 看门狗间隔为超时时间的一半，WATCHDOG_PID 指定其他进程时不发送心跳
+    std::env::set_var(env::WATCHDOG_USEC, "200000");
+    std::env::set_var(env::WATCHDOG_PID, "1");
+    assert_eq!(systemd::watchdog_interval(), None);
+    std::env::set_var(env::WATCHDOG_PID, std::process::id().to_string());
+    assert_eq!(
+        systemd::watchdog_interval(),
+        Some(Duration::from_millis(100))
+    );
+
+    let (trigger, triggered) = tokio::sync::oneshot::channel::<()>();
+    let toplevel = tokio::spawn(
+        Toplevel::new(move |s| async move {
+            s.start(SubsystemBuilder::new("systemd_notify", systemd::run));
+            s.start(SubsystemBuilder::new(
+                "trigger",
+                move |s: SubsystemHandle| async move {
+                    let _ = triggered.await;
+                    s.request_shutdown();
+                    Ok::<(), AppError>(())
+                },
+            ));
+        })
+        .handle_shutdown_requests(Duration::from_secs(1)),
+    );
+
+    assert_eq!(
+        recv(&socket),
+        format!("READY=1\nMAINPID={}", std::process::id())
+    );
+    assert_eq!(recv(&socket), env::WATCHDOG);
+    assert_eq!(recv(&socket), env::WATCHDOG);
+
+    trigger.send(()).unwrap();
+    assert!(toplevel.await.unwrap().is_ok());
+    let mut last = recv(&socket);
+    while last == env::WATCHDOG {
+        last = recv(&socket);
+    }
+    assert_eq!(last, env::STOPPING);
+
+    std::env::remove_var(env::NOTIFY_SOCKET);
+    std::env::remove_var(env::WATCHDOG_USEC);
+    std::env::remove_var(env::WATCHDOG_PID);
+}
+
+/// 测试未使用套接字激活时没有可接管的监听器
+#[test]
+fn test_systemd_take_listener() {
+    assert!(systemd::take_listener("127.0.0.1:3000".parse().unwrap()).is_none());
+}
@@ -0,0 +1,106 @@
+use llmproxy::{
+    events::{AccessEvent, AccessFilter},
+    tail::{build_stream_url, format_access_event, SseFrame, SseParser},
+};
+use std::time::Duration;
+
+// 辅助函数：创建测试访问事件
+fn create_test_event(status: u16) -> AccessEvent {
+    let mut event = AccessEvent::new(
+        "chat",
+        "POST",
+        "/v1/chat/completions",
+        "openai",
+        status,
+        Duration::from_millis(123),
+    );
+    // 2023-11-14 22:13:20.456 UTC
+    event.timestamp = 1_700_000_000_456;
+    event
+}
+
+#[test]
+fn test_access_filter_matches() {
+    let event = create_test_event(503);
+
+    let filter = AccessFilter::parse([("forward", "chat"), ("status", "5xx")]).unwrap();
+    assert!(filter.matches(&event));
+
+    let filter = AccessFilter::parse([("method", "post"), ("path", "/v1/chat")]).unwrap();
+    assert!(filter.matches(&event));
+
+    let filter = AccessFilter::parse([("forward", "chat"), ("group", "anthropic")]).unwrap();
+    assert!(!filter.matches(&event));
+
+    let filter = AccessFilter::parse([("status", "503")]).unwrap();
+    assert!(filter.matches(&event));
+    assert!(!filter.matches(&create_test_event(200)));
+
+    // 空过滤器匹配所有事件
+    let filter = AccessFilter::parse(Vec::<(String, String)>::new()).unwrap();
+    assert!(filter.matches(&event));
+}
+
+#[test]
+fn test_access_filter_invalid() {
+    assert!(AccessFilter::parse([("status", "9xx")]).is_err());
+    assert!(AccessFilter::parse([("status", "ok")]).is_err());
+    assert!(AccessFilter::parse([("client", "127.0.0.1")]).is_err());
+}
+
+#[test]
+fn test_sse_parser_handles_split_frames() {
+    let mut parser = SseParser::default();
+
+    // 帧被拆分到多个数据块中
+    assert!(parser.feed(b"event: access\ndata: {\"a\"").is_empty());
+    let frames = parser.feed(b":1}\n\n: keep-alive\n\nevent: lagged\r\ndata: 3\r\n\r\n");
+
+    assert_eq!(
+        frames,
+        vec![
+            SseFrame {
+                event: Some("access".to_string()),
+                data: "{\"a\":1}".to_string(),
+            },
+            SseFrame {
+                event: Some("lagged".to_string()),
+                data: "3".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_format_access_event() {
+    let line = format_access_event(&create_test_event(200), false);
+    assert_eq!(
+        line,
+        "22:13:20.456Z [chat] POST /v1/chat/completions -> openai 200 123ms"
+    );
+
+    let line = format_access_event(&create_test_event(500).with_error("boom"), false);
+    assert!(line.ends_with("500 123ms boom"));
+
+    // 彩色输出包含 ANSI 转义序列
+    let line = format_access_event(&create_test_event(500), true);
+    assert!(line.contains("\x1b[31m500\x1b[0m"));
+}
+
+#[test]
+fn test_build_stream_url() {
+    let url = build_stream_url(
+        "http://localhost:9000",
+        &[
+            ("forward".to_string(), "chat".to_string()),
+            ("path".to_string(), "/v1/chat completions".to_string()),
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        url.as_str(),
+        "http://localhost:9000/api/v1/access-log/stream?forward=chat&path=%2Fv1%2Fchat+completions"
+    );
+
+    assert!(build_stream_url("not a url", &[]).is_err());
+}
@@ -0,0 +1,414 @@
+use axum::{routing::get, serve::Listener};
+use llmproxy::{
+    config::{
+        AdminTlsConfig, BalanceConfig, BalanceStrategy, HttpClientConfig, TlsConfig, TlsVersion,
+        UpstreamConfig, UpstreamGroupConfig, UpstreamRef,
+    },
+    error::AppError,
+    server::{create_tcp_listener, tls_acceptor, TlsListener},
+    upstream::{RequestContext, UpstreamManager},
+};
+use openssl::{
+    asn1::Asn1Time,
+    hash::MessageDigest,
+    pkey::{PKey, Private},
+    rsa::Rsa,
+    ssl::{SslAcceptor, SslMethod},
+    symm::Cipher,
+    x509::{extension::SubjectAlternativeName, X509NameBuilder, X509},
+};
+use reqwest::{header::HeaderMap, Method};
+use std::{
+    fs,
+    io::{Read, Write},
+    net::TcpListener,
+    path::Path,
+    sync::Arc,
+    thread,
+};
+
+// 生成自签名的客户端证书和私钥
+fn client_identity() -> (PKey<Private>, X509) {
+    self_signed("llmproxy-client", false)
+}
+
+// 生成自签名证书，服务端证书包含 localhost 的 SAN
+fn self_signed(common_name: &str, server: bool) -> (PKey<Private>, X509) {
+    let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", common_name).unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_issuer_name(&name).unwrap();
+    builder.set_pubkey(&key).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+        .unwrap();
+    if server {
+        let san = SubjectAlternativeName::new()
+            .dns("localhost")
+            .build(&builder.x509v3_context(None, None))
+            .unwrap();
+        builder.append_extension(san).unwrap();
+    }
+    builder.sign(&key, MessageDigest::sha256()).unwrap();
+
+    (key, builder.build())
+}
+
+// 启动返回固定响应的 HTTPS 服务，返回服务地址和服务端证书
+fn start_tls_server() -> (String, X509) {
+    let (key, cert) = self_signed("localhost", true);
+    let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls_server()).unwrap();
+    acceptor.set_private_key(&key).unwrap();
+    acceptor.set_certificate(&cert).unwrap();
+    let acceptor = Arc::new(acceptor.build());
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            // 握手失败（客户端拒绝证书）时忽略该连接
+            let Ok(mut stream) = acceptor.accept(stream) else {
+                continue;
+            };
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                match stream.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+            let _ = stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nOK");
+            let _ = stream.shutdown();
+        }
+    });
+
+    (format!("https://localhost:{}", port), cert)
+}
+
+// 使用指定 TLS 配置创建上游管理器
+async fn manager_with_tls(tls: TlsConfig) -> Result<UpstreamManager, AppError> {
+    manager_for_url("https://gateway.internal.example.com", tls).await
+}
+
+async fn manager_for_url(base_url: &str, tls: TlsConfig) -> Result<UpstreamManager, AppError> {
+    let upstream = UpstreamConfig {
+        name: "mtls_upstream".to_string(),
+        url: format!("{}/v1/chat", base_url).into(),
+        weight: 1,
+        http_client: HttpClientConfig::default(),
+        auth: None,
+        headers: vec![],
+        breaker: None,
+        adaptive: None,
+        hint: None,
+        enabled: true,
+        proxy: true,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect: None,
+        stream_normalize: None,
+        agent: None,
+        default_headers: Default::default(),
+        pricing: vec![],
+        dns: None,
+        warmup: None,
+    };
+
+    let group = UpstreamGroupConfig {
+        name: "mtls_group".to_string(),
+        upstreams: vec![UpstreamRef {
+            name: "mtls_upstream".to_string(),
+            weight: 1,
+        }],
+        balance: BalanceConfig {
+            strategy: BalanceStrategy::RoundRobin,
+        },
+        http_client: HttpClientConfig {
+            tls: Some(tls),
+            ..HttpClientConfig::default()
+        },
+        sticky: None,
+        discovery: None,
+    };
+
+    UpstreamManager::new(vec![upstream], vec![group]).await
+}
+
+// 写入证书和私钥文件并生成 TLS 配置
+fn tls_config(dir: &Path, cert: &X509, key_pem: &[u8], passphrase: Option<&str>) -> TlsConfig {
+    let cert_path = dir.join("client.crt");
+    let key_path = dir.join("client.key");
+    fs::write(&cert_path, cert.to_pem().unwrap()).unwrap();
+    fs::write(&key_path, key_pem).unwrap();
+
+    TlsConfig {
+        cert: Some(cert_path.to_str().unwrap().to_string()),
+        key: Some(key_path.to_str().unwrap().to_string()),
+        passphrase: passphrase.map(str::to_string),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_tls_client_identity_loaded() {
+    let dir = tempfile::tempdir().unwrap();
+    let (key, cert) = client_identity();
+
+    // 传统 RSA 格式的私钥会被转换为 PKCS#8
+    let rsa_pem = key.rsa().unwrap().private_key_to_pem().unwrap();
+    assert!(
+        manager_with_tls(tls_config(dir.path(), &cert, &rsa_pem, None))
+            .await
+            .is_ok()
+    );
+
+    let pkcs8_pem = key.private_key_to_pem_pkcs8().unwrap();
+    assert!(
+        manager_with_tls(tls_config(dir.path(), &cert, &pkcs8_pem, None))
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+async fn test_tls_encrypted_key_passphrase() {
+    let dir = tempfile::tempdir().unwrap();
+    let (key, cert) = client_identity();
+    let encrypted = key
+        .private_key_to_pem_pkcs8_passphrase(Cipher::aes_256_cbc(), b"key-passphrase")
+        .unwrap();
+
+    assert!(manager_with_tls(tls_config(
+        dir.path(),
+        &cert,
+        &encrypted,
+        Some("key-passphrase")
+    ))
+    .await
+    .is_ok());
+
+    // 密码错误时启动失败
+    let result = manager_with_tls(tls_config(dir.path(), &cert, &encrypted, Some("wrong"))).await;
+    assert!(matches!(result, Err(AppError::Config(msg)) if msg.contains("Invalid TLS client key")));
+}
+
+#[tokio::test]
+async fn test_tls_missing_certificate() {
+    let result = manager_with_tls(TlsConfig {
+        cert: Some("non-existent-client.crt".to_string()),
+        key: Some("non-existent-client.key".to_string()),
+        ..Default::default()
+    })
+    .await;
+    assert!(
+        matches!(result, Err(AppError::Config(msg)) if msg.contains("non-existent-client.crt"))
+    );
+}
+
+#[tokio::test]
+async fn test_tls_server_verification() {
+    let (base_url, server_cert) = start_tls_server();
+    let forward = |manager: UpstreamManager| async move {
+        manager
+            .forward_request(
+                "mtls_group",
+                "/",
+                &RequestContext::default(),
+                &Method::GET,
+                HeaderMap::new(),
+                None,
+            )
+            .await
+    };
+
+    // 默认校验上游证书，自签名证书被拒绝
+    let manager = manager_for_url(&base_url, TlsConfig::default())
+        .await
+        .unwrap();
+    assert!(forward(manager).await.is_err());
+
+    // 信任 ca_file 中的证书后校验通过
+    let dir = tempfile::tempdir().unwrap();
+    let ca_path = dir.path().join("ca.pem");
+    fs::write(&ca_path, server_cert.to_pem().unwrap()).unwrap();
+    let manager = manager_for_url(
+        &base_url,
+        TlsConfig {
+            ca_file: Some(ca_path.to_str().unwrap().to_string()),
+            min_version: Some(TlsVersion::Tls12),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(forward(manager).await.unwrap().status(), 200);
+
+    // 跳过证书校验
+    let manager = manager_for_url(
+        &base_url,
+        TlsConfig {
+            insecure_skip_verify: true,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(forward(manager).await.unwrap().status(), 200);
+}
+
+#[tokio::test]
+async fn test_tls_invalid_ca_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let ca_path = dir.path().join("ca.pem");
+    fs::write(&ca_path, "not a certificate").unwrap();
+
+    let result = manager_with_tls(TlsConfig {
+        ca_file: Some(ca_path.to_str().unwrap().to_string()),
+        ..Default::default()
+    })
+    .await;
+    assert!(matches!(result, Err(AppError::Config(msg)) if msg.contains("ca.pem")));
+}
+
+// 写入管理服务的证书和私钥文件，client_ca 为信任的客户端证书
+fn admin_tls_config(dir: &Path, client_ca: Option<&X509>) -> (AdminTlsConfig, X509) {
+    let (key, cert) = self_signed("localhost", true);
+    let cert_path = dir.join("admin.crt");
+    let key_path = dir.join("admin.key");
+    fs::write(&cert_path, cert.to_pem().unwrap()).unwrap();
+    fs::write(&key_path, key.private_key_to_pem_pkcs8().unwrap()).unwrap();
+    let client_ca_file = client_ca.map(|ca| {
+        let ca_path = dir.join("clients.pem");
+        fs::write(&ca_path, ca.to_pem().unwrap()).unwrap();
+        ca_path.to_str().unwrap().to_string()
+    });
+
+    let config = AdminTlsConfig {
+        cert: cert_path.to_str().unwrap().to_string(),
+        key: key_path.to_str().unwrap().to_string(),
+        passphrase: None,
+        client_ca_file,
+    };
+    (config, cert)
+}
+
+// 启动使用 HTTPS 的管理服务，返回健康检查地址
+async fn start_admin_tls(tls: &AdminTlsConfig) -> String {
+    let listener = create_tcp_listener("127.0.0.1:0".parse().unwrap(), 128).unwrap();
+    let listener = TlsListener::new(listener, tls_acceptor(tls).unwrap()).unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let app = axum::Router::new().route("/health", get(|| async { "OK" }));
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    format!("localhost:{}/health", port)
+}
+
+// 创建信任管理服务证书的客户端，可附带客户端证书
+fn admin_client(server_cert: &X509, identity: Option<(&PKey<Private>, &X509)>) -> reqwest::Client {
+    let mut builder = reqwest::Client::builder().add_root_certificate(
+        reqwest::Certificate::from_pem(&server_cert.to_pem().unwrap()).unwrap(),
+    );
+    if let Some((key, cert)) = identity {
+        builder = builder.identity(
+            reqwest::Identity::from_pkcs8_pem(
+                &cert.to_pem().unwrap(),
+                &key.private_key_to_pem_pkcs8().unwrap(),
+            )
+            .unwrap(),
+        );
+    }
+    builder.build().unwrap()
+}
+
+#[tokio::test]
+async fn test_admin_tls_server() {
+    let dir = tempfile::tempdir().unwrap();
+    let (tls, server_cert) = admin_tls_config(dir.path(), None);
+    let url = start_admin_tls(&tls).await;
+
+    // 信任服务端证书的客户端通过 HTTPS 访问
+    let response = admin_client(&server_cert, None)
+        .get(format!("https://{}", url))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.unwrap(), "OK");
+
+    // 不信任服务端证书或使用 HTTP 时请求失败
+    assert!(reqwest::get(format!("https://{}", url)).await.is_err());
+    assert!(reqwest::get(format!("http://{}", url)).await.is_err());
+}
+
+#[tokio::test]
+async fn test_admin_tls_client_certificate_required() {
+    let dir = tempfile::tempdir().unwrap();
+    let (client_key, client_cert) = client_identity();
+    let (tls, server_cert) = admin_tls_config(dir.path(), Some(&client_cert));
+    let url = format!("https://{}", start_admin_tls(&tls).await);
+
+    let response = admin_client(&server_cert, Some((&client_key, &client_cert)))
+        .get(&url)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    // 未提供证书或证书不是由信任的 CA 签发时握手失败
+    assert!(admin_client(&server_cert, None)
+        .get(&url)
+        .send()
+        .await
+        .is_err());
+    let (other_key, other_cert) = client_identity();
+    assert!(admin_client(&server_cert, Some((&other_key, &other_cert)))
+        .get(&url)
+        .send()
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_admin_tls_invalid_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let (tls, _) = admin_tls_config(dir.path(), None);
+
+    let result = tls_acceptor(&AdminTlsConfig {
+        cert: "non-existent-admin.crt".to_string(),
+        ..tls.clone()
+    });
+    assert!(matches!(result, Err(AppError::Config(msg)) if msg.contains("non-existent-admin.crt")));
+
+    // 私钥与证书不匹配
+    let (other_key, _) = client_identity();
+    let other_key_path = dir.path().join("other.key");
+    fs::write(
+        &other_key_path,
+        other_key.private_key_to_pem_pkcs8().unwrap(),
+    )
+    .unwrap();
+    let result = tls_acceptor(&AdminTlsConfig {
+        key: other_key_path.to_str().unwrap().to_string(),
+        ..tls.clone()
+    });
+    assert!(matches!(result, Err(AppError::Config(msg)) if msg.contains("does not match")));
+
+    let ca_path = dir.path().join("clients.pem");
+    fs::write(&ca_path, "not a certificate").unwrap();
+    let result = tls_acceptor(&AdminTlsConfig {
+        client_ca_file: Some(ca_path.to_str().unwrap().to_string()),
+        ..tls
+    });
+    assert!(matches!(result, Err(AppError::Config(msg)) if msg.contains("clients.pem")));
+}
@@ -0,0 +1,560 @@
+use llmproxy::{
+    config::{
+        BalanceConfig, BalanceStrategy, Dialect, HttpClientConfig, StreamNormalizeConfig,
+        UpstreamConfig, UpstreamGroupConfig, UpstreamRef,
+    },
+    metrics::METRICS,
+    upstream::{RequestContext, UpstreamManager},
+};
+use reqwest::{header::HeaderMap, Method};
+use serde_json::{json, Value};
+use wiremock::{
+    matchers::{body_json, header, method, path, query_param},
+    Mock, MockServer, ResponseTemplate,
+};
+
+// 创建使用指定格式的上游管理器
+async fn dialect_manager(url: String, dialect: Dialect) -> UpstreamManager {
+    build_manager(url, Some(dialect), None).await
+}
+
+// 创建指定格式和事件流规范化配置的上游管理器
+async fn build_manager(
+    url: String,
+    dialect: Option<Dialect>,
+    stream_normalize: Option<StreamNormalizeConfig>,
+) -> UpstreamManager {
+    let upstream = UpstreamConfig {
+        name: "dialect_upstream".to_string(),
+        url: url.into(),
+        weight: 1,
+        http_client: HttpClientConfig::default(),
+        auth: None,
+        headers: vec![],
+        breaker: None,
+        adaptive: None,
+        hint: None,
+        enabled: true,
+        proxy: true,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect,
+        stream_normalize,
+        agent: None,
+        default_headers: Default::default(),
+        pricing: vec![],
+        dns: None,
+        warmup: None,
+    };
+
+    let group = UpstreamGroupConfig {
+        name: "dialect_group".to_string(),
+        upstreams: vec![UpstreamRef {
+            name: "dialect_upstream".to_string(),
+            weight: 1,
+        }],
+        balance: BalanceConfig {
+            strategy: BalanceStrategy::RoundRobin,
+        },
+        http_client: HttpClientConfig::default(),
+        sticky: None,
+        discovery: None,
+    };
+
+    UpstreamManager::new(vec![upstream], vec![group])
+        .await
+        .unwrap()
+}
+
+async fn forward(manager: &UpstreamManager, body: Value) -> reqwest::Response {
+    manager
+        .forward_request(
+            "dialect_group",
+            "/",
+            &RequestContext::default(),
+            &Method::POST,
+            HeaderMap::new(),
+            Some(body.to_string().into()),
+        )
+        .await
+        .unwrap()
+}
+
+// 解析 OpenAI 事件流中的数据行
+fn stream_data(body: &str) -> Vec<String> {
+    body.lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .map(str::to_string)
+        .collect()
+}
+
+// 读取 token 用量计数
+fn tokens_total(model: &str, token_type: &str) -> u64 {
+    METRICS
+        .tokens_total()
+        .with_label_values(&["dialect_group", "dialect_upstream", model, token_type])
+        .get()
+}
+
+#[tokio::test]
+async fn test_translate_anthropic_chat() {
+    let upstream_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(header("anthropic-version", "2023-06-01"))
+        .and(body_json(json!({
+            "model": "claude-sonnet",
+            "system": "be brief",
+            "messages": [{"role": "user", "content": [{"type": "text", "text": "hi"}]}],
+            "max_tokens": 4096,
+            "temperature": 0.5,
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-sonnet",
+            "content": [{"type": "text", "text": "hello"}],
+            "stop_reason": "end_turn",
+            "usage": {"input_tokens": 5, "output_tokens": 2},
+        })))
+        .expect(1)
+        .mount(&upstream_server)
+        .await;
+
+    let manager = dialect_manager(
+        format!("{}/v1/messages", upstream_server.uri()),
+        Dialect::Anthropic,
+    )
+    .await;
+    let response = forward(
+        &manager,
+        json!({
+            "model": "claude-sonnet",
+            "messages": [
+                {"role": "system", "content": "be brief"},
+                {"role": "user", "content": "hi"},
+            ],
+            "temperature": 0.5,
+        }),
+    )
+    .await;
+
+    assert_eq!(response.status(), 200);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["object"], "chat.completion");
+    assert_eq!(body["choices"][0]["message"]["content"], "hello");
+    assert_eq!(body["choices"][0]["finish_reason"], "stop");
+    assert_eq!(body["usage"]["total_tokens"], 7);
+}
+
+#[tokio::test]
+async fn test_translate_anthropic_tool_calls() {
+    let upstream_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "msg_2",
+            "type": "message",
+            "model": "claude-sonnet",
+            "content": [{
+                "type": "tool_use",
+                "id": "toolu_1",
+                "name": "get_weather",
+                "input": {"city": "Paris"},
+            }],
+            "stop_reason": "tool_use",
+            "usage": {"input_tokens": 10, "output_tokens": 4},
+        })))
+        .mount(&upstream_server)
+        .await;
+
+    let manager = dialect_manager(upstream_server.uri(), Dialect::Anthropic).await;
+    let response = forward(
+        &manager,
+        json!({
+            "model": "claude-sonnet",
+            "messages": [{"role": "user", "content": "weather?"}],
+            "tools": [{"type": "function", "function": {"name": "get_weather"}}],
+        }),
+    )
+    .await;
+
+    let body: Value = response.json().await.unwrap();
+    let message = &body["choices"][0]["message"];
+    assert_eq!(message["content"], Value::Null);
+    assert_eq!(message["tool_calls"][0]["id"], "toolu_1");
+    assert_eq!(message["tool_calls"][0]["function"]["name"], "get_weather");
+    let arguments: Value = serde_json::from_str(
+        message["tool_calls"][0]["function"]["arguments"]
+            .as_str()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(arguments, json!({"city": "Paris"}));
+    assert_eq!(body["choices"][0]["finish_reason"], "tool_calls");
+}
+
+#[tokio::test]
+async fn test_translate_anthropic_stream() {
+    let upstream_server = MockServer::start().await;
+
+    let events = [
+        json!({"type": "message_start", "message": {"id": "msg_3", "model": "claude-sonnet", "usage": {"input_tokens": 3}}}),
+        json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}}),
+        json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Hel"}}),
+        json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "lo"}}),
+        json!({"type": "content_block_stop", "index": 0}),
+        json!({"type": "message_delta", "delta": {"stop_reason": "end_turn"}, "usage": {"output_tokens": 2}}),
+        json!({"type": "message_stop"}),
+    ];
+    let body: String = events
+        .iter()
+        .map(|event| {
+            format!(
+                "event: {}\ndata: {}\n\n",
+                event["type"].as_str().unwrap(),
+                event
+            )
+        })
+        .collect();
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .mount(&upstream_server)
+        .await;
+
+    let manager = dialect_manager(upstream_server.uri(), Dialect::Anthropic).await;
+    let response = forward(
+        &manager,
+        json!({
+            "model": "claude-sonnet",
+            "stream": true,
+            "messages": [{"role": "user", "content": "hi"}],
+        }),
+    )
+    .await;
+
+    let data = stream_data(&response.text().await.unwrap());
+    assert_eq!(data.last().unwrap(), "[DONE]");
+    let chunks: Vec<Value> = data[..data.len() - 1]
+        .iter()
+        .map(|data| serde_json::from_str(data).unwrap())
+        .collect();
+    let text: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(text, "Hello");
+    assert!(chunks
+        .iter()
+        .all(|chunk| chunk["object"] == "chat.completion.chunk" && chunk["id"] == "msg_3"));
+    let last = chunks.last().unwrap();
+    assert_eq!(last["choices"][0]["finish_reason"], "stop");
+    assert_eq!(last["usage"]["total_tokens"], 5);
+}
+
+#[tokio::test]
+async fn test_translate_gemini_chat() {
+    let upstream_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1beta/models/gemini-pro:generateContent"))
+        .and(body_json(json!({
+            "contents": [{"role": "user", "parts": [{"text": "hi"}]}],
+            "systemInstruction": {"parts": [{"text": "be brief"}]},
+            "generationConfig": {"maxOutputTokens": 64},
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "candidates": [{
+                "content": {"role": "model", "parts": [{"text": "hello"}]},
+                "finishReason": "STOP",
+                "index": 0,
+            }],
+            "usageMetadata": {"promptTokenCount": 4, "candidatesTokenCount": 1},
+            "modelVersion": "gemini-pro",
+        })))
+        .expect(1)
+        .mount(&upstream_server)
+        .await;
+
+    let manager = dialect_manager(
+        format!("{}/v1beta/models", upstream_server.uri()),
+        Dialect::Gemini,
+    )
+    .await;
+    let response = forward(
+        &manager,
+        json!({
+            "model": "gemini-pro",
+            "max_tokens": 64,
+            "messages": [
+                {"role": "system", "content": "be brief"},
+                {"role": "user", "content": "hi"},
+            ],
+        }),
+    )
+    .await;
+
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["object"], "chat.completion");
+    assert_eq!(body["model"], "gemini-pro");
+    assert_eq!(body["choices"][0]["message"]["content"], "hello");
+    assert_eq!(body["choices"][0]["finish_reason"], "stop");
+    assert_eq!(body["usage"]["total_tokens"], 5);
+}
+
+#[tokio::test]
+async fn test_translate_gemini_stream() {
+    let upstream_server = MockServer::start().await;
+
+    let events = [
+        json!({"candidates": [{"content": {"role": "model", "parts": [{"text": "Hel"}]}, "index": 0}]}),
+        json!({"candidates": [{"content": {"role": "model", "parts": [{"text": "lo"}]}, "finishReason": "STOP", "index": 0}],
+               "usageMetadata": {"promptTokenCount": 2, "candidatesTokenCount": 2}}),
+    ];
+    let body: String = events
+        .iter()
+        .map(|event| format!("data: {}\r\n\r\n", event))
+        .collect();
+    Mock::given(method("POST"))
+        .and(path("/v1beta/models/gemini-pro:streamGenerateContent"))
+        .and(query_param("alt", "sse"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .expect(1)
+        .mount(&upstream_server)
+        .await;
+
+    let manager = dialect_manager(
+        format!("{}/v1beta/models/", upstream_server.uri()),
+        Dialect::Gemini,
+    )
+    .await;
+    let response = forward(
+        &manager,
+        json!({
+            "model": "gemini-pro",
+            "stream": true,
+            "messages": [{"role": "user", "content": "hi"}],
+        }),
+    )
+    .await;
+
+    let data = stream_data(&response.text().await.unwrap());
+    assert_eq!(data.len(), 3);
+    assert_eq!(data[2], "[DONE]");
+    let first: Value = serde_json::from_str(&data[0]).unwrap();
+    assert_eq!(first["choices"][0]["delta"]["role"], "assistant");
+    assert_eq!(first["choices"][0]["delta"]["content"], "Hel");
+    let last: Value = serde_json::from_str(&data[1]).unwrap();
+    assert_eq!(last["choices"][0]["delta"]["content"], "lo");
+    assert_eq!(last["choices"][0]["finish_reason"], "stop");
+    assert_eq!(last["usage"]["total_tokens"], 4);
+}
+
+#[tokio::test]
+async fn test_translate_passthrough() {
+    let upstream_server = MockServer::start().await;
+
+    // 不是聊天请求时请求体和响应体原样转发
+    let request = json!({"model": "claude-sonnet", "input": "embed me"});
+    Mock::given(method("POST"))
+        .and(body_json(request.clone()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"data": []})))
+        .expect(1)
+        .mount(&upstream_server)
+        .await;
+
+    let manager = dialect_manager(upstream_server.uri(), Dialect::Anthropic).await;
+    let response = forward(&manager, request).await;
+
+    assert_eq!(response.json::<Value>().await.unwrap(), json!({"data": []}));
+}
+
+#[tokio::test]
+async fn test_translate_anthropic_error() {
+    let upstream_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "type": "error",
+            "error": {"type": "invalid_request_error", "message": "bad request"},
+        })))
+        .mount(&upstream_server)
+        .await;
+
+    let manager = dialect_manager(upstream_server.uri(), Dialect::Anthropic).await;
+    let response = forward(
+        &manager,
+        json!({"model": "claude-sonnet", "messages": [{"role": "user", "content": "hi"}]}),
+    )
+    .await;
+
+    assert_eq!(response.status(), 400);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["error"]["message"], "bad request");
+    assert_eq!(body["error"]["type"], "invalid_request_error");
+}
+
+#[tokio::test]
+async fn test_usage_metrics_json() {
+    let upstream_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "chatcmpl-usage",
+            "object": "chat.completion",
+            "model": "usage-json-model",
+            "choices": [{"index": 0, "message": {"role": "assistant", "content": "ok"}, "finish_reason": "stop"}],
+            "usage": {"prompt_tokens": 11, "completion_tokens": 4, "total_tokens": 15},
+        })))
+        .mount(&upstream_server)
+        .await;
+
+    let manager = dialect_manager(upstream_server.uri(), Dialect::OpenAI).await;
+    let response = forward(
+        &manager,
+        json!({"model": "usage-json-model", "messages": [{"role": "user", "content": "hi"}]}),
+    )
+    .await;
+
+    // 响应体原样返回
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["id"], "chatcmpl-usage");
+    assert_eq!(tokens_total("usage-json-model", "prompt"), 11);
+    assert_eq!(tokens_total("usage-json-model", "completion"), 4);
+}
+
+#[tokio::test]
+async fn test_usage_metrics_stream() {
+    let upstream_server = MockServer::start().await;
+
+    let chunks = [
+        json!({"object": "chat.completion.chunk", "model": "usage-stream-model", "choices": [{"index": 0, "delta": {"content": "Hel"}}], "usage": null}),
+        json!({"object": "chat.completion.chunk", "model": "usage-stream-model", "choices": [{"index": 0, "delta": {"content": "lo"}, "finish_reason": "stop"}], "usage": null}),
+        json!({"object": "chat.completion.chunk", "model": "usage-stream-model", "choices": [], "usage": {"prompt_tokens": 7, "completion_tokens": 2, "total_tokens": 9}}),
+    ];
+    let mut body: String = chunks
+        .iter()
+        .map(|chunk| format!("data: {}\n\n", chunk))
+        .collect();
+    body.push_str("data: [DONE]\n\n");
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body.clone(), "text/event-stream"))
+        .mount(&upstream_server)
+        .await;
+
+    let manager = dialect_manager(upstream_server.uri(), Dialect::OpenAI).await;
+    let response = forward(
+        &manager,
+        json!({
+            "model": "usage-stream-model",
+            "stream": true,
+            "stream_options": {"include_usage": true},
+            "messages": [{"role": "user", "content": "hi"}],
+        }),
+    )
+    .await;
+
+    // 事件流原样转发，用量在流结束后记录
+    assert_eq!(response.text().await.unwrap(), body);
+    assert_eq!(tokens_total("usage-stream-model", "prompt"), 7);
+    assert_eq!(tokens_total("usage-stream-model", "completion"), 2);
+}
+
+#[tokio::test]
+async fn test_usage_metrics_translated_stream() {
+    let upstream_server = MockServer::start().await;
+
+    let events = [
+        json!({"type": "message_start", "message": {"id": "msg_u", "model": "usage-claude", "usage": {"input_tokens": 13}}}),
+        json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "ok"}}),
+        json!({"type": "message_delta", "delta": {"stop_reason": "end_turn"}, "usage": {"output_tokens": 6}}),
+        json!({"type": "message_stop"}),
+    ];
+    let body: String = events
+        .iter()
+        .map(|event| format!("data: {}\n\n", event))
+        .collect();
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .mount(&upstream_server)
+        .await;
+
+    let manager = dialect_manager(upstream_server.uri(), Dialect::Anthropic).await;
+    let response = forward(
+        &manager,
+        json!({
+            "model": "usage-claude",
+            "stream": true,
+            "messages": [{"role": "user", "content": "hi"}],
+        }),
+    )
+    .await;
+
+    response.text().await.unwrap();
+    assert_eq!(tokens_total("usage-claude", "prompt"), 13);
+    assert_eq!(tokens_total("usage-claude", "completion"), 6);
+}
+
+#[tokio::test]
+async fn test_normalize_stream() {
+    let upstream_server = MockServer::start().await;
+
+    let chunks = [
+        json!({"id": "c1", "object": "chat.completion.chunk", "created": 1, "model": "normalize-model", "provider": "p", "choices": [{"index": 0, "delta": {"content": "Hel"}}], "usage": null}),
+        json!({"id": "c1", "object": "chat.completion.chunk", "created": 1, "model": "normalize-model", "provider": "p", "choices": [{"index": 0, "delta": {"content": "lo"}, "finish_reason": "stop"}], "usage": {"prompt_tokens": 4, "completion_tokens": 2, "total_tokens": 6}}),
+    ];
+    // 上游发送注释、无法解析的事件，并且没有结束标记
+    let body = format!(
+        ": processing\n\ndata: {}\n\ndata: not json\n\nevent: chunk\r\ndata: {}\r\n\r\n",
+        chunks[0], chunks[1]
+    );
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .mount(&upstream_server)
+        .await;
+
+    let manager = build_manager(
+        upstream_server.uri(),
+        None,
+        Some(StreamNormalizeConfig {
+            strip_fields: vec!["provider".to_string()],
+            aggregate_usage: true,
+        }),
+    )
+    .await;
+    let response = forward(
+        &manager,
+        json!({
+            "model": "normalize-model",
+            "stream": true,
+            "messages": [{"role": "user", "content": "hi"}],
+        }),
+    )
+    .await;
+
+    let body = response.text().await.unwrap();
+    assert!(body
+        .lines()
+        .all(|line| line.is_empty() || line.starts_with("data: ")));
+    let data = stream_data(&body);
+    assert_eq!(data.len(), 4);
+    assert_eq!(data[3], "[DONE]");
+    let chunks: Vec<Value> = data[..3]
+        .iter()
+        .map(|data| serde_json::from_str(data).unwrap())
+        .collect();
+    assert!(chunks[..2]
+        .iter()
+        .all(|chunk| chunk.get("provider").is_none() && chunk.get("usage").is_none()));
+    assert_eq!(chunks[1]["choices"][0]["finish_reason"], "stop");
+    // 用量在结束标记之前单独发送
+    assert_eq!(chunks[2]["id"], "c1");
+    assert_eq!(chunks[2]["model"], "normalize-model");
+    assert_eq!(chunks[2]["choices"], json!([]));
+    assert_eq!(chunks[2]["usage"]["total_tokens"], 6);
+    assert_eq!(tokens_total("normalize-model", "prompt"), 4);
+    assert_eq!(tokens_total("normalize-model", "completion"), 2);
+}
@@ -0,0 +1,211 @@
+use axum::{
+    http::{HeaderMap as AxumHeaderMap, Uri},
+    routing::post,
+    Router,
+};
+use llmproxy::{
+    config::{
+        AuthConfig, AuthType, BalanceConfig, BalanceStrategy, BreakerConfig, HttpClientConfig,
+        UpstreamConfig, UpstreamGroupConfig, UpstreamRef,
+    },
+    error::AppError,
+    upstream::{RequestContext, UpstreamManager},
+};
+use reqwest::{header::HeaderMap, Method};
+use std::path::Path;
+use tokio::net::UnixListener;
+use validator::Validate;
+
+// 创建使用 Unix 域套接字上游的管理器
+async fn unix_manager(url: String, breaker: Option<BreakerConfig>) -> UpstreamManager {
+    let upstream = UpstreamConfig {
+        name: "uds_upstream".to_string(),
+        url: url.into(),
+        weight: 1,
+        http_client: HttpClientConfig::default(),
+        auth: Some(AuthConfig {
+            r#type: AuthType::Bearer,
+            token: Some("uds-token".to_string()),
+            token_file: None,
+            username: None,
+            password: None,
+            password_file: None,
+            oauth2: None,
+            external: None,
+        }),
+        headers: vec![],
+        breaker,
+        adaptive: None,
+        hint: None,
+        enabled: true,
+        proxy: true,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect: None,
+        stream_normalize: None,
+        agent: None,
+        default_headers: Default::default(),
+        pricing: vec![],
+        dns: None,
+        warmup: None,
+    };
+
+    let group = UpstreamGroupConfig {
+        name: "uds_group".to_string(),
+        upstreams: vec![UpstreamRef {
+            name: "uds_upstream".to_string(),
+            weight: 1,
+        }],
+        balance: BalanceConfig {
+            strategy: BalanceStrategy::RoundRobin,
+        },
+        http_client: HttpClientConfig::default(),
+        sticky: None,
+        discovery: None,
+    };
+
+    UpstreamManager::new(vec![upstream], vec![group])
+        .await
+        .unwrap()
+}
+
+// 在 Unix 域套接字上启动返回请求信息的服务
+fn start_unix_server(socket: &Path) {
+    let app = Router::new().route(
+        "/v1/chat/completions",
+        post(
+            |uri: Uri, headers: AxumHeaderMap, body: String| async move {
+                format!(
+                    "{} {} {} {}",
+                    uri,
+                    headers["host"].to_str().unwrap(),
+                    headers["authorization"].to_str().unwrap(),
+                    body
+                )
+            },
+        ),
+    );
+    let listener = UnixListener::bind(socket).unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+}
+
+#[tokio::test]
+async fn test_unix_socket_upstream_forward() {
+    let dir = tempfile::tempdir().unwrap();
+    let socket = dir.path().join("vllm.sock");
+    start_unix_server(&socket);
+
+    let manager = unix_manager(
+        format!(
+            "unix://{}:/v1/chat/completions?stream=false",
+            socket.display()
+        ),
+        None,
+    )
+    .await;
+
+    let response = manager
+        .forward_request(
+            "uds_group",
+            "/",
+            &RequestContext::default(),
+            &Method::POST,
+            HeaderMap::new(),
+            Some("hello".into()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    // HTTP 路径和查询参数、认证信息及请求体都会发送到套接字
+    assert_eq!(
+        response.text().await.unwrap(),
+        "/v1/chat/completions?stream=false localhost Bearer uds-token hello"
+    );
+}
+
+#[tokio::test]
+async fn test_unix_socket_connect_failure() {
+    let dir = tempfile::tempdir().unwrap();
+    let socket = dir.path().join("missing.sock");
+
+    let manager = unix_manager(
+        format!("unix://{}:/v1/chat/completions", socket.display()),
+        Some(BreakerConfig {
+            threshold: 1.0, // 失败率阈值很高，只有连接失败阈值能快速触发熔断
+            cooldown: 60,
+            connect_failures: 2,
+            window: None,
+            min_requests: None,
+            half_open_probes: None,
+        }),
+    )
+    .await;
+
+    for _ in 0..2 {
+        let err = manager
+            .forward_request(
+                "uds_group",
+                "/",
+                &RequestContext::default(),
+                &Method::POST,
+                HeaderMap::new(),
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("missing.sock"));
+    }
+
+    // 连接失败计入熔断器的连接失败次数
+    let result = manager
+        .forward_request(
+            "uds_group",
+            "/",
+            &RequestContext::default(),
+            &Method::POST,
+            HeaderMap::new(),
+            None,
+        )
+        .await;
+    assert!(matches!(result, Err(AppError::NoHealthyUpstreamAvailable)));
+}
+
+#[test]
+fn test_unix_socket_url_validation() {
+    let upstream = |url: &str| UpstreamConfig {
+        name: "uds_upstream".to_string(),
+        url: url.to_string().into(),
+        weight: 1,
+        http_client: HttpClientConfig::default(),
+        auth: None,
+        headers: vec![],
+        breaker: None,
+        adaptive: None,
+        hint: None,
+        enabled: true,
+        proxy: true,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect: None,
+        stream_normalize: None,
+        agent: None,
+        default_headers: Default::default(),
+        pricing: vec![],
+        dns: None,
+        warmup: None,
+    };
+
+    assert!(upstream("unix:///var/run/vllm.sock").validate().is_ok());
+    assert!(upstream("unix:///var/run/vllm.sock:/v1/chat/completions")
+        .validate()
+        .is_ok());
+
+    // 缺少套接字路径或使用主机名
+    assert!(upstream("unix:///").validate().is_err());
+    assert!(upstream("unix://localhost/var/run/vllm.sock")
+        .validate()
+        .is_err());
+}
@@ -1,16 +1,26 @@
 use llmproxy::{
     config::{
-        BalanceConfig, BalanceStrategy, BreakerConfig, HeaderOp, HeaderOpType, HttpClientConfig,
-        UpstreamConfig, UpstreamGroupConfig, UpstreamRef,
+        BalanceConfig, BalanceStrategy, BodyTransformConfig, BreakerConfig, HeaderOp, HeaderOpType,
+        Http2Config, HttpClientConfig, HttpVersion, ModelPriceConfig, PathRewriteConfig,
+        QueryParamOp, RetryConfig, StickyConfig, SystemPromptConfig, SystemPromptMode,
+        UpstreamConfig, UpstreamGroupConfig, UpstreamRef, WarmupConfig,
     },
-    upstream::UpstreamManager,
+    error::AppError,
+    events::{SystemEvent, EVENTS},
+    metrics::METRICS,
+    upstream::{RequestContext, UpstreamManager, UpstreamWarmup},
+    usage::USAGE,
+};
+use reqwest::{header::HeaderName, Method, Version};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
 };
-use reqwest::Method;
-use std::time::Duration;
 use tokio::time::sleep;
+use tokio_graceful_shutdown::{IntoSubsystem, SubsystemBuilder, Toplevel};
 
 use wiremock::{
-    matchers::{method, path},
+    matchers::{body_json, body_string, header, method, path, query_param, query_param_is_missing},
     Mock, MockServer, ResponseTemplate,
 };
 
@@ -35,6 +45,20 @@ fn create_test_configs(
             parsed_value: None,
         }],
         breaker: None,
+        adaptive: None,
+        hint: None,
+        enabled: true,
+        proxy: true,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect: None,
+        stream_normalize: None,
+        agent: None,
+        default_headers: Default::default(),
+        pricing: vec![],
+        dns: None,
+        warmup: None,
     };
 
     let mut upstream2 = UpstreamConfig {
@@ -45,6 +69,20 @@ fn create_test_configs(
         auth: None,
         headers: vec![],
         breaker: None,
+        adaptive: None,
+        hint: None,
+        enabled: true,
+        proxy: true,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect: None,
+        stream_normalize: None,
+        agent: None,
+        default_headers: Default::default(),
+        pricing: vec![],
+        dns: None,
+        warmup: None,
     };
 
     // 如果需要添加熔断器配置
@@ -52,6 +90,10 @@ fn create_test_configs(
         let breaker_config = BreakerConfig {
             threshold: 0.5, // 50% 失败率阈值
             cooldown: 1,    // 1秒冷却时间
+            connect_failures: 3,
+            window: None,
+            min_requests: None,
+            half_open_probes: None,
         };
         upstream1.breaker = Some(breaker_config.clone());
         upstream2.breaker = Some(breaker_config);
@@ -74,6 +116,8 @@ fn create_test_configs(
             strategy: BalanceStrategy::RoundRobin,
         },
         http_client: HttpClientConfig::default(),
+        sticky: None,
+        discovery: None,
     };
 
     (vec![upstream1, upstream2], vec![group_config])
@@ -110,6 +154,8 @@ async fn test_upstream_manager_with_circuit_breaker() {
         let result = upstream_manager
             .forward_request(
                 "test_group",
+                "/",
+                &RequestContext::default(),
                 &Method::GET,
                 reqwest::header::HeaderMap::new(),
                 None,
@@ -130,6 +176,8 @@ async fn test_upstream_manager_with_circuit_breaker() {
     let _result = upstream_manager
         .forward_request(
             "test_group",
+            "/",
+            &RequestContext::default(),
             &Method::GET,
             reqwest::header::HeaderMap::new(),
             None,
@@ -146,6 +194,8 @@ async fn test_upstream_manager_with_circuit_breaker() {
         let result = upstream_manager
             .forward_request(
                 "test_group",
+                "/",
+                &RequestContext::default(),
                 &Method::GET,
                 reqwest::header::HeaderMap::new(),
                 None,
@@ -167,6 +217,8 @@ async fn test_upstream_manager_with_circuit_breaker() {
         let _ = upstream_manager
             .forward_request(
                 "test_group",
+                "/",
+                &RequestContext::default(),
                 &Method::GET,
                 reqwest::header::HeaderMap::new(),
                 None,
@@ -181,6 +233,8 @@ async fn test_upstream_manager_with_circuit_breaker() {
     let _result = upstream_manager
         .forward_request(
             "test_group",
+            "/",
+            &RequestContext::default(),
             &Method::GET,
             reqwest::header::HeaderMap::new(),
             None,
@@ -203,6 +257,8 @@ async fn test_upstream_manager_with_circuit_breaker() {
     let result = upstream_manager
         .forward_request(
             "test_group",
+            "/",
+            &RequestContext::default(),
             &Method::GET,
             reqwest::header::HeaderMap::new(),
             None,
@@ -247,6 +303,8 @@ async fn test_upstream_manager_without_circuit_breaker() {
         let result = upstream_manager
             .forward_request(
                 "test_group",
+                "/",
+                &RequestContext::default(),
                 &Method::GET,
                 reqwest::header::HeaderMap::new(),
                 None,
@@ -277,6 +335,20 @@ async fn test_upstream_manager_update_group_load_balancer() {
             auth: None,
             headers: vec![],
             breaker: None,
+            adaptive: None,
+            hint: None,
+            enabled: true,
+            proxy: true,
+            path: None,
+            query_params: vec![],
+            body_transform: None,
+            dialect: None,
+            stream_normalize: None,
+            agent: None,
+            default_headers: Default::default(),
+            pricing: vec![],
+            dns: None,
+            warmup: None,
         },
         UpstreamConfig {
             name: "upstream2".to_string(),
@@ -286,6 +358,20 @@ async fn test_upstream_manager_update_group_load_balancer() {
             auth: None,
             headers: vec![],
             breaker: None,
+            adaptive: None,
+            hint: None,
+            enabled: true,
+            proxy: true,
+            path: None,
+            query_params: vec![],
+            body_transform: None,
+            dialect: None,
+            stream_normalize: None,
+            agent: None,
+            default_headers: Default::default(),
+            pricing: vec![],
+            dns: None,
+            warmup: None,
         },
         UpstreamConfig {
             name: "upstream3".to_string(),
@@ -295,6 +381,20 @@ async fn test_upstream_manager_update_group_load_balancer() {
             auth: None,
             headers: vec![],
             breaker: None,
+            adaptive: None,
+            hint: None,
+            enabled: true,
+            proxy: true,
+            path: None,
+            query_params: vec![],
+            body_transform: None,
+            dialect: None,
+            stream_normalize: None,
+            agent: None,
+            default_headers: Default::default(),
+            pricing: vec![],
+            dns: None,
+            warmup: None,
         },
     ];
 
@@ -314,6 +414,8 @@ async fn test_upstream_manager_update_group_load_balancer() {
             strategy: BalanceStrategy::RoundRobin,
         },
         http_client: HttpClientConfig::default(),
+        sticky: None,
+        discovery: None,
     }];
 
     // 创建上游管理器
@@ -341,3 +443,1560 @@ async fn test_upstream_manager_update_group_load_balancer() {
     // assert_eq!(updated_group.upstreams.len(), 1);
     // assert_eq!(updated_group.upstreams[0].name, "upstream3");
 }
+
+#[tokio::test]
+async fn test_upstream_manager_connect_failures_trip_breaker() {
+    // 获取一个未被监听的本地端口，请求会在连接阶段失败
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let closed_url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let upstream = UpstreamConfig {
+        name: "unreachable_upstream".to_string(),
+        url: closed_url.into(),
+        weight: 1,
+        http_client: HttpClientConfig::default(),
+        auth: None,
+        headers: vec![],
+        breaker: Some(BreakerConfig {
+            threshold: 1.0, // 失败率阈值很高，只有连接失败阈值能快速触发熔断
+            cooldown: 60,
+            connect_failures: 2,
+            window: None,
+            min_requests: None,
+            half_open_probes: None,
+        }),
+        hint: None,
+        enabled: true,
+        proxy: true,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect: None,
+        stream_normalize: None,
+        agent: None,
+        default_headers: Default::default(),
+        adaptive: None,
+        pricing: vec![],
+        dns: None,
+        warmup: None,
+    };
+
+    let group = UpstreamGroupConfig {
+        name: "connect_group".to_string(),
+        upstreams: vec![UpstreamRef {
+            name: "unreachable_upstream".to_string(),
+            weight: 1,
+        }],
+        balance: BalanceConfig {
+            strategy: BalanceStrategy::RoundRobin,
+        },
+        http_client: HttpClientConfig::default(),
+        sticky: None,
+        discovery: None,
+    };
+
+    let upstream_manager = UpstreamManager::new(vec![upstream], vec![group])
+        .await
+        .unwrap();
+
+    // 连续两次连接失败
+    for _ in 0..2 {
+        let result = upstream_manager
+            .forward_request(
+                "connect_group",
+                "/",
+                &RequestContext::default(),
+                &Method::GET,
+                reqwest::header::HeaderMap::new(),
+                None,
+            )
+            .await;
+        assert!(matches!(result, Err(AppError::Upstream(_))));
+    }
+
+    // 熔断器已经打开，不再尝试连接上游
+    let result = upstream_manager
+        .forward_request(
+            "connect_group",
+            "/",
+            &RequestContext::default(),
+            &Method::GET,
+            reqwest::header::HeaderMap::new(),
+            None,
+        )
+        .await;
+    assert!(matches!(result, Err(AppError::NoHealthyUpstreamAvailable)));
+}
+
+#[tokio::test]
+async fn test_upstream_manager_sticky_session_follows_hint() {
+    // 启动两个模拟服务器，分别返回不同的区域提示
+    let mock_server1 = MockServer::start().await;
+    let mock_server2 = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/test"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("x-region", "eu")
+                .set_body_string("server1"),
+        )
+        .mount(&mock_server1)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/test"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("x-region", "us")
+                .set_body_string("server2"),
+        )
+        .mount(&mock_server2)
+        .await;
+
+    let (upstreams, mut groups) = create_test_configs(
+        &format!("{}/test", mock_server1.uri()),
+        &format!("{}/test", mock_server2.uri()),
+        false,
+    );
+    groups[0].sticky = Some(StickyConfig {
+        session_header: "x-session-id".to_string(),
+        hint_header: "x-region".to_string(),
+        ttl: 60,
+    });
+
+    let upstream_manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+
+    let mut session_headers = reqwest::header::HeaderMap::new();
+    session_headers.insert("x-session-id", "session-1".parse().unwrap());
+
+    // 第一次请求由负载均衡器选择上游，并记录返回的区域提示
+    let first_body = upstream_manager
+        .forward_request(
+            "test_group",
+            "/",
+            &RequestContext::default(),
+            &Method::GET,
+            session_headers.clone(),
+            None,
+        )
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+
+    // 同一会话的后续请求固定到返回该提示的上游
+    for _ in 0..5 {
+        let body = upstream_manager
+            .forward_request(
+                "test_group",
+                "/",
+                &RequestContext::default(),
+                &Method::GET,
+                session_headers.clone(),
+                None,
+            )
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(body, first_body);
+    }
+
+    // 没有会话头的请求仍然按轮询分发
+    let mut bodies = std::collections::HashSet::new();
+    for _ in 0..4 {
+        let body = upstream_manager
+            .forward_request(
+                "test_group",
+                "/",
+                &RequestContext::default(),
+                &Method::GET,
+                reqwest::header::HeaderMap::new(),
+                None,
+            )
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        bodies.insert(body);
+    }
+    assert_eq!(bodies.len(), 2);
+}
+
+// 辅助函数：创建带重试配置的单上游测试配置
+fn create_retry_configs(
+    mock_url: &str,
+    retry: RetryConfig,
+) -> (Vec<UpstreamConfig>, Vec<UpstreamGroupConfig>) {
+    let upstream = UpstreamConfig {
+        name: "retry_upstream".to_string(),
+        url: mock_url.to_string().into(),
+        weight: 1,
+        http_client: HttpClientConfig::default(),
+        auth: None,
+        headers: vec![],
+        breaker: None,
+        adaptive: None,
+        hint: None,
+        enabled: true,
+        proxy: true,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect: None,
+        stream_normalize: None,
+        agent: None,
+        default_headers: Default::default(),
+        pricing: vec![],
+        dns: None,
+        warmup: None,
+    };
+
+    let group = UpstreamGroupConfig {
+        name: "retry_group".to_string(),
+        upstreams: vec![UpstreamRef {
+            name: "retry_upstream".to_string(),
+            weight: 1,
+        }],
+        balance: BalanceConfig {
+            strategy: BalanceStrategy::RoundRobin,
+        },
+        http_client: HttpClientConfig {
+            retry: Some(retry),
+            ..HttpClientConfig::default()
+        },
+        sticky: None,
+        discovery: None,
+    };
+
+    (vec![upstream], vec![group])
+}
+
+#[tokio::test]
+async fn test_upstream_manager_retry_budget_stops_backoff() {
+    let mock_server = MockServer::start().await;
+
+    // 上游始终返回可重试的错误
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&mock_server)
+        .await;
+
+    // 不限制时间时，10 次指数退避重试需要等待数十秒
+    let (upstreams, groups) = create_retry_configs(
+        &mock_server.uri(),
+        RetryConfig {
+            attempts: 10,
+            initial: 1000,
+            max_elapsed_ms: Some(1500),
+            retry_on_status: vec![429, 500, 502, 503],
+            retry_non_idempotent: false,
+        },
+    );
+    let upstream_manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+
+    let start = Instant::now();
+    let response = upstream_manager
+        .forward_request(
+            "retry_group",
+            "/",
+            &RequestContext::default(),
+            &Method::GET,
+            reqwest::header::HeaderMap::new(),
+            None,
+        )
+        .await
+        .unwrap();
+
+    // 预算耗尽后返回最后一次的上游响应
+    assert_eq!(response.status().as_u16(), 503);
+    assert!(start.elapsed() < Duration::from_millis(1500));
+
+    // 首次请求之外最多只能进行一次重试（首次退避 1000ms，第二次退避已超出预算）
+    let requests = mock_server.received_requests().await.unwrap();
+    assert!(requests.len() <= 2);
+}
+
+#[tokio::test]
+async fn test_upstream_manager_retry_budget_caps_slow_upstream() {
+    let mock_server = MockServer::start().await;
+
+    // 上游响应很慢
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+        .mount(&mock_server)
+        .await;
+
+    let (upstreams, groups) = create_retry_configs(
+        &mock_server.uri(),
+        RetryConfig {
+            attempts: 3,
+            initial: 100,
+            max_elapsed_ms: Some(300),
+            retry_on_status: vec![429, 500, 502, 503],
+            retry_non_idempotent: false,
+        },
+    );
+    let upstream_manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+
+    let start = Instant::now();
+    let result = upstream_manager
+        .forward_request(
+            "retry_group",
+            "/",
+            &RequestContext::default(),
+            &Method::GET,
+            reqwest::header::HeaderMap::new(),
+            None,
+        )
+        .await;
+
+    match result {
+        Err(AppError::Upstream(message)) => assert!(message.contains("retry budget")),
+        other => panic!(
+            "Expected retry budget error, got {:?}",
+            other.map(|r| r.status())
+        ),
+    }
+    assert!(start.elapsed() < Duration::from_secs(2));
+}
+
+#[tokio::test]
+async fn test_upstream_manager_retry_rules() {
+    // 发送请求，返回上游收到的请求数
+    async fn attempts(
+        status: u16,
+        retry_non_idempotent: bool,
+        request_method: Method,
+        headers: reqwest::header::HeaderMap,
+    ) -> usize {
+        let mock_server = MockServer::start().await;
+        Mock::given(method(request_method.as_str()))
+            .respond_with(ResponseTemplate::new(status))
+            .mount(&mock_server)
+            .await;
+        let (upstreams, groups) = create_retry_configs(
+            &mock_server.uri(),
+            RetryConfig {
+                attempts: 2,
+                initial: 100,
+                max_elapsed_ms: None,
+                retry_on_status: vec![429, 503],
+                retry_non_idempotent,
+            },
+        );
+        let upstream_manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+        let body = (request_method == Method::POST).then(|| "{}".into());
+        let response = upstream_manager
+            .forward_request(
+                "retry_group",
+                "/",
+                &RequestContext::default(),
+                &request_method,
+                headers,
+                body,
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), status);
+
+        let requests = mock_server.received_requests().await.unwrap();
+        // 不重试的请求头不会转发给上游
+        assert!(requests
+            .iter()
+            .all(|request| !request.headers.contains_key("x-llmproxy-no-retry")));
+        requests.len()
+    }
+    let no_headers = reqwest::header::HeaderMap::new;
+
+    // 只重试配置的状态码
+    assert_eq!(attempts(503, false, Method::GET, no_headers()).await, 3);
+    assert_eq!(attempts(429, false, Method::GET, no_headers()).await, 3);
+    assert_eq!(attempts(502, false, Method::GET, no_headers()).await, 1);
+
+    // 非幂等请求默认不重试
+    assert_eq!(attempts(503, false, Method::POST, no_headers()).await, 1);
+    assert_eq!(attempts(503, true, Method::POST, no_headers()).await, 3);
+
+    // 请求可以通过请求头关闭重试
+    let mut headers = no_headers();
+    headers.insert("x-llmproxy-no-retry", "1".parse().unwrap());
+    assert_eq!(attempts(503, false, Method::GET, headers).await, 1);
+}
+
+#[tokio::test]
+async fn test_upstream_manager_retries_connect_errors_for_post() {
+    // 预留端口后关闭监听，首次请求连接被拒绝
+    let address = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let (upstreams, groups) = create_retry_configs(
+        &format!("http://{}", address),
+        RetryConfig {
+            attempts: 3,
+            initial: 200,
+            max_elapsed_ms: None,
+            retry_on_status: vec![429, 503],
+            retry_non_idempotent: false,
+        },
+    );
+    let upstream_manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+
+    // 首次重试之前上游开始监听
+    let server = tokio::spawn(async move {
+        sleep(Duration::from_millis(50)).await;
+        let mock_server = MockServer::builder()
+            .listener(std::net::TcpListener::bind(address).unwrap())
+            .start()
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+        mock_server
+    });
+
+    // 连接失败时请求尚未发送，非幂等请求同样重试
+    let response = upstream_manager
+        .forward_request(
+            "retry_group",
+            "/",
+            &RequestContext::default(),
+            &Method::POST,
+            reqwest::header::HeaderMap::new(),
+            Some("{}".into()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    let mock_server = server.await.unwrap();
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_upstream_manager_http_version() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    for (http_version, expected) in [
+        (HttpVersion::Auto, Version::HTTP_11),
+        (HttpVersion::Http1, Version::HTTP_11),
+        (HttpVersion::Http2PriorKnowledge, Version::HTTP_2),
+    ] {
+        let (upstreams, mut groups) = create_retry_configs(
+            &mock_server.uri(),
+            RetryConfig {
+                attempts: 1,
+                initial: 100,
+                max_elapsed_ms: None,
+                retry_on_status: vec![429, 500, 502, 503],
+                retry_non_idempotent: false,
+            },
+        );
+        groups[0].http_client = HttpClientConfig {
+            http_version,
+            http2: (http_version == HttpVersion::Http2PriorKnowledge).then(Http2Config::default),
+            ..HttpClientConfig::default()
+        };
+        let upstream_manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+
+        // 明文上游在自动模式下使用 HTTP/1.1，指定 prior knowledge 时直接使用 HTTP/2
+        let response = upstream_manager
+            .forward_request(
+                "retry_group",
+                "/",
+                &RequestContext::default(),
+                &Method::GET,
+                reqwest::header::HeaderMap::new(),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+        assert_eq!(response.version(), expected);
+    }
+}
+
+#[tokio::test]
+async fn test_upstream_manager_path_rewrite() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/openai/deployments/gpt-4o/chat/completions"))
+        .and(query_param("api-version", "2024-06-01"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    // 重写后的路径替换上游 URL 的路径，保留 URL 中的查询参数
+    let (mut upstreams, groups) = create_retry_configs(
+        &format!("{}/ignored?api-version=2024-06-01", mock_server.uri()),
+        RetryConfig {
+            attempts: 1,
+            initial: 100,
+            max_elapsed_ms: None,
+            retry_on_status: vec![429, 500, 502, 503],
+            retry_non_idempotent: false,
+        },
+    );
+    upstreams[0].path = Some(PathRewriteConfig {
+        strip_prefix: Some("/v1".to_string()),
+        template: Some("/openai/deployments/{deployment}{path}".to_string()),
+        params: [("deployment".to_string(), "gpt-4o".to_string())].into(),
+        ..Default::default()
+    });
+    let upstream_manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+
+    let response = upstream_manager
+        .forward_request(
+            "retry_group",
+            "/v1/chat/completions",
+            &RequestContext::default(),
+            &Method::POST,
+            reqwest::header::HeaderMap::new(),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn test_upstream_manager_query_params() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/chat"))
+        .and(query_param("api-version", "2024-02-01"))
+        .and(query_param("tenant", "default"))
+        .and(query_param_is_missing("debug"))
+        .and(query_param("region", "us"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let (mut upstreams, groups) = create_retry_configs(
+        &format!(
+            "{}/v1/chat?api-version=old&debug=1&region=us",
+            mock_server.uri()
+        ),
+        RetryConfig {
+            attempts: 1,
+            initial: 100,
+            max_elapsed_ms: None,
+            retry_on_status: vec![429, 500, 502, 503],
+            retry_non_idempotent: false,
+        },
+    );
+    let query_op = |op, key: &str, value: Option<&str>| QueryParamOp {
+        op,
+        key: key.to_string(),
+        value: value.map(str::to_string),
+    };
+    upstreams[0].query_params = vec![
+        query_op(HeaderOpType::Replace, "api-version", Some("2024-02-01")),
+        query_op(HeaderOpType::Insert, "tenant", Some("default")),
+        query_op(HeaderOpType::Remove, "debug", None),
+        // 参数已存在时 insert 不覆盖
+        query_op(HeaderOpType::Insert, "region", Some("eu")),
+    ];
+    let upstream_manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+
+    let response = upstream_manager
+        .forward_request(
+            "retry_group",
+            "/",
+            &RequestContext::default(),
+            &Method::GET,
+            reqwest::header::HeaderMap::new(),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn test_upstream_manager_header_templates() {
+    let mock_server = MockServer::start().await;
+
+    std::env::set_var("LLMPROXY_TEST_TENANT", "tenant-a");
+    Mock::given(method("GET"))
+        .and(header("x-client-ip", "203.0.113.7"))
+        .and(header("x-correlation-id", "llmproxy-req-42"))
+        .and(header("x-forward", "forward_a"))
+        .and(header("x-tenant", "tenant-a/"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let (mut upstreams, groups) = create_retry_configs(
+        &mock_server.uri(),
+        RetryConfig {
+            attempts: 1,
+            initial: 100,
+            max_elapsed_ms: None,
+            retry_on_status: vec![429, 500, 502, 503],
+            retry_non_idempotent: false,
+        },
+    );
+    let header_op = |key: &str, value: &str| HeaderOp {
+        op: HeaderOpType::Insert,
+        key: key.to_string(),
+        value: Some(value.to_string()),
+        parsed_name: Some(HeaderName::from_bytes(key.as_bytes()).unwrap()),
+        parsed_value: None,
+    };
+    upstreams[0].headers = vec![
+        header_op("x-client-ip", "${client_ip}"),
+        header_op("x-correlation-id", "llmproxy-${request_id}"),
+        header_op("x-forward", "${forward_name}"),
+        // 未设置的环境变量展开为空字符串
+        header_op(
+            "x-tenant",
+            "${env:LLMPROXY_TEST_TENANT}/${env:LLMPROXY_TEST_UNSET}",
+        ),
+    ];
+    let upstream_manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+
+    let context = RequestContext {
+        client_ip: Some("203.0.113.7".parse().unwrap()),
+        request_id: "req-42".to_string(),
+        forward_name: "forward_a".to_string(),
+        client_id: None,
+        token_charge: None,
+    };
+    let response = upstream_manager
+        .forward_request(
+            "retry_group",
+            "/",
+            &context,
+            &Method::GET,
+            reqwest::header::HeaderMap::new(),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn test_upstream_manager_agent_and_default_headers() {
+    let mock_server = MockServer::start().await;
+
+    // 组的 agent 替换客户端的 User-Agent，默认请求头不覆盖客户端的请求头
+    Mock::given(method("GET"))
+        .and(header("user-agent", "group-agent/1.0"))
+        .and(header("x-api-version", "2024-06-01"))
+        .and(header("x-tenant", "client"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    // 上游的 agent 优先于组的配置
+    Mock::given(method("GET"))
+        .and(header("user-agent", "upstream-agent/2.0"))
+        .and(header("x-tenant", "default"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    // 未配置 agent 时保留客户端的 User-Agent，客户端没有发送时使用默认值
+    Mock::given(method("GET"))
+        .and(header("user-agent", "curl/8.0"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(header("user-agent", "LLMProxy/1.0"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let retry = RetryConfig {
+        attempts: 1,
+        initial: 100,
+        max_elapsed_ms: None,
+        retry_on_status: vec![429, 500, 502, 503],
+        retry_non_idempotent: false,
+    };
+    async fn send(manager: UpstreamManager, headers: &[(&str, &str)]) -> u16 {
+        let mut header_map = reqwest::header::HeaderMap::new();
+        for (name, value) in headers {
+            header_map.insert(
+                HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                value.parse().unwrap(),
+            );
+        }
+        manager
+            .forward_request(
+                "retry_group",
+                "/",
+                &RequestContext::default(),
+                &Method::GET,
+                header_map,
+                None,
+            )
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    }
+
+    let (mut upstreams, mut groups) = create_retry_configs(&mock_server.uri(), retry.clone());
+    groups[0].http_client.agent = Some("group-agent/1.0".to_string());
+    upstreams[0].default_headers = [
+        ("x-api-version".to_string(), "2024-06-01".to_string()),
+        ("x-tenant".to_string(), "default".to_string()),
+    ]
+    .into_iter()
+    .collect();
+    let manager = UpstreamManager::new(upstreams.clone(), groups.clone())
+        .await
+        .unwrap();
+    let client_headers = [("user-agent", "curl/8.0"), ("x-tenant", "client")];
+    assert_eq!(send(manager, &client_headers).await, 200);
+
+    upstreams[0].agent = Some("upstream-agent/2.0".to_string());
+    let manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+    assert_eq!(send(manager, &[("user-agent", "curl/8.0")]).await, 200);
+
+    let (upstreams, groups) = create_retry_configs(&mock_server.uri(), retry);
+    let manager = UpstreamManager::new(upstreams.clone(), groups.clone())
+        .await
+        .unwrap();
+    assert_eq!(send(manager, &[("user-agent", "curl/8.0")]).await, 200);
+    let manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+    assert_eq!(send(manager, &[]).await, 200);
+}
+
+// 创建配置了预热请求的上游组，使用独立的名称，避免其他测试的熔断器事件触发预热
+fn create_warmup_configs(
+    mock_url: &str,
+    warmup: WarmupConfig,
+) -> (Vec<UpstreamConfig>, Vec<UpstreamGroupConfig>) {
+    let (mut upstreams, mut groups) = create_retry_configs(
+        mock_url,
+        RetryConfig {
+            attempts: 1,
+            initial: 100,
+            max_elapsed_ms: None,
+            retry_on_status: vec![429, 500, 502, 503],
+            retry_non_idempotent: false,
+        },
+    );
+    upstreams[0].name = "warmup_upstream".to_string();
+    upstreams[0].warmup = Some(warmup);
+    groups[0].name = "warmup_group".to_string();
+    groups[0].upstreams[0].name = "warmup_upstream".to_string();
+    (upstreams, groups)
+}
+
+#[tokio::test]
+async fn test_upstream_manager_warm_up() {
+    let mock_server = MockServer::start().await;
+
+    // 预热请求替换上游 URL 的路径，按上游配置添加请求头
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(header("x-warmup", "true"))
+        .and(header("content-type", "application/json"))
+        .and(body_json(
+            serde_json::json!({"model": "gpt-4o", "max_tokens": 1}),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_string("{}"))
+        .expect(3)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let (mut upstreams, groups) = create_warmup_configs(
+        &format!("{}/v1/unused", mock_server.uri()),
+        WarmupConfig {
+            method: "POST".to_string(),
+            path: Some("/v1/chat/completions".to_string()),
+            body: Some(serde_json::json!({"model": "gpt-4o", "max_tokens": 1})),
+            requests: 3,
+            timeout: 5,
+        },
+    );
+    upstreams[0].headers = vec![HeaderOp {
+        op: HeaderOpType::Insert,
+        key: "x-warmup".to_string(),
+        value: Some("true".to_string()),
+        parsed_name: Some(HeaderName::from_static("x-warmup")),
+        parsed_value: Some(reqwest::header::HeaderValue::from_static("true")),
+    }];
+    let upstream_manager = UpstreamManager::new(upstreams.clone(), groups.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        upstream_manager.warmup_targets(),
+        vec![("warmup_group".to_string(), "warmup_upstream".to_string())]
+    );
+
+    let success = METRICS
+        .upstream_warmup_requests_total()
+        .with_label_values(&["warmup_group", "warmup_upstream", "success"]);
+    let before = success.get();
+    assert_eq!(
+        upstream_manager
+            .warm_up("warmup_group", "warmup_upstream")
+            .await
+            .unwrap(),
+        3
+    );
+    assert_eq!(success.get() - before, 3);
+
+    // 预热失败不影响上游，只计入失败的预热请求
+    upstreams[0].warmup = Some(WarmupConfig {
+        method: "GET".to_string(),
+        path: Some("/v1/models".to_string()),
+        body: None,
+        requests: 1,
+        timeout: 5,
+    });
+    let upstream_manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+    let failure = METRICS
+        .upstream_warmup_requests_total()
+        .with_label_values(&["warmup_group", "warmup_upstream", "failure"]);
+    let before = failure.get();
+    assert_eq!(
+        upstream_manager
+            .warm_up("warmup_group", "warmup_upstream")
+            .await
+            .unwrap(),
+        0
+    );
+    assert_eq!(failure.get() - before, 1);
+    assert!(upstream_manager.has_available_upstream("warmup_group"));
+
+    assert!(matches!(
+        upstream_manager
+            .warm_up("missing_group", "warmup_upstream")
+            .await,
+        Err(AppError::UpstreamGroupNotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_upstream_warmup_after_breaker_recovery() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    let (mut upstreams, mut groups) = create_warmup_configs(
+        &mock_server.uri(),
+        WarmupConfig {
+            method: "GET".to_string(),
+            path: Some("/v1/models".to_string()),
+            body: None,
+            requests: 2,
+            timeout: 5,
+        },
+    );
+    upstreams[0].name = "recovery_upstream".to_string();
+    groups[0].name = "recovery_group".to_string();
+    groups[0].upstreams[0].name = "recovery_upstream".to_string();
+    let upstream_manager = Arc::new(UpstreamManager::new(upstreams, groups).await.unwrap());
+
+    // 开始服务前预热一次
+    let warmup = UpstreamWarmup::new(upstream_manager.clone()).unwrap();
+    assert_eq!(warmup.warm_up_all().await, 2);
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 2);
+
+    tokio::spawn(
+        Toplevel::new(move |s| async move {
+            s.start(SubsystemBuilder::new("warmup", move |s| async move {
+                warmup.run(s).await
+            }));
+        })
+        .handle_shutdown_requests(Duration::from_secs(1)),
+    );
+    sleep(Duration::from_millis(100)).await;
+
+    // 熔断器打开不触发预热，恢复（关闭）时重新预热
+    for to in ["open", "closed"] {
+        EVENTS.publish_system(SystemEvent::BreakerStateChanged {
+            timestamp: 0,
+            group: "recovery_group".to_string(),
+            upstream: "recovery_upstream".to_string(),
+            from: String::new(),
+            to: to.to_string(),
+        });
+    }
+    for _ in 0..50 {
+        if mock_server.received_requests().await.unwrap().len() >= 4 {
+            break;
+        }
+        sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 4);
+
+    // 没有上游配置预热请求时不创建
+    let (upstreams, groups) = create_retry_configs(
+        &mock_server.uri(),
+        RetryConfig {
+            attempts: 1,
+            initial: 100,
+            max_elapsed_ms: None,
+            retry_on_status: vec![],
+            retry_non_idempotent: false,
+        },
+    );
+    let upstream_manager = Arc::new(UpstreamManager::new(upstreams, groups).await.unwrap());
+    assert!(UpstreamWarmup::new(upstream_manager).is_none());
+}
+
+#[tokio::test]
+async fn test_upstream_manager_body_transform() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(body_json(serde_json::json!({
+            "model": "azure-gpt4o-deployment",
+            "messages": [],
+            "temperature": 0.2,
+            "max_tokens": 1024,
+        })))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_string("not json"))
+        .respond_with(ResponseTemplate::new(202))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let (mut upstreams, groups) = create_retry_configs(
+        &mock_server.uri(),
+        RetryConfig {
+            attempts: 1,
+            initial: 100,
+            max_elapsed_ms: None,
+            retry_on_status: vec![429, 500, 502, 503],
+            retry_non_idempotent: false,
+        },
+    );
+    let mut transform = BodyTransformConfig::default();
+    transform
+        .model_map
+        .insert("gpt-4o".to_string(), "azure-gpt4o-deployment".to_string());
+    transform
+        .defaults
+        .insert("temperature".to_string(), serde_json::json!(0.7));
+    transform
+        .defaults
+        .insert("max_tokens".to_string(), serde_json::json!(1024));
+    upstreams[0].body_transform = Some(transform);
+    let upstream_manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+
+    // 客户端已设置的参数不会被默认值覆盖，原 Content-Length 不再适用于转换后的请求体
+    let body = r#"{"model":"gpt-4o","messages":[],"temperature":0.2}"#;
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert(
+        reqwest::header::CONTENT_LENGTH,
+        body.len().to_string().parse().unwrap(),
+    );
+    let response = upstream_manager
+        .forward_request(
+            "retry_group",
+            "/",
+            &RequestContext::default(),
+            &Method::POST,
+            headers,
+            Some(body.into()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    // 非 JSON 请求体原样转发
+    let response = upstream_manager
+        .forward_request(
+            "retry_group",
+            "/",
+            &RequestContext::default(),
+            &Method::POST,
+            reqwest::header::HeaderMap::new(),
+            Some("not json".into()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 202);
+}
+
+#[tokio::test]
+async fn test_upstream_manager_streaming_body() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(body_json(serde_json::json!({
+            "model": "gpt-4o",
+            "messages": [],
+        })))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let (mut upstreams, mut groups) = create_retry_configs(
+        &mock_server.uri(),
+        RetryConfig {
+            attempts: 1,
+            initial: 100,
+            max_elapsed_ms: None,
+            retry_on_status: vec![429, 500, 502, 503],
+            retry_non_idempotent: false,
+        },
+    );
+    // 未开启重试的上游组
+    let mut plain = groups[0].clone();
+    plain.name = "plain_group".to_string();
+    plain.http_client.retry = None;
+    // 上游配置了请求体转换的上游组
+    let mut transform_upstream = upstreams[0].clone();
+    transform_upstream.name = "transform_upstream".to_string();
+    transform_upstream.body_transform = Some(BodyTransformConfig::default());
+    let mut transform = plain.clone();
+    transform.name = "transform_group".to_string();
+    transform.upstreams[0].name = "transform_upstream".to_string();
+    upstreams.push(transform_upstream);
+    groups.push(plain);
+    groups.push(transform);
+    let upstream_manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+
+    // 重试需要重放请求体，请求体转换需要读取完整请求体
+    assert!(upstream_manager.accepts_streaming_body("plain_group"));
+    assert!(!upstream_manager.accepts_streaming_body("retry_group"));
+    assert!(!upstream_manager.accepts_streaming_body("transform_group"));
+    assert!(!upstream_manager.accepts_streaming_body("unknown_group"));
+
+    // 分块的请求体逐块转发给上游
+    let chunks = vec![
+        Ok::<_, std::io::Error>(bytes::Bytes::from_static(b"{\"model\":")),
+        Ok(bytes::Bytes::from_static(b"\"gpt-4o\",")),
+        Ok(bytes::Bytes::from_static(b"\"messages\":[]}")),
+    ];
+    let response = upstream_manager
+        .forward_request_stream(
+            "plain_group",
+            "/",
+            &RequestContext::default(),
+            &Method::POST,
+            reqwest::header::HeaderMap::new(),
+            reqwest::Body::wrap_stream(futures_util::stream::iter(chunks)),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn test_upstream_manager_stream_idle_timeout() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // 上游发送响应头和第一个事件后不再发送数据
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = [0u8; 4096];
+        let _ = socket.read(&mut buf).await.unwrap();
+        socket
+            .write_all(
+                b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ntransfer-encoding: chunked\r\n\r\nf\r\ndata: {\"a\":1}\n\n\r\n",
+            )
+            .await
+            .unwrap();
+        sleep(Duration::from_secs(30)).await;
+        drop(socket);
+    });
+
+    let (upstreams, mut groups) = create_retry_configs(
+        &url,
+        RetryConfig {
+            attempts: 1,
+            initial: 100,
+            max_elapsed_ms: None,
+            retry_on_status: vec![429, 500, 502, 503],
+            retry_non_idempotent: false,
+        },
+    );
+    groups[0].http_client.retry = None;
+    groups[0].http_client.stream_mode = true;
+    groups[0].http_client.timeout.stream_idle = Some(1);
+    let upstream_manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+
+    let response = upstream_manager
+        .forward_request(
+            "retry_group",
+            "/",
+            &RequestContext::default(),
+            &Method::GET,
+            reqwest::header::HeaderMap::new(),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    // 收到第一个事件后空闲超过 1 秒，响应体以错误结束
+    let start = Instant::now();
+    let mut stream = response.bytes_stream();
+    let first = futures_util::StreamExt::next(&mut stream).await.unwrap();
+    assert_eq!(first.unwrap().as_ref(), b"data: {\"a\":1}\n\n");
+    let next = futures_util::StreamExt::next(&mut stream).await.unwrap();
+    assert!(next.is_err());
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    let errors = METRICS
+        .upstream_errors_total()
+        .with_label_values(&["stream_idle_timeout", "retry_group", "retry_upstream"])
+        .get();
+    assert_eq!(errors, 1);
+}
+
+#[tokio::test]
+async fn test_upstream_manager_stream_timing_metrics() {
+    let mock_server = MockServer::start().await;
+
+    let events = "data: {\"choices\":[{\"delta\":{\"content\":\"a\"}}]}\n\n\
+                  data: {\"choices\":[{\"delta\":{\"content\":\"b\"}}]}\n\n\
+                  data: {\"choices\":[{\"delta\":{\"content\":\"c\"}}]}\n\n\
+                  data: [DONE]\n\n";
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(events, "text/event-stream"))
+        .mount(&mock_server)
+        .await;
+
+    let (mut upstreams, mut groups) = create_retry_configs(
+        &mock_server.uri(),
+        RetryConfig {
+            attempts: 1,
+            initial: 100,
+            max_elapsed_ms: None,
+            retry_on_status: vec![429, 500, 502, 503],
+            retry_non_idempotent: false,
+        },
+    );
+    upstreams[0].name = "timing_upstream".to_string();
+    groups[0].name = "timing_group".to_string();
+    groups[0].upstreams[0].name = "timing_upstream".to_string();
+    let upstream_manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+
+    let response = upstream_manager
+        .forward_request(
+            "timing_group",
+            "/",
+            &RequestContext::default(),
+            &Method::POST,
+            reqwest::header::HeaderMap::new(),
+            Some("{}".into()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.text().await.unwrap(), events);
+
+    // 每个响应记录一次首字节耗时，结束标记不计入事件数
+    let ttfb = METRICS
+        .upstream_ttfb_seconds()
+        .with_label_values(&["timing_group", "timing_upstream"])
+        .get_sample_count();
+    assert_eq!(ttfb, 1);
+    let chunks = METRICS
+        .stream_chunks_total()
+        .with_label_values(&["timing_group", "timing_upstream"])
+        .get();
+    assert_eq!(chunks, 3);
+}
+
+#[tokio::test]
+async fn test_upstream_manager_connection_metrics() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .mount(&mock_server)
+        .await;
+
+    // 使用主机名访问，使连接建立时经过 DNS 解析
+    let url = mock_server.uri().replace("127.0.0.1", "localhost");
+    let (mut upstreams, mut groups) = create_retry_configs(
+        &url,
+        RetryConfig {
+            attempts: 1,
+            initial: 100,
+            max_elapsed_ms: None,
+            retry_on_status: vec![429, 500, 502, 503],
+            retry_non_idempotent: false,
+        },
+    );
+    upstreams[0].name = "pool_upstream".to_string();
+    groups[0].name = "pool_group".to_string();
+    groups[0].upstreams[0].name = "pool_upstream".to_string();
+    let upstream_manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+
+    // 第一个请求建立新连接，第二个请求复用连接池中的连接
+    for _ in 0..2 {
+        let response = upstream_manager
+            .forward_request(
+                "pool_group",
+                "/",
+                &RequestContext::default(),
+                &Method::POST,
+                reqwest::header::HeaderMap::new(),
+                Some("{}".into()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.text().await.unwrap(), "ok");
+    }
+
+    let count = |labels: &[&str]| {
+        METRICS
+            .upstream_connection_requests_total()
+            .with_label_values(labels)
+            .get()
+    };
+    assert_eq!(count(&["pool_group", "new"]), 1);
+    assert_eq!(count(&["pool_group", "reused"]), 1);
+    let opened = METRICS
+        .upstream_connections_total()
+        .with_label_values(&["pool_group", "opened"])
+        .get();
+    assert_eq!(opened, 1);
+
+    let connect = |phase: &str| {
+        METRICS
+            .upstream_connect_seconds()
+            .with_label_values(&["pool_group", phase])
+            .get_sample_count()
+    };
+    assert_eq!(connect("dns"), 1);
+    assert_eq!(connect("handshake"), 1);
+}
+
+#[tokio::test]
+async fn test_upstream_manager_system_prompt() {
+    let guard = serde_json::json!({"role": "system", "content": "Follow the policy."});
+    let client = serde_json::json!({"role": "system", "content": "Be brief."});
+    let user = serde_json::json!({"role": "user", "content": "hi"});
+
+    for (mode, expected) in [
+        (
+            SystemPromptMode::Prepend,
+            vec![guard.clone(), client.clone(), user.clone()],
+        ),
+        (
+            SystemPromptMode::Append,
+            vec![client.clone(), guard.clone(), user.clone()],
+        ),
+        (
+            SystemPromptMode::Override,
+            vec![guard.clone(), user.clone()],
+        ),
+    ] {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_json(
+                serde_json::json!({"model": "gpt-4o", "messages": expected}),
+            ))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        // 不是聊天请求时不注入
+        Mock::given(method("POST"))
+            .and(body_json(serde_json::json!({"input": "embed me"})))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let (mut upstreams, groups) = create_retry_configs(
+            &mock_server.uri(),
+            RetryConfig {
+                attempts: 1,
+                initial: 100,
+                max_elapsed_ms: None,
+                retry_on_status: vec![429, 500, 502, 503],
+                retry_non_idempotent: false,
+            },
+        );
+        upstreams[0].body_transform = Some(BodyTransformConfig {
+            system_prompt: Some(SystemPromptConfig {
+                content: "Follow the policy.".to_string(),
+                mode,
+            }),
+            ..Default::default()
+        });
+        let upstream_manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+
+        let body =
+            serde_json::json!({"model": "gpt-4o", "messages": [client.clone(), user.clone()]});
+        let response = upstream_manager
+            .forward_request(
+                "retry_group",
+                "/",
+                &RequestContext::default(),
+                &Method::POST,
+                reqwest::header::HeaderMap::new(),
+                Some(body.to_string().into()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 200, "mode {:?}", mode);
+
+        let response = upstream_manager
+            .forward_request(
+                "retry_group",
+                "/",
+                &RequestContext::default(),
+                &Method::POST,
+                reqwest::header::HeaderMap::new(),
+                Some(r#"{"input":"embed me"}"#.into()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 202, "mode {:?}", mode);
+    }
+}
+
+#[tokio::test]
+async fn test_upstream_manager_group_status() {
+    // 服务器1返回错误，服务器2返回成功
+    let mock_server1 = MockServer::start().await;
+    let mock_server2 = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&mock_server1)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server2)
+        .await;
+
+    let (upstreams, groups) = create_test_configs(&mock_server1.uri(), &mock_server2.uri(), true);
+    let upstream_manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+
+    // 尚未处理请求时没有统计数据
+    let status = upstream_manager.group_status();
+    assert_eq!(status.len(), 1);
+    assert_eq!(status[0].name, "test_group");
+    assert_eq!(status[0].strategy, "roundrobin");
+    assert_eq!(status[0].upstreams.len(), 2);
+    for upstream in &status[0].upstreams {
+        assert!(upstream.healthy);
+        assert_eq!(upstream.breaker.as_deref(), Some("closed"));
+        assert_eq!(upstream.total_requests, 0);
+        assert!(upstream.last_selected.is_none());
+    }
+
+    // 轮询发送请求，两个上游各处理两次
+    for _ in 0..4 {
+        let _ = upstream_manager
+            .forward_request(
+                "test_group",
+                "/",
+                &RequestContext::default(),
+                &Method::GET,
+                reqwest::header::HeaderMap::new(),
+                None,
+            )
+            .await;
+    }
+
+    let status = upstream_manager.group_status();
+    let failing = status[0]
+        .upstreams
+        .iter()
+        .find(|u| u.name == "test_upstream1")
+        .unwrap();
+    let healthy = status[0]
+        .upstreams
+        .iter()
+        .find(|u| u.name == "test_upstream2")
+        .unwrap();
+
+    assert_eq!(failing.url, mock_server1.uri());
+    assert_eq!(failing.pending_requests, 0);
+    assert_eq!(failing.total_requests, 2);
+    assert_eq!(failing.total_errors, 2);
+    assert!(failing.error_rate > 0.0);
+    assert!(failing.last_selected.is_some());
+
+    assert_eq!(healthy.pending_requests, 0);
+    assert_eq!(healthy.total_requests, 2);
+    assert_eq!(healthy.total_errors, 0);
+    assert_eq!(healthy.error_rate, 0.0);
+    assert!(healthy.last_selected.is_some());
+}
+
+#[tokio::test]
+async fn test_upstream_manager_drain_upstream() {
+    let mock_server1 = MockServer::start().await;
+    let mock_server2 = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&mock_server1)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server2)
+        .await;
+
+    let (upstreams, groups) = create_test_configs(&mock_server1.uri(), &mock_server2.uri(), false);
+    let upstream_manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+
+    // 排空上游1后，所有请求都发往上游2
+    assert!(upstream_manager.set_upstream_enabled("test_upstream1", false));
+    assert!(!upstream_manager.set_upstream_enabled("unknown_upstream", false));
+
+    for _ in 0..4 {
+        let response = upstream_manager
+            .forward_request(
+                "test_group",
+                "/",
+                &RequestContext::default(),
+                &Method::GET,
+                reqwest::header::HeaderMap::new(),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+    }
+
+    let status = upstream_manager.group_status();
+    let drained = status[0]
+        .upstreams
+        .iter()
+        .find(|u| u.name == "test_upstream1")
+        .unwrap();
+    assert!(!drained.enabled);
+    assert!(!drained.healthy);
+    assert_eq!(drained.total_requests, 0);
+
+    // 重新启用后恢复接收请求
+    assert!(upstream_manager.set_upstream_enabled("test_upstream1", true));
+    let status = upstream_manager.group_status();
+    assert!(status[0].upstreams.iter().all(|u| u.enabled && u.healthy));
+}
+
+#[tokio::test]
+async fn test_upstream_manager_disabled_in_config() {
+    let (mut upstreams, groups) =
+        create_test_configs("http://127.0.0.1:1", "http://127.0.0.1:2", false);
+    upstreams[0].enabled = false;
+    upstreams[1].enabled = false;
+    let upstream_manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+
+    // 所有上游都被禁用时没有可用的上游
+    let result = upstream_manager
+        .forward_request(
+            "test_group",
+            "/",
+            &RequestContext::default(),
+            &Method::GET,
+            reqwest::header::HeaderMap::new(),
+            None,
+        )
+        .await;
+    assert!(matches!(result, Err(AppError::NoHealthyUpstreamAvailable)));
+}
+
+#[tokio::test]
+async fn test_upstream_manager_usage_cost() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "object": "chat.completion",
+            "model": "priced-model-2024",
+            "choices": [],
+            "usage": {"prompt_tokens": 1000, "completion_tokens": 500, "total_tokens": 1500},
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let (mut upstreams, groups) = create_retry_configs(
+        &mock_server.uri(),
+        RetryConfig {
+            attempts: 1,
+            initial: 100,
+            max_elapsed_ms: None,
+            retry_on_status: vec![429, 500, 502, 503],
+            retry_non_idempotent: false,
+        },
+    );
+    upstreams[0].pricing = vec![ModelPriceConfig {
+        model: "priced-model*".to_string(),
+        prompt: 2.0,
+        completion: 8.0,
+    }];
+    let upstream_manager = UpstreamManager::new(upstreams, groups).await.unwrap();
+
+    for _ in 0..2 {
+        let response = upstream_manager
+            .forward_request(
+                "retry_group",
+                "/",
+                &RequestContext::default(),
+                &Method::POST,
+                reqwest::header::HeaderMap::new(),
+                Some("{}".into()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    // (1000 * 2.0 + 500 * 8.0) / 1_000_000 = 0.006 美元 / 次
+    let cost = METRICS
+        .cost_usd_total()
+        .with_label_values(&["retry_group", "retry_upstream", "priced-model-2024"])
+        .get();
+    assert!((cost - 0.012).abs() < 1e-9);
+
+    let today = USAGE.daily(1);
+    let entry = today[0]
+        .entries
+        .iter()
+        .find(|entry| entry.model == "priced-model-2024")
+        .unwrap();
+    assert_eq!(entry.group, "retry_group");
+    assert_eq!(entry.upstream, "retry_upstream");
+    assert_eq!(entry.requests, 2);
+    assert_eq!(entry.prompt_tokens, 2000);
+    assert_eq!(entry.completion_tokens, 1000);
+    assert!((entry.cost_usd - 0.012).abs() < 1e-9);
+}
@@ -0,0 +1,340 @@
+use llmproxy::{
+    config::{
+        AuthConfig, AuthType, BalanceConfig, BalanceStrategy, ForwardConfig, HeaderOp,
+        HeaderOpType, HttpClientConfig, TimeoutConfig, UpstreamConfig, UpstreamGroupConfig,
+        UpstreamRef, WebSocketConfig,
+    },
+    metrics::METRICS,
+    r#const::response_buffer,
+    server::{is_upgrade_request, ForwardServer},
+    upstream::UpstreamManager,
+};
+use reqwest::{
+    header::{HeaderMap, HeaderName},
+    Method,
+};
+use std::sync::Arc;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+    time::Duration,
+};
+use tokio_graceful_shutdown::{IntoSubsystem, SubsystemBuilder, Toplevel};
+
+// 读取 HTTP 请求头或响应头，返回小写的头部文本
+async fn read_head<R: AsyncRead + Unpin>(reader: &mut R) -> String {
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        let mut byte = [0u8; 1];
+        if reader.read(&mut byte).await.unwrap() == 0 {
+            break;
+        }
+        head.push(byte[0]);
+    }
+    String::from_utf8(head).unwrap().to_lowercase()
+}
+
+// 启动模拟的 WebSocket 上游，接受升级后原样返回收到的数据，
+// 请求携带 x-reject 头时拒绝升级。返回上游地址和收到的升级请求头
+async fn start_echo_upstream() -> (String, mpsc::UnboundedReceiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (sender, receiver) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let sender = sender.clone();
+            tokio::spawn(async move {
+                let head = read_head(&mut stream).await;
+                let reject = head.contains("x-reject:");
+                sender.send(head).unwrap();
+                if reject {
+                    let _ = stream
+                        .write_all(
+                            b"HTTP/1.1 403 Forbidden\r\ncontent-length: 6\r\nconnection: close\r\n\r\ndenied",
+                        )
+                        .await;
+                    return;
+                }
+                stream
+                    .write_all(
+                        b"HTTP/1.1 101 Switching Protocols\r\nupgrade: websocket\r\nconnection: Upgrade\r\nsec-websocket-accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\r\n",
+                    )
+                    .await
+                    .unwrap();
+                let (mut reader, mut writer) = stream.split();
+                let _ = tokio::io::copy(&mut reader, &mut writer).await;
+            });
+        }
+    });
+    (format!("http://{}/v1/realtime", addr), receiver)
+}
+
+// 在后台运行开启 WebSocket 代理的转发服务，返回转发服务端口
+async fn run_websocket_server(name: &str, upstream_url: String, idle_timeout: u64) -> u16 {
+    let upstream = UpstreamConfig {
+        name: format!("{}_upstream", name),
+        url: upstream_url.into(),
+        weight: 1,
+        http_client: HttpClientConfig::default(),
+        auth: Some(AuthConfig {
+            r#type: AuthType::Bearer,
+            token: Some("upstream-secret".to_string()),
+            token_file: None,
+            username: None,
+            password: None,
+            password_file: None,
+            oauth2: None,
+            external: None,
+        }),
+        headers: vec![HeaderOp {
+            op: HeaderOpType::Insert,
+            key: "openai-beta".to_string(),
+            value: Some("realtime=v1".to_string()),
+            parsed_name: Some(HeaderName::from_static("openai-beta")),
+            parsed_value: Some("realtime=v1".parse().unwrap()),
+        }],
+        breaker: None,
+        adaptive: None,
+        hint: None,
+        enabled: true,
+        proxy: true,
+        path: None,
+        query_params: vec![],
+        body_transform: None,
+        dialect: None,
+        stream_normalize: None,
+        agent: None,
+        default_headers: Default::default(),
+        pricing: vec![],
+        dns: None,
+        warmup: None,
+    };
+    let group = UpstreamGroupConfig {
+        name: format!("{}_group", name),
+        upstreams: vec![UpstreamRef {
+            name: format!("{}_upstream", name),
+            weight: 1,
+        }],
+        balance: BalanceConfig {
+            strategy: BalanceStrategy::RoundRobin,
+        },
+        http_client: Default::default(),
+        sticky: None,
+        discovery: None,
+    };
+    let upstream_manager = Arc::new(
+        UpstreamManager::new(vec![upstream], vec![group])
+            .await
+            .unwrap(),
+    );
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let config = ForwardConfig {
+        name: format!("{}_forward", name),
+        port,
+        address: "127.0.0.1".to_string(),
+        default_group: format!("{}_group", name),
+        ratelimit: None,
+        timeout: Some(TimeoutConfig::default()),
+        routing: None,
+        limits: None,
+        count_tokens: false,
+        budget: None,
+        token_limit: None,
+        max_concurrent: None,
+        queue: None,
+        sse_heartbeat: None,
+        coalesce: false,
+        cache: None,
+        audit_sink: None,
+        pii_redaction: None,
+        policy: None,
+        plugins: vec![],
+        middlewares: None,
+        listener: None,
+        websocket: Some(WebSocketConfig { idle_timeout }),
+        compression: None,
+        access_log: None,
+        error_response: None,
+        response_buffer_limit: response_buffer::DEFAULT_LIMIT,
+        allowed_methods: vec![],
+        allowed_paths: vec![],
+        request_headers: None,
+        breaker: None,
+    };
+    let server = ForwardServer::new(config, upstream_manager, &[]).unwrap();
+    tokio::spawn(
+        Toplevel::new(move |s| async move {
+            s.start(SubsystemBuilder::new("forward", move |s| async move {
+                server.run(s).await
+            }));
+        })
+        .handle_shutdown_requests(Duration::from_secs(1)),
+    );
+
+    // 等待转发服务开始监听
+    for _ in 0..100 {
+        if TcpStream::connect(("127.0.0.1", port)).await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    port
+}
+
+// 向转发服务发送 WebSocket 升级请求，返回连接和响应头
+async fn upgrade(port: u16, extra_headers: &str) -> (TcpStream, String) {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let request = format!(
+        "GET /v1/realtime?model=gpt-4o-realtime HTTP/1.1\r\nhost: 127.0.0.1\r\nconnection: Upgrade\r\nupgrade: websocket\r\nsec-websocket-key: dGhlIHNhbXBsZSBub25jZQ==\r\nsec-websocket-version: 13\r\n{}\r\n",
+        extra_headers
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let head = read_head(&mut stream).await;
+    (stream, head)
+}
+
+// 等待指标达到期望值
+async fn wait_for(expected: i64, value: impl Fn() -> i64) {
+    for _ in 0..100 {
+        if value() == expected {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(value(), expected);
+}
+
+#[test]
+fn test_is_upgrade_request() {
+    let headers = |pairs: &[(&str, &str)]| {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(
+                HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                value.parse().unwrap(),
+            );
+        }
+        headers
+    };
+
+    assert!(is_upgrade_request(
+        &Method::GET,
+        &headers(&[("connection", "Upgrade"), ("upgrade", "websocket")])
+    ));
+    // Connection 可以包含多个选项，大小写不敏感
+    assert!(is_upgrade_request(
+        &Method::GET,
+        &headers(&[
+            ("connection", "keep-alive, upgrade"),
+            ("upgrade", "WebSocket")
+        ])
+    ));
+    assert!(!is_upgrade_request(
+        &Method::POST,
+        &headers(&[("connection", "upgrade"), ("upgrade", "websocket")])
+    ));
+    assert!(!is_upgrade_request(
+        &Method::GET,
+        &headers(&[("upgrade", "websocket")])
+    ));
+    assert!(!is_upgrade_request(
+        &Method::GET,
+        &headers(&[("connection", "upgrade"), ("upgrade", "h2c")])
+    ));
+}
+
+#[tokio::test]
+async fn test_websocket_proxy() {
+    let (upstream_url, mut upgrade_requests) = start_echo_upstream().await;
+    let port = run_websocket_server("websocket_proxy", upstream_url, 60).await;
+    let labels = ["websocket_proxy_forward", "websocket_proxy_group"];
+    let active = METRICS
+        .websocket_active_connections()
+        .with_label_values(&labels);
+    let upgraded = METRICS
+        .websocket_connections_total()
+        .with_label_values(&[labels[0], labels[1], "upgraded"]);
+
+    // 上游接受升级后返回 101，之后两个方向的数据原样转发
+    let (mut stream, head) = upgrade(port, "").await;
+    assert!(head.starts_with("http/1.1 101"), "{}", head);
+    assert!(head.contains("sec-websocket-accept: s3pplmbitxaq9kygzzhzrbk+xoo="));
+    stream.write_all(b"hello realtime").await.unwrap();
+    let mut echo = [0u8; 14];
+    stream.read_exact(&mut echo).await.unwrap();
+    assert_eq!(&echo, b"hello realtime");
+
+    // 升级请求按上游配置添加认证信息和请求头，保留升级相关的请求头
+    let request = upgrade_requests.recv().await.unwrap();
+    assert!(
+        request.starts_with("get /v1/realtime http/1.1"),
+        "{}",
+        request
+    );
+    assert!(request.contains("authorization: bearer upstream-secret"));
+    assert!(request.contains("openai-beta: realtime=v1"));
+    assert!(request.contains("upgrade: websocket"));
+    assert!(request.contains("sec-websocket-key: dghlihnhbxbszsbub25jzq=="));
+
+    wait_for(1, || active.get()).await;
+    assert_eq!(upgraded.get(), 1);
+    let sent = METRICS.websocket_bytes_total().with_label_values(&[
+        labels[0],
+        labels[1],
+        "client_to_upstream",
+    ]);
+    let received = METRICS.websocket_bytes_total().with_label_values(&[
+        labels[0],
+        labels[1],
+        "upstream_to_client",
+    ]);
+    assert_eq!(sent.get(), 14);
+    assert_eq!(received.get(), 14);
+
+    // 客户端关闭连接后关闭上游连接
+    drop(stream);
+    wait_for(0, || active.get()).await;
+
+    // 上游拒绝升级时原样返回上游的响应
+    let (mut stream, head) = upgrade(port, "x-reject: 1\r\n").await;
+    assert!(head.starts_with("http/1.1 403"), "{}", head);
+    let mut body = [0u8; 6];
+    stream.read_exact(&mut body).await.unwrap();
+    assert_eq!(&body, b"denied");
+    assert_eq!(
+        METRICS
+            .websocket_connections_total()
+            .with_label_values(&[labels[0], labels[1], "rejected"])
+            .get(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_websocket_idle_timeout() {
+    let (upstream_url, _upgrade_requests) = start_echo_upstream().await;
+    let port = run_websocket_server("websocket_idle", upstream_url, 1).await;
+
+    let (mut stream, head) = upgrade(port, "").await;
+    assert!(head.starts_with("http/1.1 101"), "{}", head);
+
+    // 两个方向都没有数据时，空闲超时后关闭连接
+    let mut buf = [0u8; 16];
+    let read = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut buf))
+        .await
+        .unwrap();
+    assert!(matches!(read, Ok(0) | Err(_)));
+    wait_for(0, || {
+        METRICS
+            .websocket_active_connections()
+            .with_label_values(&["websocket_idle_forward", "websocket_idle_group"])
+            .get()
+    })
+    .await;
+}